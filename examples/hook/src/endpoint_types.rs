@@ -20,6 +20,9 @@ use crate::RequestError;
 #[serde(rename_all = "camelCase")]
 pub struct ExecuteTxHookRequest {
     pub execute_tx_request: ExecuteTxGasStationRequest,
+    /// Transaction fields already parsed by the gas station.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_summary: Option<TransactionSummary>,
 }
 
 /// Original request data and headers sent to Gas Stations `execute_tx` endpoint.
@@ -47,6 +50,34 @@ pub struct ExecuteTxRequestPayload {
     pub request_type: Option<ExecuteTransactionRequestType>,
 }
 
+/// Transaction fields parsed by the gas station, so that a hook can check them without decoding
+/// `txBytes`.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    /// Transaction digest, base58 encoded.
+    pub digest: String,
+    /// Address of the transaction sender.
+    pub sender: String,
+    /// Gas budget set by the transaction.
+    #[schema(format = "uint64")]
+    pub gas_budget: u64,
+    /// Number of commands of the programmable transaction, if it is one.
+    pub command_count: Option<usize>,
+    /// Move calls of the transaction, in order.
+    pub move_calls: Vec<MoveCallSummary>,
+}
+
+/// Function called by a move call.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MoveCallSummary {
+    /// Package ID, hex encoded.
+    pub package: String,
+    pub module: String,
+    pub function: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum ExecuteTransactionRequestType {
@@ -3,15 +3,18 @@
 
 use std::ops::{BitAnd, BitOr};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::policy::AccessPolicy;
 
-/// The Decision enum represents the decision of the access controller.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// The Decision enum represents the decision of the access controller. Hold keeps the transaction
+/// until it's approved or rejected by an operator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum Decision {
     Allow,
     Deny,
+    Hold,
 }
 
 impl BitAnd for Decision {
@@ -20,7 +23,8 @@ impl BitAnd for Decision {
     fn bitand(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
             (Decision::Allow, Decision::Allow) => Decision::Allow,
-            _ => Decision::Deny,
+            (Decision::Deny, _) | (_, Decision::Deny) => Decision::Deny,
+            _ => Decision::Hold,
         }
     }
 }
@@ -31,7 +35,8 @@ impl BitOr for Decision {
     fn bitor(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
             (Decision::Deny, Decision::Deny) => Decision::Deny,
-            _ => Decision::Allow,
+            (Decision::Allow, _) | (_, Decision::Allow) => Decision::Allow,
+            _ => Decision::Hold,
         }
     }
 }
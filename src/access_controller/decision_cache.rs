@@ -0,0 +1,85 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The decisions of the access controller can be reused for a while, so that a transaction
+//! submitted again, e.g. by a client retrying it, isn't evaluated again: its rules, hooks and
+//! notifications run once.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use iota_types::digests::TransactionDigest;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::predicates::serde_duration;
+use super::AccessDecision;
+
+/// The expired decisions are dropped once the cache holds this many entries.
+const DECISION_CACHE_MAX_ENTRIES: usize = 100_000;
+
+/// How long the decision about a transaction is reused.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct DecisionCacheConfig {
+    #[serde(with = "serde_duration")]
+    pub ttl: Duration,
+}
+
+impl DecisionCacheConfig {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl }
+    }
+}
+
+/// Decisions by transaction digest, kept in memory. Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct DecisionCache {
+    entries: Arc<Mutex<HashMap<TransactionDigest, (Instant, AccessDecision)>>>,
+}
+
+impl std::fmt::Debug for DecisionCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecisionCache")
+            .field("entry_count", &self.entries.lock().len())
+            .finish()
+    }
+}
+
+impl DecisionCache {
+    /// Returns the decision about the transaction if it was made less than `ttl` ago.
+    pub fn get(
+        &self,
+        transaction_digest: &TransactionDigest,
+        ttl: Duration,
+    ) -> Option<AccessDecision> {
+        let entries = self.entries.lock();
+        entries
+            .get(transaction_digest)
+            .filter(|(decided_at, _)| decided_at.elapsed() < ttl)
+            .map(|(_, decision)| decision.clone())
+    }
+
+    /// Forgets the decision about the transaction, so that it is evaluated again.
+    pub fn remove(&self, transaction_digest: &TransactionDigest) {
+        self.entries.lock().remove(transaction_digest);
+    }
+
+    pub fn insert(
+        &self,
+        transaction_digest: TransactionDigest,
+        decision: AccessDecision,
+        ttl: Duration,
+    ) {
+        let mut entries = self.entries.lock();
+        if entries.len() >= DECISION_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (decided_at, _)| decided_at.elapsed() < ttl);
+            // Too many transactions within the TTL, start over rather than growing unbounded
+            if entries.len() >= DECISION_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(transaction_digest, (Instant::now(), decision));
+    }
+}
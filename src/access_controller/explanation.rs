@@ -0,0 +1,115 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::decision::Decision;
+
+/// Result of a predicate of a rule for a transaction, named as in the config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PredicateMatch {
+    pub predicate: String,
+    pub matched: bool,
+}
+
+impl PredicateMatch {
+    pub fn new(predicate: impl Into<String>, matched: bool) -> Self {
+        Self {
+            predicate: predicate.into(),
+            matched,
+        }
+    }
+}
+
+/// What the evaluation of a rule led to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleOutcome {
+    /// A predicate of the rule didn't match the transaction.
+    NotMatched,
+    /// The predicates matched, but not the gas usage limit of the rule.
+    GasLimitNotMatched,
+    /// The rule matched and has the `audit` action.
+    Audited,
+    /// The rule matched and has a `notify` action.
+    Notified,
+    /// The hook of the rule left the decision to the next rules.
+    HookNoDecision,
+    /// The rule matched and jumped to the target of its `goto` action.
+    Jumped,
+    /// The rule made the decision.
+    Decided,
+    /// The rule wasn't evaluated, an earlier rule made the decision or jumped over it.
+    NotEvaluated,
+}
+
+/// Why a rule made the decision about a transaction, or didn't.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RuleExplanation {
+    /// Name of the rule, or its position starting at 1.
+    pub rule: String,
+    /// The predicates set by the rule. Empty if the rule wasn't evaluated.
+    pub predicates: Vec<PredicateMatch>,
+    pub outcome: RuleOutcome,
+}
+
+/// Sets the outcome of the rule being evaluated, i.e. the last one explained. Does nothing when
+/// the decision isn't explained.
+pub(crate) fn set_outcome(explanation: &mut [RuleExplanation], outcome: RuleOutcome) {
+    if let Some(rule) = explanation.last_mut() {
+        rule.outcome = outcome;
+    }
+}
+
+/// Trace of the evaluation of the rules for a transaction, so that tests can assert on the rule
+/// that made the decision and on the outcome of each rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationTrace {
+    pub decision: Decision,
+    /// The rule that made the decision, `None` for the access policy.
+    pub matched_rule: Option<String>,
+    pub user_message: Option<String>,
+    pub deny_message: Option<String>,
+    /// The audit rules that matched, in order.
+    pub audited_rules: Vec<String>,
+    /// Every rule of the access controller, in order.
+    pub rules: Vec<RuleExplanation>,
+}
+
+impl EvaluationTrace {
+    /// Returns the explanation of the first rule named `rule`, or at the position `#n`.
+    pub fn rule(&self, rule: &str) -> Option<&RuleExplanation> {
+        self.rules
+            .iter()
+            .find(|explanation| explanation.rule == rule)
+    }
+
+    /// Returns the outcome of the first rule named `rule`, or at the position `#n`.
+    pub fn outcome(&self, rule: &str) -> Option<RuleOutcome> {
+        self.rule(rule).map(|explanation| explanation.outcome)
+    }
+
+    /// Returns whether the predicate of the rule was evaluated and matched.
+    pub fn predicate_matched(&self, rule: &str, predicate: &str) -> Option<bool> {
+        self.rule(rule)?
+            .predicates
+            .iter()
+            .find(|result| result.predicate == predicate)
+            .map(|result| result.matched)
+    }
+
+    /// Returns the rules that were evaluated, in order.
+    pub fn evaluated_rules(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|explanation| explanation.outcome != RuleOutcome::NotEvaluated)
+            .map(|explanation| explanation.rule.as_str())
+            .collect()
+    }
+
+    /// Returns true if no rule decided, and the access policy did.
+    pub fn decided_by_policy(&self) -> bool {
+        self.matched_rule.is_none()
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use maxminddb::geoip2;
+use serde::{Deserialize, Serialize};
+
+/// Resolves the country of the clients, for the `client-country` predicate.
+pub trait CountryResolver: Send + Sync {
+    /// Returns the ISO 3166-1 alpha-2 code of the country of `ip`, e.g. `CH`, if known.
+    fn country(&self, ip: IpAddr) -> Option<String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GeoIpConfig {
+    /// Path of a MaxMind DB with countries, e.g. GeoLite2-Country.mmdb.
+    pub database_path: PathBuf,
+}
+
+/// Looks up the countries in a MaxMind DB, loaded in memory.
+pub struct MaxMindCountryResolver {
+    reader: maxminddb::Reader<Vec<u8>>,
+}
+
+impl MaxMindCountryResolver {
+    pub fn open(config: &GeoIpConfig) -> anyhow::Result<Self> {
+        let reader =
+            maxminddb::Reader::open_readfile(&config.database_path).with_context(|| {
+                format!(
+                    "Failed to open the GeoIP database {}",
+                    config.database_path.display()
+                )
+            })?;
+        Ok(Self { reader })
+    }
+}
+
+impl CountryResolver for MaxMindCountryResolver {
+    fn country(&self, ip: IpAddr) -> Option<String> {
+        let country: geoip2::Country = self.reader.lookup(ip.to_canonical()).ok()?;
+        country.country?.iso_code.map(str::to_string)
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transactions matched by a rule with the `hold` action wait for an operator to approve or reject
+//! them. They are kept in the stats storage, so that any replica sharing it can resolve them, and
+//! their gas reservation is extended in the meantime.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use fastcrypto::encoding::Base64;
+use iota_json_rpc_types::IotaTransactionBlockResponseOptions;
+use iota_types::{base_types::IotaAddress, digests::TransactionDigest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::rule::TransactionContext;
+use crate::{rpc::ExecuteTransactionRequestType, tracker::StatsTracker, types::ReservationID};
+
+/// How long a transaction waits for approval. Its gas reservation expires afterwards, so that
+/// the coins return to the pool.
+pub const HOLD_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A transaction waiting for approval, with everything needed to execute it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct HeldTransaction {
+    pub transaction_digest: TransactionDigest,
+    pub reservation_id: ReservationID,
+    pub sponsor: IotaAddress,
+    pub sender: IotaAddress,
+    pub gas_budget: u64,
+    /// The rule that held the transaction.
+    pub matched_rule: Option<String>,
+    pub tx_bytes: Base64,
+    pub user_sig: Base64,
+    pub request_type: Option<ExecuteTransactionRequestType>,
+    #[serde(default)]
+    pub options: IotaTransactionBlockResponseOptions,
+    pub held_at_secs: u64,
+    /// The transaction can no longer be executed after this time.
+    pub expires_at_secs: u64,
+}
+
+impl HeldTransaction {
+    pub fn new(
+        ctx: &TransactionContext,
+        sponsor: IotaAddress,
+        matched_rule: Option<String>,
+        options: IotaTransactionBlockResponseOptions,
+    ) -> Self {
+        let held_at_secs = unix_secs();
+        Self {
+            transaction_digest: ctx.transaction_digest,
+            reservation_id: ctx.reservation_id,
+            sponsor,
+            sender: ctx.sender_address,
+            gas_budget: ctx.transaction_budget,
+            matched_rule,
+            tx_bytes: ctx.tx_bytes.clone(),
+            user_sig: ctx.user_sig.clone(),
+            request_type: ctx.request_type.clone(),
+            options,
+            held_at_secs,
+            expires_at_secs: held_at_secs + HOLD_DURATION.as_secs(),
+        }
+    }
+
+    /// Whether the gas reservation of the transaction expired, so that it can no longer be
+    /// executed.
+    pub fn is_expired(&self) -> bool {
+        unix_secs() >= self.expires_at_secs
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Stores the transaction until it's approved or rejected.
+pub async fn store(held: &HeldTransaction, stats_tracker: &StatsTracker) -> Result<()> {
+    let data = serde_json::to_string(held)?;
+    stats_tracker
+        .put_held_transaction(&held.transaction_digest.to_string(), &data)
+        .await
+        .context("Failed to store the held transaction")
+}
+
+/// Removes the held transaction, so that it's approved or rejected only once.
+pub async fn take(
+    transaction_digest: TransactionDigest,
+    stats_tracker: &StatsTracker,
+) -> Result<Option<HeldTransaction>> {
+    let Some(data) = stats_tracker
+        .take_held_transaction(&transaction_digest.to_string())
+        .await
+        .context("Failed to take the held transaction")?
+    else {
+        return Ok(None);
+    };
+    let held = serde_json::from_str(&data).context("Failed to parse the held transaction")?;
+    Ok(Some(held))
+}
+
+/// Returns the transactions waiting for approval, oldest first. The expired ones are omitted.
+pub async fn list(stats_tracker: &StatsTracker) -> Result<Vec<HeldTransaction>> {
+    let mut held_transactions = list_all(stats_tracker).await?;
+    held_transactions.retain(|held| !held.is_expired());
+    Ok(held_transactions)
+}
+
+/// Removes the expired transactions and returns them, so that their pending gas usage can be
+/// canceled.
+pub async fn purge_expired(stats_tracker: &StatsTracker) -> Result<Vec<HeldTransaction>> {
+    let mut purged = vec![];
+    for held in list_all(stats_tracker).await? {
+        if held.is_expired() {
+            // Another replica may have purged it in the meantime.
+            if let Some(held) = take(held.transaction_digest, stats_tracker).await? {
+                purged.push(held);
+            }
+        }
+    }
+    Ok(purged)
+}
+
+async fn list_all(stats_tracker: &StatsTracker) -> Result<Vec<HeldTransaction>> {
+    let entries = stats_tracker
+        .get_held_transactions()
+        .await
+        .context("Failed to list the held transactions")?;
+    let mut held_transactions = vec![];
+    for (id, data) in entries {
+        match serde_json::from_str::<HeldTransaction>(&data) {
+            Ok(held) => held_transactions.push(held),
+            Err(err) => error!("Skipping the invalid held transaction {}: {:?}", id, err),
+        }
+    }
+    held_transactions.sort_by_key(|held| held.held_at_secs);
+    Ok(held_transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{new_stats_tracker_for_testing, random_address};
+
+    #[tokio::test]
+    async fn test_store_and_take_held_transaction() {
+        let sponsor = random_address();
+        let stats_tracker = new_stats_tracker_for_testing(sponsor).await;
+        let ctx = TransactionContext {
+            transaction_digest: TransactionDigest::random(),
+            sender_address: random_address(),
+            transaction_budget: 1_000_000,
+            reservation_id: 7,
+            ..Default::default()
+        };
+        let held = HeldTransaction::new(
+            &ctx,
+            sponsor,
+            Some("rule #1".to_string()),
+            Default::default(),
+        );
+        store(&held, &stats_tracker).await.unwrap();
+
+        assert_eq!(list(&stats_tracker).await.unwrap(), vec![held.clone()]);
+        assert_eq!(
+            take(ctx.transaction_digest, &stats_tracker).await.unwrap(),
+            Some(held)
+        );
+        // A held transaction is resolved only once
+        assert_eq!(
+            take(ctx.transaction_digest, &stats_tracker).await.unwrap(),
+            None
+        );
+        assert!(list(&stats_tracker).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_held_transactions() {
+        let sponsor = random_address();
+        let stats_tracker = new_stats_tracker_for_testing(sponsor).await;
+        let held = |expires_at_secs| HeldTransaction {
+            expires_at_secs,
+            ..HeldTransaction::new(
+                &TransactionContext {
+                    transaction_digest: TransactionDigest::random(),
+                    ..Default::default()
+                },
+                sponsor,
+                None,
+                Default::default(),
+            )
+        };
+        let live = held(unix_secs() + 60);
+        let expired = held(unix_secs() - 1);
+        assert!(!live.is_expired());
+        assert!(expired.is_expired());
+        store(&live, &stats_tracker).await.unwrap();
+        store(&expired, &stats_tracker).await.unwrap();
+
+        assert_eq!(list(&stats_tracker).await.unwrap(), vec![live.clone()]);
+        assert_eq!(
+            purge_expired(&stats_tracker).await.unwrap(),
+            vec![expired.clone()]
+        );
+        assert!(purge_expired(&stats_tracker).await.unwrap().is_empty());
+        assert_eq!(
+            take(expired.transaction_digest, &stats_tracker)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            take(live.transaction_digest, &stats_tracker).await.unwrap(),
+            Some(live)
+        );
+    }
+}
@@ -40,6 +40,7 @@ impl HookAction {
         Ok(ExecuteTxOkResponse {
             decision: SkippableDecision::Deny,
             user_message: Some("denied transaction by default".to_string()),
+            attestation: None,
         })
     }
 }
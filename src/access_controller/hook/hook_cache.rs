@@ -0,0 +1,136 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::access_controller::hook::{ExecuteTxOkResponse, HookAction};
+use crate::access_controller::predicates::serde_duration;
+use crate::access_controller::rule::TransactionContext;
+
+/// Request header skipping the cached response, the hook is called and its response cached again.
+pub const HOOK_CACHE_BYPASS_HEADER: &str = "x-hook-cache-bypass";
+
+/// The expired responses are dropped once the cache holds this many entries.
+const HOOK_CACHE_MAX_ENTRIES: usize = 100_000;
+
+/// How long the responses of the hook of a rule are reused, and for which transactions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct HookCacheConfig {
+    #[serde(with = "serde_duration")]
+    pub ttl: Duration,
+    #[serde(default)]
+    pub key: HookCacheKey,
+}
+
+impl HookCacheConfig {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            key: HookCacheKey::default(),
+        }
+    }
+
+    pub fn with_key(mut self, key: HookCacheKey) -> Self {
+        self.key = key;
+        self
+    }
+}
+
+/// The response is reused for the transactions with the same key.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookCacheKey {
+    #[default]
+    SenderAddress,
+    TransactionDigest,
+}
+
+impl HookCacheKey {
+    fn of(&self, ctx: &TransactionContext) -> String {
+        match self {
+            HookCacheKey::SenderAddress => ctx.sender_address.to_string(),
+            HookCacheKey::TransactionDigest => ctx.transaction_digest.to_string(),
+        }
+    }
+}
+
+/// Responses of a hook, kept in memory. Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct HookCache {
+    entries: Arc<Mutex<HashMap<String, (Instant, ExecuteTxOkResponse)>>>,
+}
+
+impl std::fmt::Debug for HookCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HookCache")
+            .field("entry_count", &self.entries.lock().len())
+            .finish()
+    }
+}
+
+impl HookCache {
+    /// Returns the cached response of the hook if it's still valid, or calls the hook and caches
+    /// its response. The returned flag is true if the response came from the cache.
+    pub async fn call_hook(
+        &self,
+        hook_action: &HookAction,
+        config: &HookCacheConfig,
+        ctx: &TransactionContext,
+    ) -> Result<(ExecuteTxOkResponse, bool), anyhow::Error> {
+        let key = config.key.of(ctx);
+        if !ctx.headers.contains_key(HOOK_CACHE_BYPASS_HEADER) {
+            if let Some(response) = self.get(&key, config.ttl) {
+                return Ok((response, true));
+            }
+        }
+        let response = hook_action.call_hook(ctx).await?;
+        self.insert(key, response.clone(), config.ttl);
+        Ok((response, false))
+    }
+
+    fn get(&self, key: &str, ttl: Duration) -> Option<ExecuteTxOkResponse> {
+        let entries = self.entries.lock();
+        entries
+            .get(key)
+            .filter(|(cached_at, _)| cached_at.elapsed() < ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    fn insert(&self, key: String, response: ExecuteTxOkResponse, ttl: Duration) {
+        let mut entries = self.entries.lock();
+        if entries.len() >= HOOK_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+            // Too many senders within the TTL, start over rather than growing unbounded
+            if entries.len() >= HOOK_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (Instant::now(), response));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{HookCacheConfig, HookCacheKey};
+
+    #[test]
+    fn test_deserialize_hook_cache_config() {
+        let config: HookCacheConfig = serde_yaml::from_str("ttl: 5m").unwrap();
+        assert_eq!(config, HookCacheConfig::new(Duration::from_secs(300)));
+
+        let config: HookCacheConfig =
+            serde_yaml::from_str("ttl: 30s\nkey: transaction-digest").unwrap();
+        assert_eq!(
+            config,
+            HookCacheConfig::new(Duration::from_secs(30)).with_key(HookCacheKey::TransactionDigest)
+        );
+    }
+}
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::access_controller::rule::TransactionContext;
 use crate::rpc::rpc_types::ExecuteTransactionRequestType;
 
 /// Input for hook to check if transaction should be executed.
@@ -15,6 +16,46 @@ use crate::rpc::rpc_types::ExecuteTransactionRequestType;
 #[serde(rename_all = "camelCase")]
 pub struct ExecuteTxHookRequest {
     pub execute_tx_request: ExecuteTxGasStationRequest,
+    /// Transaction fields already parsed by the gas station.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_summary: Option<TransactionSummary>,
+}
+
+impl From<&TransactionContext> for ExecuteTxHookRequest {
+    fn from(ctx: &TransactionContext) -> Self {
+        let mut headers: HashMap<String, Vec<String>> = HashMap::new();
+        for (k, v) in ctx.headers.clone() {
+            let k = k.map(|v| v.to_string()).unwrap_or_default();
+            let v = String::from_utf8_lossy(v.as_bytes()).into_owned();
+            headers.entry(k).or_insert_with(Vec::new).push(v);
+        }
+        Self {
+            execute_tx_request: ExecuteTxGasStationRequest {
+                payload: ExecuteTxRequestPayload {
+                    reservation_id: ctx.reservation_id,
+                    tx_bytes: ctx.tx_bytes.encoded(),
+                    user_sig: ctx.user_sig.encoded(),
+                    request_type: ctx.request_type.clone(),
+                },
+                headers,
+            },
+            transaction_summary: Some(TransactionSummary {
+                digest: ctx.transaction_digest.to_string(),
+                sender: ctx.sender_address.to_string(),
+                gas_budget: ctx.transaction_budget,
+                command_count: ctx.ptb_command_count,
+                move_calls: ctx
+                    .move_calls
+                    .iter()
+                    .map(|(package, module, function)| MoveCallSummary {
+                        package: package.to_string(),
+                        module: module.clone(),
+                        function: function.clone(),
+                    })
+                    .collect(),
+            }),
+        }
+    }
 }
 
 /// Original request data and headers sent to Gas Stations `execute_tx` endpoint.
@@ -39,22 +80,92 @@ pub struct ExecuteTxRequestPayload {
     pub request_type: Option<ExecuteTransactionRequestType>,
 }
 
-/// Result of checking if transaction should be executed.
+/// Transaction fields parsed by the gas station, so that a hook can check them without decoding
+/// `txBytes`.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    /// Transaction digest, base58 encoded.
+    pub digest: String,
+    /// Address of the transaction sender.
+    pub sender: String,
+    /// Gas budget set by the transaction.
+    pub gas_budget: u64,
+    /// Number of commands of the programmable transaction, if it is one.
+    pub command_count: Option<usize>,
+    /// Move calls of the transaction, in order.
+    pub move_calls: Vec<MoveCallSummary>,
+}
+
+/// Function called by a move call.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCallSummary {
+    /// Package ID, hex encoded.
+    pub package: String,
+    pub module: String,
+    pub function: String,
+}
+
+/// Result of checking if transaction should be executed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ExecuteTxOkResponse {
     /// Hooks decision about transaction execution.
     pub decision: SkippableDecision,
     /// Message intended to be forwarded to caller.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_message: Option<String>,
+    /// Proof of an `allow` decision for the sidecar signer, e.g. a signature of the policy
+    /// service over the transaction digest. Forwarded as is to the signer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
 }
 
 /// "allow"/"deny" transaction or take "noDecision" and proceed with other rules.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum SkippableDecision {
     Allow,
     Deny,
     NoDecision,
 }
+
+#[cfg(test)]
+mod test {
+    use iota_types::{base_types::ObjectID, digests::TransactionDigest};
+    use serde_json::json;
+
+    use super::ExecuteTxHookRequest;
+    use crate::access_controller::rule::TransactionContext;
+    use crate::test_env::random_address;
+
+    #[test]
+    fn test_hook_request_transaction_summary() {
+        let package = ObjectID::random();
+        let ctx = TransactionContext {
+            transaction_digest: TransactionDigest::random(),
+            sender_address: random_address(),
+            transaction_budget: 1_000_000,
+            ptb_command_count: Some(2),
+            move_calls: vec![(package, "pool".to_string(), "swap".to_string())],
+            ..Default::default()
+        };
+
+        let request = serde_json::to_value(ExecuteTxHookRequest::from(&ctx)).unwrap();
+        assert_eq!(
+            request["transactionSummary"],
+            json!({
+                "digest": ctx.transaction_digest.to_string(),
+                "sender": ctx.sender_address.to_string(),
+                "gasBudget": 1_000_000,
+                "commandCount": 2,
+                "moveCalls": [{
+                    "package": package.to_string(),
+                    "module": "pool",
+                    "function": "swap",
+                }],
+            })
+        );
+    }
+}
@@ -3,7 +3,11 @@
 
 #[cfg_attr(test, path = "hook_action_test.rs")]
 mod hook_action;
+mod hook_cache;
 mod hook_server_types;
+mod notify_action;
 
 pub use hook_action::*;
+pub use hook_cache::*;
 pub use hook_server_types::*;
+pub use notify_action::*;
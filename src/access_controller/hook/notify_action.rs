@@ -0,0 +1,53 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn, Instrument};
+use url::Url;
+
+use crate::access_controller::hook::ExecuteTxHookRequest;
+use crate::access_controller::rule::TransactionContext;
+use crate::rpc::rpc_types::REQUEST_ID_HEADER;
+
+const NOTIFY_REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+/// Webhook receiving the transactions matched by a rule, without taking part in the decision.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotifyAction(pub(crate) Url);
+
+impl NotifyAction {
+    /// Sends the transaction to the webhook in the background, with the same body as the hook
+    /// request. Failures are only logged.
+    pub fn notify(&self, ctx: &TransactionContext) {
+        let url = self.0.clone();
+        let body = ExecuteTxHookRequest::from(ctx);
+        let transaction_digest = ctx.transaction_digest;
+        let request_id = ctx.request_id().map(|request_id| request_id.to_string());
+        tokio::spawn(
+            async move {
+                let result = async {
+                    let client = reqwest::Client::builder()
+                        .timeout(std::time::Duration::from_secs(
+                            NOTIFY_REQUEST_TIMEOUT_SECONDS,
+                        ))
+                        .build()?;
+                    let mut request = client.post(url.clone()).json(&body);
+                    if let Some(request_id) = &request_id {
+                        request = request.header(REQUEST_ID_HEADER, request_id);
+                    }
+                    request.send().await?.error_for_status()?;
+                    Ok::<_, anyhow::Error>(())
+                }
+                .await;
+                match result {
+                    Ok(()) => debug!("Notified {} of transaction {}", url, transaction_digest),
+                    Err(err) => warn!(
+                        "Failed to notify {} of transaction {}: {:?}",
+                        url, transaction_digest, err
+                    ),
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
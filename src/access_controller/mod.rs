@@ -5,25 +5,34 @@
 //! It provides a way to control the constraints for executing transactions, ensuring that only authorized addresses can perform specific actions.
 
 pub mod decision;
+pub mod decision_cache;
+pub mod explanation;
+pub mod geoip;
+pub mod held_transactions;
 pub mod hook;
+pub mod pending_confirmations;
 pub mod policy;
 pub mod predicates;
 pub mod rule;
+pub mod rule_templates;
 
-use std::{collections::HashMap, fmt::Formatter, sync::Arc};
+use std::{collections::HashMap, fmt::Formatter, net::IpAddr, sync::Arc};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use decision::Decision;
-use hook::SkippableDecision;
-use iota_types::digests::TransactionDigest;
+use decision_cache::{DecisionCache, DecisionCacheConfig};
+use explanation::{set_outcome, EvaluationTrace, RuleExplanation, RuleOutcome};
+use futures_util::future::join_all;
+use geoip::{CountryResolver, GeoIpConfig, MaxMindCountryResolver};
+use hook::{ExecuteTxOkResponse, HookAction, SkippableDecision};
+use iota_types::{base_types::IotaAddress, digests::TransactionDigest};
 use policy::AccessPolicy;
-use predicates::Action;
-use rule::{AccessRule, GasUsageConfirmationRequest, TransactionContext};
+use predicates::{Action, ValueAggregate};
+use rule::{AccessRule, TransactionContext};
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
-use tracing::debug;
+use tracing::{debug, info, instrument};
 
-use crate::tracker::StatsTracker;
+use crate::tracker::{stats_tracker_storage::AggregateValue, StatsTracker};
 
 #[derive(Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -31,9 +40,25 @@ pub struct AccessController {
     pub access_policy: AccessPolicy,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub rules: Vec<AccessRule>,
-
+    /// Calls the hooks of consecutive rules concurrently. Their responses are still applied in
+    /// the order of the rules.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub parallel_hooks: bool,
+    /// Reuses the decision about a transaction submitted again within the TTL, e.g. when a client
+    /// retries it, instead of evaluating the rules and calling the hooks again.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decision_cache: Option<DecisionCacheConfig>,
+    /// Resolves the country of the clients for the `client-country` predicates.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub geoip: Option<GeoIpConfig>,
+    /// Lets the clients ask for the explanation of the decisions with `explain=true`, which
+    /// reveals the names of the rules and of their predicates.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub allow_explain: bool,
+    #[serde(skip)]
+    decision_cache_entries: DecisionCache,
     #[serde(skip)]
-    confirmation_requests: Arc<Mutex<HashMap<TransactionDigest, Vec<GasUsageConfirmationRequest>>>>,
+    country_resolver: Option<Arc<dyn CountryResolver>>,
 }
 
 impl std::fmt::Debug for AccessController {
@@ -41,6 +66,10 @@ impl std::fmt::Debug for AccessController {
         f.debug_struct("AccessController")
             .field("access_policy", &self.access_policy)
             .field("rules", &self.rules)
+            .field("parallel_hooks", &self.parallel_hooks)
+            .field("decision_cache", &self.decision_cache)
+            .field("geoip", &self.geoip)
+            .field("allow_explain", &self.allow_explain)
             .finish()
     }
 }
@@ -51,49 +80,266 @@ impl AccessController {
         Self {
             access_policy,
             rules: rules.into_iter().collect(),
-            confirmation_requests: Arc::new(Mutex::new(HashMap::new())),
+            parallel_hooks: false,
+            decision_cache: None,
+            geoip: None,
+            allow_explain: false,
+            decision_cache_entries: DecisionCache::default(),
+            country_resolver: None,
         }
     }
 
+    /// Calls the hooks of consecutive rules concurrently.
+    pub fn with_parallel_hooks(mut self, parallel_hooks: bool) -> Self {
+        self.parallel_hooks = parallel_hooks;
+        self
+    }
+
+    /// Reuses the decision about a transaction submitted again within the TTL of the cache.
+    pub fn with_decision_cache(mut self, decision_cache: DecisionCacheConfig) -> Self {
+        self.decision_cache = Some(decision_cache);
+        self
+    }
+
+    /// Lets the clients ask for the explanation of the decisions.
+    pub fn with_allow_explain(mut self, allow_explain: bool) -> Self {
+        self.allow_explain = allow_explain;
+        self
+    }
+
+    /// Resolves the country of the clients with `country_resolver` instead of the GeoIP database.
+    pub fn with_country_resolver(mut self, country_resolver: Arc<dyn CountryResolver>) -> Self {
+        self.country_resolver = Some(country_resolver);
+        self
+    }
+
     /// Initializes the access controller by loading the rules from the external sources
     pub async fn initialize(&mut self) -> Result<()> {
         for (i, rule) in &mut self.rules.iter_mut().enumerate() {
             debug!("Initializing access control rule {}", i + 1);
             rule.initialize().await?;
         }
+        for (i, rule) in self.rules.iter().enumerate() {
+            if let Action::Goto(target) = &rule.action {
+                let target_rule = self.goto_target(i, target)?;
+                // The rules are a single list, the rules before the target would otherwise
+                // continue into the sub-policy.
+                if !self.rules[target_rule - 1].is_catch_all() {
+                    bail!(
+                        "Rule #{} '{}' is the target of a goto, the rule before it must allow, \
+                         deny or hold every transaction",
+                        target_rule + 1,
+                        target
+                    );
+                }
+            }
+        }
+        if let (Some(geoip), None) = (&self.geoip, &self.country_resolver) {
+            self.country_resolver = Some(Arc::new(MaxMindCountryResolver::open(geoip)?));
+        }
+        if self.uses_client_country() && self.country_resolver.is_none() {
+            info!("No GeoIP database is configured, the country of the clients is unknown");
+        }
         Ok(())
     }
 
     /// Checks if the transaction can be executed based on the access controller's rules.
+    pub async fn check_access(&self, ctx: &TransactionContext) -> Result<Decision> {
+        Ok(self.check_access_with_details(ctx).await?.decision)
+    }
+
+    /// Same as `check_access`, but also returns the rule that made the decision and the message
+    /// given by its hook.
     // If a rule matches, the corresponding action is applied. If no rule matches, the next rule is checked.
     // If none match, the default policy is applied.
-    pub async fn check_access(&self, ctx: &TransactionContext) -> Result<Decision> {
+    #[instrument(skip_all, fields(digest = %ctx.transaction_digest))]
+    pub async fn check_access_with_details(
+        &self,
+        ctx: &TransactionContext,
+    ) -> Result<AccessDecision> {
+        if self.is_disabled() {
+            return Ok(AccessDecision::new(Decision::Allow));
+        }
+
+        let Some(decision_cache) = self.decision_cache else {
+            return self.evaluate_rules(ctx, false, false).await;
+        };
+        if let Some(access_decision) = self
+            .decision_cache_entries
+            .get(&ctx.transaction_digest, decision_cache.ttl)
+        {
+            debug!(
+                "Reusing the access decision: {:?}",
+                access_decision.decision
+            );
+            // No hook was called for this decision
+            return Ok(AccessDecision {
+                hook_cache_lookups: vec![],
+                ..access_decision
+            });
+        }
+        let access_decision = self.evaluate_rules(ctx, false, false).await?;
+        self.decision_cache_entries.insert(
+            ctx.transaction_digest,
+            access_decision.clone(),
+            decision_cache.ttl,
+        );
+        Ok(access_decision)
+    }
+
+    /// Same as `check_access_with_details`, but the decision comes with the explanation of every
+    /// rule. The decision cache is bypassed, so that the rules are always evaluated.
+    #[instrument(skip_all, fields(digest = %ctx.transaction_digest))]
+    pub async fn check_access_explained(&self, ctx: &TransactionContext) -> Result<AccessDecision> {
         if self.is_disabled() {
-            return Ok(Decision::Allow);
+            return Ok(AccessDecision::new(Decision::Allow));
         }
+        self.evaluate_rules(ctx, true, false).await
+    }
+
+    /// Evaluates the rules like `check_access`, without the decision cache, and returns the trace
+    /// of the evaluation: the rule that made the decision and the outcome and predicates of every
+    /// rule. Meant for tests, the notifications are sent and the gas usage is counted as usual.
+    pub async fn evaluate_with_trace(&self, ctx: &TransactionContext) -> Result<EvaluationTrace> {
+        let access_decision = self.check_access_explained(ctx).await?;
+        Ok(EvaluationTrace {
+            decision: access_decision.decision,
+            matched_rule: access_decision.matched_rule,
+            user_message: access_decision.user_message,
+            deny_message: access_decision.deny_message,
+            audited_rules: access_decision.audited_rules,
+            rules: access_decision.explanation,
+        })
+    }
 
+    /// Evaluates the rules without any lasting effect, to tell a client in advance whether a
+    /// transaction would be allowed: the notifications aren't sent, and the gas usage limits
+    /// compare their counters plus the gas budget without counting it. The hooks are called. The
+    /// decision cache is bypassed, so that the transaction is still counted when it's executed.
+    #[instrument(skip_all, fields(digest = %ctx.transaction_digest))]
+    pub async fn dry_run(&self, ctx: &TransactionContext, explain: bool) -> Result<AccessDecision> {
+        if self.is_disabled() {
+            return Ok(AccessDecision::new(Decision::Allow));
+        }
+        self.evaluate_rules(ctx, explain, true).await
+    }
+
+    /// Evaluates the rules in order, up to the first one making a decision. The gas budget of a
+    /// transaction checked again is counted once by the gas usage limits. With `explain`, the
+    /// predicates of the rules are evaluated one by one for the explanation, and with `dry_run`,
+    /// no notification is sent and no gas usage is counted.
+    async fn evaluate_rules(
+        &self,
+        ctx: &TransactionContext,
+        explain: bool,
+        dry_run: bool,
+    ) -> Result<AccessDecision> {
+        let mut explanation = vec![];
+        let mut audited_rules = vec![];
+        let mut hook_cache_lookups = vec![];
+        let mut prefetched_hook_responses = HashMap::new();
+        let mut prefetched_until = 0;
+        // The gas usage already counted for the transaction, loaded by the first rule with a limit
+        let mut counted_gas_usage = None;
+        let mut confirmation_requests = vec![];
+        // The position of the next rule to evaluate, after a jump
+        let mut next_rule = 0;
         for (i, rule) in self.rules.iter().enumerate() {
-            if rule
+            if i < next_rule {
+                if explain {
+                    explanation.push(RuleExplanation {
+                        rule: rule.display_name(i + 1),
+                        predicates: vec![],
+                        outcome: RuleOutcome::NotEvaluated,
+                    });
+                }
+                continue;
+            }
+            if self.parallel_hooks
+                && matches!(rule.action, Action::HookAction(_))
+                && i >= prefetched_until
+            {
+                (prefetched_hook_responses, prefetched_until) =
+                    self.prefetch_hook_responses(i, ctx).await;
+            }
+            let matched = rule
                 .matches(&ctx)
                 .await
-                .with_context(|| anyhow!("Error evaluating rule #{}", i + 1))?
-            {
+                .with_context(|| anyhow!("Error evaluating rule #{}", i + 1))?;
+            if explain {
+                explanation.push(RuleExplanation {
+                    rule: rule.display_name(i + 1),
+                    predicates: rule.explain(ctx).await?,
+                    outcome: RuleOutcome::NotMatched,
+                });
+            }
+            if matched {
                 // Validate the counters if the rule partially matches
-                let matching_result = rule.match_global_limits(ctx).await?;
+                if rule.has_gas_limits() && counted_gas_usage.is_none() {
+                    counted_gas_usage = Some(
+                        pending_confirmations::get(ctx.transaction_digest, &ctx.stats_tracker)
+                            .await?,
+                    );
+                }
+                let counted = counted_gas_usage.as_deref().unwrap_or_default();
+                let matching_result = if dry_run {
+                    (rule.peek_global_limits(ctx, counted).await?, vec![])
+                } else {
+                    rule.match_global_limits_once(ctx, counted).await?
+                };
                 if !matching_result.1.is_empty() {
-                    self.confirmation_requests
-                        .lock()
-                        .await
-                        .insert(ctx.transaction_digest, matching_result.1);
+                    confirmation_requests.extend(matching_result.1);
+                    pending_confirmations::store(
+                        ctx.transaction_digest,
+                        confirmation_requests.clone(),
+                        &ctx.stats_tracker,
+                    )
+                    .await?;
                 }
                 // if the rule matches and also matches the global limits, invoke the action
                 if matching_result.0 {
-                    match &rule.action {
-                        Action::Allow => return Ok(Decision::Allow),
-                        Action::Deny => return Ok(Decision::Deny),
+                    let (decision, user_message, attestation) = match &rule.action {
+                        Action::Allow => (Decision::Allow, None, None),
+                        Action::Deny => (Decision::Deny, None, None),
+                        Action::Hold => (Decision::Hold, None, None),
+                        Action::Audit => {
+                            let rule_name = rule.display_name(i + 1);
+                            info!(
+                                rule = %rule_name,
+                                sender = %ctx.sender_address,
+                                gas_budget = ctx.transaction_budget,
+                                "Audit rule matched"
+                            );
+                            audited_rules.push(rule_name);
+                            set_outcome(&mut explanation, RuleOutcome::Audited);
+                            continue;
+                        }
+                        Action::Notify(notify_action) => {
+                            if !dry_run {
+                                notify_action.notify(ctx);
+                            }
+                            set_outcome(&mut explanation, RuleOutcome::Notified);
+                            continue;
+                        }
+                        Action::Goto(target) => {
+                            next_rule = self.goto_target(i, target)?;
+                            debug!(
+                                "Rule {} jumps to {} for transaction with digest: {}",
+                                rule.display_name(i + 1),
+                                target,
+                                ctx.transaction_digest
+                            );
+                            set_outcome(&mut explanation, RuleOutcome::Jumped);
+                            continue;
+                        }
                         Action::HookAction(hook_action) => {
                             // call hook and take defined result or continue with next rule
-                            let response = hook_action.call_hook(ctx).await?;
+                            let (response, cache_lookup) =
+                                match prefetched_hook_responses.remove(&i) {
+                                    Some(prefetched) => prefetched?,
+                                    None => call_hook(rule, hook_action, i, ctx).await?,
+                                };
+                            hook_cache_lookups.extend(cache_lookup);
                             debug!("Called hook: {}, for transaction with digest: {}. Got decision: {:?}, with user message: {:?}",
                                     hook_action.0,
                                     ctx.transaction_digest,
@@ -101,21 +347,108 @@ impl AccessController {
                                     response.user_message,
                                 );
                             match response.decision {
-                                SkippableDecision::Allow => return Ok(Decision::Allow),
-                                SkippableDecision::Deny => return Ok(Decision::Deny),
-                                _ => (),
-                            };
+                                SkippableDecision::Allow => {
+                                    (Decision::Allow, response.user_message, response.attestation)
+                                }
+                                SkippableDecision::Deny => {
+                                    (Decision::Deny, response.user_message, None)
+                                }
+                                SkippableDecision::NoDecision => {
+                                    set_outcome(&mut explanation, RuleOutcome::HookNoDecision);
+                                    continue;
+                                }
+                            }
                         }
                     };
+                    let deny_message = match decision {
+                        Decision::Deny => rule.deny_message.clone(),
+                        Decision::Allow | Decision::Hold => None,
+                    };
+                    if explain {
+                        set_outcome(&mut explanation, RuleOutcome::Decided);
+                        explanation.extend(self.rules.iter().enumerate().skip(i + 1).map(
+                            |(j, rule)| RuleExplanation {
+                                rule: rule.display_name(j + 1),
+                                predicates: vec![],
+                                outcome: RuleOutcome::NotEvaluated,
+                            },
+                        ));
+                    }
+                    return Ok(AccessDecision {
+                        decision,
+                        matched_rule: Some(rule.display_name(i + 1)),
+                        user_message,
+                        deny_message,
+                        attestation,
+                        audited_rules,
+                        hook_cache_lookups,
+                        explanation,
+                    });
                 }
+                set_outcome(&mut explanation, RuleOutcome::GasLimitNotMatched);
             }
         }
 
-        match self.access_policy {
-            AccessPolicy::AllowAll => Ok(Decision::Allow),
-            AccessPolicy::DenyAll => Ok(Decision::Deny),
-            AccessPolicy::Disabled => Ok(Decision::Allow),
+        Ok(AccessDecision {
+            audited_rules,
+            hook_cache_lookups,
+            explanation,
+            ..AccessDecision::new(self.access_policy.into())
+        })
+    }
+
+    /// Returns the position of the first rule named `target` after the rule at `from`. Jumping
+    /// forward only, the evaluation always ends.
+    fn goto_target(&self, from: usize, target: &str) -> Result<usize> {
+        self.rules
+            .iter()
+            .enumerate()
+            .skip(from + 1)
+            .find(|(_, rule)| rule.name.as_deref() == Some(target))
+            .map(|(i, _)| i)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Rule #{} jumps to '{}', which is not the name of a later rule",
+                    from + 1,
+                    target
+                )
+            })
+    }
+
+    /// Calls concurrently the hooks of the rules starting at `first`, up to the first rule that
+    /// isn't a hook. Only the hooks of the rules matching the transaction are called. The
+    /// responses are keyed by the position of the rule, and returned with the position of the
+    /// first rule that wasn't considered.
+    async fn prefetch_hook_responses(
+        &self,
+        first: usize,
+        ctx: &TransactionContext,
+    ) -> (
+        HashMap<usize, Result<(ExecuteTxOkResponse, Option<HookCacheLookup>)>>,
+        usize,
+    ) {
+        let mut calls = vec![];
+        let mut end = first;
+        for (i, rule) in self.rules.iter().enumerate().skip(first) {
+            let Action::HookAction(hook_action) = &rule.action else {
+                break;
+            };
+            // An error is left to the evaluation of the rule, which stops there.
+            match rule.matches(ctx).await {
+                Ok(true) => {
+                    calls.push(async move { (i, call_hook(rule, hook_action, i, ctx).await) })
+                }
+                Ok(false) => {}
+                Err(_) => break,
+            }
+            end = i + 1;
         }
+        debug!(
+            "Calling {} hooks concurrently for transaction with digest: {}",
+            calls.len(),
+            ctx.transaction_digest
+        );
+        (join_all(calls).await.into_iter().collect(), end)
     }
 
     pub async fn confirm_transaction(
@@ -123,27 +456,77 @@ impl AccessController {
         result: TransactionExecutionResult,
         stats_tracker: &StatsTracker,
     ) -> Result<()> {
-        let mut confirmation_requests = self.confirmation_requests.lock().await;
-        let transaction_digest = result.transaction_digest;
-        let maybe_requests = confirmation_requests.remove(&transaction_digest);
-        if let Some(requests) = maybe_requests {
-            for req in requests {
-                let diff = if let Some(real_gas_usage) = result.gas_usage {
-                    let reserved_gas_usage = req.gas_usage;
-                    let diff = reserved_gas_usage - real_gas_usage;
-                    debug!("Transaction with id: {transaction_digest} confirmed, reserved gas usage: {reserved_gas_usage}, real gas usage: {real_gas_usage}, diff: {diff}");
-                    diff
-                } else {
-                    debug!("Transaction with id: {transaction_digest} confirmed, but no gas usage was provided");
-                    req.gas_usage
-                } as i64;
-                stats_tracker
-                    .update_aggr(req.rule_meta, &req.aggregate, diff * -1)
+        // A cancelled transaction has its counted gas usage reverted, so it must be counted again
+        // when retried rather than allowed by its cached decision.
+        if result.gas_usage.is_none() {
+            self.decision_cache_entries
+                .remove(&result.transaction_digest);
+        }
+        pending_confirmations::confirm(result.transaction_digest, result.gas_usage, stats_tracker)
+            .await
+    }
+
+    /// Returns the gas usage of the sender counted by every rule with a gas usage limit that
+    /// applies to it.
+    pub async fn get_gas_usage_limits(
+        &self,
+        sender_address: &IotaAddress,
+        stats_tracker: &StatsTracker,
+    ) -> Result<Vec<GasUsageLimit>> {
+        let mut limits = vec![];
+        for (i, rule) in self.rules.iter().enumerate() {
+            let Some(usage) = rule
+                .get_gas_usage(sender_address, stats_tracker)
+                .await
+                .with_context(|| anyhow!("Error reading the gas usage of rule #{}", i + 1))?
+            else {
+                continue;
+            };
+            if let Some(limit) = rule.gas_usage.clone() {
+                limits.push(GasUsageLimit {
+                    rule_number: i + 1,
+                    limit,
+                    usage,
+                });
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Resets or credits the gas usage of the sender counted by the rule at `rule_number`,
+    /// starting at 1, and returns the new gas usage.
+    pub async fn adjust_gas_usage(
+        &self,
+        rule_number: usize,
+        sender_address: &IotaAddress,
+        adjustment: GasUsageAdjustment,
+        stats_tracker: &StatsTracker,
+    ) -> Result<GasUsageLimit> {
+        let rule = rule_number
+            .checked_sub(1)
+            .and_then(|i| self.rules.get(i))
+            .with_context(|| anyhow!("Rule #{} doesn't exist", rule_number))?;
+        let limit = rule
+            .gas_usage
+            .clone()
+            .with_context(|| anyhow!("Rule #{} has no gas usage limit", rule_number))?;
+        match adjustment {
+            GasUsageAdjustment::Reset => rule.reset_gas_usage(sender_address, stats_tracker).await,
+            GasUsageAdjustment::Credit(amount) => {
+                rule.credit_gas_usage(sender_address, amount, stats_tracker)
                     .await
-                    .context("Failed to update aggregate while when confirming transactions")?;
             }
         }
-        Ok(())
+        .with_context(|| anyhow!("Error adjusting the gas usage of rule #{}", rule_number))?;
+        let usage = rule
+            .get_gas_usage(sender_address, stats_tracker)
+            .await?
+            .unwrap_or_default();
+        Ok(GasUsageLimit {
+            rule_number,
+            limit,
+            usage,
+        })
     }
 
     /// Adds a new rule to the access controller.
@@ -156,6 +539,44 @@ impl AccessController {
         self.rules.extend(rules);
     }
 
+    /// Replaces the `notify` actions by `audit` actions, so that the rules can be evaluated
+    /// offline, e.g. by the tool, without sending any notification.
+    pub fn disable_notifications(&mut self) {
+        for rule in &mut self.rules {
+            if matches!(rule.action, Action::Notify(_)) {
+                rule.action = Action::Audit;
+            }
+        }
+    }
+
+    /// Returns true if any rule has a `coin-type` predicate, which needs the types of the objects
+    /// used by the coin commands.
+    pub fn uses_coin_types(&self) -> bool {
+        !self.is_disabled()
+            && self.rules.iter().any(|rule| {
+                rule.coin_type.is_some()
+                    || rule.any_of.iter().any(|group| group.coin_type.is_some())
+            })
+    }
+
+    /// Returns true if any rule has a `client-country` predicate, which needs the country of the
+    /// client.
+    pub fn uses_client_country(&self) -> bool {
+        !self.is_disabled()
+            && self.rules.iter().any(|rule| {
+                rule.client_country.is_some()
+                    || rule
+                        .any_of
+                        .iter()
+                        .any(|group| group.client_country.is_some())
+            })
+    }
+
+    /// Returns the country of the client at `ip`, if it can be resolved.
+    pub fn client_country(&self, ip: IpAddr) -> Option<String> {
+        self.country_resolver.as_ref()?.country(ip)
+    }
+
     /// Returns true if the access controller is disabled.
     pub fn is_disabled(&self) -> bool {
         self.access_policy == AccessPolicy::Disabled
@@ -168,6 +589,88 @@ impl AccessController {
     }
 }
 
+/// Calls the hook of the rule at position `i`, starting at 0, through its cache if it has one.
+async fn call_hook(
+    rule: &AccessRule,
+    hook_action: &HookAction,
+    i: usize,
+    ctx: &TransactionContext,
+) -> Result<(ExecuteTxOkResponse, Option<HookCacheLookup>)> {
+    match &rule.hook_cache {
+        Some(cache_config) => {
+            let (response, hit) = rule
+                .hook_cache_entries
+                .call_hook(hook_action, cache_config, ctx)
+                .await?;
+            let lookup = HookCacheLookup {
+                rule: rule.display_name(i + 1),
+                hit,
+            };
+            Ok((response, Some(lookup)))
+        }
+        None => Ok((hook_action.call_hook(ctx).await?, None)),
+    }
+}
+
+/// Decision of the access controller, with what led to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDecision {
+    pub decision: Decision,
+    /// The rule that made the decision, `None` if it was made by the access policy.
+    pub matched_rule: Option<String>,
+    /// Message intended to be forwarded to the caller, given by the hook of the matched rule.
+    pub user_message: Option<String>,
+    /// The `deny-message` of the matched rule, if it denied the transaction.
+    pub deny_message: Option<String>,
+    /// Attestation given by the hook of the matched rule, if it allowed the transaction.
+    pub attestation: Option<String>,
+    /// The rules with the `audit` action that matched before the decision was made.
+    pub audited_rules: Vec<String>,
+    /// The hooks with a cache that were called to make the decision.
+    pub hook_cache_lookups: Vec<HookCacheLookup>,
+    /// Why each rule made the decision or didn't, when the decision is explained.
+    pub explanation: Vec<RuleExplanation>,
+}
+
+impl AccessDecision {
+    pub fn new(decision: Decision) -> Self {
+        Self {
+            decision,
+            matched_rule: None,
+            user_message: None,
+            deny_message: None,
+            attestation: None,
+            audited_rules: vec![],
+            hook_cache_lookups: vec![],
+            explanation: vec![],
+        }
+    }
+}
+
+/// Whether the response of the hook of a rule came from its cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookCacheLookup {
+    pub rule: String,
+    pub hit: bool,
+}
+
+/// Gas usage counted by a rule with a gas usage limit.
+#[derive(Debug, Clone)]
+pub struct GasUsageLimit {
+    /// Position of the rule in the access controller, starting at 1.
+    pub rule_number: usize,
+    pub limit: ValueAggregate,
+    pub usage: AggregateValue,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GasUsageAdjustment {
+    /// Clears the gas usage, its window starts again with the next transaction.
+    Reset,
+    /// Subtracts the amount from the gas usage.
+    Credit(u64),
+}
+
 pub struct TransactionExecutionResult {
     pub transaction_digest: TransactionDigest,
     pub gas_usage: Option<u64>,
@@ -187,17 +690,24 @@ impl TransactionExecutionResult {
 
 #[cfg(test)]
 mod test {
-    use iota_types::base_types::IotaAddress;
+    use std::{net::IpAddr, sync::Arc};
+
+    use iota_types::{base_types::IotaAddress, digests::TransactionDigest};
 
     use crate::access_controller::{
         decision::Decision,
-        predicates::{Action, ValueIotaAddress},
-        AccessController,
+        decision_cache::DecisionCacheConfig,
+        explanation::{PredicateMatch, RuleExplanation, RuleOutcome},
+        geoip::{CountryResolver, GeoIpConfig},
+        pending_confirmations,
+        predicates::{Action, ValueCountry, ValueIotaAddress},
+        AccessController, TransactionExecutionResult,
     };
+    use crate::test_env::{new_stats_tracker_for_testing, random_address};
 
     use super::{
         policy::AccessPolicy,
-        predicates::ValueNumber,
+        predicates::{ValueAggregate, ValueNumber},
         rule::{AccessRuleBuilder, TransactionContext},
     };
 
@@ -586,6 +1096,96 @@ rules:
         assert!(matches!(ac.check_access(&tx).await, Ok(Decision::Allow)));
     }
 
+    #[tokio::test]
+    async fn test_audit_rule_continues_evaluation() {
+        let sender_address = IotaAddress::new([1; 32]);
+        let audit_rule = AccessRuleBuilder::new()
+            .name("prospective deny")
+            .sender_address(sender_address)
+            .audit()
+            .build();
+        let other_audit_rule = AccessRuleBuilder::new()
+            .sender_address(IotaAddress::new([2; 32]))
+            .audit()
+            .build();
+
+        let tx = TransactionContext::default().with_sender_address(sender_address);
+        let ac = AccessController::new(
+            AccessPolicy::DenyAll,
+            [
+                audit_rule.clone(),
+                other_audit_rule,
+                AccessRuleBuilder::new().allow().build(),
+            ],
+        );
+
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Allow);
+        assert_eq!(details.matched_rule.as_deref(), Some("#3"));
+        assert_eq!(details.audited_rules, vec!["prospective deny".to_string()]);
+
+        // The access policy decides if only audit rules match
+        let ac = AccessController::new(AccessPolicy::DenyAll, [audit_rule]);
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Deny);
+        assert_eq!(details.matched_rule, None);
+        assert_eq!(details.audited_rules, vec!["prospective deny".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_hold_rule() {
+        let sender_address = IotaAddress::new([1; 32]);
+        let hold_rule = AccessRuleBuilder::new()
+            .sender_address(sender_address)
+            .gas_budget(ValueNumber::GreaterThan(1000))
+            .hold()
+            .build();
+        let ac = AccessController::new(AccessPolicy::AllowAll, [hold_rule]);
+
+        let tx = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(5000);
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Hold);
+        assert_eq!(details.matched_rule.as_deref(), Some("#1"));
+        assert_eq!(details.deny_message, None);
+
+        let tx = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(500);
+        assert_eq!(ac.check_access(&tx).await.unwrap(), Decision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_notify_rule_continues_evaluation() {
+        let sender_address = IotaAddress::new([1; 32]);
+        // The webhook is unreachable, which doesn't change the decision
+        let notify_rule = AccessRuleBuilder::new()
+            .sender_address(sender_address)
+            .notify(url::Url::parse("http://127.0.0.1:1/events").unwrap())
+            .build();
+        let ac = AccessController::new(
+            AccessPolicy::AllowAll,
+            [
+                notify_rule.clone(),
+                AccessRuleBuilder::new()
+                    .sender_address(sender_address)
+                    .deny()
+                    .build(),
+            ],
+        );
+
+        let tx = TransactionContext::default().with_sender_address(sender_address);
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Deny);
+        assert_eq!(details.matched_rule.as_deref(), Some("#2"));
+
+        let ac = AccessController::new(AccessPolicy::DenyAll, [notify_rule]);
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Deny);
+        assert_eq!(details.matched_rule, None);
+    }
+
     #[tokio::test]
     async fn test_evaluation_logic_matching() {
         let sender_1 = IotaAddress::new([1; 32]);
@@ -631,13 +1231,395 @@ rules:
         ));
     }
 
+    #[tokio::test]
+    async fn test_retried_transaction_gas_usage_counted_once() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(60),
+                ValueNumber::GreaterThanOrEqual(300),
+            ))
+            .deny()
+            .build();
+        let ac = AccessController::new(AccessPolicy::AllowAll, [rule]);
+        let ctx = TransactionContext::default()
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+
+        assert_eq!(ac.check_access(&ctx).await.unwrap(), Decision::Allow);
+        // Without the decision cache, the rules are evaluated again but the budget isn't counted
+        assert_eq!(ac.check_access(&ctx).await.unwrap(), Decision::Allow);
+
+        let other_ctx = TransactionContext {
+            transaction_digest: TransactionDigest::random(),
+            ..ctx
+        };
+        assert_eq!(ac.check_access(&other_ctx).await.unwrap(), Decision::Deny);
+    }
+
+    #[tokio::test]
+    async fn test_client_country() {
+        struct TestCountryResolver;
+
+        impl CountryResolver for TestCountryResolver {
+            fn country(&self, ip: IpAddr) -> Option<String> {
+                (ip == IpAddr::from([1, 1, 1, 1])).then(|| "CH".to_string())
+            }
+        }
+
+        let ac = AccessController::new(AccessPolicy::AllowAll, []);
+        assert!(!ac.uses_client_country());
+        let ac = AccessController::new(
+            AccessPolicy::AllowAll,
+            [AccessRuleBuilder::new()
+                .client_country(ValueCountry::new(["CH"]))
+                .deny()
+                .build()],
+        );
+        assert!(ac.uses_client_country());
+        assert_eq!(ac.client_country(IpAddr::from([1, 1, 1, 1])), None);
+
+        let mut ac = ac.with_country_resolver(Arc::new(TestCountryResolver));
+        ac.initialize().await.unwrap();
+        assert_eq!(
+            ac.client_country(IpAddr::from([1, 1, 1, 1])).as_deref(),
+            Some("CH")
+        );
+        assert_eq!(ac.client_country(IpAddr::from([2, 2, 2, 2])), None);
+
+        let mut ac = AccessController::new(AccessPolicy::AllowAll, []);
+        ac.geoip = Some(GeoIpConfig {
+            database_path: "missing.mmdb".into(),
+        });
+        assert!(ac.initialize().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_goto_rule() {
+        let partner = IotaAddress::new([1; 32]);
+        let mut ac = AccessController::new(
+            AccessPolicy::AllowAll,
+            [
+                AccessRuleBuilder::new()
+                    .sender_address(partner)
+                    .goto("partner")
+                    .build(),
+                AccessRuleBuilder::new().deny().build(),
+                AccessRuleBuilder::new()
+                    .name("partner")
+                    .gas_budget(ValueNumber::LessThanOrEqual(1000))
+                    .allow()
+                    .build(),
+                AccessRuleBuilder::new()
+                    .name("partner-over-budget")
+                    .deny()
+                    .build(),
+            ],
+        );
+        ac.initialize().await.unwrap();
+
+        let tx = TransactionContext::default()
+            .with_sender_address(partner)
+            .with_gas_budget(500);
+        let details = ac.check_access_explained(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Allow);
+        assert_eq!(details.matched_rule.as_deref(), Some("partner"));
+        let outcomes: Vec<_> = details
+            .explanation
+            .iter()
+            .map(|rule| rule.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                RuleOutcome::Jumped,
+                RuleOutcome::NotEvaluated,
+                RuleOutcome::Decided,
+                RuleOutcome::NotEvaluated
+            ]
+        );
+
+        let tx = tx.with_gas_budget(5000);
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Deny);
+        assert_eq!(details.matched_rule.as_deref(), Some("partner-over-budget"));
+
+        let tx = TransactionContext::default().with_sender_address(IotaAddress::new([2; 32]));
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Deny);
+        assert_eq!(details.matched_rule.as_deref(), Some("#2"));
+
+        // The target must be a later rule
+        let mut ac = AccessController::new(
+            AccessPolicy::AllowAll,
+            [
+                AccessRuleBuilder::new().name("first").audit().build(),
+                AccessRuleBuilder::new().goto("first").build(),
+            ],
+        );
+        assert!(ac.initialize().await.is_err());
+
+        // The rules before the target must not continue into it
+        for before_target in [
+            AccessRuleBuilder::new().audit().build(),
+            AccessRuleBuilder::new()
+                .gas_budget(ValueNumber::LessThanOrEqual(1000))
+                .deny()
+                .build(),
+        ] {
+            let mut ac = AccessController::new(
+                AccessPolicy::AllowAll,
+                [
+                    AccessRuleBuilder::new()
+                        .sender_address(partner)
+                        .goto("partner")
+                        .build(),
+                    before_target,
+                    AccessRuleBuilder::new().name("partner").allow().build(),
+                ],
+            );
+            assert!(ac.initialize().await.is_err());
+        }
+        let mut ac = AccessController::new(
+            AccessPolicy::AllowAll,
+            [
+                AccessRuleBuilder::new()
+                    .sender_address(partner)
+                    .goto("partner")
+                    .build(),
+                AccessRuleBuilder::new().name("partner").allow().build(),
+            ],
+        );
+        assert!(ac.initialize().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_with_trace() {
+        let blocked_address = IotaAddress::new([1; 32]);
+        let rules = [
+            AccessRuleBuilder::new()
+                .name("audit-large")
+                .gas_budget(ValueNumber::GreaterThan(1000))
+                .audit()
+                .build(),
+            AccessRuleBuilder::new()
+                .name("blocked")
+                .sender_address(blocked_address)
+                .deny()
+                .build(),
+        ];
+        let ac = AccessController::new(
+            AccessPolicy::DenyAll,
+            rules
+                .clone()
+                .into_iter()
+                .chain([AccessRuleBuilder::new().allow().build()]),
+        );
+        let tx = TransactionContext::default()
+            .with_sender_address(IotaAddress::new([2; 32]))
+            .with_gas_budget(5000);
+
+        let trace = ac.evaluate_with_trace(&tx).await.unwrap();
+        assert_eq!(trace.decision, Decision::Allow);
+        assert_eq!(trace.matched_rule.as_deref(), Some("#3"));
+        assert_eq!(trace.audited_rules, vec!["audit-large".to_string()]);
+        assert_eq!(trace.outcome("audit-large"), Some(RuleOutcome::Audited));
+        assert_eq!(trace.outcome("blocked"), Some(RuleOutcome::NotMatched));
+        assert_eq!(
+            trace.predicate_matched("blocked", "sender-address"),
+            Some(false)
+        );
+        assert_eq!(trace.predicate_matched("blocked", "object-id"), None);
+        assert_eq!(
+            trace.evaluated_rules(),
+            vec!["audit-large", "blocked", "#3"]
+        );
+        assert!(!trace.decided_by_policy());
+
+        let ac = AccessController::new(AccessPolicy::DenyAll, rules);
+        let trace = ac
+            .evaluate_with_trace(&tx.with_sender_address(blocked_address))
+            .await
+            .unwrap();
+        assert_eq!(trace.decision, Decision::Deny);
+        assert_eq!(trace.outcome("blocked"), Some(RuleOutcome::Decided));
+
+        let trace = ac
+            .evaluate_with_trace(&TransactionContext::default())
+            .await
+            .unwrap();
+        assert_eq!(trace.decision, Decision::Deny);
+        assert!(trace.decided_by_policy());
+        assert_eq!(trace.outcome("#3"), None);
+    }
+
+    #[tokio::test]
+    async fn test_explained_decision() {
+        let sender_address = IotaAddress::new([1; 32]);
+        let ac = AccessController::new(
+            AccessPolicy::DenyAll,
+            [
+                AccessRuleBuilder::new()
+                    .name("audit-sender")
+                    .sender_address(sender_address)
+                    .audit()
+                    .build(),
+                AccessRuleBuilder::new()
+                    .name("large-budget")
+                    .sender_address(sender_address)
+                    .gas_budget(ValueNumber::GreaterThan(1000))
+                    .hold()
+                    .build(),
+                AccessRuleBuilder::new()
+                    .sender_address(sender_address)
+                    .allow()
+                    .build(),
+                AccessRuleBuilder::new().deny().build(),
+            ],
+        );
+        let tx = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(500);
+
+        let details = ac.check_access_explained(&tx).await.unwrap();
+        assert_eq!(details.decision, Decision::Allow);
+        assert_eq!(details.matched_rule.as_deref(), Some("#3"));
+        let sender_matched = PredicateMatch::new("sender-address", true);
+        assert_eq!(
+            details.explanation,
+            vec![
+                RuleExplanation {
+                    rule: "audit-sender".to_string(),
+                    predicates: vec![sender_matched.clone()],
+                    outcome: RuleOutcome::Audited,
+                },
+                RuleExplanation {
+                    rule: "large-budget".to_string(),
+                    predicates: vec![
+                        sender_matched.clone(),
+                        PredicateMatch::new("transaction-gas-budget", false),
+                    ],
+                    outcome: RuleOutcome::NotMatched,
+                },
+                RuleExplanation {
+                    rule: "#3".to_string(),
+                    predicates: vec![sender_matched],
+                    outcome: RuleOutcome::Decided,
+                },
+                RuleExplanation {
+                    rule: "#4".to_string(),
+                    predicates: vec![],
+                    outcome: RuleOutcome::NotEvaluated,
+                },
+            ]
+        );
+
+        // The decision isn't explained unless requested
+        let details = ac.check_access_with_details(&tx).await.unwrap();
+        assert!(details.explanation.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_releases_gas_usage() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(60),
+                ValueNumber::GreaterThanOrEqual(300),
+            ))
+            .deny()
+            .build();
+        let ac = AccessController::new(AccessPolicy::AllowAll, [rule]);
+        let ctx = TransactionContext::default()
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+        let other_ctx = TransactionContext {
+            transaction_digest: TransactionDigest::random(),
+            ..ctx.clone()
+        };
+
+        let details = ac.dry_run(&ctx, true).await.unwrap();
+        assert_eq!(details.decision, Decision::Allow);
+        assert_eq!(
+            details.explanation[0].outcome,
+            RuleOutcome::GasLimitNotMatched
+        );
+        assert_eq!(
+            ac.dry_run(&other_ctx, false).await.unwrap().decision,
+            Decision::Allow
+        );
+
+        // Nothing is counted, so there is nothing to confirm
+        assert!(
+            pending_confirmations::get(ctx.transaction_digest, &stats_tracker)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        assert_eq!(ac.check_access(&ctx).await.unwrap(), Decision::Allow);
+        // The gas usage counted for the executed transaction is kept by a dry run
+        assert_eq!(
+            ac.dry_run(&ctx, false).await.unwrap().decision,
+            Decision::Allow
+        );
+        assert!(
+            !pending_confirmations::get(ctx.transaction_digest, &stats_tracker)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(ac.check_access(&other_ctx).await.unwrap(), Decision::Deny);
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_cancelled_transaction() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(60),
+                ValueNumber::GreaterThanOrEqual(300),
+            ))
+            .deny()
+            .build();
+        let ac = AccessController::new(AccessPolicy::AllowAll, [rule])
+            .with_decision_cache(DecisionCacheConfig::new(std::time::Duration::from_secs(60)));
+        let ctx = TransactionContext::default()
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+        let other_ctx = TransactionContext {
+            transaction_digest: TransactionDigest::random(),
+            ..ctx.clone()
+        };
+
+        assert_eq!(ac.check_access(&ctx).await.unwrap(), Decision::Allow);
+        ac.confirm_transaction(
+            TransactionExecutionResult::new(ctx.transaction_digest),
+            &stats_tracker,
+        )
+        .await
+        .unwrap();
+
+        // The retried transaction is counted again instead of reusing the cached decision
+        assert_eq!(ac.check_access(&ctx).await.unwrap(), Decision::Allow);
+        assert!(
+            !pending_confirmations::get(ctx.transaction_digest, &stats_tracker)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(ac.check_access(&other_ctx).await.unwrap(), Decision::Deny);
+    }
+
     mod hook {
         use axum::http::{HeaderMap, HeaderValue};
         use url::Url;
 
         use crate::access_controller::hook::{
-            ExecuteTxOkResponse, SkippableDecision, TEST_ERROR_HEADER, TEST_RESPONSE_HEADER,
+            ExecuteTxOkResponse, HookCacheConfig, SkippableDecision, HOOK_CACHE_BYPASS_HEADER,
+            TEST_ERROR_HEADER, TEST_RESPONSE_HEADER,
         };
+        use crate::access_controller::HookCacheLookup;
 
         use super::*;
 
@@ -648,6 +1630,7 @@ rules:
             let response = ExecuteTxOkResponse {
                 decision,
                 user_message,
+                attestation: None,
             };
             let mut headers = HeaderMap::new();
             headers.insert(
@@ -681,6 +1664,166 @@ rules:
             ));
         }
 
+        #[tokio::test]
+        async fn test_hook_attestation() {
+            let hook_rule = AccessRuleBuilder::new()
+                .hook(Url::parse("https://example.net").unwrap())
+                .build();
+            let ac = AccessController::new(AccessPolicy::DenyAll, [hook_rule]);
+            let response = ExecuteTxOkResponse {
+                decision: SkippableDecision::Allow,
+                user_message: None,
+                attestation: Some("signed-by-policy-service".to_string()),
+            };
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                TEST_RESPONSE_HEADER,
+                HeaderValue::from_str(&serde_json::to_string(&response).unwrap()).unwrap(),
+            );
+            let ctx = TransactionContext::default().with_headers(headers);
+
+            let details = ac.check_access_with_details(&ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(
+                details.attestation.as_deref(),
+                Some("signed-by-policy-service")
+            );
+        }
+
+        #[tokio::test]
+        async fn test_hook_cache() {
+            let hook_rule = AccessRuleBuilder::new()
+                .name("hook")
+                .hook(Url::parse("https://example.net").unwrap())
+                .hook_cache(HookCacheConfig::new(std::time::Duration::from_secs(60)))
+                .build();
+            let ac = AccessController::new(AccessPolicy::DenyAll, [hook_rule]);
+            let lookup = |hit| {
+                vec![HookCacheLookup {
+                    rule: "hook".to_string(),
+                    hit,
+                }]
+            };
+            let sender_address = IotaAddress::new([1; 32]);
+            let allow_ctx = TransactionContext::default()
+                .with_sender_address(sender_address)
+                .with_headers(get_headers_with_test_response(
+                    SkippableDecision::Allow,
+                    None,
+                ));
+            let details = ac.check_access_with_details(&allow_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.hook_cache_lookups, lookup(false));
+
+            // The test hook denies without a test response, so the allow comes from the cache
+            let ctx = TransactionContext::default().with_sender_address(sender_address);
+            let details = ac.check_access_with_details(&ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.hook_cache_lookups, lookup(true));
+
+            let other_sender_ctx =
+                TransactionContext::default().with_sender_address(IotaAddress::new([2; 32]));
+            let details = ac
+                .check_access_with_details(&other_sender_ctx)
+                .await
+                .unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.hook_cache_lookups, lookup(false));
+
+            // Bypassing the cache replaces the cached response
+            let mut headers = HeaderMap::new();
+            headers.insert(HOOK_CACHE_BYPASS_HEADER, HeaderValue::from_static("true"));
+            let bypass_ctx = TransactionContext::default()
+                .with_sender_address(sender_address)
+                .with_headers(headers);
+            let details = ac.check_access_with_details(&bypass_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.hook_cache_lookups, lookup(false));
+            let details = ac.check_access_with_details(&ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.hook_cache_lookups, lookup(true));
+        }
+
+        #[tokio::test]
+        async fn test_decision_cache_per_digest() {
+            let hook_rule = AccessRuleBuilder::new()
+                .name("hook")
+                .hook(Url::parse("https://example.net").unwrap())
+                .build();
+            let ac = AccessController::new(AccessPolicy::DenyAll, [hook_rule])
+                .with_decision_cache(DecisionCacheConfig::new(std::time::Duration::from_secs(60)));
+            let allow_ctx = TransactionContext::default().with_headers(
+                get_headers_with_test_response(SkippableDecision::Allow, None),
+            );
+            let details = ac.check_access_with_details(&allow_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.matched_rule.as_deref(), Some("hook"));
+
+            // The retried transaction gets the same decision without calling the hook
+            let error_ctx = TransactionContext::default().with_headers({
+                let mut headers = HeaderMap::new();
+                headers.insert(TEST_ERROR_HEADER, HeaderValue::from_static("hook failed"));
+                headers
+            });
+            let details = ac.check_access_with_details(&error_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.matched_rule.as_deref(), Some("hook"));
+
+            let other_ctx = TransactionContext {
+                transaction_digest: TransactionDigest::random(),
+                ..error_ctx
+            };
+            assert!(ac.check_access_with_details(&other_ctx).await.is_err());
+        }
+
+        #[tokio::test]
+        async fn test_parallel_hooks_keep_rule_order() {
+            let hook_rule = |name: &str| {
+                AccessRuleBuilder::new()
+                    .name(name)
+                    .hook(Url::parse("https://example.net").unwrap())
+                    .build()
+            };
+            let ac = AccessController::new(
+                AccessPolicy::DenyAll,
+                [
+                    hook_rule("first"),
+                    AccessRuleBuilder::new()
+                        .sender_address(IotaAddress::new([2; 32]))
+                        .hook(Url::parse("https://example.net").unwrap())
+                        .build(),
+                    hook_rule("third"),
+                    AccessRuleBuilder::new().name("allow").allow().build(),
+                    hook_rule("fifth"),
+                ],
+            )
+            .with_parallel_hooks(true);
+
+            let deny_ctx = TransactionContext::default().with_headers(
+                get_headers_with_test_response(SkippableDecision::Deny, None),
+            );
+            let details = ac.check_access_with_details(&deny_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.matched_rule.as_deref(), Some("first"));
+
+            let no_decision_ctx = TransactionContext::default().with_headers(
+                get_headers_with_test_response(SkippableDecision::NoDecision, None),
+            );
+            let details = ac
+                .check_access_with_details(&no_decision_ctx)
+                .await
+                .unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.matched_rule.as_deref(), Some("allow"));
+
+            let error_ctx = TransactionContext::default().with_headers({
+                let mut headers = HeaderMap::new();
+                headers.insert(TEST_ERROR_HEADER, HeaderValue::from_static("hook failed"));
+                headers
+            });
+            assert!(ac.check_access_with_details(&error_ctx).await.is_err());
+        }
+
         #[tokio::test]
         async fn test_hook_can_deny_tx() {
             let hook_rule = AccessRuleBuilder::new()
@@ -704,6 +1847,65 @@ rules:
             ));
         }
 
+        #[tokio::test]
+        async fn test_hook_deny_details() {
+            let allow_rule = AccessRuleBuilder::new()
+                .sender_address(IotaAddress::new([1; 32]))
+                .allow()
+                .build();
+            let hook_rule = AccessRuleBuilder::new()
+                .name("hook")
+                .hook(Url::parse("https://example.net").unwrap())
+                .build();
+            let deny_ctx =
+                TransactionContext::default().with_headers(get_headers_with_test_response(
+                    SkippableDecision::Deny,
+                    Some("daily quota used".to_string()),
+                ));
+
+            let ac = AccessController::new(AccessPolicy::AllowAll, [allow_rule, hook_rule]);
+            let details = ac.check_access_with_details(&deny_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.matched_rule.as_deref(), Some("hook"));
+            assert_eq!(details.user_message.as_deref(), Some("daily quota used"));
+
+            // Unnamed rules are referred to by their position
+            let ac = AccessController::new(
+                AccessPolicy::AllowAll,
+                [AccessRuleBuilder::new().deny().build()],
+            );
+            let details = ac.check_access_with_details(&deny_ctx).await.unwrap();
+            assert_eq!(details.matched_rule.as_deref(), Some("#1"));
+            assert_eq!(details.user_message, None);
+        }
+
+        #[tokio::test]
+        async fn test_rule_deny_message() {
+            let message = "this package is not eligible for sponsorship";
+            let deny_rule = AccessRuleBuilder::new()
+                .deny()
+                .deny_message(message)
+                .build();
+            let hook_rule = AccessRuleBuilder::new()
+                .hook(Url::parse("https://example.net").unwrap())
+                .deny_message(message)
+                .build();
+            let allow_ctx = TransactionContext::default().with_headers(
+                get_headers_with_test_response(SkippableDecision::Allow, None),
+            );
+
+            let ac = AccessController::new(AccessPolicy::AllowAll, [deny_rule]);
+            let details = ac.check_access_with_details(&allow_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Deny);
+            assert_eq!(details.deny_message.as_deref(), Some(message));
+
+            // The message is only used when the rule denies
+            let ac = AccessController::new(AccessPolicy::DenyAll, [hook_rule]);
+            let details = ac.check_access_with_details(&allow_ctx).await.unwrap();
+            assert_eq!(details.decision, Decision::Allow);
+            assert_eq!(details.deny_message, None);
+        }
+
         #[tokio::test]
         async fn test_hook_can_forward_decision_to_next_rule() {
             let hook_rule = AccessRuleBuilder::new()
@@ -0,0 +1,273 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The gas budget of a transaction is counted by the gas usage limits when its access is checked,
+//! and replaced with the gas actually used once it's executed. The pending confirmations are kept
+//! in the stats storage, so that the counters can be reconciled after a crash in between.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use iota_types::digests::TransactionDigest;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use super::rule::GasUsageConfirmationRequest;
+use crate::{iota_client::IotaClient, rpc::rpc_types::MAX_DURATION_S, tracker::StatsTracker};
+
+/// Pending confirmations older than this are no longer expected to be confirmed by the replica
+/// that executed the transaction, since the gas reservation expired.
+pub const PENDING_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(MAX_DURATION_S);
+
+#[derive(Serialize, Deserialize)]
+struct PendingConfirmation {
+    created_at_secs: u64,
+    requests: Vec<GasUsageConfirmationRequest>,
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Stores the confirmation requests of the transaction until it's confirmed.
+pub async fn store(
+    transaction_digest: TransactionDigest,
+    requests: Vec<GasUsageConfirmationRequest>,
+    stats_tracker: &StatsTracker,
+) -> Result<()> {
+    let data = serde_json::to_string(&PendingConfirmation {
+        created_at_secs: unix_secs(),
+        requests,
+    })?;
+    stats_tracker
+        .put_pending_confirmation(&transaction_digest.to_string(), &data)
+        .await
+        .context("Failed to store the pending confirmation")
+}
+
+/// Returns the confirmation requests stored for the transaction, i.e. the gas usage already
+/// counted for it when its access was checked.
+pub async fn get(
+    transaction_digest: TransactionDigest,
+    stats_tracker: &StatsTracker,
+) -> Result<Vec<GasUsageConfirmationRequest>> {
+    let Some(data) = stats_tracker
+        .get_pending_confirmation(&transaction_digest.to_string())
+        .await
+        .context("Failed to get the pending confirmation")?
+    else {
+        return Ok(vec![]);
+    };
+    let pending: PendingConfirmation =
+        serde_json::from_str(&data).context("Failed to parse the pending confirmation")?;
+    Ok(pending.requests)
+}
+
+/// Removes the confirmation requests of the transaction and applies the gas usage to them.
+/// Nothing happens if they were already confirmed.
+pub async fn confirm(
+    transaction_digest: TransactionDigest,
+    gas_usage: Option<u64>,
+    stats_tracker: &StatsTracker,
+) -> Result<()> {
+    let Some(data) = stats_tracker
+        .take_pending_confirmation(&transaction_digest.to_string())
+        .await
+        .context("Failed to take the pending confirmation")?
+    else {
+        return Ok(());
+    };
+    let pending: PendingConfirmation =
+        serde_json::from_str(&data).context("Failed to parse the pending confirmation")?;
+    apply(
+        transaction_digest,
+        pending.requests,
+        gas_usage,
+        stats_tracker,
+    )
+    .await
+}
+
+/// Replaces the reserved gas usage with the real one, or releases it if the transaction wasn't
+/// executed.
+async fn apply(
+    transaction_digest: TransactionDigest,
+    requests: Vec<GasUsageConfirmationRequest>,
+    gas_usage: Option<u64>,
+    stats_tracker: &StatsTracker,
+) -> Result<()> {
+    for req in requests {
+        let diff = if let Some(real_gas_usage) = gas_usage {
+            let reserved_gas_usage = req.gas_usage;
+            let diff = reserved_gas_usage as i64 - real_gas_usage as i64;
+            debug!("Transaction with id: {transaction_digest} confirmed, reserved gas usage: {reserved_gas_usage}, real gas usage: {real_gas_usage}, diff: {diff}");
+            diff
+        } else {
+            debug!("Transaction with id: {transaction_digest} confirmed, but no gas usage was provided");
+            req.gas_usage as i64
+        };
+        stats_tracker
+            .update_aggr(req.rule_meta, &req.aggregate, diff * -1)
+            .await
+            .context("Failed to update aggregate while when confirming transactions")?;
+    }
+    Ok(())
+}
+
+/// Confirms the transactions left pending by a previous run, with the gas used reported by the
+/// fullnode. Only the confirmations created before `started_at_secs` are handled, once they are
+/// older than [`PENDING_CONFIRMATION_TIMEOUT`], so that the transactions still being executed by
+/// other replicas sharing the storage are left alone.
+pub async fn reconcile(
+    stats_tracker: &StatsTracker,
+    iota_client: &IotaClient,
+    started_at_secs: u64,
+) -> Result<()> {
+    let pending = stats_tracker
+        .get_pending_confirmations()
+        .await
+        .context("Failed to list the pending confirmations")?;
+    let mut reconciled = 0;
+    for (id, data) in pending {
+        let pending: PendingConfirmation = match serde_json::from_str(&data) {
+            Ok(pending) => pending,
+            Err(err) => {
+                error!(
+                    "Dropping the invalid pending confirmation {}: {:?}",
+                    id, err
+                );
+                stats_tracker.take_pending_confirmation(&id).await?;
+                continue;
+            }
+        };
+        if pending.created_at_secs >= started_at_secs
+            || pending.created_at_secs + PENDING_CONFIRMATION_TIMEOUT.as_secs() > unix_secs()
+        {
+            continue;
+        }
+        let transaction_digest: TransactionDigest = match id.parse() {
+            Ok(digest) => digest,
+            Err(err) => {
+                error!(
+                    "Dropping the pending confirmation with invalid digest {}: {:?}",
+                    id, err
+                );
+                stats_tracker.take_pending_confirmation(&id).await?;
+                continue;
+            }
+        };
+        // The entry is left in place if the fullnode can't be queried, to retry on the next start.
+        let gas_usage = match iota_client
+            .get_transaction_gas_used(transaction_digest)
+            .await
+        {
+            Ok(gas_usage) => gas_usage,
+            Err(err) => {
+                error!(
+                    "Failed to get the gas used by transaction {}: {:?}",
+                    transaction_digest, err
+                );
+                continue;
+            }
+        };
+        confirm(transaction_digest, gas_usage, stats_tracker).await?;
+        reconciled += 1;
+    }
+    info!("Reconciled {} pending gas usage confirmations", reconciled);
+    Ok(())
+}
+
+/// Runs [`reconcile`] once the confirmations left by a previous run timed out.
+pub fn spawn_reconciliation(stats_tracker: StatsTracker, iota_client: IotaClient) {
+    let started_at_secs = unix_secs();
+    tokio::spawn(async move {
+        tokio::time::sleep(PENDING_CONFIRMATION_TIMEOUT).await;
+        if let Err(err) = reconcile(&stats_tracker, &iota_client, started_at_secs).await {
+            error!("Failed to reconcile the pending confirmations: {:?}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+    use crate::{
+        test_env::{new_stats_tracker_for_testing, random_address},
+        tracker::stats_tracker_storage::{Aggregate, AggregateType},
+    };
+
+    fn confirmation_request(gas_usage: u64) -> GasUsageConfirmationRequest {
+        let mut rule_meta = Map::new();
+        rule_meta.insert(
+            "sender_address".to_string(),
+            json!(random_address().to_string()),
+        );
+        GasUsageConfirmationRequest {
+            rule_meta,
+            aggregate: Aggregate::with_name("gas_usage")
+                .with_aggr_type(AggregateType::Sum)
+                .with_window(Duration::from_secs(60)),
+            gas_usage,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_confirm_stored_requests() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let request = confirmation_request(100);
+        let digest = TransactionDigest::random();
+        stats_tracker
+            .update_aggr(request.rule_meta.clone(), &request.aggregate, 100)
+            .await
+            .unwrap();
+        store(digest, vec![request.clone()], &stats_tracker)
+            .await
+            .unwrap();
+
+        confirm(digest, Some(30), &stats_tracker).await.unwrap();
+        let usage = stats_tracker
+            .get_aggr(request.rule_meta.clone(), &request.aggregate)
+            .await
+            .unwrap();
+        assert_eq!(usage.value, 30);
+
+        // A transaction is confirmed only once
+        confirm(digest, None, &stats_tracker).await.unwrap();
+        let usage = stats_tracker
+            .get_aggr(request.rule_meta, &request.aggregate)
+            .await
+            .unwrap();
+        assert_eq!(usage.value, 30);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_without_gas_usage_releases_the_budget() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let request = confirmation_request(100);
+        let digest = TransactionDigest::random();
+        stats_tracker
+            .update_aggr(request.rule_meta.clone(), &request.aggregate, 100)
+            .await
+            .unwrap();
+        store(digest, vec![request.clone()], &stats_tracker)
+            .await
+            .unwrap();
+
+        confirm(digest, None, &stats_tracker).await.unwrap();
+        let usage = stats_tracker
+            .get_aggr(request.rule_meta, &request.aggregate)
+            .await
+            .unwrap();
+        assert_eq!(usage.value, 0);
+        assert!(stats_tracker
+            .get_pending_confirmations()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,10 +1,13 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// The AccessPolicy enum represents the access policy of the gas station.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord, Default)]
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, PartialOrd, Eq, Ord, Default,
+)]
 #[serde(rename_all = "kebab-case")]
 pub enum AccessPolicy {
     #[default]
@@ -3,15 +3,23 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::access_controller::hook::HookAction;
+use crate::access_controller::hook::{HookAction, NotifyAction};
 
 /// Action enum represents the action of the access controller. It can be either Allow or Deny.
+/// Audit only records that the rule matched, and the evaluation continues with the next rule.
+/// Hold keeps the transaction for manual approval. Notify sends the transaction to a webhook in the
+/// background, and the evaluation continues as well. Goto continues the evaluation with the next
+/// rule of the given name, skipping the rules in between.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum Action {
     #[default]
     Allow,
     Deny,
+    Audit,
+    Hold,
+    Notify(NotifyAction),
+    Goto(String),
     #[serde(untagged)]
     HookAction(HookAction),
 }
@@ -20,13 +28,25 @@ pub enum Action {
 mod test {
     use url::Url;
 
-    use super::{Action, HookAction};
+    use super::{Action, HookAction, NotifyAction};
 
     #[test]
     fn test_deserialize_valid_actions() {
         let values_and_expected = vec![
             (r#""allow""#, Action::Allow),
             (r#""deny""#, Action::Deny),
+            (r#""audit""#, Action::Audit),
+            (r#""hold""#, Action::Hold),
+            (
+                r#"{"notify": "http://example.org/events"}"#,
+                Action::Notify(NotifyAction(
+                    Url::parse("http://example.org/events").unwrap(),
+                )),
+            ),
+            (
+                r#"{"goto": "partner-a"}"#,
+                Action::Goto("partner-a".to_string()),
+            ),
             (
                 r#""http://example.org/""#,
                 Action::HookAction(HookAction(Url::parse("http://example.org/").unwrap())),
@@ -55,6 +75,18 @@ mod test {
         let values_and_expected = vec![
             (Action::Allow, r#""allow""#),
             (Action::Deny, r#""deny""#),
+            (Action::Audit, r#""audit""#),
+            (Action::Hold, r#""hold""#),
+            (
+                Action::Notify(NotifyAction(
+                    Url::parse("http://example.org/events").unwrap(),
+                )),
+                r#"{"notify":"http://example.org/events"}"#,
+            ),
+            (
+                Action::Goto("partner-a".to_string()),
+                r#"{"goto":"partner-a"}"#,
+            ),
             (
                 Action::HookAction(HookAction(Url::parse("http://example.org/").unwrap())),
                 r#""http://example.org/""#,
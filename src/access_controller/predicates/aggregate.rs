@@ -14,6 +14,9 @@ pub struct ValueAggregate {
     pub value: ValueNumber<u64>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub count_by: Vec<LimitBy>,
+    // Not serialized when fixed, so that the keys of the existing counters don't change.
+    #[serde(default, skip_serializing_if = "WindowMode::is_fixed")]
+    pub window_mode: WindowMode,
 }
 
 impl ValueAggregate {
@@ -22,6 +25,7 @@ impl ValueAggregate {
             window,
             value: limit,
             count_by: vec![],
+            window_mode: WindowMode::Fixed,
         }
     }
 
@@ -29,6 +33,30 @@ impl ValueAggregate {
         self.count_by = group_by;
         self
     }
+
+    pub fn with_window_mode(mut self, window_mode: WindowMode) -> Self {
+        self.window_mode = window_mode;
+        self
+    }
+}
+
+/// How the window of an aggregate moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowMode {
+    /// The window starts with the first counted value and the aggregate is reset to 0 once it
+    /// elapsed.
+    #[default]
+    Fixed,
+    /// The aggregate covers the last window. Values leave it gradually, as they get older than
+    /// the window.
+    Sliding,
+}
+
+impl WindowMode {
+    fn is_fixed(&self) -> bool {
+        *self == WindowMode::Fixed
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,7 +73,7 @@ impl ToString for LimitBy {
     }
 }
 
-mod serde_duration {
+pub(crate) mod serde_duration {
     use serde::Deserialize;
 
     fn parse_duration(s: &str) -> std::time::Duration {
@@ -71,7 +99,7 @@ mod serde_duration {
 
 #[cfg(test)]
 mod test {
-    use crate::access_controller::predicates::{ValueAggregate, ValueNumber};
+    use crate::access_controller::predicates::{ValueAggregate, ValueNumber, WindowMode};
 
     #[test]
     fn test_deserialize_value_aggregate() {
@@ -94,4 +122,15 @@ mod test {
         let json = serde_json::to_string(&value_aggregate).unwrap();
         assert_eq!(json, r#"{"window":"1h 30m","value":">100"}"#);
     }
+
+    #[test]
+    fn test_window_mode() {
+        let json = r#"{"window":"1h","value": "<100", "window-mode": "sliding"}"#;
+        let value_aggregate: ValueAggregate = serde_json::from_str(json).unwrap();
+        assert_eq!(value_aggregate.window_mode, WindowMode::Sliding);
+        assert_eq!(
+            serde_json::to_string(&value_aggregate).unwrap(),
+            r#"{"window":"1h","value":"<100","window-mode":"sliding"}"#
+        );
+    }
 }
@@ -0,0 +1,128 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+
+use iota_types::{TypeTag, IOTA_FRAMEWORK_ADDRESS};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Serialize,
+};
+
+/// The ValueCoinType represents a list of coin types, e.g. `0x2::iota::IOTA`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueCoinType(pub Vec<TypeTag>);
+
+impl ValueCoinType {
+    pub fn new(coin_types: impl IntoIterator<Item = TypeTag>) -> Self {
+        Self(coin_types.into_iter().collect())
+    }
+
+    pub fn includes_any<'a>(&self, coin_types: impl IntoIterator<Item = &'a TypeTag>) -> bool {
+        coin_types
+            .into_iter()
+            .any(|coin_type| self.0.contains(coin_type))
+    }
+}
+
+/// Returns `T` if the type is `Coin<T>` or `Balance<T>`.
+pub fn coin_inner_type(type_tag: &TypeTag) -> Option<&TypeTag> {
+    let TypeTag::Struct(struct_tag) = type_tag else {
+        return None;
+    };
+    let is_coin = struct_tag.address == IOTA_FRAMEWORK_ADDRESS
+        && matches!(
+            (struct_tag.module.as_str(), struct_tag.name.as_str()),
+            ("coin", "Coin") | ("balance", "Balance")
+        );
+    if is_coin {
+        struct_tag.type_params.first()
+    } else {
+        None
+    }
+}
+
+impl Serialize for ValueCoinType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let coin_types: Vec<_> = self
+            .0
+            .iter()
+            .map(|coin_type| coin_type.to_canonical_string(true))
+            .collect();
+        coin_types.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueCoinType {
+    fn deserialize<D>(deserializer: D) -> Result<ValueCoinType, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        struct ValueCoinTypeVisitor;
+
+        impl<'de> Visitor<'de> for ValueCoinTypeVisitor {
+            type Value = ValueCoinType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a coin type or a list of coin types")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let coin_type = value.parse().map_err(E::custom)?;
+                Ok(ValueCoinType(vec![coin_type]))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let coin_types: Vec<String> =
+                    Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+                coin_types
+                    .iter()
+                    .map(|coin_type| coin_type.parse().map_err(de::Error::custom))
+                    .collect::<Result<_, _>>()
+                    .map(ValueCoinType)
+            }
+        }
+
+        deserializer.deserialize_any(ValueCoinTypeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use iota_types::gas_coin::GAS;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_value_coin_type() {
+        let value: ValueCoinType = serde_yaml::from_str("0x2::iota::IOTA").unwrap();
+        assert_eq!(value, ValueCoinType::new([GAS::type_tag()]));
+
+        let value: ValueCoinType =
+            serde_yaml::from_str("[0x2::iota::IOTA, '0x3::token::TOKEN']").unwrap();
+        assert_eq!(value.0.len(), 2);
+        assert!(value.includes_any(&[GAS::type_tag()]));
+
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<ValueCoinType>(&json).unwrap(), value);
+
+        assert!(serde_yaml::from_str::<ValueCoinType>("not a type").is_err());
+    }
+
+    #[test]
+    fn test_coin_inner_type() {
+        let coin: TypeTag = "0x2::coin::Coin<0x3::token::TOKEN>".parse().unwrap();
+        let token: TypeTag = "0x3::token::TOKEN".parse().unwrap();
+        assert_eq!(coin_inner_type(&coin), Some(&token));
+        assert_eq!(coin_inner_type(&token), None);
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Matches the clients whose country couldn't be resolved.
+pub const UNKNOWN_COUNTRY: &str = "unknown";
+
+/// The ValueCountry represents a list of ISO 3166-1 alpha-2 country codes, e.g. `CH`, and
+/// possibly `unknown`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValueCountry(pub Vec<String>);
+
+impl ValueCountry {
+    pub fn new(countries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self(countries.into_iter().map(Into::into).collect())
+    }
+
+    pub fn includes(&self, country: Option<&str>) -> bool {
+        let country = country.unwrap_or(UNKNOWN_COUNTRY);
+        self.0.iter().any(|listed| listed == country)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueCountry {
+    fn deserialize<D>(deserializer: D) -> Result<ValueCountry, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Countries {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let countries = match Countries::deserialize(deserializer)? {
+            Countries::One(country) => vec![country],
+            Countries::Many(countries) => countries,
+        };
+        countries
+            .into_iter()
+            .map(|country| {
+                if country.eq_ignore_ascii_case(UNKNOWN_COUNTRY) {
+                    Ok(UNKNOWN_COUNTRY.to_string())
+                } else if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+                    Ok(country.to_ascii_uppercase())
+                } else {
+                    Err(de::Error::custom(format!(
+                        "Invalid country code: {}, expected an ISO 3166-1 alpha-2 code or '{}'",
+                        country, UNKNOWN_COUNTRY
+                    )))
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(ValueCountry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_value_country() {
+        let value: ValueCountry = serde_yaml::from_str("[ch, DE, Unknown]").unwrap();
+        assert_eq!(value, ValueCountry::new(["CH", "DE", UNKNOWN_COUNTRY]));
+        assert!(value.includes(Some("CH")));
+        assert!(value.includes(None));
+        assert!(!value.includes(Some("FR")));
+
+        let value: ValueCountry = serde_yaml::from_str("FR").unwrap();
+        assert!(value.includes(Some("FR")));
+        assert!(!value.includes(None));
+
+        assert!(serde_yaml::from_str::<ValueCountry>("France").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::bail;
+use axum::http::{HeaderMap, HeaderName};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The ValueHeader matches a header of the `execute_tx` request, e.g. the ID of the calling
+/// application set by an API gateway.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ValueHeader {
+    /// Name of the header, case-insensitive.
+    pub name: String,
+    /// Matches if the header has any of the values. If empty, the header only needs to be sent.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_values",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub value: Vec<String>,
+}
+
+impl ValueHeader {
+    pub fn new(
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            value: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if HeaderName::from_bytes(self.name.as_bytes()).is_err() {
+            bail!("Invalid header name: {}", self.name);
+        }
+        Ok(())
+    }
+
+    /// Returns true if any value of the header is accepted. The values are compared exactly.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        headers.get_all(self.name.as_str()).iter().any(|value| {
+            self.value.is_empty()
+                || value
+                    .to_str()
+                    .is_ok_and(|value| self.value.iter().any(|accepted| accepted == value))
+        })
+    }
+}
+
+/// Accepts a single value or a list of values.
+fn deserialize_values<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Values {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Values::deserialize(deserializer)? {
+        Values::One(value) => vec![value],
+        Values::Many(values) => values,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_header_matches() {
+        let header: ValueHeader =
+            serde_yaml::from_str("name: X-App-Id\nvalue: [wallet, game]").unwrap();
+        assert_eq!(header, ValueHeader::new("X-App-Id", ["wallet", "game"]));
+        header.validate().unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert!(!header.matches(&headers));
+        headers.insert("x-app-id", HeaderValue::from_static("game"));
+        assert!(header.matches(&headers));
+        headers.insert("x-app-id", HeaderValue::from_static("Game"));
+        assert!(!header.matches(&headers));
+
+        let present: ValueHeader = serde_yaml::from_str("name: x-app-id").unwrap();
+        assert!(present.matches(&headers));
+        assert!(!present.matches(&HeaderMap::new()));
+
+        let single: ValueHeader = serde_yaml::from_str("name: x-app-id\nvalue: Game").unwrap();
+        assert_eq!(single.value, vec!["Game".to_string()]);
+        assert!(single.matches(&headers));
+
+        assert!(ValueHeader::new("x app", ["a"]).validate().is_err());
+        assert!(serde_yaml::from_str::<ValueHeader>("name: x-app-id\nvalues: a").is_err());
+    }
+}
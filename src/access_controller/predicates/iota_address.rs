@@ -1,15 +1,18 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::fmt;
+use std::{collections::HashSet, fmt};
 
-use fastcrypto::encoding::decode_bytes_hex;
+use anyhow::Context;
+use fastcrypto::encoding::{decode_bytes_hex, Encoding, Hex};
 use iota_types::base_types::IotaAddress;
 use serde::{
     de::{self, Visitor},
     Deserialize, Serialize,
 };
 
+use super::source::Location;
+
 impl ValueIotaAddress {
     pub fn new(addresses: impl IntoIterator<Item = IotaAddress>) -> Self {
         let addresses: Vec<_> = addresses.into_iter().collect();
@@ -25,9 +28,22 @@ impl ValueIotaAddress {
     pub fn includes(&self, address: &IotaAddress) -> bool {
         match self {
             ValueIotaAddress::All => true,
+            ValueIotaAddress::None => false,
             ValueIotaAddress::Single(single) => single == address,
             ValueIotaAddress::List(list) => list.contains(address),
+            ValueIotaAddress::Prefix(prefix) => {
+                Hex::encode(address.to_inner()).starts_with(prefix.as_str())
+            }
+            ValueIotaAddress::External(external) => external.addresses.contains(address),
+        }
+    }
+
+    /// Loads the addresses of an external list. Does nothing for the other values.
+    pub async fn load(&mut self) -> Result<(), anyhow::Error> {
+        if let ValueIotaAddress::External(external) = self {
+            external.load().await?;
         }
+        Ok(())
     }
 
     pub fn includes_any<'a>(&self, addresses: impl IntoIterator<Item = &'a IotaAddress>) -> bool {
@@ -35,13 +51,81 @@ impl ValueIotaAddress {
     }
 }
 
-/// The ValueIotaAddress enum represents a single IotaAddress, a list of IotaAddress or all IotaAddresses.
+/// The ValueIotaAddress enum represents a single IotaAddress, a list of IotaAddress, all
+/// IotaAddresses, none of them, or the IotaAddresses starting with a prefix.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum ValueIotaAddress {
     #[default]
     All,
+    None,
     Single(IotaAddress),
     List(Vec<IotaAddress>),
+    /// Lowercase hex digits the address starts with, without `0x`, e.g. `02ab` for `0x02ab*`.
+    Prefix(String),
+    External(ExternalAddresses),
+}
+
+/// A list of addresses loaded from a location, so that long lists don't have to be inlined in the
+/// configuration. The list is empty until it's loaded.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExternalAddresses {
+    pub location: Location,
+    pub addresses: HashSet<IotaAddress>,
+}
+
+impl ExternalAddresses {
+    pub fn new(location: Location) -> Self {
+        Self {
+            location,
+            addresses: HashSet::new(),
+        }
+    }
+
+    /// Fetches the list from the location. The list is either a YAML or JSON list of addresses,
+    /// or one address per line.
+    pub async fn load(&mut self) -> Result<(), anyhow::Error> {
+        let data = self.location.fetch_string().await?;
+        self.addresses = parse_address_list(&data)
+            .with_context(|| format!("invalid address list from {}", self.location.to_string()))?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ExternalAddresses {
+    // The list may have thousands of addresses
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExternalAddresses")
+            .field("location", &self.location)
+            .field("address_count", &self.addresses.len())
+            .finish()
+    }
+}
+
+fn parse_address_list(data: &str) -> Result<HashSet<IotaAddress>, anyhow::Error> {
+    if let Ok(addresses) = serde_yaml::from_str::<Vec<IotaAddress>>(data) {
+        return Ok(addresses.into_iter().collect());
+    }
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            decode_bytes_hex(line)
+                .map_err(|err| anyhow::anyhow!("invalid address {}: {}", line, err))
+        })
+        .collect()
+}
+
+/// Parses a prefix pattern like `0x02ab*`.
+fn parse_prefix(value: &str) -> Option<Result<String, String>> {
+    let prefix = value.strip_suffix('*')?;
+    let prefix = prefix.strip_prefix("0x").unwrap_or(prefix).to_lowercase();
+    if prefix.is_empty()
+        || prefix.len() > IotaAddress::LENGTH * 2
+        || !prefix.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Some(Err(format!("Invalid address prefix: {}", value)));
+    }
+    Some(Ok(prefix))
 }
 
 impl Serialize for ValueIotaAddress {
@@ -51,8 +135,11 @@ impl Serialize for ValueIotaAddress {
     {
         match self {
             ValueIotaAddress::All => serializer.serialize_str("*"),
+            ValueIotaAddress::None => serializer.serialize_str("none"),
             ValueIotaAddress::Single(address) => address.serialize(serializer),
             ValueIotaAddress::List(addresses) => addresses.serialize(serializer),
+            ValueIotaAddress::Prefix(prefix) => serializer.serialize_str(&format!("0x{}*", prefix)),
+            ValueIotaAddress::External(external) => external.location.serialize(serializer),
         }
     }
 }
@@ -68,7 +155,9 @@ impl<'de> Deserialize<'de> for ValueIotaAddress {
             type Value = ValueIotaAddress;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a string, a single IotaAddress, or a list of IotaAddresses")
+                formatter.write_str(
+                    "'*', 'none', an address prefix like '0x02ab*', a single IotaAddress, a list of IotaAddresses, or a location",
+                )
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -77,6 +166,10 @@ impl<'de> Deserialize<'de> for ValueIotaAddress {
             {
                 if value == "*" {
                     Ok(ValueIotaAddress::All)
+                } else if value == "none" {
+                    Ok(ValueIotaAddress::None)
+                } else if let Some(prefix) = parse_prefix(value) {
+                    prefix.map(ValueIotaAddress::Prefix).map_err(E::custom)
                 } else {
                     let from_hex: IotaAddress = decode_bytes_hex(value).map_err(E::custom)?;
                     Ok(ValueIotaAddress::Single(from_hex))
@@ -90,6 +183,14 @@ impl<'de> Deserialize<'de> for ValueIotaAddress {
                 let addresses = Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
                 Ok(ValueIotaAddress::List(addresses))
             }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let location = Location::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(ValueIotaAddress::External(ExternalAddresses::new(location)))
+            }
         }
 
         deserializer.deserialize_any(ValueIotaAddressVisitor)
@@ -109,7 +210,7 @@ where
 mod test {
     use iota_types::base_types::IotaAddress;
 
-    use super::ValueIotaAddress;
+    use super::{parse_address_list, ValueIotaAddress};
 
     #[test]
     fn test_include_from_one() {
@@ -166,6 +267,58 @@ mod test {
         assert_eq!(value_iota_address, deserialized_value_iota_address);
     }
 
+    #[test]
+    fn test_include_prefix_and_none() {
+        let value_iota_address: ValueIotaAddress = serde_yaml::from_str("'0x02AB*'").unwrap();
+        assert_eq!(
+            value_iota_address,
+            ValueIotaAddress::Prefix("02ab".to_string())
+        );
+
+        let mut bytes = [0; 32];
+        bytes[0] = 0x02;
+        bytes[1] = 0xab;
+        assert!(value_iota_address.includes(&IotaAddress::new(bytes)));
+        assert!(!value_iota_address.includes(&IotaAddress::new([2; 32])));
+        assert_eq!(
+            serde_yaml::to_string(&value_iota_address).unwrap(),
+            "---\n\"0x02ab*\"\n"
+        );
+
+        let none: ValueIotaAddress = serde_yaml::from_str("none").unwrap();
+        assert_eq!(none, ValueIotaAddress::None);
+        assert!(!none.includes(&IotaAddress::new([1; 32])));
+
+        assert!(serde_yaml::from_str::<ValueIotaAddress>("'0x02xy*'").is_err());
+        assert!(serde_yaml::from_str::<ValueIotaAddress>("'0x*'").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_external_addresses() {
+        let iota_address1 = IotaAddress::new([1; 32]);
+        let iota_address2 = IotaAddress::new([2; 32]);
+        let mut value_iota_address: ValueIotaAddress = serde_yaml::from_str(
+            "location-type: memory
+data: |
+  # partners
+  0x0101010101010101010101010101010101010101010101010101010101010101
+  0x0202020202020202020202020202020202020202020202020202020202020202
+",
+        )
+        .unwrap();
+        // Nothing is included until the list is loaded
+        assert!(!value_iota_address.includes(&iota_address1));
+
+        value_iota_address.load().await.unwrap();
+        assert!(value_iota_address.includes(&iota_address1));
+        assert!(value_iota_address.includes(&iota_address2));
+        assert!(!value_iota_address.includes(&IotaAddress::new([3; 32])));
+
+        let list = format!("[\"{}\", \"{}\"]", iota_address1, iota_address2);
+        assert_eq!(parse_address_list(&list).unwrap().len(), 2);
+        assert!(parse_address_list("0x01\nnot an address").is_err());
+    }
+
     #[test]
     fn test_serde_all_addresses() {
         let value_iota_address = ValueIotaAddress::All;
@@ -3,13 +3,22 @@
 
 mod action;
 mod aggregate;
+mod coin_type;
+mod country;
+mod header;
 mod iota_address;
 mod number;
 mod rego_expression;
+mod schedule;
 mod source;
 pub use action::Action;
-pub use aggregate::{LimitBy, ValueAggregate};
+pub(crate) use aggregate::serde_duration;
+pub use aggregate::{LimitBy, ValueAggregate, WindowMode};
+pub use coin_type::{coin_inner_type, ValueCoinType};
+pub use country::{ValueCountry, UNKNOWN_COUNTRY};
+pub use header::ValueHeader;
 pub use iota_address::ValueIotaAddress;
 pub use number::ValueNumber;
-pub use rego_expression::RegoExpression;
+pub use rego_expression::{RegoExpression, RegoInput, RegoLimits};
+pub use schedule::{DayRange, Schedule, TimeRange, TimeZone};
 pub use source::{Location, SourceWithData};
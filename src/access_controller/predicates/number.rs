@@ -58,6 +58,22 @@ where
     }
 }
 
+impl<T> Display for ValueNumber<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueNumber::GreaterThan(number) => write!(f, "{}{}", OP_GT, number),
+            ValueNumber::LessThan(number) => write!(f, "{}{}", OP_LT, number),
+            ValueNumber::Equal(number) => write!(f, "{}{}", OP_EQ, number),
+            ValueNumber::NotEqual(number) => write!(f, "{}{}", OP_NE, number),
+            ValueNumber::GreaterThanOrEqual(number) => write!(f, "{}{}", OP_GE, number),
+            ValueNumber::LessThanOrEqual(number) => write!(f, "{}{}", OP_LE, number),
+        }
+    }
+}
+
 impl<T> Serialize for ValueNumber<T>
 where
     T: Display,
@@ -66,24 +82,7 @@ where
     where
         S: serde::Serializer,
     {
-        match self {
-            ValueNumber::GreaterThan(number) => {
-                serializer.serialize_str(&format!("{}{}", OP_GT, number))
-            }
-            ValueNumber::LessThan(number) => {
-                serializer.serialize_str(&format!("{}{}", OP_LT, number))
-            }
-            ValueNumber::Equal(number) => serializer.serialize_str(&format!("{}{}", OP_EQ, number)),
-            ValueNumber::NotEqual(number) => {
-                serializer.serialize_str(&format!("{}{}", OP_NE, number))
-            }
-            ValueNumber::GreaterThanOrEqual(number) => {
-                serializer.serialize_str(&format!("{}{}", OP_GE, number))
-            }
-            ValueNumber::LessThanOrEqual(number) => {
-                serializer.serialize_str(&format!("{}{}", OP_LE, number))
-            }
-        }
+        serializer.serialize_str(&self.to_string())
     }
 }
 
@@ -0,0 +1,238 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! OPA bundles: tar.gz archives with `.rego` policies, `data.json` documents and an optional
+//! `.manifest`, as built by `opa build`. A `data.json` is loaded under the path of its directory,
+//! e.g. `users/data.json` under `data.users`.
+
+use std::io::Read;
+
+use anyhow::{bail, Context};
+use reqwest::{header, StatusCode};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const MANIFEST_FILE_NAME: &str = ".manifest";
+const DATA_FILE_NAME: &str = "data.json";
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    revision: String,
+}
+
+/// The content of a bundle.
+#[derive(Debug, Default)]
+pub struct Bundle {
+    /// The policies, with their path in the bundle.
+    pub policies: Vec<(String, String)>,
+    /// The documents of all the `data.json` files, merged.
+    pub data: Value,
+    pub revision: String,
+}
+
+impl Bundle {
+    /// Reads the bundle from the tar.gz archive, failing if its policies and data are larger than
+    /// `max_size` bytes once decompressed.
+    pub fn from_tar_gz(bytes: &[u8], max_size: usize) -> Result<Self, anyhow::Error> {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+        let mut bundle = Bundle {
+            data: Value::Object(Map::new()),
+            ..Default::default()
+        };
+        let mut size = 0;
+        for entry in archive.entries().context("failed to read the bundle")? {
+            let entry = entry.context("failed to read the bundle")?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.to_string_lossy().to_string();
+            let path = path
+                .trim_start_matches("./")
+                .trim_start_matches('/')
+                .to_string();
+            let file_name = path.rsplit('/').next().unwrap_or_default();
+            if !path.ends_with(".rego")
+                && file_name != DATA_FILE_NAME
+                && file_name != MANIFEST_FILE_NAME
+            {
+                continue;
+            }
+
+            // Reads at most one byte more than allowed, to detect larger bundles
+            let mut content = String::new();
+            entry
+                .take((max_size - size + 1) as u64)
+                .read_to_string(&mut content)
+                .with_context(|| format!("failed to read {} from the bundle", path))?;
+            size += content.len();
+            if size > max_size {
+                bail!("bundle exceeds the limit of {} bytes", max_size);
+            }
+
+            if path.ends_with(".rego") {
+                bundle.policies.push((path, content));
+            } else if file_name == DATA_FILE_NAME {
+                let document: Value = serde_json::from_str(&content)
+                    .with_context(|| format!("failed to parse {} from the bundle", path))?;
+                let root: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+                merge_at(&mut bundle.data, &root[..root.len() - 1], document);
+            } else if path == MANIFEST_FILE_NAME {
+                let manifest: Manifest = serde_json::from_str(&content)
+                    .context("failed to parse the manifest of the bundle")?;
+                bundle.revision = manifest.revision;
+            }
+        }
+        if bundle.policies.is_empty() {
+            bail!("bundle has no policy");
+        }
+        Ok(bundle)
+    }
+}
+
+/// Merges the document into the data, under the given path.
+fn merge_at(data: &mut Value, path: &[&str], document: Value) {
+    let mut target = data;
+    for segment in path {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .expect("target is an object")
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    merge(target, document);
+}
+
+fn merge(target: &mut Value, document: Value) {
+    match (target, document) {
+        (Value::Object(target), Value::Object(document)) => {
+            for (key, value) in document {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, document) => *target = document,
+    }
+}
+
+/// Downloads the bundle, unless its ETag is still `etag`. Returns the bundle with its new ETag,
+/// or `None` if it's unchanged.
+pub async fn fetch_bundle(
+    url: &str,
+    etag: Option<&str>,
+) -> Result<Option<(Vec<u8>, Option<String>)>, anyhow::Error> {
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(etag) = etag {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("unable to load bundle from url: {}", url))?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    let response = response.error_for_status()?;
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(|etag| etag.to_string());
+    let data = response.bytes().await?;
+    Ok(Some((data.to_vec(), etag)))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::net::SocketAddr;
+
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::get,
+        Router,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    /// Builds a tar.gz bundle with the given files.
+    pub(crate) fn build_bundle(files: &[(&str, &str)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_read_bundle() {
+        let bytes = build_bundle(&[
+            (".manifest", r#"{"revision": "v1", "roots": [""]}"#),
+            ("policies/allow.rego", "package allow"),
+            ("data.json", r#"{"limits": {"max": 1}}"#),
+            ("users/data.json", r#"{"admins": ["0x1"]}"#),
+            ("README.md", "ignored"),
+        ]);
+
+        let bundle = Bundle::from_tar_gz(&bytes, 1024).unwrap();
+        assert_eq!(bundle.revision, "v1");
+        assert_eq!(
+            bundle.policies,
+            vec![(
+                "policies/allow.rego".to_string(),
+                "package allow".to_string()
+            )]
+        );
+        assert_eq!(
+            bundle.data,
+            json!({"limits": {"max": 1}, "users": {"admins": ["0x1"]}})
+        );
+
+        assert!(Bundle::from_tar_gz(&bytes, 20).is_err());
+        assert!(Bundle::from_tar_gz(&build_bundle(&[("data.json", "{}")]), 1024).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_bundle_with_etag() {
+        let app = Router::new().route(
+            "/bundle.tar.gz",
+            get(|headers: HeaderMap| async move {
+                if headers
+                    .get("if-none-match")
+                    .is_some_and(|etag| etag == "\"v1\"")
+                {
+                    return (StatusCode::NOT_MODIFIED, HeaderMap::new(), vec![]);
+                }
+                let mut response_headers = HeaderMap::new();
+                response_headers.insert("etag", "\"v1\"".parse().unwrap());
+                let bundle = build_bundle(&[("allow.rego", "package allow")]);
+                (StatusCode::OK, response_headers, bundle)
+            }),
+        );
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let url = format!("http://{}/bundle.tar.gz", server.local_addr());
+        tokio::spawn(server);
+
+        let (bytes, etag) = fetch_bundle(&url, None).await.unwrap().unwrap();
+        assert_eq!(etag.as_deref(), Some("\"v1\""));
+        assert_eq!(Bundle::from_tar_gz(&bytes, 1024).unwrap().policies.len(), 1);
+
+        assert!(fetch_bundle(&url, etag.as_deref()).await.unwrap().is_none());
+        assert!(fetch_bundle(&url, Some("\"v0\"")).await.unwrap().is_some());
+    }
+}
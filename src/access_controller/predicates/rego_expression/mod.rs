@@ -1,24 +1,206 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
 use anyhow::{bail, Context};
+use arc_swap::ArcSwapOption;
+use parking_lot::Mutex;
 use regorus::Value;
 use serde::{Deserialize, Serialize};
-use tracing::trace;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::{error, info, trace, warn};
 
-use super::source::{Location, SourceWithData};
+use super::serde_duration;
+use super::source::{Location, LocationPathBundle, SourceWithData};
 
 mod bcs_decoder;
+mod bundle;
 use bcs_decoder::bcs_decode_typed;
+use bundle::{fetch_bundle, Bundle};
 
 const BCS_DECODE_EXTENSION_NAME: &str = "bcs.decode_typed";
+/// Maximum number of idle engines kept per expression.
+const ENGINE_POOL_SIZE: usize = 64;
+
+/// Limits of the evaluation of a Rego expression, so that a pathological policy can't stall
+/// the transaction execution. Exceeding a limit fails the evaluation, and so the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RegoLimits {
+    #[serde(with = "serde_duration")]
+    pub max_evaluation_time: Duration,
+    /// Maximum size of the policy, in bytes.
+    pub max_policy_size: usize,
+    /// Maximum size of the JSON input, in bytes.
+    pub max_input_size: usize,
+    /// Maximum number of values, e.g. array items or object entries, of the input and of the
+    /// data of a bundle.
+    pub max_values: usize,
+    /// Maximum number of evaluations running at once. An evaluation that exceeded
+    /// `max-evaluation-time` can't be interrupted and keeps its slot until it completes, so the
+    /// evaluations fail once all the slots are held by stalled ones.
+    pub max_concurrent_evaluations: usize,
+}
+
+impl Default for RegoLimits {
+    fn default() -> Self {
+        Self {
+            max_evaluation_time: Duration::from_millis(500),
+            max_policy_size: 1024 * 1024,
+            max_input_size: 1024 * 1024,
+            max_values: 100_000,
+            max_concurrent_evaluations: 16,
+        }
+    }
+}
+
+impl RegoLimits {
+    fn check_policy_size(&self, location: &Location, data: &str) -> Result<(), anyhow::Error> {
+        if data.len() > self.max_policy_size {
+            bail!(
+                "policy {} is {} bytes, exceeding the limit of {} bytes",
+                location.to_string(),
+                data.len(),
+                self.max_policy_size
+            );
+        }
+        Ok(())
+    }
+
+    fn check_values(&self, what: &str, value: &Value) -> Result<(), anyhow::Error> {
+        let count = count_values(value);
+        if count > self.max_values {
+            bail!(
+                "{} has {} values, exceeding the limit of {} values",
+                what,
+                count,
+                self.max_values
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Counts the values of the given value, itself included.
+fn count_values(value: &Value) -> usize {
+    1 + match value {
+        Value::Array(items) => items.iter().map(count_values).sum(),
+        Value::Set(items) => items.iter().map(count_values).sum(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| count_values(key) + count_values(value))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Input of the Rego expressions, parsed once and shared by all the rules evaluating it.
+#[derive(Debug, Clone)]
+pub struct RegoInput {
+    value: Value,
+    /// Size of the JSON input, in bytes.
+    size: usize,
+}
+
+impl RegoInput {
+    pub fn from_json_str(input_data: &str) -> Result<Self, anyhow::Error> {
+        let value = Value::from_json_str(input_data)
+            .with_context(|| format!("error while converting input data to json {}", input_data))?;
+        Ok(Self {
+            value,
+            size: input_data.len(),
+        })
+    }
+}
+
+/// A compiled policy, reused across the evaluations. An evaluation needs an engine of its own,
+/// so the engines are pooled: one is taken for each evaluation and put back afterwards, or the
+/// compiled one is cloned when all of them are in use.
+#[derive(Debug)]
+struct PreparedEngine {
+    engine: regorus::Engine,
+    pool: Mutex<Vec<regorus::Engine>>,
+}
+
+impl PreparedEngine {
+    /// Compiles the policy, or the policies and data of the bundle, from the given location.
+    fn compile(
+        location: &Location,
+        data: &[u8],
+        limits: &RegoLimits,
+    ) -> Result<Self, anyhow::Error> {
+        let (policies, documents) = match location {
+            Location::LocationPathBundle(_) => {
+                let bundle = Bundle::from_tar_gz(data, limits.max_policy_size)
+                    .with_context(|| format!("failed to load bundle {}", location.to_string()))?;
+                trace!(
+                    "Loaded bundle {} revision '{}' with {} policies",
+                    location.to_string(),
+                    bundle.revision,
+                    bundle.policies.len()
+                );
+                (bundle.policies, Some(bundle.data))
+            }
+            _ => {
+                let policy = String::from_utf8_lossy(data).to_string();
+                limits.check_policy_size(location, &policy)?;
+                (vec![(location.to_string(), policy)], None)
+            }
+        };
+        let mut engine = regorus::Engine::new();
+        for (path, policy) in policies {
+            engine
+                .add_policy(path.clone(), policy)
+                .with_context(|| format!("failed to add policy {}", path))?;
+        }
+        if let Some(documents) = documents {
+            let documents = Value::from_json_str(&documents.to_string())?;
+            limits.check_values(&format!("data of {}", location.to_string()), &documents)?;
+            engine
+                .add_data(documents)
+                .with_context(|| format!("failed to add data of {}", location.to_string()))?;
+        }
+        engine.add_extension(
+            BCS_DECODE_EXTENSION_NAME.to_string(),
+            2,
+            Box::new(bcs_decode_typed),
+        )?;
+        Ok(Self {
+            engine,
+            pool: Mutex::new(vec![]),
+        })
+    }
+
+    fn eval_rule(&self, rule: String, input: Value) -> Result<Value, anyhow::Error> {
+        let pooled = self.pool.lock().pop();
+        let mut engine = pooled.unwrap_or_else(|| self.engine.clone());
+        engine.set_input(input);
+        let result = engine.eval_rule(rule)?;
+        // An engine that failed is dropped rather than reused
+        let mut pool = self.pool.lock();
+        if pool.len() < ENGINE_POOL_SIZE {
+            pool.push(engine);
+        }
+        Ok(result)
+    }
+}
 
 /// RegoExpression allows to evaluate Rego policies
 /// using the regorus engine.
 #[derive(Debug, Clone)]
 pub struct RegoExpression {
     pub source: SourceWithData,
-    pub expression: Option<regorus::Engine>,
+    /// The compiled policy, shared by the clones of the expression and replaced when the source
+    /// is reloaded.
+    expression: Arc<ArcSwapOption<PreparedEngine>>,
+    pub limits: RegoLimits,
+    /// The task polling the bundle for changes, if the source is a bundle.
+    bundle_polling: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Slots of the evaluations, shared by the clones of the expression.
+    evaluations: Arc<Semaphore>,
 }
 
 impl RegoExpression {
@@ -26,17 +208,20 @@ impl RegoExpression {
     /// is already fetched and contains the data. If the source is not fetched, the `reload_source()` method
     /// should be called to fetch the data.
     pub fn from_source(source: SourceWithData) -> Result<Self, anyhow::Error> {
-        let expression = if let Some(data) = source.get_data_string() {
-            let mut engine = regorus::Engine::new();
-            engine
-                .add_policy(source.location.to_string(), data)
-                .with_context(|| format!("failed to add policy {}", source.location.to_string()))?;
-            engine.add_extension(
-                BCS_DECODE_EXTENSION_NAME.to_string(),
-                2,
-                Box::new(bcs_decode_typed),
-            )?;
-            Some(engine)
+        Self::from_source_with_limits(source, RegoLimits::default())
+    }
+
+    /// Create a new RegoExpression from the given source, evaluated within the given limits.
+    pub fn from_source_with_limits(
+        source: SourceWithData,
+        limits: RegoLimits,
+    ) -> Result<Self, anyhow::Error> {
+        let expression = if let Some(data) = source.get_data() {
+            Some(Arc::new(PreparedEngine::compile(
+                &source.location,
+                data,
+                &limits,
+            )?))
         } else {
             trace!(
                 "Source data is empty for {}. Use 'reload_source()' to initialize the expression",
@@ -44,49 +229,133 @@ impl RegoExpression {
             );
             None
         };
-        Ok(RegoExpression { source, expression })
+        Ok(RegoExpression {
+            source,
+            expression: Arc::new(ArcSwapOption::new(expression)),
+            limits,
+            bundle_polling: Default::default(),
+            evaluations: Arc::new(Semaphore::new(limits.max_concurrent_evaluations)),
+        })
     }
 
-    /// Reload the policy from the source.
+    /// Reload the policy from the source. A bundle is then polled for changes in the background.
     pub async fn reload_source(&mut self) -> Result<(), anyhow::Error> {
+        if let Location::LocationPathBundle(bundle) = &self.source.location {
+            let bundle = bundle.clone();
+            let (data, etag) = fetch_bundle(&bundle.url, None)
+                .await?
+                .context("bundle was not modified")?;
+            self.source.data = Some(data);
+            self.compile_source()?;
+            self.start_bundle_polling(bundle, etag);
+            return Ok(());
+        }
         self.source.fetch().await?;
-        let source_data = self.source.get_data_string().with_context(|| {
+        self.compile_source()
+    }
+
+    fn compile_source(&self) -> Result<(), anyhow::Error> {
+        let source_data = self.source.get_data().with_context(|| {
             format!(
                 "Source data is empty for {}",
                 self.source.location.to_string()
             )
         })?;
-        let mut expression = regorus::Engine::new();
-        expression
-            .add_policy(self.source.location.to_string(), source_data)
-            .with_context(|| {
-                format!(
-                    "error while loading policy {}",
-                    self.source.location.to_string()
-                )
-            })?;
-        expression.add_extension(
-            BCS_DECODE_EXTENSION_NAME.to_string(),
-            2,
-            Box::new(bcs_decode_typed),
-        )?;
-        self.expression = Some(expression);
+        let expression = PreparedEngine::compile(&self.source.location, source_data, &self.limits)?;
+        self.expression.store(Some(Arc::new(expression)));
         Ok(())
     }
 
+    /// Polls the bundle every `polling-interval`, replacing the compiled policies when its ETag
+    /// changes. A bundle that fails to load is skipped, the previous one is kept. The polling stops
+    /// once the expression is dropped, e.g. when the access controller is reloaded.
+    fn start_bundle_polling(&self, bundle: LocationPathBundle, etag: Option<String>) {
+        let expression: Weak<ArcSwapOption<PreparedEngine>> = Arc::downgrade(&self.expression);
+        let location = self.source.location.clone();
+        let limits = self.limits;
+        let handle = tokio::spawn(async move {
+            let mut etag = etag;
+            loop {
+                tokio::time::sleep(bundle.polling_interval).await;
+                let Some(expression) = expression.upgrade() else {
+                    break;
+                };
+                match fetch_bundle(&bundle.url, etag.as_deref()).await {
+                    Ok(None) => trace!("Bundle {} was not modified", bundle.url),
+                    Ok(Some((data, new_etag))) => {
+                        match PreparedEngine::compile(&location, &data, &limits) {
+                            Ok(compiled) => {
+                                expression.store(Some(Arc::new(compiled)));
+                                etag = new_etag;
+                                info!("Reloaded bundle {}", bundle.url);
+                            }
+                            Err(err) => error!(
+                                "Failed to load bundle {}, keeping the previous one: {:?}",
+                                bundle.url, err
+                            ),
+                        }
+                    }
+                    Err(err) => warn!("Failed to poll bundle {}: {:?}", bundle.url, err),
+                }
+            }
+        });
+        if let Some(previous) = self.bundle_polling.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Evaluate the policy with the given input, failing if it takes longer than
+    /// `max_evaluation_time`, waiting for a free slot included. The evaluation runs on a blocking
+    /// thread, which is left to finish in the background when the time is exceeded, holding its
+    /// slot until then.
+    pub async fn evaluate(&self, input: RegoInput) -> Result<bool, anyhow::Error> {
+        let expression = self.clone();
+        let evaluations = self.evaluations.clone();
+        let max_evaluation_time = self.limits.max_evaluation_time;
+        let evaluation = async move {
+            let permit = evaluations
+                .acquire_owned()
+                .await
+                .context("Rego evaluations are closed")?;
+            tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                expression.matches_input(input)
+            })
+            .await
+            .context("Rego evaluation failed to complete")?
+        };
+        match tokio::time::timeout(max_evaluation_time, evaluation).await {
+            Ok(result) => result,
+            Err(_) => bail!(
+                "Rego evaluation of {} exceeded the time limit of {}",
+                self.source.location.to_string(),
+                humantime::format_duration(max_evaluation_time)
+            ),
+        }
+    }
+
     /// Evaluate the policy with the given input data.
     pub fn matches(&self, input_data: &str) -> Result<bool, anyhow::Error> {
+        self.matches_input(RegoInput::from_json_str(input_data)?)
+    }
+
+    /// Evaluate the policy with the given parsed input.
+    pub fn matches_input(&self, input: RegoInput) -> Result<bool, anyhow::Error> {
         let rego_rule_name = self.source.location.get_rego_rule_path().to_string();
-        if self.expression.is_none() {
+        let Some(expression) = self.expression.load_full() else {
             bail!("Rego expression is not initialized");
+        };
+        if input.size > self.limits.max_input_size {
+            bail!(
+                "input data is {} bytes, exceeding the limit of {} bytes",
+                input.size,
+                self.limits.max_input_size
+            );
         }
-        let mut engine = self.expression.clone().unwrap();
-        let value = Value::from_json_str(input_data)
-            .with_context(|| format!("error while converting input data to json {}", input_data))?;
-        engine.set_input(value);
+        self.limits.check_values("input data", &input.value)?;
 
-        let result = engine
-            .eval_rule(rego_rule_name)
+        let result = expression
+            .eval_rule(rego_rule_name, input.value)
             .with_context(|| format!("error while evaluating rule"))?;
 
         if let Value::Bool(result) = result {
@@ -97,15 +366,31 @@ impl RegoExpression {
     }
 }
 
+/// The configuration of a Rego expression: its location, with optional limits.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RegoExpressionConfig {
+    #[serde(flatten)]
+    location: Location,
+    #[serde(default, skip_serializing_if = "is_default_limits")]
+    limits: RegoLimits,
+}
+
+fn is_default_limits(limits: &RegoLimits) -> bool {
+    *limits == RegoLimits::default()
+}
+
 impl Serialize for RegoExpression {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        self.source
-            .location
-            .serialize(serializer)
-            .map_err(serde::ser::Error::custom)
+        RegoExpressionConfig {
+            location: self.source.location.clone(),
+            limits: self.limits,
+        }
+        .serialize(serializer)
+        .map_err(serde::ser::Error::custom)
     }
 }
 
@@ -114,9 +399,18 @@ impl<'de> Deserialize<'de> for RegoExpression {
     where
         D: serde::Deserializer<'de>,
     {
-        let location = Location::deserialize(deserializer)?;
-        let source_with_data = SourceWithData::new(location);
-        RegoExpression::from_source(source_with_data).map_err(serde::de::Error::custom)
+        let config = RegoExpressionConfig::deserialize(deserializer)?;
+        if config.location.get_rego_rule_path().is_empty() {
+            return Err(serde::de::Error::missing_field("rego-rule-path"));
+        }
+        if config.limits.max_concurrent_evaluations == 0 {
+            return Err(serde::de::Error::custom(
+                "max-concurrent-evaluations must be greater than 0",
+            ));
+        }
+        let source_with_data = SourceWithData::new(config.location);
+        RegoExpression::from_source_with_limits(source_with_data, config.limits)
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -160,6 +454,58 @@ mod test {
         assert_eq!(result, true);
     }
 
+    #[tokio::test]
+    async fn test_rego_expression_reuses_engine() {
+        let location = Location::new_memory(TEST_REGO_FILE_CONTENT, TEST_REGO_RULE_NAME);
+        let mut source = SourceWithData::new(location);
+        source.fetch().await.unwrap();
+        let rego_expression = RegoExpression::from_source(source).unwrap();
+        let expression = rego_expression.expression.load_full().unwrap();
+
+        for input_data in [r#"{"method": "GET"}"#, r#"{"method": "POST"}"#] {
+            let input = RegoInput::from_json_str(input_data).unwrap();
+            rego_expression.clone().evaluate(input).await.unwrap();
+        }
+        // The clones share the compiled policy, and the engine went back to the pool
+        assert_eq!(expression.pool.lock().len(), 1);
+        let input = RegoInput::from_json_str(r#"{"method": "GET"}"#).unwrap();
+        assert_eq!(rego_expression.matches_input(input).unwrap(), true);
+        assert_eq!(expression.pool.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_rego_expression_from_bundle() {
+        let bytes = bundle::test::build_bundle(&[
+            (
+                "sender.rego",
+                r#"
+                package sender
+
+                import rego.v1
+
+                default allowed := false
+                allowed if {
+                    input.sender in data.senders.allowed
+                }
+                "#,
+            ),
+            ("senders/data.json", r#"{"allowed": ["0x1", "0x2"]}"#),
+        ]);
+        let location = Location::new_bundle(
+            "http://localhost/bundle.tar.gz",
+            "data.sender.allowed",
+            Duration::from_secs(60),
+        );
+        let source = SourceWithData {
+            location,
+            data: Some(bytes),
+        };
+        let rego_expression = RegoExpression::from_source(source).unwrap();
+
+        assert!(rego_expression.matches(r#"{"sender": "0x2"}"#).unwrap());
+        assert!(!rego_expression.matches(r#"{"sender": "0x3"}"#).unwrap());
+    }
+
     #[tokio::test]
     async fn test_rego_expression_invalid_data_rego_file() {
         let invalid_rego_file = r#"######'####}"#;
@@ -170,4 +516,145 @@ mod test {
         let result = RegoExpression::from_source(source);
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_rego_expression_limits() {
+        let location = Location::new_memory(TEST_REGO_FILE_CONTENT, TEST_REGO_RULE_NAME);
+        let mut source = SourceWithData::new(location);
+        source.fetch().await.unwrap();
+
+        let limits = RegoLimits {
+            max_policy_size: 10,
+            ..Default::default()
+        };
+        let result = RegoExpression::from_source_with_limits(source.clone(), limits);
+        assert!(result.is_err());
+
+        let limits = RegoLimits {
+            max_input_size: 10,
+            ..Default::default()
+        };
+        let rego_expression = RegoExpression::from_source_with_limits(source, limits).unwrap();
+        let input = RegoInput::from_json_str(r#"{"method": "GET"}"#).unwrap();
+        assert!(rego_expression.evaluate(input).await.is_err());
+        let input = RegoInput::from_json_str(r#"{"a": 1}"#).unwrap();
+        assert_eq!(rego_expression.evaluate(input).await.unwrap(), false);
+    }
+
+    #[tokio::test]
+    async fn test_rego_expression_evaluation_time_limit() {
+        let slow_rego_file = r#"
+            package test
+
+            import rego.v1
+
+            default slow := false
+            slow if {
+                count([x | some x in numbers.range(1, 10000000)]) == 0
+            }
+        "#;
+        let location = Location::new_memory(slow_rego_file, "data.test.slow");
+        let mut source = SourceWithData::new(location);
+        source.fetch().await.unwrap();
+        let limits = RegoLimits {
+            max_evaluation_time: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let rego_expression = RegoExpression::from_source_with_limits(source, limits).unwrap();
+
+        let err = rego_expression
+            .evaluate(RegoInput::from_json_str("{}").unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeded the time limit"));
+    }
+
+    #[tokio::test]
+    async fn test_rego_expression_stalled_evaluation_holds_slot() {
+        let slow_rego_file = r#"
+            package test
+
+            import rego.v1
+
+            default slow := false
+            slow if {
+                input.slow
+                count([x | some x in numbers.range(1, 10000000)]) == 0
+            }
+        "#;
+        let location = Location::new_memory(slow_rego_file, "data.test.slow");
+        let mut source = SourceWithData::new(location);
+        source.fetch().await.unwrap();
+        let limits = RegoLimits {
+            max_evaluation_time: std::time::Duration::from_millis(10),
+            max_concurrent_evaluations: 1,
+            ..Default::default()
+        };
+        let rego_expression = RegoExpression::from_source_with_limits(source, limits).unwrap();
+
+        let slow_input = RegoInput::from_json_str(r#"{"slow": true}"#).unwrap();
+        assert!(rego_expression.evaluate(slow_input).await.is_err());
+        // The stalled evaluation still holds the only slot, so even a fast one fails
+        let fast_input = RegoInput::from_json_str(r#"{"slow": false}"#).unwrap();
+        let err = rego_expression.evaluate(fast_input).await.unwrap_err();
+        assert!(err.to_string().contains("exceeded the time limit"));
+    }
+
+    #[tokio::test]
+    async fn test_rego_expression_values_limit() {
+        let location = Location::new_memory(TEST_REGO_FILE_CONTENT, TEST_REGO_RULE_NAME);
+        let mut source = SourceWithData::new(location);
+        source.fetch().await.unwrap();
+        let limits = RegoLimits {
+            max_values: 3,
+            ..Default::default()
+        };
+        let rego_expression = RegoExpression::from_source_with_limits(source, limits).unwrap();
+
+        // The object, its key and its value
+        assert!(rego_expression.matches(r#"{"method": "GET"}"#).unwrap());
+        let err = rego_expression
+            .matches(r#"{"method": "GET", "items": [1, 2]}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeding the limit of 3 values"));
+    }
+
+    #[test]
+    fn test_deserialize_rego_limits() {
+        let rego_expression: RegoExpression = serde_yaml::from_str(
+            r#"
+            location-type: file
+            path: ./policy.rego
+            rego-rule-path: data.test.allow
+            limits:
+              max-evaluation-time: 50ms
+              max-input-size: 4096
+              max-values: 1000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            rego_expression.limits,
+            RegoLimits {
+                max_evaluation_time: std::time::Duration::from_millis(50),
+                max_input_size: 4096,
+                max_values: 1000,
+                ..Default::default()
+            }
+        );
+
+        let serialized = serde_yaml::to_string(&rego_expression).unwrap();
+        assert!(serialized.contains("max-input-size: 4096"));
+
+        let result: Result<RegoExpression, _> = serde_yaml::from_str(
+            r#"
+            location-type: file
+            path: ./policy.rego
+            rego-rule-path: data.test.allow
+            limits:
+              max-concurrent-evaluations: 0
+            "#,
+        );
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,226 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// The Schedule restricts a rule to some days of the week and hours of the day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Schedule {
+    /// Days of the week, e.g. `mon` or `mon-fri`. Every day if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<DayRange>,
+    /// Time of the day, e.g. `09:00-18:00`. The whole day if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<TimeRange>,
+    /// IANA time zone of the days and hours, e.g. `Europe/Berlin`. UTC if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<TimeZone>,
+}
+
+impl Schedule {
+    /// Returns true if the time is within the schedule.
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        let tz = self.timezone.map(|timezone| timezone.0).unwrap_or(Tz::UTC);
+        let local_time = time.with_timezone(&tz);
+        let day_matches = self.days.is_empty()
+            || self
+                .days
+                .iter()
+                .any(|days| days.contains(local_time.weekday()));
+        day_matches
+            && self
+                .hours
+                .is_none_or(|hours| hours.contains(local_time.time()))
+    }
+}
+
+/// A day of the week, or an inclusive range of days like `mon-fri`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub struct DayRange {
+    pub from: Weekday,
+    pub to: Weekday,
+}
+
+impl DayRange {
+    pub fn contains(&self, day: Weekday) -> bool {
+        let from = self.from.num_days_from_monday();
+        let to = self.to.num_days_from_monday();
+        let day = day.num_days_from_monday();
+        if from <= to {
+            from <= day && day <= to
+        } else {
+            // The range wraps around the end of the week, e.g. `sat-mon`
+            day >= from || day <= to
+        }
+    }
+}
+
+fn format_weekday(day: Weekday) -> String {
+    day.to_string().to_lowercase()
+}
+
+impl Display for DayRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", format_weekday(self.from))
+        } else {
+            write!(
+                f,
+                "{}-{}",
+                format_weekday(self.from),
+                format_weekday(self.to)
+            )
+        }
+    }
+}
+
+impl FromStr for DayRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |day: &str| {
+            day.trim()
+                .parse::<Weekday>()
+                .map_err(|_| anyhow!("Invalid day of the week: {}", day))
+        };
+        match s.split_once('-') {
+            Some((from, to)) => Ok(Self {
+                from: parse_day(from)?,
+                to: parse_day(to)?,
+            }),
+            None => {
+                let day = parse_day(s)?;
+                Ok(Self { from: day, to: day })
+            }
+        }
+    }
+}
+
+/// A range of the day like `09:00-18:00`. The end is excluded. The range spans midnight if the
+/// end is before the start, e.g. `22:00-06:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub struct TimeRange {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+impl TimeRange {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.from <= self.to {
+            self.from <= time && time < self.to
+        } else {
+            time >= self.from || time < self.to
+        }
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.from.format(TIME_FORMAT),
+            self.to.format(TIME_FORMAT)
+        )
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((from, to)) = s.split_once('-') else {
+            bail!("Invalid time range {}, expected e.g. 09:00-18:00", s);
+        };
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), TIME_FORMAT)
+                .map_err(|_| anyhow!("Invalid time of the day: {}", time))
+        };
+        Ok(Self {
+            from: parse_time(from)?,
+            to: parse_time(to)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub struct TimeZone(pub Tz);
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.name())
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(TimeZone)
+            .map_err(|_| anyhow!("Invalid time zone: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_schedule() {
+        let schedule: Schedule = serde_yaml::from_str(
+            "days: [mon-fri, sun]\nhours: 09:00-18:00\ntimezone: Europe/Berlin\n",
+        )
+        .unwrap();
+        assert_eq!(schedule.days[0].from, Weekday::Mon);
+        assert_eq!(schedule.days[0].to, Weekday::Fri);
+        assert_eq!(schedule.days[1].to, Weekday::Sun);
+        assert_eq!(
+            serde_json::to_string(&schedule).unwrap(),
+            r#"{"days":["mon-fri","sun"],"hours":"09:00-18:00","timezone":"Europe/Berlin"}"#
+        );
+
+        assert!(serde_yaml::from_str::<Schedule>("days: [someday]").is_err());
+        assert!(serde_yaml::from_str::<Schedule>("hours: 9-18").is_err());
+        assert!(serde_yaml::from_str::<Schedule>("timezone: Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_schedule_matches() {
+        let schedule: Schedule =
+            serde_yaml::from_str("days: [mon-fri]\nhours: 09:00-18:00\ntimezone: Europe/Berlin\n")
+                .unwrap();
+        // Monday 2025-06-02, Berlin is UTC+2 in summer
+        assert!(schedule.matches(time("2025-06-02T07:00:00Z")));
+        assert!(!schedule.matches(time("2025-06-02T06:59:59Z")));
+        assert!(!schedule.matches(time("2025-06-02T16:00:00Z")));
+        // Saturday
+        assert!(!schedule.matches(time("2025-06-07T10:00:00Z")));
+    }
+
+    #[test]
+    fn test_ranges_wrapping_around() {
+        let weekend: DayRange = "sat-mon".parse().unwrap();
+        assert!(weekend.contains(Weekday::Sun));
+        assert!(weekend.contains(Weekday::Mon));
+        assert!(!weekend.contains(Weekday::Tue));
+
+        let night: TimeRange = "22:00-06:00".parse().unwrap();
+        assert!(night.contains(NaiveTime::from_hms_opt(23, 0, 0).unwrap()));
+        assert!(night.contains(NaiveTime::from_hms_opt(5, 59, 0).unwrap()));
+        assert!(!night.contains(NaiveTime::from_hms_opt(6, 0, 0).unwrap()));
+    }
+}
@@ -1,11 +1,19 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
 use anyhow::{bail, Context};
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use tracing::trace;
 
+use super::serde_duration;
+use crate::redis_connection::open_redis_client;
+
+/// How often a bundle is polled for changes, unless configured.
+const DEFAULT_BUNDLE_POLLING_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceWithData {
     pub location: Location,
@@ -39,7 +47,7 @@ impl SourceWithData {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "location-type", rename_all = "kebab-case")]
 pub enum Location {
     #[serde(rename = "file")]
@@ -48,41 +56,63 @@ pub enum Location {
     LocationPathRedis(LocationPathRedis),
     #[serde(rename = "http")]
     LocationPathHttp(LocationPathHttp),
+    #[serde(rename = "bundle")]
+    LocationPathBundle(LocationPathBundle),
     #[cfg(test)]
     #[serde(rename = "memory")]
     LocationPathMemory(LocationPathMemory),
 }
 
 #[cfg(test)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct LocationPathMemory {
     pub data: String,
+    #[serde(default)]
     pub rego_rule_path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct LocationPathFile {
     path: String,
+    #[serde(default)]
     rego_rule_path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct LocationPathRedis {
     url: String,
     redis_key: String,
+    #[serde(default)]
     rego_rule_path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct LocationPathHttp {
     url: String,
+    #[serde(default)]
     rego_rule_path: String,
 }
 
+/// An OPA bundle, i.e. a tar.gz archive with `.rego` policies and `data.json` documents, served
+/// over HTTP. It's polled for changes with its ETag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LocationPathBundle {
+    pub(crate) url: String,
+    #[serde(default)]
+    rego_rule_path: String,
+    #[serde(with = "serde_duration", default = "default_bundle_polling_interval")]
+    pub(crate) polling_interval: Duration,
+}
+
+fn default_bundle_polling_interval() -> Duration {
+    DEFAULT_BUNDLE_POLLING_INTERVAL
+}
+
 impl ToString for Location {
     fn to_string(&self) -> String {
         match self {
@@ -98,6 +128,9 @@ impl ToString for Location {
             Location::LocationPathHttp(path) => {
                 format!("url: {}, rule_path: {}", path.url, path.rego_rule_path)
             }
+            Location::LocationPathBundle(path) => {
+                format!("bundle: {}, rule_path: {}", path.url, path.rego_rule_path)
+            }
             #[cfg(test)]
             Location::LocationPathMemory(path) => {
                 format!("data: {}", path.data)
@@ -136,6 +169,19 @@ impl Location {
         })
     }
 
+    /// Create a new location with the given bundle url, polled every `polling_interval`.
+    pub fn new_bundle(
+        url: impl AsRef<str>,
+        rego_rule_name: impl AsRef<str>,
+        polling_interval: Duration,
+    ) -> Self {
+        Location::LocationPathBundle(LocationPathBundle {
+            url: url.as_ref().to_string(),
+            rego_rule_path: rego_rule_name.as_ref().to_string(),
+            polling_interval,
+        })
+    }
+
     #[cfg(test)]
     pub fn new_memory(data: impl AsRef<str>, rego_rule_name: impl AsRef<str>) -> Self {
         Location::LocationPathMemory(LocationPathMemory {
@@ -150,6 +196,7 @@ impl Location {
             Location::LocationPathFile(location) => &location.rego_rule_path,
             Location::LocationPathRedis(location) => &location.rego_rule_path,
             Location::LocationPathHttp(location) => &location.rego_rule_path,
+            Location::LocationPathBundle(location) => &location.rego_rule_path,
             #[cfg(test)]
             Location::LocationPathMemory(location) => &location.rego_rule_path,
         }
@@ -180,9 +227,15 @@ impl Location {
                 let data = response.text().await?;
                 Ok(data)
             }
+            Location::LocationPathBundle(location) => {
+                bail!(
+                    "bundle {} can only be used by a rego expression",
+                    location.url
+                )
+            }
             Location::LocationPathRedis(location) => {
                 trace!("Fetching data from redis url: {}", location.url);
-                let client = redis::Client::open(location.url.clone()).with_context(|| {
+                let client = open_redis_client(&location.url).with_context(|| {
                     format!("unable to connect to redis server: {}", location.url)
                 })?;
                 let mut con = client.get_async_connection().await?;
@@ -225,8 +278,17 @@ impl Location {
                 let data = response.bytes().await?;
                 Ok(data.to_vec())
             }
+            Location::LocationPathBundle(location) => {
+                trace!("Fetching bundle from url: {}", location.url);
+                let response = reqwest::get(location.url.clone())
+                    .await
+                    .with_context(|| format!("unable to load bundle from url: {}", location.url))?
+                    .error_for_status()?;
+                let data = response.bytes().await?;
+                Ok(data.to_vec())
+            }
             Location::LocationPathRedis(url) => {
-                let client = redis::Client::open(url.url.clone())
+                let client = open_redis_client(&url.url)
                     .with_context(|| format!("unable to connect to redis server: {}", url.url))?;
                 let mut con = client.get_async_connection().await?;
                 let data: Vec<u8> = con.get(url.redis_key.clone()).await.with_context(|| {
@@ -1,15 +1,25 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Context;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
 use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
 use fastcrypto::encoding::Base64;
 use iota_types::{
-    base_types::IotaAddress,
+    base_types::{IotaAddress, ObjectID},
     digests::TransactionDigest,
+    gas_coin::GAS,
     signature::GenericSignature,
-    transaction::{TransactionData, TransactionDataAPI, TransactionDataV1, TransactionKind},
+    transaction::{
+        Argument, CallArg, Command, ObjectArg, ProgrammableTransaction, TransactionData,
+        TransactionDataAPI, TransactionDataV1, TransactionKind,
+    },
+    TypeTag,
 };
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use serde_with::skip_serializing_none;
@@ -17,17 +27,33 @@ use tracing::trace;
 use url::Url;
 
 use super::{
-    hook::HookAction,
-    predicates::{Action, LimitBy, RegoExpression, ValueAggregate, ValueIotaAddress, ValueNumber},
+    explanation::PredicateMatch,
+    hook::{HookAction, HookCache, HookCacheConfig, NotifyAction},
+    predicates::{
+        coin_inner_type, Action, LimitBy, RegoExpression, RegoInput, Schedule, ValueAggregate,
+        ValueCoinType, ValueCountry, ValueHeader, ValueIotaAddress, ValueNumber, WindowMode,
+    },
 };
 use crate::{
-    rpc::rpc_types::ExecuteTransactionRequestType,
+    rpc::rpc_types::{ExecuteTransactionRequestType, REQUEST_ID_HEADER},
     tracker::{
-        stats_tracker_storage::{Aggregate, AggregateType},
+        stats_tracker_storage::{Aggregate, AggregateType, AggregateValue},
         StatsTracker,
     },
 };
 
+/// Field of the rule meta holding the package a package gas limit counts the gas usage of.
+const PACKAGE_GAS_LIMIT_PACKAGE_FIELD: &str = "package-gas-limit-package";
+
+/// Fields of the rule that are not part of the rule meta.
+const RULE_META_IGNORED_FIELDS: [&str; 5] = [
+    "name",
+    "deny-message",
+    "valid-from",
+    "valid-until",
+    "hook-cache",
+];
+
 /// The AccessRuleBuilder is used to build an AccessRule with fluent API.
 pub struct AccessRuleBuilder {
     rule: AccessRule,
@@ -44,10 +70,18 @@ impl AccessRuleBuilder {
         self.rule
     }
 
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.rule.name = Some(name.into());
+        self
+    }
+
     pub fn sender_address(mut self, sender_address: impl Into<IotaAddress>) -> Self {
         let iota_address = sender_address.into();
         match &mut self.rule.sender_address {
-            ValueIotaAddress::All => {
+            ValueIotaAddress::All
+            | ValueIotaAddress::None
+            | ValueIotaAddress::Prefix(_)
+            | ValueIotaAddress::External(_) => {
                 self.rule.sender_address = ValueIotaAddress::Single(iota_address);
             }
             ValueIotaAddress::Single(_) => {
@@ -72,12 +106,49 @@ impl AccessRuleBuilder {
         self
     }
 
+    /// Sets the message returned to the caller when the rule denies.
+    pub fn deny_message(mut self, deny_message: impl Into<String>) -> Self {
+        self.rule.deny_message = Some(deny_message.into());
+        self
+    }
+
+    /// Sets the action of the AccessRule to audit.
+    pub fn audit(mut self) -> Self {
+        self.rule.action = Action::Audit;
+        self
+    }
+
+    /// Sets the action of the AccessRule to hold the transaction for manual approval.
+    pub fn hold(mut self) -> Self {
+        self.rule.action = Action::Hold;
+        self
+    }
+
+    /// Sets the action of the AccessRule to notify the webhook and continue the evaluation.
+    pub fn notify(mut self, url: Url) -> Self {
+        self.rule.action = Action::Notify(NotifyAction(url));
+        self
+    }
+
+    /// Sets the action of the AccessRule to continue the evaluation with the next rule named
+    /// `target`.
+    pub fn goto(mut self, target: impl Into<String>) -> Self {
+        self.rule.action = Action::Goto(target.into());
+        self
+    }
+
     /// Sets the action of the AccessRule to call hook.
     pub fn hook(mut self, url: Url) -> Self {
         self.rule.action = Action::HookAction(HookAction(url));
         self
     }
 
+    /// Reuses the responses of the hook of the AccessRule.
+    pub fn hook_cache(mut self, hook_cache: HookCacheConfig) -> Self {
+        self.rule.hook_cache = Some(hook_cache);
+        self
+    }
+
     pub fn gas_budget(mut self, gas_size: ValueNumber<u64>) -> Self {
         self.rule.transaction_gas_budget = Some(gas_size);
         self
@@ -87,7 +158,10 @@ impl AccessRuleBuilder {
         let iota_address = address.into();
         if let Some(address) = &mut self.rule.move_call_package_address {
             match address {
-                ValueIotaAddress::All => {
+                ValueIotaAddress::All
+                | ValueIotaAddress::None
+                | ValueIotaAddress::Prefix(_)
+                | ValueIotaAddress::External(_) => {
                     *address = ValueIotaAddress::Single(iota_address);
                 }
                 ValueIotaAddress::Single(_) => {
@@ -104,105 +178,589 @@ impl AccessRuleBuilder {
         self
     }
 
+    /// Excludes the sender address from the rule.
+    pub fn not_sender_address(mut self, sender_address: impl Into<IotaAddress>) -> Self {
+        let sender_address = sender_address.into();
+        match &mut self.rule.not_sender_address {
+            Some(ValueIotaAddress::List(list)) => list.push(sender_address),
+            Some(ValueIotaAddress::Single(single)) => {
+                self.rule.not_sender_address =
+                    Some(ValueIotaAddress::List(vec![*single, sender_address]));
+            }
+            Some(
+                ValueIotaAddress::All
+                | ValueIotaAddress::None
+                | ValueIotaAddress::Prefix(_)
+                | ValueIotaAddress::External(_),
+            )
+            | None => {
+                self.rule.not_sender_address = Some(ValueIotaAddress::Single(sender_address));
+            }
+        }
+        self
+    }
+
+    /// Excludes the transactions calling the package from the rule.
+    pub fn not_move_call_package_address(mut self, address: impl Into<IotaAddress>) -> Self {
+        let address = address.into();
+        match &mut self.rule.not_move_call_package_address {
+            Some(ValueIotaAddress::List(list)) => list.push(address),
+            Some(ValueIotaAddress::Single(single)) => {
+                self.rule.not_move_call_package_address =
+                    Some(ValueIotaAddress::List(vec![*single, address]));
+            }
+            Some(
+                ValueIotaAddress::All
+                | ValueIotaAddress::None
+                | ValueIotaAddress::Prefix(_)
+                | ValueIotaAddress::External(_),
+            )
+            | None => {
+                self.rule.not_move_call_package_address = Some(ValueIotaAddress::Single(address));
+            }
+        }
+        self
+    }
+
+    pub fn object_id(mut self, object_id: impl Into<IotaAddress>) -> Self {
+        let object_id = object_id.into();
+        match &mut self.rule.object_id {
+            Some(ValueIotaAddress::List(list)) => list.push(object_id),
+            Some(ValueIotaAddress::Single(single)) => {
+                self.rule.object_id = Some(ValueIotaAddress::List(vec![*single, object_id]));
+            }
+            Some(
+                ValueIotaAddress::All
+                | ValueIotaAddress::None
+                | ValueIotaAddress::Prefix(_)
+                | ValueIotaAddress::External(_),
+            )
+            | None => {
+                self.rule.object_id = Some(ValueIotaAddress::Single(object_id));
+            }
+        }
+        self
+    }
+
+    pub fn coin_type(mut self, coin_type: TypeTag) -> Self {
+        self.rule
+            .coin_type
+            .get_or_insert_with(Default::default)
+            .0
+            .push(coin_type);
+        self
+    }
+
     pub fn ptb_command_count(mut self, ptb_command_count: ValueNumber<usize>) -> Self {
         self.rule.ptb_command_count = Some(ptb_command_count);
         self
     }
 
+    pub fn tx_size_bytes(mut self, tx_size_bytes: ValueNumber<usize>) -> Self {
+        self.rule.tx_size_bytes = Some(tx_size_bytes);
+        self
+    }
+
+    pub fn input_object_count(mut self, input_object_count: ValueNumber<usize>) -> Self {
+        self.rule.input_object_count = Some(input_object_count);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: ValueNumber<u64>) -> Self {
+        self.rule.gas_price = Some(gas_price);
+        self
+    }
+
+    pub fn gas_price_ratio(mut self, gas_price_ratio: ValueNumber<f64>) -> Self {
+        self.rule.gas_price_ratio = Some(gas_price_ratio);
+        self
+    }
+
     pub fn gas_limit(mut self, gas_limit: ValueAggregate) -> Self {
         self.rule.gas_usage = Some(gas_limit);
         self
     }
 
+    pub fn package_gas_limit(mut self, package_gas_limit: ValueAggregate) -> Self {
+        self.rule.package_gas_limit = Some(package_gas_limit);
+        self
+    }
+
     pub fn rego_expression(mut self, rego_expression: RegoExpression) -> Self {
         self.rule.rego_expression = Some(rego_expression);
         self
     }
+
+    pub fn valid_from(mut self, valid_from: DateTime<Utc>) -> Self {
+        self.rule.valid_from = Some(valid_from);
+        self
+    }
+
+    pub fn valid_until(mut self, valid_until: DateTime<Utc>) -> Self {
+        self.rule.valid_until = Some(valid_until);
+        self
+    }
+
+    pub fn any_of(mut self, group: RuleGroup) -> Self {
+        self.rule.any_of.push(group);
+        self
+    }
+
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.rule.schedule = Some(schedule);
+        self
+    }
+
+    pub fn header(mut self, header: ValueHeader) -> Self {
+        self.rule.header = Some(header);
+        self
+    }
+
+    pub fn client_country(mut self, client_country: ValueCountry) -> Self {
+        self.rule.client_country = Some(client_country);
+        self
+    }
 }
 
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct AccessRule {
+    /// Name of the rule, returned to the caller when the rule makes the decision.
+    pub name: Option<String>,
     #[serde(default)]
     pub sender_address: ValueIotaAddress,
+    /// Matches if the sender is not listed.
+    pub not_sender_address: Option<ValueIotaAddress>,
     pub transaction_gas_budget: Option<ValueNumber<u64>>,
     pub move_call_package_address: Option<ValueIotaAddress>,
+    /// Matches if the transaction calls none of the listed packages.
+    pub not_move_call_package_address: Option<ValueIotaAddress>,
+    /// Matches if any object in the inputs of the programmable transaction is listed.
+    pub object_id: Option<ValueIotaAddress>,
+    /// Matches if the transaction moves any of the listed coin types.
+    pub coin_type: Option<ValueCoinType>,
     pub ptb_command_count: Option<ValueNumber<usize>>,
+    pub tx_size_bytes: Option<ValueNumber<usize>>,
+    pub input_object_count: Option<ValueNumber<usize>>,
+    pub gas_price: Option<ValueNumber<u64>>,
+    /// Compared to the gas price of the transaction divided by the reference gas price.
+    pub gas_price_ratio: Option<ValueNumber<f64>>,
     pub gas_usage: Option<ValueAggregate>,
+    /// Gas usage counted separately for each package of `move-call-package-address` called by the
+    /// transaction.
+    pub package_gas_limit: Option<ValueAggregate>,
     pub rego_expression: Option<RegoExpression>,
+    /// The rule is ignored before this time.
+    pub valid_from: Option<DateTime<Utc>>,
+    /// The rule is ignored from this time on.
+    pub valid_until: Option<DateTime<Utc>>,
+    pub schedule: Option<Schedule>,
+    /// Matches a header of the `execute_tx` request.
+    pub header: Option<ValueHeader>,
+    /// Matches the country of the client, resolved by the GeoIP database of the access
+    /// controller.
+    pub client_country: Option<ValueCountry>,
+    /// Matches if any of the groups matches. Not applicable if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_of: Vec<RuleGroup>,
 
     pub action: Action,
+    /// Reuses the responses of the hook, if the action is a hook.
+    pub hook_cache: Option<HookCacheConfig>,
+    /// Message returned to the caller instead of the generic one when the rule denies.
+    pub deny_message: Option<String>,
+
+    #[serde(skip)]
+    pub hook_cache_entries: HookCache,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct GasUsageConfirmationRequest {
     pub rule_meta: Map<String, Value>,
     pub aggregate: Aggregate,
     pub gas_usage: u64,
 }
 
+/// A predicate of a rule: whether the rule sets it, and whether the transaction matches it. A
+/// predicate the rule doesn't set matches every transaction.
+struct RulePredicate {
+    name: &'static str,
+    is_set: fn(&AccessRule) -> bool,
+    matches: fn(&AccessRule, &TransactionContext, DateTime<Utc>) -> Result<bool, anyhow::Error>,
+}
+
+/// The predicates of a rule in the order they are evaluated, shared by the matching and the
+/// explanation of the rules. The rego expression is evaluated last, after all of them, since it's
+/// the most expensive.
+const RULE_PREDICATES: &[RulePredicate] = &[
+    RulePredicate {
+        name: "valid-from",
+        is_set: |rule| rule.valid_from.is_some(),
+        matches: |rule, _, now| Ok(rule.valid_from.is_none_or(|valid_from| now >= valid_from)),
+    },
+    RulePredicate {
+        name: "valid-until",
+        is_set: |rule| rule.valid_until.is_some(),
+        matches: |rule, _, now| Ok(rule.valid_until.is_none_or(|valid_until| now < valid_until)),
+    },
+    RulePredicate {
+        name: "sender-address",
+        is_set: |rule| rule.sender_address != ValueIotaAddress::All,
+        matches: |rule, data, _| Ok(rule.sender_address.includes(&data.sender_address)),
+    },
+    RulePredicate {
+        name: "not-sender-address",
+        is_set: |rule| rule.not_sender_address.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .not_sender_address
+                .as_ref()
+                .is_none_or(|address| !address.includes(&data.sender_address)))
+        },
+    },
+    RulePredicate {
+        name: "transaction-gas-budget",
+        is_set: |rule| rule.transaction_gas_budget.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .transaction_gas_budget
+                .is_none_or(|budget| budget.matches(data.transaction_budget)))
+        },
+    },
+    RulePredicate {
+        name: "move-call-package-address",
+        is_set: |rule| rule.move_call_package_address.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .move_call_package_address
+                .as_ref()
+                .is_none_or(|address| address.includes_any(&data.move_call_package_addresses)))
+        },
+    },
+    RulePredicate {
+        name: "not-move-call-package-address",
+        is_set: |rule| rule.not_move_call_package_address.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .not_move_call_package_address
+                .as_ref()
+                .is_none_or(|address| !address.includes_any(&data.move_call_package_addresses)))
+        },
+    },
+    RulePredicate {
+        name: "object-id",
+        is_set: |rule| rule.object_id.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .object_id
+                .as_ref()
+                .is_none_or(|object_id| object_id.includes_any(&data.input_object_ids)))
+        },
+    },
+    RulePredicate {
+        name: "coin-type",
+        is_set: |rule| rule.coin_type.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .coin_type
+                .as_ref()
+                .is_none_or(|coin_type| coin_type.includes_any(&data.coin_types)))
+        },
+    },
+    RulePredicate {
+        name: "ptb-command-count",
+        is_set: |rule| rule.ptb_command_count.is_some(),
+        matches: |rule, data, _| {
+            Ok(count_matches_or_not_applicable(
+                rule.ptb_command_count,
+                data.ptb_command_count,
+            ))
+        },
+    },
+    RulePredicate {
+        name: "tx-size-bytes",
+        is_set: |rule| rule.tx_size_bytes.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .tx_size_bytes
+                .is_none_or(|size| size.matches(data.tx_size_bytes)))
+        },
+    },
+    RulePredicate {
+        name: "input-object-count",
+        is_set: |rule| rule.input_object_count.is_some(),
+        matches: |rule, data, _| {
+            Ok(count_matches_or_not_applicable(
+                rule.input_object_count,
+                data.input_object_count,
+            ))
+        },
+    },
+    RulePredicate {
+        name: "gas-price",
+        is_set: |rule| rule.gas_price.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .gas_price
+                .is_none_or(|price| price.matches(data.gas_price)))
+        },
+    },
+    RulePredicate {
+        name: "gas-price-ratio",
+        is_set: |rule| rule.gas_price_ratio.is_some(),
+        matches: |rule, data, _| {
+            gas_price_ratio_matches_or_not_applicable(rule.gas_price_ratio, data)
+        },
+    },
+    RulePredicate {
+        name: "schedule",
+        is_set: |rule| rule.schedule.is_some(),
+        matches: |rule, _, now| {
+            Ok(rule
+                .schedule
+                .as_ref()
+                .is_none_or(|schedule| schedule.matches(now)))
+        },
+    },
+    RulePredicate {
+        name: "header",
+        is_set: |rule| rule.header.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .header
+                .as_ref()
+                .is_none_or(|header| header.matches(&data.headers)))
+        },
+    },
+    RulePredicate {
+        name: "client-country",
+        is_set: |rule| rule.client_country.is_some(),
+        matches: |rule, data, _| {
+            Ok(rule
+                .client_country
+                .as_ref()
+                .is_none_or(|country| country.includes(data.client_country.as_deref())))
+        },
+    },
+    RulePredicate {
+        name: "any-of",
+        is_set: |rule| !rule.any_of.is_empty(),
+        matches: |rule, data, now| {
+            Ok(rule.any_of.is_empty() || any_group_matches(&rule.any_of, data, now)?)
+        },
+    },
+];
+
 impl AccessRule {
     pub async fn initialize(&mut self) -> Result<(), anyhow::Error> {
         trace!("Initializing rule: {:?}", self);
+        if self.package_gas_limit.is_some() && self.move_call_package_address.is_none() {
+            bail!("package-gas-limit requires move-call-package-address");
+        }
         if let Some(rego_expression) = self.rego_expression.as_mut() {
             rego_expression.reload_source().await?;
         }
+        for header in self
+            .header
+            .iter()
+            .chain(self.any_of.iter().filter_map(|group| group.header.as_ref()))
+        {
+            header.validate()?;
+        }
+        self.sender_address.load().await?;
+        for address in [
+            &mut self.not_sender_address,
+            &mut self.move_call_package_address,
+            &mut self.not_move_call_package_address,
+            &mut self.object_id,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            address.load().await?;
+        }
+        for group in self.any_of.iter_mut() {
+            for address in [
+                &mut group.sender_address,
+                &mut group.not_sender_address,
+                &mut group.move_call_package_address,
+                &mut group.not_move_call_package_address,
+                &mut group.object_id,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                address.load().await?;
+            }
+        }
         Ok(())
     }
 
+    /// Returns the name of the rule, or its position in the access controller, starting at 1, if
+    /// it has none.
+    pub fn display_name(&self, rule_number: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", rule_number))
+    }
+
     /// Returns the action of the rule.
     ///
     /// Checks if the rule matches the transaction data.
     pub async fn matches(&self, data: &TransactionContext) -> Result<bool, anyhow::Error> {
-        Ok(self.sender_address.includes(&data.sender_address)
-            // Gas Budget
-            && self
-                .transaction_gas_budget
-                .map(|size| size.matches(data.transaction_budget))
-                // If the gas size is not defined then the rule matches
-                .unwrap_or(true)
-            // Move Call Package Address
+        let now = Utc::now();
+        for predicate in RULE_PREDICATES {
+            if !(predicate.matches)(self, data, now)? {
+                return Ok(false);
+            }
+        }
+        self.match_rego_expression(data).await
+    }
+
+    /// Evaluates each predicate set by the rule, so that callers can see why it didn't match. The
+    /// gas usage limits aren't included, they are only counted by the evaluation of the rules.
+    pub async fn explain(
+        &self,
+        data: &TransactionContext,
+    ) -> Result<Vec<PredicateMatch>, anyhow::Error> {
+        let now = Utc::now();
+        let mut predicates = vec![];
+        for predicate in RULE_PREDICATES {
+            if (predicate.is_set)(self) {
+                predicates.push(PredicateMatch::new(
+                    predicate.name,
+                    (predicate.matches)(self, data, now)?,
+                ));
+            }
+        }
+        if self.rego_expression.is_some() {
+            predicates.push(PredicateMatch::new(
+                "rego-expression",
+                self.match_rego_expression(data).await?,
+            ));
+        }
+        Ok(predicates)
+    }
+
+    /// Returns true if the rule decides every transaction: it allows, denies or holds without any
+    /// predicate, validity window or gas usage limit.
+    pub fn is_catch_all(&self) -> bool {
+        matches!(self.action, Action::Allow | Action::Deny | Action::Hold)
+            && !self.has_gas_limits()
+            && self.rego_expression.is_none()
+            && RULE_PREDICATES
+                .iter()
+                .all(|predicate| !(predicate.is_set)(self))
+    }
+
+    /// Returns true if the time is within the validity window of the rule.
+    pub fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        self.valid_from.is_none_or(|valid_from| time >= valid_from)
             && self
-                .move_call_package_address.as_ref().map(|address| address.includes_any(&data.move_call_package_addresses)).unwrap_or(true)
-            && self.ptb_command_count_matches_or_not_applicable(data)
-            // Rego expression
-            && self.match_rego_expression(data)?)
+                .valid_until
+                .is_none_or(|valid_until| time < valid_until)
     }
 
     /// Match checking for global limits. Global limits use a persistent storage to track their values
     pub async fn match_global_limits(
         &self,
         ctx: &TransactionContext,
+    ) -> Result<(bool, Vec<GasUsageConfirmationRequest>), anyhow::Error> {
+        self.match_global_limits_once(ctx, &[]).await
+    }
+
+    /// Same as `match_global_limits`, but the gas budget isn't counted again by the limits that
+    /// already counted it, as given by the `counted` confirmation requests of the transaction, e.g.
+    /// when a client retries it.
+    pub async fn match_global_limits_once(
+        &self,
+        ctx: &TransactionContext,
+        counted: &[GasUsageConfirmationRequest],
+    ) -> Result<(bool, Vec<GasUsageConfirmationRequest>), anyhow::Error> {
+        self.match_global_limits_impl(ctx, counted, false).await
+    }
+
+    /// Same as `match_global_limits_once`, but nothing is counted: the gas budget of the
+    /// transaction is only added to the gas usage read from the counters. Used by dry runs, so
+    /// that they never touch the gas usage counted for a transaction being executed.
+    pub async fn peek_global_limits(
+        &self,
+        ctx: &TransactionContext,
+        counted: &[GasUsageConfirmationRequest],
+    ) -> Result<bool, anyhow::Error> {
+        Ok(self.match_global_limits_impl(ctx, counted, true).await?.0)
+    }
+
+    async fn match_global_limits_impl(
+        &self,
+        ctx: &TransactionContext,
+        counted: &[GasUsageConfirmationRequest],
+        dry_run: bool,
     ) -> Result<(bool, Vec<GasUsageConfirmationRequest>), anyhow::Error> {
         let mut confirmation_requests = vec![];
         let gas_limit_result = self
-            .match_gas_limit(ctx)
+            .match_gas_limit(ctx, counted, dry_run)
             .await
             .context("failed to match gas limit")?;
         if let Some(confirmation_request) = gas_limit_result.1 {
             confirmation_requests.push(confirmation_request);
         }
-        let result = (gas_limit_result.0, confirmation_requests);
+        let package_gas_limit_result = self
+            .match_package_gas_limit(ctx, counted, dry_run)
+            .await
+            .context("failed to match package gas limit")?;
+        confirmation_requests.extend(package_gas_limit_result.1);
+        let result = (
+            gas_limit_result.0 && package_gas_limit_result.0,
+            confirmation_requests,
+        );
         Ok(result)
     }
 
+    /// Whether the rule counts the gas usage in the stats storage.
+    pub fn has_gas_limits(&self) -> bool {
+        self.gas_usage.is_some() || self.package_gas_limit.is_some()
+    }
+
     /// Returns the rule meta data as a JSON object. The rule meta is used to calculate the hash of the rule.
-    fn get_rule_meta(&self, ctx: &TransactionContext) -> Result<Map<String, Value>, anyhow::Error> {
+    fn get_rule_meta(
+        &self,
+        sender_address: &IotaAddress,
+    ) -> Result<Map<String, Value>, anyhow::Error> {
+        let mut rule_to_hash = self.get_base_rule_meta()?;
+        if let Some(gas_limit) = self.gas_usage.as_ref() {
+            insert_count_by(&mut rule_to_hash, gas_limit, sender_address);
+        }
+        Ok(rule_to_hash)
+    }
+
+    /// Returns the rule meta data of the gas usage counted for the package.
+    fn get_package_rule_meta(
+        &self,
+        package_gas_limit: &ValueAggregate,
+        sender_address: &IotaAddress,
+        package_address: &IotaAddress,
+    ) -> Result<Map<String, Value>, anyhow::Error> {
+        let mut rule_to_hash = self.get_base_rule_meta()?;
+        insert_count_by(&mut rule_to_hash, package_gas_limit, sender_address);
+        rule_to_hash.insert(
+            PACKAGE_GAS_LIMIT_PACKAGE_FIELD.to_string(),
+            Value::String(package_address.to_string()),
+        );
+        Ok(rule_to_hash)
+    }
+
+    fn get_base_rule_meta(&self) -> Result<Map<String, Value>, anyhow::Error> {
         let json_rule =
             serde_json::to_value(self.clone()).context("Failed to serialize rule to JSON")?;
         let mut rule_to_hash = json_rule
             .as_object()
             .context("The rule isn't a map")?
             .to_owned();
-
-        if let Some(gas_limit) = self.gas_usage.as_ref() {
-            for count_by in gas_limit.count_by.iter() {
-                let count_by_value = match count_by {
-                    LimitBy::SenderAddress => ctx.sender_address.to_string(),
-                };
-                (&mut rule_to_hash).insert(count_by.to_string(), Value::String(count_by_value));
-            }
+        // Renaming the rule or extending its validity must not reset its counters.
+        for field in RULE_META_IGNORED_FIELDS {
+            rule_to_hash.remove(field);
         }
         Ok(rule_to_hash)
     }
@@ -210,21 +768,17 @@ impl AccessRule {
     async fn match_gas_limit(
         &self,
         ctx: &TransactionContext,
+        counted: &[GasUsageConfirmationRequest],
+        dry_run: bool,
     ) -> Result<(bool, Option<GasUsageConfirmationRequest>), anyhow::Error> {
         if let Some(gas_limit) = self.gas_usage.as_ref() {
             let rule_meta = self
-                .get_rule_meta(ctx)
+                .get_rule_meta(&ctx.sender_address)
                 .context("Failed to calculate rule meta")?;
 
-            let aggr = Aggregate::with_name("gas_usage")
-                .with_aggr_type(AggregateType::Sum)
-                .with_window(gas_limit.window);
+            let aggr = gas_usage_aggregate(gas_limit);
 
-            let total_gas_claim = ctx
-                .stats_tracker
-                .update_aggr(rule_meta.clone(), &aggr, ctx.transaction_budget as i64)
-                .await
-                .context("Updating aggregate failed")?;
+            let total_gas_claim = count_gas_claim(ctx, &rule_meta, &aggr, counted, dry_run).await?;
 
             let confirmation_request = GasUsageConfirmationRequest {
                 rule_meta,
@@ -242,15 +796,121 @@ impl AccessRule {
         }
     }
 
-    fn match_rego_expression(&self, ctx: &TransactionContext) -> Result<bool, anyhow::Error> {
-        if let Some(rego_expression) = self.rego_expression.as_ref() {
-            let input_payload = RegoInputPayload::from_context(ctx);
-            let input_string = serde_json::to_string_pretty(&input_payload)
-                .context("Failed to serialize input payload to JSON")?;
-            trace!("\n\n Input string: {}", input_string);
+    /// Counts the gas budget for each package of the rule called by the transaction. Matches if
+    /// the gas usage of any of these packages matches the limit.
+    async fn match_package_gas_limit(
+        &self,
+        ctx: &TransactionContext,
+        counted: &[GasUsageConfirmationRequest],
+        dry_run: bool,
+    ) -> Result<(bool, Vec<GasUsageConfirmationRequest>), anyhow::Error> {
+        let Some(package_gas_limit) = self.package_gas_limit.as_ref() else {
+            // If the package gas limit is not defined then the rule matches
+            return Ok((true, vec![]));
+        };
+        let Some(packages) = self.move_call_package_address.as_ref() else {
+            bail!("package-gas-limit requires move-call-package-address");
+        };
+        let mut called_packages: Vec<&IotaAddress> = ctx
+            .move_call_package_addresses
+            .iter()
+            .filter(|package| packages.includes(package))
+            .collect();
+        called_packages.sort();
+        called_packages.dedup();
+
+        let aggr = gas_usage_aggregate(package_gas_limit);
+        let mut matched = false;
+        let mut confirmation_requests = vec![];
+        for package in called_packages {
+            let rule_meta = self
+                .get_package_rule_meta(package_gas_limit, &ctx.sender_address, package)
+                .context("Failed to calculate rule meta")?;
+            let total_gas_claim = count_gas_claim(ctx, &rule_meta, &aggr, counted, dry_run).await?;
+            matched |= package_gas_limit.value.matches(total_gas_claim as u64);
+            confirmation_requests.push(GasUsageConfirmationRequest {
+                rule_meta,
+                aggregate: aggr.clone(),
+                gas_usage: ctx.transaction_budget,
+            });
+        }
+        Ok((matched, confirmation_requests))
+    }
+
+    /// Returns the current gas usage counted by the rule for the sender, without updating it.
+    /// Returns `None` if the rule has no gas usage limit or doesn't apply to the sender.
+    pub async fn get_gas_usage(
+        &self,
+        sender_address: &IotaAddress,
+        stats_tracker: &StatsTracker,
+    ) -> Result<Option<AggregateValue>, anyhow::Error> {
+        let Some((rule_meta, aggr)) = self.gas_usage_counter(sender_address)? else {
+            return Ok(None);
+        };
+        let value = stats_tracker
+            .get_aggr(rule_meta, &aggr)
+            .await
+            .context("Reading aggregate failed")?;
+        Ok(Some(value))
+    }
+
+    /// Resets the gas usage counted by the rule for the sender.
+    pub async fn reset_gas_usage(
+        &self,
+        sender_address: &IotaAddress,
+        stats_tracker: &StatsTracker,
+    ) -> Result<(), anyhow::Error> {
+        let (rule_meta, aggr) = self
+            .gas_usage_counter(sender_address)?
+            .context("The rule doesn't limit the gas usage of the sender")?;
+        stats_tracker
+            .reset_aggr(rule_meta, &aggr)
+            .await
+            .context("Resetting aggregate failed")
+    }
+
+    /// Subtracts `amount` from the gas usage counted by the rule for the sender. The gas usage can
+    /// become negative, leaving the sender more than the limit within the current window.
+    pub async fn credit_gas_usage(
+        &self,
+        sender_address: &IotaAddress,
+        amount: u64,
+        stats_tracker: &StatsTracker,
+    ) -> Result<(), anyhow::Error> {
+        let (rule_meta, aggr) = self
+            .gas_usage_counter(sender_address)?
+            .context("The rule doesn't limit the gas usage of the sender")?;
+        let amount = i64::try_from(amount).context("The amount is too large")?;
+        stats_tracker
+            .update_aggr(rule_meta, &aggr, -amount)
+            .await
+            .context("Updating aggregate failed")?;
+        Ok(())
+    }
 
+    /// Returns the key and the aggregate of the gas usage counted for the sender, if the rule has
+    /// a gas usage limit and applies to the sender.
+    fn gas_usage_counter(
+        &self,
+        sender_address: &IotaAddress,
+    ) -> Result<Option<(Map<String, Value>, Aggregate)>, anyhow::Error> {
+        let Some(gas_limit) = self.gas_usage.as_ref() else {
+            return Ok(None);
+        };
+        if !self.sender_address.includes(sender_address) {
+            return Ok(None);
+        }
+        let rule_meta = self
+            .get_rule_meta(sender_address)
+            .context("Failed to calculate rule meta")?;
+        Ok(Some((rule_meta, gas_usage_aggregate(gas_limit))))
+    }
+
+    async fn match_rego_expression(&self, ctx: &TransactionContext) -> Result<bool, anyhow::Error> {
+        if let Some(rego_expression) = self.rego_expression.as_ref() {
             let result = rego_expression
-                .matches(&input_string)
+                .evaluate(ctx.rego_input()?)
+                .await
                 .context("Failed to match rego expression")?;
 
             return Ok(result);
@@ -260,13 +920,177 @@ impl AccessRule {
     }
 }
 
-impl AccessRule {
-    fn ptb_command_count_matches_or_not_applicable(&self, data: &TransactionContext) -> bool {
-        match (self.ptb_command_count, data.ptb_command_count) {
-            (Some(criteria), Some(value)) => criteria.matches(value),
-            _ => true,
+fn insert_count_by(
+    rule_to_hash: &mut Map<String, Value>,
+    gas_limit: &ValueAggregate,
+    sender_address: &IotaAddress,
+) {
+    for count_by in gas_limit.count_by.iter() {
+        let count_by_value = match count_by {
+            LimitBy::SenderAddress => sender_address.to_string(),
+        };
+        rule_to_hash.insert(count_by.to_string(), Value::String(count_by_value));
+    }
+}
+
+fn gas_usage_aggregate(gas_limit: &ValueAggregate) -> Aggregate {
+    let aggr_type = match gas_limit.window_mode {
+        WindowMode::Fixed => AggregateType::Sum,
+        WindowMode::Sliding => AggregateType::SlidingSum,
+    };
+    Aggregate::with_name("gas_usage")
+        .with_aggr_type(aggr_type)
+        .with_window(gas_limit.window)
+}
+
+/// Returns the gas usage counted by a limit, including the gas budget of the transaction. The
+/// budget is counted once per transaction, as given by the `counted` confirmation requests, and
+/// isn't counted at all by a dry run.
+async fn count_gas_claim(
+    ctx: &TransactionContext,
+    rule_meta: &Map<String, Value>,
+    aggr: &Aggregate,
+    counted: &[GasUsageConfirmationRequest],
+    dry_run: bool,
+) -> Result<i64, anyhow::Error> {
+    let already_counted = counted.iter().any(|req| &req.rule_meta == rule_meta);
+    if already_counted || dry_run {
+        let value = ctx
+            .stats_tracker
+            .get_aggr(rule_meta.clone(), aggr)
+            .await
+            .context("Reading aggregate failed")?
+            .value;
+        if already_counted {
+            Ok(value)
+        } else {
+            Ok(value.saturating_add(ctx.transaction_budget as i64))
+        }
+    } else {
+        ctx.stats_tracker
+            .update_aggr(rule_meta.clone(), aggr, ctx.transaction_budget as i64)
+            .await
+            .context("Updating aggregate failed")
+    }
+}
+
+fn count_matches_or_not_applicable(
+    criteria: Option<ValueNumber<usize>>,
+    value: Option<usize>,
+) -> bool {
+    match (criteria, value) {
+        (Some(criteria), Some(value)) => criteria.matches(value),
+        _ => true,
+    }
+}
+
+/// Fails when the reference gas price is unknown, since neither matching nor not matching would
+/// be safe for both the allow and the deny rules.
+fn gas_price_ratio_matches_or_not_applicable(
+    criteria: Option<ValueNumber<f64>>,
+    data: &TransactionContext,
+) -> Result<bool, anyhow::Error> {
+    match (criteria, data.reference_gas_price) {
+        (None, _) => Ok(true),
+        (Some(criteria), Some(reference_gas_price)) if reference_gas_price > 0 => {
+            Ok(criteria.matches(data.gas_price as f64 / reference_gas_price as f64))
+        }
+        _ => bail!("gas-price-ratio can't be evaluated, the reference gas price is unknown"),
+    }
+}
+
+fn any_group_matches(
+    groups: &[RuleGroup],
+    data: &TransactionContext,
+    now: DateTime<Utc>,
+) -> Result<bool, anyhow::Error> {
+    for group in groups {
+        if group.matches(data, now)? {
+            return Ok(true);
         }
     }
+    Ok(false)
+}
+
+/// A group of predicates within the `any-of` block of a rule. All the predicates of the group
+/// must match, like in a rule. Unknown fields are rejected, since a predicate silently ignored
+/// would make the group match more transactions than intended.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RuleGroup {
+    pub sender_address: Option<ValueIotaAddress>,
+    pub not_sender_address: Option<ValueIotaAddress>,
+    pub transaction_gas_budget: Option<ValueNumber<u64>>,
+    pub move_call_package_address: Option<ValueIotaAddress>,
+    pub not_move_call_package_address: Option<ValueIotaAddress>,
+    pub object_id: Option<ValueIotaAddress>,
+    pub coin_type: Option<ValueCoinType>,
+    pub ptb_command_count: Option<ValueNumber<usize>>,
+    pub tx_size_bytes: Option<ValueNumber<usize>>,
+    pub input_object_count: Option<ValueNumber<usize>>,
+    pub gas_price: Option<ValueNumber<u64>>,
+    pub gas_price_ratio: Option<ValueNumber<f64>>,
+    pub schedule: Option<Schedule>,
+    pub header: Option<ValueHeader>,
+    pub client_country: Option<ValueCountry>,
+}
+
+impl RuleGroup {
+    pub fn matches(
+        &self,
+        data: &TransactionContext,
+        now: DateTime<Utc>,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(self
+            .sender_address
+            .as_ref()
+            .is_none_or(|address| address.includes(&data.sender_address))
+            && self
+                .not_sender_address
+                .as_ref()
+                .is_none_or(|address| !address.includes(&data.sender_address))
+            && self
+                .transaction_gas_budget
+                .is_none_or(|budget| budget.matches(data.transaction_budget))
+            && self
+                .move_call_package_address
+                .as_ref()
+                .is_none_or(|address| address.includes_any(&data.move_call_package_addresses))
+            && self
+                .not_move_call_package_address
+                .as_ref()
+                .is_none_or(|address| !address.includes_any(&data.move_call_package_addresses))
+            && self
+                .object_id
+                .as_ref()
+                .is_none_or(|object_id| object_id.includes_any(&data.input_object_ids))
+            && self
+                .coin_type
+                .as_ref()
+                .is_none_or(|coin_type| coin_type.includes_any(&data.coin_types))
+            && count_matches_or_not_applicable(self.ptb_command_count, data.ptb_command_count)
+            && self
+                .tx_size_bytes
+                .is_none_or(|size| size.matches(data.tx_size_bytes))
+            && count_matches_or_not_applicable(self.input_object_count, data.input_object_count)
+            && self
+                .gas_price
+                .is_none_or(|price| price.matches(data.gas_price))
+            && gas_price_ratio_matches_or_not_applicable(self.gas_price_ratio, data)?
+            && self
+                .schedule
+                .as_ref()
+                .is_none_or(|schedule| schedule.matches(now))
+            && self
+                .header
+                .as_ref()
+                .is_none_or(|header| header.matches(&data.headers))
+            && self
+                .client_country
+                .as_ref()
+                .is_none_or(|country| country.includes(data.client_country.as_deref())))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -289,8 +1113,25 @@ pub struct TransactionContext {
     pub sender_address: IotaAddress,
     pub transaction_budget: u64,
     pub move_call_package_addresses: Vec<IotaAddress>,
+    /// Package, module and function of each move call.
+    pub move_calls: Vec<(ObjectID, String, String)>,
+    /// Objects in the inputs of the programmable transaction, gas coins excluded.
+    pub input_object_ids: Vec<IotaAddress>,
+    /// Coin types moved by the transaction, see [`get_coin_types`].
+    pub coin_types: Vec<TypeTag>,
+    /// Objects used by the coin commands, whose coin type is only known by the fullnode.
+    pub coin_object_ids: Vec<ObjectID>,
     pub ptb_command_count: Option<usize>,
+    /// Size of the BCS serialized transaction data.
+    pub tx_size_bytes: usize,
+    /// Number of objects in the inputs of the programmable transaction, gas coins excluded.
+    pub input_object_count: Option<usize>,
+    pub gas_price: u64,
+    /// The current reference gas price, if it could be fetched.
+    pub reference_gas_price: Option<u64>,
     pub transaction_data: Value,
+    /// The input of the Rego expressions, built by the first rule evaluating one.
+    pub(crate) rego_input: Arc<OnceCell<RegoInput>>,
 
     pub stats_tracker: StatsTracker,
     pub reservation_id: u64,
@@ -298,6 +1139,8 @@ pub struct TransactionContext {
     pub user_sig: Base64,
     pub request_type: Option<ExecuteTransactionRequestType>,
     pub headers: HeaderMap,
+    /// ISO 3166-1 alpha-2 code of the country of the client, if it could be resolved.
+    pub client_country: Option<String>,
 }
 
 #[cfg(test)]
@@ -307,10 +1150,19 @@ impl Default for TransactionContext {
             sender_address: IotaAddress::default(),
             transaction_budget: 0,
             move_call_package_addresses: vec![],
+            move_calls: vec![],
+            input_object_ids: vec![],
+            coin_types: vec![],
+            coin_object_ids: vec![],
             ptb_command_count: None,
+            tx_size_bytes: 0,
+            input_object_count: None,
+            gas_price: 0,
+            reference_gas_price: None,
             stats_tracker: crate::test_env::mocked_stats_tracker(),
             transaction_digest: TransactionDigest::default(),
             transaction_data: Value::Null,
+            rego_input: Default::default(),
             reservation_id: 0,
             tx_bytes: Base64::try_from(String::default())
                 .expect("empty string should be valid base64"),
@@ -318,6 +1170,7 @@ impl Default for TransactionContext {
                 .expect("empty string should be valid base64"),
             request_type: None,
             headers: HeaderMap::default(),
+            client_country: None,
         }
     }
 }
@@ -343,19 +1196,33 @@ impl TransactionContext {
         // TODO handle the error properly
         let transaction_value = serde_json::to_value(&transaction_data)
             .expect("Failed to convert transaction data to JSON value");
+        let (coin_types, coin_object_ids) = match transaction_data.kind() {
+            TransactionKind::ProgrammableTransaction(pt) => get_coin_types(pt),
+            _ => (vec![], vec![]),
+        };
         Self {
             transaction_digest: transaction_data.digest(),
             sender_address: transaction_data.sender().clone(),
             transaction_budget: transaction_data.gas_budget(),
             move_call_package_addresses: get_move_call_package_addresses(transaction_data),
+            move_calls: get_move_calls(transaction_data),
+            input_object_ids: get_input_object_ids(transaction_data),
+            coin_types,
+            coin_object_ids,
             ptb_command_count,
+            tx_size_bytes: bcs::serialized_size(transaction_data).unwrap_or_default(),
+            input_object_count: get_input_object_count(transaction_data),
+            gas_price: transaction_data.gas_price(),
+            reference_gas_price: None,
             stats_tracker,
             transaction_data: transaction_value,
+            rego_input: Default::default(),
             reservation_id,
             tx_bytes,
             user_sig,
             request_type,
             headers,
+            client_country: None,
         }
     }
 
@@ -377,18 +1244,76 @@ impl TransactionContext {
         self
     }
 
+    pub fn with_input_object_ids(mut self, input_object_ids: Vec<IotaAddress>) -> Self {
+        self.input_object_ids = input_object_ids;
+        self
+    }
+
+    pub fn with_coin_types(mut self, coin_types: Vec<TypeTag>) -> Self {
+        self.coin_types = coin_types;
+        self
+    }
+
+    /// Adds the coin types of the objects used by the coin commands, as given by the fullnode.
+    pub fn with_coin_object_types(mut self, object_types: &HashMap<ObjectID, TypeTag>) -> Self {
+        for object_id in &self.coin_object_ids {
+            let Some(coin_type) = object_types.get(object_id).and_then(coin_inner_type) else {
+                continue;
+            };
+            if !self.coin_types.contains(coin_type) {
+                self.coin_types.push(coin_type.clone());
+            }
+        }
+        self
+    }
+
     pub fn with_ptb_command_count(mut self, ptb_count: usize) -> Self {
         self.ptb_command_count = Some(ptb_count);
         self
     }
 
+    pub fn with_tx_size_bytes(mut self, tx_size_bytes: usize) -> Self {
+        self.tx_size_bytes = tx_size_bytes;
+        self
+    }
+
+    pub fn with_input_object_count(mut self, input_object_count: usize) -> Self {
+        self.input_object_count = Some(input_object_count);
+        self
+    }
+
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    pub fn with_reference_gas_price(mut self, reference_gas_price: u64) -> Self {
+        self.reference_gas_price = Some(reference_gas_price);
+        self
+    }
+
     pub fn with_stats_tracker(mut self, stats_tracker: StatsTracker) -> Self {
         self.stats_tracker = stats_tracker;
         self
     }
 
+    /// Returns the input of the Rego expressions, so that it's serialized and parsed only once
+    /// for all the rules.
+    pub fn rego_input(&self) -> Result<RegoInput, anyhow::Error> {
+        self.rego_input
+            .get_or_try_init(|| {
+                let input_payload = RegoInputPayload::from_context(self);
+                let input_string = serde_json::to_string(&input_payload)
+                    .context("Failed to serialize input payload to JSON")?;
+                trace!("\n\n Input string: {}", input_string);
+                RegoInput::from_json_str(&input_string)
+            })
+            .cloned()
+    }
+
     pub fn with_transaction_data(mut self, transaction_data: Value) -> Self {
         self.transaction_data = transaction_data;
+        self.rego_input = Default::default();
         self
     }
 
@@ -416,6 +1341,96 @@ impl TransactionContext {
         self.headers = headers;
         self
     }
+
+    pub fn with_client_country(mut self, client_country: Option<String>) -> Self {
+        self.client_country = client_country;
+        self
+    }
+
+    /// ID of the `execute_tx` request, see [`REQUEST_ID_HEADER`].
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+    }
+}
+
+fn get_input_object_count(transaction_data: &TransactionData) -> Option<usize> {
+    match transaction_data.kind() {
+        TransactionKind::ProgrammableTransaction(pt) => Some(
+            pt.inputs
+                .iter()
+                .filter(|input| matches!(input, CallArg::Object(_)))
+                .count(),
+        ),
+        _ => None,
+    }
+}
+
+fn get_input_object_ids(transaction_data: &TransactionData) -> Vec<IotaAddress> {
+    let TransactionKind::ProgrammableTransaction(pt) = transaction_data.kind() else {
+        return vec![];
+    };
+    pt.inputs
+        .iter()
+        .filter_map(|input| match input {
+            CallArg::Object(ObjectArg::ImmOrOwnedObject((id, _, _)))
+            | CallArg::Object(ObjectArg::Receiving((id, _, _)))
+            | CallArg::Object(ObjectArg::SharedObject { id, .. }) => Some(IotaAddress::from(*id)),
+            CallArg::Pure(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the coin types moved by the programmable transaction, i.e. the type arguments of its
+/// move calls, the coin vectors it makes and IOTA if it uses the gas coin, along with the input
+/// objects used by its coin commands, whose coin type must be queried from the fullnode.
+fn get_coin_types(pt: &ProgrammableTransaction) -> (Vec<TypeTag>, Vec<ObjectID>) {
+    let mut coin_types = vec![];
+    let mut coin_object_ids = vec![];
+    let mut add_coin_type = |coin_type: &TypeTag| {
+        let coin_type = coin_inner_type(coin_type).unwrap_or(coin_type);
+        if !coin_types.contains(coin_type) {
+            coin_types.push(coin_type.clone());
+        }
+    };
+    let mut coin_args = vec![];
+    for command in &pt.commands {
+        match command {
+            Command::MoveCall(call) => call.type_arguments.iter().for_each(&mut add_coin_type),
+            Command::MakeMoveVec(Some(type_tag), _) => {
+                if let Some(coin_type) = coin_inner_type(type_tag) {
+                    add_coin_type(coin_type);
+                }
+            }
+            Command::SplitCoins(coin, _) => coin_args.push(coin),
+            Command::MergeCoins(coin, coins) => {
+                coin_args.push(coin);
+                coin_args.extend(coins);
+            }
+            Command::TransferObjects(objects, _) => coin_args.extend(objects),
+            _ => {}
+        }
+    }
+    for arg in coin_args {
+        match arg {
+            Argument::GasCoin => add_coin_type(&GAS::type_tag()),
+            Argument::Input(index) => {
+                let object_id = match pt.inputs.get(*index as usize) {
+                    Some(CallArg::Object(ObjectArg::ImmOrOwnedObject((id, _, _))))
+                    | Some(CallArg::Object(ObjectArg::Receiving((id, _, _))))
+                    | Some(CallArg::Object(ObjectArg::SharedObject { id, .. })) => *id,
+                    _ => continue,
+                };
+                if !coin_object_ids.contains(&object_id) {
+                    coin_object_ids.push(object_id);
+                }
+            }
+            // The results of other commands are covered by the type arguments of the commands
+            _ => {}
+        }
+    }
+    (coin_types, coin_object_ids)
 }
 
 fn get_move_call_package_addresses(transaction_data: &TransactionData) -> Vec<IotaAddress> {
@@ -427,26 +1442,45 @@ fn get_move_call_package_addresses(transaction_data: &TransactionData) -> Vec<Io
         .collect()
 }
 
+fn get_move_calls(transaction_data: &TransactionData) -> Vec<(ObjectID, String, String)> {
+    let TransactionData::V1(data_v1) = transaction_data;
+    data_v1
+        .move_calls()
+        .into_iter()
+        .map(|(package, module, function)| (*package, module.to_string(), function.to_string()))
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
 
-    use std::vec;
+    use std::{collections::HashMap, vec};
 
+    use axum::http::{HeaderMap, HeaderValue};
+    use chrono::{Duration, Utc};
     use iota_types::{
-        base_types::IotaAddress,
+        base_types::{IotaAddress, ObjectID, SequenceNumber},
+        digests::ObjectDigest,
+        gas_coin::GAS,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
         transaction::{
-            GasData, ProgrammableTransaction, TransactionData, TransactionDataAPI,
-            TransactionDataV1, TransactionExpiration, TransactionKind,
+            Argument, CallArg, Command, GasData, ObjectArg, ProgrammableTransaction,
+            TransactionData, TransactionDataAPI, TransactionDataV1, TransactionExpiration,
+            TransactionKind,
         },
+        TypeTag,
     };
 
     use crate::{
         access_controller::{
             predicates::{
                 Action, LimitBy, Location, RegoExpression, SourceWithData, ValueAggregate,
-                ValueIotaAddress, ValueNumber,
+                ValueCountry, ValueHeader, ValueIotaAddress, ValueNumber, WindowMode,
+            },
+            rule::{
+                get_coin_types, get_input_object_count, get_input_object_ids, AccessRule,
+                AccessRuleBuilder, TransactionContext,
             },
-            rule::{AccessRule, AccessRuleBuilder, TransactionContext},
         },
         test_env::{new_stats_tracker_for_testing, random_address},
     };
@@ -500,6 +1534,83 @@ mod test {
         assert!(!rule.matches(&unmatched_data).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_constraint_negated_addresses() {
+        let excluded_sender = IotaAddress::new([1; 32]);
+        let excluded_package_id = IotaAddress::new([2; 32]);
+        let other_package_id = IotaAddress::new([3; 32]);
+
+        let rule = AccessRuleBuilder::new()
+            .not_sender_address(excluded_sender)
+            .not_move_call_package_address(excluded_package_id)
+            .build();
+
+        let matched_data = TransactionContext::default()
+            .with_sender_address(IotaAddress::new([0; 32]))
+            .with_move_call_package_addresses(vec![other_package_id]);
+        let no_move_call_data = TransactionContext::default();
+        let excluded_sender_data = TransactionContext::default()
+            .with_sender_address(excluded_sender)
+            .with_move_call_package_addresses(vec![other_package_id]);
+        let excluded_package_data = TransactionContext::default()
+            .with_move_call_package_addresses(vec![other_package_id, excluded_package_id]);
+
+        assert!(rule.matches(&matched_data).await.unwrap());
+        assert!(rule.matches(&no_move_call_data).await.unwrap());
+        assert!(!rule.matches(&excluded_sender_data).await.unwrap());
+        assert!(!rule.matches(&excluded_package_data).await.unwrap());
+
+        let rule: AccessRule = serde_yaml::from_str(
+            "not-sender-address: '0x0101010101010101010101010101010101010101010101010101010101010101'\naction: deny\n",
+        )
+        .unwrap();
+        assert_eq!(rule.not_sender_address, Some([excluded_sender].into()));
+    }
+
+    #[tokio::test]
+    async fn test_constraint_any_of() {
+        let sender_address = IotaAddress::new([1; 32]);
+        let package_id = IotaAddress::new([2; 32]);
+        let rule: AccessRule = serde_yaml::from_str(
+            "transaction-gas-budget: '<=1000'
+any-of:
+  - sender-address: '0x0101010101010101010101010101010101010101010101010101010101010101'
+  - move-call-package-address: '0x0202020202020202020202020202020202020202020202020202020202020202'
+    ptb-command-count: '<=2'
+action: allow
+",
+        )
+        .unwrap();
+        assert_eq!(rule.any_of.len(), 2);
+
+        let sender_data = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(1000);
+        let package_data = TransactionContext::default()
+            .with_move_call_package_addresses(vec![package_id])
+            .with_ptb_command_count(2)
+            .with_gas_budget(1000);
+        let too_many_commands_data = TransactionContext::default()
+            .with_move_call_package_addresses(vec![package_id])
+            .with_ptb_command_count(3)
+            .with_gas_budget(1000);
+        let over_budget_data = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(1001);
+
+        assert!(rule.matches(&sender_data).await.unwrap());
+        assert!(rule.matches(&package_data).await.unwrap());
+        assert!(!rule.matches(&too_many_commands_data).await.unwrap());
+        assert!(!rule.matches(&over_budget_data).await.unwrap());
+        assert!(!rule.matches(&TransactionContext::default()).await.unwrap());
+
+        // The stateful predicates are not supported within the groups
+        assert!(serde_yaml::from_str::<AccessRule>(
+            "any-of:\n  - gas-usage:\n      value: 100\naction: allow\n"
+        )
+        .is_err());
+    }
+
     #[tokio::test]
     async fn test_constraint_mix_ups_sender_budget_package_address() {
         let sender_address = IotaAddress::new([1; 32]);
@@ -535,6 +1646,51 @@ mod test {
         assert!(!rule.matches(&unmatched_data_gas_limit).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_constraint_validity_window() {
+        let now = Utc::now();
+        let rule = AccessRuleBuilder::new()
+            .valid_from(now)
+            .valid_until(now + Duration::hours(1))
+            .build();
+
+        assert!(!rule.is_valid_at(now - Duration::seconds(1)));
+        assert!(rule.is_valid_at(now));
+        assert!(rule.is_valid_at(now + Duration::minutes(59)));
+        assert!(!rule.is_valid_at(now + Duration::hours(1)));
+
+        let expired_rule = AccessRuleBuilder::new()
+            .valid_until(now - Duration::seconds(1))
+            .build();
+        assert!(!expired_rule
+            .matches(&TransactionContext::default())
+            .await
+            .unwrap());
+
+        let rule: AccessRule = serde_yaml::from_str(
+            "valid-from: 2025-06-01T00:00:00Z\nvalid-until: 2025-07-01T00:00:00Z\naction: allow\n",
+        )
+        .unwrap();
+        assert!(rule.is_valid_at("2025-06-15T12:00:00Z".parse().unwrap()));
+        assert!(!rule.is_valid_at("2025-07-01T00:00:00Z".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_rule_meta_ignores_validity_and_name() {
+        let sender = random_address();
+        let rule = AccessRuleBuilder::new().sender_address(sender).build();
+        let campaign_rule = AccessRuleBuilder::new()
+            .sender_address(sender)
+            .name("campaign")
+            .valid_until(Utc::now())
+            .build();
+
+        assert_eq!(
+            rule.get_rule_meta(&sender).unwrap(),
+            campaign_rule.get_rule_meta(&sender).unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_constraint_ptb_count_matches() {
         let rule = super::AccessRule {
@@ -554,6 +1710,234 @@ mod test {
             .unwrap());
     }
 
+    #[tokio::test]
+    async fn test_constraint_tx_size_and_input_objects() {
+        let rule: AccessRule = serde_yaml::from_str(
+            "tx-size-bytes: '>65536'\ninput-object-count: '>=2'\naction: deny\n",
+        )
+        .unwrap();
+
+        let large_data = TransactionContext::default()
+            .with_tx_size_bytes(100_000)
+            .with_input_object_count(3);
+        let small_data = TransactionContext::default()
+            .with_tx_size_bytes(1_000)
+            .with_input_object_count(3);
+        let few_objects_data = TransactionContext::default()
+            .with_tx_size_bytes(100_000)
+            .with_input_object_count(1);
+
+        assert!(rule.matches(&large_data).await.unwrap());
+        assert!(!rule.matches(&small_data).await.unwrap());
+        assert!(!rule.matches(&few_objects_data).await.unwrap());
+    }
+
+    #[test]
+    fn test_get_input_object_count() {
+        let object_ref = (
+            ObjectID::random(),
+            SequenceNumber::new(),
+            ObjectDigest::random(),
+        );
+        let transaction_data = TransactionData::V1(TransactionDataV1 {
+            kind: TransactionKind::ProgrammableTransaction(ProgrammableTransaction {
+                commands: vec![],
+                inputs: vec![
+                    CallArg::Object(ObjectArg::ImmOrOwnedObject(object_ref)),
+                    CallArg::Pure(vec![1]),
+                    CallArg::Object(ObjectArg::SharedObject {
+                        id: ObjectID::random(),
+                        initial_shared_version: SequenceNumber::new(),
+                        mutable: true,
+                    }),
+                ],
+            }),
+            expiration: TransactionExpiration::None,
+            gas_data: GasData {
+                payment: vec![object_ref],
+                owner: IotaAddress::default(),
+                budget: 0,
+                price: 0,
+            },
+            sender: IotaAddress::default(),
+        });
+
+        // The gas coins are not counted
+        assert_eq!(get_input_object_count(&transaction_data), Some(2));
+        assert_eq!(get_input_object_ids(&transaction_data).len(), 2);
+        assert_eq!(
+            get_input_object_ids(&transaction_data)[0],
+            IotaAddress::from(object_ref.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_constraint_coin_type() {
+        let token: TypeTag = "0x3::token::TOKEN".parse().unwrap();
+        let rule: AccessRule =
+            serde_yaml::from_str("coin-type: ['0x3::token::TOKEN']\naction: deny\n").unwrap();
+
+        let matched_data =
+            TransactionContext::default().with_coin_types(vec![GAS::type_tag(), token]);
+        let unmatched_data = TransactionContext::default().with_coin_types(vec![GAS::type_tag()]);
+
+        assert!(rule.matches(&matched_data).await.unwrap());
+        assert!(!rule.matches(&unmatched_data).await.unwrap());
+    }
+
+    #[test]
+    fn test_get_coin_types() {
+        let token: TypeTag = "0x3::token::TOKEN".parse().unwrap();
+        let coin_id = ObjectID::random();
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let coin = builder
+            .obj(ObjectArg::ImmOrOwnedObject((
+                coin_id,
+                SequenceNumber::new(),
+                ObjectDigest::random(),
+            )))
+            .unwrap();
+        let amount = builder.pure(10u64).unwrap();
+        builder.command(Command::SplitCoins(coin, vec![amount]));
+        builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+        builder.programmable_move_call(
+            ObjectID::random(),
+            "swap".parse().unwrap(),
+            "swap".parse().unwrap(),
+            vec![format!("0x2::coin::Coin<{}>", token).parse().unwrap()],
+            vec![],
+        );
+        let pt = builder.finish();
+
+        let (coin_types, coin_object_ids) = get_coin_types(&pt);
+        assert_eq!(coin_types, vec![token.clone(), GAS::type_tag()]);
+        assert_eq!(coin_object_ids, vec![coin_id]);
+
+        let other: TypeTag = "0x4::other::OTHER".parse().unwrap();
+        let object_types = HashMap::from([(
+            coin_id,
+            format!("0x2::coin::Coin<{}>", other).parse().unwrap(),
+        )]);
+        let mut ctx = TransactionContext::default().with_coin_types(coin_types);
+        ctx.coin_object_ids = coin_object_ids;
+        let ctx = ctx.with_coin_object_types(&object_types);
+        assert_eq!(ctx.coin_types, vec![token, GAS::type_tag(), other]);
+    }
+
+    #[tokio::test]
+    async fn test_constraint_object_id() {
+        let pool_object = random_address();
+        let rule = AccessRuleBuilder::new().object_id(pool_object).build();
+
+        let matched_data = TransactionContext::default()
+            .with_input_object_ids(vec![random_address(), pool_object]);
+        let unmatched_data =
+            TransactionContext::default().with_input_object_ids(vec![random_address()]);
+
+        assert!(rule.matches(&matched_data).await.unwrap());
+        assert!(!rule.matches(&unmatched_data).await.unwrap());
+        assert!(!rule.matches(&TransactionContext::default()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_constraint_gas_price() {
+        let rule = AccessRuleBuilder::new()
+            .gas_price(ValueNumber::LessThanOrEqual(2000))
+            .build();
+
+        assert!(rule
+            .matches(&TransactionContext::default().with_gas_price(1000))
+            .await
+            .unwrap());
+        assert!(!rule
+            .matches(&TransactionContext::default().with_gas_price(2001))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_constraint_gas_price_ratio() {
+        let rule: AccessRule =
+            serde_yaml::from_str("gas-price-ratio: '>2.5'\naction: deny\n").unwrap();
+        assert!(matches!(
+            rule.gas_price_ratio,
+            Some(ValueNumber::GreaterThan(ratio)) if ratio == 2.5
+        ));
+
+        let inflated_data = TransactionContext::default()
+            .with_gas_price(3000)
+            .with_reference_gas_price(1000);
+        let regular_data = TransactionContext::default()
+            .with_gas_price(1000)
+            .with_reference_gas_price(1000);
+        let unknown_reference_data = TransactionContext::default().with_gas_price(3000);
+
+        assert!(rule.matches(&inflated_data).await.unwrap());
+        assert!(!rule.matches(&regular_data).await.unwrap());
+        // Neither an allow nor a deny rule can be applied without the reference gas price
+        assert!(rule.matches(&unknown_reference_data).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_constraint_header() {
+        let mut rule: AccessRule = serde_yaml::from_str(
+            "header:
+  name: x-app-id
+  value: [wallet, game]
+any-of:
+  - header:
+      name: x-api-key
+  - gas-price: '<=1000'
+action: allow
+",
+        )
+        .unwrap();
+        assert_eq!(
+            rule.header,
+            Some(ValueHeader::new("x-app-id", ["wallet", "game"]))
+        );
+        rule.initialize().await.unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-app-id", HeaderValue::from_static("wallet"));
+        let app_data = TransactionContext::default()
+            .with_gas_price(2000)
+            .with_headers(headers.clone());
+        headers.insert("x-api-key", HeaderValue::from_static("secret"));
+        let api_key_data = TransactionContext::default()
+            .with_gas_price(2000)
+            .with_headers(headers.clone());
+        headers.insert("x-app-id", HeaderValue::from_static("other"));
+        let other_app_data = TransactionContext::default().with_headers(headers);
+
+        assert!(!rule.matches(&app_data).await.unwrap());
+        assert!(rule.matches(&api_key_data).await.unwrap());
+        assert!(!rule.matches(&other_app_data).await.unwrap());
+        assert!(!rule.matches(&TransactionContext::default()).await.unwrap());
+
+        let mut rule = AccessRuleBuilder::new()
+            .header(ValueHeader::new("x app id", ["wallet"]))
+            .allow()
+            .build();
+        assert!(rule.initialize().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_constraint_client_country() {
+        let rule: AccessRule =
+            serde_yaml::from_str("client-country: [kp, unknown]\naction: deny\n").unwrap();
+        assert_eq!(
+            rule.client_country,
+            Some(ValueCountry::new(["KP", "unknown"]))
+        );
+
+        let listed_data = TransactionContext::default().with_client_country(Some("KP".into()));
+        let other_data = TransactionContext::default().with_client_country(Some("CH".into()));
+        assert!(rule.matches(&listed_data).await.unwrap());
+        assert!(!rule.matches(&other_data).await.unwrap());
+        assert!(rule.matches(&TransactionContext::default()).await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_constraint_mix_ups_sender_package_address() {
         let sender_address = IotaAddress::new([1; 32]);
@@ -615,6 +1999,216 @@ mod test {
         assert!(!rule.match_global_limits(&unmatched_data).await.unwrap().0);
     }
 
+    #[tokio::test]
+    async fn test_constraint_gas_usage_counted_once() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(10),
+                ValueNumber::GreaterThanOrEqual(300),
+            ))
+            .deny()
+            .build();
+        let data = TransactionContext::default()
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+
+        let (matched, counted) = rule.match_global_limits_once(&data, &[]).await.unwrap();
+        assert!(!matched);
+        // The retried transaction isn't counted again
+        let (matched, _) = rule
+            .match_global_limits_once(&data, &counted)
+            .await
+            .unwrap();
+        assert!(!matched);
+        assert!(rule.match_global_limits(&data).await.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn test_constraint_package_gas_limit() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let dapp_package = random_address();
+        let other_dapp_package = random_address();
+        let mut rule = AccessRuleBuilder::new()
+            .move_call_package_address(dapp_package)
+            .move_call_package_address(other_dapp_package)
+            .package_gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(60),
+                ValueNumber::GreaterThan(300),
+            ))
+            .deny()
+            .build();
+        rule.initialize().await.unwrap();
+        let dapp_tx = |package| {
+            TransactionContext::default()
+                .with_sender_address(random_address())
+                .with_move_call_package_addresses(vec![package, random_address()])
+                .with_gas_budget(200)
+                .with_stats_tracker(stats_tracker.clone())
+        };
+
+        // The gas usage of each package is counted, whatever the sender
+        assert!(
+            !rule
+                .match_global_limits(&dapp_tx(dapp_package))
+                .await
+                .unwrap()
+                .0
+        );
+        assert!(
+            !rule
+                .match_global_limits(&dapp_tx(other_dapp_package))
+                .await
+                .unwrap()
+                .0
+        );
+        let (matched, counted) = rule
+            .match_global_limits(&dapp_tx(dapp_package))
+            .await
+            .unwrap();
+        assert!(matched);
+        assert_eq!(counted.len(), 1);
+
+        let mut rule_without_package = AccessRuleBuilder::new()
+            .package_gas_limit(ValueAggregate::new(
+                std::time::Duration::from_secs(60),
+                ValueNumber::GreaterThan(300),
+            ))
+            .build();
+        assert!(rule_without_package.initialize().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_constraint_gas_usage_sliding_window_matches() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let sender_address = random_address();
+
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(
+                ValueAggregate::new(
+                    std::time::Duration::from_secs(60),
+                    ValueNumber::GreaterThanOrEqual(300),
+                )
+                .with_count_by(vec![LimitBy::SenderAddress])
+                .with_window_mode(WindowMode::Sliding),
+            )
+            .deny()
+            .build();
+        let data = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+
+        assert!(!rule.match_global_limits(&data).await.unwrap().0);
+        assert!(rule.match_global_limits(&data).await.unwrap().0);
+        let usage = rule
+            .get_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.value, 400);
+    }
+
+    #[tokio::test]
+    async fn test_get_gas_usage() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let sender_address = random_address();
+        let other_address = random_address();
+
+        let rule = AccessRuleBuilder::new()
+            .sender_address(sender_address)
+            .gas_limit(
+                ValueAggregate::new(
+                    std::time::Duration::from_secs(10),
+                    ValueNumber::GreaterThanOrEqual(300),
+                )
+                .with_count_by(vec![LimitBy::SenderAddress]),
+            )
+            .deny()
+            .build();
+
+        let usage = rule
+            .get_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.value, 0);
+
+        let data = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+        rule.match_global_limits(&data).await.unwrap();
+
+        let usage = rule
+            .get_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.value, 200);
+        assert!(usage.resets_in.is_some());
+
+        // The rule doesn't apply to other senders
+        assert!(rule
+            .get_gas_usage(&other_address, &stats_tracker)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_credit_gas_usage() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let sender_address = random_address();
+
+        let rule = AccessRuleBuilder::new()
+            .gas_limit(
+                ValueAggregate::new(
+                    std::time::Duration::from_secs(10),
+                    ValueNumber::GreaterThanOrEqual(300),
+                )
+                .with_count_by(vec![LimitBy::SenderAddress]),
+            )
+            .deny()
+            .build();
+        let data = TransactionContext::default()
+            .with_sender_address(sender_address)
+            .with_gas_budget(200)
+            .with_stats_tracker(stats_tracker.clone());
+
+        // The second transaction crosses the limit
+        assert!(!rule.match_global_limits(&data).await.unwrap().0);
+        assert!(rule.match_global_limits(&data).await.unwrap().0);
+
+        rule.credit_gas_usage(&sender_address, 150, &stats_tracker)
+            .await
+            .unwrap();
+        let usage = rule
+            .get_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.value, 250);
+
+        rule.reset_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap();
+        let usage = rule
+            .get_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.value, 0);
+        assert!(!rule.match_global_limits(&data).await.unwrap().0);
+
+        // Rules without gas usage limit have no counter to adjust
+        let rule = AccessRuleBuilder::new().deny().build();
+        assert!(rule
+            .reset_gas_usage(&sender_address, &stats_tracker)
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_constraint_rego_expression() {
         let rego_content = r#"
@@ -658,7 +2252,7 @@ mod test {
         let unmatched_data = TransactionContext::default()
             .with_transaction_data(serde_json::to_value(&transaction_data).unwrap());
         assert!(matches!(
-            rule.match_rego_expression(&unmatched_data),
+            rule.match_rego_expression(&unmatched_data).await,
             Ok(false)
         ));
     }
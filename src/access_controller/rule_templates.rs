@@ -0,0 +1,277 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commented templates of access controller rules for common sponsorship policies, written into
+//! a config file by `iota-gas-station-tool generate-sample-rules`.
+
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+
+use super::AccessController;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RuleTemplate {
+    /// Sponsor the mint calls of an NFT collection, with a daily limit per sender.
+    NftMint,
+    /// Sponsor any transaction up to a daily gas quota per sender.
+    PerSenderQuota,
+    /// Sponsor only the calls to a list of Move packages.
+    PackageAllowlist,
+}
+
+impl RuleTemplate {
+    /// The rules as a YAML list, not indented.
+    pub fn rules(&self) -> &'static str {
+        match self {
+            RuleTemplate::NftMint => NFT_MINT_RULES,
+            RuleTemplate::PerSenderQuota => PER_SENDER_QUOTA_RULES,
+            RuleTemplate::PackageAllowlist => PACKAGE_ALLOWLIST_RULES,
+        }
+    }
+}
+
+const NFT_MINT_RULES: &str = r#"# nft-mint: sponsors the mint calls of an NFT collection. Meant for `access-policy: deny-all`.
+# Replace 0x0000...0000 by the package of the collection.
+# Denies the senders who used more than 0.05 IOTA of gas for mints in the last day.
+- name: nft-mint-daily-limit
+  sender-address: "*"
+  move-call-package-address: "0x0000000000000000000000000000000000000000000000000000000000000000"
+  gas-usage:
+    window: 1 day
+    value: ">50000000"
+    count-by: [sender-address]
+  deny-message: "Daily mint limit reached, try again tomorrow"
+  action: deny
+# Allows small mint transactions: a single move call, and a bounded gas budget.
+- name: nft-mint
+  sender-address: "*"
+  move-call-package-address: "0x0000000000000000000000000000000000000000000000000000000000000000"
+  ptb-command-count: "<=2"
+  transaction-gas-budget: "<=10000000"
+  action: allow
+  # Hook variant: let the mint backend decide, e.g. whether the sender is on the mint list.
+  # A hook response is reused for the same sender for 5 minutes.
+  # action: http://127.0.0.1:8080
+  # hook-cache:
+  #   ttl: 5m
+  #   key: sender-address
+"#;
+
+const PER_SENDER_QUOTA_RULES: &str = r#"# per-sender-quota: sponsors any transaction up to a daily gas quota per sender. Meant for
+# `access-policy: deny-all`.
+# Denies the senders who used more than 0.1 IOTA of gas in the last 24 hours.
+- name: per-sender-quota
+  sender-address: "*"
+  gas-usage:
+    window: 1 day
+    value: ">100000000"
+    count-by: [sender-address]
+    window-mode: sliding
+  deny-message: "Daily gas quota exceeded"
+  action: deny
+# Allows the other transactions, with a bounded gas budget.
+- name: within-quota
+  sender-address: "*"
+  transaction-gas-budget: "<=50000000"
+  action: allow
+# Rego variant: compute the quota in a policy instead, e.g. per tier of sender.
+# - name: per-sender-quota-rego
+#   sender-address: "*"
+#   rego-expression:
+#     location-type: file
+#     path: "./quota.rego"
+#     rego-rule-path: data.quota.exceeded
+#   action: deny
+"#;
+
+const PACKAGE_ALLOWLIST_RULES: &str = r#"# package-allowlist: sponsors only the calls to the listed Move packages. Meant for
+# `access-policy: deny-all`.
+# Replace 0x0000...0000 by the packages of your application.
+- name: package-allowlist
+  sender-address: "*"
+  move-call-package-address:
+    - "0x0000000000000000000000000000000000000000000000000000000000000000"
+  action: allow
+  # List variant: load a long list of packages from a file, one address per line.
+  # move-call-package-address:
+  #   location-type: file
+  #   path: "./allowed_packages.txt"
+# Rego variant: decide in a policy, e.g. allow some functions of a package only.
+# - name: package-allowlist-rego
+#   sender-address: "*"
+#   rego-expression:
+#     location-type: file
+#     path: "./packages.rego"
+#     rego-rule-path: data.packages.allowed
+#   action: allow
+"#;
+
+/// Adds the rules at the end of `access-controller.rules` of the config file, and keeps the rest
+/// of the file, including its comments, as is. The access controller is added with the
+/// `deny-all` policy if the file has none.
+pub fn insert_rules(config: &str, rules: &str) -> anyhow::Result<String> {
+    let mut lines: Vec<String> = config.lines().map(str::to_string).collect();
+    let Some(access_controller_line) = lines
+        .iter()
+        .position(|line| is_key_line(line, "access-controller", 0))
+    else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push("access-controller:".to_string());
+        lines.push("  access-policy: deny-all".to_string());
+        lines.push("  rules:".to_string());
+        lines.extend(indent(rules, 4));
+        return finish(lines);
+    };
+    let block_end = block_end(&lines, access_controller_line, 0);
+    let child_indent = lines[access_controller_line + 1..block_end]
+        .iter()
+        .find(|line| is_content(line))
+        .map_or(2, |line| indentation(line));
+    let rules_line = (access_controller_line + 1..block_end)
+        .find(|&index| is_key_line(&lines[index], "rules", child_indent));
+    match rules_line {
+        Some(rules_line) => {
+            let line = &lines[rules_line];
+            let value = line.trim().trim_start_matches("rules:").trim();
+            if value.starts_with("[]") {
+                lines[rules_line] = format!("{}rules:", " ".repeat(child_indent));
+            } else if !value.is_empty() && !value.starts_with('#') {
+                bail!("access-controller.rules must be a block list to add rules to it");
+            }
+            let rules_end = block_end_with_items(&lines, rules_line, child_indent);
+            let item_indent = lines[rules_line + 1..rules_end]
+                .iter()
+                .find(|line| is_content(line))
+                .map_or(child_indent + 2, |line| indentation(line));
+            lines.splice(rules_end..rules_end, indent(rules, item_indent));
+        }
+        None => {
+            let mut new_lines = vec![format!("{}rules:", " ".repeat(child_indent))];
+            new_lines.extend(indent(rules, child_indent + 2));
+            lines.splice(block_end..block_end, new_lines);
+        }
+    }
+    finish(lines)
+}
+
+/// Checks that the access controller of the new config can be loaded.
+fn finish(lines: Vec<String>) -> anyhow::Result<String> {
+    let config = lines.join("\n") + "\n";
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(&config).context("The config with the new rules is not valid YAML")?;
+    serde_yaml::from_value::<AccessController>(value["access-controller"].clone())
+        .context("The access controller with the new rules is not valid")?;
+    Ok(config)
+}
+
+fn indent(text: &str, indent: usize) -> Vec<String> {
+    let prefix = " ".repeat(indent);
+    text.lines()
+        .map(|line| format!("{}{}", prefix, line))
+        .collect()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Whether the line has YAML content, i.e. isn't blank or a comment.
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn is_key_line(line: &str, key: &str, indent: usize) -> bool {
+    indentation(line) == indent && line.trim_start().starts_with(&format!("{}:", key))
+}
+
+/// End of the block of the key at `start`: the first content line at most as indented, or the
+/// end of the file. The trailing blank lines and comments are left after the block.
+fn block_end(lines: &[String], start: usize, indent: usize) -> usize {
+    let end = (start + 1..lines.len())
+        .find(|&index| is_content(&lines[index]) && indentation(&lines[index]) <= indent)
+        .unwrap_or(lines.len());
+    (start + 1..end)
+        .rev()
+        .find(|&index| is_content(&lines[index]))
+        .map_or(start + 1, |index| index + 1)
+}
+
+/// Like [`block_end`], but the items of a list can be as indented as its key.
+fn block_end_with_items(lines: &[String], start: usize, indent: usize) -> usize {
+    let end = (start + 1..lines.len())
+        .find(|&index| {
+            let line = &lines[index];
+            is_content(line)
+                && (indentation(line) < indent
+                    || (indentation(line) == indent && !line.trim_start().starts_with('-')))
+        })
+        .unwrap_or(lines.len());
+    (start + 1..end)
+        .rev()
+        .find(|&index| is_content(&lines[index]))
+        .map_or(start + 1, |index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULE: &str = "- sender-address: \"*\"\n  action: deny\n";
+
+    fn rules_of(config: &str) -> Vec<serde_yaml::Value> {
+        let value: serde_yaml::Value = serde_yaml::from_str(config).unwrap();
+        value["access-controller"]["rules"]
+            .as_sequence()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_templates_are_valid() {
+        for template in RuleTemplate::value_variants() {
+            let config = insert_rules("rpc-port: 9527\n", template.rules()).unwrap();
+            assert!(!rules_of(&config).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_insert_rules() {
+        // Without access controller
+        let config = insert_rules("rpc-port: 9527\n", RULE).unwrap();
+        assert_eq!(rules_of(&config).len(), 1);
+        assert!(config.contains("access-policy: deny-all"));
+
+        // After the existing rules, keeping the comments and the following fields
+        let config = "\
+# The access controller
+access-controller:
+  access-policy: allow-all
+  rules:
+  - sender-address: \"0x0101010101010101010101010101010101010101010101010101010101010101\"
+    action: allow # comment
+
+# Daily cap
+daily-gas-usage-cap: 100
+";
+        let new_config = insert_rules(config, RULE).unwrap();
+        let rules = rules_of(&new_config);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1]["action"], "deny");
+        assert!(new_config.contains("action: allow # comment"));
+        assert!(new_config.contains("# Daily cap\ndaily-gas-usage-cap: 100"));
+
+        // Into an empty list
+        let config = "access-controller:\n    access-policy: deny-all\n    rules: []\n";
+        let new_config = insert_rules(config, RULE).unwrap();
+        assert_eq!(rules_of(&new_config).len(), 1);
+
+        // Without rules
+        let config = "access-controller:\n  access-policy: deny-all\nrpc-port: 9527\n";
+        let new_config = insert_rules(config, RULE).unwrap();
+        assert_eq!(rules_of(&new_config).len(), 1);
+        assert!(new_config.ends_with("rpc-port: 9527\n"));
+    }
+}
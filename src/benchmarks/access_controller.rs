@@ -0,0 +1,256 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::access_controller::decision::Decision;
+use crate::access_controller::predicates::Action;
+use crate::access_controller::rule::TransactionContext;
+use crate::access_controller::AccessController;
+use crate::rpc::rpc_types::ExecuteTxRequest;
+use crate::tracker::stats_tracker_storage::in_memory::InMemoryStatsTrackerStorage;
+use crate::tracker::StatsTracker;
+use anyhow::{anyhow, Context};
+use axum::http::HeaderMap;
+use fastcrypto::encoding::Base64;
+use iota_types::base_types::ObjectID;
+use iota_types::crypto::{get_account_key_pair, Signature, ToFromBytes};
+use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use iota_types::signature::GenericSignature;
+use iota_types::transaction::{TransactionData, TransactionKind};
+use rand::Rng;
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of senders and packages the synthetic transactions are spread over, so that the rules
+/// matching some senders or packages match some of the transactions.
+const NUM_SYNTHETIC_SENDERS: usize = 10;
+const NUM_SYNTHETIC_PACKAGES: usize = 5;
+
+pub struct AccessControllerBenchmarkReport {
+    pub num_transactions: usize,
+    pub evaluations_per_sec: f64,
+    /// Number of transactions per decision, and per error.
+    pub decisions: BTreeMap<String, usize>,
+    pub rules: Vec<RuleBenchmark>,
+}
+
+/// Cost of a rule, evaluated for every transaction regardless of the rules before it.
+pub struct RuleBenchmark {
+    pub rule: String,
+    pub num_matches: usize,
+    pub mean_match_latency: Duration,
+    pub max_match_latency: Duration,
+    /// Latency of the hook, called for the matching transactions.
+    pub mean_hook_latency: Option<Duration>,
+}
+
+/// Replays the transactions through `access_controller`, with the stats kept in memory. The
+/// notify actions are replaced by audit actions, so that no webhook is called, but the hooks are
+/// called as configured.
+pub async fn run_access_controller_benchmark(
+    mut access_controller: AccessController,
+    transactions: &[(TransactionData, GenericSignature)],
+    reference_gas_price: u64,
+) -> anyhow::Result<AccessControllerBenchmarkReport> {
+    access_controller.disable_notifications();
+    access_controller.initialize().await?;
+    let stats_tracker = StatsTracker::new(Arc::new(InMemoryStatsTrackerStorage::new()));
+    let contexts = transactions
+        .iter()
+        .enumerate()
+        .map(|(index, (tx_data, user_sig))| {
+            let ctx = TransactionContext::new(
+                user_sig,
+                tx_data,
+                stats_tracker.clone(),
+                index as u64,
+                Base64::from_bytes(&bcs::to_bytes(tx_data)?),
+                Base64::from_bytes(user_sig.as_ref()),
+                None,
+                HeaderMap::new(),
+            );
+            Ok(ctx.with_reference_gas_price(reference_gas_price))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut decisions = BTreeMap::new();
+    let start = Instant::now();
+    for ctx in &contexts {
+        let outcome = match access_controller.check_access(ctx).await {
+            Ok(Decision::Allow) => "allow",
+            Ok(Decision::Deny) => "deny",
+            Ok(Decision::Hold) => "hold",
+            Err(_) => "error",
+        };
+        *decisions.entry(outcome.to_string()).or_insert(0) += 1;
+    }
+    let evaluations_per_sec = contexts.len() as f64 / start.elapsed().as_secs_f64();
+
+    let mut rules = vec![];
+    for (i, rule) in access_controller.rules.iter().enumerate() {
+        let mut num_matches = 0;
+        let mut total_match_latency = Duration::ZERO;
+        let mut max_match_latency = Duration::ZERO;
+        let mut hook_latencies = vec![];
+        for ctx in &contexts {
+            let start = Instant::now();
+            let matched = rule.matches(ctx).await.unwrap_or(false);
+            let latency = start.elapsed();
+            total_match_latency += latency;
+            max_match_latency = max_match_latency.max(latency);
+            if !matched {
+                continue;
+            }
+            num_matches += 1;
+            if let Action::HookAction(hook_action) = &rule.action {
+                let start = Instant::now();
+                // A failing hook costs as much as an answering one.
+                let _ = hook_action.call_hook(ctx).await;
+                hook_latencies.push(start.elapsed());
+            }
+        }
+        rules.push(RuleBenchmark {
+            rule: rule.display_name(i + 1),
+            num_matches,
+            mean_match_latency: total_match_latency / contexts.len().max(1) as u32,
+            max_match_latency,
+            mean_hook_latency: (!hook_latencies.is_empty())
+                .then(|| hook_latencies.iter().sum::<Duration>() / hook_latencies.len() as u32),
+        });
+    }
+
+    Ok(AccessControllerBenchmarkReport {
+        num_transactions: contexts.len(),
+        evaluations_per_sec,
+        decisions,
+        rules,
+    })
+}
+
+impl AccessControllerBenchmarkReport {
+    pub fn print(&self) {
+        println!("Transactions: {}", self.num_transactions);
+        println!(
+            "Throughput: {:.2} evaluations/second",
+            self.evaluations_per_sec
+        );
+        for (decision, count) in &self.decisions {
+            println!(" - {}: {}", decision, count);
+        }
+        println!("Rules, each evaluated for every transaction:");
+        for rule in &self.rules {
+            print!(
+                " - {}: {} matches, mean {:?}, max {:?}",
+                rule.rule, rule.num_matches, rule.mean_match_latency, rule.max_match_latency
+            );
+            match rule.mean_hook_latency {
+                Some(latency) => println!(", hook mean {:?}", latency),
+                None => println!(),
+            }
+        }
+    }
+}
+
+/// Reads the transactions recorded in a file with an `execute_tx` request body per line.
+pub fn read_recorded_transactions(
+    path: &Path,
+) -> anyhow::Result<Vec<(TransactionData, GenericSignature)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let request: ExecuteTxRequest = serde_json::from_str(line)
+                .with_context(|| format!("Invalid execute_tx request on line {}", i + 1))?;
+            let tx_data = request
+                .tx_bytes
+                .to_vec()
+                .ok()
+                .and_then(|bytes| bcs::from_bytes(&bytes).ok())
+                .ok_or_else(|| anyhow!("Invalid transaction on line {}", i + 1))?;
+            let user_sig = request
+                .user_sig
+                .to_vec()
+                .ok()
+                .and_then(|bytes| GenericSignature::from_bytes(&bytes).ok())
+                .ok_or_else(|| anyhow!("Invalid user signature on line {}", i + 1))?;
+            Ok((tx_data, user_sig))
+        })
+        .collect()
+}
+
+/// Generates transactions of a few senders, with up to 3 move calls to a few packages and
+/// various gas budgets.
+pub fn synthetic_transactions(count: usize) -> Vec<(TransactionData, GenericSignature)> {
+    let mut rng = rand::thread_rng();
+    let senders: Vec<_> = (0..NUM_SYNTHETIC_SENDERS)
+        .map(|_| get_account_key_pair())
+        .collect();
+    let packages: Vec<_> = (0..NUM_SYNTHETIC_PACKAGES)
+        .map(|_| ObjectID::random())
+        .collect();
+    (0..count)
+        .map(|_| {
+            let (sender, keypair) = &senders[rng.gen_range(0..senders.len())];
+            let mut builder = ProgrammableTransactionBuilder::new();
+            for _ in 0..rng.gen_range(1..=3) {
+                builder.programmable_move_call(
+                    packages[rng.gen_range(0..packages.len())],
+                    "pool".parse().unwrap(),
+                    "swap".parse().unwrap(),
+                    vec![],
+                    vec![],
+                );
+            }
+            let tx_data = TransactionData::new_with_gas_coins(
+                TransactionKind::ProgrammableTransaction(builder.finish()),
+                *sender,
+                vec![],
+                rng.gen_range(1_000_000..50_000_000),
+                1000,
+            );
+            let intent_msg = IntentMessage::new(Intent::iota_transaction(), &tx_data);
+            let user_sig = Signature::new_secure(&intent_msg, keypair).into();
+            (tx_data, user_sig)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_controller::policy::AccessPolicy;
+    use crate::access_controller::rule::AccessRuleBuilder;
+
+    #[tokio::test]
+    async fn test_access_controller_benchmark() {
+        let transactions = synthetic_transactions(20);
+        let sender = transactions[0].0.sender();
+        let access_controller = AccessController::new(
+            AccessPolicy::DenyAll,
+            [AccessRuleBuilder::new()
+                .name("first-sender")
+                .sender_address(sender)
+                .allow()
+                .build()],
+        );
+
+        let report = run_access_controller_benchmark(access_controller, &transactions, 1000)
+            .await
+            .unwrap();
+        assert_eq!(report.num_transactions, 20);
+        let num_allowed = transactions
+            .iter()
+            .filter(|(tx_data, _)| tx_data.sender() == sender)
+            .count();
+        assert_eq!(report.decisions.get("allow").copied(), Some(num_allowed));
+        assert_eq!(report.rules.len(), 1);
+        assert_eq!(report.rules[0].rule, "first-sender");
+        assert_eq!(report.rules[0].num_matches, num_allowed);
+        assert!(report.rules[0].mean_hook_latency.is_none());
+    }
+}
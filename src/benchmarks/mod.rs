@@ -1,8 +1,12 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-pub mod kms_stress;
+pub mod access_controller;
+pub mod rego;
+pub mod signer_stress;
+pub mod soak;
 
+use crate::benchmarks::soak::run_soak_test;
 use crate::rpc::client::GasStationRpcClient;
 use clap::ValueEnum;
 use iota_config::node::DEFAULT_VALIDATOR_GAS_PRICE;
@@ -20,6 +24,8 @@ use tokio::time::{interval, Duration, Instant};
 pub enum BenchmarkMode {
     ReserveOnly,
     ReserveAndExecute,
+    /// Reserve and execute at a fixed request rate for a given duration, see [`run_soak_test`].
+    Soak,
 }
 
 #[derive(Clone, Default)]
@@ -47,7 +53,19 @@ impl BenchmarkMode {
         gas_station_url: String,
         reserve_duration_sec: u64,
         num_clients: u64,
+        target_rps: u64,
+        duration: Duration,
     ) {
+        if let Self::Soak = self {
+            return run_soak_test(
+                gas_station_url,
+                reserve_duration_sec,
+                target_rps,
+                duration,
+                num_clients as usize,
+            )
+            .await;
+        }
         let mut handles = vec![];
         let stats = Arc::new(RwLock::new(BenchmarkStatsPerSecond::default()));
         let client = GasStationRpcClient::new(gas_station_url);
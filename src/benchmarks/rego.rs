@@ -0,0 +1,118 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::access_controller::predicates::{Location, RegoExpression, RegoInput, SourceWithData};
+use crate::access_controller::rule::RegoInputPayload;
+use iota_types::base_types::{IotaAddress, ObjectID};
+use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use iota_types::transaction::{TransactionData, TransactionKind};
+use std::time::{Duration, Instant};
+
+/// Compares the throughput of a policy set of `num_rules` Rego expressions, evaluated for a
+/// transaction that matches none of them, as the access controller does:
+/// - by cloning the engine of each rule and parsing the input again for each rule
+/// - by reusing the compiled engines and parsing the input once per transaction
+pub fn run_rego_benchmark(num_rules: usize, duration: Duration) {
+    let policies: Vec<(Location, String)> = (0..num_rules)
+        .map(|i| {
+            let policy = format!(
+                r#"
+                package benchmark{i}
+
+                import rego.v1
+
+                default allow := false
+                allow if {{
+                    some command in input.transaction_data.V1.kind.ProgrammableTransaction.commands
+                    command.MoveCall.function == "function_{i}"
+                }}
+                "#
+            );
+            let location = Location::new_file(
+                format!("benchmark{i}.rego"),
+                format!("data.benchmark{i}.allow"),
+            );
+            (location, policy)
+        })
+        .collect();
+    let input_data = benchmark_input();
+
+    let engines: Vec<(String, regorus::Engine)> = policies
+        .iter()
+        .map(|(location, policy)| {
+            let mut engine = regorus::Engine::new();
+            engine
+                .add_policy(location.to_string(), policy.clone())
+                .expect("failed to add policy");
+            (location.get_rego_rule_path().to_string(), engine)
+        })
+        .collect();
+    let cloned_engines = run_for(duration, || {
+        for (rule, engine) in &engines {
+            let mut engine = engine.clone();
+            engine.set_input(regorus::Value::from_json_str(&input_data).unwrap());
+            engine.eval_rule(rule.clone()).unwrap();
+        }
+    });
+
+    let expressions: Vec<RegoExpression> = policies
+        .into_iter()
+        .map(|(location, policy)| {
+            let source = SourceWithData {
+                location,
+                data: Some(policy.into_bytes()),
+            };
+            RegoExpression::from_source(source).expect("failed to compile policy")
+        })
+        .collect();
+    let compiled_engines = run_for(duration, || {
+        let input = RegoInput::from_json_str(&input_data).unwrap();
+        for expression in &expressions {
+            expression.matches_input(input.clone()).unwrap();
+        }
+    });
+
+    println!("Rules per transaction: {}", num_rules);
+    println!("Cloned engines: {:.2} transactions/second", cloned_engines);
+    println!(
+        "Compiled engines: {:.2} transactions/second",
+        compiled_engines
+    );
+    println!("Speedup: {:.2}x", compiled_engines / cloned_engines);
+}
+
+/// Runs the evaluation of a transaction repeatedly, returning the number of runs per second.
+fn run_for(duration: Duration, mut evaluate: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut runs = 0u64;
+    while start.elapsed() < duration {
+        evaluate();
+        runs += 1;
+    }
+    runs as f64 / start.elapsed().as_secs_f64()
+}
+
+/// The Rego input of a transaction with a few move calls.
+fn benchmark_input() -> String {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    for function in ["deposit", "swap", "withdraw"] {
+        builder.programmable_move_call(
+            ObjectID::random(),
+            "pool".parse().unwrap(),
+            function.parse().unwrap(),
+            vec![],
+            vec![],
+        );
+    }
+    let tx_data = TransactionData::new_with_gas_coins(
+        TransactionKind::ProgrammableTransaction(builder.finish()),
+        IotaAddress::default(),
+        vec![],
+        1_000_000,
+        1000,
+    );
+    let payload = RegoInputPayload {
+        transaction_data: serde_json::to_value(&tx_data).unwrap(),
+    };
+    serde_json::to_string(&payload).unwrap()
+}
@@ -0,0 +1,172 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::SidecarSignerConfig;
+use crate::tx_signer::{SidecarTxSigner, TxSigner};
+use iota_types::base_types::{random_object_ref, IotaAddress};
+use iota_types::transaction::{ProgrammableTransaction, TransactionData, TransactionKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub async fn run_kms_stress_test(kms_url: String, num_tasks: usize) {
+    let signer = SidecarTxSigner::new(SidecarSignerConfig::new(kms_url)).await;
+    run_signer_stress_test(signer, num_tasks, Duration::from_secs(30))
+        .await
+        .print();
+}
+
+/// Stresses `signer`, and each of its members for a multisig, one after the other, so that the
+/// latency of every backend is reported separately.
+pub async fn run_signer_stress_tests(
+    signer: Arc<dyn TxSigner>,
+    num_tasks: usize,
+    duration: Duration,
+) {
+    for member in signer.members() {
+        run_signer_stress_test(member, num_tasks, duration)
+            .await
+            .print();
+    }
+    run_signer_stress_test(signer, num_tasks, duration)
+        .await
+        .print();
+}
+
+pub struct SignerStressReport {
+    pub backend: &'static str,
+    pub address: IotaAddress,
+    pub num_success: usize,
+    pub num_failures: usize,
+    pub elapsed: Duration,
+    /// Latencies of the successful calls, sorted.
+    pub latencies: Vec<Duration>,
+}
+
+/// Signs the same transaction with `signer` from `num_tasks` tasks for `duration`.
+pub async fn run_signer_stress_test(
+    signer: Arc<dyn TxSigner>,
+    num_tasks: usize,
+    duration: Duration,
+) -> SignerStressReport {
+    let test_tx_data = TransactionData::new(
+        TransactionKind::ProgrammableTransaction(ProgrammableTransaction {
+            inputs: vec![],
+            commands: vec![],
+        }),
+        IotaAddress::ZERO,
+        random_object_ref(),
+        1000,
+        1000,
+    );
+
+    // Shared atomic flag to signal workers to stop
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    // Vector to hold worker task handles
+    let mut handles = Vec::with_capacity(num_tasks);
+
+    for _ in 0..num_tasks {
+        let stop_flag = Arc::clone(&stop_flag);
+        let signer = signer.clone();
+        let test_tx_data = test_tx_data.clone(); // Clone the test data
+
+        // Spawn a worker task, returning the latencies of its successful calls and its number of
+        // failed calls
+        let handle = tokio::spawn(async move {
+            let mut latencies = vec![];
+            let mut num_failures = 0;
+            while !stop_flag.load(Ordering::Relaxed) {
+                let start = Instant::now();
+                match signer.sign_transaction(&test_tx_data).await {
+                    Ok(_) => latencies.push(start.elapsed()),
+                    Err(_) => num_failures += 1,
+                }
+                // The local signer never yields, which would keep the timer from stopping the test
+                tokio::task::yield_now().await;
+            }
+            (latencies, num_failures)
+        });
+
+        handles.push(handle);
+    }
+
+    // Start the timer
+    let start = Instant::now();
+
+    // Let the test run for the specified duration
+    tokio::time::sleep(duration).await;
+
+    // Signal all workers to stop
+    stop_flag.store(true, Ordering::Relaxed);
+
+    // Wait for all workers to finish
+    let mut latencies = vec![];
+    let mut num_failures = 0;
+    for handle in handles {
+        if let Ok((task_latencies, task_failures)) = handle.await {
+            latencies.extend(task_latencies);
+            num_failures += task_failures;
+        }
+    }
+    latencies.sort_unstable();
+
+    SignerStressReport {
+        backend: signer.backend(),
+        address: signer.get_address(),
+        num_success: latencies.len(),
+        num_failures,
+        elapsed: start.elapsed(),
+        latencies,
+    }
+}
+
+impl SignerStressReport {
+    /// Latency of the successful calls at the given percentile.
+    pub fn latency_percentile(&self, percent: usize) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        self.latencies[(self.latencies.len() - 1) * percent / 100]
+    }
+
+    pub fn print(&self) {
+        let elapsed = self.elapsed.as_secs_f64();
+        println!("Signer {} ({}):", self.backend, self.address);
+        println!(
+            "Total calls completed: {}",
+            self.num_success + self.num_failures
+        );
+        println!(" - Successful: {}", self.num_success);
+        println!(" - Failed: {}", self.num_failures);
+        println!("Elapsed time: {:.2} seconds", elapsed);
+        println!(
+            "Throughput: {:.2} successful calls/second",
+            self.num_success as f64 / elapsed
+        );
+        println!(
+            "Latency: p50 {:?}, p99 {:?}, max {:?}",
+            self.latency_percentile(50),
+            self.latency_percentile(99),
+            self.latencies.last().copied().unwrap_or_default()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx_signer::TestTxSigner;
+    use iota_types::crypto::get_account_key_pair;
+
+    #[tokio::test]
+    async fn test_signer_stress_test() {
+        let (_, keypair) = get_account_key_pair();
+        let signer = TestTxSigner::new(keypair.into());
+        let report = run_signer_stress_test(signer, 2, Duration::from_millis(100)).await;
+        assert_eq!(report.backend, "local");
+        assert_eq!(report.num_failures, 0);
+        assert!(report.num_success > 0);
+        assert!(report.latency_percentile(50) <= report.latency_percentile(99));
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::rpc::client::GasStationRpcClient;
+use crate::rpc::rpc_types::ErrorCode;
+use iota_config::node::DEFAULT_VALIDATOR_GAS_PRICE;
+use iota_types::crypto::{get_account_key_pair, Signature};
+use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use iota_types::transaction::{TransactionData, TransactionKind};
+use parking_lot::RwLock;
+use rand::rngs::OsRng;
+use rand::Rng;
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::time::{interval, Duration, Instant};
+
+#[derive(Default)]
+struct SoakStats {
+    num_requests: u64,
+    num_errors: u64,
+    /// Reservations refused because the gas pool had no coins left for the budget.
+    num_pool_exhausted: u64,
+    /// Requests not sent because `max_in_flight` requests were still pending.
+    num_skipped: u64,
+    /// Latencies of the successful requests, in milliseconds.
+    latencies: Vec<u64>,
+}
+
+impl SoakStats {
+    /// Returns the number of requests, errors, pool exhaustions and skipped requests.
+    fn counters(&self) -> [u64; 4] {
+        [
+            self.num_requests,
+            self.num_errors,
+            self.num_pool_exhausted,
+            self.num_skipped,
+        ]
+    }
+}
+
+/// Sends requests at `target_rps` for `duration`, whatever the latency of the gas station, and
+/// records the error rate and the pool exhaustion events, e.g. to check that a pool sized for a
+/// given load holds it. Each request reserves gas and executes an empty transaction with it.
+pub async fn run_soak_test(
+    gas_station_url: String,
+    reserve_duration_sec: u64,
+    target_rps: u64,
+    duration: Duration,
+    max_in_flight: usize,
+) {
+    assert!(target_rps > 0, "The target request rate must be positive");
+    let client = GasStationRpcClient::new(gas_station_url);
+    let stats = Arc::new(RwLock::new(SoakStats::default()));
+    let in_flight = Arc::new(Semaphore::new(max_in_flight));
+    let reporter = tokio::spawn(report_every_second(stats.clone()));
+
+    let start = Instant::now();
+    let mut ticks = interval(Duration::from_secs(1) / target_rps as u32);
+    let mut handles = vec![];
+    while start.elapsed() < duration {
+        ticks.tick().await;
+        let Ok(permit) = in_flight.clone().try_acquire_owned() else {
+            stats.write().num_skipped += 1;
+            continue;
+        };
+        let client = client.clone();
+        let stats = stats.clone();
+        handles.push(tokio::spawn(async move {
+            let now = Instant::now();
+            let result = reserve_and_execute(&client, reserve_duration_sec).await;
+            let mut stats = stats.write();
+            stats.num_requests += 1;
+            match result {
+                Ok(()) => stats.latencies.push(now.elapsed().as_millis() as u64),
+                Err(pool_exhausted) => {
+                    stats.num_errors += 1;
+                    if pool_exhausted {
+                        stats.num_pool_exhausted += 1;
+                    }
+                }
+            }
+            drop(permit);
+        }));
+        handles.retain(|handle| !handle.is_finished());
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+    reporter.abort();
+
+    let mut stats = std::mem::take(&mut *stats.write());
+    stats.latencies.sort_unstable();
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("Soak test finished after {:.0}s", elapsed);
+    println!(
+        "Requests: {} ({:.2} per second, target {}), skipped: {}",
+        stats.num_requests,
+        stats.num_requests as f64 / elapsed,
+        target_rps,
+        stats.num_skipped
+    );
+    println!(
+        "Errors: {} ({:.2}%), pool exhausted: {}",
+        stats.num_errors,
+        100.0 * stats.num_errors as f64 / stats.num_requests.max(1) as f64,
+        stats.num_pool_exhausted
+    );
+    println!(
+        "Latency: p50 {}ms, p99 {}ms, max {}ms",
+        percentile(&stats.latencies, 50),
+        percentile(&stats.latencies, 99),
+        stats.latencies.last().copied().unwrap_or(0)
+    );
+}
+
+/// Returns whether the pool was exhausted on failure.
+async fn reserve_and_execute(
+    client: &GasStationRpcClient,
+    reserve_duration_sec: u64,
+) -> Result<(), bool> {
+    let budget = OsRng.gen_range(1_000_000u64..100_000_000u64);
+    let response = client
+        .reserve_gas_v2(budget, reserve_duration_sec)
+        .await
+        .map_err(|err| {
+            println!("Error: {}", err);
+            false
+        })?;
+    let result = match (response.result, response.error) {
+        (Some(result), _) => result,
+        (None, Some(err)) => {
+            let pool_exhausted = err.code == ErrorCode::InsufficientPoolBalance;
+            if !pool_exhausted {
+                println!("Error: {}", err.message);
+            }
+            return Err(pool_exhausted);
+        }
+        (None, None) => return Err(false),
+    };
+
+    let (sender, keypair) = get_account_key_pair();
+    let tx_data = TransactionData::new_with_gas_coins_allow_sponsor(
+        TransactionKind::ProgrammableTransaction(ProgrammableTransactionBuilder::new().finish()),
+        sender,
+        result
+            .gas_coins
+            .into_iter()
+            .map(|c| c.to_object_ref())
+            .collect(),
+        budget,
+        DEFAULT_VALIDATOR_GAS_PRICE,
+        result.sponsor_address,
+    );
+    let intent_msg = IntentMessage::new(Intent::iota_transaction(), &tx_data);
+    let user_sig = Signature::new_secure(&intent_msg, &keypair).into();
+    client
+        .execute_tx(result.reservation_id, &tx_data, &user_sig, None, None)
+        .await
+        .map(|_| ())
+        .map_err(|err| {
+            println!("Error: {}", err);
+            false
+        })
+}
+
+async fn report_every_second(stats: Arc<RwLock<SoakStats>>) {
+    let mut prev = stats.read().counters();
+    let mut interval = interval(Duration::from_secs(1));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let cur = stats.read().counters();
+        println!(
+            "Requests per second: {}, errors: {}, pool exhausted: {}, skipped: {}",
+            cur[0] - prev[0],
+            cur[1] - prev[1],
+            cur[2] - prev[2],
+            cur[3] - prev[3]
+        );
+        prev = cur;
+    }
+}
+
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[(sorted.len() - 1) * percent / 100]
+}
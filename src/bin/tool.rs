@@ -4,14 +4,33 @@
 
 use clap::*;
 use iota_config::Config;
-use iota_gas_station::benchmarks::kms_stress::run_kms_stress_test;
+use iota_gas_station::access_controller::rule_templates::{insert_rules, RuleTemplate};
+use iota_gas_station::benchmarks::access_controller::{
+    read_recorded_transactions, run_access_controller_benchmark, synthetic_transactions,
+};
+use iota_gas_station::benchmarks::rego::run_rego_benchmark;
+use iota_gas_station::benchmarks::signer_stress::{run_kms_stress_test, run_signer_stress_tests};
 use iota_gas_station::benchmarks::BenchmarkMode;
-use iota_gas_station::config::{GasStationConfig, GasStationStorageConfig, TxSignerConfig};
+use iota_gas_station::config::{
+    ConfigLoadOptions, GasStationConfig, GasStationStorageConfig, SidecarSignerConfig,
+    TxSignerConfig,
+};
+use iota_gas_station::dashboard::{dump_metrics, run_dashboard};
+use iota_gas_station::iota_client::IotaClient;
+use iota_gas_station::redis_connection::RedisMode;
 use iota_gas_station::rpc::client::GasStationRpcClient;
+use iota_gas_station::self_test::{read_keystore, run_sponsor_self_test};
+use iota_gas_station::sponsor_migration::migrate_sponsor_coins;
+use iota_gas_station::storage_migration::migrate_storage;
+use iota_gas_station::tx_log_replay::{read_tx_log, replay_tx_log};
+use iota_gas_station::types::PoolCoinState;
+use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
 use iota_sdk::{IOTA_DEVNET_URL, IOTA_MAINNET_URL, IOTA_TESTNET_URL};
 use iota_types::base_types::IotaAddress;
 use iota_types::crypto::{get_account_key_pair, EncodeDecodeBase64, IotaKeyPair};
+use iota_types::digests::TransactionDigest;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(
@@ -22,6 +41,9 @@ use std::path::PathBuf;
 pub enum ToolCommand {
     /// Running benchmark. This will continue reserving gas coins on the gas station for some
     /// seconds, which would automatically expire latter.
+    /// With `--benchmark-mode soak`, the requests are sent at `--target-rps` for `--duration-sec`
+    /// instead, with at most `--num-clients` of them pending, and the error rate and the pool
+    /// exhaustion events are reported.
     #[clap(name = "benchmark")]
     Benchmark {
         #[arg(long, help = "Full URL to the gas station RPC server")]
@@ -40,6 +62,88 @@ pub enum ToolCommand {
         num_clients: u64,
         #[arg(long, help = "Benchmark mode.", default_value = "reserve-only")]
         benchmark_mode: BenchmarkMode,
+        #[arg(
+            long,
+            help = "Requests per second to hold in the soak mode.",
+            default_value_t = 100
+        )]
+        target_rps: u64,
+        #[arg(
+            long,
+            help = "Duration of the soak mode, in number of seconds.",
+            default_value_t = 600
+        )]
+        duration_sec: u64,
+    },
+    /// Compare the throughput of the Rego expressions of the access controller, when cloning
+    /// their engines for every evaluation and when reusing the compiled engines.
+    #[clap(name = "benchmark-rego")]
+    BenchmarkRego {
+        #[arg(
+            long,
+            help = "Number of rules with a Rego expression.",
+            default_value_t = 50
+        )]
+        num_rules: usize,
+        #[arg(
+            long,
+            help = "Duration of each run, in number of seconds.",
+            default_value_t = 10
+        )]
+        duration_sec: u64,
+    },
+    /// Replay transactions through the access controller rules of a config file, without any
+    /// fullnode or storage, and report the evaluations per second and the latency of each rule.
+    /// The hooks of the rules are called, the notifications are not sent.
+    #[clap(name = "bench-access-controller")]
+    BenchAccessController {
+        #[arg(long, help = "Path to config file")]
+        config_path: PathBuf,
+        #[arg(long, help = "Profile of the config file to apply")]
+        profile: Option<String>,
+        #[arg(
+            long,
+            help = "File with an execute_tx request body per line, to replay instead of synthetic transactions"
+        )]
+        transactions: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Number of synthetic transactions to replay.",
+            default_value_t = 10000
+        )]
+        num_transactions: usize,
+        #[arg(
+            long,
+            help = "Reference gas price of the epoch, for the rules on the gas price.",
+            default_value_t = 1000
+        )]
+        reference_gas_price: u64,
+    },
+    /// Sign transactions concurrently with the signer of a config file, and with each of its
+    /// members for a multisig, and report the throughput and the latency of each backend.
+    #[clap(name = "stress-signer")]
+    StressSigner {
+        #[arg(long, help = "Path to config file")]
+        config_path: PathBuf,
+        #[arg(long, help = "Profile of the config file to apply")]
+        profile: Option<String>,
+        #[arg(
+            long,
+            help = "Stress the previous-signer-config instead of the signer-config"
+        )]
+        previous: bool,
+        #[arg(
+            long,
+            default_value_t = 300,
+            help = "Number of tasks to spawn to send requests to the signer."
+        )]
+        num_tasks: usize,
+        #[arg(
+            long,
+            help = "Duration of the run of each signer, in number of seconds.",
+            default_value_t = 30
+        )]
+        duration_sec: u64,
     },
     #[clap(name = "stress-kms")]
     StressKMS {
@@ -72,12 +176,75 @@ pub enum ToolCommand {
         )]
         network: Network,
     },
+    /// Add commented access controller rules for a common policy to an existing config file, to
+    /// adapt to the application.
+    #[clap(name = "generate-sample-rules")]
+    GenerateSampleRules {
+        #[arg(long, help = "Policy of the rules", value_enum)]
+        preset: RuleTemplate,
+        #[arg(long, help = "Path to config file. The rules are printed if not set")]
+        config_path: Option<PathBuf>,
+    },
     /// Converts the Bech32 key to Base64 encoded
     #[clap(name = "convert-key")]
     ConvertKeyConfig {
         #[arg(long, short, help = "bech32 encoded key i.e. iotaprivkey...")]
         key: String,
     },
+    /// Transfer all the coins of the previous sponsor to the current sponsor, to complete a key
+    /// rotation. Only run this once the previous sponsor has no pending reservations left.
+    #[clap(name = "migrate-sponsor-coins")]
+    MigrateSponsorCoins {
+        #[arg(long, help = "Path to config file")]
+        config_path: PathBuf,
+        #[arg(long, help = "Profile of the config file to apply")]
+        profile: Option<String>,
+    },
+    /// Replay the transactions of a transaction log through the access controller of a config
+    /// file, fetching them from its fullnode, and list those the rules would now deny or hold.
+    /// Exits with an error if there is any, to check a policy change before deploying it.
+    #[clap(name = "replay-tx-log")]
+    ReplayTxLog {
+        #[arg(long, help = "Path to the config file with the new rules")]
+        config_path: PathBuf,
+        #[arg(long, help = "Profile of the config file to apply")]
+        profile: Option<String>,
+        #[arg(
+            long,
+            help = "Transaction log written by a file sink or by the transactions tracing target"
+        )]
+        tx_log: PathBuf,
+    },
+    /// Copy the gas pools of the sponsors to another storage, e.g. to move to a new Redis
+    /// instance without draining the pools. Stop the Gas Station meanwhile.
+    #[clap(name = "migrate-storage")]
+    MigrateStorage {
+        #[arg(
+            long,
+            help = "URL of the storage to copy from, e.g. redis://127.0.0.1:6379"
+        )]
+        from: GasStationStorageConfig,
+        #[arg(
+            long,
+            help = "URL of the storage to copy to. It must have no pool for the sponsors"
+        )]
+        to: GasStationStorageConfig,
+        #[arg(long, help = "Namespace of the pools in the storage to copy from")]
+        from_namespace: Option<String>,
+        #[arg(long, help = "Namespace of the pools in the storage to copy to")]
+        to_namespace: Option<String>,
+        #[arg(
+            long = "sponsor-address",
+            required = true,
+            help = "Address of a sponsor whose pool is copied. Can be repeated"
+        )]
+        sponsor_addresses: Vec<IotaAddress>,
+        #[arg(
+            long,
+            help = "Mark the copied pools as draining in the source storage, so that no copied coin can be reserved from it anymore"
+        )]
+        read_only_source: bool,
+    },
     #[clap(name = "cli")]
     CLI {
         #[clap(subcommand)]
@@ -95,12 +262,156 @@ pub enum CliCommand {
     /// A more complete version of health check, which includes checking the bearer secret,
     /// storage layer and sidecar signer.
     CheckStationEndToEndHealth {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+    },
+    GetStationVersion {
         #[clap(long, help = "Full URL of the station RPC server")]
         station_rpc_url: String,
     },
-    GetStationVersion {
+    /// Reserve gas, sign a transaction that does nothing with a key of the keystore, and execute
+    /// it through the station, to check a deployment end to end.
+    SponsorSelfTest {
         #[clap(long, help = "Full URL of the station RPC server")]
         station_rpc_url: String,
+        #[clap(
+            long,
+            help = "Path to a keystore file of the IOTA CLI, e.g. ~/.iota/iota_config/iota.keystore"
+        )]
+        keystore: PathBuf,
+        #[clap(
+            long,
+            help = "Address of the key to sign with. The first key of the keystore is used if not set"
+        )]
+        address: Option<IotaAddress>,
+        #[clap(
+            long,
+            help = "Gas budget of the transaction",
+            default_value_t = 10_000_000
+        )]
+        gas_budget: u64,
+    },
+    /// List the gas coins that are kept out of the pool because a conflicting transaction locked
+    /// them, along with the epoch from which they are used again.
+    ListQuarantinedCoins {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+    },
+    /// List the gas coins in the pool with their version, balance and whether they are
+    /// available, reserved or quarantined.
+    ListCoins {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "Only list the coins of the given reservation")]
+        reservation_id: Option<u64>,
+    },
+    /// Refuse new reservations and withdraw the available coins of the pool, e.g. to decommission
+    /// the sponsor. Coins reserved at that time return to the pool once released, so run it again
+    /// after the reservations expired to collect them.
+    DrainPool {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(
+            long,
+            help = "Address to send the coins to. By default they are merged into large coins owned by the sponsor"
+        )]
+        recipient: Option<IotaAddress>,
+    },
+    /// Show a terminal dashboard with the health, the version, the state of the pool and the
+    /// request rates of the station, refreshed periodically.
+    Watch {
+        #[clap(long, help = "Full URL of the station RPC server")]
+        station_rpc_url: String,
+        #[clap(
+            long,
+            help = "Full URL of the station metrics endpoint, e.g. http://127.0.0.1:9184/metrics"
+        )]
+        metrics_url: Option<String>,
+        #[clap(long, help = "Refresh interval in seconds", default_value_t = 5)]
+        interval_sec: u64,
+    },
+    /// Scrape the metrics of the station once and print a summary of the pool and of the success
+    /// rates of the requests, followed by the station series.
+    DumpMetrics {
+        #[clap(long, help = "Full URL of the station RPC server")]
+        station_rpc_url: String,
+        #[clap(
+            long,
+            help = "Full URL of the station metrics endpoint. Defaults to port 9184 of the station host"
+        )]
+        metrics_url: Option<String>,
+    },
+    /// Accept reservations again after the pool was drained.
+    ResumePool {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+    },
+    /// Replace the bearer secret of the station without restarting it. The current secret, read
+    /// from GAS_STATION_AUTH, is still accepted during the grace period, so that the clients can
+    /// switch to the new secret in the meantime.
+    RotateAuthSecret {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, env = "GAS_STATION_NEW_AUTH", help = "The new bearer secret")]
+        new_secret: String,
+        #[clap(
+            long,
+            help = "How long the current secret is still accepted, in seconds. Defaults to 600"
+        )]
+        grace_period_sec: Option<u64>,
+    },
+    /// Show the progress of the coin initialization, or change the target balance of newly
+    /// initialized coins until the next config reload.
+    CoinInit {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "New target balance of newly initialized coins")]
+        target_init_balance: Option<u64>,
+    },
+    /// List the clients with the most reservations that expired without being executed.
+    ListExpiredReservations {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "Maximum number of clients to list. Defaults to 10")]
+        limit: Option<usize>,
+    },
+    /// Reset the gas usage of a sender counted by an access control rule with a gas usage limit,
+    /// or credit it by an amount, e.g. to unblock a sender who hit the limit with failed retries.
+    AdjustLimit {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "Address of the sender")]
+        sender: IotaAddress,
+        #[clap(
+            long,
+            help = "Position of the rule in the access controller config, starting at 1"
+        )]
+        rule_number: usize,
+        #[clap(
+            long,
+            help = "Gas to subtract from the gas usage. The gas usage is reset if not set"
+        )]
+        credit: Option<u64>,
+    },
+    /// List the transactions held by an access control rule with the `hold` action, waiting to
+    /// be approved or rejected.
+    ListHeldTransactions {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+    },
+    /// Execute a held transaction.
+    ApproveTransaction {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "Digest of the held transaction")]
+        digest: TransactionDigest,
+    },
+    /// Drop a held transaction and release its gas coins.
+    RejectTransaction {
+        #[clap(long, help = "Full URL of the station admin server")]
+        station_admin_url: String,
+        #[clap(long, help = "Digest of the held transaction")]
+        digest: TransactionDigest,
     },
 }
 
@@ -112,14 +423,101 @@ impl ToolCommand {
                 reserve_duration_sec,
                 num_clients,
                 benchmark_mode,
+                target_rps,
+                duration_sec,
             } => {
                 assert!(
                     cfg!(not(debug_assertions)),
                     "Benchmark should only run in release build"
                 );
                 benchmark_mode
-                    .run_benchmark(gas_station_url, reserve_duration_sec, num_clients)
+                    .run_benchmark(
+                        gas_station_url,
+                        reserve_duration_sec,
+                        num_clients,
+                        target_rps,
+                        Duration::from_secs(duration_sec),
+                    )
+                    .await
+            }
+            ToolCommand::BenchmarkRego {
+                num_rules,
+                duration_sec,
+            } => {
+                assert!(
+                    cfg!(not(debug_assertions)),
+                    "Benchmark should only run in release build"
+                );
+                run_rego_benchmark(num_rules, Duration::from_secs(duration_sec));
+            }
+            ToolCommand::BenchAccessController {
+                config_path,
+                profile,
+                transactions,
+                num_transactions,
+                reference_gas_price,
+            } => {
+                assert!(
+                    cfg!(not(debug_assertions)),
+                    "Benchmark should only run in release build"
+                );
+                let load_options = ConfigLoadOptions {
+                    profile,
+                    ..Default::default()
+                };
+                let config = GasStationConfig::load_from_file(&config_path, &load_options)
+                    .await
+                    .unwrap();
+                let transactions = match transactions {
+                    Some(path) => read_recorded_transactions(&path).unwrap_or_else(|e| {
+                        eprintln!("Failed to read the transactions: {:#}", e);
+                        std::process::exit(1);
+                    }),
+                    None => synthetic_transactions(num_transactions),
+                };
+                match run_access_controller_benchmark(
+                    config.access_controller,
+                    &transactions,
+                    reference_gas_price,
+                )
+                .await
+                {
+                    Ok(report) => report.print(),
+                    Err(e) => {
+                        eprintln!("Failed to run the benchmark: {:#}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            ToolCommand::StressSigner {
+                config_path,
+                profile,
+                previous,
+                num_tasks,
+                duration_sec,
+            } => {
+                let load_options = ConfigLoadOptions {
+                    profile,
+                    ..Default::default()
+                };
+                let config = GasStationConfig::load_from_file(&config_path, &load_options)
                     .await
+                    .unwrap();
+                let signer_config = if previous {
+                    let Some(previous_signer_config) = config.previous_signer_config else {
+                        eprintln!("No previous-signer-config found in the config file.");
+                        std::process::exit(1);
+                    };
+                    previous_signer_config
+                } else {
+                    config.signer_config
+                };
+                run_signer_stress_tests(
+                    signer_config.new_signer().await,
+                    num_tasks,
+                    Duration::from_secs(duration_sec),
+                )
+                .await;
             }
             ToolCommand::StressKMS { kms_url, num_tasks } => {
                 run_kms_stress_test(kms_url, num_tasks).await;
@@ -133,9 +531,9 @@ impl ToolCommand {
             } => {
                 let mut new_iota_address: Option<IotaAddress> = None;
                 let signer_config = if with_sidecar_signer {
-                    TxSignerConfig::Sidecar {
-                        sidecar_url: "http://localhost:3000".to_string(),
-                    }
+                    TxSignerConfig::Sidecar(SidecarSignerConfig::new(
+                        "http://localhost:3000".to_string(),
+                    ))
                 } else {
                     let (iota_address, keypair) = get_account_key_pair();
                     new_iota_address = Some(iota_address);
@@ -153,7 +551,11 @@ impl ToolCommand {
 
                 let config = GasStationConfig {
                     signer_config,
-                    storage_config: GasStationStorageConfig::Redis { redis_url },
+                    storage_config: GasStationStorageConfig::Redis {
+                        redis_url,
+                        namespace: None,
+                        mode: RedisMode::Single,
+                    },
                     fullnode_url,
                     ..Default::default()
                 };
@@ -169,14 +571,148 @@ impl ToolCommand {
                 }
                 config.save(config_path).unwrap();
             }
+            ToolCommand::GenerateSampleRules {
+                preset,
+                config_path,
+            } => {
+                let Some(config_path) = config_path else {
+                    print!("{}", preset.rules());
+                    return;
+                };
+                let config = match std::fs::read_to_string(&config_path) {
+                    Err(e) => {
+                        eprintln!("Failed to read the config file: {}", e);
+                        std::process::exit(1);
+                    }
+                    Ok(config) => config,
+                };
+                match insert_rules(&config, preset.rules()) {
+                    Err(e) => {
+                        eprintln!("Failed to add the rules: {:#}", e);
+                        std::process::exit(1);
+                    }
+                    Ok(config) => {
+                        std::fs::write(&config_path, config).unwrap();
+                        println!(
+                            "Added the rules to {}. Review them, and replace the placeholder addresses before starting the station.",
+                            config_path.display()
+                        );
+                    }
+                }
+            }
+            ToolCommand::MigrateSponsorCoins {
+                config_path,
+                profile,
+            } => {
+                let load_options = ConfigLoadOptions {
+                    profile,
+                    ..Default::default()
+                };
+                let config = GasStationConfig::load_from_file(&config_path, &load_options)
+                    .await
+                    .unwrap();
+                let Some(previous_signer_config) = config.previous_signer_config else {
+                    eprintln!("No previous-signer-config found in the config file.");
+                    std::process::exit(1);
+                };
+                let new_sponsor = config.signer_config.new_signer().await.get_address();
+                let previous_signer = previous_signer_config.new_signer().await;
+                let iota_client = IotaClient::new_with_failover(
+                    &config.fullnode_urls(),
+                    config.fullnode_basic_auth,
+                    config.fullnode_routing,
+                )
+                .await
+                .with_retry_policy(config.fullnode_retry_policy);
+                match migrate_sponsor_coins(&iota_client, &previous_signer, new_sponsor).await {
+                    Err(e) => {
+                        eprintln!("Failed to migrate sponsor coins: {}", e);
+                        std::process::exit(1);
+                    }
+                    Ok(balance) => {
+                        println!(
+                            "Migrated a total balance of {} from {} to {}",
+                            balance,
+                            previous_signer.get_address(),
+                            new_sponsor
+                        );
+                    }
+                }
+            }
+            ToolCommand::ReplayTxLog {
+                config_path,
+                profile,
+                tx_log,
+            } => {
+                let load_options = ConfigLoadOptions {
+                    profile,
+                    ..Default::default()
+                };
+                let config = GasStationConfig::load_from_file(&config_path, &load_options)
+                    .await
+                    .unwrap();
+                let entries = read_tx_log(&tx_log).unwrap_or_else(|e| {
+                    eprintln!("Failed to read the transaction log: {:#}", e);
+                    std::process::exit(1);
+                });
+                let iota_client = IotaClient::new_with_failover(
+                    &config.fullnode_urls(),
+                    config.fullnode_basic_auth,
+                    config.fullnode_routing,
+                )
+                .await
+                .with_retry_policy(config.fullnode_retry_policy);
+                let report = replay_tx_log(&iota_client, config.access_controller, &entries)
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Failed to replay the transaction log: {:#}", e);
+                        std::process::exit(1);
+                    });
+                report.print();
+                if let Err(e) = report.check_no_regression() {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            ToolCommand::MigrateStorage {
+                from,
+                to,
+                from_namespace,
+                to_namespace,
+                sponsor_addresses,
+                read_only_source,
+            } => match migrate_storage(
+                &from.with_namespace(from_namespace),
+                &to.with_namespace(to_namespace),
+                &sponsor_addresses,
+                read_only_source,
+            )
+            .await
+            {
+                Err(e) => {
+                    eprintln!("Failed to migrate the storage: {:#}", e);
+                    std::process::exit(1);
+                }
+                Ok(pools) => {
+                    for (sponsor_address, pool) in pools {
+                        println!(
+                            "Copied the pool of {}: {} available coins, {} reservations, {} quarantined coins",
+                            sponsor_address,
+                            pool.available_coins.len(),
+                            pool.reservations.len(),
+                            pool.quarantined_coins.len()
+                        );
+                    }
+                }
+            },
             ToolCommand::CLI { cli_command } => match cli_command {
                 CliCommand::CheckStationHealth { station_rpc_url } => {
                     let station_client = GasStationRpcClient::new(station_rpc_url);
                     station_client.health().await.unwrap();
                     println!("Station server is healthy");
                 }
-                CliCommand::CheckStationEndToEndHealth { station_rpc_url } => {
-                    let station_client = GasStationRpcClient::new(station_rpc_url);
+                CliCommand::CheckStationEndToEndHealth { station_admin_url } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
                     match station_client.debug_health_check().await {
                         Err(e) => {
                             eprintln!("Station server is not healthy: {}", e);
@@ -192,6 +728,336 @@ impl ToolCommand {
                     let version = station_client.version().await.unwrap();
                     println!("Station server version: {}", version);
                 }
+                CliCommand::SponsorSelfTest {
+                    station_rpc_url,
+                    keystore,
+                    address,
+                    gas_budget,
+                } => {
+                    let keypair = match read_keystore(&keystore, address) {
+                        Err(e) => {
+                            eprintln!("Failed to read the key: {:#}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(keypair) => keypair,
+                    };
+                    let station_client = GasStationRpcClient::new(station_rpc_url);
+                    match run_sponsor_self_test(&station_client, &keypair, gas_budget).await {
+                        Err(e) => {
+                            eprintln!("Self test failed: {:#}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(effects) => {
+                            println!("{}", effects);
+                            if effects.status().is_err() {
+                                eprintln!("Self test failed: {:?}", effects.status());
+                                std::process::exit(1);
+                            }
+                            println!("Self test succeeded");
+                        }
+                    }
+                }
+                CliCommand::ListQuarantinedCoins { station_admin_url } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.get_quarantined_coins().await {
+                        Err(e) => {
+                            eprintln!("Failed to get quarantined coins: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(coins) if coins.is_empty() => println!("No quarantined coins"),
+                        Ok(coins) => {
+                            for coin in coins {
+                                println!(
+                                    "{} balance: {} release epoch: {}",
+                                    coin.object_id, coin.balance, coin.release_epoch
+                                );
+                            }
+                        }
+                    }
+                }
+                CliCommand::ListCoins {
+                    station_admin_url,
+                    reservation_id,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    let mut coins = match station_client.get_pool_coins().await {
+                        Err(e) => {
+                            eprintln!("Failed to get pool coins: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(coins) => coins,
+                    };
+                    if let Some(reservation_id) = reservation_id {
+                        coins.retain(|coin| match coin.state {
+                            PoolCoinState::Reserved {
+                                reservation_id: id, ..
+                            } => id == reservation_id,
+                            _ => false,
+                        });
+                    }
+                    let format_optional = |value: Option<u64>| {
+                        value.map_or_else(|| "-".to_string(), |value| value.to_string())
+                    };
+                    println!(
+                        "{:<66} {:>10} {:>20} STATE",
+                        "OBJECT ID", "VERSION", "BALANCE"
+                    );
+                    for coin in &coins {
+                        let state = match &coin.state {
+                            PoolCoinState::Available => "available".to_string(),
+                            PoolCoinState::Reserved {
+                                reservation_id,
+                                expiration_time,
+                            } => format!(
+                                "reserved (reservation {}, expires at {} ms)",
+                                reservation_id, expiration_time
+                            ),
+                            PoolCoinState::Quarantined { release_epoch } => {
+                                format!("quarantined until epoch {}", release_epoch)
+                            }
+                        };
+                        println!(
+                            "{:<66} {:>10} {:>20} {}",
+                            coin.object_id,
+                            format_optional(coin.version),
+                            format_optional(coin.balance),
+                            state
+                        );
+                    }
+                    println!("Total: {} coins", coins.len());
+                }
+                CliCommand::DrainPool {
+                    station_admin_url,
+                    recipient,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.drain_pool(recipient).await {
+                        Err(e) => {
+                            eprintln!("Failed to drain the pool: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(response) => {
+                            println!(
+                                "Drained {} coins, transferred a total balance of {} to {}",
+                                response.drained_coin_count,
+                                response.transferred_balance,
+                                response.recipient
+                            );
+                        }
+                    }
+                }
+                CliCommand::Watch {
+                    station_rpc_url,
+                    metrics_url,
+                    interval_sec,
+                } => {
+                    run_dashboard(
+                        station_rpc_url,
+                        metrics_url,
+                        Duration::from_secs(interval_sec.max(1)),
+                    )
+                    .await;
+                }
+                CliCommand::DumpMetrics {
+                    station_rpc_url,
+                    metrics_url,
+                } => match dump_metrics(&station_rpc_url, metrics_url.as_deref()).await {
+                    Err(e) => {
+                        eprintln!("Failed to get the metrics: {}", e);
+                        std::process::exit(1);
+                    }
+                    Ok(dump) => print!("{}", dump),
+                },
+                CliCommand::ResumePool { station_admin_url } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.resume_pool().await {
+                        Err(e) => {
+                            eprintln!("Failed to resume the pool: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(()) => println!("The pool accepts reservations again"),
+                    }
+                }
+                CliCommand::RotateAuthSecret {
+                    station_admin_url,
+                    new_secret,
+                    grace_period_sec,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client
+                        .rotate_auth_secret(new_secret, grace_period_sec)
+                        .await
+                    {
+                        Err(e) => {
+                            eprintln!("Failed to rotate the auth secret: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(()) => println!(
+                            "Rotated the auth secret. Set GAS_STATION_AUTH to the new secret in \
+                             the deployment before the station restarts"
+                        ),
+                    }
+                }
+                CliCommand::CoinInit {
+                    station_admin_url,
+                    target_init_balance,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    if let Some(target_init_balance) = target_init_balance {
+                        if let Err(e) = station_client
+                            .set_target_init_balance(target_init_balance)
+                            .await
+                        {
+                            eprintln!("Failed to set the target init balance: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    match station_client.get_coin_init_status().await {
+                        Err(e) => {
+                            eprintln!("Failed to get the coin init status: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(status) => {
+                            match status.target_init_balance {
+                                Some(balance) => println!("Target init balance: {}", balance),
+                                None => println!("The coin initializer is not running"),
+                            }
+                            match status.progress {
+                                Some(progress) => println!(
+                                    "Initialization in progress with target balance {}: {} coins \
+                                     split, {} coins added, {} coins pending",
+                                    progress.target_init_balance,
+                                    progress.split_coin_count,
+                                    progress.added_coin_count,
+                                    progress.pending_coins.len()
+                                ),
+                                None => println!("No initialization in progress"),
+                            }
+                        }
+                    }
+                }
+                CliCommand::ListExpiredReservations {
+                    station_admin_url,
+                    limit,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.get_expired_reservations(limit).await {
+                        Err(e) => {
+                            eprintln!("Failed to get the expired reservations: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(stats) if stats.is_empty() => {
+                            println!("No reservation expired unused");
+                        }
+                        Ok(stats) => {
+                            for client in stats {
+                                println!(
+                                    "{}: {} of {} reservations expired unused",
+                                    client.client, client.expired_count, client.reservation_count
+                                );
+                            }
+                        }
+                    }
+                }
+                CliCommand::AdjustLimit {
+                    station_admin_url,
+                    sender,
+                    rule_number,
+                    credit,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client
+                        .adjust_limit(sender, rule_number, credit)
+                        .await
+                    {
+                        Err(e) => {
+                            eprintln!("Failed to adjust the limit: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(limit) => {
+                            println!(
+                                "Gas usage of {} counted by rule #{}: {} (limit {}, {} remaining)",
+                                sender,
+                                limit.rule_number,
+                                limit.gas_usage,
+                                limit.limit,
+                                limit.remaining
+                            );
+                        }
+                    }
+                }
+                CliCommand::ListHeldTransactions { station_admin_url } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    let held_transactions = match station_client.list_held_transactions().await {
+                        Err(e) => {
+                            eprintln!("Failed to list the held transactions: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(held_transactions) => held_transactions,
+                    };
+                    println!(
+                        "{:<44} {:<66} {:>15} {:>12} RULE",
+                        "DIGEST", "SENDER", "GAS BUDGET", "EXPIRES AT"
+                    );
+                    for held in &held_transactions {
+                        println!(
+                            "{:<44} {:<66} {:>15} {:>12} {}",
+                            held.transaction_digest.to_string(),
+                            held.sender.to_string(),
+                            held.gas_budget,
+                            held.expires_at_secs,
+                            held.matched_rule.as_deref().unwrap_or("-")
+                        );
+                    }
+                    println!("Total: {} held transactions", held_transactions.len());
+                }
+                CliCommand::ApproveTransaction {
+                    station_admin_url,
+                    digest,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.approve_transaction(digest).await {
+                        Err(e) => {
+                            eprintln!("Failed to approve the transaction: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(response) => match (response.effects, response.error) {
+                            (Some(effects), _) => {
+                                println!(
+                                    "Executed transaction {} with status: {:?}",
+                                    digest,
+                                    effects.status()
+                                );
+                            }
+                            (None, error) => {
+                                eprintln!(
+                                    "Failed to execute the transaction: {}",
+                                    error.map_or_else(
+                                        || "Unknown error".to_string(),
+                                        |error| error.to_string()
+                                    )
+                                );
+                                std::process::exit(1);
+                            }
+                        },
+                    }
+                }
+                CliCommand::RejectTransaction {
+                    station_admin_url,
+                    digest,
+                } => {
+                    let station_client = GasStationRpcClient::new(station_admin_url);
+                    match station_client.reject_transaction(digest).await {
+                        Err(e) => {
+                            eprintln!("Failed to reject the transaction: {}", e);
+                            std::process::exit(1);
+                        }
+                        Ok(held) => println!(
+                            "Rejected transaction {} of sender {}",
+                            held.transaction_digest, held.sender
+                        ),
+                    }
+                }
             },
             ToolCommand::ConvertKeyConfig { key } => {
                 let key = IotaKeyPair::decode(&key).unwrap();
@@ -0,0 +1,185 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_OPEN_DURATION_SEC: u64 = 30;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+    /// How long requests fail fast once the circuit is open. After that, requests are let
+    /// through again: the first success closes the circuit, a failure opens it again.
+    pub open_duration_sec: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            open_duration_sec: DEFAULT_OPEN_DURATION_SEC,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    /// The open duration elapsed and requests are let through to probe the service.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Value of the circuit state gauge.
+    pub fn as_metric(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::Open => 1,
+            CircuitState::HalfOpen => 2,
+        }
+    }
+}
+
+/// Returned instead of sending a request while the circuit is open. Callers can detect it with
+/// `anyhow::Error::downcast_ref::<CircuitOpenError>()`.
+#[derive(Debug)]
+pub struct CircuitOpenError {
+    pub name: &'static str,
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Circuit breaker of the {} is open after repeated failures, retry in {:?}",
+            self.name, self.retry_in
+        )
+    }
+}
+
+impl std::error::Error for CircuitOpenError {}
+
+pub fn is_circuit_open(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<CircuitOpenError>().is_some()
+}
+
+/// Tracks consecutive failures of a remote service and makes requests fail fast while it is
+/// considered down.
+pub struct CircuitBreaker {
+    name: &'static str,
+    config: CircuitBreakerConfig,
+    consecutive_failures: AtomicU32,
+    opened_at: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name,
+            config,
+            consecutive_failures: AtomicU32::new(0),
+            opened_at: Mutex::new(None),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.opened_at.lock() {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_duration() => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Fails if requests must not be sent at the moment.
+    pub fn check(&self) -> Result<(), CircuitOpenError> {
+        match *self.opened_at.lock() {
+            Some(opened_at) if opened_at.elapsed() < self.open_duration() => {
+                Err(CircuitOpenError {
+                    name: self.name,
+                    retry_in: self.open_duration() - opened_at.elapsed(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        if self.opened_at.lock().take().is_some() {
+            info!("Circuit breaker of the {} is closed", self.name);
+        }
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < self.config.failure_threshold {
+            return;
+        }
+        let mut opened_at = self.opened_at.lock();
+        if opened_at.map_or(true, |opened_at| {
+            opened_at.elapsed() >= self.open_duration()
+        }) {
+            warn!(
+                "Circuit breaker of the {} is open after {} consecutive failures",
+                self.name, failures
+            );
+            *opened_at = Some(Instant::now());
+        }
+    }
+
+    fn open_duration(&self) -> Duration {
+        Duration::from_secs(self.config.open_duration_sec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(
+            "test service",
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                open_duration_sec: 60,
+            },
+        );
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        let err = anyhow::Error::from(breaker.check().unwrap_err());
+        assert!(is_circuit_open(&err));
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open() {
+        let breaker = CircuitBreaker::new(
+            "test service",
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                open_duration_sec: 0,
+            },
+        );
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.check().is_ok());
+    }
+}
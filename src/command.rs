@@ -2,22 +2,33 @@
 // Modifications Copyright (c) 2025 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::config::GasStationConfig;
-use crate::gas_station::gas_station_core::GasStationContainer;
+use crate::access_controller::pending_confirmations;
+use crate::config::{ConfigLoadOptions, GasStationConfig, DEFAULT_LOG_LEVEL};
+use crate::config_reloader::ConfigReloader;
+use crate::gas_station::gas_station_core::{GasStationContainer, PreviousSponsor};
 use crate::gas_station_initializer::GasStationInitializer;
 use crate::iota_client::IotaClient;
-use crate::metrics::{GasStationCoreMetrics, GasStationRpcMetrics, StorageMetrics};
+use crate::leader_election::LeaderElection;
+use crate::logging::TxLogger;
+use crate::metrics::{
+    GasStationCoreMetrics, GasStationRpcMetrics, IotaClientMetrics, StorageMetrics,
+};
+use crate::redis_connection::set_redis_security;
 use crate::rpc::GasStationServer;
 use crate::storage::connect_storage;
-use crate::tracker::stats_tracker_storage::redis::connect_stats_storage;
+use crate::tracker::scoped_gas_usage_caps::ScopedGasUsageCaps;
+use crate::tracker::stats_tracker_storage::{connect_stats_storage, StatsStorageConfig};
 use crate::tracker::StatsTracker;
-use crate::{TRANSACTION_LOGGING_ENV_NAME, TRANSACTION_LOGGING_TARGET_NAME, VERSION};
+use crate::webhooks::Webhooks;
+use crate::{
+    OTLP_ENDPOINT_ENV_NAME, TRANSACTION_LOGGING_ENV_NAME, TRANSACTION_LOGGING_TARGET_NAME, VERSION,
+};
 use arc_swap::ArcSwap;
 use clap::*;
-use iota_config::Config;
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 
 #[derive(Parser)]
@@ -30,52 +41,153 @@ use tracing::info;
 pub struct Command {
     #[arg(env, long, help = "Path to config file")]
     config_path: PathBuf,
+    #[arg(
+        env = "GAS_STATION_PROFILE",
+        long,
+        help = "Profile of the config file to apply, e.g. testnet or mainnet"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long,
+        help = "Reject config files with unknown fields or invalid settings, also when reloading"
+    )]
+    strict: bool,
 }
 
 impl Command {
     pub async fn execute(self) {
-        let config = GasStationConfig::load(&self.config_path).expect("Failed to load config file");
+        let load_options = ConfigLoadOptions {
+            profile: self.profile.clone(),
+            strict: self.strict,
+        };
+        let config = GasStationConfig::load_from_file(&self.config_path, &load_options)
+            .await
+            .expect("Failed to load config file");
+        // Snapshot of the startup config, used to detect changes that cannot be hot-reloaded.
+        let startup_config = GasStationConfig::load_from_file(&self.config_path, &load_options)
+            .await
+            .expect("Failed to load config file");
 
         let GasStationConfig {
             signer_config,
+            previous_signer_config,
             storage_config: gas_station_config,
+            stats_storage_config,
+            redis_security,
             fullnode_url,
+            failover_fullnode_urls,
+            expected_chain_identifier,
             fullnode_basic_auth,
+            fullnode_routing,
+            fullnode_retry_policy,
+            reference_gas_price_cache_ttl_sec,
+            local_execution_timeout_sec,
+            fullnode_circuit_breaker,
+            execution_limit,
+            cors,
+            ip_filter,
+            body_limit,
             rpc_host_ip,
             rpc_port,
+            admin_host_ip,
+            admin_port,
             metrics_port,
             coin_init_config,
+            leader_election,
             daily_gas_usage_cap,
+            daily_gas_usage_cap_per_sender,
+            daily_gas_usage_cap_per_package,
             mut access_controller,
+            log_level,
+            otlp_tracing,
+            transaction_log_sinks,
+            webhooks,
         } = config;
 
-        let metric_address = SocketAddr::new(IpAddr::V4(rpc_host_ip), metrics_port);
+        // The metrics are served on the first of the RPC addresses.
+        let metric_address = SocketAddr::new(rpc_host_ip.ips()[0], metrics_port);
         let registry_service = iota_metrics::start_prometheus_server(metric_address);
         let prometheus_registry = registry_service.default_registry();
         let mut telemetry_config = telemetry_subscribers::TelemetryConfig::new()
-            .with_log_level("off,iota_gas_station=debug")
+            .with_log_level(log_level.as_deref().unwrap_or(DEFAULT_LOG_LEVEL))
             .with_env()
             .with_prom_registry(&prometheus_registry);
 
         if std::env::var(TRANSACTION_LOGGING_ENV_NAME) == Ok("true".to_string()) {
             telemetry_config = telemetry_config.with_trace_target(TRANSACTION_LOGGING_TARGET_NAME);
         }
-        let _guard = telemetry_config.init();
+        if let Some(otlp_tracing) = &otlp_tracing {
+            // The exporter reads its endpoint from the environment.
+            std::env::set_var(OTLP_ENDPOINT_ENV_NAME, &otlp_tracing.endpoint);
+            telemetry_config.enable_otlp_tracing = true;
+            telemetry_config = telemetry_config.with_sample_rate(otlp_tracing.sample_rate);
+        }
+        let (_guard, tracing_handle) = telemetry_config.init();
         info!("Metrics server started at {:?}", metric_address);
+        if let Some(otlp_tracing) = &otlp_tracing {
+            info!("Exporting traces to {}", otlp_tracing.endpoint);
+        }
 
         let signer = signer_config.new_signer().await;
         let storage_metrics = StorageMetrics::new(&prometheus_registry);
         let sponsor_address = signer.get_address();
         info!("Sponsor address: {:?}", sponsor_address);
 
-        let storage = connect_storage(&gas_station_config, sponsor_address, storage_metrics).await;
-        let iota_client = IotaClient::new(&fullnode_url, fullnode_basic_auth).await;
-        let _coin_init_task = if let Some(coin_init_config) = coin_init_config {
+        if let Some(redis_security) = redis_security {
+            set_redis_security(redis_security).expect("Failed to set the Redis security settings");
+        }
+        let storage = connect_storage(
+            &gas_station_config,
+            sponsor_address,
+            storage_metrics.clone(),
+        )
+        .await;
+        let previous_sponsor = match previous_signer_config {
+            Some(previous_signer_config) => {
+                let signer = previous_signer_config.new_signer().await;
+                let previous_sponsor_address = signer.get_address();
+                info!("Previous sponsor address: {:?}", previous_sponsor_address);
+                let gas_station_store = connect_storage(
+                    &gas_station_config,
+                    previous_sponsor_address,
+                    storage_metrics.clone(),
+                )
+                .await;
+                Some(PreviousSponsor {
+                    signer,
+                    gas_station_store,
+                })
+            }
+            None => None,
+        };
+        let fullnode_urls = [vec![fullnode_url], failover_fullnode_urls].concat();
+        let iota_client =
+            IotaClient::new_with_failover(&fullnode_urls, fullnode_basic_auth, fullnode_routing)
+                .await
+                .with_retry_policy(fullnode_retry_policy)
+                .with_reference_gas_price_ttl(Duration::from_secs(
+                    reference_gas_price_cache_ttl_sec,
+                ))
+                .with_local_execution_timeout(Duration::from_secs(local_execution_timeout_sec))
+                .with_circuit_breaker(fullnode_circuit_breaker)
+                .with_metrics(IotaClientMetrics::new(&prometheus_registry));
+        let chain_identifier = iota_client
+            .verify_chain_identifier(expected_chain_identifier.as_deref())
+            .await
+            .expect("Failed to verify the network of the fullnode");
+        info!(
+            "Connected to the network with chain identifier {}",
+            chain_identifier
+        );
+        iota_client.start_reference_gas_price_refresh();
+        let leader_election = LeaderElection::start(storage.clone(), leader_election).await;
+        let coin_init_task = if let Some(coin_init_config) = coin_init_config {
             let task = GasStationInitializer::start(
                 iota_client.clone(),
                 storage.clone(),
                 coin_init_config,
                 signer.clone(),
+                leader_election.leadership(),
             )
             .await;
             Some(task)
@@ -83,17 +195,31 @@ impl Command {
             None
         };
         let core_metrics = GasStationCoreMetrics::new(&prometheus_registry);
-        let stats_storage = connect_stats_storage(&gas_station_config, sponsor_address).await;
-        let stats_tracker = StatsTracker::new(Arc::new(stats_storage));
+        let webhooks = Webhooks::from_config(&webhooks).expect("Failed to create the webhooks");
+        let stats_storage_config =
+            stats_storage_config.unwrap_or_else(|| StatsStorageConfig::from(&gas_station_config));
+        let stats_storage =
+            connect_stats_storage(&stats_storage_config, sponsor_address, &storage_metrics).await;
+        let stats_tracker = StatsTracker::new(stats_storage);
+        pending_confirmations::spawn_reconciliation(stats_tracker.clone(), iota_client.clone());
+        let scoped_gas_usage_caps = Arc::new(ScopedGasUsageCaps::new(
+            daily_gas_usage_cap_per_sender,
+            daily_gas_usage_cap_per_package,
+        ));
         let container = GasStationContainer::new(
             signer,
             storage,
+            previous_sponsor,
             iota_client,
             daily_gas_usage_cap,
             core_metrics,
+            webhooks,
+            leader_election.leadership(),
         )
         .await;
         let rpc_metrics = GasStationRpcMetrics::new(&prometheus_registry);
+        let tx_logger = TxLogger::from_config(&transaction_log_sinks)
+            .expect("Failed to create the transaction log sinks");
         access_controller
             .initialize()
             .await
@@ -103,15 +229,39 @@ impl Command {
             access_controller.rules.len()
         );
         let access_controller = Arc::new(ArcSwap::new(Arc::new(access_controller)));
+        let config_reloader = Arc::new(
+            ConfigReloader::new(
+                self.config_path.clone(),
+                &startup_config,
+                access_controller.clone(),
+                container.get_gas_station_arc(),
+                scoped_gas_usage_caps.clone(),
+                coin_init_task
+                    .as_ref()
+                    .map(|task| task.target_init_balance()),
+                Some(tracing_handle),
+            )
+            .with_load_options(load_options),
+        );
+        #[cfg(unix)]
+        let _sighup_task = config_reloader.clone().spawn_sighup_handler();
 
         let server = GasStationServer::new(
             container.get_gas_station_arc(),
-            rpc_host_ip,
+            rpc_host_ip.ips().to_vec(),
             rpc_port,
+            admin_host_ip.ips().to_vec(),
+            admin_port,
             rpc_metrics,
             access_controller,
             stats_tracker,
-            self.config_path.clone(),
+            scoped_gas_usage_caps,
+            tx_logger,
+            config_reloader,
+            execution_limit,
+            cors,
+            ip_filter,
+            body_limit,
         )
         .await;
         server.handle.await.unwrap();
@@ -2,80 +2,379 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::access_controller::AccessController;
-use crate::tx_signer::{SidecarTxSigner, TestTxSigner, TxSigner};
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::config_overrides::{apply_env_overrides, apply_profile};
+use crate::config_secrets::resolve_secrets;
+use crate::config_validation::validate_config;
+use crate::faucet::FaucetTopUpConfig;
+use crate::iota_client::FullnodeRouting;
+use crate::leader_election::LeaderElectionConfig;
+use crate::logging::TxLogSinkConfig;
+use crate::redis_connection::{RedisMode, RedisSecurityConfig};
+use crate::retry_policy::RetryPolicy;
+use crate::rpc::body_limit::BodyLimitConfig;
+use crate::rpc::cors::CorsConfig;
+use crate::rpc::execution_limiter::ExecutionLimitConfig;
+use crate::rpc::ip_filter::IpFilterConfig;
+use crate::tracker::stats_tracker_storage::StatsStorageConfig;
+use crate::tx_signer::{GcpKmsTxSigner, MultisigTxSigner, SidecarTxSigner, TestTxSigner, TxSigner};
+use crate::webhooks::WebhookConfig;
+use anyhow::Context;
 use iota_config::Config;
-use iota_types::crypto::{get_account_key_pair, IotaKeyPair};
+use iota_types::crypto::{get_account_key_pair, IotaKeyPair, PublicKey};
 use iota_types::gas_coin::NANOS_PER_IOTA;
+use iota_types::multisig::{ThresholdUnit, WeightUnit};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use std::net::Ipv4Addr;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
 pub const DEFAULT_RPC_PORT: u16 = 9527;
+pub const DEFAULT_ADMIN_PORT: u16 = 9528;
 pub const DEFAULT_METRICS_PORT: u16 = 9184;
 // 0.1 IOTA.
 pub const DEFAULT_INIT_COIN_BALANCE: u64 = NANOS_PER_IOTA / 10;
 // 24 hours.
 const DEFAULT_COIN_POOL_REFRESH_INTERVAL_SEC: u64 = 60 * 60 * 24;
 pub const DEFAULT_DAILY_GAS_USAGE_CAP: u64 = 1500 * NANOS_PER_IOTA;
+pub const DEFAULT_LOG_LEVEL: &str = "off,iota_gas_station=debug";
+pub const DEFAULT_SIDECAR_REQUEST_TIMEOUT_SEC: u64 = 10;
+pub const DEFAULT_SIDECAR_MAX_ATTEMPTS: usize = 3;
+pub const DEFAULT_SIDECAR_HEALTH_CHECK_INTERVAL_SEC: u64 = 10;
+pub const DEFAULT_REFERENCE_GAS_PRICE_CACHE_TTL_SEC: u64 = 60;
+pub const DEFAULT_LOCAL_EXECUTION_TIMEOUT_SEC: u64 = 30;
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4317";
 
 // Use 127.0.0.1 for tests to avoid OS complaining about permissions.
 #[cfg(test)]
 pub const LOCALHOST: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
 #[cfg(not(test))]
 pub const LOCALHOST: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+// The admin endpoints are only reachable from the local machine unless configured otherwise.
+pub const DEFAULT_ADMIN_HOST_IP: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
 
 #[serde_as]
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct GasStationConfig {
     pub signer_config: TxSignerConfig,
-    pub rpc_host_ip: Ipv4Addr,
+    /// Signer of the sponsor being rotated out. Pending reservations of the previous sponsor can
+    /// still be executed, while new reservations only use the current sponsor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_signer_config: Option<TxSignerConfig>,
+    pub rpc_host_ip: BindIps,
     pub rpc_port: u16,
+    /// IP addresses of the listeners serving the privileged endpoints, e.g. reloading the access
+    /// controller. Kept separate from the public RPC listeners.
+    #[serde(default = "default_admin_host_ip")]
+    pub admin_host_ip: BindIps,
+    #[serde(default = "default_admin_port")]
+    pub admin_port: u16,
     pub metrics_port: u16,
     pub storage_config: GasStationStorageConfig,
+    /// Where the stats tracker keeps the aggregates of the access controller rules, the usage
+    /// caps and the usage reports. Defaults to `storage_config`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_storage_config: Option<StatsStorageConfig>,
+    /// TLS and ACL settings of all the connections to Redis: the gas pool, the stats storage and
+    /// the Redis locations of the access controller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redis_security: Option<RedisSecurityConfig>,
     pub fullnode_url: String,
+    /// Additional fullnodes used when `fullnode_url` is unhealthy, or to route to the fastest
+    /// fullnode depending on `fullnode_routing`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover_fullnode_urls: Vec<String>,
+    /// Chain identifier of the network to sponsor transactions on, as returned by the
+    /// `iota_getChainIdentifier` RPC method. The Gas Station refuses to start when the fullnode is
+    /// on another network. Not checked if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_chain_identifier: Option<String>,
+    #[serde(default)]
+    pub fullnode_routing: FullnodeRouting,
+    /// How failed fullnode queries for coins, the reference gas price and object states are
+    /// retried before the error is returned.
+    #[serde(default)]
+    pub fullnode_retry_policy: RetryPolicy,
+    /// How long the reference gas price is cached at most. It is also refreshed on epoch change.
+    #[serde(default = "default_reference_gas_price_cache_ttl_sec")]
+    pub reference_gas_price_cache_ttl_sec: u64,
+    /// How long an execution with `WaitForLocalExecution` waits at most for the fullnodes to know
+    /// the transaction.
+    #[serde(default = "default_local_execution_timeout_sec")]
+    pub local_execution_timeout_sec: u64,
+    /// After how many consecutive failures requests to the fullnode fail fast, and for how long.
+    #[serde(default)]
+    pub fullnode_circuit_breaker: CircuitBreakerConfig,
+    /// Bounds the number of transactions executed at the same time. Unlimited if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_limit: Option<ExecutionLimitConfig>,
+    /// Origins, methods and headers of the browser dApps allowed to call the public endpoints.
+    /// Cross-origin requests are not allowed if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cors: Option<CorsConfig>,
+    /// Client IP addresses allowed or denied on the public endpoints. Any client is allowed if
+    /// not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_filter: Option<IpFilterConfig>,
+    /// Maximum size of the request bodies of the public endpoints, compressed or not.
+    #[serde(default)]
+    pub body_limit: BodyLimitConfig,
     /// An optional basic auth when connecting to the fullnode. If specified, the format is
     /// (username, password).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fullnode_basic_auth: Option<(String, String)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub coin_init_config: Option<CoinInitConfig>,
+    /// How the replicas sharing the storage elect the one initializing the pool and sweeping
+    /// the expired reservations.
+    #[serde(default)]
+    pub leader_election: LeaderElectionConfig,
     pub daily_gas_usage_cap: u64,
+    /// Maximum gas each sender can use per day. Unlimited if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_gas_usage_cap_per_sender: Option<u64>,
+    /// Maximum gas that transactions calling a given Move package can use per day. Unlimited if
+    /// not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_gas_usage_cap_per_package: Option<u64>,
     #[serde(default)]
     pub access_controller: AccessController,
+    /// Log filter directives, e.g. `info,iota_gas_station=debug`. Can be changed by reloading
+    /// the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Exports the spans of the requests to an OpenTelemetry collector. Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp_tracing: Option<OtlpTracingConfig>,
+    /// Destinations every executed transaction is published to, in addition to the
+    /// `transactions` tracing target.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transaction_log_sinks: Vec<TxLogSinkConfig>,
+    /// Webhooks notified when reservations are created or expire unused, and when transactions
+    /// are executed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
 }
 
 impl Config for GasStationConfig {}
 
+/// How the config file is loaded, the same at startup and when reloading.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoadOptions {
+    /// Profile of the config file applied over its top-level fields.
+    pub profile: Option<String>,
+    /// Rejects unknown fields and invalid settings.
+    pub strict: bool,
+}
+
+impl GasStationConfig {
+    /// Loads the config file with the selected profile, the fields overridden by the
+    /// `GAS_STATION__*` environment variables and the secret references resolved.
+    pub async fn load_from_file(path: &Path, options: &ConfigLoadOptions) -> anyhow::Result<Self> {
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&file)?;
+        apply_profile(&mut config, options.profile.as_deref())?;
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        apply_env_overrides(&mut config, vars)?;
+        resolve_secrets(&mut config).await?;
+        let gas_station_config = serde_yaml::from_value(config.clone())?;
+        if options.strict {
+            validate_config(&config, &gas_station_config)?;
+        }
+        Ok(gas_station_config)
+    }
+
+    /// All the configured fullnode URLs, the primary one first.
+    pub fn fullnode_urls(&self) -> Vec<String> {
+        std::iter::once(self.fullnode_url.clone())
+            .chain(self.failover_fullnode_urls.iter().cloned())
+            .collect()
+    }
+}
+
 impl Default for GasStationConfig {
     fn default() -> Self {
         GasStationConfig {
             signer_config: TxSignerConfig::default(),
-            rpc_host_ip: LOCALHOST,
+            previous_signer_config: None,
+            rpc_host_ip: LOCALHOST.into(),
             rpc_port: DEFAULT_RPC_PORT,
+            admin_host_ip: DEFAULT_ADMIN_HOST_IP.into(),
+            admin_port: DEFAULT_ADMIN_PORT,
             metrics_port: DEFAULT_METRICS_PORT,
             storage_config: GasStationStorageConfig::default(),
+            stats_storage_config: None,
+            redis_security: None,
             fullnode_url: "http://localhost:9000".to_string(),
+            failover_fullnode_urls: vec![],
+            expected_chain_identifier: None,
+            fullnode_routing: FullnodeRouting::default(),
+            fullnode_retry_policy: RetryPolicy::default(),
+            reference_gas_price_cache_ttl_sec: DEFAULT_REFERENCE_GAS_PRICE_CACHE_TTL_SEC,
+            local_execution_timeout_sec: DEFAULT_LOCAL_EXECUTION_TIMEOUT_SEC,
+            fullnode_circuit_breaker: CircuitBreakerConfig::default(),
+            execution_limit: None,
+            cors: None,
+            ip_filter: None,
+            body_limit: BodyLimitConfig::default(),
             fullnode_basic_auth: None,
             coin_init_config: Some(CoinInitConfig::default()),
+            leader_election: LeaderElectionConfig::default(),
             daily_gas_usage_cap: DEFAULT_DAILY_GAS_USAGE_CAP,
+            daily_gas_usage_cap_per_sender: None,
+            daily_gas_usage_cap_per_package: None,
             access_controller: AccessController::default(),
+            log_level: None,
+            otlp_tracing: None,
+            transaction_log_sinks: vec![],
+            webhooks: vec![],
         }
     }
 }
 
+fn default_admin_host_ip() -> BindIps {
+    DEFAULT_ADMIN_HOST_IP.into()
+}
+
+/// IP addresses to listen on, IPv4 or IPv6, given as a single address or as a list. A single
+/// `::` also accepts the IPv4 clients (dual-stack).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "OneOrManyIps", into = "OneOrManyIps")]
+pub struct BindIps(Vec<IpAddr>);
+
+impl BindIps {
+    pub fn ips(&self) -> &[IpAddr] {
+        &self.0
+    }
+}
+
+impl From<Ipv4Addr> for BindIps {
+    fn from(ip: Ipv4Addr) -> Self {
+        Self(vec![IpAddr::V4(ip)])
+    }
+}
+
+impl From<IpAddr> for BindIps {
+    fn from(ip: IpAddr) -> Self {
+        Self(vec![ip])
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum OneOrManyIps {
+    One(IpAddr),
+    Many(Vec<IpAddr>),
+}
+
+impl TryFrom<OneOrManyIps> for BindIps {
+    type Error = anyhow::Error;
+
+    fn try_from(ips: OneOrManyIps) -> Result<Self, Self::Error> {
+        let ips = match ips {
+            OneOrManyIps::One(ip) => vec![ip],
+            OneOrManyIps::Many(ips) => ips,
+        };
+        anyhow::ensure!(
+            !ips.is_empty(),
+            "At least one IP address to listen on is required"
+        );
+        Ok(Self(ips))
+    }
+}
+
+impl From<BindIps> for OneOrManyIps {
+    fn from(ips: BindIps) -> Self {
+        match <[IpAddr; 1]>::try_from(ips.0) {
+            Ok([ip]) => OneOrManyIps::One(ip),
+            Err(ips) => OneOrManyIps::Many(ips),
+        }
+    }
+}
+
+fn default_admin_port() -> u16 {
+    DEFAULT_ADMIN_PORT
+}
+
+fn default_reference_gas_price_cache_ttl_sec() -> u64 {
+    DEFAULT_REFERENCE_GAS_PRICE_CACHE_TTL_SEC
+}
+
+fn default_local_execution_timeout_sec() -> u64 {
+    DEFAULT_LOCAL_EXECUTION_TIMEOUT_SEC
+}
+
 #[serde_as]
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum GasStationStorageConfig {
-    Redis { redis_url: String },
+    Redis {
+        redis_url: String,
+        /// Keeps the keys of the station apart from those of other stations sharing the same
+        /// Redis, e.g. `staging` and `prod`. Without it, the keys are only prefixed by the sponsor
+        /// address.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+        /// How to connect to Redis: a single node, Redis Sentinel or Redis Cluster.
+        #[serde(default, skip_serializing_if = "RedisMode::is_single")]
+        mode: RedisMode,
+    },
+}
+
+impl GasStationStorageConfig {
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            Self::Redis { namespace, .. } => namespace.as_deref(),
+        }
+    }
+
+    pub fn with_namespace(self, namespace: Option<String>) -> Self {
+        match self {
+            Self::Redis {
+                redis_url, mode, ..
+            } => Self::Redis {
+                redis_url,
+                namespace,
+                mode,
+            },
+        }
+    }
+}
+
+impl FromStr for GasStationStorageConfig {
+    type Err = anyhow::Error;
+
+    /// Picks the storage backend from the scheme of the URL, e.g. `redis://127.0.0.1:6379`.
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        // The URL isn't part of the error, since it may contain a password.
+        match url.split_once("://").map(|(scheme, _)| scheme) {
+            Some("redis" | "rediss" | "redis+unix" | "unix") => Ok(Self::Redis {
+                redis_url: url.to_string(),
+                namespace: None,
+                mode: RedisMode::Single,
+            }),
+            Some(scheme) => anyhow::bail!(
+                "Unsupported storage {}://, only Redis storages are supported",
+                scheme
+            ),
+            None => anyhow::bail!("Invalid storage URL, expected e.g. redis://127.0.0.1:6379"),
+        }
+    }
 }
 
 impl Default for GasStationStorageConfig {
     fn default() -> Self {
         Self::Redis {
             redis_url: "redis://127.0.0.1:6379".to_string(),
+            namespace: None,
+            mode: RedisMode::Single,
         }
     }
 }
@@ -85,7 +384,9 @@ impl Default for GasStationStorageConfig {
 #[serde(rename_all = "kebab-case")]
 pub enum TxSignerConfig {
     Local { keypair: IotaKeyPair },
-    Sidecar { sidecar_url: String },
+    Sidecar(SidecarSignerConfig),
+    GcpKms { key_version_name: String },
+    Multisig(MultisigSignerConfig),
 }
 
 impl Default for TxSignerConfig {
@@ -101,9 +402,134 @@ impl TxSignerConfig {
     pub async fn new_signer(self) -> Arc<dyn TxSigner> {
         match self {
             TxSignerConfig::Local { keypair } => TestTxSigner::new(keypair),
-            TxSignerConfig::Sidecar { sidecar_url } => SidecarTxSigner::new(sidecar_url).await,
+            TxSignerConfig::Sidecar(sidecar_config) => SidecarTxSigner::new(sidecar_config).await,
+            TxSignerConfig::GcpKms { key_version_name } => {
+                GcpKmsTxSigner::new(key_version_name).await
+            }
+            TxSignerConfig::Multisig(multisig_config) => {
+                MultisigTxSigner::new(multisig_config).await
+            }
+        }
+    }
+}
+
+/// Sponsor whose address is a multisig of several signers, e.g. held by different custodians.
+/// The members must be listed in the same order as when the multisig address was derived.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MultisigSignerConfig {
+    pub threshold: ThresholdUnit,
+    pub members: Vec<MultisigMemberConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MultisigMemberConfig {
+    pub signer: TxSignerConfig,
+    #[serde(default = "default_multisig_weight")]
+    pub weight: WeightUnit,
+    /// Required for the sidecar signers, which only expose their address. The other signers
+    /// know their public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<PublicKey>,
+}
+
+fn default_multisig_weight() -> WeightUnit {
+    1
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SidecarSignerConfig {
+    #[serde(alias = "sidecar_url")]
+    pub sidecar_url: String,
+    /// Additional sidecars signing with the same key as the one at `sidecar_url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover_sidecar_urls: Vec<String>,
+    #[serde(default)]
+    pub load_balancing: SidecarLoadBalancing,
+    #[serde(default = "default_sidecar_health_check_interval_sec")]
+    pub health_check_interval_sec: u64,
+    /// Bearer token sent in the `Authorization` header of every request to the sidecar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer_token: Option<String>,
+    /// Static headers sent with every request to the sidecar.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Client certificate for mutual TLS with the sidecar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<SidecarTlsConfig>,
+    #[serde(default = "default_sidecar_request_timeout_sec")]
+    pub request_timeout_sec: u64,
+    /// How many times a request is attempted before giving up. Attempts are spaced with an
+    /// exponential backoff.
+    #[serde(default = "default_sidecar_max_attempts")]
+    pub max_attempts: usize,
+    /// Refuse to execute the transactions of users without an attestation of their access
+    /// decision, which the sidecar checks before signing.
+    #[serde(default)]
+    pub require_attestation: bool,
+}
+
+impl SidecarSignerConfig {
+    pub fn new(sidecar_url: String) -> Self {
+        Self {
+            sidecar_url,
+            failover_sidecar_urls: vec![],
+            load_balancing: SidecarLoadBalancing::default(),
+            health_check_interval_sec: DEFAULT_SIDECAR_HEALTH_CHECK_INTERVAL_SEC,
+            bearer_token: None,
+            headers: BTreeMap::new(),
+            tls: None,
+            request_timeout_sec: DEFAULT_SIDECAR_REQUEST_TIMEOUT_SEC,
+            max_attempts: DEFAULT_SIDECAR_MAX_ATTEMPTS,
+            require_attestation: false,
         }
     }
+
+    /// All the configured sidecar URLs, the primary one first.
+    pub fn sidecar_urls(&self) -> Vec<String> {
+        std::iter::once(self.sidecar_url.clone())
+            .chain(self.failover_sidecar_urls.iter().cloned())
+            .collect()
+    }
+}
+
+fn default_sidecar_health_check_interval_sec() -> u64 {
+    DEFAULT_SIDECAR_HEALTH_CHECK_INTERVAL_SEC
+}
+
+fn default_sidecar_request_timeout_sec() -> u64 {
+    DEFAULT_SIDECAR_REQUEST_TIMEOUT_SEC
+}
+
+fn default_sidecar_max_attempts() -> usize {
+    DEFAULT_SIDECAR_MAX_ATTEMPTS
+}
+
+/// How requests are spread over the configured sidecars.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SidecarLoadBalancing {
+    #[default]
+    /// Always use the first healthy sidecar, in the configured order.
+    Failover,
+    /// Rotate over the healthy sidecars.
+    RoundRobin,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SidecarTlsConfig {
+    /// PEM encoded client certificate.
+    pub client_cert_path: PathBuf,
+    /// PEM encoded PKCS#8 private key of the client certificate.
+    pub client_key_path: PathBuf,
+    /// PEM encoded CA certificate used to verify the sidecar, if it is not signed by a public CA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert_path: Option<PathBuf>,
 }
 
 #[serde_as]
@@ -116,6 +542,14 @@ pub struct CoinInitConfig {
     /// requires initialization, i.e. splitting into smaller coins and add them to the Gas Station.
     /// This is in seconds.
     pub refresh_interval_sec: u64,
+    /// Requests funds from the faucet when the pool runs low. Only on test networks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faucet_top_up: Option<FaucetTopUpConfig>,
+    /// Available coins of the pool with a balance above this many times `target_init_balance`,
+    /// e.g. left by the change of large payments, are split again on each refresh. Not split if
+    /// not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resplit_balance_factor: Option<u64>,
 }
 
 impl Default for CoinInitConfig {
@@ -123,6 +557,154 @@ impl Default for CoinInitConfig {
         CoinInitConfig {
             target_init_balance: DEFAULT_INIT_COIN_BALANCE,
             refresh_interval_sec: DEFAULT_COIN_POOL_REFRESH_INTERVAL_SEC,
+            faucet_top_up: None,
+            resplit_balance_factor: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct OtlpTracingConfig {
+    /// gRPC endpoint of the OTLP collector.
+    pub endpoint: String,
+    /// Fraction of the requests that are traced, between 0 and 1.
+    pub sample_rate: f64,
+}
+
+impl Default for OtlpTracingConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_OTLP_ENDPOINT.to_string(),
+            sample_rate: 1.0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_config_from_url() {
+        let config: GasStationStorageConfig = "redis://:password@127.0.0.1:6379".parse().unwrap();
+        assert!(matches!(
+            config,
+            GasStationStorageConfig::Redis { redis_url, namespace: None, mode: RedisMode::Single } if redis_url == "redis://:password@127.0.0.1:6379"
+        ));
+        let err = "postgres://localhost/gas_station"
+            .parse::<GasStationStorageConfig>()
+            .unwrap_err();
+        assert!(!err.to_string().contains("localhost"));
+    }
+
+    #[test]
+    fn test_bind_ips() {
+        let ips: BindIps = serde_yaml::from_str("0.0.0.0").unwrap();
+        assert_eq!(ips.ips(), &["0.0.0.0".parse::<IpAddr>().unwrap()]);
+        assert_eq!(serde_yaml::to_string(&ips).unwrap().trim(), "0.0.0.0");
+
+        let ips: BindIps = serde_yaml::from_str("[\"0.0.0.0\", \"::\"]").unwrap();
+        assert_eq!(
+            ips.ips(),
+            &[
+                "0.0.0.0".parse::<IpAddr>().unwrap(),
+                "::".parse::<IpAddr>().unwrap()
+            ]
+        );
+        assert!(serde_yaml::from_str::<BindIps>("[]").is_err());
+    }
+
+    #[test]
+    fn test_otlp_tracing_config() {
+        let config: OtlpTracingConfig = serde_yaml::from_str("sample-rate: 0.1").unwrap();
+        assert_eq!(config.endpoint, DEFAULT_OTLP_ENDPOINT);
+        assert_eq!(config.sample_rate, 0.1);
+    }
+
+    #[test]
+    fn test_multisig_signer_config() {
+        let config: TxSignerConfig = serde_yaml::from_str(
+            r#"
+multisig:
+  threshold: 2
+  members:
+    - signer:
+        local:
+          keypair: AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f
+    - weight: 2
+      signer:
+        sidecar:
+          sidecar-url: https://localhost:3000
+"#,
+        )
+        .unwrap();
+        let TxSignerConfig::Multisig(config) = config else {
+            panic!("Expected multisig signer config");
+        };
+        assert_eq!(config.threshold, 2);
+        assert_eq!(config.members.len(), 2);
+        assert_eq!(config.members[0].weight, 1);
+        assert_eq!(config.members[1].weight, 2);
+        assert!(config.members[1].public_key.is_none());
+        assert!(matches!(
+            config.members[1].signer,
+            TxSignerConfig::Sidecar(_)
+        ));
+    }
+
+    #[test]
+    fn test_sidecar_signer_config() {
+        let config: TxSignerConfig =
+            serde_yaml::from_str("sidecar:\n  sidecar_url: http://localhost:3000\n").unwrap();
+        let TxSignerConfig::Sidecar(config) = config else {
+            panic!("Expected sidecar signer config");
+        };
+        assert_eq!(config.sidecar_url, "http://localhost:3000");
+        assert_eq!(
+            config.request_timeout_sec,
+            DEFAULT_SIDECAR_REQUEST_TIMEOUT_SEC
+        );
+        assert_eq!(config.max_attempts, DEFAULT_SIDECAR_MAX_ATTEMPTS);
+        assert!(!config.require_attestation);
+        assert_eq!(config.load_balancing, SidecarLoadBalancing::Failover);
+        assert_eq!(config.sidecar_urls(), vec!["http://localhost:3000"]);
+
+        let config: TxSignerConfig = serde_yaml::from_str(
+            r#"
+sidecar:
+  sidecar-url: https://localhost:3000
+  bearer-token: secret
+  headers:
+    x-api-key: key
+  tls:
+    client-cert-path: /certs/client.pem
+    client-key-path: /certs/client.key
+  failover-sidecar-urls:
+    - https://localhost:3001
+  load-balancing: round-robin
+  request-timeout-sec: 5
+  max-attempts: 1
+  require-attestation: true
+"#,
+        )
+        .unwrap();
+        let TxSignerConfig::Sidecar(config) = config else {
+            panic!("Expected sidecar signer config");
+        };
+        assert_eq!(config.bearer_token.as_deref(), Some("secret"));
+        assert_eq!(
+            config.headers.get("x-api-key").map(String::as_str),
+            Some("key")
+        );
+        assert!(config.tls.as_ref().unwrap().ca_cert_path.is_none());
+        assert_eq!(config.request_timeout_sec, 5);
+        assert_eq!(config.max_attempts, 1);
+        assert!(config.require_attestation);
+        assert_eq!(config.load_balancing, SidecarLoadBalancing::RoundRobin);
+        assert_eq!(
+            config.sidecar_urls(),
+            vec!["https://localhost:3000", "https://localhost:3001"]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Overrides of the config file fields by profiles and environment variables, so that several
+//! networks or container deployments can share the same config file and only set the fields that
+//! differ.
+
+use anyhow::{bail, Context};
+use serde_yaml::Value;
+
+/// Field of the config file holding the profiles, by name.
+pub const PROFILES_FIELD: &str = "profiles";
+
+/// Prefix of the environment variables overriding config fields, e.g. `GAS_STATION__RPC_PORT`.
+pub const CONFIG_ENV_PREFIX: &str = "GAS_STATION__";
+
+/// Separates the nested fields, e.g. `GAS_STATION__COIN_INIT_CONFIG__TARGET_INIT_BALANCE`.
+const NESTED_FIELD_SEPARATOR: &str = "__";
+
+/// Applies the fields of the profile over the top-level fields of the config, e.g.
+/// `profiles.mainnet.fullnode-url` over `fullnode-url`. Mappings are merged field by field, other
+/// values are replaced. The profiles are removed from the config, whether one is selected or not.
+pub fn apply_profile(config: &mut Value, profile: Option<&str>) -> anyhow::Result<()> {
+    let profiles = match config {
+        Value::Mapping(mapping) => mapping.remove(&Value::String(PROFILES_FIELD.to_string())),
+        _ => None,
+    };
+    let Some(name) = profile else {
+        return Ok(());
+    };
+    let Some(Value::Mapping(profiles)) = profiles else {
+        bail!("Profile {} not found: the config has no profiles", name);
+    };
+    let Some(profile) = profiles.get(&Value::String(name.to_string())) else {
+        let names = profiles.iter().filter_map(|(name, _)| name.as_str());
+        bail!(
+            "Profile {} not found, the config has the profiles: {}",
+            name,
+            names.collect::<Vec<_>>().join(", ")
+        );
+    };
+    merge(config, profile.clone());
+    Ok(())
+}
+
+fn merge(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Mapping(base), Value::Mapping(value)) => {
+            for (key, value) in value {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// Applies the variables starting with [`CONFIG_ENV_PREFIX`] to the YAML config. Their values are
+/// parsed as YAML, so numbers, booleans and lists like `[a, b]` keep their type.
+pub fn apply_env_overrides(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> anyhow::Result<()> {
+    let mut overrides: Vec<_> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let path = name.strip_prefix(CONFIG_ENV_PREFIX)?.to_string();
+            Some((name, path, value))
+        })
+        .collect();
+    // Parents before their fields, so that a field can refine an overridden parent.
+    overrides.sort();
+    for (name, path, value) in overrides {
+        let path: Vec<String> = path
+            .split(NESTED_FIELD_SEPARATOR)
+            .map(|segment| segment.to_ascii_lowercase())
+            .collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            bail!("Invalid config override {}: empty field name", name);
+        }
+        let value = serde_yaml::from_str(&value).unwrap_or(Value::String(value));
+        set_field(config, &path, value)
+            .with_context(|| format!("Invalid config override {}", name))?;
+    }
+    Ok(())
+}
+
+fn set_field(node: &mut Value, path: &[String], value: Value) -> anyhow::Result<()> {
+    let Some((field, rest)) = path.split_first() else {
+        *node = value;
+        return Ok(());
+    };
+    if node.is_null() {
+        *node = Value::Mapping(Default::default());
+    }
+    let child = match node {
+        Value::Mapping(mapping) => {
+            // Matches the existing key whatever its case, e.g. `redis_url` or `fullnode-url`.
+            let key = mapping
+                .iter()
+                .map(|(key, _)| key)
+                .find(|key| {
+                    key.as_str()
+                        .is_some_and(|key| key.to_ascii_lowercase().replace('-', "_") == *field)
+                })
+                .cloned()
+                .unwrap_or_else(|| Value::String(field.replace('_', "-")));
+            if !mapping.contains_key(&key) {
+                mapping.insert(key.clone(), Value::Null);
+            }
+            mapping.get_mut(&key).unwrap()
+        }
+        Value::Sequence(sequence) => {
+            let index: usize = field
+                .parse()
+                .with_context(|| format!("{} is not a list index", field))?;
+            if index == sequence.len() {
+                sequence.push(Value::Null);
+            }
+            sequence
+                .get_mut(index)
+                .with_context(|| format!("List index {} is out of bounds", index))?
+        }
+        _ => bail!("{} is not a field of a mapping or a list", field),
+    };
+    set_field(child, rest, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_env_overrides() {
+        let mut config: Value = serde_yaml::from_str(
+            r#"
+rpc-port: 9527
+fullnode-url: "http://localhost:9000"
+storage-config:
+  redis:
+    redis_url: "redis://127.0.0.1"
+failover-fullnode-urls: ["http://localhost:9001"]
+"#,
+        )
+        .unwrap();
+        let vars = [
+            ("GAS_STATION__RPC_PORT", "9000"),
+            ("GAS_STATION__FULLNODE_URL", "https://api.testnet.iota.cafe"),
+            (
+                "GAS_STATION__STORAGE_CONFIG__REDIS__REDIS_URL",
+                "redis://redis:6379",
+            ),
+            (
+                "GAS_STATION__FAILOVER_FULLNODE_URLS__1",
+                "http://localhost:9002",
+            ),
+            ("GAS_STATION__COIN_INIT_CONFIG__TARGET_INIT_BALANCE", "1000"),
+            ("GAS_STATION_AUTH", "not a config field"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        apply_env_overrides(&mut config, vars).unwrap();
+
+        let expected: Value = serde_yaml::from_str(
+            r#"
+rpc-port: 9000
+fullnode-url: "https://api.testnet.iota.cafe"
+storage-config:
+  redis:
+    redis_url: "redis://redis:6379"
+failover-fullnode-urls: ["http://localhost:9001", "http://localhost:9002"]
+coin-init-config:
+  target-init-balance: 1000
+"#,
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let yaml = r#"
+fullnode-url: "http://localhost:9000"
+daily-gas-usage-cap: 1500000000000
+access-controller:
+  access-policy: deny-all
+  rules:
+    - sender-address: "*"
+      action: allow
+profiles:
+  mainnet:
+    fullnode-url: "https://api.mainnet.iota.cafe"
+    access-controller:
+      rules: []
+"#;
+        let mut config: Value = serde_yaml::from_str(yaml).unwrap();
+        apply_profile(&mut config, Some("mainnet")).unwrap();
+        let expected: Value = serde_yaml::from_str(
+            r#"
+fullnode-url: "https://api.mainnet.iota.cafe"
+daily-gas-usage-cap: 1500000000000
+access-controller:
+  access-policy: deny-all
+  rules: []
+"#,
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        // Without a profile, only the top-level fields are used
+        let mut config: Value = serde_yaml::from_str(yaml).unwrap();
+        apply_profile(&mut config, None).unwrap();
+        assert!(config.get(PROFILES_FIELD).is_none());
+        assert_eq!(config["fullnode-url"], "http://localhost:9000");
+
+        let mut config: Value = serde_yaml::from_str(yaml).unwrap();
+        let err = apply_profile(&mut config, Some("testnet")).unwrap_err();
+        assert!(err.to_string().contains("mainnet"));
+    }
+
+    #[test]
+    fn test_invalid_env_override() {
+        let mut config: Value = serde_yaml::from_str("rpc-port: 9527").unwrap();
+        let vars = [("GAS_STATION__RPC_PORT__PORT".to_string(), "1".to_string())];
+        assert!(apply_env_overrides(&mut config, vars).is_err());
+        let vars = [("GAS_STATION____RPC_PORT".to_string(), "1".to_string())];
+        assert!(apply_env_overrides(&mut config, vars).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::access_controller::AccessController;
+use crate::config::{ConfigLoadOptions, GasStationConfig};
+use crate::gas_station::gas_station_core::GasStation;
+use crate::tracker::scoped_gas_usage_caps::ScopedGasUsageCaps;
+use anyhow::{anyhow, bail, Context};
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use telemetry_subscribers::TracingHandle;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+type Settings = BTreeMap<&'static str, Value>;
+
+/// Re-reads the config file and hot-applies the settings that can be changed at runtime:
+/// the access controller rules (including the gas usage rate limits), the daily gas usage caps,
+/// the target balance of newly initialized coins and the log level.
+///
+/// All other settings require a restart. A reload that changes any of them is rejected as a
+/// whole, without applying anything.
+pub struct ConfigReloader {
+    config_path: PathBuf,
+    restart_required_settings: Settings,
+    access_controller: Arc<ArcSwap<AccessController>>,
+    gas_station: Arc<GasStation>,
+    scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+    target_init_balance: Option<Arc<AtomicU64>>,
+    tracing_handle: Option<TracingHandle>,
+    load_options: ConfigLoadOptions,
+    // Serializes concurrent reloads, e.g. an RPC request racing with a SIGHUP.
+    reload_lock: Mutex<()>,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config_path: PathBuf,
+        current_config: &GasStationConfig,
+        access_controller: Arc<ArcSwap<AccessController>>,
+        gas_station: Arc<GasStation>,
+        scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+        target_init_balance: Option<Arc<AtomicU64>>,
+        tracing_handle: Option<TracingHandle>,
+    ) -> Self {
+        Self {
+            config_path,
+            restart_required_settings: restart_required_settings(current_config),
+            access_controller,
+            gas_station,
+            scoped_gas_usage_caps,
+            target_init_balance,
+            tracing_handle,
+            load_options: ConfigLoadOptions::default(),
+            reload_lock: Mutex::new(()),
+        }
+    }
+
+    /// Loads the config file like at startup, e.g. with the same profile.
+    pub fn with_load_options(mut self, load_options: ConfigLoadOptions) -> Self {
+        self.load_options = load_options;
+        self
+    }
+
+    /// Returns the target balance of newly initialized coins, if the coin initializer runs.
+    pub fn target_init_balance(&self) -> Option<u64> {
+        self.target_init_balance
+            .as_ref()
+            .map(|target_init_balance| target_init_balance.load(Ordering::Relaxed))
+    }
+
+    /// Changes the target balance of newly initialized coins until the next reload. It applies
+    /// from the next run of the coin initializer; coins being split keep their target.
+    pub fn set_target_init_balance(&self, balance: u64) -> anyhow::Result<()> {
+        let Some(target_init_balance) = &self.target_init_balance else {
+            bail!("The coin initializer is not running");
+        };
+        if balance == 0 {
+            bail!("The target init balance must be positive");
+        }
+        target_init_balance.store(balance, Ordering::Relaxed);
+        info!("Target init balance set to {}", balance);
+        Ok(())
+    }
+
+    /// Reloads only the access controller from the config file.
+    pub async fn reload_access_controller(&self) -> anyhow::Result<()> {
+        let _guard = self.reload_lock.lock().await;
+        let mut access_controller =
+            GasStationConfig::load_from_file(&self.config_path, &self.load_options)
+                .await
+                .context("Failed to load config file")?
+                .access_controller;
+        access_controller.initialize().await?;
+        self.access_controller.store(Arc::new(access_controller));
+        info!(
+            "Access controller reloaded successfully with {} rules",
+            self.access_controller.load().rules.len()
+        );
+        Ok(())
+    }
+
+    /// Reloads all the hot-reloadable settings from the config file.
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let _guard = self.reload_lock.lock().await;
+        let new_config = GasStationConfig::load_from_file(&self.config_path, &self.load_options)
+            .await
+            .context("Failed to load config file")?;
+        let changed = changed_settings(
+            &self.restart_required_settings,
+            &restart_required_settings(&new_config),
+        );
+        if !changed.is_empty() {
+            bail!(
+                "The following settings cannot be reloaded and require a restart: {}",
+                changed.join(", ")
+            );
+        }
+
+        let GasStationConfig {
+            mut access_controller,
+            daily_gas_usage_cap,
+            daily_gas_usage_cap_per_sender,
+            daily_gas_usage_cap_per_package,
+            coin_init_config,
+            log_level,
+            ..
+        } = new_config;
+        access_controller.initialize().await?;
+        if let (Some(tracing_handle), Some(log_level)) = (&self.tracing_handle, &log_level) {
+            tracing_handle
+                .update_log(log_level)
+                .map_err(|err| anyhow!("Invalid log level {:?}: {}", log_level, err))?;
+        }
+
+        // Nothing below can fail, so the reload is never partially applied.
+        self.access_controller.store(Arc::new(access_controller));
+        self.gas_station
+            .set_daily_gas_usage_cap(daily_gas_usage_cap);
+        self.scoped_gas_usage_caps.set_caps(
+            daily_gas_usage_cap_per_sender,
+            daily_gas_usage_cap_per_package,
+        );
+        if let (Some(target_init_balance), Some(coin_init_config)) =
+            (&self.target_init_balance, coin_init_config)
+        {
+            target_init_balance.store(coin_init_config.target_init_balance, Ordering::Relaxed);
+        }
+        info!(
+            "Config reloaded successfully. Access controller rules: {}, daily gas usage cap: {}",
+            self.access_controller.load().rules.len(),
+            daily_gas_usage_cap
+        );
+        Ok(())
+    }
+
+    /// Reloads the config every time the process receives a SIGHUP.
+    #[cfg(unix)]
+    pub fn spawn_sighup_handler(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sighup = signal(SignalKind::hangup()).expect("Failed to register SIGHUP handler");
+        tokio::spawn(async move {
+            while sighup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading config");
+                if let Err(err) = self.reload().await {
+                    error!("Failed to reload config: {:?}", err);
+                }
+            }
+        })
+    }
+}
+
+fn restart_required_settings(config: &GasStationConfig) -> Settings {
+    BTreeMap::from([
+        ("signer-config", to_value(&config.signer_config)),
+        (
+            "previous-signer-config",
+            to_value(&config.previous_signer_config),
+        ),
+        ("rpc-host-ip", to_value(&config.rpc_host_ip)),
+        ("rpc-port", to_value(&config.rpc_port)),
+        ("admin-host-ip", to_value(&config.admin_host_ip)),
+        ("admin-port", to_value(&config.admin_port)),
+        ("metrics-port", to_value(&config.metrics_port)),
+        ("storage-config", to_value(&config.storage_config)),
+        ("redis-security", to_value(&config.redis_security)),
+        (
+            "stats-storage-config",
+            to_value(&config.stats_storage_config),
+        ),
+        ("fullnode-url", to_value(&config.fullnode_url)),
+        (
+            "failover-fullnode-urls",
+            to_value(&config.failover_fullnode_urls),
+        ),
+        (
+            "expected-chain-identifier",
+            to_value(&config.expected_chain_identifier),
+        ),
+        ("fullnode-routing", to_value(&config.fullnode_routing)),
+        (
+            "fullnode-retry-policy",
+            to_value(&config.fullnode_retry_policy),
+        ),
+        (
+            "reference-gas-price-cache-ttl-sec",
+            to_value(&config.reference_gas_price_cache_ttl_sec),
+        ),
+        (
+            "local-execution-timeout-sec",
+            to_value(&config.local_execution_timeout_sec),
+        ),
+        (
+            "fullnode-circuit-breaker",
+            to_value(&config.fullnode_circuit_breaker),
+        ),
+        ("execution-limit", to_value(&config.execution_limit)),
+        ("cors", to_value(&config.cors)),
+        ("ip-filter", to_value(&config.ip_filter)),
+        ("body-limit", to_value(&config.body_limit)),
+        ("fullnode-basic-auth", to_value(&config.fullnode_basic_auth)),
+        ("otlp-tracing", to_value(&config.otlp_tracing)),
+        (
+            "transaction-log-sinks",
+            to_value(&config.transaction_log_sinks),
+        ),
+        ("webhooks", to_value(&config.webhooks)),
+        ("leader-election", to_value(&config.leader_election)),
+        (
+            "coin-init-config.refresh-interval-sec",
+            to_value(
+                &config
+                    .coin_init_config
+                    .as_ref()
+                    .map(|c| c.refresh_interval_sec),
+            ),
+        ),
+        (
+            "coin-init-config.resplit-balance-factor",
+            to_value(
+                &config
+                    .coin_init_config
+                    .as_ref()
+                    .map(|c| c.resplit_balance_factor),
+            ),
+        ),
+        (
+            "coin-init-config.faucet-top-up",
+            to_value(
+                &config
+                    .coin_init_config
+                    .as_ref()
+                    .map(|c| c.faucet_top_up.clone()),
+            ),
+        ),
+    ])
+}
+
+fn changed_settings(current: &Settings, new: &Settings) -> Vec<&'static str> {
+    current
+        .iter()
+        .filter(|(name, value)| new.get(*name) != Some(*value))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("Config values must be serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CoinInitConfig, SidecarSignerConfig, TxSignerConfig};
+
+    fn test_config() -> GasStationConfig {
+        GasStationConfig {
+            signer_config: TxSignerConfig::Sidecar(SidecarSignerConfig::new(
+                "http://localhost:3000".to_string(),
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_changed_settings() {
+        let current = restart_required_settings(&test_config());
+
+        let mut new_config = test_config();
+        new_config.daily_gas_usage_cap = 1;
+        new_config.daily_gas_usage_cap_per_sender = Some(1);
+        new_config.log_level = Some("info".to_string());
+        new_config.coin_init_config = Some(CoinInitConfig {
+            target_init_balance: 1,
+            ..Default::default()
+        });
+        assert!(changed_settings(&current, &restart_required_settings(&new_config)).is_empty());
+
+        new_config.rpc_port += 1;
+        new_config.coin_init_config = None;
+        assert_eq!(
+            changed_settings(&current, &restart_required_settings(&new_config)),
+            vec!["coin-init-config.refresh-interval-sec", "rpc-port"]
+        );
+    }
+}
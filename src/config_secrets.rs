@@ -0,0 +1,177 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! References to secrets in the config file, resolved when the config is loaded, so that
+//! passwords and keys never have to be written in plain text in the file.
+//!
+//! Any string value of the config can be a reference:
+//! - `env://NAME` is replaced by the environment variable `NAME`.
+//! - `file:///run/secrets/key` is replaced by the content of the file, without the trailing
+//!   whitespace.
+//! - `vault://secret/data/gas-station#redis-url` is replaced by the field `redis-url` of the
+//!   HashiCorp Vault secret at the API path `secret/data/gas-station`, read from `VAULT_ADDR`
+//!   with `VAULT_TOKEN`.
+
+use anyhow::{anyhow, bail, Context};
+use serde_yaml::Value;
+
+const ENV_SCHEME: &str = "env://";
+const FILE_SCHEME: &str = "file://";
+const VAULT_SCHEME: &str = "vault://";
+
+pub const VAULT_ADDR_ENV_NAME: &str = "VAULT_ADDR";
+pub const VAULT_TOKEN_ENV_NAME: &str = "VAULT_TOKEN";
+const VAULT_TOKEN_HEADER: &str = "X-Vault-Token";
+
+/// Replaces the secret references of the config by the secrets.
+pub async fn resolve_secrets(config: &mut Value) -> anyhow::Result<()> {
+    let mut strings = vec![];
+    collect_strings(config, &mut strings);
+    for value in strings {
+        let Value::String(reference) = value else {
+            continue;
+        };
+        if let Some(secret) = resolve_secret(reference).await? {
+            *reference = secret;
+        }
+    }
+    Ok(())
+}
+
+fn collect_strings<'a>(node: &'a mut Value, strings: &mut Vec<&'a mut Value>) {
+    match node {
+        Value::String(_) => strings.push(node),
+        Value::Mapping(mapping) => mapping
+            .iter_mut()
+            .for_each(|(_, value)| collect_strings(value, strings)),
+        Value::Sequence(sequence) => sequence
+            .iter_mut()
+            .for_each(|value| collect_strings(value, strings)),
+        _ => {}
+    }
+}
+
+/// Returns None if the value is not a secret reference.
+async fn resolve_secret(value: &str) -> anyhow::Result<Option<String>> {
+    if let Some(name) = value.strip_prefix(ENV_SCHEME) {
+        let secret =
+            std::env::var(name).with_context(|| format!("Failed to read the secret {}", value))?;
+        return Ok(Some(secret));
+    }
+    if let Some(path) = value.strip_prefix(FILE_SCHEME) {
+        let secret = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the secret {}", value))?;
+        return Ok(Some(secret.trim_end().to_string()));
+    }
+    if let Some(reference) = value.strip_prefix(VAULT_SCHEME) {
+        let vault_addr = std::env::var(VAULT_ADDR_ENV_NAME)
+            .with_context(|| format!("{} is required to read {}", VAULT_ADDR_ENV_NAME, value))?;
+        let vault_token = std::env::var(VAULT_TOKEN_ENV_NAME)
+            .with_context(|| format!("{} is required to read {}", VAULT_TOKEN_ENV_NAME, value))?;
+        let secret = read_vault_secret(&vault_addr, &vault_token, reference)
+            .await
+            .with_context(|| format!("Failed to read the secret {}", value))?;
+        return Ok(Some(secret));
+    }
+    Ok(None)
+}
+
+/// Reads the field after `#` of the secret at the API path before it. Both the KV version 1 and
+/// version 2 secrets engines are supported.
+async fn read_vault_secret(
+    vault_addr: &str,
+    vault_token: &str,
+    reference: &str,
+) -> anyhow::Result<String> {
+    let (path, field) = reference
+        .split_once('#')
+        .ok_or_else(|| anyhow!("Missing the #field of the secret"))?;
+    let response = reqwest::Client::new()
+        .get(format!("{}/v1/{}", vault_addr.trim_end_matches('/'), path))
+        .header(VAULT_TOKEN_HEADER, vault_token)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!("Vault responded with {}", response.status());
+    }
+    let body: serde_json::Value = response.json().await?;
+    let data = &body["data"];
+    // KV version 2 nests the fields in another `data`.
+    let secret = data["data"][field]
+        .as_str()
+        .or_else(|| data[field].as_str())
+        .ok_or_else(|| anyhow!("The secret has no field {}", field))?;
+    Ok(secret.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+    use std::net::SocketAddr;
+
+    #[tokio::test]
+    async fn test_resolve_secrets() {
+        std::env::set_var("TEST_RESOLVE_SECRETS_REDIS_URL", "redis://:password@redis");
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f\n",
+        )
+        .unwrap();
+        let mut config: Value = serde_yaml::from_str(&format!(
+            r#"
+signer-config:
+  local:
+    keypair: "file://{}"
+storage-config:
+  redis:
+    redis_url: "env://TEST_RESOLVE_SECRETS_REDIS_URL"
+fullnode-url: "https://api.testnet.iota.cafe"
+"#,
+            file.path().display()
+        ))
+        .unwrap();
+        resolve_secrets(&mut config).await.unwrap();
+        assert_eq!(
+            config["signer-config"]["local"]["keypair"],
+            "AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f"
+        );
+        assert_eq!(
+            config["storage-config"]["redis"]["redis_url"],
+            "redis://:password@redis"
+        );
+        assert_eq!(config["fullnode-url"], "https://api.testnet.iota.cafe");
+
+        let mut config = Value::String("env://TEST_RESOLVE_SECRETS_MISSING".to_string());
+        assert!(resolve_secrets(&mut config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_vault_secret() {
+        let app = Router::new().route(
+            "/v1/secret/data/gas-station",
+            get(|headers: axum::http::HeaderMap| async move {
+                assert_eq!(headers[VAULT_TOKEN_HEADER], "token");
+                axum::Json(
+                    serde_json::json!({ "data": { "data": { "redis-url": "redis://redis" } } }),
+                )
+            }),
+        );
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let vault_addr = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+
+        let secret = read_vault_secret(&vault_addr, "token", "secret/data/gas-station#redis-url")
+            .await
+            .unwrap();
+        assert_eq!(secret, "redis://redis");
+        assert!(
+            read_vault_secret(&vault_addr, "token", "secret/data/gas-station#password")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Strict validation of the config file, enabled by the `--strict` flag. Unknown fields are
+//! otherwise silently ignored, e.g. a misspelled `acccess-controller` falls back to the disabled
+//! access controller.
+
+use itertools::Itertools;
+use serde_yaml::Value;
+
+use crate::access_controller::policy::AccessPolicy;
+use crate::config::{GasStationConfig, GasStationStorageConfig, TxSignerConfig};
+use crate::redis_connection::RedisMode;
+use iota_types::multisig::{ThresholdUnit, MAX_SIGNER_IN_MULTISIG};
+
+/// Checks the config for unknown fields and invalid settings, and reports all the errors at once.
+/// `input` is the YAML the config was deserialized from.
+pub fn validate_config(input: &Value, config: &GasStationConfig) -> anyhow::Result<()> {
+    let known = serde_yaml::to_value(config)?;
+    let mut errors: Vec<String> = unknown_fields(input, &known)
+        .into_iter()
+        .map(|path| format!("{}: unknown field", path))
+        .collect();
+    errors.extend(invalid_settings(config));
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "Invalid config, {} error(s):\n{}",
+            errors.len(),
+            errors
+                .iter()
+                .map(|error| format!("  - {}", error))
+                .join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Paths of the fields of `input` missing from `known`, the config serialized back. Empty values
+/// are not reported, since the fields with their default value are not serialized.
+fn unknown_fields(input: &Value, known: &Value) -> Vec<String> {
+    let mut paths = vec![];
+    collect_unknown_fields(input, known, "", &mut paths);
+    paths
+}
+
+fn collect_unknown_fields(input: &Value, known: &Value, path: &str, paths: &mut Vec<String>) {
+    match (input, known) {
+        (Value::Mapping(input), Value::Mapping(known)) => {
+            for (key, value) in input {
+                let Some(name) = key.as_str() else {
+                    continue;
+                };
+                let field_path = if path.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                // Aliases like `sidecar_url` for `sidecar-url` are serialized in kebab-case.
+                let known_value = known.iter().find_map(|(known_key, known_value)| {
+                    known_key
+                        .as_str()
+                        .is_some_and(|known_key| {
+                            known_key.replace('_', "-") == name.replace('_', "-")
+                        })
+                        .then_some(known_value)
+                });
+                match known_value {
+                    Some(known_value) => {
+                        collect_unknown_fields(value, known_value, &field_path, paths)
+                    }
+                    None if !is_empty(value) => paths.push(field_path),
+                    None => {}
+                }
+            }
+        }
+        (Value::Sequence(input), Value::Sequence(known)) => {
+            for (index, (value, known_value)) in input.iter().zip(known).enumerate() {
+                collect_unknown_fields(value, known_value, &format!("{}[{}]", path, index), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(value) => !value,
+        Value::String(value) => value.is_empty(),
+        Value::Sequence(values) => values.is_empty(),
+        Value::Mapping(values) => values.is_empty(),
+        Value::Number(_) => false,
+    }
+}
+
+fn invalid_settings(config: &GasStationConfig) -> Vec<String> {
+    let mut errors = vec![];
+    let ports = [
+        ("rpc-port", config.rpc_port),
+        ("admin-port", config.admin_port),
+        ("metrics-port", config.metrics_port),
+    ];
+    for (index, (name, port)) in ports.iter().enumerate() {
+        if *port == 0 {
+            errors.push(format!("{}: must be between 1 and 65535", name));
+        }
+        if let Some((other_name, _)) = ports[..index].iter().find(|(_, other)| other == port) {
+            errors.push(format!("{}: already used by {}", name, other_name));
+        }
+    }
+    for url in config.fullnode_urls() {
+        if let Err(err) = url::Url::parse(&url) {
+            errors.push(format!("fullnode-url: invalid URL {}: {}", url, err));
+        }
+    }
+
+    errors.extend(signer_errors("signer-config", &config.signer_config));
+    if let Some(previous_signer_config) = &config.previous_signer_config {
+        errors.extend(signer_errors(
+            "previous-signer-config",
+            previous_signer_config,
+        ));
+    }
+
+    if let Some(namespace) = config.storage_config.namespace() {
+        if namespace.is_empty()
+            || !namespace
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            errors.push(
+                "storage-config.redis.namespace: must only contain letters, digits, '-', '_' and '.'"
+                    .to_string(),
+            );
+        }
+    }
+
+    let GasStationStorageConfig::Redis { mode, .. } = &config.storage_config;
+    if let RedisMode::Sentinel {
+        master_name,
+        sentinel_urls,
+    } = mode
+    {
+        if master_name.is_empty() {
+            errors.push(
+                "storage-config.redis.mode.sentinel.master-name: must not be empty".to_string(),
+            );
+        }
+        if sentinel_urls.is_empty() {
+            errors.push(
+                "storage-config.redis.mode.sentinel.sentinel-urls: at least one sentinel is required"
+                    .to_string(),
+            );
+        }
+    }
+
+    let access_controller = &config.access_controller;
+    if access_controller.access_policy == AccessPolicy::DenyAll
+        && access_controller.rules.is_empty()
+    {
+        errors.push(
+            "access-controller: the deny-all policy has no rules, so no transaction can be sponsored"
+                .to_string(),
+        );
+    }
+
+    if config.daily_gas_usage_cap == 0 {
+        errors.push("daily-gas-usage-cap: must be greater than 0".to_string());
+    }
+    let scoped_caps = [
+        (
+            "daily-gas-usage-cap-per-sender",
+            config.daily_gas_usage_cap_per_sender,
+        ),
+        (
+            "daily-gas-usage-cap-per-package",
+            config.daily_gas_usage_cap_per_package,
+        ),
+    ];
+    for (name, cap) in scoped_caps {
+        match cap {
+            Some(0) => errors.push(format!("{}: must be greater than 0", name)),
+            Some(cap) if cap > config.daily_gas_usage_cap => errors.push(format!(
+                "{}: {} is above daily-gas-usage-cap {}, so it can never be reached",
+                name, cap, config.daily_gas_usage_cap
+            )),
+            _ => {}
+        }
+    }
+    if let Some(coin_init_config) = &config.coin_init_config {
+        if coin_init_config.target_init_balance == 0 {
+            errors.push("coin-init-config.target-init-balance: must be greater than 0".to_string());
+        }
+        if coin_init_config.refresh_interval_sec == 0 {
+            errors
+                .push("coin-init-config.refresh-interval-sec: must be greater than 0".to_string());
+        }
+        if matches!(coin_init_config.resplit_balance_factor, Some(factor) if factor <= 2) {
+            errors.push(
+                "coin-init-config.resplit-balance-factor: must be greater than 2".to_string(),
+            );
+        }
+        if let Some(faucet_top_up) = &coin_init_config.faucet_top_up {
+            if faucet_top_up.check_interval_sec == 0 {
+                errors.push(
+                    "coin-init-config.faucet-top-up.check-interval-sec: must be greater than 0"
+                        .to_string(),
+                );
+            }
+        }
+    }
+    errors
+}
+
+fn signer_errors(name: &str, config: &TxSignerConfig) -> Vec<String> {
+    let mut errors = vec![];
+    let TxSignerConfig::Multisig(multisig) = config else {
+        return errors;
+    };
+    if multisig.members.is_empty() || multisig.members.len() > MAX_SIGNER_IN_MULTISIG {
+        errors.push(format!(
+            "{}.multisig.members: must have between 1 and {} members",
+            name, MAX_SIGNER_IN_MULTISIG
+        ));
+    }
+    for (index, member) in multisig.members.iter().enumerate() {
+        let member_name = format!("{}.multisig.members[{}]", name, index);
+        if member.weight == 0 {
+            errors.push(format!("{}.weight: must be greater than 0", member_name));
+        }
+        match &member.signer {
+            TxSignerConfig::Multisig(_) => {
+                errors.push(format!("{}.signer: can't be a multisig", member_name))
+            }
+            TxSignerConfig::Sidecar(_) if member.public_key.is_none() => errors.push(format!(
+                "{}.public-key: required for a sidecar signer",
+                member_name
+            )),
+            _ => {}
+        }
+    }
+    let total_weight: ThresholdUnit = multisig
+        .members
+        .iter()
+        .map(|member| member.weight as ThresholdUnit)
+        .sum();
+    if multisig.threshold == 0 {
+        errors.push(format!(
+            "{}.multisig.threshold: must be greater than 0",
+            name
+        ));
+    } else if multisig.threshold > total_weight {
+        errors.push(format!(
+            "{}.multisig.threshold: {} is above the total weight {} of the members, so no transaction can be signed",
+            name, multisig.threshold, total_weight
+        ));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(yaml: &str) -> anyhow::Result<()> {
+        let input: Value = serde_yaml::from_str(yaml).unwrap();
+        let config: GasStationConfig = serde_yaml::from_value(input.clone()).unwrap();
+        validate_config(&input, &config)
+    }
+
+    const VALID_CONFIG: &str = r#"
+signer-config:
+  local:
+    keypair: AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f
+rpc-host-ip: 0.0.0.0
+rpc-port: 9527
+metrics-port: 9184
+storage-config:
+  redis:
+    redis_url: "redis://127.0.0.1"
+fullnode-url: "http://localhost:9000"
+daily-gas-usage-cap: 1500000000000
+webhooks: []
+access-controller:
+  access-policy: deny-all
+  rules:
+    - sender-address: "*"
+      action: allow
+"#;
+
+    #[test]
+    fn test_valid_config() {
+        validate(VALID_CONFIG).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let config = VALID_CONFIG.replace("access-controller:", "acccess-controller:")
+            + "fullnode-circuit-breaker:\n  failure-treshold: 3\n";
+        let err = validate(&config).unwrap_err().to_string();
+        assert!(err.contains("acccess-controller: unknown field"), "{}", err);
+        assert!(
+            err.contains("fullnode-circuit-breaker.failure-treshold: unknown field"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let config = VALID_CONFIG.replace("metrics-port: 9184", "metrics-port: 9527")
+            + "daily-gas-usage-cap-per-sender: 2000000000000\n";
+        let config = config.replace(
+            "  rules:\n    - sender-address: \"*\"\n      action: allow\n",
+            "",
+        );
+        let err = validate(&config).unwrap_err().to_string();
+        assert!(err.starts_with("Invalid config, 3 error(s):"), "{}", err);
+        assert!(
+            err.contains("metrics-port: already used by rpc-port"),
+            "{}",
+            err
+        );
+        assert!(err.contains("the deny-all policy has no rules"), "{}", err);
+        assert!(err.contains("daily-gas-usage-cap-per-sender"), "{}", err);
+    }
+
+    #[test]
+    fn test_multisig_signer() {
+        let member = "      - signer:\n          local:\n            keypair: AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f\n";
+        let multisig = format!(
+            "signer-config:\n  multisig:\n    threshold: 2\n    members:\n{}{}",
+            member, member
+        );
+        let config = VALID_CONFIG.replace(
+            "signer-config:\n  local:\n    keypair: AKT1Ghtd+yNbI9fFCQin3FpiGx8xoUdJMe7iAhoFUm4f\n",
+            &multisig,
+        );
+        validate(&config).unwrap();
+        let err = validate(&config.replace("threshold: 2", "threshold: 3"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("signer-config.multisig.threshold: 3 is above the total weight 2"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_storage_namespace() {
+        let config = VALID_CONFIG.replace(
+            "redis_url: \"redis://127.0.0.1\"",
+            "redis_url: \"redis://127.0.0.1\"\n    namespace: prod",
+        );
+        validate(&config).unwrap();
+        let err = validate(&config.replace("prod", "prod:eu"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("storage-config.redis.namespace"), "{}", err);
+    }
+}
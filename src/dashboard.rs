@@ -0,0 +1,345 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::config::DEFAULT_METRICS_PORT;
+use crate::rpc::client::GasStationRpcClient;
+use crate::rpc::rpc_types::SponsorInfo;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Gauges shown as they are, summed over all the sponsors.
+const GAUGES: &[(&str, &str)] = &[
+    ("pool_available_gas_coin_count", "Available coins"),
+    ("pool_available_gas_total_balance", "Available balance"),
+    ("pool_reserved_gas_coin_count", "Reserved coins"),
+    ("pool_oldest_reservation_age_sec", "Oldest reservation (s)"),
+    (
+        "gas_station_quarantined_gas_coin_count",
+        "Quarantined coins",
+    ),
+    ("daily_gas_usage", "Daily gas usage"),
+    (
+        "fullnode_circuit_state",
+        "Fullnode circuit (0 closed, 1 open, 2 half-open)",
+    ),
+];
+
+/// Counters shown as a rate per second since the previous refresh.
+const RATES: &[(&str, &str)] = &[
+    ("num_reserve_gas_requests", "reserve_gas requests"),
+    ("num_failed_reserve_gas_requests", "reserve_gas failures"),
+    ("num_execute_tx_requests", "execute_tx requests"),
+    ("num_failed_execute_tx_requests", "execute_tx failures"),
+    ("num_blocked_execute_tx_requests", "execute_tx denied"),
+    ("num_expired_gas_coins", "Expired coins"),
+];
+
+/// Requests whose success rate is shown by `dump-metrics`, as (label, total, successful).
+const SUCCESS_RATES: &[(&str, &str, &str)] = &[
+    (
+        "reserve_gas",
+        "num_reserve_gas_requests",
+        "num_successful_reserve_gas_requests",
+    ),
+    (
+        "execute_tx",
+        "num_execute_tx_requests",
+        "num_successful_execute_tx_requests",
+    ),
+];
+
+/// Series of the metrics port that are not specific to the Gas Station.
+const NON_STATION_METRIC_PREFIXES: &[&str] = &["process_", "tracing_", "uptime"];
+
+/// Everything shown on the dashboard, fetched at one refresh.
+pub struct DashboardSnapshot {
+    pub fetched_at: Instant,
+    pub health: Result<(), String>,
+    pub version: Option<String>,
+    pub sponsor_info: Option<SponsorInfo>,
+    /// Values of the metrics, summed over their labels. Empty if no metrics URL is given or
+    /// the metrics cannot be fetched.
+    pub metrics: HashMap<String, f64>,
+}
+
+impl DashboardSnapshot {
+    pub async fn fetch(
+        station_client: &GasStationRpcClient,
+        http_client: &reqwest::Client,
+        metrics_url: Option<&str>,
+    ) -> Self {
+        let health = station_client.health().await.map_err(|err| err.to_string());
+        let version = station_client.version().await.ok();
+        let sponsor_info = station_client.sponsor_info().await.ok();
+        let metrics = match metrics_url {
+            Some(metrics_url) => match fetch_text(http_client, metrics_url).await {
+                Ok(text) => parse_metrics(&text),
+                Err(_) => HashMap::new(),
+            },
+            None => HashMap::new(),
+        };
+        Self {
+            fetched_at: Instant::now(),
+            health,
+            version,
+            sponsor_info,
+            metrics,
+        }
+    }
+
+    /// Renders the snapshot. Rates are computed against `previous`, and omitted without it.
+    pub fn render(&self, previous: Option<&DashboardSnapshot>) -> String {
+        let mut out = String::new();
+        writeln!(out, "IOTA Gas Station").unwrap();
+        writeln!(out, "================").unwrap();
+        let health = match &self.health {
+            Ok(()) => "healthy".to_string(),
+            Err(err) => format!("UNHEALTHY ({})", err),
+        };
+        writeln!(out, "{:<50} {}", "Health", health).unwrap();
+        writeln!(
+            out,
+            "{:<50} {}",
+            "Version",
+            self.version.as_deref().unwrap_or("-")
+        )
+        .unwrap();
+        if let Some(info) = &self.sponsor_info {
+            for sponsor in &info.sponsor_addresses {
+                writeln!(out, "{:<50} {}", "Sponsor", sponsor).unwrap();
+            }
+            writeln!(
+                out,
+                "{:<50} {}",
+                "Reference gas price", info.reference_gas_price
+            )
+            .unwrap();
+        }
+
+        if self.metrics.is_empty() {
+            writeln!(out, "\nNo metrics available").unwrap();
+            return out;
+        }
+        writeln!(out, "\nPool").unwrap();
+        for (name, label) in GAUGES {
+            if let Some(value) = self.metrics.get(*name) {
+                writeln!(out, "  {:<48} {}", label, value).unwrap();
+            }
+        }
+        writeln!(out, "\nRequests (per second)").unwrap();
+        for (name, label) in RATES {
+            let rate = previous.and_then(|previous| self.rate(previous, name));
+            let rate = rate.map_or_else(|| "-".to_string(), |rate| format!("{:.2}", rate));
+            writeln!(out, "  {:<48} {}", label, rate).unwrap();
+        }
+        out
+    }
+
+    fn rate(&self, previous: &DashboardSnapshot, name: &str) -> Option<f64> {
+        let elapsed = self
+            .fetched_at
+            .duration_since(previous.fetched_at)
+            .as_secs_f64();
+        let current = self.metrics.get(name)?;
+        let previous = previous.metrics.get(name)?;
+        // Counters are reset when the Gas Station restarts.
+        if elapsed <= 0.0 || current < previous {
+            return None;
+        }
+        Some((current - previous) / elapsed)
+    }
+}
+
+/// Refreshes the dashboard every `interval` until the process is stopped.
+pub async fn run_dashboard(
+    station_rpc_url: String,
+    metrics_url: Option<String>,
+    interval: Duration,
+) {
+    let station_client = GasStationRpcClient::new(station_rpc_url);
+    let http_client = reqwest::Client::new();
+    let mut previous: Option<DashboardSnapshot> = None;
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let snapshot =
+            DashboardSnapshot::fetch(&station_client, &http_client, metrics_url.as_deref()).await;
+        print!("{}{}", CLEAR_SCREEN, snapshot.render(previous.as_ref()));
+        println!("\nRefreshing every {:?}, press Ctrl+C to exit", interval);
+        previous = Some(snapshot);
+    }
+}
+
+/// Scrapes the metrics once and renders their summary followed by the station series. Without
+/// `metrics_url`, the metrics are scraped from the default metrics port of the station host.
+pub async fn dump_metrics(
+    station_rpc_url: &str,
+    metrics_url: Option<&str>,
+) -> anyhow::Result<String> {
+    let metrics_url = match metrics_url {
+        Some(metrics_url) => metrics_url.to_string(),
+        None => default_metrics_url(station_rpc_url)?,
+    };
+    let text = fetch_text(&reqwest::Client::new(), &metrics_url).await?;
+    Ok(render_metrics_dump(&text))
+}
+
+/// The `/metrics` endpoint on the default metrics port of the host of the RPC server.
+fn default_metrics_url(station_rpc_url: &str) -> anyhow::Result<String> {
+    let mut url = url::Url::parse(station_rpc_url)?;
+    url.set_port(Some(DEFAULT_METRICS_PORT))
+        .map_err(|_| anyhow::anyhow!("Invalid station RPC URL {}", station_rpc_url))?;
+    url.set_path("/metrics");
+    url.set_query(None);
+    Ok(url.to_string())
+}
+
+fn render_metrics_dump(text: &str) -> String {
+    let metrics = parse_metrics(text);
+    let mut out = String::new();
+    writeln!(out, "Pool").unwrap();
+    for (name, label) in GAUGES {
+        let value = metrics
+            .get(*name)
+            .map_or_else(|| "-".to_string(), |value| value.to_string());
+        writeln!(out, "  {:<48} {}", label, value).unwrap();
+    }
+    writeln!(out, "\nRequests since start").unwrap();
+    for (label, total_name, successful_name) in SUCCESS_RATES {
+        let total = metrics.get(*total_name).copied().unwrap_or(0.0);
+        let successful = metrics.get(*successful_name).copied().unwrap_or(0.0);
+        let rate = if total > 0.0 {
+            format!("{:.2}%", successful / total * 100.0)
+        } else {
+            "-".to_string()
+        };
+        writeln!(
+            out,
+            "  {:<48} {} of {} successful ({})",
+            label, successful, total, rate
+        )
+        .unwrap();
+    }
+    writeln!(out, "\nStation series").unwrap();
+    let mut series: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter(|line| {
+            !NON_STATION_METRIC_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .collect();
+    series.sort_unstable();
+    for line in series {
+        writeln!(out, "  {}", line).unwrap();
+    }
+    out
+}
+
+async fn fetch_text(http_client: &reqwest::Client, url: &str) -> anyhow::Result<String> {
+    Ok(http_client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Parses the Prometheus text format, summing the samples of each metric over their labels.
+fn parse_metrics(text: &str) -> HashMap<String, f64> {
+    let mut metrics = HashMap::new();
+    for line in text.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((series, value)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        let name = series.split('{').next().unwrap_or(series).trim();
+        if name.is_empty() {
+            continue;
+        }
+        *metrics.entry(name.to_string()).or_insert(0.0) += value;
+    }
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metrics() {
+        let metrics = parse_metrics(
+            "# HELP pool_available_gas_coin_count Number of available gas coins\n\
+             # TYPE pool_available_gas_coin_count gauge\n\
+             pool_available_gas_coin_count{sponsor=\"0x1\"} 10\n\
+             pool_available_gas_coin_count{sponsor=\"0x2\"} 5\n\
+             num_reserve_gas_requests 42\n",
+        );
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics["pool_available_gas_coin_count"], 15.0);
+        assert_eq!(metrics["num_reserve_gas_requests"], 42.0);
+    }
+
+    #[test]
+    fn test_render_metrics_dump() {
+        let out = render_metrics_dump(
+            "# TYPE num_reserve_gas_requests counter\n\
+             num_reserve_gas_requests 8\n\
+             num_successful_reserve_gas_requests 6\n\
+             pool_available_gas_total_balance{sponsor=\"0x1\"} 1000\n\
+             process_cpu_seconds_total 12\n",
+        );
+        assert!(out.contains("6 of 8 successful (75.00%)"), "{}", out);
+        assert!(out.contains("pool_available_gas_total_balance{sponsor=\"0x1\"} 1000"));
+        assert!(!out.contains("process_cpu_seconds_total"));
+        assert!(!out.contains("# TYPE"));
+
+        assert_eq!(
+            default_metrics_url("http://127.0.0.1:9527").unwrap(),
+            "http://127.0.0.1:9184/metrics"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let previous = DashboardSnapshot {
+            fetched_at: Instant::now(),
+            health: Ok(()),
+            version: Some("0.1.0".to_string()),
+            sponsor_info: None,
+            metrics: HashMap::from([("num_reserve_gas_requests".to_string(), 10.0)]),
+        };
+        let snapshot = DashboardSnapshot {
+            fetched_at: previous.fetched_at + Duration::from_secs(2),
+            health: Err("connection refused".to_string()),
+            version: None,
+            sponsor_info: None,
+            metrics: HashMap::from([
+                ("num_reserve_gas_requests".to_string(), 20.0),
+                ("pool_reserved_gas_coin_count".to_string(), 3.0),
+            ]),
+        };
+        let out = snapshot.render(Some(&previous));
+        assert!(out.contains("UNHEALTHY (connection refused)"));
+        assert!(out.contains("Reserved coins"));
+        assert!(out.contains("5.00"));
+        assert!(!out.contains("Available coins"));
+
+        let out = previous.render(None);
+        assert!(out.contains("0.1.0"));
+        assert!(!out.contains("5.00"));
+    }
+}
@@ -1,6 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::fmt;
+
+use crate::types::ReservationID;
+
 #[macro_export]
 macro_rules! retry_with_max_attempts {
     ($func:expr, $max_attempts:expr) => {{
@@ -29,6 +33,55 @@ macro_rules! retry_forever {
     }};
 }
 
+/// Failures of the gas reservation or of the transaction execution that the clients can act on.
+/// They can be found with `anyhow::Error::downcast_ref::<GasStationError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GasStationError {
+    /// The pool has no coins covering the gas budget.
+    InsufficientPoolBalance,
+    /// The pool is being drained, so no coins are reserved.
+    PoolDraining,
+    /// The daily gas usage of the sponsor reached its cap.
+    GasUsageCapExceeded,
+    /// The reservation expired or was already used.
+    ReservationExpired(ReservationID),
+    /// The transaction uses the gas coin as an argument of a command.
+    GasCoinUsedAsArgument,
+    /// The fullnode rejected the signature of the user.
+    InvalidUserSig,
+    /// The idempotency key of the reservation was already used for another gas budget.
+    IdempotencyKeyReused,
+}
+
+impl fmt::Display for GasStationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasStationError::InsufficientPoolBalance => {
+                write!(f, "Unable to reserve gas coins for the given budget.")
+            }
+            GasStationError::PoolDraining => write!(f, "The gas pool is being drained"),
+            GasStationError::GasUsageCapExceeded => write!(f, "Gas usage exceeds daily cap"),
+            GasStationError::ReservationExpired(reservation_id) => {
+                write!(f, "Reservation no longer exist: {}", reservation_id)
+            }
+            GasStationError::GasCoinUsedAsArgument => {
+                write!(f, "Gas coin can only be used to pay gas")
+            }
+            GasStationError::InvalidUserSig => {
+                write!(f, "The user signature was rejected by the fullnode")
+            }
+            GasStationError::IdempotencyKeyReused => {
+                write!(
+                    f,
+                    "The idempotency key was already used for another gas budget"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GasStationError {}
+
 pub fn generate_event_id() -> String {
     use uuid::Uuid;
     Uuid::new_v4().to_string()
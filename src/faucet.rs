@@ -0,0 +1,196 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Funds the sponsor from the faucet of a test network, so that the pool of a dev or CI
+//! environment doesn't run dry.
+
+use anyhow::{bail, Context};
+use iota_types::base_types::{IotaAddress, ObjectID};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Chain identifier of the IOTA mainnet, which has no faucet.
+pub const IOTA_MAINNET_CHAIN_IDENTIFIER: &str = "6364aad5";
+/// Chain identifiers of the public test networks with a faucet: testnet and devnet.
+pub const IOTA_TEST_NETWORK_CHAIN_IDENTIFIERS: [&str; 2] = ["2304aa97", "e678123a"];
+
+const DEFAULT_CHECK_INTERVAL_SEC: u64 = 60;
+const FAUCET_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FaucetTopUpConfig {
+    /// URL of the faucet, e.g. `https://faucet.testnet.iota.cafe`.
+    pub faucet_url: String,
+    /// Funds are requested when the available balance of the pool is below this, in nanos.
+    pub min_pool_balance: u64,
+    /// How often the balance of the pool is checked, in seconds.
+    #[serde(default = "default_check_interval_sec")]
+    pub check_interval_sec: u64,
+    /// Chain identifiers of other test networks with a faucet, e.g. of a local network, whose
+    /// identifier changes with every genesis.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chain_identifiers: Vec<String>,
+}
+
+fn default_check_interval_sec() -> u64 {
+    DEFAULT_CHECK_INTERVAL_SEC
+}
+
+impl FaucetTopUpConfig {
+    /// Whether the network of `chain_identifier` is a known test network, or one of the
+    /// configured `chain_identifiers`.
+    pub fn is_test_network(&self, chain_identifier: &str) -> bool {
+        IOTA_TEST_NETWORK_CHAIN_IDENTIFIERS
+            .iter()
+            .copied()
+            .chain(self.chain_identifiers.iter().map(String::as_str))
+            .any(|allowed| allowed.eq_ignore_ascii_case(chain_identifier))
+    }
+}
+
+/// Requests funds for `recipient` and returns the ID of the faucet task. The faucet sends them
+/// asynchronously, see [`get_faucet_coins`].
+pub async fn request_faucet_funds(
+    faucet_url: &str,
+    recipient: IotaAddress,
+) -> anyhow::Result<String> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/v1/gas", faucet_url.trim_end_matches('/')))
+        .timeout(FAUCET_REQUEST_TIMEOUT)
+        .json(&json!({ "FixedAmountRequest": { "recipient": recipient } }))
+        .send()
+        .await
+        .context("Failed to send the faucet request")?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .with_context(|| format!("Invalid faucet response with status {}", status))?;
+    if let Some(error) = body.get("error").and_then(Value::as_str) {
+        bail!("The faucet rejected the request: {}", error);
+    }
+    if !status.is_success() {
+        bail!("The faucet request failed with status {}", status);
+    }
+    body.get("task")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .context("The faucet response has no task")
+}
+
+/// Returns the coins sent by the faucet task, or None while it is in progress.
+pub async fn get_faucet_coins(
+    faucet_url: &str,
+    task: &str,
+) -> anyhow::Result<Option<Vec<ObjectID>>> {
+    let response = reqwest::Client::new()
+        .get(format!(
+            "{}/v1/status/{}",
+            faucet_url.trim_end_matches('/'),
+            task
+        ))
+        .timeout(FAUCET_REQUEST_TIMEOUT)
+        .send()
+        .await
+        .context("Failed to send the faucet status request")?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .with_context(|| format!("Invalid faucet status response with status {}", status))?;
+    if let Some(error) = body.get("error").and_then(Value::as_str) {
+        bail!("The faucet task {} failed: {}", task, error);
+    }
+    let task_status = &body["status"];
+    match task_status["status"].as_str() {
+        Some("INPROGRESS") => Ok(None),
+        Some("SUCCEEDED") => task_status["transferred_gas_objects"]["sent"]
+            .as_array()
+            .context("The faucet status has no sent coins")?
+            .iter()
+            .map(|coin| {
+                coin["id"]
+                    .as_str()
+                    .and_then(|id| id.parse().ok())
+                    .context("Invalid coin sent by the faucet")
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(Some),
+        other => bail!("The faucet task {} ended with status {:?}", task, other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Path;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use std::net::SocketAddr;
+
+    #[tokio::test]
+    async fn test_request_faucet_funds() {
+        let coin = ObjectID::random();
+        let app = Router::new()
+            .route(
+                "/v1/gas",
+                post(|axum::Json(body): axum::Json<Value>| async move {
+                    let recipient = &body["FixedAmountRequest"]["recipient"];
+                    if recipient == &json!(IotaAddress::ZERO) {
+                        axum::Json(json!({ "task": null, "error": "Too many requests" }))
+                    } else {
+                        axum::Json(json!({ "task": "1", "error": null }))
+                    }
+                }),
+            )
+            .route(
+                "/v1/status/:task",
+                get(move |Path(task): Path<String>| async move {
+                    let status = match task.as_str() {
+                        "1" => json!({
+                            "status": "SUCCEEDED",
+                            "transferred_gas_objects": {
+                                "sent": [{ "amount": 1000, "id": coin, "transfer_tx_digest": "" }]
+                            }
+                        }),
+                        "2" => json!({ "status": "INPROGRESS", "transferred_gas_objects": null }),
+                        _ => json!({ "status": "DISCARDED", "transferred_gas_objects": null }),
+                    };
+                    axum::Json(json!({ "status": status, "error": null }))
+                }),
+            );
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let faucet_url = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+
+        let task = request_faucet_funds(&faucet_url, IotaAddress::random_for_testing_only())
+            .await
+            .unwrap();
+        assert_eq!(task, "1");
+        let err = request_faucet_funds(&faucet_url, IotaAddress::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Too many requests"));
+
+        assert_eq!(
+            get_faucet_coins(&faucet_url, "1").await.unwrap(),
+            Some(vec![coin])
+        );
+        assert_eq!(get_faucet_coins(&faucet_url, "2").await.unwrap(), None);
+        assert!(get_faucet_coins(&faucet_url, "3").await.is_err());
+    }
+
+    #[test]
+    fn test_is_test_network() {
+        let mut config: FaucetTopUpConfig =
+            serde_yaml::from_str("faucet-url: http://localhost:9123\nmin-pool-balance: 1").unwrap();
+        assert!(!config.is_test_network(IOTA_MAINNET_CHAIN_IDENTIFIER));
+        assert!(config.is_test_network("2304aa97"));
+        assert!(!config.is_test_network("4c78adac"));
+        config.chain_identifiers = vec!["4C78ADAC".to_string()];
+        assert!(config.is_test_network("4c78adac"));
+    }
+}
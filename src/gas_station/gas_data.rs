@@ -0,0 +1,185 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use iota_types::base_types::IotaAddress;
+use iota_types::transaction::{TransactionData, TransactionDataAPI};
+
+use crate::types::{Reservation, ReservationID};
+
+/// Gas data of a transaction that doesn't match its reservation. It can be found with
+/// `anyhow::Error::downcast_ref::<GasDataError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GasDataError {
+    /// The gas owner is not a sponsor of the Gas Station.
+    UnknownGasOwner(IotaAddress),
+    /// The gas payment is not exactly the coins of the reservation, at their reserved versions.
+    PaymentMismatch {
+        reservation_id: ReservationID,
+        reason: String,
+    },
+    BudgetExceedsReservation {
+        reservation_id: ReservationID,
+        gas_budget: u64,
+        reserved_budget: u64,
+    },
+}
+
+impl fmt::Display for GasDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasDataError::UnknownGasOwner(owner) => write!(
+                f,
+                "Sponsor {} is not registered, the gas owner must be the sponsor_address of the reservation",
+                owner
+            ),
+            GasDataError::PaymentMismatch {
+                reservation_id,
+                reason,
+            } => write!(
+                f,
+                "The gas payment must be the gas_coins of reservation {}: {}",
+                reservation_id, reason
+            ),
+            GasDataError::BudgetExceedsReservation {
+                reservation_id,
+                gas_budget,
+                reserved_budget,
+            } => write!(
+                f,
+                "Gas budget {} exceeds the budget {} of reservation {}",
+                gas_budget, reserved_budget, reservation_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GasDataError {}
+
+/// Checks that the transaction pays with the coins of the reservation, at the versions they were
+/// reserved at, and within the reserved budget. The gas owner is checked when the sponsor is
+/// looked up.
+pub fn check_gas_data(
+    reservation_id: ReservationID,
+    reservation: &Reservation,
+    tx_data: &TransactionData,
+) -> Result<(), GasDataError> {
+    let mismatch = |reason: String| GasDataError::PaymentMismatch {
+        reservation_id,
+        reason,
+    };
+    let gas_data = tx_data.gas_data();
+    let reserved: HashMap<_, _> = reservation
+        .gas_coins
+        .iter()
+        .map(|coin| (coin.object_ref.0, coin.object_ref))
+        .collect();
+    let mut paid = HashSet::new();
+    for object_ref in &gas_data.payment {
+        let Some(reserved_ref) = reserved.get(&object_ref.0) else {
+            return Err(mismatch(format!("coin {} is not reserved", object_ref.0)));
+        };
+        if object_ref.1 != reserved_ref.1 || object_ref.2 != reserved_ref.2 {
+            return Err(mismatch(format!(
+                "coin {} is at version {}, but was reserved at version {}",
+                object_ref.0,
+                object_ref.1.value(),
+                reserved_ref.1.value()
+            )));
+        }
+        if !paid.insert(object_ref.0) {
+            return Err(mismatch(format!("coin {} is paid twice", object_ref.0)));
+        }
+    }
+    if let Some(missing) = reservation
+        .gas_coins
+        .iter()
+        .find(|coin| !paid.contains(&coin.object_ref.0))
+    {
+        return Err(mismatch(format!(
+            "coin {} is missing",
+            missing.object_ref.0
+        )));
+    }
+    if gas_data.budget > reservation.gas_budget {
+        return Err(GasDataError::BudgetExceedsReservation {
+            reservation_id,
+            gas_budget: gas_data.budget,
+            reserved_budget: reservation.gas_budget,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_types::base_types::{random_object_ref, ObjectRef, SequenceNumber};
+    use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use iota_types::transaction::TransactionKind;
+
+    use super::*;
+    use crate::types::GasCoin;
+
+    fn transaction(payment: Vec<ObjectRef>, budget: u64) -> TransactionData {
+        TransactionData::new_with_gas_coins_allow_sponsor(
+            TransactionKind::programmable(ProgrammableTransactionBuilder::new().finish()),
+            IotaAddress::random_for_testing_only(),
+            payment,
+            budget,
+            1000,
+            IotaAddress::random_for_testing_only(),
+        )
+    }
+
+    #[test]
+    fn test_check_gas_data() {
+        let coins = [random_object_ref(), random_object_ref()];
+        let reservation = Reservation {
+            gas_budget: 100,
+            gas_coins: coins
+                .iter()
+                .map(|object_ref| GasCoin {
+                    object_ref: *object_ref,
+                    balance: 50,
+                })
+                .collect(),
+        };
+        let check = |payment: Vec<_>, budget| {
+            check_gas_data(7, &reservation, &transaction(payment, budget))
+        };
+
+        assert_eq!(check(vec![coins[1], coins[0]], 100), Ok(()));
+        assert_eq!(check(coins.to_vec(), 60), Ok(()));
+        assert_eq!(
+            check(coins.to_vec(), 101),
+            Err(GasDataError::BudgetExceedsReservation {
+                reservation_id: 7,
+                gas_budget: 101,
+                reserved_budget: 100,
+            })
+        );
+
+        let other = random_object_ref();
+        let stale = (
+            coins[1].0,
+            SequenceNumber::from_u64(coins[1].1.value() + 1),
+            coins[1].2,
+        );
+        for payment in [
+            vec![coins[0]],
+            vec![coins[0], other],
+            vec![coins[0], stale],
+            vec![coins[0], coins[1], coins[1]],
+        ] {
+            assert!(matches!(
+                check(payment, 100),
+                Err(GasDataError::PaymentMismatch {
+                    reservation_id: 7,
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -1,83 +1,254 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::errors::GasStationError;
 use crate::iota_client::IotaClient;
+use crate::leader_election::Leadership;
 use crate::metrics::GasStationCoreMetrics;
-use crate::rpc::rpc_types::ExecuteTransactionRequestType;
-use crate::storage::Storage;
-use crate::tx_signer::TxSigner;
-use crate::types::{GasCoin, ReservationID};
+use crate::rpc::rpc_types::{ExecuteTransactionRequestType, TransactionIntent};
+use crate::sponsor_migration::transfer_batch;
+use crate::storage::{Storage, MAX_GAS_PER_QUERY};
+use crate::tx_signer::{SigningContext, TxSigner};
+use crate::types::{
+    ClientReservationStats, GasCoin, InitProgress, PoolCoin, QuarantinedCoin, ReservationID,
+};
+use crate::webhooks::{WebhookEvent, Webhooks};
 use crate::{retry_forever, retry_with_max_attempts};
-use anyhow::bail;
-use iota_json_rpc_types::{IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI};
+use anyhow::{bail, Context};
+use iota_json_rpc_types::{
+    IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse,
+    IotaTransactionBlockResponseOptions,
+};
 use iota_types::base_types::{IotaAddress, ObjectID, ObjectRef};
+use iota_types::digests::TransactionDigest;
+use iota_types::gas::GasCostSummary;
 use iota_types::gas_coin::NANOS_PER_IOTA;
 use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use iota_types::signature::GenericSignature;
 use iota_types::transaction::{
-    Argument, Command, Transaction, TransactionData, TransactionDataAPI, TransactionKind,
+    Argument, Command, ProgrammableTransaction, Transaction, TransactionData, TransactionDataAPI,
+    TransactionKind,
 };
+use iota_types::TypeTag;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tap::TapFallible;
 use tokio::task::JoinHandle;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
+use super::gas_data::{check_gas_data, GasDataError};
 use super::gas_usage_cap::GasUsageCap;
 
 const EXPIRATION_JOB_INTERVAL: Duration = Duration::from_secs(1);
+const POOL_METRICS_SAMPLING_INTERVAL: Duration = Duration::from_secs(10);
+const CLIENT_RETENTION_MARGIN: Duration = Duration::from_secs(60 * 60 * 24);
+/// A leased reservation is kept from expiring for at least this long after each renewal.
+const RESERVATION_LEASE_DURATION: Duration = Duration::from_secs(30);
+const RESERVATION_LEASE_RENEWAL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Extra margin added on top of the dry-run gas cost when recommending a gas budget, in percent.
+/// Protects against small differences between the dry run and the actual execution.
+const GAS_BUDGET_SAFETY_MARGIN_PERCENT: u64 = 20;
 
 pub struct GasStationContainer {
     inner: Arc<GasStation>,
     _coin_unlocker_task: JoinHandle<()>,
+    pool_metrics_sampler_task: JoinHandle<()>,
     // This is always Some. It is None only after the drop method is called.
     cancel_sender: Option<tokio::sync::oneshot::Sender<()>>,
 }
 
+/// Keeps a reservation from expiring while its transaction is being processed, by pushing back
+/// its expiration until the lease is dropped. The renewal stops once the coins are taken for
+/// execution, or if the reservation was released or expired in the meantime.
+pub struct ReservationLease {
+    renewal_task: JoinHandle<()>,
+}
+
+impl Drop for ReservationLease {
+    fn drop(&mut self) {
+        self.renewal_task.abort();
+    }
+}
+
+/// A sponsor account that is being rotated out. Its pending reservations can still be executed,
+/// but no new reservations are made from its pool.
+pub struct PreviousSponsor {
+    pub signer: Arc<dyn TxSigner>,
+    pub gas_station_store: Arc<dyn Storage>,
+}
+
 pub struct GasStation {
     signer: Arc<dyn TxSigner>,
     gas_station_store: Arc<dyn Storage>,
+    previous_sponsor: Option<PreviousSponsor>,
     iota_client: IotaClient,
     metrics: Arc<GasStationCoreMetrics>,
     gas_usage_cap: Arc<GasUsageCap>,
+    webhooks: Webhooks,
 }
 
 impl GasStation {
     pub async fn new(
         signer: Arc<dyn TxSigner>,
         gas_station_store: Arc<dyn Storage>,
+        previous_sponsor: Option<PreviousSponsor>,
         iota_client: IotaClient,
         metrics: Arc<GasStationCoreMetrics>,
         gas_usage_cap: Arc<GasUsageCap>,
+        webhooks: Webhooks,
     ) -> Arc<Self> {
         let pool = Self {
             signer,
             gas_station_store,
+            previous_sponsor,
             iota_client,
             metrics,
             gas_usage_cap,
+            webhooks,
         };
 
         Arc::new(pool)
     }
 
+    pub fn get_sponsor_address(&self) -> IotaAddress {
+        self.signer.get_address()
+    }
+
+    pub fn set_daily_gas_usage_cap(&self, daily_cap: u64) {
+        self.gas_usage_cap.set_daily_cap(daily_cap);
+    }
+
+    pub async fn get_reference_gas_price(&self) -> anyhow::Result<u64> {
+        self.iota_client.get_reference_gas_price().await
+    }
+
+    pub async fn get_object_types(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> anyhow::Result<HashMap<ObjectID, TypeTag>> {
+        self.iota_client.get_object_types(object_ids).await
+    }
+
+    /// Sets the expiration of the reservation to `duration` from now, to keep its coins reserved
+    /// longer or to release them sooner.
+    pub async fn set_reservation_expiration(
+        &self,
+        sponsor: IotaAddress,
+        reservation_id: ReservationID,
+        duration: Duration,
+    ) -> anyhow::Result<()> {
+        let (_, gas_station_store) = self.get_sponsor_account(&sponsor)?;
+        gas_station_store
+            .set_reservation_expiration(reservation_id, duration.as_millis() as u64)
+            .await
+    }
+
+    /// Renews the expiration of the reservation every few seconds until the returned lease is
+    /// dropped, so that its coins are not reclaimed while the transaction waits for an execution
+    /// slot or its access check.
+    pub fn lease_reservation(
+        &self,
+        sponsor: IotaAddress,
+        reservation_id: ReservationID,
+    ) -> anyhow::Result<ReservationLease> {
+        let (_, gas_station_store) = self.get_sponsor_account(&sponsor)?;
+        let gas_station_store = gas_station_store.clone();
+        let metrics = self.metrics.clone();
+        let renewal_task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RESERVATION_LEASE_RENEWAL_INTERVAL);
+            loop {
+                interval.tick().await;
+                match gas_station_store
+                    .extend_reservation_expiration(
+                        reservation_id,
+                        RESERVATION_LEASE_DURATION.as_millis() as u64,
+                    )
+                    .await
+                {
+                    Ok(true) => {
+                        metrics
+                            .num_reservation_lease_renewals
+                            .with_label_values(&[&sponsor.to_string()])
+                            .inc();
+                    }
+                    Ok(false) => {
+                        debug!(?reservation_id, "Reservation is no longer pending");
+                        break;
+                    }
+                    Err(err) => {
+                        warn!(
+                            ?reservation_id,
+                            "Failed to renew the reservation: {:?}", err
+                        );
+                    }
+                }
+            }
+        });
+        Ok(ReservationLease { renewal_task })
+    }
+
     pub async fn reserve_gas(
         &self,
         gas_budget: u64,
         duration: Duration,
+    ) -> anyhow::Result<(IotaAddress, ReservationID, Vec<ObjectRef>)> {
+        self.reserve_gas_with_idempotency_key(gas_budget, duration, None)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    /// Same as `reserve_gas`, but with an idempotency key, the reservation made earlier with the
+    /// same key is returned while it is still usable.
+    pub async fn reserve_gas_with_idempotency_key(
+        &self,
+        gas_budget: u64,
+        duration: Duration,
+        idempotency_key: Option<&str>,
     ) -> anyhow::Result<(IotaAddress, ReservationID, Vec<ObjectRef>)> {
         let cur_time = std::time::Instant::now();
         self.gas_usage_cap.check_usage().await?;
         let sponsor = self.signer.get_address();
-        let (reservation_id, gas_coins) = self
-            .gas_station_store
-            .reserve_gas_coins(gas_budget, duration.as_millis() as u64)
-            .await?;
+        let reserved_duration_ms = duration.as_millis() as u64;
+        let (reservation_id, gas_coins) = match idempotency_key {
+            Some(idempotency_key) => {
+                let (reservation_id, gas_coins, is_new) = self
+                    .gas_station_store
+                    .reserve_gas_coins_idempotent(gas_budget, reserved_duration_ms, idempotency_key)
+                    .await?;
+                if !is_new {
+                    debug!(
+                        ?reservation_id,
+                        "Returning the reservation made with the same idempotency key"
+                    );
+                    self.metrics.num_reused_reservations.inc();
+                    return Ok((
+                        sponsor,
+                        reservation_id,
+                        gas_coins.into_iter().map(|c| c.object_ref).collect(),
+                    ));
+                }
+                (reservation_id, gas_coins)
+            }
+            None => {
+                self.gas_station_store
+                    .reserve_gas_coins(gas_budget, reserved_duration_ms)
+                    .await?
+            }
+        };
         let elapsed = cur_time.elapsed().as_millis();
         self.metrics.reserve_gas_latency_ms.observe(elapsed as u64);
         self.metrics
             .reserved_gas_coin_count_per_request
             .observe(gas_coins.len() as u64);
+        self.webhooks.notify(WebhookEvent::ReservationCreated {
+            sponsor,
+            reservation_id,
+            gas_budget,
+            gas_coins: gas_coins.iter().map(|c| c.object_ref.0).collect(),
+            reserve_duration_secs: duration.as_secs(),
+        });
         Ok((
             sponsor,
             reservation_id,
@@ -85,6 +256,56 @@ impl GasStation {
         ))
     }
 
+    /// Estimates a gas budget for the given transaction kind by dry-running it on the fullnode.
+    /// Returns the recommended budget, the reference gas price and the dry-run gas cost summary.
+    pub async fn estimate_gas_budget(
+        &self,
+        sender: IotaAddress,
+        tx_kind: TransactionKind,
+    ) -> anyhow::Result<(u64, u64, GasCostSummary)> {
+        let reference_gas_price = self.iota_client.get_reference_gas_price().await?;
+        let gas_cost_summary = self.iota_client.estimate_gas_cost(sender, tx_kind).await?;
+        let gas_budget = Self::calculate_recommended_budget(&gas_cost_summary);
+        debug!(
+            "Estimated gas budget {} for sender {:?} with cost summary {:?}",
+            gas_budget, sender, gas_cost_summary
+        );
+        Ok((gas_budget, reference_gas_price, gas_cost_summary))
+    }
+
+    /// Builds the programmable transaction of an intent of `sender`, and returns it with its gas
+    /// budget, estimated with a dry run if not given, and the reference gas price.
+    pub async fn build_intent(
+        &self,
+        sender: IotaAddress,
+        intent: &TransactionIntent,
+        gas_budget: Option<u64>,
+    ) -> anyhow::Result<(ProgrammableTransaction, u64, u64)> {
+        let pt = self.iota_client.build_intent(intent).await?;
+        let (gas_budget, reference_gas_price) = match gas_budget {
+            Some(gas_budget) => (gas_budget, self.get_reference_gas_price().await?),
+            None => {
+                let (gas_budget, reference_gas_price, _) = self
+                    .estimate_gas_budget(
+                        sender,
+                        TransactionKind::ProgrammableTransaction(pt.clone()),
+                    )
+                    .await?;
+                (gas_budget, reference_gas_price)
+            }
+        };
+        Ok((pt, gas_budget, reference_gas_price))
+    }
+
+    /// The budget has to cover the computation and storage cost before the storage rebate is
+    /// applied, so the rebate is not subtracted here.
+    fn calculate_recommended_budget(gas_cost_summary: &GasCostSummary) -> u64 {
+        let estimated = gas_cost_summary
+            .computation_cost
+            .saturating_add(gas_cost_summary.storage_cost);
+        estimated.saturating_add(estimated * GAS_BUDGET_SAFETY_MARGIN_PERCENT / 100)
+    }
+
     pub async fn execute_transaction(
         &self,
         reservation_id: ReservationID,
@@ -92,11 +313,46 @@ impl GasStation {
         user_sig: GenericSignature,
         request_type: Option<ExecuteTransactionRequestType>,
     ) -> anyhow::Result<IotaTransactionBlockEffects> {
+        self.execute_transaction_with_options(
+            reservation_id,
+            tx_data,
+            user_sig,
+            request_type,
+            IotaTransactionBlockResponseOptions::new(),
+            None,
+        )
+        .await
+        .map(|(effects, _)| effects)
+    }
+
+    /// Same as `execute_transaction`, but also returns the transaction response of the fullnode,
+    /// which includes what is requested by `options` in addition to the effects. The access
+    /// decision in `signing_context` is passed to the signer.
+    pub async fn execute_transaction_with_options(
+        &self,
+        reservation_id: ReservationID,
+        tx_data: TransactionData,
+        user_sig: GenericSignature,
+        request_type: Option<ExecuteTransactionRequestType>,
+        options: IotaTransactionBlockResponseOptions,
+        signing_context: Option<SigningContext>,
+    ) -> anyhow::Result<(IotaTransactionBlockEffects, IotaTransactionBlockResponse)> {
         let sponsor = tx_data.gas_data().owner;
-        if !self.signer.is_valid_address(&sponsor) {
-            bail!("Sponsor {:?} is not registered", sponsor);
-        };
+        let sender = tx_data.sender();
+        let (signer, gas_station_store) = self.get_sponsor_account(&sponsor)?;
+        // Checked before the reservation is used, since the signer would refuse the transaction.
+        if signer.requires_attestation()
+            && signing_context
+                .as_ref()
+                .and_then(|context| context.attestation.as_ref())
+                .is_none()
+        {
+            bail!("The signer only signs transactions whose access decision is attested by a hook");
+        }
         Self::check_transaction_validity(&tx_data)?;
+        // Checked before the reservation is taken out, so that it can be retried with the right
+        // gas data.
+        self.check_gas_data(reservation_id, &tx_data).await?;
         let payment: Vec<_> = tx_data
             .gas_data()
             .payment
@@ -108,7 +364,7 @@ impl GasStation {
             ?reservation_id,
             "Payment coins in transaction: {:?}", payment
         );
-        self.gas_station_store
+        gas_station_store
             .ready_for_execution(reservation_id)
             .await?;
         debug!(?reservation_id, "Reservation is ready for execution");
@@ -123,10 +379,34 @@ impl GasStation {
             "Total gas coin balance prior to execution: {}", total_gas_coin_balance,
         );
         let response = self
-            .execute_transaction_impl(reservation_id, tx_data, user_sig, request_type)
+            .execute_transaction_impl(
+                signer,
+                reservation_id,
+                tx_data,
+                user_sig,
+                request_type,
+                options,
+                signing_context.as_ref(),
+            )
             .await;
+        self.webhooks.notify(match &response {
+            Ok((effects, _)) => WebhookEvent::ExecuteSuccess {
+                sponsor,
+                sender,
+                reservation_id,
+                digest: *effects.transaction_digest(),
+                gas_used: effects.gas_cost_summary().gas_used(),
+                success: effects.status().is_ok(),
+            },
+            Err(err) => WebhookEvent::ExecuteFailure {
+                sponsor,
+                sender,
+                reservation_id,
+                error: err.to_string(),
+            },
+        });
         let updated_coins = match &response {
-            Ok(effects) => {
+            Ok((effects, _)) => {
                 let new_gas_coin = effects.gas_object().reference.to_object_ref();
                 let new_balance =
                     total_gas_coin_balance as i64 - effects.gas_cost_summary().net_gas_usage();
@@ -152,8 +432,7 @@ impl GasStation {
                     ?reservation_id,
                     "Querying latest gas state since transaction failed"
                 );
-                self.iota_client
-                    .get_latest_gas_objects(payment)
+                self.get_latest_gas_objects_forever(payment)
                     .await
                     .into_values()
                     .flatten()
@@ -164,7 +443,15 @@ impl GasStation {
         // Regardless of whether the transaction succeeded, we need to release the coins.
         // Otherwise, we lose track of them. This is because `ready_for_execution` already takes
         // the coins out of the pool and will not be covered by the auto-release mechanism.
-        self.release_gas_coins(updated_coins).await;
+        // Coins locked by a conflicting transaction are quarantined instead, and released once
+        // the lock is cleared.
+        if matches!(&response, Err(err) if is_object_locked_error(err)) {
+            self.quarantine_gas_coins(gas_station_store, sponsor, updated_coins)
+                .await;
+        } else {
+            self.release_gas_coins(gas_station_store, updated_coins)
+                .await;
+        }
         if smashed_coin_count > 0 {
             info!(
                 ?reservation_id,
@@ -180,24 +467,70 @@ impl GasStation {
         response
     }
 
+    /// Waits until the fullnode knows the executed transaction, for requests with
+    /// `WaitForLocalExecution`. Called once the gas coins are released, so that they aren't held
+    /// while waiting. Returns whether the fullnode knew it before the timeout.
+    pub async fn wait_for_local_execution(&self, digest: TransactionDigest) -> bool {
+        self.iota_client.wait_for_local_execution(digest).await
+    }
+
+    /// Checks that the gas data of the transaction matches its reservation: the gas owner is a
+    /// sponsor, the payment is the reserved coins and the budget is within the reserved budget.
+    /// Fails with a [`GasDataError`] otherwise. The reservations whose coins aren't known, e.g.
+    /// copied by `migrate-storage`, are only checked for the gas owner.
+    pub async fn check_gas_data(
+        &self,
+        reservation_id: ReservationID,
+        tx_data: &TransactionData,
+    ) -> anyhow::Result<()> {
+        let (_, gas_station_store) = self.get_sponsor_account(&tx_data.gas_data().owner)?;
+        if let Some(reservation) = gas_station_store.get_reservation(reservation_id).await? {
+            check_gas_data(reservation_id, &reservation, tx_data)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the signer and the pool of the given sponsor, which is either the current sponsor
+    /// or the previous one during a key rotation.
+    fn get_sponsor_account(
+        &self,
+        sponsor: &IotaAddress,
+    ) -> anyhow::Result<(&Arc<dyn TxSigner>, &Arc<dyn Storage>)> {
+        if self.signer.is_valid_address(sponsor) {
+            return Ok((&self.signer, &self.gas_station_store));
+        }
+        match &self.previous_sponsor {
+            Some(previous) if previous.signer.is_valid_address(sponsor) => {
+                Ok((&previous.signer, &previous.gas_station_store))
+            }
+            _ => Err(GasDataError::UnknownGasOwner(*sponsor).into()),
+        }
+    }
+
     async fn execute_transaction_impl(
         &self,
+        signer: &Arc<dyn TxSigner>,
         reservation_id: ReservationID,
         tx_data: TransactionData,
         user_sig: GenericSignature,
         request_type: Option<ExecuteTransactionRequestType>,
-    ) -> anyhow::Result<IotaTransactionBlockEffects> {
+        options: IotaTransactionBlockResponseOptions,
+        signing_context: Option<&SigningContext>,
+    ) -> anyhow::Result<(IotaTransactionBlockEffects, IotaTransactionBlockResponse)> {
         let sponsor = tx_data.gas_data().owner;
         let cur_time = std::time::Instant::now();
-        let sponsor_sig = retry_with_max_attempts!(
-            async {
-                self.signer
-                    .sign_transaction(&tx_data)
-                    .await
-                    .tap_err(|err| error!("Failed to sign transaction: {:?}", err))
-            },
-            3
-        )?;
+        let sponsor_sig = async {
+            retry_with_max_attempts!(
+                async {
+                    self.sign_transaction(signer, &tx_data, signing_context)
+                        .await
+                        .tap_err(|err| error!("Failed to sign transaction: {:?}", err))
+                },
+                3
+            )
+        }
+        .instrument(info_span!("sign_transaction", ?reservation_id))
+        .await?;
         let elapsed = cur_time.elapsed().as_millis();
         self.metrics
             .transaction_signing_latency_ms
@@ -206,10 +539,21 @@ impl GasStation {
 
         let tx = Transaction::from_generic_sig_data(tx_data, vec![sponsor_sig, user_sig]);
         let cur_time = std::time::Instant::now();
-        let effects = self
+        let response = self
             .iota_client
-            .execute_transaction(tx, 3, request_type)
-            .await?;
+            .execute_transaction_with_options(tx, 3, request_type, options)
+            .await
+            .map_err(|err| {
+                if is_user_sig_rejected_error(&err) {
+                    err.context(GasStationError::InvalidUserSig)
+                } else {
+                    err
+                }
+            })?;
+        let effects = response
+            .effects
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No effects"))?;
         debug!(?reservation_id, "Transaction executed");
         let elapsed = cur_time.elapsed().as_millis();
         self.metrics
@@ -221,11 +565,11 @@ impl GasStation {
             .daily_gas_usage
             .with_label_values(&[&sponsor.to_string()])
             .set(new_daily_usage);
-        Ok(effects)
+        Ok((effects, response))
     }
 
     async fn get_total_gas_coin_balance(&self, gas_coins: Vec<ObjectID>) -> u64 {
-        let latest = self.iota_client.get_latest_gas_objects(gas_coins).await;
+        let latest = self.get_latest_gas_objects_forever(gas_coins).await;
         latest
             .into_values()
             .flatten()
@@ -261,16 +605,35 @@ impl GasStation {
             .into_iter()
             .any(|arg| matches!(*arg, Argument::GasCoin));
         if uses_gas {
-            bail!("Gas coin can only be used to pay gas")
+            return Err(GasStationError::GasCoinUsedAsArgument.into());
         };
         Ok(())
     }
 
-    /// Release gas coins back to the Gas Station, by adding them to the storage.
-    async fn release_gas_coins(&self, gas_coins: Vec<GasCoin>) {
+    /// Coins that are out of the pool must be put back eventually, otherwise we lose track of
+    /// them. Hence their latest state is re-queried even after the client retry policy gives up.
+    async fn get_latest_gas_objects_forever(
+        &self,
+        gas_coins: Vec<ObjectID>,
+    ) -> HashMap<ObjectID, Option<GasCoin>> {
+        retry_forever!(async {
+            self.iota_client
+                .get_latest_gas_objects(gas_coins.clone())
+                .await
+                .tap_err(|err| error!("Failed to get latest gas objects: {:?}", err))
+        })
+        .unwrap()
+    }
+
+    /// Release gas coins back to the Gas Station, by adding them to the given storage.
+    async fn release_gas_coins(
+        &self,
+        gas_station_store: &Arc<dyn Storage>,
+        gas_coins: Vec<GasCoin>,
+    ) {
         debug!("Trying to release gas coins: {:?}", gas_coins);
         retry_forever!(async {
-            self.gas_station_store
+            gas_station_store
                 .add_new_coins(gas_coins.clone())
                 .await
                 .tap_err(|err| error!("Failed to call update_gas_coins on storage: {:?}", err))
@@ -278,11 +641,170 @@ impl GasStation {
         .unwrap();
     }
 
+    /// Keeps the given coins out of the pool until the next epoch, when the locks held by
+    /// conflicting transactions are cleared.
+    async fn quarantine_gas_coins(
+        &self,
+        gas_station_store: &Arc<dyn Storage>,
+        sponsor: IotaAddress,
+        gas_coins: Vec<GasCoin>,
+    ) {
+        let release_epoch = match self.iota_client.get_current_epoch().await {
+            Ok(epoch) => epoch + 1,
+            Err(err) => {
+                error!(
+                    "Failed to get the current epoch, releasing the coins instead of quarantining them: {:?}",
+                    err
+                );
+                self.release_gas_coins(gas_station_store, gas_coins).await;
+                return;
+            }
+        };
+        let count = gas_coins.len();
+        retry_forever!(async {
+            gas_station_store
+                .quarantine_coins(gas_coins.clone(), release_epoch)
+                .await
+                .tap_err(|err| error!("Failed to call quarantine_coins on storage: {:?}", err))
+        })
+        .unwrap();
+        warn!(
+            "Quarantined {} locked coins until epoch {}: {:?}",
+            count, release_epoch, gas_coins
+        );
+        self.metrics
+            .num_quarantined_gas_coins
+            .with_label_values(&[&sponsor.to_string()])
+            .inc_by(count as u64);
+    }
+
+    /// Lists the quarantined coins of the current and the previous sponsor.
+    pub async fn get_quarantined_coins(&self) -> anyhow::Result<Vec<QuarantinedCoin>> {
+        let mut coins = self.gas_station_store.get_quarantined_coins().await?;
+        if let Some(previous) = &self.previous_sponsor {
+            coins.extend(previous.gas_station_store.get_quarantined_coins().await?);
+        }
+        Ok(coins)
+    }
+
+    /// Keeps the response of an executed transaction, to answer the retries of its request.
+    pub async fn store_executed_transaction(
+        &self,
+        idempotency_key: &str,
+        data: &str,
+        retention: Duration,
+    ) -> anyhow::Result<()> {
+        self.gas_station_store
+            .put_executed_transaction(idempotency_key, data, retention.as_millis() as u64)
+            .await
+    }
+
+    pub async fn get_executed_transaction(
+        &self,
+        idempotency_key: &str,
+    ) -> anyhow::Result<Option<String>> {
+        self.gas_station_store
+            .get_executed_transaction(idempotency_key)
+            .await
+    }
+
+    /// Lists the coins in the pool of the current and the previous sponsor.
+    pub async fn get_pool_coins(&self) -> anyhow::Result<Vec<PoolCoin>> {
+        let mut coins = self.gas_station_store.get_pool_coins().await?;
+        if let Some(previous) = &self.previous_sponsor {
+            coins.extend(previous.gas_station_store.get_pool_coins().await?);
+        }
+        Ok(coins)
+    }
+
+    /// Refuses new reservations and transfers all the available coins of the current sponsor to
+    /// `recipient`, merged into as few coins as possible. Coins that are reserved at that time
+    /// return to the pool once executed or expired, so draining can be repeated to collect them,
+    /// as well as the coins of the batches too small to cover the gas budget. Returns the number
+    /// of drained coins and the transferred balance.
+    pub async fn drain_pool(&self, recipient: IotaAddress) -> anyhow::Result<(usize, u64)> {
+        let coins = self.gas_station_store.drain_available_coins().await?;
+        info!(
+            "Draining {} available coins of {} to {}",
+            coins.len(),
+            self.signer.get_address(),
+            recipient
+        );
+        let rgp = self.iota_client.get_reference_gas_price().await?;
+        let mut drained_coin_count = 0;
+        let mut transferred_balance = 0;
+        // The coins are only removed from the pool once their transfer is confirmed, so that the
+        // coins of a failed or skipped batch are still tracked, and drained by the next run.
+        for batch in coins.chunks(MAX_GAS_PER_QUERY) {
+            let transferred =
+                transfer_batch(&self.iota_client, &self.signer, batch, recipient, rgp)
+                    .await
+                    .with_context(|| {
+                        format!(
+                    "Failed to transfer the coins after draining {} coins with total balance {}",
+                    drained_coin_count, transferred_balance
+                )
+                    })?;
+            let Some(transferred) = transferred else {
+                continue;
+            };
+            let object_ids: Vec<_> = batch.iter().map(|coin| coin.object_ref.0).collect();
+            self.gas_station_store
+                .remove_available_coins(&object_ids)
+                .await?;
+            drained_coin_count += batch.len();
+            transferred_balance += transferred;
+        }
+        Ok((drained_coin_count, transferred_balance))
+    }
+
+    /// Allows reservations again after the pool was drained. The pool only holds the coins
+    /// released since then; the coins initializer adds the merged coins back on its next run.
+    pub async fn resume_pool(&self) -> anyhow::Result<()> {
+        self.gas_station_store.resume_pool().await
+    }
+
+    /// Records the client of a new reservation of the current sponsor, for the client
+    /// reservation stats. Kept a day longer than the reservation, in case it is extended.
+    pub async fn record_reservation_client(
+        &self,
+        reservation_id: ReservationID,
+        client: &str,
+        duration: Duration,
+    ) -> anyhow::Result<()> {
+        let retention = duration + CLIENT_RETENTION_MARGIN;
+        self.gas_station_store
+            .record_reservation_client(reservation_id, client, retention.as_millis() as u64)
+            .await
+    }
+
+    /// Returns the `limit` clients of the current sponsor with the most reservations expired
+    /// without being executed.
+    pub async fn get_client_reservation_stats(
+        &self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ClientReservationStats>> {
+        self.gas_station_store
+            .get_client_reservation_stats(limit)
+            .await
+    }
+
+    /// Returns the progress of the coin initialization of the current sponsor, if coins are being
+    /// split or the last initialization was interrupted.
+    pub async fn get_init_progress(&self) -> anyhow::Result<Option<InitProgress>> {
+        self.gas_station_store.get_init_progress().await
+    }
+
     /// Performs an end-to-end flow of reserving gas, signing a transaction, and releasing the gas coins.
     pub async fn debug_check_health(&self) -> anyhow::Result<()> {
+        self.check_readiness()?;
         let gas_budget = NANOS_PER_IOTA / 10;
         let (_address, _reservation_id, gas_coins) =
             self.reserve_gas(gas_budget, Duration::from_secs(3)).await?;
+        // A signer requiring attestations would refuse the test transaction, which no hook allowed.
+        if self.signer.requires_attestation() {
+            return Ok(());
+        }
         let tx_kind = TransactionKind::ProgrammableTransaction(
             ProgrammableTransactionBuilder::new().finish(),
         );
@@ -294,33 +816,77 @@ impl GasStation {
             gas_budget,
             0,
         );
-        self.signer.sign_transaction(&tx_data).await?;
+        self.sign_transaction(&self.signer, &tx_data, None).await?;
         Ok(())
     }
 
+    /// Checks that the fullnode and the signers are reachable, without sending any request, so
+    /// that a slow signer is told apart from a slow fullnode.
+    pub fn check_readiness(&self) -> anyhow::Result<()> {
+        self.iota_client.check_circuit_breaker()?;
+        std::iter::once(&self.signer)
+            .chain(
+                self.previous_sponsor
+                    .as_ref()
+                    .map(|previous| &previous.signer),
+            )
+            .try_for_each(|signer| {
+                signer.check_reachability().with_context(|| {
+                    format!(
+                        "The {} signer of {} is unreachable",
+                        signer.backend(),
+                        signer.get_address()
+                    )
+                })
+            })
+    }
+
+    /// Signs with `signer`, recording the latency and the errors of its backend.
+    async fn sign_transaction(
+        &self,
+        signer: &Arc<dyn TxSigner>,
+        tx_data: &TransactionData,
+        context: Option<&SigningContext>,
+    ) -> anyhow::Result<GenericSignature> {
+        let cur_time = std::time::Instant::now();
+        let result = match context {
+            Some(context) => signer.sign_transaction_with_context(tx_data, context).await,
+            None => signer.sign_transaction(tx_data).await,
+        };
+        let backend = signer.backend();
+        self.metrics
+            .signer_latency_ms
+            .with_label_values(&[backend])
+            .observe(cur_time.elapsed().as_millis() as u64);
+        if result.is_err() {
+            self.metrics
+                .num_signer_errors
+                .with_label_values(&[backend])
+                .inc();
+        }
+        result
+    }
+
+    /// Only the leader sweeps the pool, so that replicas sharing it don't query the same coins.
     async fn start_coin_unlock_task(
         self: Arc<Self>,
+        leadership: Leadership,
         mut cancel_receiver: tokio::sync::oneshot::Receiver<()>,
     ) -> JoinHandle<()> {
         tokio::task::spawn(async move {
             loop {
-                let expire_results = self.gas_station_store.expire_coins().await;
-                let unlocked_coins = expire_results.unwrap_or_else(|err| {
-                    error!("Failed to call expire_coins to the storage: {:?}", err);
-                    vec![]
-                });
-                if !unlocked_coins.is_empty() {
-                    debug!("Coins that are expired: {:?}", unlocked_coins);
-                    let latest_coins: Vec<_> = self
-                        .iota_client
-                        .get_latest_gas_objects(unlocked_coins.clone())
-                        .await
-                        .into_values()
-                        .flatten()
-                        .collect();
-                    let count = latest_coins.len();
-                    self.release_gas_coins(latest_coins).await;
-                    info!("Released {:?} coins after expiration", count);
+                let gas_station_stores = std::iter::once((&self.signer, &self.gas_station_store))
+                    .chain(
+                        self.previous_sponsor
+                            .as_ref()
+                            .map(|previous| (&previous.signer, &previous.gas_station_store)),
+                    );
+                if leadership.is_leader() {
+                    for (signer, gas_station_store) in gas_station_stores {
+                        self.unlock_expired_coins(signer.get_address(), gas_station_store)
+                            .await;
+                        self.release_quarantined_coins(gas_station_store).await;
+                    }
                 }
                 tokio::select! {
                     _ = tokio::time::sleep(EXPIRATION_JOB_INTERVAL) => {}
@@ -333,6 +899,142 @@ impl GasStation {
         })
     }
 
+    async fn unlock_expired_coins(
+        &self,
+        sponsor: IotaAddress,
+        gas_station_store: &Arc<dyn Storage>,
+    ) {
+        let expire_results = gas_station_store.expire_reservations().await;
+        let expired_reservations = expire_results.unwrap_or_else(|err| {
+            error!(
+                "Failed to call expire_reservations to the storage: {:?}",
+                err
+            );
+            vec![]
+        });
+        self.metrics
+            .num_expired_unused_reservations
+            .with_label_values(&[&sponsor.to_string()])
+            .inc_by(expired_reservations.len() as u64);
+        let mut unlocked_coins = vec![];
+        for (reservation_id, gas_coins) in expired_reservations {
+            unlocked_coins.extend(gas_coins.iter().copied());
+            self.webhooks
+                .notify(WebhookEvent::ReservationExpiredUnused {
+                    sponsor,
+                    reservation_id,
+                    gas_coins,
+                });
+        }
+        if !unlocked_coins.is_empty() {
+            debug!("Coins that are expired: {:?}", unlocked_coins);
+            let latest_coins: Vec<_> = self
+                .get_latest_gas_objects_forever(unlocked_coins.clone())
+                .await
+                .into_values()
+                .flatten()
+                .collect();
+            let count = latest_coins.len();
+            self.release_gas_coins(gas_station_store, latest_coins)
+                .await;
+            info!("Released {:?} coins after expiration", count);
+        }
+    }
+
+    async fn release_quarantined_coins(&self, gas_station_store: &Arc<dyn Storage>) {
+        let current_epoch = match self.iota_client.get_current_epoch().await {
+            Ok(epoch) => epoch,
+            Err(err) => {
+                debug!("Failed to get the current epoch: {:?}", err);
+                return;
+            }
+        };
+        let released_coins = gas_station_store
+            .release_quarantined_coins(current_epoch)
+            .await
+            .unwrap_or_else(|err| {
+                error!(
+                    "Failed to call release_quarantined_coins to the storage: {:?}",
+                    err
+                );
+                vec![]
+            });
+        if !released_coins.is_empty() {
+            let latest_coins: Vec<_> = self
+                .get_latest_gas_objects_forever(released_coins)
+                .await
+                .into_values()
+                .flatten()
+                .collect();
+            let count = latest_coins.len();
+            self.release_gas_coins(gas_station_store, latest_coins)
+                .await;
+            info!(
+                "Released {:?} quarantined coins at epoch {}",
+                count, current_epoch
+            );
+        }
+    }
+
+    fn start_pool_metrics_sampler_task(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::task::spawn(async move {
+            let mut interval = tokio::time::interval(POOL_METRICS_SAMPLING_INTERVAL);
+            loop {
+                interval.tick().await;
+                self.sample_pool_metrics(self.signer.get_address(), &self.gas_station_store)
+                    .await;
+                if let Some(previous) = &self.previous_sponsor {
+                    self.sample_pool_metrics(
+                        previous.signer.get_address(),
+                        &previous.gas_station_store,
+                    )
+                    .await;
+                }
+            }
+        })
+    }
+
+    async fn sample_pool_metrics(
+        &self,
+        sponsor: IotaAddress,
+        gas_station_store: &Arc<dyn Storage>,
+    ) {
+        let sponsor = sponsor.to_string();
+        match gas_station_store.get_available_coin_count().await {
+            Ok(count) => self
+                .metrics
+                .pool_available_gas_coin_count
+                .with_label_values(&[&sponsor])
+                .set(count as i64),
+            Err(err) => error!("Failed to sample the available coin count: {:?}", err),
+        }
+        let total_balance = gas_station_store.get_available_coin_total_balance().await;
+        self.metrics
+            .pool_available_gas_total_balance
+            .with_label_values(&[&sponsor])
+            .set(total_balance as i64);
+        match gas_station_store.get_reservation_stats().await {
+            Ok(stats) => {
+                let oldest_reservation_age_sec = stats
+                    .oldest_reservation_time_ms
+                    .map(|time_ms| {
+                        (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(time_ms)
+                            / 1000
+                    })
+                    .unwrap_or_default();
+                self.metrics
+                    .pool_reserved_gas_coin_count
+                    .with_label_values(&[&sponsor])
+                    .set(stats.reserved_coin_count as i64);
+                self.metrics
+                    .pool_oldest_reservation_age_sec
+                    .with_label_values(&[&sponsor])
+                    .set(oldest_reservation_age_sec as i64);
+            }
+            Err(err) => error!("Failed to sample the reservation stats: {:?}", err),
+        }
+    }
+
     pub async fn query_pool_available_coin_count(&self) -> usize {
         self.gas_station_store
             .get_available_coin_count()
@@ -341,28 +1043,64 @@ impl GasStation {
     }
 }
 
+/// Whether the transaction failed because some of its objects are locked by a conflicting
+/// transaction, e.g. after an equivocation. Such objects cannot be used until the end of the epoch.
+fn is_object_locked_error(err: &anyhow::Error) -> bool {
+    const MARKERS: [&str; 4] = [
+        "ObjectLockConflict",
+        "ObjectsDoubleUsed",
+        "already locked by a different transaction",
+        "equivocat",
+    ];
+    let message = format!("{:?}", err);
+    MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+/// Whether the fullnode rejected the transaction because of the signature of the user. The user
+/// signature is verified before the execution, so this only happens for the signatures that can't
+/// be fully verified by the Gas Station, e.g. zkLogin.
+fn is_user_sig_rejected_error(err: &anyhow::Error) -> bool {
+    const MARKERS: [&str; 3] = [
+        "Signature is not valid",
+        "SignerSignatureAbsent",
+        "InvalidSignature",
+    ];
+    let message = format!("{:?}", err);
+    MARKERS.iter().any(|marker| message.contains(marker))
+}
+
 impl GasStationContainer {
     pub async fn new(
         signer: Arc<dyn TxSigner>,
         gas_station_store: Arc<dyn Storage>,
+        previous_sponsor: Option<PreviousSponsor>,
         iota_client: IotaClient,
         gas_usage_daily_cap: u64,
         metrics: Arc<GasStationCoreMetrics>,
+        webhooks: Webhooks,
+        leadership: Leadership,
     ) -> Self {
         let inner = GasStation::new(
             signer,
             gas_station_store,
+            previous_sponsor,
             iota_client,
             metrics,
             Arc::new(GasUsageCap::new(gas_usage_daily_cap)),
+            webhooks,
         )
         .await;
         let (cancel_sender, cancel_receiver) = tokio::sync::oneshot::channel();
-        let _coin_unlocker_task = inner.clone().start_coin_unlock_task(cancel_receiver).await;
+        let _coin_unlocker_task = inner
+            .clone()
+            .start_coin_unlock_task(leadership, cancel_receiver)
+            .await;
+        let pool_metrics_sampler_task = inner.clone().start_pool_metrics_sampler_task();
 
         Self {
             inner,
             _coin_unlocker_task,
+            pool_metrics_sampler_task,
             cancel_sender: Some(cancel_sender),
         }
     }
@@ -380,5 +1118,76 @@ impl GasStationContainer {
 impl Drop for GasStationContainer {
     fn drop(&mut self) {
         self.cancel_sender.take().unwrap().send(()).unwrap();
+        self.pool_metrics_sampler_task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::start_gas_station;
+
+    #[tokio::test]
+    async fn test_sample_pool_metrics() {
+        let (_test_cluster, container) =
+            start_gas_station(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let station = container.get_gas_station_arc();
+        station
+            .reserve_gas(NANOS_PER_IOTA * 3, Duration::from_secs(10))
+            .await
+            .unwrap();
+        let sponsor = station.get_sponsor_address();
+        station
+            .sample_pool_metrics(sponsor, &station.gas_station_store)
+            .await;
+
+        let sponsor = sponsor.to_string();
+        let metrics = &station.metrics;
+        let gauge = |gauge: &prometheus::IntGaugeVec| gauge.with_label_values(&[&sponsor]).get();
+        assert_eq!(gauge(&metrics.pool_available_gas_coin_count), 7);
+        assert_eq!(
+            gauge(&metrics.pool_available_gas_total_balance),
+            (NANOS_PER_IOTA * 7) as i64
+        );
+        assert_eq!(gauge(&metrics.pool_reserved_gas_coin_count), 3);
+        assert!(gauge(&metrics.pool_oldest_reservation_age_sec) < 10);
+    }
+
+    #[test]
+    fn test_is_object_locked_error() {
+        assert!(is_object_locked_error(&anyhow::anyhow!(
+            "Transaction is rejected: ObjectLockConflict {{ obj_ref: .. }}"
+        )));
+        assert!(is_object_locked_error(
+            &anyhow::anyhow!("ObjectsDoubleUsed").context("Failed to execute transaction")
+        ));
+        assert!(!is_object_locked_error(&anyhow::anyhow!("InsufficientGas")));
+    }
+
+    #[test]
+    fn test_is_user_sig_rejected_error() {
+        assert!(is_user_sig_rejected_error(&anyhow::anyhow!(
+            "Transaction is rejected: InvalidSignature {{ error: .. }}"
+        )));
+        assert!(!is_user_sig_rejected_error(&anyhow::anyhow!(
+            "ObjectLockConflict"
+        )));
+    }
+
+    #[test]
+    fn test_calculate_recommended_budget() {
+        let summary = GasCostSummary {
+            computation_cost: 1000,
+            storage_cost: 2000,
+            storage_rebate: 500,
+            ..Default::default()
+        };
+        // (1000 + 2000) + 20% margin; the rebate is not subtracted.
+        assert_eq!(GasStation::calculate_recommended_budget(&summary), 3600);
+
+        assert_eq!(
+            GasStation::calculate_recommended_budget(&GasCostSummary::default()),
+            0
+        );
     }
 }
@@ -1,12 +1,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::bail;
+use crate::errors::GasStationError;
 use chrono::{Local, NaiveDate};
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
 
 pub struct GasUsageCap {
-    daily_cap: u64,
+    daily_cap: AtomicU64,
     inner: RwLock<GasUsageCapInner>,
 }
 
@@ -18,7 +19,7 @@ struct GasUsageCapInner {
 impl GasUsageCap {
     pub fn new(daily_cap: u64) -> Self {
         Self {
-            daily_cap,
+            daily_cap: AtomicU64::new(daily_cap),
             inner: RwLock::new(GasUsageCapInner {
                 cur_daily_usage: 0,
                 cur_date: Local::now().date_naive(),
@@ -29,12 +30,16 @@ impl GasUsageCap {
     pub async fn check_usage(&self) -> anyhow::Result<()> {
         self.reset_date_maybe().await;
         let cur_daily_usage = self.inner.read().await.cur_daily_usage;
-        if cur_daily_usage >= self.daily_cap as i64 {
-            bail!("Gas usage exceeds daily cap");
+        if cur_daily_usage >= self.daily_cap.load(Ordering::Relaxed) as i64 {
+            return Err(GasStationError::GasUsageCapExceeded.into());
         }
         Ok(())
     }
 
+    pub fn set_daily_cap(&self, daily_cap: u64) {
+        self.daily_cap.store(daily_cap, Ordering::Relaxed);
+    }
+
     /// Update daily usage and returns the new current usage.
     pub async fn update_usage(&self, usage: i64) -> i64 {
         self.reset_date_maybe().await;
@@ -84,4 +89,13 @@ mod tests {
         cap.inner.write().await.cur_date = today + chrono::Duration::days(1);
         assert!(cap.check_usage().await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_gas_usage_cap_update() {
+        let cap = GasUsageCap::new(100);
+        cap.update_usage(100).await;
+        assert!(cap.check_usage().await.is_err());
+        cap.set_daily_cap(200);
+        assert!(cap.check_usage().await.is_ok());
+    }
 }
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod gas_data;
 pub mod gas_station_core;
 mod gas_usage_cap;
 
@@ -128,7 +129,6 @@ mod tests {
             .unwrap();
     }
 
-    #[ignore]
     #[tokio::test]
     async fn test_incomplete_gas_usage() {
         let (test_cluster, container) =
@@ -159,7 +159,6 @@ mod tests {
         assert!(effects.status().is_ok());
     }
 
-    #[ignore]
     #[tokio::test]
     async fn test_mixed_up_gas_coins() {
         let (test_cluster, container) =
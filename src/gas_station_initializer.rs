@@ -2,13 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::CoinInitConfig;
+use crate::faucet::{get_faucet_coins, request_faucet_funds, FaucetTopUpConfig};
 use crate::iota_client::IotaClient;
+use crate::leader_election::Leadership;
 use crate::retry_forever;
 use crate::storage::Storage;
 use crate::tx_signer::TxSigner;
-use crate::types::GasCoin;
+use crate::types::{GasCoin, InitProgress};
 use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
-use iota_types::base_types::IotaAddress;
+use iota_types::base_types::{IotaAddress, ObjectID};
 use iota_types::coin::{PAY_MODULE_NAME, PAY_SPLIT_N_FUNC_NAME};
 use iota_types::gas_coin::GAS;
 use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
@@ -17,20 +19,26 @@ use iota_types::IOTA_FRAMEWORK_PACKAGE_ID;
 use parking_lot::Mutex;
 use std::cmp::min;
 use std::collections::VecDeque;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tap::TapFallible;
 use tokio::task::JoinHandle;
 use tokio::time::Instant;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Any coin owned by the sponsor address with balance above target_init_coin_balance * NEW_COIN_BALANCE_FACTOR_THRESHOLD
 /// is considered a new coin, and we will try to split it into smaller coins with balance close to target_init_coin_balance.
 const NEW_COIN_BALANCE_FACTOR_THRESHOLD: u64 = 200;
 
-/// Assume that initializing the Gas Station (i.e. splitting coins) will take at most 12 hours.
-const MAX_INIT_DURATION_SEC: u64 = 60 * 60 * 12;
+/// The init lock is renewed every INIT_LOCK_RENEWAL_INTERVAL while coins are being split. If the
+/// initializer crashes, the lock expires after INIT_LOCK_DURATION_SEC and the next run resumes
+/// the initialization from the progress kept in the storage.
+const INIT_LOCK_DURATION_SEC: u64 = 60 * 10;
+const INIT_LOCK_RENEWAL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of coins of the pool split again on each refresh.
+const MAX_RESPLIT_COIN_COUNT: usize = 100;
 
 #[derive(Clone)]
 struct CoinSplitEnv {
@@ -39,8 +47,10 @@ struct CoinSplitEnv {
     signer: Arc<dyn TxSigner>,
     sponsor_address: IotaAddress,
     iota_client: IotaClient,
-    task_queue: Arc<Mutex<VecDeque<JoinHandle<Vec<GasCoin>>>>>,
+    storage: Arc<dyn Storage>,
+    task_queue: Arc<Mutex<VecDeque<(ObjectID, JoinHandle<Vec<GasCoin>>)>>>,
     total_coin_count: Arc<AtomicUsize>,
+    progress: Arc<Mutex<InitProgress>>,
     rgp: u64,
 }
 
@@ -53,12 +63,45 @@ impl CoinSplitEnv {
             );
             return Some(coin);
         }
+        let object_id = coin.object_ref.0;
         let env = self.clone();
         let task = tokio::task::spawn(async move { env.split_one_gas_coin(coin).await });
-        self.task_queue.lock().push_back(task);
+        self.progress.lock().pending_coins.push(object_id);
+        self.task_queue.lock().push_back((object_id, task));
         None
     }
 
+    /// Adds the coins of a finished split to the pool, and persists the progress so that the
+    /// split is not done again if the initialization is resumed.
+    async fn complete_task(&self, object_id: ObjectID, coins: Vec<GasCoin>) {
+        for chunk in coins.chunks(5000) {
+            self.storage.add_new_coins(chunk.to_vec()).await.unwrap();
+        }
+        let progress = {
+            let mut progress = self.progress.lock();
+            // The remainder of a split coin keeps its ID, and may be pending again already.
+            if let Some(index) = progress
+                .pending_coins
+                .iter()
+                .position(|id| *id == object_id)
+            {
+                progress.pending_coins.remove(index);
+            }
+            progress.split_coin_count += 1;
+            progress.added_coin_count += coins.len() as u64;
+            progress.clone()
+        };
+        self.save_progress(&progress).await;
+    }
+
+    async fn save_progress(&self, progress: &InitProgress) {
+        let _ = self
+            .storage
+            .set_init_progress(Some(progress))
+            .await
+            .tap_err(|err| error!("Failed to save the coin init progress: {:?}", err));
+    }
+
     fn increment_total_coin_count_by(&self, delta: usize) {
         info!(
             "Number of coins got so far: {}",
@@ -126,15 +169,19 @@ impl CoinSplitEnv {
                 }
                 Err(e) => {
                     error!("Failed to execute transaction: {:?}", e);
-                    coin = self
-                        .iota_client
-                        .get_latest_gas_objects([coin.object_ref.0])
-                        .await
-                        .into_iter()
-                        .next()
-                        .unwrap()
-                        .1
-                        .unwrap();
+                    // The coin is being split, so we must not give up on it.
+                    coin = retry_forever!(async {
+                        self.iota_client
+                            .get_latest_gas_objects([coin.object_ref.0])
+                            .await
+                            .tap_err(|err| error!("Failed to get latest gas coin: {:?}", err))
+                    })
+                    .unwrap()
+                    .into_iter()
+                    .next()
+                    .unwrap()
+                    .1
+                    .unwrap();
                     continue;
                 }
             }
@@ -160,11 +207,23 @@ impl CoinSplitEnv {
 
 enum RunMode {
     Init,
-    Refresh,
+    /// Splits the new coins of the sponsor, and the coins of the pool with a balance above
+    /// `resplit_balance_factor` times the target balance, if set.
+    Refresh {
+        resplit_balance_factor: Option<u64>,
+    },
+    /// Splits the coins sent by the faucet. Only these coins are added, since the other coins of
+    /// the sponsor that are not in the pool may be used by transactions being executed.
+    TopUp {
+        coins: Vec<ObjectID>,
+    },
 }
 
 pub struct GasStationInitializer {
     _task_handle: JoinHandle<()>,
+    faucet_top_up_task: Option<JoinHandle<()>>,
+    // Shared with the config reloader, so that the target balance can be changed at runtime.
+    target_init_balance: Arc<AtomicU64>,
     // This is always Some. It is None only after the drop method is called.
     cancel_sender: Option<tokio::sync::oneshot::Sender<()>>,
 }
@@ -172,6 +231,9 @@ pub struct GasStationInitializer {
 impl Drop for GasStationInitializer {
     fn drop(&mut self) {
         self.cancel_sender.take().unwrap().send(()).unwrap();
+        if let Some(task) = &self.faucet_top_up_task {
+            task.abort();
+        }
     }
 }
 
@@ -181,6 +243,7 @@ impl GasStationInitializer {
         storage: Arc<dyn Storage>,
         coin_init_config: CoinInitConfig,
         signer: Arc<dyn TxSigner>,
+        leadership: Leadership,
     ) -> Self {
         if !storage.is_initialized().await.unwrap() {
             // If the pool has never been initialized, always run once at the beginning to make sure we have enough coins.
@@ -190,33 +253,72 @@ impl GasStationInitializer {
                 RunMode::Init,
                 coin_init_config.target_init_balance,
                 &signer,
+                &leadership,
+            )
+            .await;
+        } else if storage.get_init_progress().await.unwrap().is_some() {
+            // An interrupted initialization is resumed right away, if its lock has expired.
+            Self::run_once(
+                iota_client.clone(),
+                &storage,
+                RunMode::Refresh {
+                    resplit_balance_factor: None,
+                },
+                coin_init_config.target_init_balance,
+                &signer,
+                &leadership,
             )
             .await;
         }
+        let target_init_balance = Arc::new(AtomicU64::new(coin_init_config.target_init_balance));
+        let faucet_top_up_task = coin_init_config.faucet_top_up.map(|config| {
+            tokio::spawn(Self::run_faucet_top_up(
+                iota_client.clone(),
+                storage.clone(),
+                config,
+                target_init_balance.clone(),
+                signer.clone(),
+                leadership.clone(),
+            ))
+        });
         let (cancel_sender, cancel_receiver) = tokio::sync::oneshot::channel();
         let _task_handle = tokio::spawn(Self::run(
             iota_client,
             storage,
-            coin_init_config,
+            coin_init_config.refresh_interval_sec,
+            coin_init_config.resplit_balance_factor,
+            target_init_balance.clone(),
             signer,
+            leadership,
             cancel_receiver,
         ));
         Self {
             _task_handle,
+            faucet_top_up_task,
+            target_init_balance,
             cancel_sender: Some(cancel_sender),
         }
     }
 
+    /// Returns the target balance used by the refresh task. Updating it takes effect on the
+    /// next refresh.
+    pub fn target_init_balance(&self) -> Arc<AtomicU64> {
+        self.target_init_balance.clone()
+    }
+
     async fn run(
         iota_client: IotaClient,
         storage: Arc<dyn Storage>,
-        coin_init_config: CoinInitConfig,
+        refresh_interval_sec: u64,
+        resplit_balance_factor: Option<u64>,
+        target_init_balance: Arc<AtomicU64>,
         signer: Arc<dyn TxSigner>,
+        leadership: Leadership,
         mut cancel_receiver: tokio::sync::oneshot::Receiver<()>,
     ) {
         loop {
             tokio::select! {
-                _ = tokio::time::sleep(Duration::from_secs(coin_init_config.refresh_interval_sec)) => {}
+                _ = tokio::time::sleep(Duration::from_secs(refresh_interval_sec)) => {}
                 _ = &mut cancel_receiver => {
                     info!("Coin init task is cancelled");
                     break;
@@ -226,24 +328,106 @@ impl GasStationInitializer {
             Self::run_once(
                 iota_client.clone(),
                 &storage,
-                RunMode::Refresh,
-                coin_init_config.target_init_balance,
+                RunMode::Refresh {
+                    resplit_balance_factor,
+                },
+                target_init_balance.load(Ordering::Relaxed),
                 &signer,
+                &leadership,
             )
             .await;
         }
     }
 
+    /// Requests funds from the faucet whenever the available balance of the pool is below
+    /// `min_pool_balance`, and adds them to the pool once they arrive.
+    async fn run_faucet_top_up(
+        iota_client: IotaClient,
+        storage: Arc<dyn Storage>,
+        config: FaucetTopUpConfig,
+        target_init_balance: Arc<AtomicU64>,
+        signer: Arc<dyn TxSigner>,
+        leadership: Leadership,
+    ) {
+        match iota_client.get_chain_identifier().await {
+            Ok(chain_identifier) if !config.is_test_network(&chain_identifier) => {
+                warn!("Faucet top-up is disabled, the fullnode is not on a test network");
+                return;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                error!("Faucet top-up is disabled: {:?}", err);
+                return;
+            }
+        }
+        info!(
+            "Requesting funds from {} when the pool balance is below {}",
+            config.faucet_url, config.min_pool_balance
+        );
+        let sponsor_address = signer.get_address();
+        // The faucet task of the last request, until its coins are added.
+        let mut pending_task: Option<String> = None;
+        loop {
+            tokio::time::sleep(Duration::from_secs(config.check_interval_sec)).await;
+            if !leadership.is_leader() || storage.is_draining().await.unwrap_or(false) {
+                continue;
+            }
+            // Funds requested on a previous check are added first.
+            if let Some(task) = &pending_task {
+                match get_faucet_coins(&config.faucet_url, task).await {
+                    Ok(None) => continue,
+                    Ok(Some(coins)) => {
+                        pending_task = None;
+                        Self::run_once(
+                            iota_client.clone(),
+                            &storage,
+                            RunMode::TopUp { coins },
+                            target_init_balance.load(Ordering::Relaxed),
+                            &signer,
+                            &leadership,
+                        )
+                        .await;
+                    }
+                    Err(err) => {
+                        error!("Failed to get the funds sent by the faucet: {:?}", err);
+                        pending_task = None;
+                    }
+                }
+            }
+            let balance = storage.get_available_coin_total_balance().await;
+            if balance >= config.min_pool_balance {
+                continue;
+            }
+            info!(
+                "Pool balance {} is below {}. Requesting funds from the faucet",
+                balance, config.min_pool_balance
+            );
+            pending_task = request_faucet_funds(&config.faucet_url, sponsor_address)
+                .await
+                .tap_err(|err| error!("Failed to request funds from the faucet: {:?}", err))
+                .ok();
+        }
+    }
+
     async fn run_once(
         iota_client: IotaClient,
         storage: &Arc<dyn Storage>,
         mode: RunMode,
         target_init_coin_balance: u64,
         signer: &Arc<dyn TxSigner>,
+        leadership: &Leadership,
     ) {
-        let sponsor_address = signer.get_address();
+        if !leadership.is_leader() {
+            info!("Another replica is the leader of the pool. Skipping new coin initialization");
+            return;
+        }
+        if storage.is_draining().await.unwrap_or(false) {
+            info!("The pool is being drained. Skipping new coin initialization");
+            return;
+        }
+        let holder_id = uuid::Uuid::new_v4().to_string();
         if storage
-            .acquire_init_lock(MAX_INIT_DURATION_SEC)
+            .acquire_init_lock(&holder_id, INIT_LOCK_DURATION_SEC)
             .await
             .unwrap()
         {
@@ -252,55 +436,230 @@ impl GasStationInitializer {
             info!("Another task is already initializing the pool. Skipping this round");
             return;
         }
+        let lock_renewal = tokio::spawn({
+            let storage = storage.clone();
+            let holder_id = holder_id.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(INIT_LOCK_RENEWAL_INTERVAL).await;
+                    match storage
+                        .renew_init_lock(&holder_id, INIT_LOCK_DURATION_SEC)
+                        .await
+                    {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            error!("The init lock expired and was acquired by another task");
+                            break;
+                        }
+                        Err(err) => error!("Failed to renew the init lock: {:?}", err),
+                    }
+                }
+            }
+        });
+        Self::initialize(iota_client, storage, mode, target_init_coin_balance, signer).await;
+        lock_renewal.abort();
+        storage.release_init_lock(&holder_id).await.unwrap();
+    }
+
+    async fn initialize(
+        iota_client: IotaClient,
+        storage: &Arc<dyn Storage>,
+        mode: RunMode,
+        target_init_coin_balance: u64,
+        signer: &Arc<dyn TxSigner>,
+    ) {
+        let sponsor_address = signer.get_address();
         let start = Instant::now();
-        let balance_threshold = if matches!(mode, RunMode::Init) {
-            info!("The pool has never been initialized. Initializing it for the first time");
-            0
-        } else {
-            target_init_coin_balance * NEW_COIN_BALANCE_FACTOR_THRESHOLD
+        let is_first_init = matches!(mode, RunMode::Init);
+        let previous_progress = match storage.get_init_progress().await {
+            Ok(progress) => progress.filter(|progress| !progress.pending_coins.is_empty()),
+            Err(err) => {
+                error!("Failed to get the coin init progress: {:?}", err);
+                return;
+            }
         };
-        let coins = iota_client
-            .get_all_owned_iota_coins_above_balance_threshold(sponsor_address, balance_threshold)
-            .await;
-        if coins.is_empty() {
+        let (coins, progress) = if let Some(progress) = previous_progress {
             info!(
-                "No coins with balance above {} found. Skipping new coin initialization",
-                balance_threshold
+                "Resuming the interrupted coin initialization with {} pending coins",
+                progress.pending_coins.len()
             );
-            storage.release_init_lock().await.unwrap();
+            let coins = if is_first_init {
+                // The coins split off before the interruption may not have been added yet. The
+                // pool has never been used, so all the coins of the sponsor are scanned again,
+                // the ones already added are not counted twice.
+                match iota_client
+                    .get_all_owned_iota_coins_above_balance_threshold(sponsor_address, 0)
+                    .await
+                {
+                    Ok(coins) => coins,
+                    Err(err) => {
+                        error!("Failed to query coins to initialize: {:?}", err);
+                        return;
+                    }
+                }
+            } else {
+                match iota_client
+                    .get_latest_gas_objects(progress.pending_coins.clone())
+                    .await
+                {
+                    // The coins that no longer exist were merged or split completely before the
+                    // interruption.
+                    Ok(coins) => coins.into_values().flatten().collect::<Vec<_>>(),
+                    Err(err) => {
+                        error!("Failed to query the pending coins: {:?}", err);
+                        return;
+                    }
+                }
+            };
+            let progress = InitProgress {
+                pending_coins: vec![],
+                ..progress
+            };
+            (coins, progress)
+        } else {
+            let balance_threshold = match mode {
+                RunMode::Init => {
+                    info!(
+                        "The pool has never been initialized. Initializing it for the first time"
+                    );
+                    0
+                }
+                RunMode::Refresh { .. } => {
+                    target_init_coin_balance * NEW_COIN_BALANCE_FACTOR_THRESHOLD
+                }
+                RunMode::TopUp { .. } => 0,
+            };
+            let coins = if let RunMode::TopUp { coins } = &mode {
+                match iota_client.get_latest_gas_objects(coins.clone()).await {
+                    Ok(coins) => coins.into_values().flatten().collect(),
+                    Err(err) => {
+                        error!("Failed to query the coins sent by the faucet: {:?}", err);
+                        return;
+                    }
+                }
+            } else {
+                match iota_client
+                    .get_all_owned_iota_coins_above_balance_threshold(
+                        sponsor_address,
+                        balance_threshold,
+                    )
+                    .await
+                {
+                    Ok(coins) => coins,
+                    Err(err) => {
+                        error!("Failed to query coins to initialize: {:?}", err);
+                        return;
+                    }
+                }
+            };
+            let coins = match mode {
+                RunMode::Refresh {
+                    resplit_balance_factor: Some(resplit_balance_factor),
+                } => {
+                    let mut coins = coins;
+                    match storage
+                        .take_large_coins(
+                            target_init_coin_balance * resplit_balance_factor,
+                            MAX_RESPLIT_COIN_COUNT,
+                        )
+                        .await
+                    {
+                        Ok(large_coins) => {
+                            info!(
+                                "Taking {} coins out of the pool to split them again",
+                                large_coins.len()
+                            );
+                            // The large coins of the pool are also owned by the sponsor.
+                            coins.retain(|coin| {
+                                !large_coins
+                                    .iter()
+                                    .any(|large_coin| large_coin.object_ref.0 == coin.object_ref.0)
+                            });
+                            coins.extend(large_coins);
+                        }
+                        Err(err) => error!("Failed to take the large coins of the pool: {:?}", err),
+                    }
+                    coins
+                }
+                _ => coins,
+            };
+            if coins.is_empty() {
+                info!(
+                    "No coins with balance above {} found. Skipping new coin initialization",
+                    balance_threshold
+                );
+                return;
+            }
+            let progress = InitProgress {
+                target_init_balance: target_init_coin_balance,
+                started_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+                ..Default::default()
+            };
+            (coins, progress)
+        };
+        if coins.is_empty() {
+            info!("None of the pending coins are left. Finishing the coin initialization");
+            let _ = storage.set_init_progress(None).await;
             return;
         }
+        // The coins are pending until they are split, so that the coins taken out of the pool
+        // are not lost if the calibration fails.
+        let progress = InitProgress {
+            pending_coins: coins.iter().map(|coin| coin.object_ref.0).collect(),
+            ..progress
+        };
+        let _ = storage
+            .set_init_progress(Some(&progress))
+            .await
+            .tap_err(|err| error!("Failed to save the coin init progress: {:?}", err));
         let total_coin_count = Arc::new(AtomicUsize::new(coins.len()));
-        let rgp = iota_client.get_reference_gas_price().await;
-        let gas_cost_per_object = iota_client
-            .calibrate_gas_cost_per_object(sponsor_address, &coins[0])
-            .await;
+        let calibration = async {
+            let rgp = iota_client.get_reference_gas_price().await?;
+            let gas_cost_per_object = iota_client
+                .calibrate_gas_cost_per_object(sponsor_address, &coins[0])
+                .await?;
+            anyhow::Ok((rgp, gas_cost_per_object))
+        };
+        let (rgp, gas_cost_per_object) = match calibration.await {
+            Ok(calibration) => calibration,
+            Err(err) => {
+                error!("Failed to calibrate new coin initialization: {:?}", err);
+                return;
+            }
+        };
         info!("Calibrated gas cost per object: {:?}", gas_cost_per_object);
-        let result = Self::split_gas_coins(
+        Self::split_gas_coins(
             coins,
             CoinSplitEnv {
-                target_init_coin_balance,
+                target_init_coin_balance: progress.target_init_balance,
                 gas_cost_per_object,
                 signer: signer.clone(),
                 sponsor_address,
                 iota_client,
+                storage: storage.clone(),
                 task_queue: Default::default(),
                 total_coin_count,
+                progress: Arc::new(Mutex::new(progress)),
                 rgp,
             },
         )
         .await;
-        for chunk in result.chunks(5000) {
-            storage.add_new_coins(chunk.to_vec()).await.unwrap();
+        let _ = storage
+            .set_init_progress(None)
+            .await
+            .tap_err(|err| error!("Failed to clear the coin init progress: {:?}", err));
+        // The pool is only initialized once all the coins were split and added, so that an
+        // interrupted first initialization scans the coins of the sponsor again.
+        if is_first_init {
+            storage.set_initialized().await.unwrap();
         }
-        storage.release_init_lock().await.unwrap();
         info!(
             "New coin initialization took {:?}s",
             start.elapsed().as_secs()
         );
     }
 
-    async fn split_gas_coins(coins: Vec<GasCoin>, env: CoinSplitEnv) -> Vec<GasCoin> {
+    async fn split_gas_coins(coins: Vec<GasCoin>, env: CoinSplitEnv) {
         let total_balance: u64 = coins.iter().map(|c| c.balance).sum();
         info!(
             "Splitting {} coins with total balance of {} into smaller coins with target balance of {}. This will result in close to {} coins",
@@ -309,24 +668,34 @@ impl GasStationInitializer {
             env.target_init_coin_balance,
             total_balance / env.target_init_coin_balance,
         );
+        // The coins are added to the pool as soon as they are split off.
         let mut result = vec![];
+        env.progress.lock().pending_coins.clear();
         for coin in coins {
             result.extend(env.enqueue_task(coin));
         }
+        let progress = env.progress.lock().clone();
+        env.save_progress(&progress).await;
+        let mut coin_count = result.len();
+        let mut new_total_balance: u64 = result.iter().map(|c| c.balance).sum();
+        for chunk in result.chunks(5000) {
+            env.storage.add_new_coins(chunk.to_vec()).await.unwrap();
+        }
         loop {
-            let Some(task) = env.task_queue.lock().pop_front() else {
+            let Some((object_id, task)) = env.task_queue.lock().pop_front() else {
                 break;
             };
-            result.extend(task.await.unwrap());
+            let coins = task.await.unwrap();
+            coin_count += coins.len();
+            new_total_balance += coins.iter().map(|c| c.balance).sum::<u64>();
+            env.complete_task(object_id, coins).await;
         }
-        let new_total_balance: u64 = result.iter().map(|c| c.balance).sum();
         info!(
             "Splitting finished. Got {} coins. New total balance: {}. Spent {} gas in total",
-            result.len(),
+            coin_count,
             new_total_balance,
-            total_balance - new_total_balance
+            total_balance.saturating_sub(new_total_balance)
         );
-        result
     }
 }
 
@@ -337,6 +706,7 @@ mod tests {
         GasStationInitializer, NEW_COIN_BALANCE_FACTOR_THRESHOLD,
     };
     use crate::iota_client::IotaClient;
+    use crate::leader_election::Leadership;
     use crate::storage::connect_storage_for_testing;
     use crate::test_env::start_iota_cluster;
     use iota_types::gas_coin::NANOS_PER_IOTA;
@@ -356,8 +726,11 @@ mod tests {
             CoinInitConfig {
                 target_init_balance: NANOS_PER_IOTA,
                 refresh_interval_sec: 200,
+                faucet_top_up: None,
+                resplit_balance_factor: None,
             },
             signer,
+            Leadership::new_for_testing(),
         )
         .await;
         assert!(storage.get_available_coin_count().await.unwrap() > 900);
@@ -377,8 +750,11 @@ mod tests {
             CoinInitConfig {
                 target_init_balance,
                 refresh_interval_sec: 200,
+                faucet_top_up: None,
+                resplit_balance_factor: None,
             },
             signer,
+            Leadership::new_for_testing(),
         )
         .await;
         assert!(storage.get_available_coin_count().await.unwrap() > 800);
@@ -398,8 +774,11 @@ mod tests {
             CoinInitConfig {
                 target_init_balance: NANOS_PER_IOTA,
                 refresh_interval_sec: 1,
+                faucet_top_up: None,
+                resplit_balance_factor: None,
             },
             signer,
+            Leadership::new_for_testing(),
         )
         .await;
         assert!(storage.is_initialized().await.unwrap());
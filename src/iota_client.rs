@@ -1,51 +1,383 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::rpc::rpc_types::ExecuteTransactionRequestType;
+use crate::circuit_breaker::{is_circuit_open, CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use crate::metrics::IotaClientMetrics;
+use crate::retry_policy::RetryPolicy;
+use crate::retry_with_max_attempts;
+use crate::rpc::rpc_types::{ExecuteTransactionRequestType, TransactionIntent};
 use crate::types::GasCoin;
-use crate::{retry_forever, retry_with_max_attempts};
+use anyhow::{bail, Context};
+use futures_util::future::try_join_all;
 use futures_util::stream::FuturesUnordered;
 use futures_util::StreamExt;
 use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
 use iota_json_rpc_types::{
     IotaData, IotaObjectDataOptions, IotaObjectResponse, IotaTransactionBlockEffects,
-    IotaTransactionBlockResponseOptions,
+    IotaTransactionBlockResponse, IotaTransactionBlockResponseOptions,
 };
 use iota_sdk::IotaClientBuilder;
 use iota_types::base_types::{IotaAddress, ObjectID, ObjectRef};
 use iota_types::coin::{PAY_MODULE_NAME, PAY_SPLIT_N_FUNC_NAME};
+use iota_types::digests::TransactionDigest;
+use iota_types::gas::GasCostSummary;
 use iota_types::gas_coin::GAS;
 use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use iota_types::transaction::{
-    Argument, ObjectArg, ProgrammableTransaction, Transaction, TransactionKind,
+    Argument, ObjectArg, ProgrammableTransaction, SenderSignedData, Transaction, TransactionKind,
 };
-use iota_types::IOTA_FRAMEWORK_PACKAGE_ID;
+use iota_types::{TypeTag, IOTA_FRAMEWORK_PACKAGE_ID};
 use itertools::Itertools;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tap::TapFallible;
-use tracing::{debug, info};
+use tracing::{debug, error, info, instrument, warn};
+
+const FULLNODE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_REFERENCE_GAS_PRICE_TTL: Duration = Duration::from_secs(60);
+/// How often the background task checks whether the cached reference gas price must be refreshed.
+const REFERENCE_GAS_PRICE_REFRESH_TICK: Duration = Duration::from_secs(1);
+/// The cached reference gas price is refreshed this long before it expires, so that requests
+/// never have to wait for it.
+const REFERENCE_GAS_PRICE_REFRESH_AHEAD: Duration = Duration::from_secs(2);
+/// An epoch change can lag behind the scheduled end of the epoch. Until it happens, the reference
+/// gas price is re-queried at this interval.
+const EPOCH_CHANGE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How long an execution with `WaitForLocalExecution` waits at most for the fullnode to know the
+/// transaction.
+pub const DEFAULT_LOCAL_EXECUTION_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the fullnode is asked for a transaction it doesn't know yet.
+const LOCAL_EXECUTION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How requests are routed when multiple fullnodes are configured.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FullnodeRouting {
+    #[default]
+    /// Always use the first healthy fullnode, in the configured order.
+    Failover,
+    /// Use the healthy fullnode that answered the last health check the fastest.
+    LowestLatency,
+}
+
+#[derive(Clone, Copy)]
+struct CachedReferenceGasPrice {
+    price: u64,
+    epoch: u64,
+    updated_at: Instant,
+    expires_at: Instant,
+}
+
+type ReferenceGasPriceCache = RwLock<Option<CachedReferenceGasPrice>>;
+
+struct FullnodeEndpoint {
+    url: String,
+    client: iota_sdk::IotaClient,
+    healthy: AtomicBool,
+    latency_us: AtomicU64,
+}
 
 #[derive(Clone)]
 pub struct IotaClient {
-    iota_client: iota_sdk::IotaClient,
+    endpoints: Arc<Vec<FullnodeEndpoint>>,
+    routing: FullnodeRouting,
+    retry_policy: RetryPolicy,
+    // Shared by all the clones, the reference gas price only changes once per epoch.
+    reference_gas_price: Arc<ReferenceGasPriceCache>,
+    reference_gas_price_ttl: Duration,
+    local_execution_timeout: Duration,
+    circuit_breaker: Arc<CircuitBreaker>,
+    metrics: Arc<IotaClientMetrics>,
 }
 
 impl IotaClient {
     pub async fn new(fullnode_url: &str, basic_auth: Option<(String, String)>) -> Self {
-        let mut iota_client_builder = IotaClientBuilder::default().max_concurrent_requests(100000);
-        if let Some((username, password)) = basic_auth {
-            iota_client_builder = iota_client_builder.basic_auth(username, password);
+        Self::new_with_failover(
+            &[fullnode_url.to_string()],
+            basic_auth,
+            FullnodeRouting::default(),
+        )
+        .await
+    }
+
+    /// Connects to all the given fullnodes. Requests go to a single healthy fullnode picked
+    /// according to `routing`, and a background task keeps checking the health of every fullnode.
+    pub async fn new_with_failover(
+        fullnode_urls: &[String],
+        basic_auth: Option<(String, String)>,
+        routing: FullnodeRouting,
+    ) -> Self {
+        let mut endpoints = vec![];
+        for url in fullnode_urls {
+            let mut iota_client_builder =
+                IotaClientBuilder::default().max_concurrent_requests(100000);
+            if let Some((username, password)) = basic_auth.clone() {
+                iota_client_builder = iota_client_builder.basic_auth(username, password);
+            }
+            match iota_client_builder.build(url).await {
+                Ok(client) => endpoints.push(FullnodeEndpoint {
+                    url: url.clone(),
+                    client,
+                    healthy: AtomicBool::new(true),
+                    latency_us: AtomicU64::new(0),
+                }),
+                // A single fullnode must be reachable, as there is nothing to fail over to.
+                Err(err) if fullnode_urls.len() == 1 => {
+                    panic!("Failed to connect to fullnode {}: {:?}", url, err)
+                }
+                Err(err) => error!("Failed to connect to fullnode {}: {:?}", url, err),
+            }
+        }
+        assert!(!endpoints.is_empty(), "Unable to connect to any fullnode");
+        let endpoints = Arc::new(endpoints);
+        if endpoints.len() > 1 {
+            tokio::spawn(Self::run_health_checks(Arc::downgrade(&endpoints)));
+        }
+        Self {
+            endpoints,
+            routing,
+            retry_policy: RetryPolicy::default(),
+            reference_gas_price: Default::default(),
+            reference_gas_price_ttl: DEFAULT_REFERENCE_GAS_PRICE_TTL,
+            local_execution_timeout: DEFAULT_LOCAL_EXECUTION_TIMEOUT,
+            circuit_breaker: Arc::new(CircuitBreaker::new(
+                "fullnode",
+                CircuitBreakerConfig::default(),
+            )),
+            metrics: IotaClientMetrics::new_for_testing(),
+        }
+    }
+
+    /// Sets the policy used to retry coin queries, gas price fetches and object refreshes.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how long a cached reference gas price is used at most. It is also refreshed as soon
+    /// as the epoch changes.
+    pub fn with_reference_gas_price_ttl(mut self, ttl: Duration) -> Self {
+        self.reference_gas_price_ttl = ttl;
+        self
+    }
+
+    /// Sets how long an execution with `WaitForLocalExecution` waits at most for the fullnodes to
+    /// know the transaction.
+    pub fn with_local_execution_timeout(mut self, timeout: Duration) -> Self {
+        self.local_execution_timeout = timeout;
+        self
+    }
+
+    /// Sets after how many consecutive failures requests to the fullnode fail fast, and for how
+    /// long.
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Arc::new(CircuitBreaker::new("fullnode", config));
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<IotaClientMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Starts a background task that refreshes the cached reference gas price before it expires,
+    /// so that `get_reference_gas_price` never has to query the fullnode. Stops once all the
+    /// clients are gone.
+    pub fn start_reference_gas_price_refresh(&self) {
+        tokio::spawn(Self::run_reference_gas_price_refresh(
+            Arc::downgrade(&self.endpoints),
+            self.routing,
+            Arc::downgrade(&self.reference_gas_price),
+            self.reference_gas_price_ttl,
+            self.circuit_breaker.clone(),
+            self.metrics.clone(),
+        ));
+    }
+
+    async fn run_reference_gas_price_refresh(
+        endpoints: Weak<Vec<FullnodeEndpoint>>,
+        routing: FullnodeRouting,
+        cache: Weak<ReferenceGasPriceCache>,
+        ttl: Duration,
+        circuit_breaker: Arc<CircuitBreaker>,
+        metrics: Arc<IotaClientMetrics>,
+    ) {
+        loop {
+            tokio::time::sleep(REFERENCE_GAS_PRICE_REFRESH_TICK).await;
+            let (Some(endpoints), Some(cache)) = (endpoints.upgrade(), cache.upgrade()) else {
+                break;
+            };
+            let cached = *cache.read();
+            let refresh_due = cached.map_or(true, |cached| {
+                Instant::now() + REFERENCE_GAS_PRICE_REFRESH_AHEAD >= cached.expires_at
+            });
+            if refresh_due {
+                let client = current_client(&endpoints, routing);
+                let request = fetch_reference_gas_price(client, ttl);
+                match guarded(&circuit_breaker, &metrics, true, request).await {
+                    Ok(fetched) => {
+                        if cached.is_some_and(|cached| cached.epoch != fetched.epoch) {
+                            info!(
+                                "Reference gas price of epoch {} is {}",
+                                fetched.epoch, fetched.price
+                            );
+                        }
+                        *cache.write() = Some(fetched);
+                    }
+                    Err(err) => warn!("Failed to refresh reference gas price: {:?}", err),
+                }
+            }
+            if let Some(cached) = *cache.read() {
+                metrics
+                    .reference_gas_price_cache_age_sec
+                    .set(cached.updated_at.elapsed().as_secs() as i64);
+            }
         }
-        let iota_client = iota_client_builder.build(fullnode_url).await.unwrap();
-        Self { iota_client }
+    }
+
+    /// Periodically measures the latency of every fullnode. Stops once all the clients are gone.
+    async fn run_health_checks(endpoints: Weak<Vec<FullnodeEndpoint>>) {
+        loop {
+            tokio::time::sleep(FULLNODE_HEALTH_CHECK_INTERVAL).await;
+            let Some(endpoints) = endpoints.upgrade() else {
+                break;
+            };
+            for endpoint in endpoints.iter() {
+                let start = Instant::now();
+                let result = tokio::time::timeout(
+                    FULLNODE_HEALTH_CHECK_INTERVAL,
+                    endpoint
+                        .client
+                        .read_api()
+                        .get_latest_checkpoint_sequence_number(),
+                )
+                .await;
+                let healthy = matches!(result, Ok(Ok(_)));
+                if healthy {
+                    endpoint
+                        .latency_us
+                        .store(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+                }
+                if endpoint.healthy.swap(healthy, Ordering::Relaxed) != healthy {
+                    if healthy {
+                        info!("Fullnode {} is healthy again", endpoint.url);
+                    } else {
+                        warn!("Fullnode {} is marked as unhealthy", endpoint.url);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The client of the fullnode that requests should currently go to.
+    fn client(&self) -> &iota_sdk::IotaClient {
+        current_client(&self.endpoints, self.routing)
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker.state()
+    }
+
+    /// Fails with a `CircuitOpenError` while requests to the fullnode fail fast.
+    pub fn check_circuit_breaker(&self) -> anyhow::Result<()> {
+        Ok(self.circuit_breaker.check()?)
+    }
+
+    /// Sends a request whose failure means that the fullnode is unhealthy.
+    async fn guarded<T, E: Into<anyhow::Error>>(
+        &self,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> anyhow::Result<T> {
+        guarded(&self.circuit_breaker, &self.metrics, true, request).await
+    }
+
+    /// Sends a request about a transaction provided by the user. It fails fast while the circuit
+    /// is open, but its failures are not counted, since an invalid transaction must not open it.
+    async fn guarded_user_request<T, E: Into<anyhow::Error>>(
+        &self,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> anyhow::Result<T> {
+        guarded(&self.circuit_breaker, &self.metrics, false, request).await
+    }
+
+    /// Retries according to the retry policy, but not while the circuit is open.
+    async fn retry<T, F, Fut>(&self, action: F) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        self.retry_policy
+            .retry_if(action, |err| !is_circuit_open(err))
+            .await
+    }
+
+    pub async fn get_chain_identifier(&self) -> anyhow::Result<String> {
+        self.retry(|| async {
+            self.guarded(self.client().read_api().get_chain_identifier())
+                .await
+                .tap_err(|err| debug!("Failed to get chain identifier: {:?}", err))
+        })
+        .await
+        .context("Failed to get chain identifier")
+    }
+
+    /// Fails if any of the fullnodes is not on the network of the expected chain identifier, or
+    /// if they are not all on the same network, so that the Gas Station never sponsors
+    /// transactions on another network than the configured one, even after a failover. The chain
+    /// identifiers are recorded in the metrics either way.
+    pub async fn verify_chain_identifier(
+        &self,
+        expected_chain_identifier: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let mut verified: Option<String> = None;
+        for endpoint in self.endpoints.iter() {
+            let chain_identifier = self
+                .retry(|| async {
+                    endpoint
+                        .client
+                        .read_api()
+                        .get_chain_identifier()
+                        .await
+                        .map_err(anyhow::Error::from)
+                        .tap_err(|err| debug!("Failed to get chain identifier: {:?}", err))
+                })
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to get chain identifier of fullnode {}",
+                        endpoint.url
+                    )
+                })?;
+            self.metrics
+                .fullnode_chain_identifier
+                .with_label_values(&[&chain_identifier])
+                .set(1);
+            let expected = expected_chain_identifier.or(verified.as_deref());
+            if let Some(expected) = expected {
+                if !expected.eq_ignore_ascii_case(&chain_identifier) {
+                    bail!(
+                        "The fullnode {} is on the network with chain identifier {}, but {} is expected",
+                        endpoint.url,
+                        chain_identifier,
+                        expected
+                    );
+                }
+            }
+            verified.get_or_insert(chain_identifier);
+        }
+        verified.context("No fullnode to verify")
     }
 
     pub async fn get_all_owned_iota_coins_above_balance_threshold(
         &self,
         address: IotaAddress,
         balance_threshold: u64,
-    ) -> Vec<GasCoin> {
+    ) -> anyhow::Result<Vec<GasCoin>> {
         info!(
             "Querying all gas coins owned by sponsor address {} that has at least {} balance",
             address, balance_threshold
@@ -53,14 +385,18 @@ impl IotaClient {
         let mut cursor = None;
         let mut coins = Vec::new();
         loop {
-            let page = retry_forever!(async {
-                self.iota_client
-                    .coin_read_api()
-                    .get_coins(address, None, cursor, None)
+            let page = self
+                .retry(|| async {
+                    self.guarded(
+                        self.client()
+                            .coin_read_api()
+                            .get_coins(address, None, cursor, None),
+                    )
                     .await
                     .tap_err(|err| debug!("Failed to get owned gas coins: {:?}", err))
-            })
-            .unwrap();
+                })
+                .await
+                .context("Failed to get owned gas coins")?;
             for coin in page.data {
                 if coin.balance >= balance_threshold {
                     coins.push(GasCoin {
@@ -75,59 +411,83 @@ impl IotaClient {
                 break;
             }
         }
-        coins
+        Ok(coins)
+    }
+
+    /// Returns the cached reference gas price, and only queries the fullnode if it expired.
+    pub async fn get_reference_gas_price(&self) -> anyhow::Result<u64> {
+        Ok(self.get_cached_reference_gas_price().await?.price)
     }
 
-    pub async fn get_reference_gas_price(&self) -> u64 {
-        retry_forever!(async {
-            self.iota_client
-                .governance_api()
-                .get_reference_gas_price()
+    /// Returns the current epoch, which is cached along with the reference gas price.
+    pub async fn get_current_epoch(&self) -> anyhow::Result<u64> {
+        Ok(self.get_cached_reference_gas_price().await?.epoch)
+    }
+
+    async fn get_cached_reference_gas_price(&self) -> anyhow::Result<CachedReferenceGasPrice> {
+        if let Some(cached) = *self.reference_gas_price.read() {
+            if Instant::now() < cached.expires_at {
+                return Ok(cached);
+            }
+        }
+        let fetched = self
+            .retry(|| async {
+                self.guarded(fetch_reference_gas_price(
+                    self.client(),
+                    self.reference_gas_price_ttl,
+                ))
                 .await
                 .tap_err(|err| debug!("Failed to get reference gas price: {:?}", err))
-        })
-        .unwrap()
+            })
+            .await
+            .context("Failed to get reference gas price")?;
+        *self.reference_gas_price.write() = Some(fetched);
+        Ok(fetched)
     }
 
     pub async fn get_latest_gas_objects(
         &self,
         object_ids: impl IntoIterator<Item = ObjectID>,
-    ) -> HashMap<ObjectID, Option<GasCoin>> {
+    ) -> anyhow::Result<HashMap<ObjectID, Option<GasCoin>>> {
         let tasks: FuturesUnordered<_> = object_ids
             .into_iter()
             .chunks(50)
             .into_iter()
             .map(|chunk| {
                 let chunk: Vec<_> = chunk.collect();
-                let iota_client = self.iota_client.clone();
+                let client = self.clone();
                 tokio::spawn(async move {
-                    retry_forever!(async {
-                        let chunk = chunk.clone();
-                        let result = iota_client
-                            .clone()
-                            .read_api()
-                            .multi_get_object_with_options(
-                                chunk.clone(),
-                                IotaObjectDataOptions::default().with_bcs(),
-                            )
-                            .await
-                            .map_err(anyhow::Error::from)?;
-                        if result.len() != chunk.len() {
-                            anyhow::bail!(
-                                "Unable to get all gas coins, got {} out of {}",
-                                result.len(),
-                                chunk.len()
-                            );
-                        }
-                        Ok(chunk.into_iter().zip(result).collect::<Vec<_>>())
-                    })
-                    .unwrap()
+                    client
+                        .retry(|| async {
+                            let chunk = chunk.clone();
+                            let result = client
+                                .guarded(client.client().read_api().multi_get_object_with_options(
+                                    chunk.clone(),
+                                    IotaObjectDataOptions::default().with_bcs(),
+                                ))
+                                .await?;
+                            if result.len() != chunk.len() {
+                                anyhow::bail!(
+                                    "Unable to get all gas coins, got {} out of {}",
+                                    result.len(),
+                                    chunk.len()
+                                );
+                            }
+                            Ok(chunk.into_iter().zip(result).collect::<Vec<_>>())
+                        })
+                        .await
                 })
             })
             .collect();
-        let objects: Vec<_> = tasks.collect().await;
-        let objects: Vec<_> = objects.into_iter().flat_map(|r| r.unwrap()).collect();
-        objects
+        let mut objects = vec![];
+        for result in tasks.collect::<Vec<_>>().await {
+            objects.extend(
+                result
+                    .unwrap()
+                    .context("Failed to get latest gas objects")?,
+            );
+        }
+        Ok(objects
             .into_iter()
             .map(|(id, response)| {
                 let object = match Self::try_get_iota_coin_balance(&response) {
@@ -142,7 +502,7 @@ impl IotaClient {
                 };
                 (id, object)
             })
-            .collect()
+            .collect())
     }
 
     pub fn construct_coin_split_pt(
@@ -165,7 +525,7 @@ impl IotaClient {
         &self,
         sponsor_address: IotaAddress,
         gas_coin: &GasCoin,
-    ) -> u64 {
+    ) -> anyhow::Result<u64> {
         const SPLIT_COUNT: u64 = 500;
         let mut pt_builder = ProgrammableTransactionBuilder::new();
         let object_arg = pt_builder
@@ -180,22 +540,89 @@ impl IotaClient {
             vec![object_arg, pure_arg],
         );
         let pt = pt_builder.finish();
-        let response = retry_forever!(async {
-            self.iota_client
-                .read_api()
-                .dev_inspect_transaction_block(
+        let response = self
+            .retry(|| async {
+                self.guarded(self.client().read_api().dev_inspect_transaction_block(
                     sponsor_address,
                     TransactionKind::ProgrammableTransaction(pt.clone()),
                     None,
                     None,
                     None,
-                )
+                ))
                 .await
-        })
-        .unwrap();
+            })
+            .await
+            .context("Failed to calibrate gas cost per object")?;
         let gas_used = response.effects.gas_cost_summary().gas_used();
         // Multiply by 2 to be conservative and resilient to precision loss.
-        gas_used / SPLIT_COUNT * 2
+        Ok(gas_used / SPLIT_COUNT * 2)
+    }
+
+    /// Dry-runs the given transaction kind on behalf of `sender` and returns the gas cost
+    /// summary reported by the fullnode.
+    pub async fn estimate_gas_cost(
+        &self,
+        sender: IotaAddress,
+        tx_kind: TransactionKind,
+    ) -> anyhow::Result<GasCostSummary> {
+        let response = self
+            .guarded_user_request(
+                self.client()
+                    .read_api()
+                    .dev_inspect_transaction_block(sender, tx_kind, None, None, None),
+            )
+            .await
+            .tap_err(|err| debug!("Failed to dev inspect transaction: {:?}", err))?;
+        if let Some(error) = response.error {
+            anyhow::bail!("Transaction dry run failed: {}", error);
+        }
+        Ok(response.effects.gas_cost_summary().clone())
+    }
+
+    /// Builds the programmable transaction of an intent, resolving its objects and the arguments
+    /// of its Move call with the fullnode.
+    pub async fn build_intent(
+        &self,
+        intent: &TransactionIntent,
+    ) -> anyhow::Result<ProgrammableTransaction> {
+        let builder = self.client().transaction_builder();
+        let mut pt_builder = ProgrammableTransactionBuilder::new();
+        match intent {
+            TransactionIntent::MoveCall {
+                package,
+                module,
+                function,
+                type_arguments,
+                arguments,
+            } => {
+                self.guarded_user_request(builder.single_move_call(
+                    &mut pt_builder,
+                    *package,
+                    module,
+                    function,
+                    type_arguments.clone(),
+                    arguments.clone(),
+                ))
+                .await?;
+            }
+            TransactionIntent::TransferObjects {
+                object_ids,
+                recipient,
+            } => {
+                let object_refs = try_join_all(
+                    object_ids
+                        .iter()
+                        .map(|id| self.guarded_user_request(builder.get_object_ref(*id))),
+                )
+                .await?;
+                let objects = object_refs
+                    .into_iter()
+                    .map(|object_ref| pt_builder.obj(ObjectArg::ImmOrOwnedObject(object_ref)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                pt_builder.transfer_args(*recipient, objects);
+            }
+        }
+        Ok(pt_builder.finish())
     }
 
     pub async fn execute_transaction(
@@ -204,23 +631,52 @@ impl IotaClient {
         max_attempts: usize,
         request_type: Option<ExecuteTransactionRequestType>,
     ) -> anyhow::Result<IotaTransactionBlockEffects> {
+        self.execute_transaction_with_options(
+            tx,
+            max_attempts,
+            request_type,
+            IotaTransactionBlockResponseOptions::new(),
+        )
+        .await?
+        .effects
+        .ok_or_else(|| anyhow::anyhow!("No effects"))
+    }
+
+    /// Executes the transaction and returns the response with the effects, along with what is
+    /// requested by `options`. The request type is passed on to the fullnode, waiting for the
+    /// local execution is left to `wait_for_local_execution`, so that the gas coins can be
+    /// released first.
+    #[instrument(skip_all, fields(digest = %tx.digest()))]
+    pub async fn execute_transaction_with_options(
+        &self,
+        tx: Transaction,
+        max_attempts: usize,
+        request_type: Option<ExecuteTransactionRequestType>,
+        options: IotaTransactionBlockResponseOptions,
+    ) -> anyhow::Result<IotaTransactionBlockResponse> {
+        let options = options.with_effects();
         let digest = *tx.digest();
         debug!(?digest, "Executing transaction: {:?}", tx);
         let request_type =
             request_type.unwrap_or(ExecuteTransactionRequestType::WaitForEffectsCert);
+        self.check_circuit_breaker()?;
         let response = retry_with_max_attempts!(
             async {
-                self.iota_client
-                    .quorum_driver_api()
-                    .execute_transaction_block(
+                self.guarded_user_request(
+                    self.client().quorum_driver_api().execute_transaction_block(
                         tx.clone(),
-                        IotaTransactionBlockResponseOptions::new().with_effects(),
+                        options.clone(),
                         request_type.clone(),
-                    )
-                    .await
-                    .tap_err(|err| debug!(?digest, "execute_transaction error: {:?}", err))
-                    .map_err(anyhow::Error::from)
-                    .and_then(|r| r.effects.ok_or_else(|| anyhow::anyhow!("No effects")))
+                    ),
+                )
+                .await
+                .tap_err(|err| debug!(?digest, "execute_transaction error: {:?}", err))
+                .and_then(|r| {
+                    if r.effects.is_none() {
+                        anyhow::bail!("No effects");
+                    }
+                    Ok(r)
+                })
             },
             max_attempts
         );
@@ -228,11 +684,139 @@ impl IotaClient {
         response
     }
 
+    /// Waits until the fullnode the requests are routed to knows the executed transaction, so
+    /// that the reads of the client that follow see it. Returns whether it did before the
+    /// timeout. Gives up early when the fullnode can't be reached.
+    pub async fn wait_for_local_execution(&self, digest: TransactionDigest) -> bool {
+        let wait = async {
+            loop {
+                // An unknown transaction is omitted from the response rather than an error, so
+                // polling doesn't count against the circuit breaker.
+                let responses = self
+                    .retry(|| async {
+                        self.guarded(
+                            self.client()
+                                .read_api()
+                                .multi_get_transactions_with_options(
+                                    vec![digest],
+                                    IotaTransactionBlockResponseOptions::new(),
+                                ),
+                        )
+                        .await
+                    })
+                    .await;
+                match responses {
+                    Ok(responses) if responses.iter().any(|r| r.digest == digest) => return true,
+                    Ok(_) => tokio::time::sleep(LOCAL_EXECUTION_POLL_INTERVAL).await,
+                    Err(err) => {
+                        debug!(?digest, "Failed to get transaction: {:?}", err);
+                        return false;
+                    }
+                }
+            }
+        };
+        let confirmed = tokio::time::timeout(self.local_execution_timeout, wait)
+            .await
+            .unwrap_or(false);
+        if !confirmed {
+            warn!(
+                ?digest,
+                "The fullnode didn't know the transaction within {:?}",
+                self.local_execution_timeout
+            );
+        }
+        confirmed
+    }
+
+    /// Returns the gas used by an executed transaction, or `None` if the fullnode doesn't know the
+    /// transaction.
+    pub async fn get_transaction_gas_used(
+        &self,
+        digest: TransactionDigest,
+    ) -> anyhow::Result<Option<u64>> {
+        let responses = self
+            .retry(|| async {
+                self.guarded(
+                    self.client()
+                        .read_api()
+                        .multi_get_transactions_with_options(
+                            vec![digest],
+                            IotaTransactionBlockResponseOptions::new().with_effects(),
+                        ),
+                )
+                .await
+                .tap_err(|err| debug!(?digest, "Failed to get transaction: {:?}", err))
+            })
+            .await
+            .context("Failed to get transaction")?;
+        Ok(responses
+            .into_iter()
+            .find(|response| response.digest == digest)
+            .and_then(|response| response.effects)
+            .map(|effects| effects.gas_cost_summary().gas_used()))
+    }
+
+    /// Returns the transactions with their signatures, as submitted to the network. The
+    /// transactions the fullnode doesn't know are omitted.
+    pub async fn get_sender_signed_transactions(
+        &self,
+        digests: Vec<TransactionDigest>,
+    ) -> anyhow::Result<HashMap<TransactionDigest, SenderSignedData>> {
+        let responses = self
+            .retry(|| async {
+                self.guarded(
+                    self.client()
+                        .read_api()
+                        .multi_get_transactions_with_options(
+                            digests.clone(),
+                            IotaTransactionBlockResponseOptions::new().with_raw_input(),
+                        ),
+                )
+                .await
+            })
+            .await
+            .context("Failed to get the transactions")?;
+        responses
+            .into_iter()
+            .filter(|response| !response.raw_transaction.is_empty())
+            .map(|response| {
+                let data = bcs::from_bytes(&response.raw_transaction)
+                    .with_context(|| format!("Invalid transaction {}", response.digest))?;
+                Ok((response.digest, data))
+            })
+            .collect()
+    }
+
+    /// Returns the types of the objects. The objects that don't exist and the packages are omitted.
+    pub async fn get_object_types(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> anyhow::Result<HashMap<ObjectID, TypeTag>> {
+        let responses = self
+            .retry(|| async {
+                self.guarded(self.client().read_api().multi_get_object_with_options(
+                    object_ids.clone(),
+                    IotaObjectDataOptions::new().with_type(),
+                ))
+                .await
+            })
+            .await
+            .context("Failed to get the object types")?;
+        Ok(responses
+            .into_iter()
+            .filter_map(|response| {
+                let data = response.data?;
+                let struct_tag = data.type_?.try_into().ok()?;
+                Some((data.object_id, TypeTag::Struct(Box::new(struct_tag))))
+            })
+            .collect())
+    }
+
     /// Wait for a known valid object version to be available on the fullnode.
     pub async fn wait_for_object(&self, obj_ref: ObjectRef) {
         loop {
             let response = self
-                .iota_client
+                .client()
                 .read_api()
                 .get_object_with_options(obj_ref.0, IotaObjectDataOptions::default())
                 .await;
@@ -262,3 +846,140 @@ impl IotaClient {
         })
     }
 }
+
+/// The client of the fullnode that requests should currently go to.
+fn current_client(
+    endpoints: &[FullnodeEndpoint],
+    routing: FullnodeRouting,
+) -> &iota_sdk::IotaClient {
+    let status: Vec<_> = endpoints
+        .iter()
+        .map(|endpoint| {
+            (
+                endpoint.healthy.load(Ordering::Relaxed),
+                endpoint.latency_us.load(Ordering::Relaxed),
+            )
+        })
+        .collect();
+    &endpoints[select_endpoint(routing, &status)].client
+}
+
+/// Sends a request unless the circuit is open, and records its outcome in the circuit breaker.
+async fn guarded<T, E: Into<anyhow::Error>>(
+    circuit_breaker: &CircuitBreaker,
+    metrics: &IotaClientMetrics,
+    count_failure: bool,
+    request: impl Future<Output = Result<T, E>>,
+) -> anyhow::Result<T> {
+    circuit_breaker.check()?;
+    let result = request.await.map_err(Into::into);
+    match &result {
+        Ok(_) => circuit_breaker.record_success(),
+        Err(_) if count_failure => circuit_breaker.record_failure(),
+        Err(_) => {}
+    }
+    metrics
+        .fullnode_circuit_state
+        .set(circuit_breaker.state().as_metric());
+    result
+}
+
+async fn fetch_reference_gas_price(
+    client: &iota_sdk::IotaClient,
+    ttl: Duration,
+) -> anyhow::Result<CachedReferenceGasPrice> {
+    let system_state = client
+        .governance_api()
+        .get_latest_iota_system_state()
+        .await?;
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    let epoch_end_ms = system_state.epoch_start_timestamp_ms + system_state.epoch_duration_ms;
+    let until_epoch_end = Duration::from_millis(epoch_end_ms.saturating_sub(now_ms));
+    let now = Instant::now();
+    Ok(CachedReferenceGasPrice {
+        price: system_state.reference_gas_price,
+        epoch: system_state.epoch,
+        updated_at: now,
+        expires_at: now + reference_gas_price_validity(ttl, until_epoch_end),
+    })
+}
+
+/// How long a freshly queried reference gas price can be used: at most `ttl`, and no longer than
+/// the current epoch.
+fn reference_gas_price_validity(ttl: Duration, until_epoch_end: Duration) -> Duration {
+    ttl.min(until_epoch_end.max(EPOCH_CHANGE_POLL_INTERVAL))
+}
+
+/// Picks the fullnode to use given the health and latency of each of them. Falls back to the
+/// first fullnode if none of them is healthy.
+fn select_endpoint(routing: FullnodeRouting, status: &[(bool, u64)]) -> usize {
+    let mut healthy = status
+        .iter()
+        .enumerate()
+        .filter(|(_, (healthy, _))| *healthy);
+    let selected = match routing {
+        FullnodeRouting::Failover => healthy.next(),
+        FullnodeRouting::LowestLatency => healthy.min_by_key(|(_, (_, latency))| *latency),
+    };
+    selected.map(|(index, _)| index).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verify_chain_identifier() {
+        let test_cluster = test_cluster::TestClusterBuilder::new().build().await;
+        let client = IotaClient::new(&test_cluster.fullnode_handle.rpc_url, None).await;
+        let chain_identifier = client.verify_chain_identifier(None).await.unwrap();
+        assert_eq!(
+            client
+                .verify_chain_identifier(Some(&chain_identifier))
+                .await
+                .unwrap(),
+            chain_identifier
+        );
+        assert_eq!(
+            client
+                .metrics
+                .fullnode_chain_identifier
+                .with_label_values(&[&chain_identifier])
+                .get(),
+            1
+        );
+        assert!(client
+            .verify_chain_identifier(Some("00000000"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_select_endpoint() {
+        let status = [(false, 10), (true, 30), (true, 20)];
+        assert_eq!(select_endpoint(FullnodeRouting::Failover, &status), 1);
+        assert_eq!(select_endpoint(FullnodeRouting::LowestLatency, &status), 2);
+
+        let status = [(false, 10), (false, 30)];
+        assert_eq!(select_endpoint(FullnodeRouting::Failover, &status), 0);
+        assert_eq!(select_endpoint(FullnodeRouting::LowestLatency, &status), 0);
+    }
+
+    #[test]
+    fn test_reference_gas_price_validity() {
+        let ttl = Duration::from_secs(60);
+        assert_eq!(
+            reference_gas_price_validity(ttl, Duration::from_secs(3600)),
+            ttl
+        );
+        assert_eq!(
+            reference_gas_price_validity(ttl, Duration::from_secs(30)),
+            Duration::from_secs(30)
+        );
+        // The epoch change is overdue.
+        assert_eq!(
+            reference_gas_price_validity(ttl, Duration::ZERO),
+            EPOCH_CHANGE_POLL_INTERVAL
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replicas sharing the same pool elect a leader through a lease in the storage. Only the leader
+//! initializes and splits the coins of the pool and sweeps the expired reservations, the other
+//! replicas take over once the leader stops renewing its lease.
+
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+const DEFAULT_LEASE_DURATION_SEC: u64 = 15;
+
+/// The leader renews its lease this many times per lease duration, so that a slow renewal
+/// doesn't make it lose the lease.
+const RENEWALS_PER_LEASE: u32 = 3;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LeaderElectionConfig {
+    /// How long the lease of the leader is valid without renewal. Another replica takes over at
+    /// most this long after the leader stopped.
+    pub lease_duration_sec: u64,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        Self {
+            lease_duration_sec: DEFAULT_LEASE_DURATION_SEC,
+        }
+    }
+}
+
+/// Whether this replica is currently the leader. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct Leadership {
+    is_leader: Arc<AtomicBool>,
+}
+
+impl Leadership {
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::Relaxed)
+    }
+
+    /// Always the leader, as a single replica.
+    #[cfg(test)]
+    pub fn new_for_testing() -> Self {
+        Self {
+            is_leader: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+pub struct LeaderElection {
+    leadership: Leadership,
+    _task_handle: JoinHandle<()>,
+    // This is always Some. It is None only after the drop method is called.
+    cancel_sender: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl Drop for LeaderElection {
+    fn drop(&mut self) {
+        let _ = self.cancel_sender.take().unwrap().send(());
+    }
+}
+
+impl LeaderElection {
+    /// Tries to acquire the lease once before returning, so that the leadership is known at
+    /// startup, and keeps renewing or trying to acquire it in the background.
+    pub async fn start(storage: Arc<dyn Storage>, config: LeaderElectionConfig) -> Self {
+        let hostname = hostname::get()
+            .map(|hostname| hostname.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let holder_id = format!("{}-{}", hostname, uuid::Uuid::new_v4());
+        let lease_duration = Duration::from_secs(config.lease_duration_sec);
+        let leadership = Leadership::default();
+        Self::renew(&storage, &holder_id, lease_duration, &leadership).await;
+        let (cancel_sender, cancel_receiver) = tokio::sync::oneshot::channel();
+        let _task_handle = tokio::spawn(Self::run(
+            storage,
+            holder_id,
+            lease_duration,
+            leadership.clone(),
+            cancel_receiver,
+        ));
+        Self {
+            leadership,
+            _task_handle,
+            cancel_sender: Some(cancel_sender),
+        }
+    }
+
+    pub fn leadership(&self) -> Leadership {
+        self.leadership.clone()
+    }
+
+    async fn run(
+        storage: Arc<dyn Storage>,
+        holder_id: String,
+        lease_duration: Duration,
+        leadership: Leadership,
+        mut cancel_receiver: tokio::sync::oneshot::Receiver<()>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(lease_duration / RENEWALS_PER_LEASE) => {}
+                _ = &mut cancel_receiver => {
+                    info!("Leader election task is cancelled");
+                    break;
+                }
+            }
+            Self::renew(&storage, &holder_id, lease_duration, &leadership).await;
+        }
+        if leadership.is_leader.swap(false, Ordering::Relaxed) {
+            // Let another replica take over without waiting for the lease to expire.
+            if let Err(err) = storage.release_leader_lease(&holder_id).await {
+                error!("Failed to release the leader lease: {:?}", err);
+            }
+        }
+    }
+
+    async fn renew(
+        storage: &Arc<dyn Storage>,
+        holder_id: &str,
+        lease_duration: Duration,
+        leadership: &Leadership,
+    ) {
+        let is_leader = storage
+            .acquire_leader_lease(holder_id, lease_duration.as_millis() as u64)
+            .await
+            .unwrap_or_else(|err| {
+                // Step down, the lease may expire before it can be renewed.
+                error!("Failed to renew the leader lease: {:?}", err);
+                false
+            });
+        let was_leader = leadership.is_leader.swap(is_leader, Ordering::Relaxed);
+        if is_leader && !was_leader {
+            info!("Became the leader of the pool as {}", holder_id);
+        } else if !is_leader && was_leader {
+            info!("No longer the leader of the pool");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::connect_storage_for_testing;
+    use iota_types::base_types::IotaAddress;
+
+    #[tokio::test]
+    async fn test_single_leader_among_replicas() {
+        let storage = connect_storage_for_testing(IotaAddress::random_for_testing_only()).await;
+        let config = LeaderElectionConfig {
+            lease_duration_sec: 1,
+        };
+        let first = LeaderElection::start(storage.clone(), config.clone()).await;
+        let second = LeaderElection::start(storage.clone(), config).await;
+        assert!(first.leadership().is_leader());
+        assert!(!second.leadership().is_leader());
+
+        // The leader keeps its lease while it's running
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(first.leadership().is_leader());
+        assert!(!second.leadership().is_leader());
+
+        // The other replica takes over once the leader stops
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(second.leadership().is_leader());
+    }
+}
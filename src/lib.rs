@@ -4,26 +4,44 @@
 
 pub mod access_controller;
 pub mod benchmarks;
+pub mod circuit_breaker;
 pub mod command;
 pub mod config;
+pub mod config_overrides;
+pub mod config_secrets;
+pub mod config_validation;
+pub mod config_reloader;
+pub mod dashboard;
 pub mod errors;
+pub mod faucet;
 pub mod gas_station;
 pub mod gas_station_initializer;
 pub mod iota_client;
+pub mod leader_election;
 pub mod logging;
 pub mod metrics;
+pub mod redis_connection;
+pub mod retry_policy;
 pub mod rpc;
+pub mod self_test;
+pub mod sponsor_migration;
 pub mod storage;
+pub mod storage_migration;
 pub mod tracker;
 
 #[cfg(test)]
 pub mod test_env;
+pub mod tx_log_replay;
 pub mod tx_signer;
 pub mod types;
+pub mod webhooks;
 
 pub const AUTH_ENV_NAME: &str = "GAS_STATION_AUTH";
+/// Secret still accepted for a grace period after the start, while rotating `GAS_STATION_AUTH`.
+pub const PREVIOUS_AUTH_ENV_NAME: &str = "GAS_STATION_AUTH_PREVIOUS";
 pub const TRANSACTION_LOGGING_ENV_NAME: &str = "TRANSACTIONS_LOGGING";
 pub const TRANSACTION_LOGGING_TARGET_NAME: &str = "transactions";
+pub const OTLP_ENDPOINT_ENV_NAME: &str = "OTLP_ENDPOINT";
 pub const GIT_REVISION: &str = {
     if let Some(revision) = option_env!("GIT_REVISION") {
         revision
@@ -0,0 +1,200 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use iota_types::base_types::IotaAddress;
+use iota_types::digests::TransactionDigest;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{TxLogRecord, TxLogSink};
+use crate::access_controller::decision::Decision;
+
+const DEFAULT_MAX_FILE_SIZE_BYTES: u64 = 100 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 5;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileTxLogSinkConfig {
+    pub path: PathBuf,
+    /// The file is rotated once it reaches this size.
+    #[serde(default = "default_max_file_size_bytes")]
+    pub max_file_size_bytes: u64,
+    /// Number of rotated files kept next to the current one, as `<path>.1` (the most recent)
+    /// up to `<path>.<max-files>`.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_max_file_size_bytes() -> u64 {
+    DEFAULT_MAX_FILE_SIZE_BYTES
+}
+
+fn default_max_files() -> usize {
+    DEFAULT_MAX_FILES
+}
+
+/// One line of the file transaction log.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTxLogLine {
+    pub timestamp: String,
+    pub digest: TransactionDigest,
+    pub sender: IotaAddress,
+    pub sponsor: IotaAddress,
+    pub gas_used: u64,
+    pub success: bool,
+    pub decision: Decision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl From<&TxLogRecord> for FileTxLogLine {
+    fn from(record: &TxLogRecord) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            digest: record.digest,
+            sender: record.sender,
+            sponsor: record.sponsor,
+            gas_used: record.gas_used,
+            success: record.success,
+            decision: record.decision.clone(),
+            request_id: record.request_id.clone(),
+        }
+    }
+}
+
+/// Appends one JSON line per executed transaction to a local file, rotated by size.
+pub struct FileTxLogSink {
+    config: FileTxLogSinkConfig,
+    file: Mutex<FileState>,
+}
+
+struct FileState {
+    file: File,
+    size: u64,
+}
+
+impl FileTxLogSink {
+    pub fn new(config: &FileTxLogSinkConfig) -> anyhow::Result<Self> {
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let file = open_for_append(&config.path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            config: config.clone(),
+            file: Mutex::new(FileState { file, size }),
+        })
+    }
+
+    fn write_line(&self, line: &str) -> anyhow::Result<()> {
+        let mut state = self.file.lock();
+        let len = line.len() as u64 + 1;
+        if state.size > 0 && state.size + len > self.config.max_file_size_bytes {
+            self.rotate(&mut state)?;
+        }
+        writeln!(state.file, "{}", line)
+            .with_context(|| format!("Failed to write to {}", self.config.path.display()))?;
+        state.size += len;
+        Ok(())
+    }
+
+    fn rotate(&self, state: &mut FileState) -> anyhow::Result<()> {
+        state.file.flush()?;
+        let path = &self.config.path;
+        if self.config.max_files == 0 {
+            fs::remove_file(path)?;
+        } else {
+            for index in (1..self.config.max_files).rev() {
+                let from = rotated_path(path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(path, index + 1))?;
+                }
+            }
+            fs::rename(path, rotated_path(path, 1))?;
+        }
+        state.file = open_for_append(path)?;
+        state.size = 0;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TxLogSink for FileTxLogSink {
+    async fn log(&self, record: &TxLogRecord) -> anyhow::Result<()> {
+        let line = serde_json::to_string(&FileTxLogLine::from(record))?;
+        self.write_line(&line)
+    }
+}
+
+fn open_for_append(path: &Path) -> anyhow::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", index));
+    PathBuf::from(rotated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("transactions.jsonl");
+        let sink = FileTxLogSink::new(&FileTxLogSinkConfig {
+            path: path.clone(),
+            max_file_size_bytes: 10,
+            max_files: 2,
+        })
+        .unwrap();
+
+        for line in ["line-1", "line-2", "line-3", "line-4"] {
+            sink.write_line(line).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line-4\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "line-3\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "line-2\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_appends_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FileTxLogSinkConfig {
+            path: dir.path().join("transactions.jsonl"),
+            max_file_size_bytes: DEFAULT_MAX_FILE_SIZE_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        };
+        FileTxLogSink::new(&config)
+            .unwrap()
+            .write_line("a")
+            .unwrap();
+        FileTxLogSink::new(&config)
+            .unwrap()
+            .write_line("b")
+            .unwrap();
+        assert_eq!(fs::read_to_string(&config.path).unwrap(), "a\nb\n");
+    }
+}
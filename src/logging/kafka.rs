@@ -0,0 +1,88 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use serde::{Deserialize, Serialize};
+
+use super::{TxLogMessage, TxLogRecord, TxLogSink};
+
+const DEFAULT_KAFKA_MESSAGE_TIMEOUT_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KafkaTxLogSinkConfig {
+    /// Comma separated list of `host:port` bootstrap brokers.
+    pub brokers: String,
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sasl: Option<KafkaSaslConfig>,
+    /// How long a record is retried before it is dropped.
+    #[serde(default = "default_kafka_message_timeout_ms")]
+    pub message_timeout_ms: u64,
+}
+
+/// SASL authentication, always over TLS.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KafkaSaslConfig {
+    /// E.g. `PLAIN`, `SCRAM-SHA-256` or `SCRAM-SHA-512`.
+    pub mechanism: String,
+    pub username: String,
+    pub password: String,
+}
+
+fn default_kafka_message_timeout_ms() -> u64 {
+    DEFAULT_KAFKA_MESSAGE_TIMEOUT_MS
+}
+
+/// Publishes one JSON record per executed transaction, keyed by the transaction digest.
+pub struct KafkaTxLogSink {
+    producer: FutureProducer,
+    topic: String,
+    message_timeout: Duration,
+}
+
+impl KafkaTxLogSink {
+    pub fn new(config: &KafkaTxLogSinkConfig) -> anyhow::Result<Self> {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", config.message_timeout_ms.to_string());
+        if let Some(sasl) = &config.sasl {
+            client_config
+                .set("security.protocol", "SASL_SSL")
+                .set("sasl.mechanisms", &sasl.mechanism)
+                .set("sasl.username", &sasl.username)
+                .set("sasl.password", &sasl.password);
+        }
+        let producer = client_config
+            .create()
+            .context("Failed to create the Kafka producer")?;
+        Ok(Self {
+            producer,
+            topic: config.topic.clone(),
+            message_timeout: Duration::from_millis(config.message_timeout_ms),
+        })
+    }
+}
+
+#[async_trait]
+impl TxLogSink for KafkaTxLogSink {
+    async fn log(&self, record: &TxLogRecord) -> anyhow::Result<()> {
+        let key = record.digest.to_string();
+        let payload = TxLogMessage::new(record).to_string();
+        self.producer
+            .send(
+                FutureRecord::to(&self.topic).key(&key).payload(&payload),
+                self.message_timeout,
+            )
+            .await
+            .map_err(|(err, _)| anyhow::anyhow!("Failed to publish to Kafka: {}", err))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod file;
+mod kafka;
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use iota_json_rpc_types::{IotaTransactionBlockEffects, IotaTransactionBlockEffectsAPI};
+use iota_types::base_types::IotaAddress;
+use iota_types::digests::TransactionDigest;
+use serde::{Deserialize, Serialize};
+use tracing::{error, trace};
+
+use crate::access_controller::decision::Decision;
+use crate::types::ReservationID;
+use crate::TRANSACTION_LOGGING_TARGET_NAME;
+
+pub use file::{FileTxLogLine, FileTxLogSink, FileTxLogSinkConfig};
+pub use kafka::{KafkaSaslConfig, KafkaTxLogSink, KafkaTxLogSinkConfig};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TxLogMessage<D: Serialize + Clone> {
+    pub timestamp: i64,
+    pub level: String,
+    pub host: String,
+    pub message: String,
+    pub details: D,
+}
+
+impl<D> TxLogMessage<D>
+where
+    D: Serialize + Clone,
+{
+    pub fn new(transaction_effects: D) -> Self {
+        let hostname = hostname::get().unwrap().to_string_lossy().to_string();
+        Self {
+            timestamp: chrono::Utc::now().timestamp(),
+            level: "trace".to_string(),
+            host: hostname,
+            message: "transaction data".to_string(),
+            details: transaction_effects,
+        }
+    }
+}
+
+impl<D> Display for TxLogMessage<D>
+where
+    D: Serialize + Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let serialized = serde_json::to_string(&self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", serialized)
+    }
+}
+
+/// A transaction executed by the Gas Station, as published to the transaction log sinks.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TxLogRecord {
+    pub digest: TransactionDigest,
+    pub sender: IotaAddress,
+    pub sponsor: IotaAddress,
+    pub reservation_id: ReservationID,
+    pub gas_used: u64,
+    pub success: bool,
+    pub decision: Decision,
+    pub effects: IotaTransactionBlockEffects,
+    /// ID of the `execute_tx` request that executed the transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl TxLogRecord {
+    pub fn new(
+        sender: IotaAddress,
+        sponsor: IotaAddress,
+        reservation_id: ReservationID,
+        decision: Decision,
+        effects: &IotaTransactionBlockEffects,
+    ) -> Self {
+        Self {
+            digest: *effects.transaction_digest(),
+            sender,
+            sponsor,
+            reservation_id,
+            gas_used: effects.gas_cost_summary().gas_used(),
+            success: effects.status().is_ok(),
+            decision,
+            effects: effects.clone(),
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+/// A destination of the transaction log.
+#[async_trait]
+pub trait TxLogSink: Send + Sync {
+    async fn log(&self, record: &TxLogRecord) -> anyhow::Result<()>;
+}
+
+/// Writes the effects to the `transactions` tracing target. It is enabled by setting the
+/// `TRANSACTIONS_LOGGING` environment variable to `true`.
+pub struct TracingTxLogSink;
+
+#[async_trait]
+impl TxLogSink for TracingTxLogSink {
+    async fn log(&self, record: &TxLogRecord) -> anyhow::Result<()> {
+        trace!(target: TRANSACTION_LOGGING_TARGET_NAME, "{}", TxLogMessage::new(&record.effects));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TxLogSinkConfig {
+    Kafka(KafkaTxLogSinkConfig),
+    File(FileTxLogSinkConfig),
+}
+
+impl TxLogSinkConfig {
+    pub fn new_sink(&self) -> anyhow::Result<Arc<dyn TxLogSink>> {
+        match self {
+            TxLogSinkConfig::Kafka(config) => Ok(Arc::new(KafkaTxLogSink::new(config)?)),
+            TxLogSinkConfig::File(config) => Ok(Arc::new(FileTxLogSink::new(config)?)),
+        }
+    }
+}
+
+/// Publishes every executed transaction to all the configured sinks.
+#[derive(Clone, Default)]
+pub struct TxLogger {
+    sinks: Vec<Arc<dyn TxLogSink>>,
+}
+
+impl TxLogger {
+    pub fn new(sinks: Vec<Arc<dyn TxLogSink>>) -> Self {
+        Self { sinks }
+    }
+
+    pub fn from_config(configs: &[TxLogSinkConfig]) -> anyhow::Result<Self> {
+        let mut sinks: Vec<Arc<dyn TxLogSink>> = vec![Arc::new(TracingTxLogSink)];
+        for config in configs {
+            sinks.push(config.new_sink()?);
+        }
+        Ok(Self::new(sinks))
+    }
+
+    /// Publishes the record in the background, so that slow sinks don't delay the response.
+    /// Failures are only logged.
+    pub fn log(&self, record: TxLogRecord) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let sinks = self.sinks.clone();
+        tokio::spawn(async move {
+            for sink in sinks {
+                if let Err(err) = sink.log(&record).await {
+                    error!(
+                        "Failed to publish transaction {} to the transaction log: {:?}",
+                        record.digest, err
+                    );
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sink_config() {
+        let config: Vec<TxLogSinkConfig> = serde_yaml::from_str(
+            "- kafka:\n    brokers: localhost:9092\n    topic: sponsored-transactions\n\
+             - file:\n    path: /var/log/gas-station/transactions.jsonl\n",
+        )
+        .unwrap();
+        let TxLogSinkConfig::Kafka(kafka) = &config[0] else {
+            panic!("Expected a Kafka sink");
+        };
+        assert_eq!(kafka.topic, "sponsored-transactions");
+        assert!(kafka.sasl.is_none());
+        let TxLogSinkConfig::File(file) = &config[1] else {
+            panic!("Expected a file sink");
+        };
+        assert_eq!(file.max_files, 5);
+    }
+}
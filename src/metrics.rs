@@ -1,10 +1,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_metrics::histogram::Histogram;
+use iota_metrics::histogram::{Histogram, HistogramVec};
 use prometheus::{
     register_int_counter_vec_with_registry, register_int_counter_with_registry,
-    register_int_gauge_vec_with_registry, IntCounter, IntCounterVec, IntGaugeVec, Registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Registry,
 };
 use std::sync::Arc;
 use tracing::error;
@@ -26,10 +27,23 @@ pub struct GasStationRpcMetrics {
     pub num_authorized_execute_tx_requests: IntCounter,
     pub num_successful_execute_tx_requests: IntCounter,
     pub num_failed_execute_tx_requests: IntCounter,
+    pub num_replayed_execute_tx_requests: IntCounter,
+    pub num_rejected_execute_tx_requests: IntCounter,
+
+    // Requests to the public endpoints rejected by the IP filter
+    pub num_ip_filtered_requests: IntCounter,
+
+    // Admission queue of the execute_tx endpoint
+    pub execute_tx_queue_depth: IntGauge,
+    pub execute_tx_queue_wait_time_ms: Histogram,
 
     /// Access controller metrics
     pub num_allowed_execute_tx_requests: IntCounter,
     pub num_blocked_execute_tx_requests: IntCounter,
+    pub num_audited_execute_tx_requests: IntCounterVec,
+    pub num_held_execute_tx_requests: IntCounter,
+    pub num_hook_cache_hits: IntCounterVec,
+    pub num_hook_cache_misses: IntCounterVec,
 }
 
 impl GasStationRpcMetrics {
@@ -87,6 +101,35 @@ impl GasStationRpcMetrics {
                 registry,
             )
             .unwrap(),
+            num_replayed_execute_tx_requests: register_int_counter_with_registry!(
+                "num_replayed_execute_tx_requests",
+                "Total number of execute_tx RPC requests answered with the response stored for their idempotency key",
+                registry,
+            )
+            .unwrap(),
+            num_rejected_execute_tx_requests: register_int_counter_with_registry!(
+                "num_rejected_execute_tx_requests",
+                "Total number of execute_tx RPC requests rejected because too many transactions were being executed",
+                registry,
+            )
+            .unwrap(),
+            num_ip_filtered_requests: register_int_counter_with_registry!(
+                "num_ip_filtered_requests",
+                "Total number of RPC requests rejected because of the IP address of the client",
+                registry,
+            )
+            .unwrap(),
+            execute_tx_queue_depth: register_int_gauge_with_registry!(
+                "execute_tx_queue_depth",
+                "Number of execute_tx RPC requests waiting for a free execution slot",
+                registry,
+            )
+            .unwrap(),
+            execute_tx_queue_wait_time_ms: Histogram::new_in_registry(
+                "execute_tx_queue_wait_time_ms",
+                "Time the execute_tx RPC requests waited for a free execution slot, in milliseconds",
+                registry,
+            ),
             num_failed_execute_tx_requests: register_int_counter_with_registry!(
                 "num_failed_execute_tx_requests",
                 "Total number of execute_tx RPC requests that failed",
@@ -105,6 +148,33 @@ impl GasStationRpcMetrics {
                 registry,
             )
             .unwrap(),
+            num_audited_execute_tx_requests: register_int_counter_vec_with_registry!(
+                "num_audited_execute_tx_requests",
+                "Total number execute_tx RPC requests matched by the audit rules of the Access Controller, by rule",
+                &["rule"],
+                registry,
+            )
+            .unwrap(),
+            num_held_execute_tx_requests: register_int_counter_with_registry!(
+                "num_held_execute_tx_requests",
+                "Total number execute_tx RPC requests held for manual approval by the Access Controller",
+                registry,
+            )
+            .unwrap(),
+            num_hook_cache_hits: register_int_counter_vec_with_registry!(
+                "num_hook_cache_hits",
+                "Total number of hook responses of the Access Controller reused from the cache, by rule",
+                &["rule"],
+                registry,
+            )
+            .unwrap(),
+            num_hook_cache_misses: register_int_counter_vec_with_registry!(
+                "num_hook_cache_misses",
+                "Total number of hook calls of the Access Controller made for a rule with a cache, by rule",
+                &["rule"],
+                registry,
+            )
+            .unwrap(),
         })
     }
 
@@ -115,13 +185,26 @@ impl GasStationRpcMetrics {
 
 pub struct GasStationCoreMetrics {
     pub num_expired_gas_coins: IntCounterVec,
+    pub num_expired_unused_reservations: IntCounterVec,
+    pub num_reservation_lease_renewals: IntCounterVec,
     pub num_smashed_gas_coins: IntCounterVec,
+    pub num_quarantined_gas_coins: IntCounterVec,
     pub reserved_gas_coin_count_per_request: Histogram,
     pub reserve_gas_latency_ms: Histogram,
+    pub num_reused_reservations: IntCounter,
     pub transaction_signing_latency_ms: Histogram,
     pub transaction_execution_latency_ms: Histogram,
+    // Per signing request to the signer backend, without the retries of the core.
+    pub signer_latency_ms: HistogramVec,
+    pub num_signer_errors: IntCounterVec,
     pub num_gas_station_invariant_violations: IntCounter,
     pub daily_gas_usage: IntGaugeVec,
+
+    // Sampled periodically from the storage.
+    pub pool_available_gas_coin_count: IntGaugeVec,
+    pub pool_available_gas_total_balance: IntGaugeVec,
+    pub pool_reserved_gas_coin_count: IntGaugeVec,
+    pub pool_oldest_reservation_age_sec: IntGaugeVec,
 }
 
 impl GasStationCoreMetrics {
@@ -139,6 +222,20 @@ impl GasStationCoreMetrics {
                 registry,
             )
                 .unwrap(),
+            num_expired_unused_reservations: register_int_counter_vec_with_registry!(
+                "num_expired_unused_reservations",
+                "Total number of reservations that expired without a transaction being executed",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
+            num_reservation_lease_renewals: register_int_counter_vec_with_registry!(
+                "num_reservation_lease_renewals",
+                "Total number of times a reservation was kept from expiring while its execute_tx request was in progress",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
             num_smashed_gas_coins: register_int_counter_vec_with_registry!(
                 "num_smashed_gas_coins",
                 "Total number of gas coins that are smashed (i.e. deleted) during transaction execution",
@@ -146,11 +243,24 @@ impl GasStationCoreMetrics {
                 registry,
             )
                 .unwrap(),
+            num_quarantined_gas_coins: register_int_counter_vec_with_registry!(
+                "num_quarantined_gas_coins",
+                "Total number of gas coins that are quarantined because a conflicting transaction locked them",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
             reserve_gas_latency_ms: Histogram::new_in_registry(
                 "reserve_gas_latency",
                 "Latency of gas reservation, in milliseconds",
                 registry,
             ),
+            num_reused_reservations: register_int_counter_with_registry!(
+                "num_reused_reservations",
+                "Total number of reserve_gas requests answered with the reservation made earlier with the same idempotency key",
+                registry,
+            )
+                .unwrap(),
             transaction_signing_latency_ms: Histogram::new_in_registry(
                 "transaction_signing_latency",
                 "Latency of transaction signing, in milliseconds",
@@ -161,6 +271,19 @@ impl GasStationCoreMetrics {
                 "Latency of transaction execution, in milliseconds",
                 registry,
             ),
+            signer_latency_ms: HistogramVec::new_in_registry(
+                "signer_latency",
+                "Latency of the signing requests, in milliseconds, per signer backend",
+                &["signer"],
+                registry,
+            ),
+            num_signer_errors: register_int_counter_vec_with_registry!(
+                "num_signer_errors",
+                "Total number of signing requests that failed, per signer backend",
+                &["signer"],
+                registry,
+            )
+                .unwrap(),
             num_gas_station_invariant_violations: register_int_counter_with_registry!(
                 "num_gas_station_invariant_violations",
                 "Total number of invariant violations in the Gas Station core",
@@ -174,6 +297,34 @@ impl GasStationCoreMetrics {
                 registry,
             )
                 .unwrap(),
+            pool_available_gas_coin_count: register_int_gauge_vec_with_registry!(
+                "pool_available_gas_coin_count",
+                "Number of gas coins available for reservation, sampled periodically",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
+            pool_available_gas_total_balance: register_int_gauge_vec_with_registry!(
+                "pool_available_gas_total_balance",
+                "Total balance of the gas coins available for reservation, sampled periodically",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
+            pool_reserved_gas_coin_count: register_int_gauge_vec_with_registry!(
+                "pool_reserved_gas_coin_count",
+                "Number of gas coins reserved and not yet executed or expired, sampled periodically",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
+            pool_oldest_reservation_age_sec: register_int_gauge_vec_with_registry!(
+                "pool_oldest_reservation_age_sec",
+                "Age of the oldest reservation not yet executed or expired, in seconds. 0 if there is none",
+                &["sponsor"],
+                registry,
+            )
+                .unwrap(),
         })
     }
 
@@ -191,9 +342,46 @@ impl GasStationCoreMetrics {
     }
 }
 
+pub struct IotaClientMetrics {
+    pub reference_gas_price_cache_age_sec: IntGauge,
+    pub fullnode_circuit_state: IntGauge,
+    pub fullnode_chain_identifier: IntGaugeVec,
+}
+
+impl IotaClientMetrics {
+    pub fn new(registry: &Registry) -> Arc<Self> {
+        Arc::new(Self {
+            reference_gas_price_cache_age_sec: register_int_gauge_with_registry!(
+                "reference_gas_price_cache_age_sec",
+                "Time since the cached reference gas price was last refreshed, in seconds",
+                registry,
+            )
+            .unwrap(),
+            fullnode_circuit_state: register_int_gauge_with_registry!(
+                "fullnode_circuit_state",
+                "State of the fullnode circuit breaker: 0 closed, 1 open, 2 half-open",
+                registry,
+            )
+            .unwrap(),
+            fullnode_chain_identifier: register_int_gauge_vec_with_registry!(
+                "fullnode_chain_identifier",
+                "Chain identifier of the network of the fullnode, as a label always set to 1",
+                &["chain_identifier"],
+                registry,
+            )
+            .unwrap(),
+        })
+    }
+
+    pub fn new_for_testing() -> Arc<Self> {
+        Self::new(&Registry::new())
+    }
+}
+
 pub struct StorageMetrics {
     pub gas_station_available_gas_coin_count: IntGaugeVec,
     pub gas_station_available_gas_total_balance: IntGaugeVec,
+    pub gas_station_quarantined_gas_coin_count: IntGaugeVec,
 
     pub num_reserve_gas_coins_requests: IntCounter,
     pub num_successful_reserve_gas_coins_requests: IntCounter,
@@ -203,6 +391,16 @@ pub struct StorageMetrics {
     pub num_successful_add_new_coins_requests: IntCounter,
     pub num_expire_coins_requests: IntCounter,
     pub num_successful_expire_coins_requests: IntCounter,
+
+    pub num_redis_reconnects: IntCounterVec,
+    pub num_failed_redis_pings: IntCounterVec,
+}
+
+/// Counters of a connection to Redis, labelled by the storage using it.
+#[derive(Clone)]
+pub struct RedisConnectionMetrics {
+    pub num_reconnects: IntCounter,
+    pub num_failed_pings: IntCounter,
 }
 
 impl StorageMetrics {
@@ -222,6 +420,13 @@ impl StorageMetrics {
                 registry,
             )
             .unwrap(),
+            gas_station_quarantined_gas_coin_count: register_int_gauge_vec_with_registry!(
+                "gas_station_quarantined_gas_coin_count",
+                "Current number of gas coins kept out of the pool until a later epoch",
+                &["sponsor"],
+                registry,
+            )
+            .unwrap(),
             num_reserve_gas_coins_requests: register_int_counter_with_registry!(
                 "num_reserve_gas_coins_requests",
                 "Total number of reserve_gas_coins requests received",
@@ -270,10 +475,32 @@ impl StorageMetrics {
                 registry,
             )
             .unwrap(),
+            num_redis_reconnects: register_int_counter_vec_with_registry!(
+                "num_redis_reconnects",
+                "Total number of times a connection to Redis was reset after a failure",
+                &["storage"],
+                registry,
+            )
+            .unwrap(),
+            num_failed_redis_pings: register_int_counter_vec_with_registry!(
+                "num_failed_redis_pings",
+                "Total number of liveness pings to Redis that failed or timed out",
+                &["storage"],
+                registry,
+            )
+            .unwrap(),
         })
     }
 
     pub fn new_for_testing() -> Arc<Self> {
         Self::new(&Registry::new())
     }
+
+    /// Counters of the connection to Redis of `storage`, e.g. `pool` or `stats`.
+    pub fn redis_connection_metrics(&self, storage: &str) -> RedisConnectionMetrics {
+        RedisConnectionMetrics {
+            num_reconnects: self.num_redis_reconnects.with_label_values(&[storage]),
+            num_failed_pings: self.num_failed_redis_pings.with_label_values(&[storage]),
+        }
+    }
 }
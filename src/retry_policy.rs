@@ -0,0 +1,199 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEFAULT_MAX_ATTEMPTS: usize = 10;
+const DEFAULT_INITIAL_BACKOFF_MS: u64 = 100;
+const DEFAULT_MAX_BACKOFF_MS: u64 = 5_000;
+const DEFAULT_DEADLINE_SEC: u64 = 60;
+
+/// Defines how a failing request is retried. Retrying stops at whichever comes first of
+/// `max-attempts` and `deadline-sec`, and the last error is then returned to the caller.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. Unlimited if null.
+    pub max_attempts: Option<usize>,
+    /// Delay before the first retry. The delay doubles after each attempt.
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the delay between two attempts.
+    pub max_backoff_ms: u64,
+    /// Randomize each delay between zero and its nominal value, so that many callers failing at
+    /// the same time don't retry in lockstep.
+    pub jitter: bool,
+    /// Overall time allowed for all the attempts. Unlimited if null.
+    pub deadline_sec: Option<u64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: Some(DEFAULT_MAX_ATTEMPTS),
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+            max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+            jitter: true,
+            deadline_sec: Some(DEFAULT_DEADLINE_SEC),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Runs `action` until it succeeds or the policy is exhausted.
+    pub async fn retry<T, F, Fut>(&self, action: F) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        self.retry_if(action, |_| true).await
+    }
+
+    /// Same as `retry`, but gives up right away on errors for which `should_retry` is false.
+    pub async fn retry_if<T, F, Fut>(
+        &self,
+        mut action: F,
+        should_retry: impl Fn(&anyhow::Error) -> bool,
+    ) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let deadline = self
+            .deadline_sec
+            .map(|sec| Instant::now() + Duration::from_secs(sec));
+        let mut backoff = Duration::from_millis(self.initial_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, action())
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow!("Attempt timed out"))),
+                None => action().await,
+            };
+            let err = match result {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            if !should_retry(&err) {
+                return Err(err);
+            }
+            if self.max_attempts.is_some_and(|max| attempt >= max) {
+                return Err(err.context(format!("Giving up after {} attempts", attempt)));
+            }
+            let delay = if self.jitter {
+                backoff.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+            } else {
+                backoff
+            };
+            if deadline.is_some_and(|deadline| Instant::now() + delay >= deadline) {
+                return Err(err.context(format!(
+                    "Retry deadline exceeded after {} attempts",
+                    attempt
+                )));
+            }
+            tokio::time::sleep(delay).await;
+            backoff = (backoff * 2).min(Duration::from_millis(self.max_backoff_ms));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: Some(3),
+            initial_backoff_ms: 1,
+            max_backoff_ms: 1,
+            jitter: false,
+            deadline_sec: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_failures() {
+        let attempts = AtomicUsize::new(0);
+        let result = test_policy()
+            .retry(|| async {
+                if attempts.fetch_add(1, Ordering::Relaxed) < 2 {
+                    bail!("Failed");
+                }
+                Ok(42)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let attempts = AtomicUsize::new(0);
+        let result: anyhow::Result<()> = test_policy()
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                bail!("Failed")
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_deadline() {
+        let policy = RetryPolicy {
+            max_attempts: None,
+            initial_backoff_ms: 400,
+            max_backoff_ms: 400,
+            deadline_sec: Some(1),
+            ..test_policy()
+        };
+        let attempts = AtomicUsize::new(0);
+        let result: anyhow::Result<()> = policy
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                bail!("Failed")
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_stops_on_non_retryable_error() {
+        let attempts = AtomicUsize::new(0);
+        let result: anyhow::Result<()> = test_policy()
+            .retry_if(
+                || async {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    bail!("Fatal")
+                },
+                |err| err.to_string() != "Fatal",
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_deserialize_partial_policy() {
+        let policy: RetryPolicy =
+            serde_yaml::from_str("max-attempts: null\njitter: false").unwrap();
+        assert_eq!(
+            policy,
+            RetryPolicy {
+                max_attempts: None,
+                jitter: false,
+                ..Default::default()
+            }
+        );
+    }
+}
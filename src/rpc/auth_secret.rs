@@ -0,0 +1,144 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The bearer secret of the RPC servers. When it is rotated, the previous secret is still
+//! accepted during a grace period, so that the clients can switch to the new secret without
+//! their requests being rejected in the meantime.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use tracing::{debug, info};
+
+use crate::{AUTH_ENV_NAME, PREVIOUS_AUTH_ENV_NAME};
+
+/// How long the previous secret is accepted when no grace period is given.
+pub const DEFAULT_AUTH_GRACE_PERIOD: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Default)]
+pub struct AuthSecret {
+    secrets: RwLock<Secrets>,
+}
+
+#[derive(Debug, Default)]
+struct Secrets {
+    current: Option<String>,
+    /// The previous secret, and until when it is accepted.
+    previous: Option<(String, Instant)>,
+}
+
+impl AuthSecret {
+    pub fn new(current: Option<String>) -> Self {
+        Self {
+            secrets: RwLock::new(Secrets {
+                current,
+                previous: None,
+            }),
+        }
+    }
+
+    /// Reads the secret from `GAS_STATION_AUTH`. The secret of `GAS_STATION_AUTH_PREVIOUS` is
+    /// accepted for [`DEFAULT_AUTH_GRACE_PERIOD`] after the start, so that the replicas can be
+    /// restarted one by one with a new secret.
+    pub fn from_env() -> Self {
+        let secret = Self::new(std::env::var(AUTH_ENV_NAME).ok());
+        if let Ok(previous) = std::env::var(PREVIOUS_AUTH_ENV_NAME) {
+            if secret.is_enabled() {
+                secret.secrets.write().previous =
+                    Some((previous, Instant::now() + DEFAULT_AUTH_GRACE_PERIOD));
+            }
+        }
+        secret
+    }
+
+    /// Whether the requests must be authorized.
+    pub fn is_enabled(&self) -> bool {
+        self.secrets.read().current.is_some()
+    }
+
+    pub fn is_authorized(&self, token: Option<&str>) -> bool {
+        let secrets = self.secrets.read();
+        let Some(current) = &secrets.current else {
+            return true;
+        };
+        let Some(token) = token else {
+            return false;
+        };
+        if token == current {
+            return true;
+        }
+        match &secrets.previous {
+            Some((previous, expires_at)) if token == previous && Instant::now() < *expires_at => {
+                debug!("Request authorized with the previous secret");
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `token` is the current secret, or authorization is disabled. Unlike
+    /// [`Self::is_authorized`], the previous secret is rejected, so that a leaked previous secret
+    /// can't be used to rotate the secret again.
+    pub fn is_current(&self, token: Option<&str>) -> bool {
+        match &self.secrets.read().current {
+            Some(current) => token == Some(current.as_str()),
+            None => true,
+        }
+    }
+
+    /// Replaces the secret by `new_secret`. The current secret is still accepted during
+    /// `grace_period`.
+    pub fn rotate(&self, new_secret: String, grace_period: Duration) -> anyhow::Result<()> {
+        if new_secret.is_empty() {
+            anyhow::bail!("The new secret is empty");
+        }
+        let mut secrets = self.secrets.write();
+        let Some(current) = secrets.current.take() else {
+            anyhow::bail!(
+                "Authorization is disabled, set {} to enable it",
+                AUTH_ENV_NAME
+            );
+        };
+        secrets.current = Some(new_secret);
+        secrets.previous = Some((current, Instant::now() + grace_period));
+        info!(
+            "Rotated the authorization secret, the previous one is accepted for {:?}",
+            grace_period
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        let secret = AuthSecret::new(Some("old".to_string()));
+        assert!(secret.is_authorized(Some("old")));
+        assert!(!secret.is_authorized(Some("new")));
+        assert!(!secret.is_authorized(None));
+
+        secret
+            .rotate("new".to_string(), Duration::from_secs(60))
+            .unwrap();
+        assert!(secret.is_authorized(Some("new")));
+        assert!(secret.is_authorized(Some("old")));
+        assert!(secret.is_current(Some("new")));
+        assert!(!secret.is_current(Some("old")));
+        assert!(!secret.is_current(None));
+
+        // Without grace period, the previous secret is rejected right away
+        secret.rotate("newer".to_string(), Duration::ZERO).unwrap();
+        assert!(secret.is_authorized(Some("newer")));
+        assert!(!secret.is_authorized(Some("new")));
+        assert!(!secret.is_authorized(Some("old")));
+
+        let disabled = AuthSecret::new(None);
+        assert!(disabled.is_authorized(None));
+        assert!(disabled
+            .rotate("new".to_string(), Duration::from_secs(60))
+            .is_err());
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bounds the size of the request bodies of the public endpoints, so that large or compressed
+//! bodies cannot exhaust the memory of the Gas Station. Compressed bodies are decompressed up
+//! to the same bound.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::State;
+use axum::http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::{Deserialize, Serialize};
+
+use super::server::reject_request;
+use crate::rpc::rpc_types::{ErrorCode, RpcError};
+
+// Transactions are at most 128 KiB, i.e. about 171 KiB in base64.
+const DEFAULT_MAX_EXECUTE_TX_BODY_SIZE_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_BODY_SIZE_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BodyLimitConfig {
+    /// Maximum size of the `execute_tx` request bodies, after decompression.
+    pub max_execute_tx_body_size_bytes: usize,
+    /// Maximum size of the request bodies of the other public endpoints, after decompression.
+    pub max_body_size_bytes: usize,
+}
+
+impl Default for BodyLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_execute_tx_body_size_bytes: DEFAULT_MAX_EXECUTE_TX_BODY_SIZE_BYTES,
+            max_body_size_bytes: DEFAULT_MAX_BODY_SIZE_BYTES,
+        }
+    }
+}
+
+impl BodyLimitConfig {
+    fn max_size_bytes(&self, path: &str) -> usize {
+        if path.ends_with("/execute_tx") {
+            self.max_execute_tx_body_size_bytes
+        } else {
+            self.max_body_size_bytes
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Identity,
+    Gzip,
+    Deflate,
+}
+
+pub(crate) async fn limit_body(
+    State(config): State<Arc<BodyLimitConfig>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let max_size_bytes = config.max_size_bytes(&path);
+    let too_large = || {
+        reject_request(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &path,
+            RpcError::new(
+                ErrorCode::PayloadTooLarge,
+                format!("Request body exceeds {} bytes", max_size_bytes),
+            ),
+        )
+    };
+    let encoding = match request
+        .headers()
+        .get(CONTENT_ENCODING)
+        .map(|value| {
+            value
+                .to_str()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .as_deref()
+    {
+        None | Some("") | Some("identity") => Encoding::Identity,
+        Some("gzip") | Some("x-gzip") => Encoding::Gzip,
+        Some("deflate") => Encoding::Deflate,
+        Some(encoding) => {
+            return reject_request(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                &path,
+                RpcError::new(
+                    ErrorCode::InvalidRequest,
+                    format!("Unsupported content encoding: {}", encoding),
+                ),
+            );
+        }
+    };
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.is_some_and(|length| length > max_size_bytes) {
+        return too_large();
+    }
+
+    let (mut parts, body) = request.into_parts();
+    // The length header can be missing or wrong, so the body is read up to the limit.
+    let Some(bytes) = read_body(body, max_size_bytes).await else {
+        return too_large();
+    };
+    let bytes = match decode(bytes, encoding, max_size_bytes) {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => return too_large(),
+        Err(err) => {
+            return reject_request(
+                StatusCode::BAD_REQUEST,
+                &path,
+                RpcError::new(
+                    ErrorCode::InvalidRequest,
+                    format!("Failed to decompress the request body: {}", err),
+                ),
+            );
+        }
+    };
+    if encoding != Encoding::Identity {
+        parts.headers.remove(CONTENT_ENCODING);
+        parts.headers.insert(CONTENT_LENGTH, bytes.len().into());
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// Returns None if the body exceeds `max_size_bytes`, or can't be read.
+async fn read_body(mut body: Body, max_size_bytes: usize) -> Option<Bytes> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.ok()?;
+        if bytes.len() + chunk.len() > max_size_bytes {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Some(bytes.into())
+}
+
+/// Returns None if the decompressed body exceeds `max_size_bytes`.
+fn decode(
+    bytes: Bytes,
+    encoding: Encoding,
+    max_size_bytes: usize,
+) -> std::io::Result<Option<Bytes>> {
+    let decoder: Box<dyn Read> = match encoding {
+        Encoding::Identity => return Ok(Some(bytes)),
+        Encoding::Gzip => Box::new(GzDecoder::new(&bytes[..])),
+        // HTTP `deflate` is the zlib format, not raw DEFLATE
+        Encoding::Deflate => Box::new(ZlibDecoder::new(&bytes[..])),
+    };
+    let mut decoded = Vec::new();
+    // Stops reading one byte past the limit, without decompressing the rest of the body.
+    decoder
+        .take(max_size_bytes as u64 + 1)
+        .read_to_end(&mut decoded)?;
+    if decoded.len() > max_size_bytes {
+        return Ok(None);
+    }
+    Ok(Some(decoded.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::middleware;
+    use axum::routing::post;
+    use axum::Router;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+    use std::net::SocketAddr;
+
+    async fn start_server(config: BodyLimitConfig) -> String {
+        let app = Router::new()
+            .route(
+                "/v2/execute_tx",
+                post(|body: Bytes| async move { body.len().to_string() }),
+            )
+            .layer(middleware::from_fn_with_state(Arc::new(config), limit_body));
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let url = format!("http://{}/v2/execute_tx", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zlib(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_limit_body() {
+        let url = start_server(BodyLimitConfig {
+            max_execute_tx_body_size_bytes: 1000,
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .body(vec![b'a'; 1000])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "1000");
+
+        let response = client
+            .post(&url)
+            .body(vec![b'a'; 1001])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+        let error: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(error["error"]["code"], "PAYLOAD_TOO_LARGE");
+
+        // Compressed bodies are decompressed
+        let response = client
+            .post(&url)
+            .header("content-encoding", "gzip")
+            .body(gzip(&[b'a'; 1000]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "1000");
+        let response = client
+            .post(&url)
+            .header("content-encoding", "deflate")
+            .body(zlib(&[b'a'; 1000]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "1000");
+
+        // A small compressed body can't expand past the limit
+        let compressed = gzip(&[b'a'; 1_000_000]);
+        assert!(compressed.len() < 1000);
+        let response = client
+            .post(&url)
+            .header("content-encoding", "gzip")
+            .body(compressed)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = client
+            .post(&url)
+            .header("content-encoding", "br")
+            .body(vec![b'a'; 10])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
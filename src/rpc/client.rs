@@ -1,18 +1,25 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::access_controller::held_transactions::HeldTransaction;
 use crate::read_auth_env;
 use crate::rpc::rpc_types::{
-    ExecuteTransactionRequestType, ExecuteTxRequest, ExecuteTxResponse, ReserveGasRequest,
-    ReserveGasResponse,
+    AdjustLimitRequest, BuildAndReserveRequest, BuildAndReserveResult, CheckTxRequest,
+    CheckTxResult, CoinInitStatus, DrainPoolRequest, DrainPoolResponse, EstimateGasRequest,
+    EstimateGasResult, ExecuteTransactionRequestType, ExecuteTxRequest, ExecuteTxResponse,
+    ExecuteTxResponseV2, ExpiredReservationsRequest, ExplainQuery, GasStationResponse,
+    HeldTransactionRequest, LimitsRequest, LimitsResponse, ReserveGasRequest, ReserveGasResponse,
+    ReserveGasResponseV2, RotateAuthSecretRequest, RuleLimit, SetCoinInitRequest, SponsorInfo,
+    UsageReport, UsageRequest,
 };
-use crate::types::ReservationID;
+use crate::types::{ClientReservationStats, PoolCoin, QuarantinedCoin, ReservationID};
 use anyhow::bail;
 use fastcrypto::encoding::Base64;
-use iota_json_rpc_types::IotaTransactionBlockEffects;
+use iota_json_rpc_types::{IotaTransactionBlockEffects, IotaTransactionBlockResponseOptions};
 use iota_types::base_types::{IotaAddress, ObjectRef};
+use iota_types::digests::TransactionDigest;
 use iota_types::signature::GenericSignature;
-use iota_types::transaction::TransactionData;
+use iota_types::transaction::{TransactionData, TransactionKind};
 use reqwest::header::{HeaderMap, AUTHORIZATION};
 use reqwest::Client;
 
@@ -57,6 +64,18 @@ impl GasStationRpcClient {
             .await
     }
 
+    pub async fn readyz(&self) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/readyz", self.server_address))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("Readiness check failed: {}", response.text().await?);
+        }
+        Ok(())
+    }
+
     pub async fn debug_health_check(&self) -> anyhow::Result<()> {
         let mut headers = HeaderMap::new();
         if let Some(auth) = read_auth_env() {
@@ -79,14 +98,47 @@ impl GasStationRpcClient {
         }
     }
 
+    pub async fn sponsor_info(&self) -> anyhow::Result<SponsorInfo> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/sponsor", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<SponsorInfo>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
     pub async fn reserve_gas(
         &self,
         gas_budget: u64,
         reserve_duration_secs: u64,
+    ) -> anyhow::Result<(IotaAddress, ReservationID, Vec<ObjectRef>)> {
+        self.reserve_gas_with_idempotency_key(gas_budget, reserve_duration_secs, None)
+            .await
+    }
+
+    /// Same as `reserve_gas`, but retrying with the same idempotency key returns the same
+    /// reservation until it expires.
+    pub async fn reserve_gas_with_idempotency_key(
+        &self,
+        gas_budget: u64,
+        reserve_duration_secs: u64,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<(IotaAddress, ReservationID, Vec<ObjectRef>)> {
         let request = ReserveGasRequest {
             gas_budget,
             reserve_duration_secs,
+            idempotency_key,
         };
         let mut headers = HeaderMap::new();
         if let Some(auth) = read_auth_env() {
@@ -129,6 +181,30 @@ impl GasStationRpcClient {
         request_type: Option<ExecuteTransactionRequestType>,
         headers: Option<HeaderMap>,
     ) -> anyhow::Result<IotaTransactionBlockEffects> {
+        self.execute_tx_with_options(
+            reservation_id,
+            tx_data,
+            user_sig,
+            request_type,
+            None,
+            headers,
+        )
+        .await?
+        .effects
+        .ok_or_else(|| anyhow::anyhow!("Unknown error"))
+    }
+
+    /// Same as `execute_tx`, but the response also includes what is requested by `options`, e.g.
+    /// the events, the object changes or the balance changes. The effects are always included.
+    pub async fn execute_tx_with_options(
+        &self,
+        reservation_id: ReservationID,
+        tx_data: &TransactionData,
+        user_sig: &GenericSignature,
+        request_type: Option<ExecuteTransactionRequestType>,
+        options: Option<IotaTransactionBlockResponseOptions>,
+        headers: Option<HeaderMap>,
+    ) -> anyhow::Result<ExecuteTxResponse> {
         let mut headers = headers.unwrap_or_default();
         if let Some(auth) = read_auth_env() {
             headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
@@ -138,6 +214,8 @@ impl GasStationRpcClient {
             tx_bytes: Base64::from_bytes(&bcs::to_bytes(&tx_data).unwrap()),
             user_sig: Base64::from_bytes(user_sig.as_ref()),
             request_type,
+            options,
+            idempotency_key: None,
         };
         let response = self
             .client
@@ -148,13 +226,171 @@ impl GasStationRpcClient {
             .await?
             .json::<ExecuteTxResponse>()
             .await?;
-        response.effects.ok_or_else(|| {
+        if response.effects.is_none() {
+            anyhow::bail!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()));
+        }
+        Ok(response)
+    }
+
+    /// Calls `/v2/reserve_gas`. The response is returned as is, so that the code of the error can
+    /// be inspected.
+    pub async fn reserve_gas_v2(
+        &self,
+        gas_budget: u64,
+        reserve_duration_secs: u64,
+    ) -> anyhow::Result<ReserveGasResponseV2> {
+        let request = ReserveGasRequest {
+            gas_budget,
+            reserve_duration_secs,
+            idempotency_key: None,
+        };
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v2/reserve_gas", self.server_address))
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?
+            .json::<ReserveGasResponseV2>()
+            .await?;
+        Ok(response)
+    }
+
+    /// Calls `/v2/execute_tx`. The response is returned as is, so that the code of the error can
+    /// be inspected.
+    pub async fn execute_tx_v2(
+        &self,
+        reservation_id: ReservationID,
+        tx_data: &TransactionData,
+        user_sig: &GenericSignature,
+        request_type: Option<ExecuteTransactionRequestType>,
+        options: Option<IotaTransactionBlockResponseOptions>,
+        headers: Option<HeaderMap>,
+    ) -> anyhow::Result<ExecuteTxResponseV2> {
+        let mut headers = headers.unwrap_or_default();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let request = ExecuteTxRequest {
+            reservation_id,
+            tx_bytes: Base64::from_bytes(&bcs::to_bytes(&tx_data).unwrap()),
+            user_sig: Base64::from_bytes(user_sig.as_ref()),
+            request_type,
+            options,
+            idempotency_key: None,
+        };
+        let response = self
+            .client
+            .post(format!("{}/v2/execute_tx", self.server_address))
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?
+            .json::<ExecuteTxResponseV2>()
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn estimate_gas(
+        &self,
+        sender: IotaAddress,
+        tx_kind: &TransactionKind,
+    ) -> anyhow::Result<EstimateGasResult> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let request = EstimateGasRequest {
+            sender,
+            tx_kind_bytes: Base64::from_bytes(&bcs::to_bytes(tx_kind)?),
+        };
+        let response = self
+            .client
+            .post(format!("{}/v1/estimate_gas", self.server_address))
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?
+            .json::<GasStationResponse<EstimateGasResult>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Checks with the access controller whether the transaction signed by the user would be
+    /// allowed, without executing it. With `explain`, the result tells why each rule made the
+    /// decision or didn't.
+    pub async fn check_tx(
+        &self,
+        tx_data: &TransactionData,
+        user_sig: &GenericSignature,
+        explain: bool,
+    ) -> anyhow::Result<CheckTxResult> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let request = CheckTxRequest {
+            tx_bytes: Base64::from_bytes(&bcs::to_bytes(tx_data)?),
+            user_sig: Base64::from_bytes(user_sig.as_ref()),
+        };
+        let response = self
+            .client
+            .post(format!("{}/v1/check_tx", self.server_address))
+            .headers(headers)
+            .query(&ExplainQuery { explain })
+            .json(&request)
+            .send()
+            .await?
+            .json::<GasStationResponse<CheckTxResult>>()
+            .await?;
+        response.result.ok_or_else(|| {
             anyhow::anyhow!(response
                 .error
                 .unwrap_or_else(|| "Unknown error".to_string()))
         })
     }
 
+    /// Has the Gas Station build the transaction of an intent with reserved gas coins. Returns
+    /// the reservation and the transaction to be signed by the sender.
+    pub async fn build_and_reserve(
+        &self,
+        request: &BuildAndReserveRequest,
+    ) -> anyhow::Result<(ReservationID, TransactionData)> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/build_and_reserve", self.server_address))
+            .headers(headers)
+            .json(request)
+            .send()
+            .await?
+            .json::<GasStationResponse<BuildAndReserveResult>>()
+            .await?;
+        let result = response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })?;
+        let tx_bytes = result
+            .tx_bytes
+            .to_vec()
+            .map_err(|_| anyhow::anyhow!("Invalid tx_bytes in the response"))?;
+        Ok((result.reservation_id, bcs::from_bytes(&tx_bytes)?))
+    }
+
     pub async fn reload_access_controller(&self) -> anyhow::Result<()> {
         let mut headers = HeaderMap::new();
         if let Some(auth) = read_auth_env() {
@@ -174,4 +410,353 @@ impl GasStationRpcClient {
         };
         Ok(())
     }
+
+    pub async fn get_quarantined_coins(&self) -> anyhow::Result<Vec<QuarantinedCoin>> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/quarantined_coins", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<Vec<QuarantinedCoin>>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    pub async fn get_pool_coins(&self) -> anyhow::Result<Vec<PoolCoin>> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/coins", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<Vec<PoolCoin>>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Refuses new reservations and transfers the available coins of the sponsor to `recipient`,
+    /// or merges them into large coins owned by the sponsor if `None`.
+    pub async fn drain_pool(
+        &self,
+        recipient: Option<IotaAddress>,
+    ) -> anyhow::Result<DrainPoolResponse> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/drain_pool", self.server_address))
+            .headers(headers)
+            .json(&DrainPoolRequest { recipient })
+            .send()
+            .await?
+            .json::<GasStationResponse<DrainPoolResponse>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    pub async fn resume_pool(&self) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/resume_pool", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<String>>()
+            .await?;
+        if let Some(error) = response.error {
+            bail!(error);
+        }
+        Ok(())
+    }
+
+    /// Makes `new_secret` the bearer secret of the station. The current secret, read from
+    /// `GAS_STATION_AUTH`, is still accepted during the grace period.
+    pub async fn rotate_auth_secret(
+        &self,
+        new_secret: String,
+        grace_period_secs: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/rotate_auth_secret", self.server_address))
+            .headers(headers)
+            .json(&RotateAuthSecretRequest {
+                new_secret,
+                grace_period_secs,
+            })
+            .send()
+            .await?
+            .json::<GasStationResponse<String>>()
+            .await?;
+        if let Some(error) = response.error {
+            bail!(error);
+        }
+        Ok(())
+    }
+
+    /// Returns the target balance of newly initialized coins, and the progress of the coin
+    /// initialization.
+    pub async fn get_coin_init_status(&self) -> anyhow::Result<CoinInitStatus> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/coin_init", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<CoinInitStatus>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Changes the target balance of newly initialized coins until the next config reload.
+    pub async fn set_target_init_balance(&self, target_init_balance: u64) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/coin_init", self.server_address))
+            .headers(headers)
+            .json(&SetCoinInitRequest {
+                target_init_balance,
+            })
+            .send()
+            .await?
+            .json::<GasStationResponse<String>>()
+            .await?;
+        if let Some(error) = response.error {
+            bail!(error);
+        }
+        Ok(())
+    }
+
+    /// Returns the clients with the most reservations that expired without being executed.
+    pub async fn get_expired_reservations(
+        &self,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Vec<ClientReservationStats>> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/expired_reservations", self.server_address))
+            .headers(headers)
+            .query(&ExpiredReservationsRequest { limit })
+            .send()
+            .await?
+            .json::<GasStationResponse<Vec<ClientReservationStats>>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Returns the gas used by the sponsored transactions of `request.sender` or
+    /// `request.package` within `request.window`.
+    pub async fn get_usage(&self, request: &UsageRequest) -> anyhow::Result<UsageReport> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/usage", self.server_address))
+            .headers(headers)
+            .query(request)
+            .send()
+            .await?
+            .json::<GasStationResponse<UsageReport>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Returns the gas usage of `sender` for every access control rule limiting it.
+    pub async fn get_limits(&self, sender: IotaAddress) -> anyhow::Result<LimitsResponse> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/limits", self.server_address))
+            .headers(headers)
+            .query(&LimitsRequest { sender })
+            .send()
+            .await?
+            .json::<GasStationResponse<LimitsResponse>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Resets the gas usage of `sender` counted by the access control rule at `rule_number`, or
+    /// subtracts `credit` from it if set.
+    pub async fn adjust_limit(
+        &self,
+        sender: IotaAddress,
+        rule_number: usize,
+        credit: Option<u64>,
+    ) -> anyhow::Result<RuleLimit> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/adjust_limit", self.server_address))
+            .headers(headers)
+            .json(&AdjustLimitRequest {
+                sender,
+                rule_number,
+                credit,
+            })
+            .send()
+            .await?
+            .json::<GasStationResponse<RuleLimit>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Returns the transactions held by the access controller until they are approved or
+    /// rejected, oldest first.
+    pub async fn list_held_transactions(&self) -> anyhow::Result<Vec<HeldTransaction>> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/held_transactions", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<Vec<HeldTransaction>>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    /// Executes a held transaction. The response is the same as the one of `/v2/execute_tx`.
+    pub async fn approve_transaction(
+        &self,
+        transaction_digest: TransactionDigest,
+    ) -> anyhow::Result<ExecuteTxResponseV2> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/approve_transaction", self.server_address))
+            .headers(headers)
+            .json(&HeldTransactionRequest { transaction_digest })
+            .send()
+            .await?
+            .json::<ExecuteTxResponseV2>()
+            .await?;
+        Ok(response)
+    }
+
+    /// Drops a held transaction and releases its gas coins.
+    pub async fn reject_transaction(
+        &self,
+        transaction_digest: TransactionDigest,
+    ) -> anyhow::Result<HeldTransaction> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .post(format!("{}/v1/reject_transaction", self.server_address))
+            .headers(headers)
+            .json(&HeldTransactionRequest { transaction_digest })
+            .send()
+            .await?
+            .json::<GasStationResponse<HeldTransaction>>()
+            .await?;
+        response.result.ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
+
+    pub async fn reload_config(&self) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(auth) = read_auth_env() {
+            headers.insert(AUTHORIZATION, format!("Bearer {}", auth).parse().unwrap());
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/reload_config", self.server_address))
+            .headers(headers)
+            .send()
+            .await?
+            .json::<GasStationResponse<String>>()
+            .await?;
+        response.result.map(|_| ()).ok_or_else(|| {
+            anyhow::anyhow!(response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()))
+        })
+    }
 }
@@ -0,0 +1,218 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cross-origin requests to the public endpoints, so that browser dApps can call the Gas Station
+//! directly.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+    VARY,
+};
+use axum::http::{HeaderMap, HeaderValue, Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::rpc_types::{CLIENT_ID_HEADER, IDEMPOTENCY_KEY_HEADER, REQUEST_ID_HEADER};
+
+/// Allows any origin when listed in `allowed_origins`.
+pub const ANY_ORIGIN: &str = "*";
+
+const DEFAULT_MAX_AGE_SEC: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CorsConfig {
+    /// Origins allowed to call the public endpoints, e.g. `https://app.example.com`, or `*` for
+    /// any origin.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    /// Request headers the browsers are allowed to send.
+    pub allowed_headers: Vec<String>,
+    /// How long the browsers cache the answer to a preflight request.
+    pub max_age_sec: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec![],
+            allowed_methods: vec![Method::GET.to_string(), Method::POST.to_string()],
+            allowed_headers: vec![
+                "authorization".to_string(),
+                "content-type".to_string(),
+                IDEMPOTENCY_KEY_HEADER.to_string(),
+                REQUEST_ID_HEADER.to_string(),
+                CLIENT_ID_HEADER.to_string(),
+            ],
+            max_age_sec: DEFAULT_MAX_AGE_SEC,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Value of the `Access-Control-Allow-Origin` header for the origin of a request, if it's
+    /// allowed.
+    fn allowed_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if self
+            .allowed_origins
+            .iter()
+            .any(|allowed| allowed == ANY_ORIGIN)
+        {
+            return Some(HeaderValue::from_static(ANY_ORIGIN));
+        }
+        let origin_str = origin.to_str().ok()?;
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/') == origin_str)
+            .then(|| origin.clone())
+    }
+}
+
+/// Answers the preflight requests of the allowed origins, and adds the CORS headers to the
+/// responses of their actual requests. Requests without an `Origin` are left untouched.
+pub(crate) async fn handle_cors<B>(
+    State(config): State<Arc<CorsConfig>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(origin) = request.headers().get(ORIGIN).cloned() else {
+        return next.run(request).await;
+    };
+    let allowed_origin = config.allowed_origin(&origin);
+    let is_preflight = request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+    if is_preflight {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        if let Some(allowed_origin) = allowed_origin {
+            let headers = response.headers_mut();
+            insert_origin_headers(headers, allowed_origin);
+            insert_joined(
+                headers,
+                ACCESS_CONTROL_ALLOW_METHODS,
+                &config.allowed_methods,
+            );
+            insert_joined(
+                headers,
+                ACCESS_CONTROL_ALLOW_HEADERS,
+                &config.allowed_headers,
+            );
+            headers.insert(
+                ACCESS_CONTROL_MAX_AGE,
+                HeaderValue::from(config.max_age_sec),
+            );
+        }
+        return response;
+    }
+    let mut response = next.run(request).await;
+    if let Some(allowed_origin) = allowed_origin {
+        let headers = response.headers_mut();
+        insert_origin_headers(headers, allowed_origin);
+        headers.insert(
+            ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static(REQUEST_ID_HEADER),
+        );
+    }
+    response
+}
+
+fn insert_origin_headers(headers: &mut HeaderMap, allowed_origin: HeaderValue) {
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allowed_origin);
+    // Caches must not reuse the response for another origin.
+    headers.insert(VARY, HeaderValue::from_static("origin"));
+}
+
+fn insert_joined(headers: &mut HeaderMap, name: axum::http::HeaderName, values: &[String]) {
+    if let Ok(value) = HeaderValue::from_str(&values.join(", ")) {
+        headers.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::middleware;
+    use axum::routing::post;
+    use axum::Router;
+    use std::net::SocketAddr;
+
+    async fn start_server(config: CorsConfig) -> String {
+        let app = Router::new()
+            .route("/v1/reserve_gas", post(|| async { "OK" }))
+            .layer(middleware::from_fn_with_state(
+                Arc::new(config),
+                handle_cors,
+            ));
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let url = format!("http://{}/v1/reserve_gas", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+
+    #[tokio::test]
+    async fn test_cors() {
+        let url = start_server(CorsConfig {
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            ..Default::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let preflight = client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("origin", "https://app.example.com")
+            .header("access-control-request-method", "POST")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(preflight.status(), reqwest::StatusCode::NO_CONTENT);
+        let headers = preflight.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(headers["access-control-allow-methods"], "GET, POST");
+        assert_eq!(headers["access-control-max-age"], "3600");
+
+        let response = client
+            .post(&url)
+            .header("origin", "https://app.example.com")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+
+        // Other origins don't get the CORS headers, so the browsers block them
+        let response = client
+            .post(&url)
+            .header("origin", "https://other.example.com")
+            .send()
+            .await
+            .unwrap();
+        assert!(!response
+            .headers()
+            .contains_key("access-control-allow-origin"));
+    }
+
+    #[test]
+    fn test_any_origin() {
+        let config = CorsConfig {
+            allowed_origins: vec![ANY_ORIGIN.to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.allowed_origin(&HeaderValue::from_static("https://app.example.com")),
+            Some(HeaderValue::from_static(ANY_ORIGIN))
+        );
+    }
+}
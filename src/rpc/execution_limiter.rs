@@ -0,0 +1,178 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bounds the number of transactions executed at the same time, so that a burst of `execute_tx`
+//! requests cannot exhaust the fullnode connections or the tokio tasks. Requests above the bound
+//! wait in a queue until a slot is free.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics::GasStationRpcMetrics;
+
+const DEFAULT_MAX_CONCURRENT_EXECUTIONS: usize = 100;
+const DEFAULT_MAX_QUEUE_LENGTH: usize = 1000;
+const DEFAULT_QUEUE_TIMEOUT_MS: u64 = 5000;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ExecutionLimitConfig {
+    /// Number of `execute_tx` requests processed at the same time.
+    pub max_concurrent_executions: usize,
+    /// Number of requests waiting for a free slot. Further requests are rejected right away.
+    pub max_queue_length: usize,
+    /// How long a request waits for a free slot before it is rejected.
+    pub queue_timeout_ms: u64,
+}
+
+impl Default for ExecutionLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_executions: DEFAULT_MAX_CONCURRENT_EXECUTIONS,
+            max_queue_length: DEFAULT_MAX_QUEUE_LENGTH,
+            queue_timeout_ms: DEFAULT_QUEUE_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Why a request was not admitted for execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionError {
+    QueueFull,
+    QueueTimeout,
+}
+
+impl fmt::Display for AdmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdmissionError::QueueFull => {
+                write!(f, "Too many transactions are being executed, retry later")
+            }
+            AdmissionError::QueueTimeout => write!(
+                f,
+                "Timed out waiting for the execution of the other transactions, retry later"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AdmissionError {}
+
+pub struct ExecutionLimiter {
+    config: ExecutionLimitConfig,
+    semaphore: Arc<Semaphore>,
+    queue_length: AtomicUsize,
+    metrics: Arc<GasStationRpcMetrics>,
+}
+
+impl ExecutionLimiter {
+    pub fn new(config: ExecutionLimitConfig, metrics: Arc<GasStationRpcMetrics>) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(config.max_concurrent_executions)),
+            config,
+            queue_length: AtomicUsize::new(0),
+            metrics,
+        }
+    }
+
+    /// Waits for a free execution slot, which is released when the returned permit is dropped.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, AdmissionError> {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            self.metrics.execute_tx_queue_wait_time_ms.observe(0);
+            return Ok(permit);
+        }
+        let _queue_slot = self.enter_queue()?;
+        let started_at = Instant::now();
+        let permit = tokio::time::timeout(
+            Duration::from_millis(self.config.queue_timeout_ms),
+            self.semaphore.clone().acquire_owned(),
+        )
+        .await;
+        self.metrics
+            .execute_tx_queue_wait_time_ms
+            .observe(started_at.elapsed().as_millis() as u64);
+        match permit {
+            Ok(permit) => Ok(permit.expect("The execution semaphore is never closed")),
+            Err(_) => Err(AdmissionError::QueueTimeout),
+        }
+    }
+
+    fn enter_queue(&self) -> Result<QueueSlot<'_>, AdmissionError> {
+        let max_queue_length = self.config.max_queue_length;
+        self.queue_length
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |length| {
+                (length < max_queue_length).then_some(length + 1)
+            })
+            .map_err(|_| AdmissionError::QueueFull)?;
+        self.metrics.execute_tx_queue_depth.inc();
+        Ok(QueueSlot { limiter: self })
+    }
+}
+
+/// Leaves the queue when dropped, also when the client disconnects while waiting.
+struct QueueSlot<'a> {
+    limiter: &'a ExecutionLimiter,
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.limiter.queue_length.fetch_sub(1, Ordering::SeqCst);
+        self.limiter.metrics.execute_tx_queue_depth.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_limiter(max_queue_length: usize, queue_timeout_ms: u64) -> ExecutionLimiter {
+        ExecutionLimiter::new(
+            ExecutionLimitConfig {
+                max_concurrent_executions: 1,
+                max_queue_length,
+                queue_timeout_ms,
+            },
+            GasStationRpcMetrics::new_for_testing(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_queued_execution_admitted_when_slot_released() {
+        let limiter = Arc::new(new_limiter(1, 5000));
+        let permit = limiter.acquire().await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire().await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(limiter.metrics.execute_tx_queue_depth.get(), 1);
+        // The queue is full
+        assert_eq!(
+            limiter.acquire().await.unwrap_err(),
+            AdmissionError::QueueFull
+        );
+
+        drop(permit);
+        queued.await.unwrap().unwrap();
+        assert_eq!(limiter.metrics.execute_tx_queue_depth.get(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queued_execution_times_out() {
+        let limiter = new_limiter(1, 50);
+        let _permit = limiter.acquire().await.unwrap();
+
+        assert_eq!(
+            limiter.acquire().await.unwrap_err(),
+            AdmissionError::QueueTimeout
+        );
+        assert_eq!(limiter.queue_length.load(Ordering::SeqCst), 0);
+        assert_eq!(limiter.metrics.execute_tx_queue_depth.get(), 0);
+    }
+}
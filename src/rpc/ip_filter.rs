@@ -0,0 +1,199 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filters the requests to the public endpoints by the IP address of the client, before they
+//! are authorized.
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::server::reject_request;
+use crate::metrics::GasStationRpcMetrics;
+use crate::rpc::rpc_types::{ErrorCode, RpcError};
+
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// A CIDR range like `10.0.0.0/8` or `2001:db8::/32`, or a single IP address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpRange(IpNet);
+
+impl IpRange {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.contains(&ip.to_canonical())
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(net) = s.parse::<IpNet>() {
+            return Ok(Self(net));
+        }
+        let ip = s
+            .parse::<IpAddr>()
+            .map_err(|_| anyhow::anyhow!("Invalid IP address or CIDR range: {}", s))?;
+        Ok(Self(IpNet::from(ip)))
+    }
+}
+
+impl TryFrom<String> for IpRange {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<IpRange> for String {
+    fn from(range: IpRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct IpFilterConfig {
+    /// Only clients in these ranges are allowed. Any client is allowed if empty.
+    pub allow: Vec<IpRange>,
+    /// Clients in these ranges are rejected, even if they are allowed by `allow`.
+    pub deny: Vec<IpRange>,
+    /// Proxies in front of the Gas Station. The client of a request coming from one of them is
+    /// taken from its `X-Forwarded-For` header.
+    pub trusted_proxies: Vec<IpRange>,
+}
+
+impl IpFilterConfig {
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        if self.deny.iter().any(|range| range.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|range| range.contains(ip))
+    }
+
+    /// The client of the request: the peer, or the last address in `X-Forwarded-For` that isn't a
+    /// trusted proxy when the peer is one. Addresses further left can be forged by the client.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted_proxy(&peer) {
+            return peer;
+        }
+        let forwarded_for: Vec<IpAddr> = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|ip| ip.trim().parse().ok())
+            .collect();
+        forwarded_for
+            .iter()
+            .rev()
+            .find(|ip| !self.is_trusted_proxy(ip))
+            .or(forwarded_for.first())
+            .copied()
+            .unwrap_or(peer)
+    }
+
+    fn is_trusted_proxy(&self, ip: &IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|range| range.contains(ip))
+    }
+}
+
+pub(crate) struct IpFilter {
+    pub config: IpFilterConfig,
+    pub metrics: Arc<GasStationRpcMetrics>,
+}
+
+pub(crate) async fn filter_ip<B>(
+    State(filter): State<Arc<IpFilter>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let client_ip = filter.config.client_ip(peer.ip(), request.headers());
+    if !filter.config.is_allowed(&client_ip) {
+        debug!("Rejected request from {}", client_ip);
+        filter.metrics.num_ip_filtered_requests.inc();
+        return reject_request(
+            StatusCode::FORBIDDEN,
+            request.uri().path(),
+            RpcError::new(ErrorCode::IpNotAllowed, "IP address not allowed"),
+        );
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(ranges: &[&str]) -> Vec<IpRange> {
+        ranges.iter().map(|range| range.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let config = IpFilterConfig {
+            allow: ranges(&["10.0.0.0/8", "2001:db8::/32"]),
+            deny: ranges(&["10.0.0.1"]),
+            ..Default::default()
+        };
+        assert!(config.is_allowed(&"10.1.2.3".parse().unwrap()));
+        assert!(config.is_allowed(&"2001:db8::1".parse().unwrap()));
+        // IPv4-mapped IPv6 addresses are matched as IPv4
+        assert!(config.is_allowed(&"::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!config.is_allowed(&"10.0.0.1".parse().unwrap()));
+        assert!(!config.is_allowed(&"192.168.0.1".parse().unwrap()));
+
+        let deny_only = IpFilterConfig {
+            deny: ranges(&["192.168.0.0/16"]),
+            ..Default::default()
+        };
+        assert!(deny_only.is_allowed(&"10.1.2.3".parse().unwrap()));
+        assert!(!deny_only.is_allowed(&"192.168.0.1".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let config = IpFilterConfig {
+            trusted_proxies: ranges(&["172.16.0.0/12"]),
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            FORWARDED_FOR_HEADER,
+            "1.1.1.1, 2.2.2.2, 172.16.0.2".parse().unwrap(),
+        );
+        // The client can only forge the addresses left of the one added by the trusted proxy
+        assert_eq!(
+            config.client_ip("172.16.0.1".parse().unwrap(), &headers),
+            "2.2.2.2".parse::<IpAddr>().unwrap()
+        );
+        // The header of untrusted peers is ignored
+        assert_eq!(
+            config.client_ip("3.3.3.3".parse().unwrap(), &headers),
+            "3.3.3.3".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            config.client_ip("172.16.0.1".parse().unwrap(), &HeaderMap::new()),
+            "172.16.0.1".parse::<IpAddr>().unwrap()
+        );
+    }
+}
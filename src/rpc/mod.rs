@@ -1,32 +1,51 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod auth_secret;
+pub mod body_limit;
 pub mod client;
+pub mod cors;
+pub mod execution_limiter;
+pub mod ip_filter;
+mod request_id;
 pub(crate) mod rpc_types;
 mod server;
+mod user_sig;
 
-pub use rpc_types::ExecuteTransactionRequestType;
+pub use rpc_types::{ErrorCode, ExecuteTransactionRequestType, RpcError};
 pub use server::GasStationServer;
 
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
+    use crate::access_controller::decision::Decision;
+    use crate::access_controller::explanation::{PredicateMatch, RuleExplanation, RuleOutcome};
     use crate::access_controller::policy::AccessPolicy;
-    use crate::access_controller::predicates::{ValueAggregate, ValueNumber};
+    use crate::access_controller::predicates::{LimitBy, ValueAggregate, ValueNumber};
     use crate::access_controller::rule::AccessRuleBuilder;
     use crate::access_controller::AccessController;
     use crate::config::GasStationConfig;
+    use crate::rpc::rpc_types::{
+        BuildAndReserveRequest, ErrorCode, TransactionIntent, UsageRequest, IDEMPOTENCY_KEY_HEADER,
+        MAX_BUDGET, MAX_DURATION_S, REQUEST_ID_HEADER,
+    };
     use crate::rpc::ExecuteTransactionRequestType;
     use crate::test_env::{
-        create_test_transaction, start_rpc_server_for_testing,
-        start_rpc_server_for_testing_no_auth, start_rpc_server_for_testing_with_access_controller,
-        DEFAULT_TEST_CONFIG_PATH,
+        create_test_transaction, random_address, sign_user_transaction,
+        start_rpc_server_for_testing, start_rpc_server_for_testing_no_auth,
+        start_rpc_server_for_testing_with_access_controller, DEFAULT_TEST_CONFIG_PATH,
     };
     use crate::AUTH_ENV_NAME;
     use iota_config::Config;
-    use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
+    use iota_json_rpc_types::{
+        IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponseOptions,
+    };
+    use iota_types::base_types::ObjectID;
     use iota_types::gas_coin::NANOS_PER_IOTA;
+    use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use iota_types::transaction::{TransactionDataAPI, TransactionKind};
+    use reqwest::header::HeaderMap;
 
     #[tokio::test]
     async fn test_basic_rpc_flow() {
@@ -50,6 +69,84 @@ mod tests {
         assert!(effects.status().is_ok());
     }
 
+    #[tokio::test]
+    async fn test_execute_tx_with_idempotency_key() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+
+        let (sponsor, reservation_id, gas_coins) = client
+            .reserve_gas_with_idempotency_key(NANOS_PER_IOTA, 10, Some("reserve".to_string()))
+            .await
+            .unwrap();
+        // The retried reservation is the same
+        let (_, retried_reservation_id, _) = client
+            .reserve_gas_with_idempotency_key(NANOS_PER_IOTA, 10, Some("reserve".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(retried_reservation_id, reservation_id);
+
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "execute".parse().unwrap());
+        let effects = client
+            .execute_tx(
+                reservation_id,
+                &tx_data,
+                &user_sig,
+                None,
+                Some(headers.clone()),
+            )
+            .await
+            .unwrap();
+        assert!(effects.status().is_ok());
+
+        // The retried transaction isn't executed again
+        let retried_effects = client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, Some(headers))
+            .await
+            .unwrap();
+        assert_eq!(retried_effects, effects);
+        assert!(client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_usage_report() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+        let admin_client = server.get_local_admin_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let effects = client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .unwrap();
+
+        let report = admin_client
+            .get_usage(&UsageRequest {
+                sender: Some(tx_data.sender()),
+                window: Some("1h".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.gas_used, effects.gas_cost_summary().gas_used());
+        assert_eq!(report.transaction_count, 1);
+        assert_eq!(report.window_secs, 3600);
+
+        // Either the sender or the package must be given.
+        assert!(admin_client
+            .get_usage(&UsageRequest::default())
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_invalid_auth() {
         let (_test_cluster, _container, server) =
@@ -66,6 +163,27 @@ mod tests {
         assert!(client.reserve_gas(NANOS_PER_IOTA, 10).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_request_id() {
+        let (_test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let url = format!("http://localhost:{}/", server.rpc_port);
+        let client = reqwest::Client::new();
+
+        // The request ID of the client is echoed
+        let response = client
+            .get(&url)
+            .header(REQUEST_ID_HEADER, "some-request-id")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "some-request-id");
+
+        // Otherwise one is generated
+        let response = client.get(&url).send().await.unwrap();
+        assert!(!response.headers()[REQUEST_ID_HEADER].is_empty());
+    }
+
     #[tokio::test]
     async fn test_no_auth() {
         let (_test_cluster, _container, server) =
@@ -105,6 +223,216 @@ mod tests {
             .is_err());
     }
 
+    #[tokio::test]
+    async fn test_v2_error_codes() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing_with_access_controller(
+                vec![NANOS_PER_IOTA; 10],
+                NANOS_PER_IOTA,
+                AccessController::new(AccessPolicy::DenyAll, []),
+            )
+            .await;
+        let client = server.get_local_client();
+
+        let response = client.reserve_gas_v2(MAX_BUDGET + 1, 10).await.unwrap();
+        assert_eq!(response.error.unwrap().code, ErrorCode::InvalidRequest);
+
+        let response = client.reserve_gas_v2(NANOS_PER_IOTA, 10).await.unwrap();
+        let result = response.result.unwrap();
+        let gas_coins = result
+            .gas_coins
+            .into_iter()
+            .map(|c| c.to_object_ref())
+            .collect();
+
+        let response = client
+            .reserve_gas_v2(NANOS_PER_IOTA * 10, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.error.unwrap().code,
+            ErrorCode::InsufficientPoolBalance
+        );
+
+        let (tx_data, user_sig) =
+            create_test_transaction(&test_cluster, result.sponsor_address, gas_coins).await;
+        let mut stale_tx_data = tx_data.clone();
+        stale_tx_data.gas_data_mut().budget -= 1;
+        let response = client
+            .execute_tx_v2(
+                result.reservation_id,
+                &stale_tx_data,
+                &user_sig,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, ErrorCode::InvalidUserSig);
+
+        let response = client
+            .execute_tx_v2(result.reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, ErrorCode::AccessDenied);
+        assert_eq!(error.message, "Access denied by access controller");
+
+        // The v1 endpoint keeps returning the plain message.
+        let err = client
+            .execute_tx(result.reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Access denied by access controller");
+    }
+
+    #[tokio::test]
+    async fn test_gas_data_error_codes() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (_, _, other_gas_coins) = client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+
+        let (tx_data, user_sig) =
+            create_test_transaction(&test_cluster, sponsor, other_gas_coins).await;
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, ErrorCode::GasPaymentMismatch);
+
+        let (mut tx_data, _) =
+            create_test_transaction(&test_cluster, sponsor, gas_coins.clone()).await;
+        tx_data.gas_data_mut().budget = NANOS_PER_IOTA + 1;
+        let user_sig = sign_user_transaction(&test_cluster, &tx_data);
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.error.unwrap().code,
+            ErrorCode::GasBudgetExceedsReservation
+        );
+
+        tx_data.gas_data_mut().owner = random_address();
+        let user_sig = sign_user_transaction(&test_cluster, &tx_data);
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, ErrorCode::GasOwnerMismatch);
+
+        // The reservation is left untouched by the failed requests
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let effects = client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .unwrap();
+        assert!(effects.status().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_tx() {
+        let rules = [AccessRuleBuilder::new()
+            .name("any-budget")
+            .gas_budget(ValueNumber::GreaterThan(0))
+            .deny()
+            .deny_message("Not sponsored")
+            .build()];
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing_with_access_controller(
+                vec![NANOS_PER_IOTA; 10],
+                NANOS_PER_IOTA,
+                AccessController::new(AccessPolicy::AllowAll, rules).with_allow_explain(true),
+            )
+            .await;
+        let client = server.get_local_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+
+        let result = client.check_tx(&tx_data, &user_sig, true).await.unwrap();
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(result.matched_rule.as_deref(), Some("any-budget"));
+        assert_eq!(result.deny_message.as_deref(), Some("Not sponsored"));
+        assert_eq!(
+            result.explanation,
+            Some(vec![RuleExplanation {
+                rule: "any-budget".to_string(),
+                predicates: vec![PredicateMatch::new("transaction-gas-budget", true)],
+                outcome: RuleOutcome::Decided,
+            }])
+        );
+
+        let result = client.check_tx(&tx_data, &user_sig, false).await.unwrap();
+        assert_eq!(result.decision, Decision::Deny);
+        assert_eq!(result.explanation, None);
+
+        // The check doesn't execute the transaction
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, ErrorCode::AccessDenied);
+        assert_eq!(error.explanation, None);
+    }
+
+    #[tokio::test]
+    async fn test_build_and_reserve() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+        let sponsor = client.sponsor_info().await.unwrap().sponsor_addresses[0];
+        let user = test_cluster
+            .get_addresses()
+            .into_iter()
+            .find(|address| *address != sponsor)
+            .unwrap();
+        let object = test_cluster
+            .wallet
+            .get_one_gas_object_owned_by_address(user)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let request = BuildAndReserveRequest {
+            sender: user,
+            intent: TransactionIntent::TransferObjects {
+                object_ids: vec![object.0],
+                recipient: user,
+            },
+            gas_budget: None,
+            reserve_duration_secs: 10,
+            idempotency_key: None,
+        };
+        let (reservation_id, tx_data) = client.build_and_reserve(&request).await.unwrap();
+        assert_eq!(tx_data.sender(), user);
+        assert_eq!(tx_data.gas_owner(), sponsor);
+        assert!(tx_data.gas_budget() > 0);
+
+        let user_sig = sign_user_transaction(&test_cluster, &tx_data);
+        let effects = client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .unwrap();
+        assert!(effects.status().is_ok());
+
+        // The objects are resolved before reserving the gas coins
+        let request = BuildAndReserveRequest {
+            intent: TransactionIntent::TransferObjects {
+                object_ids: vec![ObjectID::random()],
+                recipient: user,
+            },
+            ..request
+        };
+        assert!(client.build_and_reserve(&request).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_access_allow_after_ac_reload() {
         let reservation_time_secs = 5;
@@ -136,7 +464,11 @@ mod tests {
         gas_station_config.access_controller = new_access_controller;
         gas_station_config.save(DEFAULT_TEST_CONFIG_PATH).unwrap();
 
-        client.reload_access_controller().await.unwrap();
+        server
+            .get_local_admin_client()
+            .reload_access_controller()
+            .await
+            .unwrap();
 
         let (sponsor, reservation_id, gas_coins) = client
             .reserve_gas(NANOS_PER_IOTA, reservation_time_secs)
@@ -188,13 +520,122 @@ mod tests {
             .is_err());
     }
 
+    #[tokio::test]
+    async fn test_limits() {
+        let rules = [AccessRuleBuilder::new()
+            .gas_limit(
+                ValueAggregate::new(
+                    Duration::from_secs(60),
+                    ValueNumber::GreaterThanOrEqual(NANOS_PER_IOTA),
+                )
+                .with_count_by(vec![LimitBy::SenderAddress]),
+            )
+            .deny()
+            .build()];
+
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing_with_access_controller(
+                vec![NANOS_PER_IOTA; 10],
+                NANOS_PER_IOTA,
+                AccessController::new(AccessPolicy::AllowAll, rules),
+            )
+            .await;
+        let client = server.get_local_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let effects = client
+            .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+            .await
+            .unwrap();
+        let gas_used = effects.gas_cost_summary().gas_used();
+
+        let response = client.get_limits(tx_data.sender()).await.unwrap();
+        assert_eq!(response.sender, tx_data.sender());
+        assert_eq!(response.limits.len(), 1);
+        let limit = &response.limits[0];
+        assert_eq!(limit.rule_number, 1);
+        assert_eq!(limit.window_secs, 60);
+        assert_eq!(limit.limit, format!(">={}", NANOS_PER_IOTA));
+        assert_eq!(limit.gas_usage, gas_used);
+        assert_eq!(limit.remaining, NANOS_PER_IOTA - gas_used);
+        assert!(limit.resets_in_secs.is_some());
+
+        // Nothing was counted for another sender yet.
+        let response = client.get_limits(random_address()).await.unwrap();
+        assert_eq!(response.limits[0].gas_usage, 0);
+        assert_eq!(response.limits[0].resets_in_secs, None);
+
+        let admin_client = server.get_local_admin_client();
+        let limit = admin_client
+            .adjust_limit(tx_data.sender(), 1, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(limit.gas_usage, gas_used - 1);
+        let limit = admin_client
+            .adjust_limit(tx_data.sender(), 1, None)
+            .await
+            .unwrap();
+        assert_eq!(limit.gas_usage, 0);
+        assert_eq!(limit.remaining, NANOS_PER_IOTA);
+        // There is no second rule.
+        assert!(admin_client
+            .adjust_limit(tx_data.sender(), 2, None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sponsor_info() {
+        let (_test_cluster, container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+
+        let info = client.sponsor_info().await.unwrap();
+        assert_eq!(info.sponsor_addresses, vec![container.get_signer_address()]);
+        assert!(info.reference_gas_price > 0);
+        assert_eq!(info.max_budget, MAX_BUDGET);
+        assert_eq!(info.max_duration_secs, MAX_DURATION_S);
+        assert_eq!(info.access_policy, AccessPolicy::Disabled);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+        let sender = test_cluster.get_address_0();
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.transfer_iota(sender, Some(1));
+        let tx_kind = TransactionKind::programmable(builder.finish());
+
+        let estimate = client.estimate_gas(sender, &tx_kind).await.unwrap();
+        assert!(estimate.reference_gas_price > 0);
+        assert!(estimate.gas_budget >= estimate.computation_cost + estimate.storage_cost);
+    }
+
+    #[tokio::test]
+    async fn test_readyz() {
+        let (_test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        server.get_local_client().readyz().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_debug_health_check() {
         let (_test_cluster, _container, server) =
             start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
 
-        let client = server.get_local_client();
+        let client = server.get_local_admin_client();
         client.debug_health_check().await.unwrap();
+
+        // The admin endpoints are not exposed on the public listener.
+        assert!(server
+            .get_local_client()
+            .debug_health_check()
+            .await
+            .is_err());
     }
 
     #[tokio::test]
@@ -251,5 +692,140 @@ mod tests {
             .await
             .unwrap();
         assert!(effects.status().is_ok());
+
+        // The fullnode already knows the transaction when the response is received.
+        test_cluster
+            .iota_client()
+            .read_api()
+            .get_transaction_with_options(
+                *effects.transaction_digest(),
+                IotaTransactionBlockResponseOptions::new(),
+            )
+            .await
+            .unwrap();
+    }
+    #[tokio::test]
+    async fn test_execute_tx_with_options() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let response = client
+            .execute_tx_with_options(
+                reservation_id,
+                &tx_data,
+                &user_sig,
+                None,
+                Some(
+                    IotaTransactionBlockResponseOptions::new()
+                        .with_object_changes()
+                        .with_balance_changes(),
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(response.effects.unwrap().status().is_ok());
+        assert!(!response.object_changes.unwrap().is_empty());
+        assert!(!response.balance_changes.unwrap().is_empty());
+        assert!(response.events.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hold_and_approve_transaction() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing_with_access_controller(
+                vec![NANOS_PER_IOTA; 10],
+                NANOS_PER_IOTA,
+                AccessController::new(
+                    AccessPolicy::DenyAll,
+                    [AccessRuleBuilder::new().name("review").hold().build()],
+                ),
+            )
+            .await;
+        let client = server.get_local_client();
+        let admin_client = server.get_local_admin_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, ErrorCode::PendingApproval);
+        assert_eq!(error.matched_rule.as_deref(), Some("review"));
+
+        // The reservation is kept alive beyond its requested duration.
+        tokio::time::sleep(Duration::from_secs(12)).await;
+        let held = admin_client.list_held_transactions().await.unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].transaction_digest, tx_data.digest());
+        assert_eq!(held[0].reservation_id, reservation_id);
+
+        let response = admin_client
+            .approve_transaction(tx_data.digest())
+            .await
+            .unwrap();
+        assert!(response.effects.unwrap().status().is_ok());
+        assert!(admin_client
+            .list_held_transactions()
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            admin_client
+                .approve_transaction(tx_data.digest())
+                .await
+                .unwrap()
+                .error
+                .unwrap()
+                .code,
+            ErrorCode::InvalidRequest
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hold_and_reject_transaction() {
+        let (test_cluster, _container, server) =
+            start_rpc_server_for_testing_with_access_controller(
+                vec![NANOS_PER_IOTA; 10],
+                NANOS_PER_IOTA,
+                AccessController::new(
+                    AccessPolicy::DenyAll,
+                    [AccessRuleBuilder::new().hold().build()],
+                ),
+            )
+            .await;
+        let client = server.get_local_client();
+        let admin_client = server.get_local_admin_client();
+
+        let (sponsor, reservation_id, gas_coins) =
+            client.reserve_gas(NANOS_PER_IOTA, 10).await.unwrap();
+        let (tx_data, user_sig) = create_test_transaction(&test_cluster, sponsor, gas_coins).await;
+        let response = client
+            .execute_tx_v2(reservation_id, &tx_data, &user_sig, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, ErrorCode::PendingApproval);
+
+        let held = admin_client
+            .reject_transaction(tx_data.digest())
+            .await
+            .unwrap();
+        assert_eq!(held.transaction_digest, tx_data.digest());
+        assert!(admin_client
+            .list_held_transactions()
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(admin_client
+            .reject_transaction(tx_data.digest())
+            .await
+            .is_err());
     }
 }
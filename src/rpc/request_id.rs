@@ -0,0 +1,61 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Every request is identified by the `X-Request-Id` header of the client, or by a generated ID
+//! if the client didn't send one. The ID is attached to the logs of the request, forwarded to the
+//! hooks and echoed in the response, so that a request can be followed across the systems.
+
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::{info_span, Instrument};
+
+use crate::rpc::rpc_types::REQUEST_ID_HEADER;
+
+/// Longer request IDs of the clients are replaced by a generated one.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+pub(crate) async fn propagate_request_id<B>(mut request: Request<B>, next: Next<B>) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|request_id| is_valid_request_id(request_id))
+        .map(|request_id| request_id.to_string())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let header_value =
+        HeaderValue::from_str(&request_id).expect("Request IDs are valid header values");
+    // The handlers read the ID from the headers, like any other header of the request.
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value.clone());
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        path = %request.uri().path(),
+    );
+    let mut response = next.run(request).instrument(span).await;
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value);
+    response
+}
+
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("0b6c5a3e-3f4d-4d0e-9a57-1a2f6b8c9d10"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("with space"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+    }
+}
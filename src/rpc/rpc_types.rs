@@ -1,11 +1,19 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::types::ReservationID;
+use crate::access_controller::decision::Decision;
+use crate::access_controller::explanation::RuleExplanation;
+use crate::access_controller::policy::AccessPolicy;
+use crate::types::{InitProgress, ReservationID};
 use fastcrypto::encoding::Base64;
-use iota_json_rpc_types::{IotaObjectRef, IotaTransactionBlockEffects};
+use iota_json_rpc_types::{
+    BalanceChange, IotaObjectRef, IotaTransactionBlockEffects, IotaTransactionBlockEvents,
+    IotaTransactionBlockResponse, IotaTransactionBlockResponseOptions, IotaTypeTag, ObjectChange,
+};
+use iota_sdk::json::IotaJsonValue;
 use iota_types::{
-    base_types::{IotaAddress, ObjectRef},
+    base_types::{IotaAddress, ObjectID, ObjectRef},
+    digests::TransactionDigest,
     quorum_driver_types::ExecuteTransactionRequestType as IotaExecuteTransactionRequestType,
 };
 use schemars::JsonSchema;
@@ -17,39 +25,229 @@ pub const MAX_BUDGET: u64 = 2_000_000_000;
 // 10 mins.
 pub const MAX_DURATION_S: u64 = 10 * 60;
 
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 128;
+
+/// Header carrying the idempotency key of an `execute_tx` request, instead of its
+/// `idempotency_key` field.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header identifying a request in the logs of the Gas Station and of its hooks. Generated if
+/// the client doesn't send it, and returned in the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header identifying the client of a reservation in the expired reservation stats, e.g. the name
+/// of its API key. The IP address of the client is used if not set.
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Longer client IDs are truncated.
+pub const MAX_CLIENT_ID_LENGTH: usize = 128;
+
 #[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
 pub struct ReserveGasRequest {
     pub gas_budget: u64,
     pub reserve_duration_secs: u64,
+    /// Unique key chosen by the client. Retrying the request with the same key returns the same
+    /// reservation until it expires, instead of reserving new coins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 impl ReserveGasRequest {
     pub fn check_validity(&self) -> anyhow::Result<()> {
-        if self.gas_budget == 0 {
-            anyhow::bail!("Gas budget must be positive");
-        }
-        if self.gas_budget > MAX_BUDGET {
-            anyhow::bail!("Gas budget must be less than {}", MAX_BUDGET);
-        }
-        if self.reserve_duration_secs == 0 {
-            anyhow::bail!("Reserve duration must be positive");
-        }
-        if self.reserve_duration_secs > MAX_DURATION_S {
+        check_gas_budget(self.gas_budget)?;
+        check_reserve_duration(self.reserve_duration_secs)?;
+        check_idempotency_key(self.idempotency_key.as_deref())
+    }
+}
+
+pub fn check_gas_budget(gas_budget: u64) -> anyhow::Result<()> {
+    if gas_budget == 0 {
+        anyhow::bail!("Gas budget must be positive");
+    }
+    if gas_budget > MAX_BUDGET {
+        anyhow::bail!("Gas budget must be less than {}", MAX_BUDGET);
+    }
+    Ok(())
+}
+
+fn check_reserve_duration(reserve_duration_secs: u64) -> anyhow::Result<()> {
+    if reserve_duration_secs == 0 {
+        anyhow::bail!("Reserve duration must be positive");
+    }
+    if reserve_duration_secs > MAX_DURATION_S {
+        anyhow::bail!(
+            "Reserve duration must be less than {} seconds",
+            MAX_DURATION_S
+        );
+    }
+    Ok(())
+}
+
+fn check_idempotency_key(idempotency_key: Option<&str>) -> anyhow::Result<()> {
+    if let Some(idempotency_key) = idempotency_key {
+        if idempotency_key.is_empty() || idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
             anyhow::bail!(
-                "Reserve duration must be less than {} seconds",
-                MAX_DURATION_S
+                "Idempotency key must have between 1 and {} characters",
+                MAX_IDEMPOTENCY_KEY_LENGTH
             );
         }
+    }
+    Ok(())
+}
+
+/// Body of `/v1/build_and_reserve`: what the sender wants to do. The Gas Station builds the
+/// transaction with gas coins reserved for it, so that the sender only has to sign it.
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
+pub struct BuildAndReserveRequest {
+    pub sender: IotaAddress,
+    pub intent: TransactionIntent,
+    /// Estimated with a dry run of the transaction if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_budget: Option<u64>,
+    pub reserve_duration_secs: u64,
+    /// Same as the `idempotency_key` of `/v1/reserve_gas`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+impl BuildAndReserveRequest {
+    pub fn check_validity(&self) -> anyhow::Result<()> {
+        if let Some(gas_budget) = self.gas_budget {
+            check_gas_budget(gas_budget)?;
+        }
+        check_reserve_duration(self.reserve_duration_secs)?;
+        check_idempotency_key(self.idempotency_key.as_deref())?;
+        if let TransactionIntent::TransferObjects { object_ids, .. } = &self.intent {
+            if object_ids.is_empty() {
+                anyhow::bail!("At least one object must be transferred");
+            }
+        }
         Ok(())
     }
 }
 
+/// What a transaction built by the Gas Station does. The gas coins can't be used by it.
+#[derive(Clone, Debug, PartialEq, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionIntent {
+    /// Calls a Move function. The arguments are JSON values like in the `unsafe_moveCall`
+    /// method of the fullnode, the objects given by their ID.
+    MoveCall {
+        package: ObjectID,
+        module: String,
+        function: String,
+        #[serde(default)]
+        type_arguments: Vec<IotaTypeTag>,
+        #[serde(default)]
+        arguments: Vec<IotaJsonValue>,
+    },
+    /// Transfers objects owned by the sender.
+    TransferObjects {
+        object_ids: Vec<ObjectID>,
+        recipient: IotaAddress,
+    },
+}
+
 #[derive(Debug, JsonSchema, Serialize, Deserialize)]
-pub struct ReserveGasResponse {
+pub struct BuildAndReserveResult {
+    pub sponsor_address: IotaAddress,
+    pub reservation_id: ReservationID,
+    pub gas_budget: u64,
+    /// Base64 encoded BCS serialized `TransactionData`, to be signed by the sender and sent to
+    /// `execute_tx` with the reservation ID.
+    pub tx_bytes: Base64,
+}
+
+/// Stable codes of the errors returned by the `/v2` endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Unauthorized,
+    /// The IP address of the client is not allowed by the IP filter.
+    IpNotAllowed,
+    InvalidRequest,
+    /// The request body exceeds the configured size, after decompression.
+    PayloadTooLarge,
+    InvalidUserSig,
+    /// The gas owner of the transaction is not the sponsor of the reservation.
+    GasOwnerMismatch,
+    /// The gas payment of the transaction is not the coins of the reservation, at the versions
+    /// they were reserved at.
+    GasPaymentMismatch,
+    /// The gas budget of the transaction exceeds the budget of the reservation.
+    GasBudgetExceedsReservation,
+    InsufficientPoolBalance,
+    PoolDraining,
+    GasUsageCapExceeded,
+    ReservationExpired,
+    AccessDenied,
+    /// The transaction is held until an operator approves or rejects it.
+    PendingApproval,
+    AccessCheckFailed,
+    /// Too many transactions are being executed, the request can be retried later.
+    TooManyRequests,
+    ExecutionFailed,
+    InternalError,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Message meant for the end user, e.g. given by the hook that denied the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+    /// The access controller rule that denied the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+    /// Why each access controller rule denied or held the transaction or didn't, when requested
+    /// with `explain=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Vec<RuleExplanation>>,
+}
+
+impl RpcError {
+    pub fn new(code: ErrorCode, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            user_message: None,
+            matched_rule: None,
+            explanation: None,
+        }
+    }
+
+    pub fn with_user_message(mut self, user_message: Option<String>) -> Self {
+        self.user_message = user_message;
+        self
+    }
+
+    pub fn with_matched_rule(mut self, matched_rule: Option<String>) -> Self {
+        self.matched_rule = matched_rule;
+        self
+    }
+
+    pub fn with_explanation(mut self, explanation: Option<Vec<RuleExplanation>>) -> Self {
+        self.explanation = explanation;
+        self
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Response of `/v1/reserve_gas`, or of `/v2/reserve_gas` with an [`RpcError`].
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct ReserveGasResponse<E = String> {
     pub result: Option<ReserveGasResult>,
-    pub error: Option<String>,
+    pub error: Option<E>,
 }
 
+pub type ReserveGasResponseV2 = ReserveGasResponse<RpcError>;
+
 #[derive(Debug, JsonSchema, Serialize, Deserialize)]
 pub struct ReserveGasResult {
     pub sponsor_address: IotaAddress,
@@ -57,7 +255,7 @@ pub struct ReserveGasResult {
     pub gas_coins: Vec<IotaObjectRef>,
 }
 
-impl ReserveGasResponse {
+impl<E> ReserveGasResponse<E> {
     pub fn new_ok(
         sponsor_address: IotaAddress,
         reservation_id: ReservationID,
@@ -72,7 +270,9 @@ impl ReserveGasResponse {
             error: None,
         }
     }
+}
 
+impl ReserveGasResponse {
     pub fn new_err(error: anyhow::Error) -> Self {
         Self {
             result: None,
@@ -81,15 +281,41 @@ impl ReserveGasResponse {
     }
 }
 
+impl ReserveGasResponseV2 {
+    pub fn new_rpc_err(error: RpcError) -> Self {
+        Self {
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl From<ReserveGasResponseV2> for ReserveGasResponse {
+    fn from(response: ReserveGasResponseV2) -> Self {
+        Self {
+            result: response.result,
+            error: response.error.map(|error| error.message),
+        }
+    }
+}
+
 #[derive(Debug, JsonSchema, Serialize, Deserialize)]
 pub struct ExecuteTxRequest {
     pub reservation_id: ReservationID,
     pub tx_bytes: Base64,
     pub user_sig: Base64,
     pub request_type: Option<ExecuteTransactionRequestType>,
+    /// What the response includes in addition to the effects, e.g. the events, the object
+    /// changes or the balance changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<IotaTransactionBlockResponseOptions>,
+    /// Unique key chosen by the client. Once the transaction is executed, retrying the request
+    /// with the same key returns the same response instead of executing it again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum ExecuteTransactionRequestType {
     WaitForEffectsCert,
@@ -109,28 +335,289 @@ impl Into<Option<IotaExecuteTransactionRequestType>> for ExecuteTransactionReque
     }
 }
 
+/// Response of `/v1/execute_tx`, or of `/v2/execute_tx` with an [`RpcError`].
 #[derive(Debug, JsonSchema, Serialize, Deserialize)]
-pub struct ExecuteTxResponse {
+pub struct ExecuteTxResponse<E = String> {
     pub effects: Option<IotaTransactionBlockEffects>,
-    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<IotaTransactionBlockEvents>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_changes: Option<Vec<ObjectChange>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_changes: Option<Vec<BalanceChange>>,
+    pub error: Option<E>,
+    /// Message intended for the end user, given by the hook that denied the transaction. Only
+    /// used by `/v1/execute_tx`, `/v2/execute_tx` includes it in the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+    /// The access controller rule that denied the transaction. Only used by `/v1/execute_tx`,
+    /// `/v2/execute_tx` includes it in the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+    /// Explanation of the access decision, when requested with `explain=true`. Only used by
+    /// `/v1/execute_tx`, `/v2/execute_tx` includes it in the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Vec<RuleExplanation>>,
 }
 
-impl ExecuteTxResponse {
+pub type ExecuteTxResponseV2 = ExecuteTxResponse<RpcError>;
+
+impl<E> ExecuteTxResponse<E> {
     pub fn new_ok(effects: IotaTransactionBlockEffects) -> Self {
         Self {
             effects: Some(effects),
+            events: None,
+            object_changes: None,
+            balance_changes: None,
             error: None,
+            user_message: None,
+            matched_rule: None,
+            explanation: None,
         }
     }
 
+    /// Includes the parts of the transaction response that were requested with the options.
+    pub fn with_response(mut self, response: IotaTransactionBlockResponse) -> Self {
+        self.events = response.events;
+        self.object_changes = response.object_changes;
+        self.balance_changes = response.balance_changes;
+        self
+    }
+}
+
+impl ExecuteTxResponse {
     pub fn new_err(error: anyhow::Error) -> Self {
         Self {
             effects: None,
+            events: None,
+            object_changes: None,
+            balance_changes: None,
             error: Some(error.to_string()),
+            user_message: None,
+            matched_rule: None,
+            explanation: None,
+        }
+    }
+}
+
+impl ExecuteTxResponseV2 {
+    pub fn new_rpc_err(error: RpcError) -> Self {
+        Self {
+            effects: None,
+            events: None,
+            object_changes: None,
+            balance_changes: None,
+            error: Some(error),
+            user_message: None,
+            matched_rule: None,
+            explanation: None,
+        }
+    }
+}
+
+impl From<ExecuteTxResponseV2> for ExecuteTxResponse {
+    fn from(response: ExecuteTxResponseV2) -> Self {
+        let (error, user_message, matched_rule, explanation) = match response.error {
+            Some(error) => (
+                Some(error.message),
+                error.user_message,
+                error.matched_rule,
+                error.explanation,
+            ),
+            None => (None, None, None, None),
+        };
+        Self {
+            effects: response.effects,
+            events: response.events,
+            object_changes: response.object_changes,
+            balance_changes: response.balance_changes,
+            error,
+            user_message,
+            matched_rule,
+            explanation,
         }
     }
 }
 
+/// Query of `/v1/execute_tx`, `/v2/execute_tx` and `/v1/check_tx`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExplainQuery {
+    /// Returns why each access controller rule made the decision or didn't. The explanation
+    /// names the rules and their predicates.
+    #[serde(default)]
+    pub explain: bool,
+}
+
+/// Body of `/v1/check_tx`: a transaction signed by the user, checked by the access controller
+/// without being executed.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct CheckTxRequest {
+    pub tx_bytes: Base64,
+    pub user_sig: Base64,
+}
+
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct CheckTxResult {
+    pub decision: Decision,
+    /// The rule that made the decision, `None` for the access policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Vec<RuleExplanation>>,
+}
+
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct EstimateGasRequest {
+    pub sender: IotaAddress,
+    /// Base64 encoded BCS serialized `TransactionKind`.
+    pub tx_kind_bytes: Base64,
+}
+
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct EstimateGasResult {
+    /// Recommended gas budget, including a safety margin on top of the dry-run cost.
+    pub gas_budget: u64,
+    pub reference_gas_price: u64,
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    pub storage_rebate: u64,
+}
+
+/// Information about the sponsor and the limits of the gas station, allowing client SDKs to
+/// configure themselves.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct SponsorInfo {
+    pub sponsor_addresses: Vec<IotaAddress>,
+    pub reference_gas_price: u64,
+    pub max_budget: u64,
+    pub max_duration_secs: u64,
+    pub access_policy: AccessPolicy,
+}
+
+/// Query of `/v1/usage`. Exactly one of `sender` and `package` must be set.
+#[derive(Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UsageRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<IotaAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<IotaAddress>,
+    /// Length of the reported period, ending now, e.g. `24h` or `7d`. Defaults to `24h`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+}
+
+/// Gas actually used by the sponsored transactions of a sender or of a Move package.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct UsageReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<IotaAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<IotaAddress>,
+    pub window_secs: u64,
+    pub gas_used: u64,
+    pub transaction_count: u64,
+}
+
+/// Query of `/v1/limits`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct LimitsRequest {
+    pub sender: IotaAddress,
+}
+
+/// Gas usage limits of the access controller rules that apply to a sender.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct LimitsResponse {
+    pub sender: IotaAddress,
+    pub limits: Vec<RuleLimit>,
+}
+
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct RuleLimit {
+    /// Position of the rule in the access controller config, starting at 1.
+    pub rule_number: usize,
+    pub window_secs: u64,
+    /// Condition of the rule on the gas usage, e.g. `>=1000000000`.
+    pub limit: String,
+    /// Gas counted within the current window. Includes the budget of transactions that are
+    /// not confirmed yet.
+    pub gas_usage: u64,
+    /// Gas left until the gas usage reaches the limit.
+    pub remaining: u64,
+    /// Not set if nothing was counted yet, the window then starts with the next transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resets_in_secs: Option<u64>,
+}
+
+/// Body of `/v1/adjust_limit`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct AdjustLimitRequest {
+    pub sender: IotaAddress,
+    /// Position of the rule in the access controller config, starting at 1.
+    pub rule_number: usize,
+    /// Gas subtracted from the gas usage. The gas usage is reset if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit: Option<u64>,
+}
+
+/// Body of `/v1/rotate_auth_secret`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct RotateAuthSecretRequest {
+    pub new_secret: String,
+    /// How long the current secret is still accepted, in seconds. Defaults to 600.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grace_period_secs: Option<u64>,
+}
+
+/// Query of `/v1/expired_reservations`.
+#[derive(Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct ExpiredReservationsRequest {
+    /// Maximum number of clients to return. Defaults to 10.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Response of `GET /v1/coin_init`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct CoinInitStatus {
+    /// Target balance of newly initialized coins. None if the coin initializer is not running.
+    pub target_init_balance: Option<u64>,
+    /// Progress of the ongoing or interrupted initialization, if any.
+    pub progress: Option<InitProgress>,
+}
+
+/// Body of `POST /v1/coin_init`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct SetCoinInitRequest {
+    pub target_init_balance: u64,
+}
+
+/// Body of `/v1/drain_pool`.
+#[derive(Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct DrainPoolRequest {
+    /// Where to send the available coins. They are merged into large coins owned by the sponsor
+    /// if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<IotaAddress>,
+}
+
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct DrainPoolResponse {
+    pub recipient: IotaAddress,
+    pub drained_coin_count: usize,
+    /// Balance received by the recipient, net of the gas used to transfer it.
+    pub transferred_balance: u64,
+}
+
+/// Body of `/v1/approve_transaction` and `/v1/reject_transaction`.
+#[derive(Debug, JsonSchema, Serialize, Deserialize)]
+pub struct HeldTransactionRequest {
+    pub transaction_digest: TransactionDigest,
+}
+
 #[derive(Debug, JsonSchema, Serialize, Deserialize)]
 pub struct GasStationResponse<D = ()> {
     #[serde(skip_serializing_if = "Option::is_none")]
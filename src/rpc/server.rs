@@ -2,62 +2,127 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::access_controller::decision::Decision;
+use crate::access_controller::held_transactions::{self, HeldTransaction, HOLD_DURATION};
 use crate::access_controller::rule::TransactionContext;
-use crate::access_controller::{AccessController, TransactionExecutionResult};
-use crate::config::GasStationConfig;
-use crate::errors::generate_event_id;
+use crate::access_controller::{
+    AccessController, AccessDecision, GasUsageAdjustment, GasUsageLimit, TransactionExecutionResult,
+};
+use crate::config_reloader::ConfigReloader;
+use crate::errors::{generate_event_id, GasStationError};
+use crate::gas_station::gas_data::GasDataError;
 use crate::gas_station::gas_station_core::GasStation;
-use crate::logging::TxLogMessage;
+use crate::logging::{TxLogRecord, TxLogger};
 use crate::metrics::GasStationRpcMetrics;
+use crate::rpc::auth_secret::{AuthSecret, DEFAULT_AUTH_GRACE_PERIOD};
+use crate::rpc::body_limit::{limit_body, BodyLimitConfig};
 use crate::rpc::client::GasStationRpcClient;
+use crate::rpc::cors::{handle_cors, CorsConfig};
+use crate::rpc::execution_limiter::{ExecutionLimitConfig, ExecutionLimiter};
+use crate::rpc::ip_filter::{filter_ip, IpFilter, IpFilterConfig};
+use crate::rpc::request_id::propagate_request_id;
 use crate::rpc::rpc_types::{
-    ExecuteTxRequest, ExecuteTxResponse, GasStationResponse, ReserveGasRequest, ReserveGasResponse,
+    check_gas_budget, AdjustLimitRequest, BuildAndReserveRequest, BuildAndReserveResult,
+    CheckTxRequest, CheckTxResult, CoinInitStatus, DrainPoolRequest, DrainPoolResponse, ErrorCode,
+    EstimateGasRequest, EstimateGasResult, ExecuteTransactionRequestType, ExecuteTxRequest,
+    ExecuteTxResponse, ExecuteTxResponseV2, ExpiredReservationsRequest, ExplainQuery,
+    GasStationResponse, HeldTransactionRequest, LimitsRequest, LimitsResponse, ReserveGasRequest,
+    ReserveGasResponse, ReserveGasResponseV2, RotateAuthSecretRequest, RpcError, RuleLimit,
+    SetCoinInitRequest, SponsorInfo, UsageReport, UsageRequest, CLIENT_ID_HEADER,
+    IDEMPOTENCY_KEY_HEADER, MAX_BUDGET, MAX_CLIENT_ID_LENGTH, MAX_DURATION_S,
+    MAX_IDEMPOTENCY_KEY_LENGTH,
 };
+use crate::rpc::user_sig::verify_user_sig;
+use crate::tracker::scoped_gas_usage_caps::{is_scoped_gas_usage_cap_exceeded, ScopedGasUsageCaps};
+use crate::tracker::usage_report::{UsageScope, MAX_USAGE_REPORT_WINDOW};
 use crate::tracker::StatsTracker;
-use crate::{read_auth_env, VERSION};
+use crate::tx_signer::SigningContext;
+use crate::types::ReservationID;
+use crate::VERSION;
 use arc_swap::ArcSwap;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Query};
 use axum::headers::authorization::Bearer;
 use axum::headers::Authorization;
 use axum::http::{HeaderMap, StatusCode};
-use axum::response::IntoResponse;
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router, TypedHeader};
 use fastcrypto::encoding::Base64;
-use iota_config::Config;
-use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
+use futures_util::future::try_join_all;
+use iota_json_rpc_types::{IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponseOptions};
+use iota_types::base_types::IotaAddress;
 use iota_types::crypto::ToFromBytes;
+use iota_types::digests::TransactionDigest;
 use iota_types::signature::GenericSignature;
-use iota_types::transaction::TransactionData;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::path::PathBuf;
+use iota_types::transaction::{TransactionData, TransactionDataAPI, TransactionKind};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, SocketAddr, TcpListener};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::task::JoinHandle;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, instrument, warn};
+
+const DEFAULT_USAGE_WINDOW: Duration = Duration::from_secs(60 * 60 * 24);
+
+const DEFAULT_EXPIRED_RESERVATIONS_LIMIT: usize = 10;
+
+/// How long the response of a transaction executed with an idempotency key is kept for the
+/// retries of its request.
+const EXECUTED_TRANSACTION_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Response of an executed transaction, stored under the idempotency key of its request.
+#[derive(Serialize, Deserialize)]
+struct ExecutedTransaction {
+    transaction_digest: TransactionDigest,
+    response: ExecuteTxResponseV2,
+}
 
 pub struct GasStationServer {
     pub handle: JoinHandle<()>,
     pub rpc_port: u16,
+    pub admin_port: u16,
 }
 
 impl GasStationServer {
     pub async fn new(
         station: Arc<GasStation>,
-        host_ip: Ipv4Addr,
+        host_ips: Vec<IpAddr>,
         rpc_port: u16,
+        admin_host_ips: Vec<IpAddr>,
+        admin_port: u16,
         metrics: Arc<GasStationRpcMetrics>,
         access_controller: Arc<ArcSwap<AccessController>>,
         stats_tracker: StatsTracker,
-        config_path: PathBuf,
+        scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+        tx_logger: TxLogger,
+        config_reloader: Arc<ConfigReloader>,
+        execution_limit: Option<ExecutionLimitConfig>,
+        cors: Option<CorsConfig>,
+        ip_filter: Option<IpFilterConfig>,
+        body_limit: BodyLimitConfig,
     ) -> Self {
+        let execution_limiter =
+            execution_limit.map(|config| Arc::new(ExecutionLimiter::new(config, metrics.clone())));
+        let client_ip_config = ip_filter.clone().unwrap_or_default();
+        let ip_filter = ip_filter.map(|config| {
+            Arc::new(IpFilter {
+                config,
+                metrics: metrics.clone(),
+            })
+        });
         let state = ServerState::new(
             station,
             metrics,
             access_controller,
             stats_tracker,
-            config_path,
+            scoped_gas_usage_caps,
+            tx_logger,
+            config_reloader,
+            execution_limiter,
+            client_ip_config,
         );
-        if state.secret.is_none() {
+        if !state.secret.is_enabled() {
             warn!(
                 "⚠️  {} environment variable is not set. Authorization is disabled! ⚠️",
                 crate::AUTH_ENV_NAME
@@ -66,40 +131,111 @@ impl GasStationServer {
         let app = Router::new()
             .route("/", get(health))
             .route("/version", get(version))
-            .route("/debug_health_check", post(debug_health_check))
+            .route("/readyz", get(readyz))
             .route("/v1/reserve_gas", post(reserve_gas))
             .route("/v1/execute_tx", post(execute_tx))
+            .route("/v1/estimate_gas", post(estimate_gas))
+            .route("/v1/sponsor", get(sponsor_info))
+            .route("/v1/limits", get(limits))
+            .route("/v1/check_tx", post(check_tx))
+            .route("/v1/build_and_reserve", post(build_and_reserve))
+            .route("/v2/reserve_gas", post(reserve_gas_v2))
+            .route("/v2/execute_tx", post(execute_tx_v2))
+            .layer(Extension(state.clone()))
+            // Replaced by the limits of `body_limit`, which also apply to compressed bodies.
+            .layer(DefaultBodyLimit::disable())
+            .layer(middleware::from_fn_with_state(
+                Arc::new(body_limit),
+                limit_body,
+            ))
+            .layer(middleware::from_fn(propagate_request_id));
+        // Checked before the authorization, by the handlers.
+        let app = match ip_filter {
+            Some(ip_filter) => app.layer(middleware::from_fn_with_state(ip_filter, filter_ip)),
+            None => app,
+        };
+        // Browser dApps only call the public endpoints.
+        let app = match cors {
+            Some(cors) => app.layer(middleware::from_fn_with_state(Arc::new(cors), handle_cors)),
+            None => app,
+        };
+        // Privileged endpoints are served by a separate listener, so that they can be kept
+        // away from the public interface.
+        let admin_app = Router::new()
+            .route("/", get(health))
+            .route("/debug_health_check", post(debug_health_check))
             .route(
                 "/v1/reload_access_controller",
                 get(reload_access_controller),
             )
-            .layer(Extension(state));
+            .route("/v1/reload_config", get(reload_config))
+            .route("/v1/quarantined_coins", get(quarantined_coins))
+            .route("/v1/coins", get(pool_coins))
+            .route("/v1/usage", get(usage))
+            .route("/v1/adjust_limit", post(adjust_limit))
+            .route("/v1/drain_pool", post(drain_pool))
+            .route("/v1/resume_pool", post(resume_pool))
+            .route("/v1/rotate_auth_secret", post(rotate_auth_secret))
+            .route("/v1/coin_init", get(coin_init_status).post(set_coin_init))
+            .route("/v1/expired_reservations", get(expired_reservations))
+            .route("/v1/held_transactions", get(list_held_transactions))
+            .route("/v1/approve_transaction", post(approve_transaction))
+            .route("/v1/reject_transaction", post(reject_transaction))
+            .layer(Extension(state))
+            .layer(middleware::from_fn(propagate_request_id));
 
-        let address = SocketAddr::new(IpAddr::V4(host_ip), rpc_port);
+        let servers = bind_listeners(&host_ips, rpc_port)
+            .into_iter()
+            .map(|(address, listener)| {
+                info!("listening on {}", address);
+                axum::Server::from_tcp(listener).unwrap().serve(
+                    app.clone()
+                        .into_make_service_with_connect_info::<SocketAddr>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let admin_servers = bind_listeners(&admin_host_ips, admin_port)
+            .into_iter()
+            .map(|(address, listener)| {
+                info!("admin endpoints listening on {}", address);
+                axum::Server::from_tcp(listener)
+                    .unwrap()
+                    .serve(admin_app.clone().into_make_service())
+            })
+            .collect::<Vec<_>>();
 
         let handle = tokio::spawn(async move {
-            info!("listening on {}", address);
-            axum::Server::bind(&address)
-                .serve(app.into_make_service())
-                .await
-                .unwrap();
+            tokio::try_join!(try_join_all(servers), try_join_all(admin_servers)).unwrap();
         });
-        Self { handle, rpc_port }
+        Self {
+            handle,
+            rpc_port,
+            admin_port,
+        }
     }
 
     pub fn get_local_client(&self) -> GasStationRpcClient {
         GasStationRpcClient::new(format!("http://localhost:{}", self.rpc_port))
     }
+
+    pub fn get_local_admin_client(&self) -> GasStationRpcClient {
+        GasStationRpcClient::new(format!("http://localhost:{}", self.admin_port))
+    }
 }
 
 #[derive(Clone)]
 struct ServerState {
     gas_station: Arc<GasStation>,
-    secret: Arc<Option<String>>,
+    secret: Arc<AuthSecret>,
     metrics: Arc<GasStationRpcMetrics>,
     access_controller: Arc<ArcSwap<AccessController>>,
     stats_tracker: StatsTracker,
-    config_path: PathBuf,
+    scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+    tx_logger: TxLogger,
+    config_reloader: Arc<ConfigReloader>,
+    execution_limiter: Option<Arc<ExecutionLimiter>>,
+    /// Tells the client of a request behind the trusted proxies of the IP filter.
+    client_ip_config: IpFilterConfig,
 }
 
 impl ServerState {
@@ -108,20 +244,71 @@ impl ServerState {
         metrics: Arc<GasStationRpcMetrics>,
         access_controller: Arc<ArcSwap<AccessController>>,
         stats_tracker: StatsTracker,
-        config_path: PathBuf,
+        scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+        tx_logger: TxLogger,
+        config_reloader: Arc<ConfigReloader>,
+        execution_limiter: Option<Arc<ExecutionLimiter>>,
+        client_ip_config: IpFilterConfig,
     ) -> Self {
-        let secret = Arc::new(read_auth_env());
+        let secret = Arc::new(AuthSecret::from_env());
         Self {
             gas_station,
             secret,
             metrics,
             access_controller,
             stats_tracker,
-            config_path,
+            scoped_gas_usage_caps,
+            tx_logger,
+            config_reloader,
+            execution_limiter,
+            client_ip_config,
         }
     }
 }
 
+/// Binds a listener on each of the IP addresses. When IPv4 addresses are also given, the IPv6
+/// listeners only accept IPv6 clients, so that e.g. `0.0.0.0` and `::` can share the port.
+fn bind_listeners(ips: &[IpAddr], port: u16) -> Vec<(SocketAddr, TcpListener)> {
+    let has_ipv4 = ips.iter().any(IpAddr::is_ipv4);
+    ips.iter()
+        .map(|ip| {
+            let address = SocketAddr::new(*ip, port);
+            let listener = bind_listener(address, ip.is_ipv6() && has_ipv4)
+                .unwrap_or_else(|err| panic!("Failed to listen on {}: {:?}", address, err));
+            (address, listener)
+        })
+        .collect()
+}
+
+fn bind_listener(address: SocketAddr, only_v6: bool) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    if address.is_ipv6() {
+        socket.set_only_v6(only_v6)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(1024)?;
+    Ok(socket.into())
+}
+
+/// Response to a request rejected before reaching its endpoint. Like the responses of the
+/// endpoints, the error is an object for the `/v2` endpoints and a string for the others.
+pub(super) fn reject_request(status: StatusCode, path: &str, error: RpcError) -> Response {
+    if path.starts_with("/v2/") {
+        (status, Json(ReserveGasResponseV2::new_rpc_err(error))).into_response()
+    } else {
+        (
+            status,
+            Json(GasStationResponse::<()>::new_err_from_str(error.message)),
+        )
+            .into_response()
+    }
+}
+
 async fn health() -> &'static str {
     info!("Received health request");
     "OK"
@@ -132,16 +319,27 @@ async fn version() -> &'static str {
     VERSION
 }
 
+/// Readiness probe, failing while the fullnode or a signer is unreachable.
+async fn readyz(Extension(server): Extension<ServerState>) -> (StatusCode, String) {
+    match server.gas_station.check_readiness() {
+        Ok(()) => (StatusCode::OK, "OK".to_string()),
+        Err(err) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Not ready: {:#}", err),
+        ),
+    }
+}
+
 async fn debug_health_check(
     authorization: Option<TypedHeader<Authorization<Bearer>>>,
     Extension(server): Extension<ServerState>,
 ) -> String {
     info!("Received debug_health_check request");
-    if let Some(secret) = server.secret.as_ref() {
-        let token = authorization.as_ref().map(|auth| auth.token());
-        if token != Some(secret.as_str()) {
-            return "Unauthorized".to_string();
-        }
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return "Unauthorized".to_string();
     }
     if let Err(err) = server.gas_station.debug_check_health().await {
         return format!("Failed to check health: {:?}", err);
@@ -150,33 +348,59 @@ async fn debug_health_check(
 }
 
 async fn reserve_gas(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     authorization: Option<TypedHeader<Authorization<Bearer>>>,
     Extension(server): Extension<ServerState>,
     Json(payload): Json<ReserveGasRequest>,
 ) -> impl IntoResponse {
-    if let Some(secret) = server.secret.as_ref() {
-        let token = authorization.as_ref().map(|auth| auth.token());
-        if token != Some(secret.as_str()) {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ReserveGasResponse::new_err(anyhow::anyhow!(
-                    "Authorization token is required or invalid"
-                ))),
-            );
-        }
+    let (status, Json(response)) = reserve_gas_v2(
+        headers,
+        connect_info,
+        authorization,
+        Extension(server),
+        Json(payload),
+    )
+    .await;
+    (status, Json(ReserveGasResponse::from(response)))
+}
+
+/// Same as `reserve_gas`, but the errors come with a stable code.
+async fn reserve_gas_v2(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(payload): Json<ReserveGasRequest>,
+) -> (StatusCode, Json<ReserveGasResponseV2>) {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ReserveGasResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::Unauthorized,
+                "Authorization token is required or invalid",
+            ))),
+        );
     }
     server.metrics.num_authorized_reserve_gas_requests.inc();
-    debug!("Received v1 reserve_gas request: {:?}", payload);
+    debug!("Received reserve_gas request: {:?}", payload);
     if let Err(err) = payload.check_validity() {
         debug!("Invalid reserve_gas request: {:?}", err);
         return (
             StatusCode::BAD_REQUEST,
-            Json(ReserveGasResponse::new_err(err)),
+            Json(ReserveGasResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InvalidRequest,
+                err,
+            ))),
         );
     }
     let ReserveGasRequest {
         gas_budget,
         reserve_duration_secs,
+        idempotency_key,
     } = payload;
     server
         .metrics
@@ -186,33 +410,48 @@ async fn reserve_gas(
         .metrics
         .reserve_duration_per_request
         .observe(reserve_duration_secs);
+    let client = reservation_client(
+        &headers,
+        connect_info.map(|ConnectInfo(peer)| peer),
+        &server.client_ip_config,
+    );
     // Spawn a thread to process the request so that it will finish even when client drops the connection.
     tokio::task::spawn(reserve_gas_impl(
         server.gas_station.clone(),
         server.metrics.clone(),
         gas_budget,
         reserve_duration_secs,
+        idempotency_key,
+        client,
     ))
     .await
     .unwrap_or_else(|err| {
         error!("Failed to spawn reserve_gas task: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ReserveGasResponse::new_err(anyhow::anyhow!(
-                "Failed to spawn reserve_gas task"
+            Json(ReserveGasResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InternalError,
+                "Failed to spawn reserve_gas task",
             ))),
         )
     })
 }
 
+#[instrument(skip(gas_station, metrics))]
 async fn reserve_gas_impl(
     gas_station: Arc<GasStation>,
     metrics: Arc<GasStationRpcMetrics>,
     gas_budget: u64,
     reserve_duration_secs: u64,
-) -> (StatusCode, Json<ReserveGasResponse>) {
+    idempotency_key: Option<String>,
+    client: String,
+) -> (StatusCode, Json<ReserveGasResponseV2>) {
     match gas_station
-        .reserve_gas(gas_budget, Duration::from_secs(reserve_duration_secs))
+        .reserve_gas_with_idempotency_key(
+            gas_budget,
+            Duration::from_secs(reserve_duration_secs),
+            idempotency_key.as_deref(),
+        )
         .await
     {
         Ok((sponsor, reservation_id, gas_coins)) => {
@@ -225,7 +464,17 @@ async fn reserve_gas_impl(
                 gas_coins
             );
             metrics.num_successful_reserve_gas_requests.inc();
-            let response = ReserveGasResponse::new_ok(sponsor, reservation_id, gas_coins);
+            if let Err(err) = gas_station
+                .record_reservation_client(
+                    reservation_id,
+                    &client,
+                    Duration::from_secs(reserve_duration_secs),
+                )
+                .await
+            {
+                warn!("Failed to record the client of the reservation: {:?}", err);
+            }
+            let response = ReserveGasResponseV2::new_ok(sponsor, reservation_id, gas_coins);
             (StatusCode::OK, Json(response))
         }
         Err(err) => {
@@ -233,48 +482,315 @@ async fn reserve_gas_impl(
             metrics.num_failed_reserve_gas_requests.inc();
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ReserveGasResponse::new_err(err)),
+                Json(ReserveGasResponseV2::new_rpc_err(RpcError::new(
+                    reserve_gas_error_code(&err),
+                    err,
+                ))),
             )
         }
     }
 }
 
-async fn execute_tx(
+/// The client of a reservation: its `x-client-id` header, or its IP address resolved through the
+/// trusted proxies.
+fn reservation_client(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    client_ip_config: &IpFilterConfig,
+) -> String {
+    headers
+        .get(CLIENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|client_id| !client_id.is_empty())
+        .map(|client_id| client_id.chars().take(MAX_CLIENT_ID_LENGTH).collect())
+        .or_else(|| {
+            peer.map(|peer| {
+                client_ip_config
+                    .client_ip(peer.ip(), headers)
+                    .to_canonical()
+                    .to_string()
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Builds the transaction of an intent with reserved gas coins, so that the sender only has to
+/// sign it and send it to `execute_tx`.
+async fn build_and_reserve(
     headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     authorization: Option<TypedHeader<Authorization<Bearer>>>,
     Extension(server): Extension<ServerState>,
-    Json(payload): Json<ExecuteTxRequest>,
+    Json(payload): Json<BuildAndReserveRequest>,
 ) -> impl IntoResponse {
-    server.metrics.num_execute_tx_requests.inc();
-    if let Some(secret) = server.secret.as_ref() {
-        let token = authorization.as_ref().map(|auth| auth.token());
-        if token != Some(secret.as_str()) {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(GasStationResponse::new_err_from_str(
+                "Authorization token is required or invalid",
+            )),
+        );
+    }
+    server.metrics.num_authorized_reserve_gas_requests.inc();
+    debug!("Received v1 build_and_reserve request: {:?}", payload);
+    if let Err(err) = payload.check_validity() {
+        debug!("Invalid build_and_reserve request: {:?}", err);
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err(err)),
+        );
+    }
+    let BuildAndReserveRequest {
+        sender,
+        intent,
+        gas_budget,
+        reserve_duration_secs,
+        idempotency_key,
+    } = payload;
+    // The transaction is built before reserving the coins, so that an invalid intent doesn't
+    // leave a reservation behind.
+    let (pt, gas_budget, reference_gas_price) = match server
+        .gas_station
+        .build_intent(sender, &intent, gas_budget)
+        .await
+        .and_then(|(pt, gas_budget, reference_gas_price)| {
+            check_gas_budget(gas_budget)?;
+            Ok((pt, gas_budget, reference_gas_price))
+        }) {
+        Ok(built) => built,
+        Err(err) => {
+            debug!("Failed to build the transaction of {:?}: {:?}", intent, err);
             return (
-                StatusCode::UNAUTHORIZED,
-                Json(ExecuteTxResponse::new_err(anyhow::anyhow!(
-                    "Invalid authorization token"
-                ))),
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err(err)),
+            );
+        }
+    };
+    server
+        .metrics
+        .target_gas_budget_per_request
+        .observe(gas_budget);
+    server
+        .metrics
+        .reserve_duration_per_request
+        .observe(reserve_duration_secs);
+    let client = reservation_client(
+        &headers,
+        connect_info.map(|ConnectInfo(peer)| peer),
+        &server.client_ip_config,
+    );
+    // Spawn a thread to process the request so that it will finish even when client drops the connection.
+    let reserved = tokio::task::spawn(reserve_gas_impl(
+        server.gas_station.clone(),
+        server.metrics.clone(),
+        gas_budget,
+        reserve_duration_secs,
+        idempotency_key,
+        client,
+    ))
+    .await;
+    let (status, Json(response)) = match reserved {
+        Ok(reserved) => reserved,
+        Err(err) => {
+            error!("Failed to spawn build_and_reserve task: {:?}", err);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err_from_str(
+                    "Failed to spawn build_and_reserve task",
+                )),
             );
         }
+    };
+    let Some(reservation) = response.result else {
+        let error = response.error.map(|error| error.message);
+        return (
+            status,
+            Json(GasStationResponse::new_err_from_str(
+                error.unwrap_or_else(|| "Unknown error".to_string()),
+            )),
+        );
+    };
+    let tx_data = TransactionData::new_programmable_allow_sponsor(
+        sender,
+        reservation
+            .gas_coins
+            .into_iter()
+            .map(|coin| coin.to_object_ref())
+            .collect(),
+        pt,
+        gas_budget,
+        reference_gas_price,
+        reservation.sponsor_address,
+    );
+    let result = BuildAndReserveResult {
+        sponsor_address: reservation.sponsor_address,
+        reservation_id: reservation.reservation_id,
+        gas_budget,
+        tx_bytes: Base64::from_bytes(&bcs::to_bytes(&tx_data).unwrap()),
+    };
+    (StatusCode::OK, Json(GasStationResponse::new_ok(result)))
+}
+
+async fn execute_tx(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    query: Query<ExplainQuery>,
+    Json(payload): Json<ExecuteTxRequest>,
+) -> impl IntoResponse {
+    let (status, Json(response)) = execute_tx_v2(
+        headers,
+        connect_info,
+        authorization,
+        Extension(server),
+        query,
+        Json(payload),
+    )
+    .await;
+    (status, Json(ExecuteTxResponse::from(response)))
+}
+
+/// Same as `execute_tx`, but the errors come with a stable code.
+async fn execute_tx_v2(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Query(query): Query<ExplainQuery>,
+    Json(payload): Json<ExecuteTxRequest>,
+) -> (StatusCode, Json<ExecuteTxResponseV2>) {
+    server.metrics.num_execute_tx_requests.inc();
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::Unauthorized,
+                "Invalid authorization token",
+            ))),
+        );
     }
 
     server.metrics.num_authorized_execute_tx_requests.inc();
 
-    debug!("Received v1 execute_tx request: {:?}", payload);
+    if let Err(err) = check_explain_allowed(&server, query.explain) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InvalidRequest,
+                err,
+            ))),
+        );
+    }
+
+    debug!("Received execute_tx request: {:?}", payload);
     let ExecuteTxRequest {
         reservation_id,
         tx_bytes,
         user_sig: user_sig_raw,
         request_type,
+        options,
+        idempotency_key,
     } = payload;
-    let Ok((tx_data, user_sig)) = convert_tx_and_sig(tx_bytes.clone(), user_sig_raw.clone()) else {
+    let idempotency_key = idempotency_key.or_else(|| {
+        headers
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    });
+    if idempotency_key
+        .as_ref()
+        .is_some_and(|key| key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH)
+    {
         return (
             StatusCode::BAD_REQUEST,
-            Json(ExecuteTxResponse::new_err(anyhow::anyhow!(
-                "Invalid bcs bytes for TransactionData"
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InvalidRequest,
+                format!(
+                    "Idempotency key must have between 1 and {} characters",
+                    MAX_IDEMPOTENCY_KEY_LENGTH
+                ),
             ))),
         );
+    }
+    let (tx_data, user_sig) = match convert_tx_and_sig(tx_bytes.clone(), user_sig_raw.clone()) {
+        Ok(converted) => converted,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ExecuteTxResponseV2::new_rpc_err(err)),
+            )
+        }
     };
+    if let Some(idempotency_key) = &idempotency_key {
+        match get_executed_transaction(&server.gas_station, idempotency_key).await {
+            Ok(Some(executed)) if executed.transaction_digest == tx_data.digest() => {
+                debug!(
+                    "Returning the response stored for the idempotency key of transaction {}",
+                    executed.transaction_digest
+                );
+                server.metrics.num_replayed_execute_tx_requests.inc();
+                return (StatusCode::OK, Json(executed.response));
+            }
+            Ok(Some(_)) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::InvalidRequest,
+                        "The idempotency key was already used for another transaction",
+                    ))),
+                );
+            }
+            Ok(None) => {}
+            Err(err) => {
+                let event_id = generate_event_id();
+                error!(
+                    "EventId={} Error while reading the executed transaction: {:?}",
+                    event_id, err
+                );
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::InternalError,
+                        format!(
+                            "Error while reading the executed transaction. EventId={}",
+                            event_id
+                        ),
+                    ))),
+                );
+            }
+        }
+    }
+
+    // Checked before the access check, so that a transaction that can't be executed isn't
+    // counted by the rules.
+    if let Err(err) = server
+        .gas_station
+        .check_gas_data(reservation_id, &tx_data)
+        .await
+    {
+        debug!("Invalid gas data: {:?}", err);
+        let (status, code) = match err.downcast_ref::<GasDataError>() {
+            Some(err) => (StatusCode::BAD_REQUEST, gas_data_error_code(err)),
+            None => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::InternalError),
+        };
+        return (
+            status,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(code, err))),
+        );
+    }
+
+    // Keep the coins reserved while waiting for an execution slot and the access check.
+    let reservation_lease = server
+        .gas_station
+        .lease_reservation(tx_data.gas_data().owner, reservation_id)
+        .ok();
 
     // collect information about request and transaction
     let ctx = TransactionContext::new(
@@ -287,47 +803,187 @@ async fn execute_tx(
         request_type,
         headers,
     );
+    let peer = connect_info.map(|ConnectInfo(peer)| peer);
+    let ctx = match complete_tx_context(&server, ctx, peer).await {
+        Ok(ctx) => ctx,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ExecuteTxResponseV2::new_rpc_err(err)),
+            )
+        }
+    };
+
+    // Wait for a free execution slot, held until the spawned task completes.
+    let execution_permit = match &server.execution_limiter {
+        Some(execution_limiter) => match execution_limiter.acquire().await {
+            Ok(permit) => Some(permit),
+            Err(err) => {
+                warn!("Rejected execute_tx request: {}", err);
+                server.metrics.num_rejected_execute_tx_requests.inc();
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::TooManyRequests,
+                        err,
+                    ))),
+                );
+            }
+        },
+        None => None,
+    };
 
     // Spawn a thread to process the request so that it will finish even when client drops the connection.
-    tokio::task::spawn(execute_tx_impl(
+    let gas_station = server.gas_station.clone();
+    let transaction_digest = ctx.transaction_digest;
+    let request_type = ctx.request_type.clone();
+    let execution = execute_tx_impl(
         server.gas_station.clone(),
         server.metrics.clone(),
         tx_data,
         user_sig,
+        options.unwrap_or_default(),
         server.access_controller.clone(),
+        server.scoped_gas_usage_caps.clone(),
+        server.tx_logger.clone(),
         ctx,
-    ))
+        query.explain,
+    );
+    tokio::task::spawn(async move {
+        let (status, Json(response)) = execution.await;
+        drop(reservation_lease);
+        drop(execution_permit);
+        wait_for_local_execution(&gas_station, request_type.as_ref(), &response).await;
+        let Some(idempotency_key) = idempotency_key.filter(|_| status == StatusCode::OK) else {
+            return (status, Json(response));
+        };
+        let executed = ExecutedTransaction {
+            transaction_digest,
+            response,
+        };
+        if let Err(err) =
+            store_executed_transaction(&gas_station, &idempotency_key, &executed).await
+        {
+            // The transaction was executed, so the client still gets its response.
+            error!("Failed to store the executed transaction: {:?}", err);
+        }
+        (status, Json(executed.response))
+    })
     .await
     .unwrap_or_else(|err| {
         error!("Failed to spawn execute_tx task: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ExecuteTxResponse::new_err(anyhow::anyhow!(
-                "Failed to spawn execute_tx task"
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InternalError,
+                "Failed to spawn execute_tx task",
             ))),
         )
     })
 }
 
+#[instrument(
+    skip_all,
+    fields(reservation_id = ctx.reservation_id, digest = %ctx.transaction_digest)
+)]
 async fn execute_tx_impl(
     gas_station: Arc<GasStation>,
     metrics: Arc<GasStationRpcMetrics>,
     tx_data: TransactionData,
     user_sig: GenericSignature,
+    options: IotaTransactionBlockResponseOptions,
     access_controller: Arc<ArcSwap<AccessController>>,
+    scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+    tx_logger: TxLogger,
     ctx: TransactionContext,
-) -> (StatusCode, Json<ExecuteTxResponse>) {
-    match access_controller.load().check_access(&ctx).await {
-        Ok(Decision::Allow) => {
+    explain: bool,
+) -> (StatusCode, Json<ExecuteTxResponseV2>) {
+    let access_decision = if explain {
+        access_controller.load().check_access_explained(&ctx).await
+    } else {
+        access_controller
+            .load()
+            .check_access_with_details(&ctx)
+            .await
+    };
+    if let Ok(AccessDecision {
+        audited_rules,
+        hook_cache_lookups,
+        ..
+    }) = &access_decision
+    {
+        for rule in audited_rules {
+            metrics
+                .num_audited_execute_tx_requests
+                .with_label_values(&[rule])
+                .inc();
+        }
+        for lookup in hook_cache_lookups {
+            let counter = if lookup.hit {
+                &metrics.num_hook_cache_hits
+            } else {
+                &metrics.num_hook_cache_misses
+            };
+            counter.with_label_values(&[&lookup.rule]).inc();
+        }
+    }
+    let signing_context = match access_decision {
+        Ok(AccessDecision {
+            decision: Decision::Allow,
+            matched_rule,
+            attestation,
+            ..
+        }) => {
             metrics.num_allowed_execute_tx_requests.inc();
+            SigningContext {
+                transaction_digest: ctx.transaction_digest.to_string(),
+                matched_rule,
+                attestation,
+            }
+        }
+        Ok(AccessDecision {
+            decision: Decision::Hold,
+            matched_rule,
+            explanation,
+            ..
+        }) => {
+            let sponsor = tx_data.gas_data().owner;
+            let (status, Json(mut response)) = hold_tx(
+                &gas_station,
+                &metrics,
+                &access_controller,
+                sponsor,
+                matched_rule,
+                options,
+                &ctx,
+            )
+            .await;
+            if let Some(error) = response.error.take() {
+                response.error = Some(error.with_explanation(explain.then_some(explanation)));
+            }
+            return (status, Json(response));
         }
-        Ok(Decision::Deny) => {
+        Ok(AccessDecision {
+            decision: Decision::Deny,
+            matched_rule,
+            user_message,
+            deny_message,
+            explanation,
+            ..
+        }) => {
             metrics.num_failed_execute_tx_requests.inc();
             return (
                 StatusCode::FORBIDDEN,
-                Json(ExecuteTxResponse::new_err(anyhow::anyhow!(
-                    "Access denied by access controller"
-                ))),
+                Json(ExecuteTxResponseV2::new_rpc_err(
+                    RpcError::new(
+                        ErrorCode::AccessDenied,
+                        deny_message
+                            .unwrap_or_else(|| "Access denied by access controller".to_string()),
+                    )
+                    .with_user_message(user_message)
+                    .with_matched_rule(matched_rule)
+                    .with_explanation(explain.then_some(explanation)),
+                )),
             );
         }
         Err(err) => {
@@ -338,124 +994,1339 @@ async fn execute_tx_impl(
             );
             return (
                 StatusCode::BAD_REQUEST,
-                Json(ExecuteTxResponse::new_err(anyhow::anyhow!(
-                    "Error while checking access. EventId={}",
-                    event_id
+                Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                    ErrorCode::AccessCheckFailed,
+                    format!("Error while checking access. EventId={}", event_id),
                 ))),
             );
         }
-    }
-
-    let transaction_digest = tx_data.digest();
-    match gas_station
-        .execute_transaction(ctx.reservation_id, tx_data, user_sig, ctx.request_type)
-        .await
-    {
-        Ok(effects) => {
-            info!(
-                ?ctx.reservation_id,
-                "Successfully executed transaction {:?} with status: {:?}",
-                effects.transaction_digest(),
-                effects.status()
-            );
-            trace!(target: "transactions", "{}", TxLogMessage::new(&effects));
+    };
 
-            metrics.num_successful_execute_tx_requests.inc();
-            let confirmation_result = access_controller
-                .load()
-                .confirm_transaction(
-                    TransactionExecutionResult::new(transaction_digest)
-                        .with_gas_usage(effects.gas_cost_summary().gas_used()),
-                    &ctx.stats_tracker.clone(),
-                )
-                .await;
-            // When then confirmation fails, the error shouldn't prevent the user from
-            // receiving the successful response.
-            if let Err(err) = confirmation_result {
-                error!("Error while confirming transaction in AC: {:?}", err);
-            }
+    execute_allowed_tx(
+        gas_station,
+        metrics,
+        tx_data,
+        user_sig,
+        options,
+        access_controller,
+        scoped_gas_usage_caps,
+        tx_logger,
+        ctx,
+        signing_context,
+    )
+    .await
+}
 
-            (StatusCode::OK, Json(ExecuteTxResponse::new_ok(effects)))
+/// Adds what the access rules may need to know about the transaction, besides its content.
+async fn complete_tx_context(
+    server: &ServerState,
+    ctx: TransactionContext,
+    peer: Option<SocketAddr>,
+) -> Result<TransactionContext, RpcError> {
+    let access_controller = server.access_controller.load_full();
+    // The country is only resolved if a rule needs it.
+    let ctx = match peer {
+        Some(peer) if access_controller.uses_client_country() => {
+            let client_ip = server.client_ip_config.client_ip(peer.ip(), &ctx.headers);
+            let client_country = access_controller.client_country(client_ip);
+            ctx.with_client_country(client_country)
         }
+        _ => ctx,
+    };
+    // Used by the gas price ratio of the access rules. The price is cached by the client.
+    let ctx = match server.gas_station.get_reference_gas_price().await {
+        Ok(reference_gas_price) => ctx.with_reference_gas_price(reference_gas_price),
         Err(err) => {
-            error!("Failed to execute transaction: {:?}", err);
-
-            let confirmation_result = access_controller
-                .load()
-                .confirm_transaction(
-                    TransactionExecutionResult::new(transaction_digest),
-                    &ctx.stats_tracker,
-                )
-                .await;
-            if let Err(err) = confirmation_result {
-                error!("Error while canceling transaction in AC: {:?}", err);
-            }
-
-            metrics.num_failed_execute_tx_requests.inc();
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ExecuteTxResponse::new_err(err)),
-            )
+            warn!("Failed to get the reference gas price: {:?}", err);
+            ctx
+        }
+    };
+    // Only the fullnode knows the coin types of the objects used by the coin commands. They are
+    // only queried if a rule needs them, and the request fails without them so that no coin type
+    // is missed.
+    if ctx.coin_object_ids.is_empty() || !access_controller.uses_coin_types() {
+        return Ok(ctx);
+    }
+    match server
+        .gas_station
+        .get_object_types(ctx.coin_object_ids.clone())
+        .await
+    {
+        Ok(object_types) => Ok(ctx.with_coin_object_types(&object_types)),
+        Err(err) => {
+            let event_id = generate_event_id();
+            warn!(
+                "EventId={} Error while getting the coin types: {:?}",
+                event_id, err
+            );
+            Err(RpcError::new(
+                ErrorCode::AccessCheckFailed,
+                format!("Error while checking access. EventId={}", event_id),
+            ))
         }
     }
 }
 
-async fn reload_access_controller(
+/// Tells whether the access controller would allow a transaction signed by the user, without
+/// executing it, reserving gas or counting its budget in the gas usage limits.
+async fn check_tx(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     authorization: Option<TypedHeader<Authorization<Bearer>>>,
     Extension(server): Extension<ServerState>,
+    Query(query): Query<ExplainQuery>,
+    Json(payload): Json<CheckTxRequest>,
 ) -> impl IntoResponse {
-    if let Some(secret) = server.secret.as_ref() {
-        let token = authorization.as_ref().map(|auth| auth.token());
-        if token != Some(secret.as_str()) {
-            return (
-                StatusCode::FORBIDDEN,
-                Json(GasStationResponse::new_err_from_str(
-                    "Invalid authorization token",
-                )),
-            );
-        }
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(GasStationResponse::new_err_from_str(
+                "Authorization token is required or invalid",
+            )),
+        );
+    }
+    if let Err(err) = check_explain_allowed(&server, query.explain) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err(err)),
+        );
     }
-    let mut access_controller = match GasStationConfig::load(&server.config_path) {
-        Ok(new_config) => new_config.access_controller,
+    debug!("Received v1 check_tx request: {:?}", payload);
+    let CheckTxRequest { tx_bytes, user_sig } = payload;
+    let (tx_data, parsed_user_sig) = match convert_tx_and_sig(tx_bytes.clone(), user_sig.clone()) {
+        Ok(converted) => converted,
         Err(err) => {
-            error!("Failed to load config file: {:?}", err);
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(GasStationResponse::new_err_from_str(
-                    "Failed to load config file",
-                )),
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err_from_str(&err.message)),
+            )
+        }
+    };
+    let ctx = TransactionContext::new(
+        &parsed_user_sig,
+        &tx_data,
+        server.stats_tracker.clone(),
+        0,
+        tx_bytes,
+        user_sig,
+        None,
+        headers,
+    );
+    let peer = connect_info.map(|ConnectInfo(peer)| peer);
+    let ctx = match complete_tx_context(&server, ctx, peer).await {
+        Ok(ctx) => ctx,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err_from_str(&err.message)),
+            )
+        }
+    };
+    match server
+        .access_controller
+        .load()
+        .dry_run(&ctx, query.explain)
+        .await
+    {
+        Ok(access_decision) => {
+            let result = CheckTxResult {
+                decision: access_decision.decision,
+                matched_rule: access_decision.matched_rule,
+                user_message: access_decision.user_message,
+                deny_message: access_decision.deny_message,
+                explanation: query.explain.then_some(access_decision.explanation),
+            };
+            (StatusCode::OK, Json(GasStationResponse::new_ok(result)))
+        }
+        Err(err) => {
+            let event_id = generate_event_id();
+            warn!(
+                "EventId={} Error while checking access: {:?}",
+                event_id, err
+            );
+            (
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err_from_str(format!(
+                    "Error while checking access. EventId={}",
+                    event_id
+                ))),
+            )
+        }
+    }
+}
+
+async fn get_executed_transaction(
+    gas_station: &GasStation,
+    idempotency_key: &str,
+) -> anyhow::Result<Option<ExecutedTransaction>> {
+    let Some(data) = gas_station
+        .get_executed_transaction(idempotency_key)
+        .await?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+async fn store_executed_transaction(
+    gas_station: &GasStation,
+    idempotency_key: &str,
+    executed: &ExecutedTransaction,
+) -> anyhow::Result<()> {
+    let data = serde_json::to_string(executed)?;
+    gas_station
+        .store_executed_transaction(idempotency_key, &data, EXECUTED_TRANSACTION_RETENTION)
+        .await
+}
+
+/// Executes a transaction allowed by the access controller, once it fits in the daily gas usage
+/// caps.
+#[instrument(
+    skip_all,
+    fields(reservation_id = ctx.reservation_id, digest = %ctx.transaction_digest)
+)]
+async fn execute_allowed_tx(
+    gas_station: Arc<GasStation>,
+    metrics: Arc<GasStationRpcMetrics>,
+    tx_data: TransactionData,
+    user_sig: GenericSignature,
+    options: IotaTransactionBlockResponseOptions,
+    access_controller: Arc<ArcSwap<AccessController>>,
+    scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+    tx_logger: TxLogger,
+    ctx: TransactionContext,
+    signing_context: SigningContext,
+) -> (StatusCode, Json<ExecuteTxResponseV2>) {
+    let gas_usage_reservation = match scoped_gas_usage_caps
+        .reserve(
+            &ctx.stats_tracker,
+            ctx.sender_address,
+            &ctx.move_call_package_addresses,
+            ctx.transaction_budget,
+        )
+        .await
+    {
+        Ok(reservation) => reservation,
+        Err(err) => {
+            // The budget was already counted by the access controller.
+            cancel_in_access_controller(
+                &access_controller,
+                ctx.transaction_digest,
+                &ctx.stats_tracker,
+            )
+            .await;
+            metrics.num_failed_execute_tx_requests.inc();
+            if is_scoped_gas_usage_cap_exceeded(&err) {
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::GasUsageCapExceeded,
+                        err,
+                    ))),
+                );
+            }
+            let event_id = generate_event_id();
+            error!(
+                "EventId={} Error while checking the daily gas usage caps: {:?}",
+                event_id, err
+            );
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                    ErrorCode::InternalError,
+                    format!(
+                        "Error while checking the daily gas usage caps. EventId={}",
+                        event_id
+                    ),
+                ))),
             );
         }
     };
-    let result = access_controller.initialize().await;
+
+    let transaction_digest = tx_data.digest();
+    let sponsor = tx_data.gas_data().owner;
+    match gas_station
+        .execute_transaction_with_options(
+            ctx.reservation_id,
+            tx_data,
+            user_sig,
+            ctx.request_type,
+            options,
+            Some(signing_context),
+        )
+        .await
+    {
+        Ok((effects, response)) => {
+            info!(
+                ?ctx.reservation_id,
+                "Successfully executed transaction {:?} with status: {:?}",
+                effects.transaction_digest(),
+                effects.status()
+            );
+            tx_logger.log(
+                TxLogRecord::new(
+                    ctx.sender_address,
+                    sponsor,
+                    ctx.reservation_id,
+                    Decision::Allow,
+                    &effects,
+                )
+                .with_request_id(ctx.request_id().map(|request_id| request_id.to_string())),
+            );
+
+            metrics.num_successful_execute_tx_requests.inc();
+            let gas_used = effects.gas_cost_summary().gas_used();
+            gas_usage_reservation
+                .settle(&ctx.stats_tracker, Some(gas_used))
+                .await;
+            ctx.stats_tracker
+                .record_gas_usage(
+                    ctx.sender_address,
+                    &ctx.move_call_package_addresses,
+                    gas_used,
+                )
+                .await;
+            let confirmation_result = access_controller
+                .load()
+                .confirm_transaction(
+                    TransactionExecutionResult::new(transaction_digest).with_gas_usage(gas_used),
+                    &ctx.stats_tracker.clone(),
+                )
+                .await;
+            // When then confirmation fails, the error shouldn't prevent the user from
+            // receiving the successful response.
+            if let Err(err) = confirmation_result {
+                error!("Error while confirming transaction in AC: {:?}", err);
+            }
+
+            (
+                StatusCode::OK,
+                Json(ExecuteTxResponseV2::new_ok(effects).with_response(response)),
+            )
+        }
+        Err(err) => {
+            error!("Failed to execute transaction: {:?}", err);
+
+            gas_usage_reservation.settle(&ctx.stats_tracker, None).await;
+            cancel_in_access_controller(&access_controller, transaction_digest, &ctx.stats_tracker)
+                .await;
+
+            metrics.num_failed_execute_tx_requests.inc();
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                    execute_tx_error_code(&err),
+                    err,
+                ))),
+            )
+        }
+    }
+}
+
+/// The explanation reveals the rules of the access controller, so the clients can only ask for it
+/// when the configuration allows it.
+fn check_explain_allowed(server: &ServerState, explain: bool) -> anyhow::Result<()> {
+    if explain && !server.access_controller.load().allow_explain {
+        anyhow::bail!("explain=true is not allowed by the access controller configuration");
+    }
+    Ok(())
+}
+
+/// With `WaitForLocalExecution`, waits until the fullnode knows the executed transaction before
+/// the response is returned. The gas coins and the execution slot must be released by then, so
+/// that the wait doesn't hold them.
+async fn wait_for_local_execution(
+    gas_station: &GasStation,
+    request_type: Option<&ExecuteTransactionRequestType>,
+    response: &ExecuteTxResponseV2,
+) {
+    if request_type != Some(&ExecuteTransactionRequestType::WaitForLocalExecution) {
+        return;
+    }
+    if let Some(effects) = &response.effects {
+        gas_station
+            .wait_for_local_execution(*effects.transaction_digest())
+            .await;
+    }
+}
+
+/// Keeps the reservation of the transaction alive and stores the transaction until an operator
+/// approves or rejects it.
+async fn hold_tx(
+    gas_station: &GasStation,
+    metrics: &GasStationRpcMetrics,
+    access_controller: &ArcSwap<AccessController>,
+    sponsor: IotaAddress,
+    matched_rule: Option<String>,
+    options: IotaTransactionBlockResponseOptions,
+    ctx: &TransactionContext,
+) -> (StatusCode, Json<ExecuteTxResponseV2>) {
+    let held = HeldTransaction::new(ctx, sponsor, matched_rule.clone(), options);
+    purge_expired_holds(access_controller, &ctx.stats_tracker).await;
+    let result = match gas_station
+        .set_reservation_expiration(sponsor, ctx.reservation_id, HOLD_DURATION)
+        .await
+    {
+        Ok(()) => {
+            let result = held_transactions::store(&held, &ctx.stats_tracker).await;
+            if result.is_err() {
+                release_reservation(gas_station, sponsor, ctx.reservation_id).await;
+            }
+            result
+        }
+        Err(err) => Err(err),
+    };
     if let Err(err) = result {
-        error!("Failed to initialize access controller: {:?}", err);
+        error!("Failed to hold transaction: {:?}", err);
+        cancel_in_access_controller(
+            access_controller,
+            ctx.transaction_digest,
+            &ctx.stats_tracker,
+        )
+        .await;
+        metrics.num_failed_execute_tx_requests.inc();
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(GasStationResponse::new_err(err)),
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                execute_tx_error_code(&err),
+                err,
+            ))),
         );
     }
-    server.access_controller.store(Arc::new(access_controller));
     info!(
-        "Access controller reloaded successfully with {} rules",
-        server.access_controller.load().rules.len()
+        sender = %ctx.sender_address,
+        gas_budget = ctx.transaction_budget,
+        "Transaction held for manual approval by {}",
+        matched_rule.as_deref().unwrap_or("the access policy")
     );
+    metrics.num_held_execute_tx_requests.inc();
+    (
+        StatusCode::ACCEPTED,
+        Json(ExecuteTxResponseV2::new_rpc_err(
+            RpcError::new(
+                ErrorCode::PendingApproval,
+                "Transaction is held until it's approved by an operator",
+            )
+            .with_matched_rule(matched_rule),
+        )),
+    )
+}
+
+/// Expires the reservation right away, so that its coins return to the pool.
+async fn release_reservation(
+    gas_station: &GasStation,
+    sponsor: IotaAddress,
+    reservation_id: ReservationID,
+) {
+    if let Err(err) = gas_station
+        .set_reservation_expiration(sponsor, reservation_id, Duration::ZERO)
+        .await
+    {
+        warn!(
+            "Failed to release reservation {}: {:?}",
+            reservation_id, err
+        );
+    }
+}
+
+/// Removes the held transactions whose reservation expired, and cancels their gas usage.
+async fn purge_expired_holds(
+    access_controller: &ArcSwap<AccessController>,
+    stats_tracker: &StatsTracker,
+) {
+    let purged = match held_transactions::purge_expired(stats_tracker).await {
+        Ok(purged) => purged,
+        Err(err) => {
+            error!("Failed to purge the expired held transactions: {:?}", err);
+            return;
+        }
+    };
+    for held in purged {
+        info!(
+            "Held transaction {} of sender {} expired without being approved",
+            held.transaction_digest, held.sender
+        );
+        cancel_in_access_controller(access_controller, held.transaction_digest, stats_tracker)
+            .await;
+    }
+}
+
+async fn cancel_in_access_controller(
+    access_controller: &ArcSwap<AccessController>,
+    transaction_digest: TransactionDigest,
+    stats_tracker: &StatsTracker,
+) {
+    let confirmation_result = access_controller
+        .load()
+        .confirm_transaction(
+            TransactionExecutionResult::new(transaction_digest),
+            stats_tracker,
+        )
+        .await;
+    if let Err(err) = confirmation_result {
+        error!("Error while canceling transaction in AC: {:?}", err);
+    }
+}
+
+async fn estimate_gas(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(payload): Json<EstimateGasRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(GasStationResponse::new_err_from_str(
+                "Authorization token is required or invalid",
+            )),
+        );
+    }
+    debug!("Received v1 estimate_gas request: {:?}", payload);
+    let EstimateGasRequest {
+        sender,
+        tx_kind_bytes,
+    } = payload;
+    let Ok(tx_kind) = convert_tx_kind(tx_kind_bytes) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid bcs bytes for TransactionKind",
+            )),
+        );
+    };
+    match server
+        .gas_station
+        .estimate_gas_budget(sender, tx_kind)
+        .await
+    {
+        Ok((gas_budget, reference_gas_price, gas_cost_summary)) => {
+            let result = EstimateGasResult {
+                gas_budget,
+                reference_gas_price,
+                computation_cost: gas_cost_summary.computation_cost,
+                storage_cost: gas_cost_summary.storage_cost,
+                storage_rebate: gas_cost_summary.storage_rebate,
+            };
+            (StatusCode::OK, Json(GasStationResponse::new_ok(result)))
+        }
+        Err(err) => {
+            debug!("Failed to estimate gas: {:?}", err);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn sponsor_info(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(GasStationResponse::new_err_from_str(
+                "Authorization token is required or invalid",
+            )),
+        );
+    }
+    debug!("Received v1 sponsor request");
+    let reference_gas_price = match server.gas_station.get_reference_gas_price().await {
+        Ok(reference_gas_price) => reference_gas_price,
+        Err(err) => {
+            error!("Failed to get reference gas price: {:?}", err);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            );
+        }
+    };
+    let info = SponsorInfo {
+        sponsor_addresses: vec![server.gas_station.get_sponsor_address()],
+        reference_gas_price,
+        max_budget: MAX_BUDGET,
+        max_duration_secs: MAX_DURATION_S,
+        access_policy: server.access_controller.load().access_policy,
+    };
+    (StatusCode::OK, Json(GasStationResponse::new_ok(info)))
+}
+
+async fn limits(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Query(request): Query<LimitsRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(GasStationResponse::new_err_from_str(
+                "Authorization token is required or invalid",
+            )),
+        );
+    }
+    debug!("Received v1 limits request for sender {}", request.sender);
+    let limits = server
+        .access_controller
+        .load()
+        .get_gas_usage_limits(&request.sender, &server.stats_tracker)
+        .await;
+    match limits {
+        Ok(limits) => (
+            StatusCode::OK,
+            Json(GasStationResponse::new_ok(LimitsResponse {
+                sender: request.sender,
+                limits: limits.into_iter().map(to_rule_limit).collect(),
+            })),
+        ),
+        Err(err) => {
+            error!("Failed to get limits: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn adjust_limit(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<AdjustLimitRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let adjustment = match request.credit {
+        Some(amount) => GasUsageAdjustment::Credit(amount),
+        None => GasUsageAdjustment::Reset,
+    };
+    info!(
+        "Adjusting the gas usage of sender {} counted by rule #{}: {:?}",
+        request.sender, request.rule_number, adjustment
+    );
+    let result = server
+        .access_controller
+        .load()
+        .adjust_gas_usage(
+            request.rule_number,
+            &request.sender,
+            adjustment,
+            &server.stats_tracker,
+        )
+        .await;
+    match result {
+        Ok(limit) => (
+            StatusCode::OK,
+            Json(GasStationResponse::new_ok(to_rule_limit(limit))),
+        ),
+        Err(err) => {
+            error!("Failed to adjust the limit: {:?}", err);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+fn to_rule_limit(limit: GasUsageLimit) -> RuleLimit {
+    let gas_usage = limit.usage.value.max(0) as u64;
+    RuleLimit {
+        rule_number: limit.rule_number,
+        window_secs: limit.limit.window.as_secs(),
+        limit: limit.limit.value.to_string(),
+        gas_usage,
+        remaining: limit.limit.value.get_number().saturating_sub(gas_usage),
+        resets_in_secs: limit.usage.resets_in.map(|d| d.as_secs()),
+    }
+}
+
+async fn reload_access_controller(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    if let Err(err) = server.config_reloader.reload_access_controller().await {
+        error!("Failed to reload access controller: {:?}", err);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(GasStationResponse::new_err(err)),
+        );
+    }
     return (StatusCode::OK, Json(GasStationResponse::new_ok("success")));
 }
 
+async fn reload_config(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    if let Err(err) = server.config_reloader.reload().await {
+        error!("Failed to reload config: {:?}", err);
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err(err)),
+        );
+    }
+    (StatusCode::OK, Json(GasStationResponse::new_ok("success")))
+}
+
+async fn quarantined_coins(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    match server.gas_station.get_quarantined_coins().await {
+        Ok(coins) => (StatusCode::OK, Json(GasStationResponse::new_ok(coins))),
+        Err(err) => {
+            error!("Failed to get quarantined coins: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn pool_coins(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    match server.gas_station.get_pool_coins().await {
+        Ok(coins) => (StatusCode::OK, Json(GasStationResponse::new_ok(coins))),
+        Err(err) => {
+            error!("Failed to get pool coins: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn drain_pool(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<DrainPoolRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let recipient = request
+        .recipient
+        .unwrap_or_else(|| server.gas_station.get_sponsor_address());
+    match server.gas_station.drain_pool(recipient).await {
+        Ok((drained_coin_count, transferred_balance)) => (
+            StatusCode::OK,
+            Json(GasStationResponse::new_ok(DrainPoolResponse {
+                recipient,
+                drained_coin_count,
+                transferred_balance,
+            })),
+        ),
+        Err(err) => {
+            error!("Failed to drain the pool: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn resume_pool(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    match server.gas_station.resume_pool().await {
+        Ok(()) => (StatusCode::OK, Json(GasStationResponse::new_ok("success"))),
+        Err(err) => {
+            error!("Failed to resume the pool: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn rotate_auth_secret(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<RotateAuthSecretRequest>,
+) -> impl IntoResponse {
+    // The previous secret is still accepted by the other endpoints, but must not allow to rotate
+    // the secret again and lock out the holders of the current one.
+    if !server
+        .secret
+        .is_current(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let grace_period = request
+        .grace_period_secs
+        .map_or(DEFAULT_AUTH_GRACE_PERIOD, Duration::from_secs);
+    match server.secret.rotate(request.new_secret, grace_period) {
+        Ok(()) => (StatusCode::OK, Json(GasStationResponse::new_ok("success"))),
+        Err(err) => (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err(err)),
+        ),
+    }
+}
+
+async fn expired_reservations(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Query(request): Query<ExpiredReservationsRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let limit = request.limit.unwrap_or(DEFAULT_EXPIRED_RESERVATIONS_LIMIT);
+    match server.gas_station.get_client_reservation_stats(limit).await {
+        Ok(stats) => (StatusCode::OK, Json(GasStationResponse::new_ok(stats))),
+        Err(err) => {
+            error!("Failed to get the client reservation stats: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn coin_init_status(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    match server.gas_station.get_init_progress().await {
+        Ok(progress) => (
+            StatusCode::OK,
+            Json(GasStationResponse::new_ok(CoinInitStatus {
+                target_init_balance: server.config_reloader.target_init_balance(),
+                progress,
+            })),
+        ),
+        Err(err) => {
+            error!("Failed to get the coin init progress: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+async fn set_coin_init(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<SetCoinInitRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    match server
+        .config_reloader
+        .set_target_init_balance(request.target_init_balance)
+    {
+        Ok(()) => (StatusCode::OK, Json(GasStationResponse::new_ok("success"))),
+        Err(err) => (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err(err)),
+        ),
+    }
+}
+
+async fn list_held_transactions(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    purge_expired_holds(&server.access_controller, &server.stats_tracker).await;
+    match held_transactions::list(&server.stats_tracker).await {
+        Ok(held) => (StatusCode::OK, Json(GasStationResponse::new_ok(held))),
+        Err(err) => {
+            error!("Failed to list the held transactions: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+/// Executes a held transaction, as if it was allowed by the access controller.
+async fn approve_transaction(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<HeldTransactionRequest>,
+) -> (StatusCode, Json<ExecuteTxResponseV2>) {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::Unauthorized,
+                "Invalid authorization token",
+            ))),
+        );
+    }
+    let held =
+        match held_transactions::take(request.transaction_digest, &server.stats_tracker).await {
+            Ok(Some(held)) => held,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::InvalidRequest,
+                        format!(
+                            "No held transaction with digest {}",
+                            request.transaction_digest
+                        ),
+                    ))),
+                );
+            }
+            Err(err) => {
+                error!("Failed to take the held transaction: {:?}", err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                        ErrorCode::InternalError,
+                        err,
+                    ))),
+                );
+            }
+        };
+    if held.is_expired() {
+        cancel_in_access_controller(
+            &server.access_controller,
+            held.transaction_digest,
+            &server.stats_tracker,
+        )
+        .await;
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::ReservationExpired,
+                format!(
+                    "The held transaction {} expired at {}, its gas reservation is no longer valid",
+                    held.transaction_digest, held.expires_at_secs
+                ),
+            ))),
+        );
+    }
+    info!(
+        "Approving held transaction {} of sender {}",
+        held.transaction_digest, held.sender
+    );
+    let (tx_data, user_sig) = match convert_tx_and_sig(held.tx_bytes.clone(), held.user_sig.clone())
+    {
+        Ok(converted) => converted,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ExecuteTxResponseV2::new_rpc_err(err)),
+            )
+        }
+    };
+    // The headers of the original request are not kept, the access controller already made its
+    // decision.
+    let ctx = TransactionContext::new(
+        &user_sig,
+        &tx_data,
+        server.stats_tracker.clone(),
+        held.reservation_id,
+        held.tx_bytes,
+        held.user_sig,
+        held.request_type,
+        HeaderMap::new(),
+    );
+    let request_type = held.request_type.clone();
+    // Approved by an admin, there is no attestation of the decision.
+    let signing_context = SigningContext {
+        transaction_digest: held.transaction_digest.to_string(),
+        matched_rule: held.matched_rule,
+        attestation: None,
+    };
+    let gas_station = server.gas_station.clone();
+    let execution = execute_allowed_tx(
+        server.gas_station.clone(),
+        server.metrics.clone(),
+        tx_data,
+        user_sig,
+        held.options,
+        server.access_controller.clone(),
+        server.scoped_gas_usage_caps.clone(),
+        server.tx_logger.clone(),
+        ctx,
+        signing_context,
+    );
+    // Spawn a thread to process the request so that it will finish even when client drops the connection.
+    tokio::task::spawn(async move {
+        let (status, Json(response)) = execution.await;
+        wait_for_local_execution(&gas_station, request_type.as_ref(), &response).await;
+        (status, Json(response))
+    })
+    .await
+    .unwrap_or_else(|err| {
+        error!("Failed to spawn execute_tx task: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ExecuteTxResponseV2::new_rpc_err(RpcError::new(
+                ErrorCode::InternalError,
+                "Failed to spawn execute_tx task",
+            ))),
+        )
+    })
+}
+
+/// Drops a held transaction and releases its gas coins.
+async fn reject_transaction(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Json(request): Json<HeldTransactionRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let held =
+        match held_transactions::take(request.transaction_digest, &server.stats_tracker).await {
+            Ok(Some(held)) => held,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(GasStationResponse::new_err_from_str(format!(
+                        "No held transaction with digest {}",
+                        request.transaction_digest
+                    ))),
+                );
+            }
+            Err(err) => {
+                error!("Failed to take the held transaction: {:?}", err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(GasStationResponse::new_err(err)),
+                );
+            }
+        };
+    info!(
+        "Rejecting held transaction {} of sender {}",
+        held.transaction_digest, held.sender
+    );
+    release_reservation(&server.gas_station, held.sponsor, held.reservation_id).await;
+    cancel_in_access_controller(
+        &server.access_controller,
+        held.transaction_digest,
+        &server.stats_tracker,
+    )
+    .await;
+    (StatusCode::OK, Json(GasStationResponse::new_ok(held)))
+}
+
+async fn usage(
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Extension(server): Extension<ServerState>,
+    Query(request): Query<UsageRequest>,
+) -> impl IntoResponse {
+    if !server
+        .secret
+        .is_authorized(authorization.as_ref().map(|auth| auth.token()))
+    {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(GasStationResponse::new_err_from_str(
+                "Invalid authorization token",
+            )),
+        );
+    }
+    let scope = match (request.sender, request.package) {
+        (Some(sender), None) => UsageScope::Sender(sender),
+        (None, Some(package)) => UsageScope::Package(package),
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err_from_str(
+                    "Exactly one of sender and package must be set",
+                )),
+            );
+        }
+    };
+    let window = match request.window.as_deref().map(humantime::parse_duration) {
+        None => DEFAULT_USAGE_WINDOW,
+        Some(Ok(window)) => window,
+        Some(Err(err)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(GasStationResponse::new_err_from_str(format!(
+                    "Invalid window: {}",
+                    err
+                ))),
+            );
+        }
+    };
+    if window > MAX_USAGE_REPORT_WINDOW {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(GasStationResponse::new_err_from_str(format!(
+                "Window must not exceed {}",
+                humantime::format_duration(MAX_USAGE_REPORT_WINDOW)
+            ))),
+        );
+    }
+    match server.stats_tracker.get_gas_usage(scope, window).await {
+        Ok(usage) => (
+            StatusCode::OK,
+            Json(GasStationResponse::new_ok(UsageReport {
+                sender: request.sender,
+                package: request.package,
+                window_secs: window.as_secs(),
+                gas_used: usage.gas_used,
+                transaction_count: usage.transaction_count,
+            })),
+        ),
+        Err(err) => {
+            error!("Failed to get gas usage: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GasStationResponse::new_err(err)),
+            )
+        }
+    }
+}
+
+fn convert_tx_kind(tx_kind_bytes: Base64) -> anyhow::Result<TransactionKind> {
+    let tx_kind = bcs::from_bytes(
+        &tx_kind_bytes
+            .to_vec()
+            .map_err(|_| anyhow::anyhow!("Failed to convert tx_kind_bytes to vector"))?,
+    )?;
+    Ok(tx_kind)
+}
+
 fn convert_tx_and_sig(
     tx_bytes: Base64,
     user_sig: Base64,
-) -> anyhow::Result<(TransactionData, GenericSignature)> {
-    let tx = bcs::from_bytes(
-        &tx_bytes
-            .to_vec()
-            .map_err(|_| anyhow::anyhow!("Failed to convert tx_bytes to vector"))?,
-    )?;
-    let user_sig = GenericSignature::from_bytes(
-        &user_sig
-            .to_vec()
-            .map_err(|_| anyhow::anyhow!("Failed to convert user_sig to vector"))?,
-    )?;
+) -> Result<(TransactionData, GenericSignature), RpcError> {
+    let tx = tx_bytes
+        .to_vec()
+        .ok()
+        .and_then(|bytes| bcs::from_bytes(&bytes).ok())
+        .ok_or_else(|| {
+            RpcError::new(
+                ErrorCode::InvalidRequest,
+                "Invalid bcs bytes for TransactionData",
+            )
+        })?;
+    let user_sig = user_sig
+        .to_vec()
+        .ok()
+        .and_then(|bytes| GenericSignature::from_bytes(&bytes).ok())
+        .ok_or_else(|| RpcError::new(ErrorCode::InvalidUserSig, "Invalid user signature"))?;
+    verify_user_sig(&tx, &user_sig).map_err(|err| {
+        debug!("Invalid user signature: {:?}", err);
+        RpcError::new(ErrorCode::InvalidUserSig, err)
+    })?;
     Ok((tx, user_sig))
 }
+
+fn reserve_gas_error_code(err: &anyhow::Error) -> ErrorCode {
+    match err.downcast_ref::<GasStationError>() {
+        Some(err) => gas_station_error_code(err),
+        None => ErrorCode::InternalError,
+    }
+}
+
+fn gas_station_error_code(err: &GasStationError) -> ErrorCode {
+    match err {
+        GasStationError::InsufficientPoolBalance => ErrorCode::InsufficientPoolBalance,
+        GasStationError::PoolDraining => ErrorCode::PoolDraining,
+        GasStationError::GasUsageCapExceeded => ErrorCode::GasUsageCapExceeded,
+        GasStationError::ReservationExpired(_) => ErrorCode::ReservationExpired,
+        GasStationError::GasCoinUsedAsArgument => ErrorCode::InvalidRequest,
+        GasStationError::InvalidUserSig => ErrorCode::InvalidUserSig,
+        GasStationError::IdempotencyKeyReused => ErrorCode::InvalidRequest,
+    }
+}
+
+fn gas_data_error_code(err: &GasDataError) -> ErrorCode {
+    match err {
+        GasDataError::UnknownGasOwner(_) => ErrorCode::GasOwnerMismatch,
+        GasDataError::PaymentMismatch { .. } => ErrorCode::GasPaymentMismatch,
+        GasDataError::BudgetExceedsReservation { .. } => ErrorCode::GasBudgetExceedsReservation,
+    }
+}
+
+fn execute_tx_error_code(err: &anyhow::Error) -> ErrorCode {
+    if let Some(err) = err.downcast_ref::<GasDataError>() {
+        return gas_data_error_code(err);
+    }
+    match err.downcast_ref::<GasStationError>() {
+        Some(err) => gas_station_error_code(err),
+        None => ErrorCode::ExecutionFailed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservation_client() {
+        let config = IpFilterConfig {
+            trusted_proxies: vec!["172.16.0.0/12".parse().unwrap()],
+            ..Default::default()
+        };
+        let proxy: SocketAddr = "172.16.0.1:9527".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.1.1.1".parse().unwrap());
+        // Behind a trusted proxy, the reservation is counted under the forwarded client
+        assert_eq!(
+            reservation_client(&headers, Some(proxy), &config),
+            "1.1.1.1"
+        );
+        assert_eq!(
+            reservation_client(&headers, Some("3.3.3.3:9527".parse().unwrap()), &config),
+            "3.3.3.3"
+        );
+        headers.insert(CLIENT_ID_HEADER, "client".parse().unwrap());
+        assert_eq!(reservation_client(&headers, Some(proxy), &config), "client");
+        assert_eq!(
+            reservation_client(&HeaderMap::new(), None, &config),
+            "unknown"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Context};
+use fastcrypto::traits::ToFromBytes;
+use iota_types::base_types::IotaAddress;
+use iota_types::crypto::{CompressedSignature, IotaSignature, Signature};
+use iota_types::multisig::MultiSig;
+use iota_types::signature::GenericSignature;
+use iota_types::transaction::{TransactionData, TransactionDataAPI};
+use iota_types::zk_login_authenticator::ZkLoginAuthenticator;
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+
+/// Verifies the signature of the sender over the transaction before it's sponsored, so that a
+/// signature over other bytes or with the wrong intent fails right away, with an error telling
+/// what is wrong, instead of after a round trip to the validators.
+///
+/// The plain signatures are verified fully. A multisig must be of the sender's multisig public
+/// key and reach its threshold, and the signatures of its members are verified, except those of
+/// its zkLogin members. A zkLogin signature must be of the sender's address and its ephemeral
+/// signature is verified, while the proof and the max epoch are verified by the validators,
+/// which know the JWKs.
+pub fn verify_user_sig(
+    tx_data: &TransactionData,
+    user_sig: &GenericSignature,
+) -> anyhow::Result<()> {
+    match user_sig {
+        GenericSignature::Signature(signature) => {
+            let sender = tx_data.sender();
+            let signer = IotaAddress::from(&signature.to_public_key()?);
+            if signer != sender {
+                bail!(
+                    "The user signature is made by {} instead of the sender {}",
+                    signer,
+                    sender
+                );
+            }
+            verify_signature(tx_data, signature, "user signature")
+        }
+        GenericSignature::MultiSig(multisig) => verify_multisig(tx_data, multisig),
+        GenericSignature::ZkLoginAuthenticator(zklogin) => verify_zklogin(tx_data, zklogin),
+        _ => Ok(()),
+    }
+}
+
+fn verify_multisig(tx_data: &TransactionData, multisig: &MultiSig) -> anyhow::Result<()> {
+    let sender = tx_data.sender();
+    let multisig_pk = multisig.get_pk();
+    let signer = IotaAddress::from(multisig_pk);
+    if signer != sender {
+        bail!(
+            "The multisig user signature is of the multisig address {} instead of the sender {}",
+            signer,
+            sender
+        );
+    }
+    let members = multisig_pk.pubkeys();
+    let indices = multisig.get_indices()?;
+    if indices.len() != multisig.get_sigs().len() {
+        bail!("The multisig user signature has a bitmap not matching its signatures");
+    }
+    let mut weight = 0u16;
+    for (index, compressed) in indices.into_iter().zip(multisig.get_sigs()) {
+        let Some((public_key, member_weight)) = members.get(index as usize) else {
+            bail!(
+                "The multisig user signature has a signature of unknown member {}",
+                index
+            );
+        };
+        // The zkLogin members need the JWKs, they are verified by the validators.
+        if !matches!(compressed, CompressedSignature::ZkLogin(_)) {
+            let signature = Signature::from_bytes(
+                &[
+                    &[public_key.flag()][..],
+                    compressed.as_ref(),
+                    public_key.as_ref(),
+                ]
+                .concat(),
+            )
+            .with_context(|| {
+                format!(
+                    "The multisig user signature has an invalid signature of member {}",
+                    index
+                )
+            })?;
+            verify_signature(
+                tx_data,
+                &signature,
+                &format!(
+                    "signature of member {} of the multisig user signature",
+                    index
+                ),
+            )?;
+        }
+        weight = weight.saturating_add(*member_weight as u16);
+    }
+    let threshold = *multisig_pk.get_threshold();
+    if weight < threshold {
+        bail!(
+            "The multisig user signature has a weight of {}, below the threshold {} of the multisig",
+            weight,
+            threshold
+        );
+    }
+    Ok(())
+}
+
+fn verify_zklogin(tx_data: &TransactionData, zklogin: &ZkLoginAuthenticator) -> anyhow::Result<()> {
+    let sender = tx_data.sender();
+    let addresses = zklogin_addresses(zklogin);
+    if !addresses.contains(&sender) {
+        bail!(
+            "The zkLogin user signature is of the address {} instead of the sender {}",
+            addresses
+                .first()
+                .map_or_else(|| "unknown".to_string(), ToString::to_string),
+            sender
+        );
+    }
+    verify_signature(
+        tx_data,
+        &zklogin.user_signature,
+        "ephemeral signature of the zkLogin user signature",
+    )
+}
+
+/// The addresses of a zkLogin signature, with and without the padding of the address seed used
+/// by the first zkLogin addresses.
+fn zklogin_addresses(zklogin: &ZkLoginAuthenticator) -> Vec<IotaAddress> {
+    [
+        IotaAddress::try_from_unpadded(&zklogin.inputs),
+        IotaAddress::try_from_padded(&zklogin.inputs),
+    ]
+    .into_iter()
+    .filter_map(Result::ok)
+    .collect()
+}
+
+/// Verifies a plain signature over the transaction, by the key it carries. `name` describes the
+/// signature in the errors.
+fn verify_signature(
+    tx_data: &TransactionData,
+    signature: &Signature,
+    name: &str,
+) -> anyhow::Result<()> {
+    let signer = IotaAddress::from(&signature.to_public_key()?);
+    let scheme = signature.scheme();
+    let transaction = IntentMessage::new(Intent::iota_transaction(), tx_data);
+    if signature
+        .verify_secure(&transaction, signer, scheme)
+        .is_ok()
+    {
+        return Ok(());
+    }
+    let personal_message = IntentMessage::new(
+        Intent::personal_message(),
+        PersonalMessage {
+            message: bcs::to_bytes(tx_data)?,
+        },
+    );
+    if signature
+        .verify_secure(&personal_message, signer, scheme)
+        .is_ok()
+    {
+        bail!(
+            "The {} is over a personal message, the tx_bytes must be signed as a transaction",
+            name
+        );
+    }
+    bail!("The {} doesn't match the tx_bytes, they were changed after being signed, e.g. by a new reservation", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_types::base_types::random_object_ref;
+    use iota_types::crypto::{get_account_key_pair, get_key_pair, IotaKeyPair, Signature};
+    use iota_types::multisig::MultiSigPublicKey;
+    use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use iota_types::transaction::TransactionKind;
+    use iota_types::utils::{get_zklogin_inputs, get_zklogin_user_address, get_zklogin_user_key};
+
+    use super::*;
+
+    fn transaction(sender: IotaAddress) -> TransactionData {
+        TransactionData::new_with_gas_coins_allow_sponsor(
+            TransactionKind::programmable(ProgrammableTransactionBuilder::new().finish()),
+            sender,
+            vec![random_object_ref()],
+            1000,
+            1000,
+            IotaAddress::random_for_testing_only(),
+        )
+    }
+
+    fn sign(tx_data: &TransactionData, keypair: &IotaKeyPair) -> GenericSignature {
+        GenericSignature::from(Signature::new_secure(
+            &IntentMessage::new(Intent::iota_transaction(), tx_data),
+            keypair,
+        ))
+    }
+
+    #[test]
+    fn test_verify_user_sig() {
+        let (sender, keypair) = get_account_key_pair();
+        let mut tx_data = transaction(sender);
+        let user_sig = GenericSignature::from(Signature::new_secure(
+            &IntentMessage::new(Intent::iota_transaction(), &tx_data),
+            &keypair,
+        ));
+        verify_user_sig(&tx_data, &user_sig).unwrap();
+
+        let (_, other_keypair) = get_account_key_pair();
+        let other_sig = GenericSignature::from(Signature::new_secure(
+            &IntentMessage::new(Intent::iota_transaction(), &tx_data),
+            &other_keypair,
+        ));
+        let err = verify_user_sig(&tx_data, &other_sig).unwrap_err();
+        assert!(err.to_string().contains("instead of the sender"));
+
+        let personal_message_sig = GenericSignature::from(Signature::new_secure(
+            &IntentMessage::new(
+                Intent::personal_message(),
+                PersonalMessage {
+                    message: bcs::to_bytes(&tx_data).unwrap(),
+                },
+            ),
+            &keypair,
+        ));
+        let err = verify_user_sig(&tx_data, &personal_message_sig).unwrap_err();
+        assert!(err.to_string().contains("personal message"));
+
+        tx_data.gas_data_mut().budget += 1;
+        let err = verify_user_sig(&tx_data, &user_sig).unwrap_err();
+        assert!(err.to_string().contains("doesn't match the tx_bytes"));
+    }
+
+    #[test]
+    fn test_verify_multisig_user_sig() {
+        let keypairs: Vec<IotaKeyPair> = (0..3)
+            .map(|_| IotaKeyPair::Ed25519(get_key_pair().1))
+            .collect();
+        let multisig_pk = MultiSigPublicKey::new(
+            keypairs.iter().map(|keypair| keypair.public()).collect(),
+            vec![1, 1, 1],
+            2,
+        )
+        .unwrap();
+        let sender = IotaAddress::from(&multisig_pk);
+        let mut tx_data = transaction(sender);
+        let combine = |tx_data: &TransactionData, signers: &[usize]| {
+            GenericSignature::MultiSig(
+                MultiSig::combine(
+                    signers
+                        .iter()
+                        .map(|index| sign(tx_data, &keypairs[*index]))
+                        .collect(),
+                    multisig_pk.clone(),
+                )
+                .unwrap(),
+            )
+        };
+
+        let user_sig = combine(&tx_data, &[0, 2]);
+        verify_user_sig(&tx_data, &user_sig).unwrap();
+
+        let err = verify_user_sig(&tx_data, &combine(&tx_data, &[1])).unwrap_err();
+        assert!(err.to_string().contains("below the threshold 2"));
+
+        // The members signed other bytes.
+        let mut changed_tx_data = tx_data.clone();
+        changed_tx_data.gas_data_mut().budget += 1;
+        let err = verify_user_sig(&changed_tx_data, &user_sig).unwrap_err();
+        assert!(err.to_string().contains("member 0"));
+        assert!(err.to_string().contains("doesn't match the tx_bytes"));
+
+        tx_data = transaction(IotaAddress::random_for_testing_only());
+        let err = verify_user_sig(&tx_data, &combine(&tx_data, &[0, 1])).unwrap_err();
+        assert!(err.to_string().contains("instead of the sender"));
+    }
+
+    #[test]
+    fn test_verify_zklogin_user_sig() {
+        let sender = get_zklogin_user_address();
+        let ephemeral_keypair = get_zklogin_user_key();
+        let zklogin = |tx_data: &TransactionData| {
+            let GenericSignature::Signature(signature) = sign(tx_data, &ephemeral_keypair) else {
+                unreachable!()
+            };
+            GenericSignature::ZkLoginAuthenticator(ZkLoginAuthenticator::new(
+                get_zklogin_inputs(),
+                10,
+                signature,
+            ))
+        };
+        let mut tx_data = transaction(sender);
+        let user_sig = zklogin(&tx_data);
+        verify_user_sig(&tx_data, &user_sig).unwrap();
+
+        tx_data.gas_data_mut().budget += 1;
+        let err = verify_user_sig(&tx_data, &user_sig).unwrap_err();
+        assert!(err.to_string().contains("ephemeral signature"));
+        assert!(err.to_string().contains("doesn't match the tx_bytes"));
+
+        let tx_data = transaction(IotaAddress::random_for_testing_only());
+        let err = verify_user_sig(&tx_data, &zklogin(&tx_data)).unwrap_err();
+        assert!(err.to_string().contains("instead of the sender"));
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end check of a deployed station: reserves gas, signs a transaction that does nothing
+//! with a user key, and executes it through the station, as a client of the station would.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use iota_json_rpc_types::IotaTransactionBlockEffects;
+use iota_types::base_types::IotaAddress;
+use iota_types::crypto::{EncodeDecodeBase64, IotaKeyPair, Signature};
+use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use iota_types::transaction::{TransactionData, TransactionKind};
+use shared_crypto::intent::{Intent, IntentMessage};
+
+use crate::rpc::client::GasStationRpcClient;
+
+/// Reservation duration of the self test, long enough to sign and execute the transaction.
+const SELF_TEST_RESERVE_DURATION_SECS: u64 = 30;
+
+/// Reads the key of `address` from a keystore file of the IOTA CLI, a JSON list of base64
+/// encoded keys. The first key is used if no address is given.
+pub fn read_keystore(path: &Path, address: Option<IotaAddress>) -> anyhow::Result<IotaKeyPair> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the keystore {}", path.display()))?;
+    let keys: Vec<String> = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid keystore {}", path.display()))?;
+    let mut keypairs = keys.iter().map(|key| {
+        IotaKeyPair::decode_base64(key)
+            .map_err(|err| anyhow!("Invalid key in keystore {}: {}", path.display(), err))
+    });
+    match address {
+        Some(address) => keypairs
+            .find(|keypair| {
+                keypair
+                    .as_ref()
+                    .is_ok_and(|keypair| IotaAddress::from(&keypair.public()) == address)
+            })
+            .ok_or_else(|| anyhow!("No key of {} in keystore {}", address, path.display()))?,
+        None => keypairs
+            .next()
+            .ok_or_else(|| anyhow!("The keystore {} is empty", path.display()))?,
+    }
+}
+
+/// Reserves `gas_budget`, then executes a transaction without commands sent by the owner of
+/// `keypair` and sponsored by the station. Returns the effects of the transaction.
+pub async fn run_sponsor_self_test(
+    client: &GasStationRpcClient,
+    keypair: &IotaKeyPair,
+    gas_budget: u64,
+) -> anyhow::Result<IotaTransactionBlockEffects> {
+    let sponsor_info = client
+        .sponsor_info()
+        .await
+        .context("Failed to get the sponsor info")?;
+    let (sponsor, reservation_id, gas_coins) = client
+        .reserve_gas(gas_budget, SELF_TEST_RESERVE_DURATION_SECS)
+        .await
+        .context("Failed to reserve gas")?;
+    let sender = IotaAddress::from(&keypair.public());
+    let tx_data = TransactionData::new_with_gas_coins_allow_sponsor(
+        TransactionKind::ProgrammableTransaction(ProgrammableTransactionBuilder::new().finish()),
+        sender,
+        gas_coins,
+        gas_budget,
+        sponsor_info.reference_gas_price,
+        sponsor,
+    );
+    let intent_msg = IntentMessage::new(Intent::iota_transaction(), &tx_data);
+    let user_sig = Signature::new_secure(&intent_msg, keypair).into();
+    client
+        .execute_tx(reservation_id, &tx_data, &user_sig, None, None)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to execute the transaction of reservation {}",
+                reservation_id
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::start_rpc_server_for_testing;
+    use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
+    use iota_types::crypto::get_account_key_pair;
+    use iota_types::gas_coin::NANOS_PER_IOTA;
+
+    #[test]
+    fn test_read_keystore() {
+        let first = IotaKeyPair::Ed25519(get_account_key_pair().1);
+        let second = IotaKeyPair::Ed25519(get_account_key_pair().1);
+        let second_address = IotaAddress::from(&second.public());
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            serde_json::to_string(&[first.encode_base64(), second.encode_base64()]).unwrap(),
+        )
+        .unwrap();
+
+        let keypair = read_keystore(file.path(), None).unwrap();
+        assert_eq!(keypair.public(), first.public());
+        let keypair = read_keystore(file.path(), Some(second_address)).unwrap();
+        assert_eq!(keypair.public(), second.public());
+        assert!(read_keystore(file.path(), Some(IotaAddress::random_for_testing_only())).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_sponsor_self_test() {
+        let (_test_cluster, _container, server) =
+            start_rpc_server_for_testing(vec![NANOS_PER_IOTA; 10], NANOS_PER_IOTA).await;
+        let client = server.get_local_client();
+        let keypair = IotaKeyPair::Ed25519(get_account_key_pair().1);
+        let effects = run_sponsor_self_test(&client, &keypair, NANOS_PER_IOTA / 10)
+            .await
+            .unwrap();
+        assert!(effects.status().is_ok());
+    }
+}
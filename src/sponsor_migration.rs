@@ -0,0 +1,142 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::iota_client::IotaClient;
+use crate::storage::MAX_GAS_PER_QUERY;
+use crate::tx_signer::TxSigner;
+use crate::types::GasCoin;
+use anyhow::bail;
+use iota_json_rpc_types::IotaTransactionBlockEffectsAPI;
+use iota_types::base_types::IotaAddress;
+use iota_types::gas_coin::NANOS_PER_IOTA;
+use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use iota_types::transaction::{Transaction, TransactionData};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Gas budget of a single migration transaction. Coins are merged into the gas coin, so this
+/// only has to cover the transfer itself.
+const MIGRATION_GAS_BUDGET: u64 = NANOS_PER_IOTA / 10;
+
+/// Moves all the IOTA coins owned by the previous sponsor to the new sponsor, as the last step of
+/// a key rotation. The coins are merged and transferred in batches; the gas pool initializer of
+/// the new sponsor splits them again on its next run.
+///
+/// This must only run once the previous sponsor has no pending reservations left, otherwise
+/// their execution fails. Returns the total balance transferred to the new sponsor.
+pub async fn migrate_sponsor_coins(
+    iota_client: &IotaClient,
+    previous_signer: &Arc<dyn TxSigner>,
+    new_sponsor: IotaAddress,
+) -> anyhow::Result<u64> {
+    let previous_sponsor = previous_signer.get_address();
+    if previous_sponsor == new_sponsor {
+        bail!("The previous and the new sponsor are the same address");
+    }
+    let coins = iota_client
+        .get_all_owned_iota_coins_above_balance_threshold(previous_sponsor, 0)
+        .await?;
+    info!(
+        "Migrating {} coins from {} to {}",
+        coins.len(),
+        previous_sponsor,
+        new_sponsor
+    );
+    transfer_coins(iota_client, previous_signer, &coins, new_sponsor).await
+}
+
+/// Merges the given coins of the signer and transfers them to `recipient` in batches, which may
+/// be the signer itself. Batches that cannot cover the gas budget are skipped. Returns the total
+/// balance transferred.
+pub async fn transfer_coins(
+    iota_client: &IotaClient,
+    signer: &Arc<dyn TxSigner>,
+    coins: &[GasCoin],
+    recipient: IotaAddress,
+) -> anyhow::Result<u64> {
+    let rgp = iota_client.get_reference_gas_price().await?;
+    let mut transferred_balance = 0;
+    for batch in coins.chunks(MAX_GAS_PER_QUERY) {
+        transferred_balance += transfer_batch(iota_client, signer, batch, recipient, rgp)
+            .await?
+            .unwrap_or_default();
+    }
+    Ok(transferred_balance)
+}
+
+/// Merges a batch of at most `MAX_GAS_PER_QUERY` coins of the signer and transfers it to
+/// `recipient`. Returns the balance transferred once the effects confirm it, or None if the batch
+/// was skipped because it cannot cover the gas budget.
+pub async fn transfer_batch(
+    iota_client: &IotaClient,
+    signer: &Arc<dyn TxSigner>,
+    batch: &[GasCoin],
+    recipient: IotaAddress,
+    rgp: u64,
+) -> anyhow::Result<Option<u64>> {
+    let batch_balance: u64 = batch.iter().map(|coin| coin.balance).sum();
+    if batch_balance <= MIGRATION_GAS_BUDGET {
+        warn!(
+            "Skipping {} coins with total balance {} which cannot cover the gas budget",
+            batch.len(),
+            batch_balance
+        );
+        return Ok(None);
+    }
+    let tx_data = build_migration_tx(signer.get_address(), recipient, batch, rgp);
+    let sig = signer.sign_transaction(&tx_data).await?;
+    let tx = Transaction::from_generic_sig_data(tx_data, vec![sig]);
+    let effects = iota_client.execute_transaction(tx, 3, None).await?;
+    if !effects.status().is_ok() {
+        bail!("Transfer transaction failed: {:?}", effects.status());
+    }
+    let transferred = (batch_balance as i64 - effects.gas_cost_summary().net_gas_usage()) as u64;
+    info!(
+        "Transferred {} coins with total balance {} to {}",
+        batch.len(),
+        transferred,
+        recipient
+    );
+    Ok(Some(transferred))
+}
+
+fn build_migration_tx(
+    previous_sponsor: IotaAddress,
+    new_sponsor: IotaAddress,
+    coins: &[GasCoin],
+    rgp: u64,
+) -> TransactionData {
+    let mut pt_builder = ProgrammableTransactionBuilder::new();
+    pt_builder.pay_all_iota(new_sponsor);
+    TransactionData::new_programmable(
+        previous_sponsor,
+        coins.iter().map(|coin| coin.object_ref).collect(),
+        pt_builder.finish(),
+        MIGRATION_GAS_BUDGET,
+        rgp,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iota_types::base_types::random_object_ref;
+    use iota_types::transaction::TransactionDataAPI;
+
+    #[test]
+    fn test_build_migration_tx() {
+        let previous_sponsor = IotaAddress::random_for_testing_only();
+        let new_sponsor = IotaAddress::random_for_testing_only();
+        let coins: Vec<_> = (0..3)
+            .map(|_| GasCoin {
+                object_ref: random_object_ref(),
+                balance: NANOS_PER_IOTA,
+            })
+            .collect();
+        let tx_data = build_migration_tx(previous_sponsor, new_sponsor, &coins, 1000);
+        assert_eq!(tx_data.sender(), previous_sponsor);
+        assert_eq!(tx_data.gas_data().owner, previous_sponsor);
+        assert_eq!(tx_data.gas_data().payment.len(), 3);
+        assert_eq!(tx_data.gas_data().budget, MIGRATION_GAS_BUDGET);
+    }
+}
@@ -4,7 +4,10 @@
 use crate::config::GasStationStorageConfig;
 use crate::metrics::StorageMetrics;
 use crate::storage::redis::RedisStorage;
-use crate::types::{GasCoin, ReservationID};
+use crate::types::{
+    ClientReservationStats, GasCoin, InitProgress, PoolCoin, PoolSnapshot, QuarantinedCoin,
+    Reservation, ReservationID, ReservationStats,
+};
 use iota_types::base_types::{IotaAddress, ObjectID};
 use std::sync::Arc;
 
@@ -24,17 +27,126 @@ pub trait Storage: Sync + Send {
     /// 2. It keeps a record of the reserved coins with timestamp, so that in the case
     ///    when caller forgets to release them, some cleanup process can clean them up latter.
     /// 3. It should never return more than 256 coins at a time since that's the upper bound of gas.
+    /// To keep the large coins for the large budgets, it should reserve the smallest coin covering
+    /// the budget, or the fewest coins if none does.
     async fn reserve_gas_coins(
         &self,
         target_budget: u64,
         reserved_duration_ms: u64,
     ) -> anyhow::Result<(ReservationID, Vec<GasCoin>)>;
 
+    /// Same as `reserve_gas_coins`, but while the reservation made with the same
+    /// `idempotency_key` is neither executed, released nor expired, it is returned instead of
+    /// reserving new coins. Also returns whether the reservation was made by this call. Fails if
+    /// the key was used for another budget.
+    async fn reserve_gas_coins_idempotent(
+        &self,
+        target_budget: u64,
+        reserved_duration_ms: u64,
+        idempotency_key: &str,
+    ) -> anyhow::Result<(ReservationID, Vec<GasCoin>, bool)>;
+
     async fn ready_for_execution(&self, reservation_id: ReservationID) -> anyhow::Result<()>;
 
+    /// Returns the gas budget and the coins of a reservation that is neither executed nor
+    /// expired. Returns `None` as well for the reservations copied from another storage, whose
+    /// budget and coins are not known.
+    async fn get_reservation(
+        &self,
+        reservation_id: ReservationID,
+    ) -> anyhow::Result<Option<Reservation>>;
+
     async fn add_new_coins(&self, new_coins: Vec<GasCoin>) -> anyhow::Result<()>;
 
-    async fn expire_coins(&self) -> anyhow::Result<Vec<ObjectID>>;
+    /// Takes the expired reservations out of the reservation queue and returns their coins.
+    async fn expire_reservations(&self) -> anyhow::Result<Vec<(ReservationID, Vec<ObjectID>)>>;
+
+    async fn expire_coins(&self) -> anyhow::Result<Vec<ObjectID>> {
+        Ok(self
+            .expire_reservations()
+            .await?
+            .into_iter()
+            .flat_map(|(_, coin_ids)| coin_ids)
+            .collect())
+    }
+
+    /// Keeps coins that cannot be used until `release_epoch` out of the pool. Quarantined coins
+    /// are neither available nor reserved.
+    async fn quarantine_coins(&self, coins: Vec<GasCoin>, release_epoch: u64)
+        -> anyhow::Result<()>;
+
+    /// Takes the coins whose release epoch is at most `current_epoch` out of the quarantine and
+    /// returns their IDs. The caller is responsible for adding them back to the pool.
+    async fn release_quarantined_coins(&self, current_epoch: u64) -> anyhow::Result<Vec<ObjectID>>;
+
+    async fn get_quarantined_coins(&self) -> anyhow::Result<Vec<QuarantinedCoin>>;
+
+    /// Lists every coin of the pool along with whether it is available, reserved or quarantined.
+    /// This reads the whole pool, so it is only meant for debugging.
+    async fn get_pool_coins(&self) -> anyhow::Result<Vec<PoolCoin>>;
+
+    /// Marks the pool as draining, so that reserving gas coins fails from now on, and returns
+    /// all the available coins. They stay in the pool until removed with
+    /// `remove_available_coins`, once transferred. Coins that are reserved or quarantined at that
+    /// time are left in the pool once released.
+    async fn drain_available_coins(&self) -> anyhow::Result<Vec<GasCoin>>;
+
+    /// Removes the given available coins from the pool, e.g. once the drain transferred them.
+    /// Returns how many were removed; coins that are not available are ignored.
+    async fn remove_available_coins(&self, object_ids: &[ObjectID]) -> anyhow::Result<usize>;
+
+    /// Takes out up to `max_count` available coins with a balance of at least `min_balance`, the
+    /// largest first, so that they can be split again. Nothing is taken out while draining.
+    async fn take_large_coins(
+        &self,
+        min_balance: u64,
+        max_count: usize,
+    ) -> anyhow::Result<Vec<GasCoin>>;
+
+    async fn is_draining(&self) -> anyhow::Result<bool>;
+
+    /// Allows reserving gas coins again after the pool was drained.
+    async fn resume_pool(&self) -> anyhow::Result<()>;
+
+    /// Reads the whole pool at once, to copy it to another storage. With `read_only`, the pool is
+    /// marked as draining in the same step, so that no coin copied can be reserved from it anymore.
+    async fn export_pool(&self, read_only: bool) -> anyhow::Result<PoolSnapshot>;
+
+    /// Writes a pool exported from another storage. Fails if this storage already has a pool for
+    /// the sponsor, so that two pools are never merged.
+    async fn import_pool(&self, pool: &PoolSnapshot) -> anyhow::Result<()>;
+
+    /// Sets the expiration of the reservation to `reserved_duration_ms` from now, to keep its
+    /// coins reserved longer or to release them sooner. Fails if the reservation no longer exists.
+    async fn set_reservation_expiration(
+        &self,
+        reservation_id: ReservationID,
+        reserved_duration_ms: u64,
+    ) -> anyhow::Result<()>;
+
+    /// Pushes back the expiration of the reservation to at least `reserved_duration_ms` from now,
+    /// without ever bringing it forward. Returns false if the reservation no longer exists or
+    /// already expired, in which case it is left untouched.
+    async fn extend_reservation_expiration(
+        &self,
+        reservation_id: ReservationID,
+        reserved_duration_ms: u64,
+    ) -> anyhow::Result<bool>;
+
+    /// Stores the response of an executed transaction under the idempotency key of its request,
+    /// for `retention_ms`.
+    async fn put_executed_transaction(
+        &self,
+        idempotency_key: &str,
+        data: &str,
+        retention_ms: u64,
+    ) -> anyhow::Result<()>;
+
+    /// Returns the response stored under the idempotency key, if it's still retained.
+    async fn get_executed_transaction(
+        &self,
+        idempotency_key: &str,
+    ) -> anyhow::Result<Option<String>>;
 
     /// Initialize some of the Gas Station statistics at the startup.
     /// Such as the total number of gas coins and the total balance.
@@ -53,9 +165,42 @@ pub trait Storage: Sync + Send {
     /// Once the lock is acquired, until it expires, no other caller can acquire the lock.
     /// The reason we use a lock duration is such that in case the server crashed while holding the lock,
     /// the lock will be automatically considered as released after the lock duration.
-    async fn acquire_init_lock(&self, lock_duration_sec: u64) -> anyhow::Result<bool>;
+    async fn acquire_init_lock(
+        &self,
+        holder_id: &str,
+        lock_duration_sec: u64,
+    ) -> anyhow::Result<bool>;
+
+    /// Extends the init lock to `lock_duration_sec` from now if `holder_id` still holds it.
+    /// Returns false if the lock was acquired by another caller after it expired.
+    async fn renew_init_lock(
+        &self,
+        holder_id: &str,
+        lock_duration_sec: u64,
+    ) -> anyhow::Result<bool>;
+
+    /// Releases the init lock if `holder_id` holds it.
+    async fn release_init_lock(&self, holder_id: &str) -> anyhow::Result<()>;
+
+    /// Marks the pool as initialized, once the first initialization completed.
+    async fn set_initialized(&self) -> anyhow::Result<()>;
+
+    /// Stores the progress of the coin initialization, or removes it once it's finished.
+    async fn set_init_progress(&self, progress: Option<&InitProgress>) -> anyhow::Result<()>;
+
+    async fn get_init_progress(&self) -> anyhow::Result<Option<InitProgress>>;
+
+    /// Acquires the leader lease of the pool for `holder_id`, or renews it if `holder_id` already
+    /// holds it. Returns whether `holder_id` holds the lease. A lease that isn't renewed within
+    /// `lease_duration_ms` can be acquired by another replica.
+    async fn acquire_leader_lease(
+        &self,
+        holder_id: &str,
+        lease_duration_ms: u64,
+    ) -> anyhow::Result<bool>;
 
-    async fn release_init_lock(&self) -> anyhow::Result<()>;
+    /// Releases the leader lease if `holder_id` holds it.
+    async fn release_leader_lease(&self, holder_id: &str) -> anyhow::Result<()>;
 
     async fn check_health(&self) -> anyhow::Result<()>;
 
@@ -66,19 +211,55 @@ pub trait Storage: Sync + Send {
 
     async fn get_available_coin_total_balance(&self) -> u64;
 
+    async fn get_reservation_stats(&self) -> anyhow::Result<ReservationStats>;
+
+    /// Records the client of a new reservation, so that it is counted in the client reservation
+    /// stats if it expires without being executed. The client is forgotten after `retention_ms`.
+    async fn record_reservation_client(
+        &self,
+        reservation_id: ReservationID,
+        client: &str,
+        retention_ms: u64,
+    ) -> anyhow::Result<()>;
+
+    /// Returns the `limit` clients with the most reservations expired without being executed.
+    async fn get_client_reservation_stats(
+        &self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ClientReservationStats>>;
+
     #[cfg(test)]
     async fn get_reserved_coin_count(&self) -> usize;
 }
 
+/// Prefix of the keys of the sponsor in a storage shared by several stations, e.g. `prod:0x...`.
+pub fn storage_key_prefix(namespace: Option<&str>, sponsor_address: IotaAddress) -> String {
+    match namespace {
+        Some(namespace) => format!("{}:{}", namespace, sponsor_address),
+        None => sponsor_address.to_string(),
+    }
+}
+
 pub async fn connect_storage(
     config: &GasStationStorageConfig,
     sponsor_address: IotaAddress,
     metrics: Arc<StorageMetrics>,
 ) -> Arc<dyn Storage> {
     let storage: Arc<dyn Storage> = match config {
-        GasStationStorageConfig::Redis { redis_url } => {
-            Arc::new(RedisStorage::new(redis_url, sponsor_address, metrics).await)
-        }
+        GasStationStorageConfig::Redis {
+            redis_url,
+            namespace,
+            mode,
+        } => Arc::new(
+            RedisStorage::new(
+                redis_url,
+                mode,
+                namespace.as_deref(),
+                sponsor_address,
+                metrics,
+            )
+            .await,
+        ),
     };
     storage
         .check_health()
@@ -116,18 +297,29 @@ pub async fn connect_storage_for_testing(sponsor_address: IotaAddress) -> Arc<dy
 
 #[cfg(test)]
 mod tests {
+    use crate::errors::GasStationError;
     use crate::storage::{connect_storage_for_testing, Storage, MAX_GAS_PER_QUERY};
-    use crate::types::GasCoin;
+    use crate::types::{
+        ClientReservationStats, GasCoin, InitProgress, Reservation, ReservationStats,
+    };
     use iota_types::base_types::{random_object_ref, IotaAddress, ObjectID, SequenceNumber};
     use iota_types::digests::ObjectDigest;
     use rand::random;
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::sync::Arc;
     use std::time::Duration;
 
     async fn assert_coin_count(storage: &Arc<dyn Storage>, available: usize, reserved: usize) {
         assert_eq!(storage.get_available_coin_count().await.unwrap(), available);
         assert_eq!(storage.get_reserved_coin_count().await, reserved);
+        assert_eq!(
+            storage
+                .get_reservation_stats()
+                .await
+                .unwrap()
+                .reserved_coin_count,
+            reserved as u64
+        );
     }
 
     async fn setup(sponsor: IotaAddress, init_balances: Vec<u64>) -> Arc<dyn Storage> {
@@ -155,8 +347,6 @@ mod tests {
         let storage = connect_storage_for_testing(sponsor).await;
         assert!(!storage.is_initialized().await.unwrap());
         storage.add_new_coins(vec![]).await.unwrap();
-        // Still not initialized because we are not adding any coins.
-        assert!(!storage.is_initialized().await.unwrap());
         storage
             .add_new_coins(vec![GasCoin {
                 object_ref: random_object_ref(),
@@ -164,6 +354,9 @@ mod tests {
             }])
             .await
             .unwrap();
+        // Still not initialized until the initialization completes.
+        assert!(!storage.is_initialized().await.unwrap());
+        storage.set_initialized().await.unwrap();
         assert!(storage.is_initialized().await.unwrap());
     }
 
@@ -201,7 +394,11 @@ mod tests {
     async fn test_insufficient_pool_budget() {
         let sponsor = IotaAddress::random_for_testing_only();
         let storage = setup(sponsor, vec![1; 100]).await;
-        assert!(storage.reserve_gas_coins(101, 1000).await.is_err());
+        let err = storage.reserve_gas_coins(101, 1000).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GasStationError>(),
+            Some(&GasStationError::InsufficientPoolBalance)
+        );
         assert_coin_count(&storage, 100, 0).await;
     }
 
@@ -221,6 +418,42 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_reservation_stats() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 10]).await;
+        assert_eq!(
+            storage.get_reservation_stats().await.unwrap(),
+            ReservationStats::default()
+        );
+
+        let (res_id1, _) = storage.reserve_gas_coins(2, 1000).await.unwrap();
+        let oldest1 = storage
+            .get_reservation_stats()
+            .await
+            .unwrap()
+            .oldest_reservation_time_ms
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        storage.reserve_gas_coins(3, 1000).await.unwrap();
+        let stats = storage.get_reservation_stats().await.unwrap();
+        assert_eq!(stats.reserved_coin_count, 5);
+        assert_eq!(stats.oldest_reservation_time_ms, Some(oldest1));
+
+        // Reservations that are being executed are no longer counted.
+        storage.ready_for_execution(res_id1).await.unwrap();
+        let stats = storage.get_reservation_stats().await.unwrap();
+        assert_eq!(stats.reserved_coin_count, 3);
+        assert!(stats.oldest_reservation_time_ms.unwrap() > oldest1);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        storage.expire_coins().await.unwrap();
+        assert_eq!(
+            storage.get_reservation_stats().await.unwrap(),
+            ReservationStats::default()
+        );
+    }
+
     #[tokio::test]
     async fn test_coin_release_with_updated_balance() {
         let sponsor = IotaAddress::random_for_testing_only();
@@ -259,6 +492,36 @@ mod tests {
         assert_coin_count(&storage, 50, 0).await;
     }
 
+    #[tokio::test]
+    async fn test_get_reservation() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id1, reserved_gas_coins1) = storage.reserve_gas_coins(10, 900).await.unwrap();
+        let (res_id2, reserved_gas_coins2) = storage.reserve_gas_coins(5, 1000).await.unwrap();
+        assert_eq!(
+            storage.get_reservation(res_id1).await.unwrap(),
+            Some(Reservation {
+                gas_budget: 10,
+                gas_coins: reserved_gas_coins1,
+            })
+        );
+        assert_eq!(
+            storage.get_reservation(res_id2).await.unwrap(),
+            Some(Reservation {
+                gas_budget: 5,
+                gas_coins: reserved_gas_coins2,
+            })
+        );
+        assert_eq!(storage.get_reservation(res_id2 + 1).await.unwrap(), None);
+
+        storage.ready_for_execution(res_id2).await.unwrap();
+        assert_eq!(storage.get_reservation(res_id2).await.unwrap(), None);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        storage.expire_coins().await.unwrap();
+        assert_eq!(storage.get_reservation(res_id1).await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_coin_expiration() {
         let sponsor = IotaAddress::random_for_testing_only();
@@ -304,6 +567,252 @@ mod tests {
         assert_coin_count(&storage, 10, 0).await;
     }
 
+    #[tokio::test]
+    async fn test_reservation_expiration() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id1, reserved_gas_coins1) = storage.reserve_gas_coins(10, 900).await.unwrap();
+        let (res_id2, reserved_gas_coins2) = storage.reserve_gas_coins(20, 900).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let expired = storage
+            .expire_reservations()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(reservation_id, coin_ids)| {
+                (
+                    reservation_id,
+                    coin_ids.into_iter().collect::<BTreeSet<_>>(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let coin_ids = |coins: &[GasCoin]| {
+            coins
+                .iter()
+                .map(|coin| coin.object_ref.0)
+                .collect::<BTreeSet<_>>()
+        };
+        assert_eq!(
+            expired,
+            BTreeMap::from([
+                (res_id1, coin_ids(&reserved_gas_coins1)),
+                (res_id2, coin_ids(&reserved_gas_coins2)),
+            ])
+        );
+        assert!(storage.expire_reservations().await.unwrap().is_empty());
+        assert_coin_count(&storage, 70, 0).await;
+    }
+
+    #[tokio::test]
+    async fn test_client_reservation_stats() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let mut reservation_ids = vec![];
+        for client in ["a", "a", "b", "b", "b"] {
+            let (res_id, _) = storage.reserve_gas_coins(1, 900).await.unwrap();
+            storage
+                .record_reservation_client(res_id, client, 60000)
+                .await
+                .unwrap();
+            reservation_ids.push(res_id);
+        }
+        // Recorded once per reservation
+        storage
+            .record_reservation_client(reservation_ids[0], "a", 60000)
+            .await
+            .unwrap();
+        // Executed reservations are not counted as expired
+        storage
+            .ready_for_execution(reservation_ids[2])
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(storage.expire_reservations().await.unwrap().len(), 4);
+
+        let stats = storage.get_client_reservation_stats(10).await.unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                ClientReservationStats {
+                    client: "b".to_string(),
+                    reservation_count: 3,
+                    expired_count: 2,
+                },
+                ClientReservationStats {
+                    client: "a".to_string(),
+                    reservation_count: 2,
+                    expired_count: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            storage.get_client_reservation_stats(1).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_reservation_expiration() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id1, _) = storage.reserve_gas_coins(10, 900).await.unwrap();
+        let (res_id2, _) = storage.reserve_gas_coins(20, 60_000).await.unwrap();
+        storage
+            .set_reservation_expiration(res_id1, 60_000)
+            .await
+            .unwrap();
+        storage
+            .set_reservation_expiration(res_id2, 0)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let expired = storage.expire_reservations().await.unwrap();
+        assert_eq!(
+            expired
+                .iter()
+                .map(|(reservation_id, _)| *reservation_id)
+                .collect::<Vec<_>>(),
+            vec![res_id2]
+        );
+        assert_coin_count(&storage, 70, 10).await;
+
+        storage.ready_for_execution(res_id1).await.unwrap();
+        let err = storage
+            .set_reservation_expiration(res_id1, 60_000)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GasStationError>(),
+            Some(&GasStationError::ReservationExpired(res_id1))
+        );
+        let err = storage.ready_for_execution(res_id1).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GasStationError>(),
+            Some(&GasStationError::ReservationExpired(res_id1))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extend_reservation_expiration() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id1, _) = storage.reserve_gas_coins(10, 900).await.unwrap();
+        let (res_id2, _) = storage.reserve_gas_coins(20, 60_000).await.unwrap();
+        let (res_id3, _) = storage.reserve_gas_coins(30, 60_000).await.unwrap();
+        assert!(storage
+            .extend_reservation_expiration(res_id1, 60_000)
+            .await
+            .unwrap());
+        // The expiration is not brought forward.
+        assert!(storage
+            .extend_reservation_expiration(res_id2, 0)
+            .await
+            .unwrap());
+        // A released reservation is not renewed.
+        storage
+            .set_reservation_expiration(res_id3, 0)
+            .await
+            .unwrap();
+        assert!(!storage
+            .extend_reservation_expiration(res_id3, 60_000)
+            .await
+            .unwrap());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let expired = storage.expire_reservations().await.unwrap();
+        assert_eq!(
+            expired
+                .iter()
+                .map(|(reservation_id, _)| *reservation_id)
+                .collect::<Vec<_>>(),
+            vec![res_id3]
+        );
+        assert_coin_count(&storage, 70, 30).await;
+
+        storage.ready_for_execution(res_id1).await.unwrap();
+        assert!(!storage
+            .extend_reservation_expiration(res_id1, 60_000)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_reservation() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id, reserved_gas_coins, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 1000, "key")
+            .await
+            .unwrap();
+        assert!(is_new);
+        assert_eq!(
+            storage
+                .reserve_gas_coins_idempotent(10, 1000, "key")
+                .await
+                .unwrap(),
+            (res_id, reserved_gas_coins, false)
+        );
+        assert_coin_count(&storage, 90, 10).await;
+
+        let (other_res_id, _, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 1000, "other-key")
+            .await
+            .unwrap();
+        assert!(is_new);
+        assert_ne!(other_res_id, res_id);
+        assert_coin_count(&storage, 80, 20).await;
+
+        // The key can't be reused for another budget
+        assert_eq!(
+            storage
+                .reserve_gas_coins_idempotent(20, 1000, "key")
+                .await
+                .unwrap_err()
+                .downcast_ref::<GasStationError>(),
+            Some(&GasStationError::IdempotencyKeyReused)
+        );
+
+        // Once the reservation expired, the key reserves new coins
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        storage.expire_coins().await.unwrap();
+        let (_, _, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 1000, "key")
+            .await
+            .unwrap();
+        assert!(is_new);
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_reservation_after_execution() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        let (res_id, _, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 60_000, "key")
+            .await
+            .unwrap();
+        assert!(is_new);
+        storage.ready_for_execution(res_id).await.unwrap();
+        let (new_res_id, _, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 60_000, "key")
+            .await
+            .unwrap();
+        assert!(is_new);
+        assert_ne!(new_res_id, res_id);
+
+        // Nor is a released reservation returned
+        storage
+            .set_reservation_expiration(new_res_id, 0)
+            .await
+            .unwrap();
+        let (_, _, is_new) = storage
+            .reserve_gas_coins_idempotent(10, 60_000, "key")
+            .await
+            .unwrap();
+        assert!(is_new);
+    }
+
     #[tokio::test]
     async fn test_multiple_sponsors() {
         let sponsors = (0..10)
@@ -352,10 +861,70 @@ mod tests {
     async fn test_acquire_init_lock() {
         let sponsor = IotaAddress::random_for_testing_only();
         let storage = setup(sponsor, vec![1; 100]).await;
-        assert!(storage.acquire_init_lock(5).await.unwrap());
-        assert!(!storage.acquire_init_lock(1).await.unwrap());
+        assert!(storage.acquire_init_lock("a", 5).await.unwrap());
+        assert!(!storage.acquire_init_lock("b", 1).await.unwrap());
+        // Only released by its holder
+        storage.release_init_lock("b").await.unwrap();
+        assert!(!storage.acquire_init_lock("b", 1).await.unwrap());
+        storage.release_init_lock("a").await.unwrap();
+        assert!(storage.acquire_init_lock("b", 5).await.unwrap());
         tokio::time::sleep(Duration::from_secs(6)).await;
-        assert!(storage.acquire_init_lock(5).await.unwrap());
+        assert!(storage.acquire_init_lock("a", 5).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_renew_init_lock() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        assert!(storage.acquire_init_lock("a", 2).await.unwrap());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(storage.renew_init_lock("a", 5).await.unwrap());
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(!storage.acquire_init_lock("b", 5).await.unwrap());
+        // Not renewed by another caller
+        assert!(!storage.renew_init_lock("b", 5).await.unwrap());
+
+        // A lock taken over after it expired can't be renewed by its previous holder
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        assert!(storage.acquire_init_lock("b", 5).await.unwrap());
+        assert!(!storage.renew_init_lock("a", 5).await.unwrap());
+        assert!(!storage.acquire_init_lock("a", 5).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_init_progress() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        assert_eq!(storage.get_init_progress().await.unwrap(), None);
+        let progress = InitProgress {
+            target_init_balance: 1000,
+            pending_coins: vec![ObjectID::random(), ObjectID::random()],
+            split_coin_count: 3,
+            added_coin_count: 400,
+            started_at_ms: 1,
+        };
+        storage.set_init_progress(Some(&progress)).await.unwrap();
+        assert_eq!(storage.get_init_progress().await.unwrap(), Some(progress));
+        storage.set_init_progress(None).await.unwrap();
+        assert_eq!(storage.get_init_progress().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_leader_lease() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let storage = setup(sponsor, vec![1; 100]).await;
+        assert!(storage.acquire_leader_lease("a", 1000).await.unwrap());
+        assert!(!storage.acquire_leader_lease("b", 1000).await.unwrap());
+        // Renewed by its holder
+        assert!(storage.acquire_leader_lease("a", 1000).await.unwrap());
+        // Only released by its holder
+        storage.release_leader_lease("b").await.unwrap();
+        assert!(!storage.acquire_leader_lease("b", 1000).await.unwrap());
+        storage.release_leader_lease("a").await.unwrap();
+        assert!(storage.acquire_leader_lease("b", 1000).await.unwrap());
+        // Taken over once it expires
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(storage.acquire_leader_lease("a", 1000).await.unwrap());
     }
 
     #[tokio::test]
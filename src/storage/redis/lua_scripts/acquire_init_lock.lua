@@ -8,20 +8,25 @@
 -- The duration should be long enough such that the initialization can be completed.
 -- If the lock is already acquired, the function returns 0.
 -- If the lock is acquired, the function returns 1 and sets the lock's expiration time.
+-- The lock holds its expiration time and the ID of its holder, separated by ",", so that only the
+-- holder can renew or release it.
 -- The first argument is the sponsor's address.
 -- The second argument is the current timestamp.
 -- The third argument is the duration for which the lock should be held. This should be in the same
 -- units as the current timestamp.
+-- The fourth argument is the ID of the caller acquiring the lock.
 
 local sponsor_address = ARGV[1]
 local current_time = tonumber(ARGV[2])
 local lock_duration = tonumber(ARGV[3])
+local holder_id = ARGV[4]
 
 local t_init_lock = sponsor_address .. ':init_lock'
-local locked_timestamp = redis.call('GET', t_init_lock)
+local lock = redis.call('GET', t_init_lock)
+local locked_timestamp = lock and tonumber(string.match(lock, '^[^,]*'))
 
-if locked_timestamp == false or tonumber(locked_timestamp) < current_time then
-    redis.call('SET', t_init_lock, current_time + lock_duration)
+if not locked_timestamp or locked_timestamp < current_time then
+    redis.call('SET', t_init_lock, (current_time + lock_duration) .. ',' .. holder_id)
     return 1
 else
     return 0
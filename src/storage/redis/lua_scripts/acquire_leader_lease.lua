@@ -0,0 +1,28 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- Acquires or renews the leader lease of a sponsor's pool.
+-- Replicas sharing the pool elect the one initializing the pool and sweeping the expired
+-- reservations through this lease.
+-- If the lease is free or already held by the caller, it is set to expire after the lease
+-- duration and the function returns 1. If another replica holds the lease, it returns 0.
+-- The first argument is the sponsor's address.
+-- The second argument is the ID of the replica acquiring the lease.
+-- The third argument is the lease duration in milliseconds.
+
+local sponsor_address = ARGV[1]
+local holder_id = ARGV[2]
+local lease_duration_ms = tonumber(ARGV[3])
+
+local t_leader_lease = sponsor_address .. ':leader_lease'
+local holder = redis.call('GET', t_leader_lease)
+
+if holder == false then
+    redis.call('SET', t_leader_lease, holder_id, 'PX', lease_duration_ms)
+    return 1
+elseif holder == holder_id then
+    redis.call('PEXPIRE', t_leader_lease, lease_duration_ms)
+    return 1
+else
+    return 0
+end
@@ -6,31 +6,28 @@
 -- The second argument is a JSON array of new coins.
 -- Each coin is just a string, using "," to separate these fields:
 --   balance, object id, object version, object digest.
--- In this script we don't care about the format, just push each to the queue.
--- We also set the initialized flag to 1 if we added any coins.
+-- Each coin is added to a sorted set, scored by its balance. A coin that is already available isn't counted again.
 -- Returns a table with the new total balance and new coin count.
 
 local sponsor_address = ARGV[1]
 local new_coins = ARGV[2]
 
-local t_available_gas_coins = sponsor_address .. ':available_gas_coins'
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
 
 local decoded_new_coins = cjson.decode(new_coins)
 local count = #decoded_new_coins
 
 local total_balance = 0
+local added_count = 0
 for i = 1, count, 1 do
     local coin = decoded_new_coins[i]
     local idx1, _ = string.find(coin, ',', 1)
-    local balance = string.sub(coin, 1, idx1 - 1)
-    total_balance = total_balance + tonumber(balance)
+    local balance = tonumber(string.sub(coin, 1, idx1 - 1))
 
-    redis.call('RPUSH', t_available_gas_coins, coin)
-end
-
-if count > 0 then
-	local initialized_key = sponsor_address .. ':initialized'
-    redis.call('SET', initialized_key, 1)
+    if redis.call('ZADD', t_available_gas_coins, balance, coin) == 1 then
+        total_balance = total_balance + balance
+        added_count = added_count + 1
+    end
 end
 
 local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
@@ -41,7 +38,7 @@ redis.call('SET', t_available_coin_total_balance, new_total_balance)
 
 local t_available_coin_count = sponsor_address .. ':available_coin_count'
 local cur_coin_count = redis.call('GET', t_available_coin_count)
-local new_coin_count = cur_coin_count + count
+local new_coin_count = cur_coin_count + added_count
 redis.call('SET', t_available_coin_count, new_coin_count)
 
 return {new_total_balance, new_coin_count}
@@ -0,0 +1,17 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to drain the gas pool of a sponsor address, e.g. before decommissioning it.
+-- It marks the pool as draining, so that no new gas coins can be reserved, and lists all the available gas coins.
+-- The coins stay in the pool until they are removed by remove_available_coins.lua, once transferred.
+-- Reserved and quarantined coins are left untouched.
+-- The first argument is the sponsor's address.
+-- Returns the available coins, in the same format as they are stored (balance, object id, object version, object digest).
+
+local sponsor_address = ARGV[1]
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+
+redis.call('SET', sponsor_address .. ':draining', 1)
+
+return redis.call('ZRANGE', t_available_gas_coins, 0, -1)
@@ -2,7 +2,9 @@
 -- SPDX-License-Identifier: Apache-2.0
 
 -- This script is used to expire gas coins that have been reserved but not used after the expiration time.
--- It takes out all gas coins from the expiration_queue that have expired and returns them to the caller.
+-- It takes out all gas coins from the expiration_queue that have expired and returns them to the caller,
+-- as a flat list of reservation ids each followed by the comma separated ids of its coins.
+-- The expired reservations are counted per client, when the client of the reservation was recorded.
 -- The first argument is the sponsor's address.
 -- The second argument is the current timestamp.
 
@@ -10,18 +12,32 @@ local sponsor_address = ARGV[1]
 local current_time = tonumber(ARGV[2])
 
 local t_expiration_queue = sponsor_address .. ':expiration_queue'
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_reserved_coin_count = sponsor_address .. ':reserved_coin_count'
+local t_expired_by_client = sponsor_address .. ':expired_reservations_by_client'
 
 local elements = redis.call('ZRANGEBYSCORE', t_expiration_queue, 0, current_time)
 
 local expired_reservations = {}
 if #elements > 0 then
     for _, reservation_id in ipairs(elements) do
+        redis.call('ZREM', t_reservation_queue, reservation_id)
         local key = sponsor_address .. ':' .. reservation_id
         local object_ids = redis.call('GET', key)
         if object_ids then
+            local _, count = string.gsub(object_ids, ',', '')
+            redis.call('DECRBY', t_reserved_coin_count, count + 1)
             redis.call('DEL', key)
+            table.insert(expired_reservations, reservation_id)
             table.insert(expired_reservations, object_ids)
         end
+        redis.call('DEL', sponsor_address .. ':reservation_gas:' .. reservation_id)
+        local client_key = sponsor_address .. ':reservation_client:' .. reservation_id
+        local client = redis.call('GET', client_key)
+        if client then
+            redis.call('ZINCRBY', t_expired_by_client, 1, client)
+            redis.call('DEL', client_key)
+        end
     end
     redis.call('ZREMRANGEBYSCORE', t_expiration_queue, 0, current_time)
 end
@@ -0,0 +1,52 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to read the whole gas pool of a sponsor address at once, to copy it to another storage.
+-- The first argument is the sponsor's address.
+-- The second argument is 1 to make the pool read-only afterwards, i.e. to mark it as draining so that no gas coins
+-- can be reserved from it anymore, 0 otherwise.
+-- Returns a table with:
+--   the available coins, in the same format as they are stored (balance, object id, object version, object digest),
+--   a flat list alternating the reservation id, its expiration time, its reservation time and its comma separated
+--   object ids,
+--   a flat list alternating the quarantined coins (object id, balance) and their release epoch,
+--   the last reservation id,
+--   1 if the pool is initialized, 0 otherwise,
+--   1 if the pool was being drained before this call, 0 otherwise.
+
+local sponsor_address = ARGV[1]
+local read_only = ARGV[2] == '1'
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_expiration_queue = sponsor_address .. ':expiration_queue'
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_quarantined_gas_coins = sponsor_address .. ':quarantined_gas_coins'
+local t_draining = sponsor_address .. ':draining'
+
+local available_coins = redis.call('ZRANGE', t_available_gas_coins, 0, -1)
+
+local reservations = {}
+local elements = redis.call('ZRANGE', t_expiration_queue, 0, -1, 'WITHSCORES')
+for i = 1, #elements, 2 do
+    local reservation_id = elements[i]
+    local object_ids = redis.call('GET', sponsor_address .. ':' .. reservation_id)
+    -- Reservations that are being executed are no longer stored.
+    if object_ids then
+        local reservation_time = redis.call('ZSCORE', t_reservation_queue, reservation_id) or elements[i + 1]
+        table.insert(reservations, reservation_id)
+        table.insert(reservations, elements[i + 1])
+        table.insert(reservations, reservation_time)
+        table.insert(reservations, object_ids)
+    end
+end
+
+local quarantined_coins = redis.call('ZRANGE', t_quarantined_gas_coins, 0, -1, 'WITHSCORES')
+local last_reservation_id = tonumber(redis.call('GET', sponsor_address .. ':next_reservation_id') or 0)
+local initialized = redis.call('EXISTS', sponsor_address .. ':initialized')
+local draining = redis.call('EXISTS', t_draining)
+
+if read_only then
+    redis.call('SET', t_draining, 1)
+end
+
+return {available_coins, reservations, quarantined_coins, last_reservation_id, initialized, draining}
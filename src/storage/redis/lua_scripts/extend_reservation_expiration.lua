@@ -0,0 +1,31 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to push back the expiration time of a reservation that is still pending.
+-- The expiration time is never brought forward, and a reservation that already expired is not
+-- renewed, so that a reservation released on purpose stays released.
+-- Returns 1 if the reservation is still pending, 0 otherwise.
+-- The first argument is the sponsor's address.
+-- The second argument is the reservation id.
+-- The third argument is the current timestamp.
+-- The fourth argument is the new expiration time.
+
+local sponsor_address = ARGV[1]
+local reservation_id = ARGV[2]
+local current_time = tonumber(ARGV[3])
+local expiration_time = tonumber(ARGV[4])
+
+local key = sponsor_address .. ':' .. reservation_id
+local t_expiration_queue = sponsor_address .. ':expiration_queue'
+
+if redis.call('EXISTS', key) == 0 then
+    return 0
+end
+local current_expiration = tonumber(redis.call('ZSCORE', t_expiration_queue, reservation_id))
+if current_expiration == nil or current_expiration <= current_time then
+    return 0
+end
+if current_expiration < expiration_time then
+    redis.call('ZADD', t_expiration_queue, expiration_time, reservation_id)
+end
+return 1
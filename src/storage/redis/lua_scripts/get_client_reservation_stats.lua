@@ -0,0 +1,26 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to list the clients with the most reservations expired unused.
+-- The first argument is the sponsor's address.
+-- The second argument is the maximum number of clients to return.
+-- Returns a flat list of clients each followed by its number of reservations expired unused and its number of
+-- reservations.
+
+local sponsor_address = ARGV[1]
+local limit = tonumber(ARGV[2])
+
+local t_expired_by_client = sponsor_address .. ':expired_reservations_by_client'
+local t_reservations_by_client = sponsor_address .. ':reservations_by_client'
+
+local result = {}
+if limit > 0 then
+    local clients = redis.call('ZREVRANGE', t_expired_by_client, 0, limit - 1, 'WITHSCORES')
+    for i = 1, #clients, 2 do
+        table.insert(result, clients[i])
+        table.insert(result, clients[i + 1])
+        table.insert(result, redis.call('ZSCORE', t_reservations_by_client, clients[i]) or '0')
+    end
+end
+
+return result
@@ -0,0 +1,9 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to check if the gas pool of a sponsor address is being drained.
+-- The first argument is the sponsor's address.
+
+local sponsor_address = ARGV[1]
+
+return redis.call('EXISTS', sponsor_address .. ':draining')
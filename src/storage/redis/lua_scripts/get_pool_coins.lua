@@ -0,0 +1,30 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to list the available and reserved gas coins of a sponsor address, to debug the pool.
+-- The first argument is the sponsor's address.
+-- Returns a table with two lists:
+--   the available coins, in the same format as they are stored (balance, object id, object version, object digest),
+--   and a flat list alternating the reservation id, its expiration time and its comma separated object ids.
+
+local sponsor_address = ARGV[1]
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_expiration_queue = sponsor_address .. ':expiration_queue'
+
+local available_coins = redis.call('ZRANGE', t_available_gas_coins, 0, -1)
+
+local reservations = {}
+local elements = redis.call('ZRANGE', t_expiration_queue, 0, -1, 'WITHSCORES')
+for i = 1, #elements, 2 do
+    local reservation_id = elements[i]
+    local object_ids = redis.call('GET', sponsor_address .. ':' .. reservation_id)
+    -- Reservations that are being executed are no longer stored.
+    if object_ids then
+        table.insert(reservations, reservation_id)
+        table.insert(reservations, elements[i + 1])
+        table.insert(reservations, object_ids)
+    end
+end
+
+return {available_coins, reservations}
@@ -0,0 +1,12 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to list the quarantined gas coins of a sponsor address.
+-- The first argument is the sponsor's address.
+-- Returns a flat list alternating each coin (object id, balance) and its release epoch.
+
+local sponsor_address = ARGV[1]
+
+local t_quarantined_gas_coins = sponsor_address .. ':quarantined_gas_coins'
+
+return redis.call('ZRANGE', t_quarantined_gas_coins, 0, -1, 'WITHSCORES')
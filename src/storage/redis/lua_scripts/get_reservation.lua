@@ -0,0 +1,13 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to get the gas budget and the coins of a reservation, to check the gas data of its transaction.
+-- Returns a list starting with the gas budget, followed by the reserved coins. The list is empty if the reservation
+-- no longer exists, or if its budget and coins were not recorded, e.g. when it was copied from another storage.
+-- The first argument is the sponsor's address.
+-- The second argument is the reservation id.
+
+local sponsor_address = ARGV[1]
+local reservation_id = ARGV[2]
+
+return redis.call('LRANGE', sponsor_address .. ':reservation_gas:' .. reservation_id, 0, -1)
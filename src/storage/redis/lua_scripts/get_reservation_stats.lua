@@ -0,0 +1,26 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to sample the reservations of a sponsor address for metrics.
+-- The first argument is the sponsor's address.
+-- Returns the number of reserved gas coins, and the time of the oldest reservation that is neither
+-- executed nor expired yet, or -1 if there is none.
+
+local sponsor_address = ARGV[1]
+
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_reserved_coin_count = sponsor_address .. ':reserved_coin_count'
+
+-- Reservations made before the counter existed are not counted, so it can temporarily go below zero.
+local reserved_coin_count = tonumber(redis.call('GET', t_reserved_coin_count) or 0)
+if reserved_coin_count < 0 then
+    reserved_coin_count = 0
+end
+
+local oldest_reservation_time = -1
+local oldest = redis.call('ZRANGE', t_reservation_queue, 0, 0, 'WITHSCORES')
+if #oldest > 0 then
+    oldest_reservation_time = tonumber(oldest[2])
+end
+
+return {reserved_coin_count, oldest_reservation_time}
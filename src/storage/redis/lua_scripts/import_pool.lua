@@ -0,0 +1,58 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to write the gas pool of a sponsor address copied from another storage.
+-- The first argument is the sponsor's address.
+-- The second argument is a JSON object with:
+--   available_coins: the available coins (balance, object id, object version, object digest),
+--   reservations: the reservations, each with its id, expiration time, reservation time and comma separated
+--   object ids,
+--   quarantined_coins: the quarantined coins (object id, balance), each with its release epoch,
+--   last_reservation_id, initialized and draining.
+-- Fails if the sponsor address already has a gas pool, so that two pools are never merged.
+
+local sponsor_address = ARGV[1]
+local pool = cjson.decode(ARGV[2])
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_expiration_queue = sponsor_address .. ':expiration_queue'
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_quarantined_gas_coins = sponsor_address .. ':quarantined_gas_coins'
+
+if redis.call('EXISTS', sponsor_address .. ':initialized', t_available_gas_coins, t_expiration_queue,
+        t_quarantined_gas_coins) > 0 then
+    error('The storage already has a gas pool for ' .. sponsor_address)
+end
+
+local total_balance = 0
+for _, coin in ipairs(pool.available_coins) do
+    local idx, _ = string.find(coin, ',', 1)
+    local balance = tonumber(string.sub(coin, 1, idx - 1))
+    redis.call('ZADD', t_available_gas_coins, balance, coin)
+    total_balance = total_balance + balance
+end
+redis.call('SET', sponsor_address .. ':available_coin_total_balance', total_balance)
+redis.call('SET', sponsor_address .. ':available_coin_count', #pool.available_coins)
+
+local reserved_coin_count = 0
+for _, reservation in ipairs(pool.reservations) do
+    local reservation_id = reservation.reservation_id
+    redis.call('SET', sponsor_address .. ':' .. reservation_id, reservation.object_ids)
+    redis.call('ZADD', t_expiration_queue, reservation.expiration_time, reservation_id)
+    redis.call('ZADD', t_reservation_queue, reservation.reservation_time, reservation_id)
+    local _, count = string.gsub(reservation.object_ids, ',', '')
+    reserved_coin_count = reserved_coin_count + count + 1
+end
+redis.call('SET', sponsor_address .. ':reserved_coin_count', reserved_coin_count)
+
+for _, coin in ipairs(pool.quarantined_coins) do
+    redis.call('ZADD', t_quarantined_gas_coins, coin.release_epoch, coin.coin)
+end
+
+redis.call('SET', sponsor_address .. ':next_reservation_id', pool.last_reservation_id)
+if pool.initialized then
+    redis.call('SET', sponsor_address .. ':initialized', 1)
+end
+if pool.draining then
+    redis.call('SET', sponsor_address .. ':draining', 1)
+end
@@ -3,24 +3,39 @@
 
 -- This script is used to initialize a few coin related statistics for a sponsor address at startup.
 -- Including the total balance and the total coin count.
+-- The coins still stored in the available_gas_coins list by earlier versions are moved to the
+-- available_gas_coins_by_balance sorted set first.
 -- The first argument is the sponsor's address.
 -- Returns a table with the new coin count and new total balance.
 
 local sponsor_address = ARGV[1]
 
-local t_available_gas_coins = sponsor_address .. ':available_gas_coins'
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+
+local t_legacy_available_gas_coins = sponsor_address .. ':available_gas_coins'
+local legacy_coins = redis.call('LRANGE', t_legacy_available_gas_coins, 0, -1)
+for _, coin in ipairs(legacy_coins) do
+    local idx, _ = string.find(coin, ',', 1)
+    redis.call('ZADD', t_available_gas_coins, tonumber(string.sub(coin, 1, idx - 1)), coin)
+end
+if #legacy_coins > 0 then
+    redis.call('DEL', t_legacy_available_gas_coins)
+    -- Duplicated coins of the list are stored once, so the statistics are computed again.
+    redis.call('DEL', sponsor_address .. ':available_coin_count')
+    redis.call('DEL', sponsor_address .. ':available_coin_total_balance')
+end
 
 local t_available_coin_count = sponsor_address .. ':available_coin_count'
 local coin_count = redis.call('GET', t_available_coin_count)
 if not coin_count then
-    coin_count = redis.call('LLEN', t_available_gas_coins)
+    coin_count = redis.call('ZCARD', t_available_gas_coins)
     redis.call('SET', t_available_coin_count, coin_count)
 end
 
 local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
 local total_balance = redis.call('GET', t_available_coin_total_balance)
 if not total_balance then
-    local elements = redis.call('LRANGE', t_available_gas_coins, 0, -1)
+    local elements = redis.call('ZRANGE', t_available_gas_coins, 0, -1)
     total_balance = 0
     for _, coin in ipairs(elements) do
         -- Each coin is just a string, using "," to separate fields. The first is balance.
@@ -0,0 +1,22 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to put aside gas coins that cannot be used until a later epoch,
+-- e.g. because they are locked by an equivocated transaction.
+-- The first argument is the sponsor's address.
+-- The second argument is the epoch from which the coins can be used again.
+-- The third argument is a JSON array of coins. Each coin is a string, using "," to separate
+-- these fields: object id, balance.
+-- The coins are stored in a sorted set, scored by their release epoch.
+
+local sponsor_address = ARGV[1]
+local release_epoch = tonumber(ARGV[2])
+local coins = cjson.decode(ARGV[3])
+
+local t_quarantined_gas_coins = sponsor_address .. ':quarantined_gas_coins'
+
+for _, coin in ipairs(coins) do
+    redis.call('ZADD', t_quarantined_gas_coins, release_epoch, coin)
+end
+
+return redis.call('ZCARD', t_quarantined_gas_coins)
@@ -7,14 +7,23 @@
 -- right before the transaction is executed.
 -- The first argument is the sponsor's address.
 -- The second argument is the reservation id.
+-- Returns 1, or 0 if the reservation no longer exists.
 
 local sponsor_address = ARGV[1]
 local reservation_id = ARGV[2]
 
 local key = sponsor_address .. ':' .. reservation_id
-local exists = redis.call('EXISTS', key)
-if exists == 1 then
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_reserved_coin_count = sponsor_address .. ':reserved_coin_count'
+redis.call('ZREM', t_reservation_queue, reservation_id)
+redis.call('DEL', sponsor_address .. ':reservation_client:' .. reservation_id)
+redis.call('DEL', sponsor_address .. ':reservation_gas:' .. reservation_id)
+local object_ids = redis.call('GET', key)
+if object_ids then
+    local _, count = string.gsub(object_ids, ',', '')
+    redis.call('DECRBY', t_reserved_coin_count, count + 1)
     redis.call('DEL', key)
+    return 1
 else
-    error('Reservation no longer exist: ' .. reservation_id)
+    return 0
 end
\ No newline at end of file
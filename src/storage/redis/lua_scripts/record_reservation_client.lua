@@ -0,0 +1,21 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to record the client that made a reservation, so that the reservations expiring unused
+-- can be counted per client.
+-- The client is kept until the reservation is executed or expires, and the reservations of the client are counted
+-- once, even if the same reservation is returned again for an idempotency key.
+-- The first argument is the sponsor's address.
+-- The second argument is the reservation id.
+-- The third argument is the client.
+-- The fourth argument is how long the client is kept at most, in milliseconds.
+
+local sponsor_address = ARGV[1]
+local reservation_id = ARGV[2]
+local client = ARGV[3]
+local retention_ms = tonumber(ARGV[4])
+
+local key = sponsor_address .. ':reservation_client:' .. reservation_id
+if redis.call('SET', key, client, 'NX', 'PX', retention_ms) then
+    redis.call('ZINCRBY', sponsor_address .. ':reservations_by_client', 1, client)
+end
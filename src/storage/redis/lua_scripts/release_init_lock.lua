@@ -1,11 +1,16 @@
 -- Copyright (c) Mysten Labs, Inc.
 -- SPDX-License-Identifier: Apache-2.0
 
--- Release the lock for initializing a sponsor's account.
+-- Release the lock for initializing a sponsor's account, if it is held by the caller.
 -- This is done by setting the lock expiration time to 0.
 -- The first argument is the sponsor's address.
+-- The second argument is the ID of the caller releasing the lock.
 
 local sponsor_address = ARGV[1]
+local holder_id = ARGV[2]
 
 local t_init_lock = sponsor_address .. ':init_lock'
-redis.call('SET', t_init_lock, 0)
+local lock = redis.call('GET', t_init_lock)
+if lock and string.match(lock, ',(.*)$') == holder_id then
+    redis.call('SET', t_init_lock, 0)
+end
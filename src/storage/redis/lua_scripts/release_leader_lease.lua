@@ -0,0 +1,15 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- Releases the leader lease of a sponsor's pool if it is held by the caller, so that another
+-- replica can take over without waiting for the lease to expire.
+-- The first argument is the sponsor's address.
+-- The second argument is the ID of the replica releasing the lease.
+
+local sponsor_address = ARGV[1]
+local holder_id = ARGV[2]
+
+local t_leader_lease = sponsor_address .. ':leader_lease'
+if redis.call('GET', t_leader_lease) == holder_id then
+    redis.call('DEL', t_leader_lease)
+end
@@ -0,0 +1,21 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to take the quarantined gas coins whose release epoch has been reached
+-- out of the quarantine.
+-- The first argument is the sponsor's address.
+-- The second argument is the current epoch.
+-- Returns the list of released coins, each in the form of: object id, balance, and the number
+-- of coins still quarantined. The caller is responsible for putting them back into the pool.
+
+local sponsor_address = ARGV[1]
+local current_epoch = tonumber(ARGV[2])
+
+local t_quarantined_gas_coins = sponsor_address .. ':quarantined_gas_coins'
+
+local released = redis.call('ZRANGEBYSCORE', t_quarantined_gas_coins, '-inf', current_epoch)
+if #released > 0 then
+    redis.call('ZREMRANGEBYSCORE', t_quarantined_gas_coins, '-inf', current_epoch)
+end
+
+return {released, redis.call('ZCARD', t_quarantined_gas_coins)}
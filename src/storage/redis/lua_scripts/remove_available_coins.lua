@@ -0,0 +1,38 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to remove available gas coins from the pool once they are transferred, when draining it.
+-- The first argument is the sponsor's address.
+-- The remaining arguments are the object IDs of the coins to remove. Coins that are not available are ignored.
+-- Returns a table with the number of coins removed, the new total balance and the new coin count.
+
+local sponsor_address = ARGV[1]
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
+local t_available_coin_count = sponsor_address .. ':available_coin_count'
+
+local object_ids = {}
+for i = 2, #ARGV do
+    object_ids[ARGV[i]] = true
+end
+
+local removed_count = 0
+local removed_balance = 0
+for _, coin in ipairs(redis.call('ZRANGE', t_available_gas_coins, 0, -1)) do
+    local idx1, _ = string.find(coin, ',', 1)
+    local idx2, _ = string.find(coin, ',', idx1 + 1)
+    local object_id = string.sub(coin, idx1 + 1, idx2 - 1)
+    if object_ids[object_id] then
+        redis.call('ZREM', t_available_gas_coins, coin)
+        removed_count = removed_count + 1
+        removed_balance = removed_balance + tonumber(string.sub(coin, 1, idx1 - 1))
+    end
+end
+
+local new_total_balance = tonumber(redis.call('GET', t_available_coin_total_balance) or 0) - removed_balance
+local new_coin_count = tonumber(redis.call('GET', t_available_coin_count) or 0) - removed_count
+redis.call('SET', t_available_coin_total_balance, new_total_balance)
+redis.call('SET', t_available_coin_count, new_coin_count)
+
+return {removed_count, new_total_balance, new_coin_count}
@@ -0,0 +1,24 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- Extends the lock for initializing a sponsor's account, if it is still held by the caller.
+-- If another caller acquired the lock after it expired, the lock is left as is and the function
+-- returns 0. Otherwise it returns 1.
+-- The first argument is the sponsor's address.
+-- The second argument is the current timestamp.
+-- The third argument is the duration for which the lock should be held from now.
+-- The fourth argument is the ID of the caller renewing the lock.
+
+local sponsor_address = ARGV[1]
+local current_time = tonumber(ARGV[2])
+local lock_duration = tonumber(ARGV[3])
+local holder_id = ARGV[4]
+
+local t_init_lock = sponsor_address .. ':init_lock'
+local lock = redis.call('GET', t_init_lock)
+
+if lock == false or string.match(lock, ',(.*)$') ~= holder_id then
+    return 0
+end
+redis.call('SET', t_init_lock, (current_time + lock_duration) .. ',' .. holder_id)
+return 1
@@ -2,23 +2,67 @@
 -- SPDX-License-Identifier: Apache-2.0
 
 -- This script is used to reserve gas coins for a sponsor address.
--- It takes out gas coins from the available_gas_coins list and returns them to the caller.
+-- It takes out gas coins from the available_gas_coins_by_balance sorted set and returns them to the caller.
+-- The smallest coin covering the budget is taken. If no coin is large enough, the largest coins are taken
+-- until the smallest coin covering the rest of the budget can be added, so that the fewest coins are locked
+-- and the large coins are kept for the large budgets.
 -- It also creates a unique reservation id and stores the reserved coins in a separate reservation map.
 -- The reservation id is used to track the reserved coins and to release them back to the available pool if not used.
--- The reservation id is added to the expiration_queue to track the expiration time of the reserved coins.
+-- The target budget and the reserved coins are recorded along with it, to check the gas data of its transaction.
+-- The reservation id is added to the expiration_queue to track the expiration time of the reserved coins,
+-- and to the reservation_queue to track how long the coins have been reserved.
 -- The first argument is the sponsor's address.
 -- The second argument is the target budget.
 -- The third argument is the expiration time.
--- Returns a table with the reservation id, reserved coins, new total balance, and new coin count.
+-- The fourth argument is the current time.
+-- The fifth argument is the idempotency key of the request, or an empty string. A reservation made with the same key
+-- is returned as is while it is neither executed, released nor expired, instead of reserving new coins.
+-- Returns a table with the reservation id, reserved coins, new total balance, new coin count, and a status:
+-- 1 if the reservation already existed for the idempotency key, 2 if the pool is being drained, 3 if the
+-- idempotency key was used for another budget, 0 otherwise.
+-- No coins are returned if the pool is being drained or if it can't cover the budget.
 
 local sponsor_address = ARGV[1]
 local target_budget = tonumber(ARGV[2])
 local expiration_time = tonumber(ARGV[3])
+local current_time = tonumber(ARGV[4])
+local idempotency_key = ARGV[5]
 
 local MAX_GAS_PER_QUERY = 256
 
-local t_available_gas_coins = sponsor_address .. ':available_gas_coins'
+local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
+local t_available_coin_count = sponsor_address .. ':available_coin_count'
+
 local t_expiration_queue = sponsor_address .. ':expiration_queue'
+
+local t_idempotency_key = nil
+if idempotency_key and idempotency_key ~= '' then
+    t_idempotency_key = sponsor_address .. ':reservation_idempotency_key:' .. idempotency_key
+    local existing = redis.call('GET', t_idempotency_key)
+    if existing then
+        local reservation = cjson.decode(existing)
+        local expires_at = redis.call('ZSCORE', t_expiration_queue, reservation.reservation_id)
+        -- The reservation is gone once executed, and is being expired once released or past its expiration.
+        if redis.call('EXISTS', sponsor_address .. ':' .. reservation.reservation_id) == 1
+            and expires_at and tonumber(expires_at) > current_time then
+            if reservation.gas_budget ~= ARGV[2] then
+                return {0, {}, 0, 0, 3}
+            end
+            local cur_total_balance = redis.call('GET', t_available_coin_total_balance) or 0
+            local cur_coin_count = redis.call('GET', t_available_coin_count) or 0
+            return {reservation.reservation_id, reservation.coins, cur_total_balance, cur_coin_count, 1}
+        end
+        redis.call('DEL', t_idempotency_key)
+    end
+end
+
+if redis.call('EXISTS', sponsor_address .. ':draining') == 1 then
+    return {0, {}, 0, 0, 2}
+end
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_reservation_queue = sponsor_address .. ':reservation_queue'
+local t_reserved_coin_count = sponsor_address .. ':reserved_coin_count'
 local t_next_reservation_id = sponsor_address .. ':next_reservation_id'
 
 local total_balance = 0
@@ -26,8 +70,13 @@ local coins = {}
 local object_ids = {}
 
 while total_balance < target_budget and #coins < MAX_GAS_PER_QUERY do
-    local coin = redis.call('LPOP', t_available_gas_coins)
+    local remaining_budget = target_budget - total_balance
+    local coin = redis.call('ZRANGEBYSCORE', t_available_gas_coins, remaining_budget, '+inf', 'LIMIT', 0, 1)[1]
+    if not coin then
+        coin = redis.call('ZRANGE', t_available_gas_coins, -1, -1)[1]
+    end
     if not coin then break end
+    redis.call('ZREM', t_available_gas_coins, coin)
 
     local idx1, _ = string.find(coin, ',', 1)
     local balance = string.sub(coin, 1, idx1 - 1)
@@ -41,20 +90,19 @@ while total_balance < target_budget and #coins < MAX_GAS_PER_QUERY do
 end
 
 if total_balance < target_budget then
-    -- If the threshold is not reached, push the coins back to the front of the queue in the original order.
-    for i = #coins, 1, -1 do
-        redis.call('LPUSH', t_available_gas_coins, coins[i])
+    -- If the threshold is not reached, put the coins back.
+    for i = 1, #coins, 1 do
+        local idx, _ = string.find(coins[i], ',', 1)
+        redis.call('ZADD', t_available_gas_coins, tonumber(string.sub(coins[i], 1, idx - 1)), coins[i])
     end
-    return {0, {}, 0, 0}
+    return {0, {}, 0, 0, 0}
 end
 
-local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
 -- TODO: For some reason DECRBY is not working, so we have to do this in two steps.
 local cur_coin_total_balance = redis.call('GET', t_available_coin_total_balance)
 local new_total_balance = cur_coin_total_balance - total_balance
 redis.call('SET', t_available_coin_total_balance, new_total_balance)
 
-local t_available_coin_count = sponsor_address .. ':available_coin_count'
 local cur_coin_count = redis.call('GET', t_available_coin_count)
 local new_coin_count = cur_coin_count - #coins
 redis.call('SET', t_available_coin_count, new_coin_count)
@@ -64,6 +112,14 @@ local reservation_id = redis.call('GET', t_next_reservation_id)
 local concated_object_ids = table.concat(object_ids, ',')
 local key = sponsor_address .. ':' .. reservation_id
 redis.call('SET', key, concated_object_ids)
+redis.call('RPUSH', sponsor_address .. ':reservation_gas:' .. reservation_id, ARGV[2], unpack(coins))
 redis.call('ZADD', t_expiration_queue, expiration_time, reservation_id)
+redis.call('ZADD', t_reservation_queue, current_time, reservation_id)
+redis.call('INCRBY', t_reserved_coin_count, #coins)
+
+if t_idempotency_key then
+    local reservation = cjson.encode({reservation_id = reservation_id, gas_budget = ARGV[2], coins = coins})
+    redis.call('SET', t_idempotency_key, reservation, 'PX', math.max(expiration_time - current_time, 1))
+end
 
-return {reservation_id, coins, new_total_balance, new_coin_count}
+return {reservation_id, coins, new_total_balance, new_coin_count, 0}
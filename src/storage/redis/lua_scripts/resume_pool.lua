@@ -0,0 +1,9 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to allow reservations again on the gas pool of a sponsor address after it was drained.
+-- The first argument is the sponsor's address.
+
+local sponsor_address = ARGV[1]
+
+redis.call('DEL', sponsor_address .. ':draining')
@@ -0,0 +1,22 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to change the expiration time of a reservation, to keep its coins reserved
+-- longer or to release them sooner.
+-- The first argument is the sponsor's address.
+-- The second argument is the reservation id.
+-- The third argument is the new expiration time.
+-- Returns 1, or 0 if the reservation no longer exists.
+
+local sponsor_address = ARGV[1]
+local reservation_id = ARGV[2]
+local expiration_time = tonumber(ARGV[3])
+
+local key = sponsor_address .. ':' .. reservation_id
+local t_expiration_queue = sponsor_address .. ':expiration_queue'
+
+if redis.call('EXISTS', key) == 0 then
+    return 0
+end
+redis.call('ZADD', t_expiration_queue, expiration_time, reservation_id)
+return 1
@@ -0,0 +1,40 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- This script is used to take out the available gas coins with a large balance, so that they can be split again.
+-- The first argument is the sponsor's address.
+-- The second argument is the minimum balance of the coins to take out.
+-- The third argument is the maximum number of coins to take out, the largest first.
+-- Nothing is taken out if the pool is being drained.
+-- Returns a table with the coins taken out, in the same format as they are stored
+-- (balance, object id, object version, object digest), the new total balance and the new coin count.
+
+local sponsor_address = ARGV[1]
+local min_balance = tonumber(ARGV[2])
+local max_count = tonumber(ARGV[3])
+
+local t_available_gas_coins = sponsor_address .. ':available_gas_coins_by_balance'
+local t_available_coin_total_balance = sponsor_address .. ':available_coin_total_balance'
+local t_available_coin_count = sponsor_address .. ':available_coin_count'
+
+local cur_total_balance = tonumber(redis.call('GET', t_available_coin_total_balance) or 0)
+local cur_coin_count = tonumber(redis.call('GET', t_available_coin_count) or 0)
+
+if redis.call('GET', sponsor_address .. ':draining') then
+    return {{}, cur_total_balance, cur_coin_count}
+end
+
+local coins = redis.call('ZREVRANGEBYSCORE', t_available_gas_coins, '+inf', min_balance, 'LIMIT', 0, max_count)
+local total_balance = 0
+for _, coin in ipairs(coins) do
+    redis.call('ZREM', t_available_gas_coins, coin)
+    local idx1, _ = string.find(coin, ',', 1)
+    total_balance = total_balance + tonumber(string.sub(coin, 1, idx1 - 1))
+end
+
+local new_total_balance = cur_total_balance - total_balance
+local new_coin_count = cur_coin_count - #coins
+redis.call('SET', t_available_coin_total_balance, new_total_balance)
+redis.call('SET', t_available_coin_count, new_coin_count)
+
+return {coins, new_total_balance, new_coin_count}
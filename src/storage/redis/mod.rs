@@ -3,13 +3,17 @@
 
 mod script_manager;
 
+use crate::errors::GasStationError;
 use crate::metrics::StorageMetrics;
+use crate::redis_connection::{connect_redis, RedisConnection, RedisMode};
 use crate::storage::redis::script_manager::ScriptManager;
-use crate::storage::Storage;
-use crate::types::{GasCoin, ReservationID};
+use crate::storage::{storage_key_prefix, Storage};
+use crate::types::{
+    ClientReservationStats, GasCoin, InitProgress, PoolCoin, PoolCoinState, PoolSnapshot,
+    QuarantinedCoin, Reservation, ReservationID, ReservationSnapshot, ReservationStats,
+};
 use chrono::Utc;
 use iota_types::base_types::{IotaAddress, ObjectDigest, ObjectID, SequenceNumber};
-use redis::aio::ConnectionManager;
 use std::ops::Add;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -17,59 +21,87 @@ use std::time::Duration;
 use tracing::{debug, info};
 
 pub struct RedisStorage {
-    conn_manager: ConnectionManager,
+    conn_manager: RedisConnection,
     // String format of the sponsor address to avoid converting it to string multiple times.
     sponsor_str: String,
+    // Prefix of all the keys of the pool: the sponsor address, preceded by the namespace if any.
+    // It is also passed as the key of the scripts, so that a Redis Cluster routes them to the node
+    // holding the keys of the sponsor.
+    key_prefix: String,
     metrics: Arc<StorageMetrics>,
 }
 
 impl RedisStorage {
     pub async fn new(
         redis_url: &str,
+        mode: &RedisMode,
+        namespace: Option<&str>,
         sponsor_address: IotaAddress,
         metrics: Arc<StorageMetrics>,
     ) -> Self {
-        let client = redis::Client::open(redis_url).unwrap();
-        let conn_manager = ConnectionManager::new(client).await.unwrap();
+        let conn_manager = connect_redis(redis_url, mode, metrics.redis_connection_metrics("pool"))
+            .await
+            .unwrap();
         Self {
             conn_manager,
             sponsor_str: sponsor_address.to_string(),
+            key_prefix: mode.key_prefix(storage_key_prefix(namespace, sponsor_address)),
             metrics,
         }
     }
-}
 
-#[async_trait::async_trait]
-impl Storage for RedisStorage {
-    async fn reserve_gas_coins(
+    fn init_progress_key(&self) -> String {
+        format!("{}:init_progress", self.key_prefix)
+    }
+
+    fn executed_transaction_key(&self, idempotency_key: &str) -> String {
+        format!(
+            "{}:executed_transaction:{}",
+            self.key_prefix, idempotency_key
+        )
+    }
+
+    /// Reserves gas coins, or returns the reservation made with the idempotency key, along with
+    /// whether it's new.
+    async fn reserve_gas_coins_with_key(
         &self,
         target_budget: u64,
         reserved_duration_ms: u64,
-    ) -> anyhow::Result<(ReservationID, Vec<GasCoin>)> {
+        idempotency_key: Option<&str>,
+    ) -> anyhow::Result<(ReservationID, Vec<GasCoin>, bool)> {
         self.metrics.num_reserve_gas_coins_requests.inc();
 
-        let expiration_time = Utc::now()
+        let now = Utc::now();
+        let expiration_time = now
             .add(Duration::from_millis(reserved_duration_ms))
             .timestamp_millis() as u64;
         let mut conn = self.conn_manager.clone();
-        let (reservation_id, coins, new_total_balance, new_coin_count): (
+        let (reservation_id, coins, new_total_balance, new_coin_count, status): (
             ReservationID,
             Vec<String>,
             i64,
             i64,
+            u8,
         ) = ScriptManager::reserve_gas_coins_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .arg(target_budget)
             .arg(expiration_time)
+            .arg(now.timestamp_millis() as u64)
+            .arg(idempotency_key.unwrap_or_default())
             .invoke_async(&mut conn)
             .await?;
         // The script returns (0, []) if it is unable to find enough coins to reserve.
         // We choose to handle the error here instead of inside the script so that we could
         // provide a more readable error message.
+        if status == 2 {
+            return Err(GasStationError::PoolDraining.into());
+        }
+        if status == 3 {
+            return Err(GasStationError::IdempotencyKeyReused.into());
+        }
         if coins.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Unable to reserve gas coins for the given budget."
-            ));
+            return Err(GasStationError::InsufficientPoolBalance.into());
         }
         let gas_coins: Vec<_> = coins
             .into_iter()
@@ -96,18 +128,46 @@ impl Storage for RedisStorage {
             .with_label_values(&[&self.sponsor_str])
             .set(new_total_balance);
         self.metrics.num_successful_reserve_gas_coins_requests.inc();
+        Ok((reservation_id, gas_coins, status == 0))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for RedisStorage {
+    async fn reserve_gas_coins(
+        &self,
+        target_budget: u64,
+        reserved_duration_ms: u64,
+    ) -> anyhow::Result<(ReservationID, Vec<GasCoin>)> {
+        let (reservation_id, gas_coins, _) = self
+            .reserve_gas_coins_with_key(target_budget, reserved_duration_ms, None)
+            .await?;
         Ok((reservation_id, gas_coins))
     }
 
+    async fn reserve_gas_coins_idempotent(
+        &self,
+        target_budget: u64,
+        reserved_duration_ms: u64,
+        idempotency_key: &str,
+    ) -> anyhow::Result<(ReservationID, Vec<GasCoin>, bool)> {
+        self.reserve_gas_coins_with_key(target_budget, reserved_duration_ms, Some(idempotency_key))
+            .await
+    }
+
     async fn ready_for_execution(&self, reservation_id: ReservationID) -> anyhow::Result<()> {
         self.metrics.num_ready_for_execution_requests.inc();
 
         let mut conn = self.conn_manager.clone();
-        ScriptManager::ready_for_execution_script()
-            .arg(self.sponsor_str.clone())
+        let exists = ScriptManager::ready_for_execution_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .arg(reservation_id)
-            .invoke_async::<_, ()>(&mut conn)
+            .invoke_async::<_, bool>(&mut conn)
             .await?;
+        if !exists {
+            return Err(GasStationError::ReservationExpired(reservation_id).into());
+        }
 
         self.metrics
             .num_successful_ready_for_execution_requests
@@ -115,6 +175,29 @@ impl Storage for RedisStorage {
         Ok(())
     }
 
+    async fn get_reservation(
+        &self,
+        reservation_id: ReservationID,
+    ) -> anyhow::Result<Option<Reservation>> {
+        let mut conn = self.conn_manager.clone();
+        let reservation: Vec<String> = ScriptManager::get_reservation_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(reservation_id)
+            .invoke_async(&mut conn)
+            .await?;
+        let Some((gas_budget, gas_coins)) = reservation.split_first() else {
+            return Ok(None);
+        };
+        Ok(Some(Reservation {
+            gas_budget: gas_budget.parse()?,
+            gas_coins: gas_coins
+                .iter()
+                .map(|coin| parse_gas_coin(coin))
+                .collect::<anyhow::Result<_>>()?,
+        }))
+    }
+
     async fn add_new_coins(&self, new_coins: Vec<GasCoin>) -> anyhow::Result<()> {
         self.metrics.num_add_new_coins_requests.inc();
         let formatted_coins = new_coins
@@ -135,7 +218,8 @@ impl Storage for RedisStorage {
 
         let mut conn = self.conn_manager.clone();
         let (new_total_balance, new_coin_count): (i64, i64) = ScriptManager::add_new_coins_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .arg(serde_json::to_string(&formatted_coins)?)
             .invoke_async(&mut conn)
             .await?;
@@ -156,31 +240,390 @@ impl Storage for RedisStorage {
         Ok(())
     }
 
-    async fn expire_coins(&self) -> anyhow::Result<Vec<ObjectID>> {
+    async fn expire_reservations(&self) -> anyhow::Result<Vec<(ReservationID, Vec<ObjectID>)>> {
         self.metrics.num_expire_coins_requests.inc();
 
         let now = Utc::now().timestamp_millis() as u64;
         let mut conn = self.conn_manager.clone();
-        let expired_coin_strings: Vec<String> = ScriptManager::expire_coins_script()
-            .arg(self.sponsor_str.clone())
+        let expired_strings: Vec<String> = ScriptManager::expire_coins_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .arg(now)
             .invoke_async(&mut conn)
             .await?;
-        // The script returns a list of comma separated coin ids.
-        let expired_coin_ids = expired_coin_strings
-            .iter()
-            .flat_map(|s| s.split(',').map(|id| ObjectID::from_str(id).unwrap()))
+        // The script returns each reservation id followed by its comma separated coin ids.
+        let expired_reservations = expired_strings
+            .chunks(2)
+            .map(|chunk| {
+                let reservation_id = chunk[0].parse::<ReservationID>().unwrap();
+                let coin_ids = chunk[1]
+                    .split(',')
+                    .map(|id| ObjectID::from_str(id).unwrap())
+                    .collect();
+                (reservation_id, coin_ids)
+            })
             .collect();
 
         self.metrics.num_successful_expire_coins_requests.inc();
-        Ok(expired_coin_ids)
+        Ok(expired_reservations)
+    }
+
+    async fn quarantine_coins(
+        &self,
+        coins: Vec<GasCoin>,
+        release_epoch: u64,
+    ) -> anyhow::Result<()> {
+        // The format is: object_id,balance
+        let formatted_coins = coins
+            .iter()
+            .map(|c| format!("{},{}", c.object_ref.0, c.balance))
+            .collect::<Vec<String>>();
+        let mut conn = self.conn_manager.clone();
+        let quarantined_coin_count: i64 = ScriptManager::quarantine_coins_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(release_epoch)
+            .arg(serde_json::to_string(&formatted_coins)?)
+            .invoke_async(&mut conn)
+            .await?;
+        self.metrics
+            .gas_station_quarantined_gas_coin_count
+            .with_label_values(&[&self.sponsor_str])
+            .set(quarantined_coin_count);
+        Ok(())
+    }
+
+    async fn release_quarantined_coins(&self, current_epoch: u64) -> anyhow::Result<Vec<ObjectID>> {
+        let mut conn = self.conn_manager.clone();
+        let (released, quarantined_coin_count): (Vec<String>, i64) =
+            ScriptManager::release_quarantined_coins_script()
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
+                .arg(current_epoch)
+                .invoke_async(&mut conn)
+                .await?;
+        self.metrics
+            .gas_station_quarantined_gas_coin_count
+            .with_label_values(&[&self.sponsor_str])
+            .set(quarantined_coin_count);
+        released
+            .iter()
+            .map(|coin| parse_quarantined_coin(coin, 0).map(|coin| coin.object_id))
+            .collect()
+    }
+
+    async fn get_quarantined_coins(&self) -> anyhow::Result<Vec<QuarantinedCoin>> {
+        let mut conn = self.conn_manager.clone();
+        let coins: Vec<(String, u64)> = ScriptManager::get_quarantined_coins_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .invoke_async(&mut conn)
+            .await?;
+        self.metrics
+            .gas_station_quarantined_gas_coin_count
+            .with_label_values(&[&self.sponsor_str])
+            .set(coins.len() as i64);
+        coins
+            .iter()
+            .map(|(coin, release_epoch)| parse_quarantined_coin(coin, *release_epoch))
+            .collect()
+    }
+
+    async fn get_pool_coins(&self) -> anyhow::Result<Vec<PoolCoin>> {
+        let mut conn = self.conn_manager.clone();
+        let (available_coins, reservations): (Vec<String>, Vec<String>) =
+            ScriptManager::get_pool_coins_script()
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
+                .invoke_async(&mut conn)
+                .await?;
+        let mut coins = available_coins
+            .iter()
+            .map(|coin| parse_available_coin(coin))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for reservation in reservations.chunks(3) {
+            let [reservation_id, expiration_time, object_ids] = reservation else {
+                anyhow::bail!("Invalid reservation: {:?}", reservation);
+            };
+            let state = PoolCoinState::Reserved {
+                reservation_id: reservation_id.parse()?,
+                expiration_time: expiration_time.parse()?,
+            };
+            for object_id in object_ids.split(',') {
+                coins.push(PoolCoin {
+                    object_id: ObjectID::from_str(object_id)?,
+                    version: None,
+                    balance: None,
+                    state: state.clone(),
+                });
+            }
+        }
+        coins.extend(
+            self.get_quarantined_coins()
+                .await?
+                .into_iter()
+                .map(|coin| PoolCoin {
+                    object_id: coin.object_id,
+                    version: None,
+                    balance: Some(coin.balance),
+                    state: PoolCoinState::Quarantined {
+                        release_epoch: coin.release_epoch,
+                    },
+                }),
+        );
+        Ok(coins)
+    }
+
+    async fn drain_available_coins(&self) -> anyhow::Result<Vec<GasCoin>> {
+        let mut conn = self.conn_manager.clone();
+        let coins: Vec<String> = ScriptManager::drain_available_coins_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .invoke_async(&mut conn)
+            .await?;
+        coins.iter().map(|coin| parse_gas_coin(coin)).collect()
+    }
+
+    async fn remove_available_coins(&self, object_ids: &[ObjectID]) -> anyhow::Result<usize> {
+        let mut conn = self.conn_manager.clone();
+        let (removed_count, new_total_balance, new_coin_count): (usize, i64, i64) =
+            ScriptManager::remove_available_coins_script()
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
+                .arg(
+                    object_ids
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                )
+                .invoke_async(&mut conn)
+                .await?;
+        self.metrics
+            .gas_station_available_gas_coin_count
+            .with_label_values(&[&self.sponsor_str])
+            .set(new_coin_count);
+        self.metrics
+            .gas_station_available_gas_total_balance
+            .with_label_values(&[&self.sponsor_str])
+            .set(new_total_balance);
+        Ok(removed_count)
+    }
+
+    async fn take_large_coins(
+        &self,
+        min_balance: u64,
+        max_count: usize,
+    ) -> anyhow::Result<Vec<GasCoin>> {
+        let mut conn = self.conn_manager.clone();
+        let (coins, new_total_balance, new_coin_count): (Vec<String>, i64, i64) =
+            ScriptManager::take_large_coins_script()
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
+                .arg(min_balance)
+                .arg(max_count)
+                .invoke_async(&mut conn)
+                .await?;
+        self.metrics
+            .gas_station_available_gas_coin_count
+            .with_label_values(&[&self.sponsor_str])
+            .set(new_coin_count);
+        self.metrics
+            .gas_station_available_gas_total_balance
+            .with_label_values(&[&self.sponsor_str])
+            .set(new_total_balance);
+        coins.iter().map(|coin| parse_gas_coin(coin)).collect()
+    }
+
+    async fn is_draining(&self) -> anyhow::Result<bool> {
+        let mut conn = self.conn_manager.clone();
+        let result = ScriptManager::get_is_draining_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .invoke_async::<_, bool>(&mut conn)
+            .await?;
+        Ok(result)
+    }
+
+    async fn resume_pool(&self) -> anyhow::Result<()> {
+        let mut conn = self.conn_manager.clone();
+        ScriptManager::resume_pool_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .invoke_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_reservation_expiration(
+        &self,
+        reservation_id: ReservationID,
+        reserved_duration_ms: u64,
+    ) -> anyhow::Result<()> {
+        let expiration_time = Utc::now()
+            .add(Duration::from_millis(reserved_duration_ms))
+            .timestamp_millis() as u64;
+        let mut conn = self.conn_manager.clone();
+        let exists = ScriptManager::set_reservation_expiration_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(reservation_id)
+            .arg(expiration_time)
+            .invoke_async::<_, bool>(&mut conn)
+            .await?;
+        if !exists {
+            return Err(GasStationError::ReservationExpired(reservation_id).into());
+        }
+        Ok(())
+    }
+
+    async fn extend_reservation_expiration(
+        &self,
+        reservation_id: ReservationID,
+        reserved_duration_ms: u64,
+    ) -> anyhow::Result<bool> {
+        let now = Utc::now();
+        let expiration_time = now
+            .add(Duration::from_millis(reserved_duration_ms))
+            .timestamp_millis() as u64;
+        let mut conn = self.conn_manager.clone();
+        let pending = ScriptManager::extend_reservation_expiration_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(reservation_id)
+            .arg(now.timestamp_millis() as u64)
+            .arg(expiration_time)
+            .invoke_async::<_, bool>(&mut conn)
+            .await?;
+        Ok(pending)
+    }
+
+    async fn export_pool(&self, read_only: bool) -> anyhow::Result<PoolSnapshot> {
+        let mut conn = self.conn_manager.clone();
+        let (
+            available_coins,
+            reservations,
+            quarantined_coins,
+            last_reservation_id,
+            initialized,
+            draining,
+        ): (
+            Vec<String>,
+            Vec<String>,
+            Vec<String>,
+            ReservationID,
+            bool,
+            bool,
+        ) = ScriptManager::export_pool_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(read_only as u8)
+            .invoke_async(&mut conn)
+            .await?;
+        let available_coins = available_coins
+            .iter()
+            .map(|coin| parse_gas_coin(coin))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let reservations = reservations
+            .chunks(4)
+            .map(|reservation| {
+                let [reservation_id, expiration_time, reservation_time, object_ids] = reservation
+                else {
+                    anyhow::bail!("Invalid reservation: {:?}", reservation);
+                };
+                Ok(ReservationSnapshot {
+                    reservation_id: reservation_id.parse()?,
+                    object_ids: object_ids
+                        .split(',')
+                        .map(ObjectID::from_str)
+                        .collect::<Result<_, _>>()?,
+                    expiration_time_ms: expiration_time.parse()?,
+                    reservation_time_ms: reservation_time.parse()?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let quarantined_coins = quarantined_coins
+            .chunks(2)
+            .map(|coin| {
+                let [coin, release_epoch] = coin else {
+                    anyhow::bail!("Invalid quarantined coin: {:?}", coin);
+                };
+                parse_quarantined_coin(coin, release_epoch.parse()?)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(PoolSnapshot {
+            available_coins,
+            reservations,
+            quarantined_coins,
+            last_reservation_id,
+            is_initialized: initialized,
+            is_draining: draining,
+        })
+    }
+
+    async fn import_pool(&self, pool: &PoolSnapshot) -> anyhow::Result<()> {
+        // The numbers are passed as strings, since the JSON numbers of Lua lose precision.
+        let pool_json = serde_json::json!({
+            "available_coins": pool
+                .available_coins
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{},{},{},{}",
+                        c.balance,
+                        c.object_ref.0,
+                        c.object_ref.1.value(),
+                        c.object_ref.2
+                    )
+                })
+                .collect::<Vec<_>>(),
+            "reservations": pool
+                .reservations
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "reservation_id": r.reservation_id.to_string(),
+                        "expiration_time": r.expiration_time_ms.to_string(),
+                        "reservation_time": r.reservation_time_ms.to_string(),
+                        "object_ids": r
+                            .object_ids
+                            .iter()
+                            .map(|id| id.to_string())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "quarantined_coins": pool
+                .quarantined_coins
+                .iter()
+                .map(|c| {
+                    serde_json::json!({
+                        "coin": format!("{},{}", c.object_id, c.balance),
+                        "release_epoch": c.release_epoch.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "last_reservation_id": pool.last_reservation_id.to_string(),
+            "initialized": pool.is_initialized,
+            "draining": pool.is_draining,
+        });
+        let mut conn = self.conn_manager.clone();
+        ScriptManager::import_pool_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(serde_json::to_string(&pool_json)?)
+            .invoke_async::<_, ()>(&mut conn)
+            .await?;
+        self.init_coin_stats_at_startup().await?;
+        Ok(())
     }
 
     async fn init_coin_stats_at_startup(&self) -> anyhow::Result<(u64, u64)> {
         let mut conn = self.conn_manager.clone();
         let (available_coin_count, available_coin_total_balance): (i64, i64) =
             ScriptManager::init_coin_stats_at_startup_script()
-                .arg(self.sponsor_str.clone())
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
                 .invoke_async(&mut conn)
                 .await?;
         info!(
@@ -206,13 +649,18 @@ impl Storage for RedisStorage {
     async fn is_initialized(&self) -> anyhow::Result<bool> {
         let mut conn = self.conn_manager.clone();
         let result = ScriptManager::get_is_initialized_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .invoke_async::<_, bool>(&mut conn)
             .await?;
         Ok(result)
     }
 
-    async fn acquire_init_lock(&self, lock_duration_sec: u64) -> anyhow::Result<bool> {
+    async fn acquire_init_lock(
+        &self,
+        holder_id: &str,
+        lock_duration_sec: u64,
+    ) -> anyhow::Result<bool> {
         let mut conn = self.conn_manager.clone();
         let cur_timestamp = Utc::now().timestamp() as u64;
         debug!(
@@ -220,24 +668,143 @@ impl Storage for RedisStorage {
             cur_timestamp, lock_duration_sec
         );
         let result = ScriptManager::acquire_init_lock_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .arg(cur_timestamp)
             .arg(lock_duration_sec)
+            .arg(holder_id)
+            .invoke_async::<_, bool>(&mut conn)
+            .await?;
+        Ok(result)
+    }
+
+    async fn renew_init_lock(
+        &self,
+        holder_id: &str,
+        lock_duration_sec: u64,
+    ) -> anyhow::Result<bool> {
+        let mut conn = self.conn_manager.clone();
+        let result = ScriptManager::renew_init_lock_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(Utc::now().timestamp() as u64)
+            .arg(lock_duration_sec)
+            .arg(holder_id)
             .invoke_async::<_, bool>(&mut conn)
             .await?;
         Ok(result)
     }
 
-    async fn release_init_lock(&self) -> anyhow::Result<()> {
+    async fn release_init_lock(&self, holder_id: &str) -> anyhow::Result<()> {
         debug!("Releasing the init lock.");
         let mut conn = self.conn_manager.clone();
         ScriptManager::release_init_lock_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(holder_id)
+            .invoke_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_initialized(&self) -> anyhow::Result<()> {
+        let mut conn = self.conn_manager.clone();
+        redis::cmd("SET")
+            .arg(format!("{}:initialized", self.key_prefix))
+            .arg(1)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn acquire_leader_lease(
+        &self,
+        holder_id: &str,
+        lease_duration_ms: u64,
+    ) -> anyhow::Result<bool> {
+        let mut conn = self.conn_manager.clone();
+        let result = ScriptManager::acquire_leader_lease_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(holder_id)
+            .arg(lease_duration_ms.max(1))
+            .invoke_async::<_, bool>(&mut conn)
+            .await?;
+        Ok(result)
+    }
+
+    async fn release_leader_lease(&self, holder_id: &str) -> anyhow::Result<()> {
+        debug!("Releasing the leader lease.");
+        let mut conn = self.conn_manager.clone();
+        ScriptManager::release_leader_lease_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(holder_id)
             .invoke_async::<_, ()>(&mut conn)
             .await?;
         Ok(())
     }
 
+    async fn set_init_progress(&self, progress: Option<&InitProgress>) -> anyhow::Result<()> {
+        let mut conn = self.conn_manager.clone();
+        match progress {
+            Some(progress) => {
+                redis::cmd("SET")
+                    .arg(self.init_progress_key())
+                    .arg(serde_json::to_string(progress)?)
+                    .query_async::<_, ()>(&mut conn)
+                    .await?
+            }
+            None => {
+                redis::cmd("DEL")
+                    .arg(self.init_progress_key())
+                    .query_async::<_, ()>(&mut conn)
+                    .await?
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_init_progress(&self) -> anyhow::Result<Option<InitProgress>> {
+        let mut conn = self.conn_manager.clone();
+        let progress = redis::cmd("GET")
+            .arg(self.init_progress_key())
+            .query_async::<_, Option<String>>(&mut conn)
+            .await?;
+        Ok(progress
+            .map(|progress| serde_json::from_str(&progress))
+            .transpose()?)
+    }
+
+    async fn put_executed_transaction(
+        &self,
+        idempotency_key: &str,
+        data: &str,
+        retention_ms: u64,
+    ) -> anyhow::Result<()> {
+        let mut conn = self.conn_manager.clone();
+        redis::cmd("SET")
+            .arg(self.executed_transaction_key(idempotency_key))
+            .arg(data)
+            .arg("PX")
+            .arg(retention_ms.max(1))
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_executed_transaction(
+        &self,
+        idempotency_key: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let mut conn = self.conn_manager.clone();
+        let data = redis::cmd("GET")
+            .arg(self.executed_transaction_key(idempotency_key))
+            .query_async::<_, Option<String>>(&mut conn)
+            .await?;
+        Ok(data)
+    }
+
     async fn check_health(&self) -> anyhow::Result<()> {
         let mut conn = self.conn_manager.clone();
         redis::cmd("PING")
@@ -258,7 +825,8 @@ impl Storage for RedisStorage {
     async fn get_available_coin_count(&self) -> anyhow::Result<usize> {
         let mut conn = self.conn_manager.clone();
         let count = ScriptManager::get_available_coin_count_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .invoke_async::<_, usize>(&mut conn)
             .await?;
         Ok(count)
@@ -267,31 +835,138 @@ impl Storage for RedisStorage {
     async fn get_available_coin_total_balance(&self) -> u64 {
         let mut conn = self.conn_manager.clone();
         ScriptManager::get_available_coin_total_balance_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .invoke_async::<_, u64>(&mut conn)
             .await
             .unwrap()
     }
 
+    async fn get_reservation_stats(&self) -> anyhow::Result<ReservationStats> {
+        let mut conn = self.conn_manager.clone();
+        let (reserved_coin_count, oldest_reservation_time_ms): (u64, i64) =
+            ScriptManager::get_reservation_stats_script()
+                .key(&self.key_prefix)
+                .arg(self.key_prefix.clone())
+                .invoke_async(&mut conn)
+                .await?;
+        Ok(ReservationStats {
+            reserved_coin_count,
+            oldest_reservation_time_ms: u64::try_from(oldest_reservation_time_ms).ok(),
+        })
+    }
+
+    async fn record_reservation_client(
+        &self,
+        reservation_id: ReservationID,
+        client: &str,
+        retention_ms: u64,
+    ) -> anyhow::Result<()> {
+        let mut conn = self.conn_manager.clone();
+        ScriptManager::record_reservation_client_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(reservation_id)
+            .arg(client)
+            .arg(retention_ms)
+            .invoke_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_client_reservation_stats(
+        &self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ClientReservationStats>> {
+        let mut conn = self.conn_manager.clone();
+        let stats: Vec<String> = ScriptManager::get_client_reservation_stats_script()
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
+            .arg(limit)
+            .invoke_async(&mut conn)
+            .await?;
+        stats
+            .chunks(3)
+            .map(|client_stats| {
+                let [client, expired_count, reservation_count] = client_stats else {
+                    anyhow::bail!("Invalid client reservation stats: {:?}", client_stats);
+                };
+                Ok(ClientReservationStats {
+                    client: client.clone(),
+                    reservation_count: reservation_count.parse()?,
+                    expired_count: expired_count.parse()?,
+                })
+            })
+            .collect()
+    }
+
     #[cfg(test)]
     async fn get_reserved_coin_count(&self) -> usize {
         let mut conn = self.conn_manager.clone();
         ScriptManager::get_reserved_coin_count_script()
-            .arg(self.sponsor_str.clone())
+            .key(&self.key_prefix)
+            .arg(self.key_prefix.clone())
             .invoke_async::<_, usize>(&mut conn)
             .await
             .unwrap()
     }
 }
 
+fn parse_gas_coin(coin: &str) -> anyhow::Result<GasCoin> {
+    // The format is: balance,object_id,version,digest
+    let mut splits = coin.split(',');
+    let (Some(balance), Some(object_id), Some(version), Some(digest)) =
+        (splits.next(), splits.next(), splits.next(), splits.next())
+    else {
+        anyhow::bail!("Invalid gas coin: {}", coin);
+    };
+    Ok(GasCoin {
+        balance: balance.parse()?,
+        object_ref: (
+            ObjectID::from_str(object_id)?,
+            SequenceNumber::from(version.parse::<u64>()?),
+            ObjectDigest::from_str(digest)?,
+        ),
+    })
+}
+
+fn parse_available_coin(coin: &str) -> anyhow::Result<PoolCoin> {
+    // The format is: balance,object_id,version,digest
+    let mut splits = coin.split(',');
+    let (Some(balance), Some(object_id), Some(version)) =
+        (splits.next(), splits.next(), splits.next())
+    else {
+        anyhow::bail!("Invalid gas coin: {}", coin);
+    };
+    Ok(PoolCoin {
+        object_id: ObjectID::from_str(object_id)?,
+        version: Some(version.parse()?),
+        balance: Some(balance.parse()?),
+        state: PoolCoinState::Available,
+    })
+}
+
+fn parse_quarantined_coin(coin: &str, release_epoch: u64) -> anyhow::Result<QuarantinedCoin> {
+    let (object_id, balance) = coin
+        .split_once(',')
+        .ok_or_else(|| anyhow::anyhow!("Invalid quarantined coin: {}", coin))?;
+    Ok(QuarantinedCoin {
+        object_id: ObjectID::from_str(object_id)?,
+        balance: balance.parse()?,
+        release_epoch,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use iota_types::base_types::{random_object_ref, IotaAddress};
 
     use crate::{
+        errors::GasStationError,
         metrics::StorageMetrics,
+        redis_connection::RedisMode,
         storage::{redis::RedisStorage, Storage},
-        types::GasCoin,
+        types::{GasCoin, PoolCoinState, QuarantinedCoin},
     };
 
     #[tokio::test]
@@ -354,9 +1029,299 @@ mod tests {
         assert_eq!(total_balance, 1000);
     }
 
+    #[tokio::test]
+    async fn test_quarantine_coins() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = (0..3)
+            .map(|i| GasCoin {
+                balance: 100 * (i + 1),
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage
+            .quarantine_coins(coins[..2].to_vec(), 5)
+            .await
+            .unwrap();
+        storage
+            .quarantine_coins(coins[2..].to_vec(), 6)
+            .await
+            .unwrap();
+        assert_eq!(storage.get_quarantined_coins().await.unwrap().len(), 3);
+        assert_eq!(storage.get_available_coin_count().await.unwrap(), 0);
+
+        assert!(storage
+            .release_quarantined_coins(4)
+            .await
+            .unwrap()
+            .is_empty());
+        let released = storage.release_quarantined_coins(5).await.unwrap();
+        assert_eq!(released.len(), 2);
+        assert!(released.contains(&coins[0].object_ref.0));
+        assert!(released.contains(&coins[1].object_ref.0));
+
+        let remaining = storage.get_quarantined_coins().await.unwrap();
+        assert_eq!(
+            remaining,
+            vec![QuarantinedCoin {
+                object_id: coins[2].object_ref.0,
+                balance: 300,
+                release_epoch: 6,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pool_coins() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = (0..4)
+            .map(|i| GasCoin {
+                balance: 100 * (i + 1),
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage.add_new_coins(coins[..3].to_vec()).await.unwrap();
+        storage
+            .quarantine_coins(coins[3..].to_vec(), 5)
+            .await
+            .unwrap();
+        let (reservation_id, reserved) = storage.reserve_gas_coins(100, 1000).await.unwrap();
+
+        let pool_coins = storage.get_pool_coins().await.unwrap();
+        assert_eq!(pool_coins.len(), 4);
+        let coin_of = |object_id| {
+            pool_coins
+                .iter()
+                .find(|coin| coin.object_id == object_id)
+                .unwrap()
+        };
+        let reserved_coin = coin_of(reserved[0].object_ref.0);
+        assert!(matches!(
+            reserved_coin.state,
+            PoolCoinState::Reserved { reservation_id: id, .. } if id == reservation_id
+        ));
+        let available_coin = coin_of(coins[1].object_ref.0);
+        assert_eq!(available_coin.state, PoolCoinState::Available);
+        assert_eq!(available_coin.balance, Some(200));
+        assert_eq!(available_coin.version, Some(coins[1].object_ref.1.value()));
+        assert_eq!(
+            coin_of(coins[3].object_ref.0).state,
+            PoolCoinState::Quarantined { release_epoch: 5 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_available_coins() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = (0..3)
+            .map(|i| GasCoin {
+                balance: 100 * (i + 1),
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage.add_new_coins(coins.clone()).await.unwrap();
+        let (_, reserved) = storage.reserve_gas_coins(100, 1000).await.unwrap();
+        assert!(!storage.is_draining().await.unwrap());
+
+        let drained = storage.drain_available_coins().await.unwrap();
+        assert_eq!(drained, coins[reserved.len()..].to_vec());
+        assert!(storage.is_draining().await.unwrap());
+        // The coins stay in the pool until they are transferred.
+        assert_eq!(
+            storage.get_available_coin_count().await.unwrap(),
+            drained.len()
+        );
+        let removed = storage
+            .remove_available_coins(&[drained[0].object_ref.0, reserved[0].object_ref.0])
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(
+            storage.get_available_coin_count().await.unwrap(),
+            drained.len() - 1
+        );
+        assert_eq!(
+            storage.get_available_coin_total_balance().await,
+            drained[1..].iter().map(|coin| coin.balance).sum::<u64>()
+        );
+
+        // Released coins are kept in the pool, but cannot be reserved until the pool is resumed.
+        storage.add_new_coins(reserved).await.unwrap();
+        let err = storage.reserve_gas_coins(100, 1000).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GasStationError>(),
+            Some(&GasStationError::PoolDraining)
+        );
+        storage.resume_pool().await.unwrap();
+        assert!(!storage.is_draining().await.unwrap());
+        storage.reserve_gas_coins(100, 1000).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_take_large_coins() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = [500, 100, 300, 50]
+            .into_iter()
+            .map(|balance| GasCoin {
+                balance,
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage.add_new_coins(coins.clone()).await.unwrap();
+
+        let taken = storage.take_large_coins(300, 1).await.unwrap();
+        assert_eq!(taken, vec![coins[0].clone()]);
+        let taken = storage.take_large_coins(300, 10).await.unwrap();
+        assert_eq!(taken, vec![coins[2].clone()]);
+        assert_eq!(storage.get_available_coin_count().await.unwrap(), 2);
+        assert_eq!(storage.get_available_coin_total_balance().await, 150);
+
+        // Nothing is taken out while draining
+        storage.drain_available_coins().await.unwrap();
+        storage.add_new_coins(coins.clone()).await.unwrap();
+        assert!(storage.take_large_coins(0, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_smallest_coins() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = [500, 100, 300, 50]
+            .into_iter()
+            .map(|balance| GasCoin {
+                balance,
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage.add_new_coins(coins.clone()).await.unwrap();
+
+        // The smallest coin covering the budget is reserved
+        let (_, reserved) = storage.reserve_gas_coins(80, 1000).await.unwrap();
+        assert_eq!(reserved, vec![coins[1].clone()]);
+        let (_, reserved) = storage.reserve_gas_coins(200, 1000).await.unwrap();
+        assert_eq!(reserved, vec![coins[2].clone()]);
+        // Without a coin large enough, the largest one is completed with the smallest one covering the rest
+        let (_, reserved) = storage.reserve_gas_coins(540, 1000).await.unwrap();
+        assert_eq!(reserved, vec![coins[0].clone(), coins[3].clone()]);
+        assert_eq!(storage.get_available_coin_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_available_coins_moved_from_list() {
+        let storage = setup_storage().await;
+        let coin = GasCoin {
+            balance: 100,
+            object_ref: random_object_ref(),
+        };
+        let mut conn = storage.conn_manager.clone();
+        redis::cmd("RPUSH")
+            .arg(format!("{}:available_gas_coins", IotaAddress::ZERO))
+            .arg(format!(
+                "{},{},{},{}",
+                coin.balance,
+                coin.object_ref.0,
+                coin.object_ref.1.value(),
+                coin.object_ref.2
+            ))
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .unwrap();
+
+        let (coin_count, total_balance) = storage.init_coin_stats_at_startup().await.unwrap();
+        assert_eq!(coin_count, 1);
+        assert_eq!(total_balance, 100);
+        let (_, reserved) = storage.reserve_gas_coins(100, 1000).await.unwrap();
+        assert_eq!(reserved, vec![coin]);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_pool() {
+        let storage = setup_storage().await;
+        let coins: Vec<_> = (0..4)
+            .map(|i| GasCoin {
+                balance: 100 * (i + 1),
+                object_ref: random_object_ref(),
+            })
+            .collect();
+        storage.add_new_coins(coins.clone()).await.unwrap();
+        storage.set_initialized().await.unwrap();
+        let (reservation_id, reserved) = storage.reserve_gas_coins(100, 60000).await.unwrap();
+        storage
+            .quarantine_coins(vec![coins[3].clone()], 5)
+            .await
+            .unwrap();
+
+        let pool = storage.export_pool(true).await.unwrap();
+        assert_eq!(pool.available_coins.len(), 3);
+        assert_eq!(pool.reservations.len(), 1);
+        assert_eq!(pool.reservations[0].reservation_id, reservation_id);
+        assert_eq!(
+            pool.reservations[0].object_ids,
+            vec![reserved[0].object_ref.0]
+        );
+        assert_eq!(pool.quarantined_coins[0].release_epoch, 5);
+        assert_eq!(pool.last_reservation_id, reservation_id);
+        assert!(pool.is_initialized);
+        assert!(!pool.is_draining);
+        // The source is read-only from now on
+        assert!(storage.is_draining().await.unwrap());
+        assert!(storage.reserve_gas_coins(100, 1000).await.is_err());
+
+        let target = RedisStorage::new(
+            "redis://127.0.0.1:6379",
+            &RedisMode::Single,
+            None,
+            IotaAddress::random_for_testing_only(),
+            StorageMetrics::new_for_testing(),
+        )
+        .await;
+        target.import_pool(&pool).await.unwrap();
+        assert_eq!(target.export_pool(false).await.unwrap(), pool);
+        assert_eq!(target.get_available_coin_count().await.unwrap(), 3);
+        assert_eq!(target.get_reserved_coin_count().await, 1);
+        let (next_reservation_id, _) = target.reserve_gas_coins(100, 1000).await.unwrap();
+        assert_eq!(next_reservation_id, reservation_id + 1);
+        // Pools are never merged
+        assert!(target.import_pool(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_namespaces() {
+        let sponsor = IotaAddress::random_for_testing_only();
+        let connect = |namespace| async move {
+            let storage = RedisStorage::new(
+                "redis://127.0.0.1:6379",
+                &RedisMode::Single,
+                namespace,
+                sponsor,
+                StorageMetrics::new_for_testing(),
+            )
+            .await;
+            storage.init_coin_stats_at_startup().await.unwrap();
+            storage
+        };
+        let staging = connect(Some("staging")).await;
+        let prod = connect(Some("prod")).await;
+        staging
+            .add_new_coins(vec![GasCoin {
+                balance: 100,
+                object_ref: random_object_ref(),
+            }])
+            .await
+            .unwrap();
+        assert_eq!(staging.get_available_coin_count().await.unwrap(), 1);
+        assert_eq!(prod.get_available_coin_count().await.unwrap(), 0);
+        assert!(prod.reserve_gas_coins(100, 1000).await.is_err());
+        let without_namespace = connect(None).await;
+        assert_eq!(
+            without_namespace.get_available_coin_count().await.unwrap(),
+            0
+        );
+    }
+
     async fn setup_storage() -> RedisStorage {
         let storage = RedisStorage::new(
             "redis://127.0.0.1:6379",
+            &RedisMode::Single,
+            None,
             IotaAddress::ZERO,
             StorageMetrics::new_for_testing(),
         )
@@ -13,7 +13,32 @@ const GET_IS_INITIALIZED_SCRIPT: &str = include_str!("lua_scripts/get_is_initial
 const GET_AVAILABLE_COIN_TOTAL_BALANCE_SCRIPT: &str =
     include_str!("lua_scripts/get_available_coin_total_balance.lua");
 const ACQUIRE_INIT_LOCK_SCRIPT: &str = include_str!("lua_scripts/acquire_init_lock.lua");
+const RENEW_INIT_LOCK_SCRIPT: &str = include_str!("lua_scripts/renew_init_lock.lua");
 const RELEASE_INIT_LOCK_SCRIPT: &str = include_str!("lua_scripts/release_init_lock.lua");
+const ACQUIRE_LEADER_LEASE_SCRIPT: &str = include_str!("lua_scripts/acquire_leader_lease.lua");
+const RELEASE_LEADER_LEASE_SCRIPT: &str = include_str!("lua_scripts/release_leader_lease.lua");
+const QUARANTINE_COINS_SCRIPT: &str = include_str!("lua_scripts/quarantine_coins.lua");
+const RELEASE_QUARANTINED_COINS_SCRIPT: &str =
+    include_str!("lua_scripts/release_quarantined_coins.lua");
+const GET_QUARANTINED_COINS_SCRIPT: &str = include_str!("lua_scripts/get_quarantined_coins.lua");
+const GET_RESERVATION_STATS_SCRIPT: &str = include_str!("lua_scripts/get_reservation_stats.lua");
+const GET_POOL_COINS_SCRIPT: &str = include_str!("lua_scripts/get_pool_coins.lua");
+const DRAIN_AVAILABLE_COINS_SCRIPT: &str = include_str!("lua_scripts/drain_available_coins.lua");
+const RECORD_RESERVATION_CLIENT_SCRIPT: &str =
+    include_str!("lua_scripts/record_reservation_client.lua");
+const GET_CLIENT_RESERVATION_STATS_SCRIPT: &str =
+    include_str!("lua_scripts/get_client_reservation_stats.lua");
+const REMOVE_AVAILABLE_COINS_SCRIPT: &str = include_str!("lua_scripts/remove_available_coins.lua");
+const TAKE_LARGE_COINS_SCRIPT: &str = include_str!("lua_scripts/take_large_coins.lua");
+const GET_IS_DRAINING_SCRIPT: &str = include_str!("lua_scripts/get_is_draining.lua");
+const RESUME_POOL_SCRIPT: &str = include_str!("lua_scripts/resume_pool.lua");
+const EXPORT_POOL_SCRIPT: &str = include_str!("lua_scripts/export_pool.lua");
+const IMPORT_POOL_SCRIPT: &str = include_str!("lua_scripts/import_pool.lua");
+const SET_RESERVATION_EXPIRATION_SCRIPT: &str =
+    include_str!("lua_scripts/set_reservation_expiration.lua");
+const EXTEND_RESERVATION_EXPIRATION_SCRIPT: &str =
+    include_str!("lua_scripts/extend_reservation_expiration.lua");
+const GET_RESERVATION_SCRIPT: &str = include_str!("lua_scripts/get_reservation.lua");
 
 #[cfg(test)]
 const GET_RESERVED_COIN_COUNT_SCRIPT: &str =
@@ -68,11 +93,113 @@ impl ScriptManager {
         Lazy::force(&SCRIPT)
     }
 
+    pub fn renew_init_lock_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RENEW_INIT_LOCK_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
     pub fn release_init_lock_script() -> &'static Script {
         static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RELEASE_INIT_LOCK_SCRIPT));
         Lazy::force(&SCRIPT)
     }
 
+    pub fn acquire_leader_lease_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(ACQUIRE_LEADER_LEASE_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn release_leader_lease_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RELEASE_LEADER_LEASE_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn quarantine_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(QUARANTINE_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn release_quarantined_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RELEASE_QUARANTINED_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_quarantined_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_QUARANTINED_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_reservation_stats_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_RESERVATION_STATS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_pool_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_POOL_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn drain_available_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(DRAIN_AVAILABLE_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn record_reservation_client_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RECORD_RESERVATION_CLIENT_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_client_reservation_stats_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> =
+            Lazy::new(|| Script::new(GET_CLIENT_RESERVATION_STATS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn remove_available_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(REMOVE_AVAILABLE_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn take_large_coins_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(TAKE_LARGE_COINS_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_is_draining_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_IS_DRAINING_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn resume_pool_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RESUME_POOL_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn set_reservation_expiration_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(SET_RESERVATION_EXPIRATION_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn extend_reservation_expiration_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> =
+            Lazy::new(|| Script::new(EXTEND_RESERVATION_EXPIRATION_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_reservation_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_RESERVATION_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn export_pool_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(EXPORT_POOL_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn import_pool_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(IMPORT_POOL_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
     // This needs to be test only because it's really expensive to call in production.
     #[cfg(test)]
     pub fn get_reserved_coin_count_script() -> &'static Script {
@@ -0,0 +1,55 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::GasStationStorageConfig;
+use crate::metrics::StorageMetrics;
+use crate::storage::connect_storage;
+use crate::types::PoolSnapshot;
+use anyhow::{bail, Context};
+use iota_types::base_types::IotaAddress;
+use prometheus::Registry;
+use tracing::info;
+
+/// Copies the pools of the sponsors from the `from` storage to the `to` storage: the available
+/// coins, the pending reservations with their expiration, the quarantined coins, and the
+/// counters. Each pool is read in a single step, and written in a single step.
+///
+/// With `read_only_source`, the pools of the source are marked as draining when they are read, so
+/// that a station still using the source cannot reserve the copied coins. The Gas Station should
+/// be stopped meanwhile, since the coins of the transactions being executed return to the source.
+/// Returns the pools copied.
+pub async fn migrate_storage(
+    from: &GasStationStorageConfig,
+    to: &GasStationStorageConfig,
+    sponsor_addresses: &[IotaAddress],
+    read_only_source: bool,
+) -> anyhow::Result<Vec<(IotaAddress, PoolSnapshot)>> {
+    let metrics = StorageMetrics::new(&Registry::new());
+    let mut pools = vec![];
+    for sponsor_address in sponsor_addresses {
+        let source = connect_storage(from, *sponsor_address, metrics.clone()).await;
+        let target = connect_storage(to, *sponsor_address, metrics.clone()).await;
+        if target.is_initialized().await? {
+            bail!(
+                "The target storage already has a gas pool for {}",
+                sponsor_address
+            );
+        }
+        let pool = source.export_pool(read_only_source).await?;
+        info!(
+            "Copying the pool of {}: {} available coins, {} reservations, {} quarantined coins",
+            sponsor_address,
+            pool.available_coins.len(),
+            pool.reservations.len(),
+            pool.quarantined_coins.len()
+        );
+        target.import_pool(&pool).await.with_context(|| {
+            format!(
+                "Failed to write the pool of {} to the target storage",
+                sponsor_address
+            )
+        })?;
+        pools.push((*sponsor_address, pool));
+    }
+    Ok(pools)
+}
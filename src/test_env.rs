@@ -2,17 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::access_controller::AccessController;
-use crate::config::{CoinInitConfig, GasStationStorageConfig, DEFAULT_DAILY_GAS_USAGE_CAP};
-use crate::gas_station::gas_station_core::GasStationContainer;
+use crate::config::{
+    CoinInitConfig, GasStationConfig, GasStationStorageConfig, DEFAULT_DAILY_GAS_USAGE_CAP,
+};
+use crate::config_reloader::ConfigReloader;
+use crate::gas_station::gas_station_core::{GasStation, GasStationContainer};
 use crate::gas_station_initializer::GasStationInitializer;
 use crate::iota_client::IotaClient;
-use crate::metrics::{GasStationCoreMetrics, GasStationRpcMetrics};
+use crate::leader_election::Leadership;
+use crate::logging::TxLogger;
+use crate::metrics::{GasStationCoreMetrics, GasStationRpcMetrics, StorageMetrics};
+use crate::rpc::body_limit::BodyLimitConfig;
 use crate::rpc::GasStationServer;
 use crate::storage::connect_storage_for_testing;
-use crate::tracker::stats_tracker_storage::redis::connect_stats_storage;
-use crate::tracker::stats_tracker_storage::{self, StatsTrackerStorage};
+use crate::tracker::scoped_gas_usage_caps::ScopedGasUsageCaps;
+use crate::tracker::stats_tracker_storage::{
+    self, connect_stats_storage, StatsStorageConfig, StatsTrackerStorage,
+};
 use crate::tracker::StatsTracker;
 use crate::tx_signer::{TestTxSigner, TxSigner};
+use crate::webhooks::Webhooks;
 use crate::AUTH_ENV_NAME;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
@@ -70,14 +79,18 @@ pub async fn start_gas_station(
             ..Default::default()
         },
         signer.clone(),
+        Leadership::new_for_testing(),
     )
     .await;
     let station = GasStationContainer::new(
         signer,
         storage,
+        None,
         iota_client,
         DEFAULT_DAILY_GAS_USAGE_CAP,
         GasStationCoreMetrics::new_for_testing(),
+        Webhooks::default(),
+        Leadership::new_for_testing(),
     )
     .await;
     (test_cluster, station)
@@ -92,14 +105,30 @@ pub async fn start_rpc_server_for_testing(
     let signer_address = container.get_signer_address();
     std::env::set_var(AUTH_ENV_NAME, "some secret");
 
+    let access_controller = Arc::new(ArcSwap::new(Arc::new(AccessController::default())));
+    let scoped_gas_usage_caps = Arc::new(ScopedGasUsageCaps::new(None, None));
+    let config_reloader = new_config_reloader_for_testing(
+        container.get_gas_station_arc(),
+        access_controller.clone(),
+        scoped_gas_usage_caps.clone(),
+    );
+
     let server = GasStationServer::new(
         container.get_gas_station_arc(),
-        localhost.parse().unwrap(),
+        vec![localhost.parse().unwrap()],
+        get_available_port(&localhost),
+        vec![localhost.parse().unwrap()],
         get_available_port(&localhost),
         GasStationRpcMetrics::new_for_testing(),
-        Arc::new(ArcSwap::new(Arc::new(AccessController::default()))),
+        access_controller,
         new_stats_tracker_for_testing(signer_address).await,
-        PathBuf::from_str(DEFAULT_TEST_CONFIG_PATH).unwrap(),
+        scoped_gas_usage_caps,
+        TxLogger::default(),
+        config_reloader,
+        None,
+        None,
+        None,
+        BodyLimitConfig::default(),
     )
     .await;
     (test_cluster, container, server)
@@ -113,14 +142,30 @@ pub async fn start_rpc_server_for_testing_no_auth(
     let localhost = localhost_for_testing();
     let signer_address = container.get_signer_address();
 
+    let access_controller = Arc::new(ArcSwap::new(Arc::new(AccessController::default())));
+    let scoped_gas_usage_caps = Arc::new(ScopedGasUsageCaps::new(None, None));
+    let config_reloader = new_config_reloader_for_testing(
+        container.get_gas_station_arc(),
+        access_controller.clone(),
+        scoped_gas_usage_caps.clone(),
+    );
+
     let server = GasStationServer::new(
         container.get_gas_station_arc(),
-        localhost.parse().unwrap(),
+        vec![localhost.parse().unwrap()],
+        get_available_port(&localhost),
+        vec![localhost.parse().unwrap()],
         get_available_port(&localhost),
         GasStationRpcMetrics::new_for_testing(),
-        Arc::new(ArcSwap::new(Arc::new(AccessController::default()))),
+        access_controller,
         new_stats_tracker_for_testing(signer_address).await,
-        PathBuf::from_str(DEFAULT_TEST_CONFIG_PATH).unwrap(),
+        scoped_gas_usage_caps,
+        TxLogger::default(),
+        config_reloader,
+        None,
+        None,
+        None,
+        BodyLimitConfig::default(),
     )
     .await;
     (test_cluster, container, server)
@@ -136,19 +181,51 @@ pub async fn start_rpc_server_for_testing_with_access_controller(
     let signer_address = container.get_signer_address();
     std::env::set_var(AUTH_ENV_NAME, "some secret");
 
+    let access_controller = Arc::new(ArcSwap::new(Arc::new(access_controller)));
+    let scoped_gas_usage_caps = Arc::new(ScopedGasUsageCaps::new(None, None));
+    let config_reloader = new_config_reloader_for_testing(
+        container.get_gas_station_arc(),
+        access_controller.clone(),
+        scoped_gas_usage_caps.clone(),
+    );
+
     let server = GasStationServer::new(
         container.get_gas_station_arc(),
-        localhost.parse().unwrap(),
+        vec![localhost.parse().unwrap()],
+        get_available_port(&localhost),
+        vec![localhost.parse().unwrap()],
         get_available_port(&localhost),
         GasStationRpcMetrics::new_for_testing(),
-        Arc::new(ArcSwap::new(Arc::new(access_controller))),
+        access_controller,
         new_stats_tracker_for_testing(signer_address).await,
-        PathBuf::from_str(DEFAULT_TEST_CONFIG_PATH).unwrap(),
+        scoped_gas_usage_caps,
+        TxLogger::default(),
+        config_reloader,
+        None,
+        None,
+        None,
+        BodyLimitConfig::default(),
     )
     .await;
     (test_cluster, container, server)
 }
 
+pub fn new_config_reloader_for_testing(
+    gas_station: Arc<GasStation>,
+    access_controller: Arc<ArcSwap<AccessController>>,
+    scoped_gas_usage_caps: Arc<ScopedGasUsageCaps>,
+) -> Arc<ConfigReloader> {
+    Arc::new(ConfigReloader::new(
+        PathBuf::from_str(DEFAULT_TEST_CONFIG_PATH).unwrap(),
+        &GasStationConfig::default(),
+        access_controller,
+        gas_station,
+        scoped_gas_usage_caps,
+        None,
+        None,
+    ))
+}
+
 pub async fn create_test_transaction(
     test_cluster: &TestCluster,
     sponsor: IotaAddress,
@@ -173,19 +250,27 @@ pub async fn create_test_transaction(
     // TODO: Add proper sponsored transaction support to test tx builder.
     tx_data.gas_data_mut().payment = gas_coins;
     tx_data.gas_data_mut().owner = sponsor;
-    let user_sig = test_cluster
-        .sign_transaction(&tx_data)
+    let user_sig = sign_user_transaction(test_cluster, &tx_data);
+    (tx_data, user_sig)
+}
+
+/// Signs the transaction with the key of its sender, held by the wallet of the test cluster.
+pub fn sign_user_transaction(
+    test_cluster: &TestCluster,
+    tx_data: &TransactionData,
+) -> GenericSignature {
+    test_cluster
+        .sign_transaction(tx_data)
         .into_data()
         .tx_signatures_mut_for_testing()
         .pop()
-        .unwrap();
-    (tx_data, user_sig)
+        .unwrap()
 }
 
 pub async fn new_stats_tracker_for_testing(sponsor_address: IotaAddress) -> StatsTracker {
-    StatsTracker::new(Arc::new(
-        connect_stats_storage(&GasStationStorageConfig::default(), sponsor_address).await,
-    ))
+    let config = StatsStorageConfig::from(&GasStationStorageConfig::default());
+    let metrics = StorageMetrics::new_for_testing();
+    StatsTracker::new(connect_stats_storage(&config, sponsor_address, &metrics).await)
 }
 
 pub fn random_address() -> IotaAddress {
@@ -205,6 +290,58 @@ impl StatsTrackerStorage for MockedStatsTrackerStorage {
     ) -> anyhow::Result<i64> {
         Ok(0)
     }
+
+    async fn get_aggr_values(
+        &self,
+        key_metas: &[Vec<(String, Value)>],
+        _aggregate: &stats_tracker_storage::Aggregate,
+    ) -> anyhow::Result<Vec<i64>> {
+        Ok(vec![0; key_metas.len()])
+    }
+
+    async fn get_aggr(
+        &self,
+        _key_meta: &[(String, Value)],
+        _aggregate: &stats_tracker_storage::Aggregate,
+    ) -> anyhow::Result<stats_tracker_storage::AggregateValue> {
+        Ok(Default::default())
+    }
+
+    async fn reset_aggr(
+        &self,
+        _key_meta: &[(String, Value)],
+        _aggregate: &stats_tracker_storage::Aggregate,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn put_pending_confirmation(&self, _id: &str, _data: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn take_pending_confirmation(&self, _id: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn get_pending_confirmation(&self, _id: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn get_pending_confirmations(&self) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(vec![])
+    }
+
+    async fn put_held_transaction(&self, _id: &str, _data: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn take_held_transaction(&self, _id: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn get_held_transactions(&self) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(vec![])
+    }
 }
 
 pub fn mocked_stats_tracker() -> StatsTracker {
@@ -7,7 +7,9 @@ use std::sync::Arc;
 
 use serde_json::Value;
 
+pub mod scoped_gas_usage_caps;
 pub mod stats_tracker_storage;
+pub mod usage_report;
 
 #[derive(Clone)]
 pub struct StatsTracker {
@@ -28,4 +30,50 @@ impl StatsTracker {
         let key_meta = key_meta.into_iter().collect::<Vec<_>>();
         self.store.update_aggr(&key_meta, aggregate, value).await
     }
+
+    pub async fn get_aggr(
+        &self,
+        key_meta: impl IntoIterator<Item = (String, Value)> + Send,
+        aggregate: &stats_tracker_storage::Aggregate,
+    ) -> Result<stats_tracker_storage::AggregateValue> {
+        let key_meta = key_meta.into_iter().collect::<Vec<_>>();
+        self.store.get_aggr(&key_meta, aggregate).await
+    }
+
+    pub async fn reset_aggr(
+        &self,
+        key_meta: impl IntoIterator<Item = (String, Value)> + Send,
+        aggregate: &stats_tracker_storage::Aggregate,
+    ) -> Result<()> {
+        let key_meta = key_meta.into_iter().collect::<Vec<_>>();
+        self.store.reset_aggr(&key_meta, aggregate).await
+    }
+
+    pub async fn put_pending_confirmation(&self, id: &str, data: &str) -> Result<()> {
+        self.store.put_pending_confirmation(id, data).await
+    }
+
+    pub async fn take_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        self.store.take_pending_confirmation(id).await
+    }
+
+    pub async fn get_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        self.store.get_pending_confirmation(id).await
+    }
+
+    pub async fn get_pending_confirmations(&self) -> Result<Vec<(String, String)>> {
+        self.store.get_pending_confirmations().await
+    }
+
+    pub async fn put_held_transaction(&self, id: &str, data: &str) -> Result<()> {
+        self.store.put_held_transaction(id, data).await
+    }
+
+    pub async fn take_held_transaction(&self, id: &str) -> Result<Option<String>> {
+        self.store.take_held_transaction(id).await
+    }
+
+    pub async fn get_held_transactions(&self) -> Result<Vec<(String, String)>> {
+        self.store.get_held_transactions().await
+    }
 }
@@ -0,0 +1,259 @@
+//  Copyright (c) 2025 IOTA Stiftung
+//  SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use iota_types::base_types::IotaAddress;
+use serde_json::Value;
+use tracing::error;
+
+use super::stats_tracker_storage::{Aggregate, AggregateType};
+use super::StatsTracker;
+
+const DAILY_WINDOW: Duration = Duration::from_secs(60 * 60 * 24);
+const SENDER_AGGREGATE_NAME: &str = "sender_daily_gas_usage";
+const PACKAGE_AGGREGATE_NAME: &str = "package_daily_gas_usage";
+const NO_CAP: u64 = u64::MAX;
+
+/// Daily gas usage caps that apply to each sender and each called Move package separately, so
+/// that a single integrator cannot exhaust the budget of the whole sponsor.
+pub struct ScopedGasUsageCaps {
+    per_sender: AtomicU64,
+    per_package: AtomicU64,
+}
+
+/// Returned when reserving a transaction budget would exceed one of the caps.
+#[derive(Debug)]
+pub struct ScopedGasUsageCapExceeded {
+    pub scope: String,
+    pub cap: u64,
+}
+
+impl fmt::Display for ScopedGasUsageCapExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Daily gas usage cap of {} exceeded for {}",
+            self.cap, self.scope
+        )
+    }
+}
+
+impl std::error::Error for ScopedGasUsageCapExceeded {}
+
+/// The budget of a transaction counted against the caps. It has to be settled once the
+/// transaction is executed, so that only the gas actually used remains counted.
+pub struct GasUsageReservation {
+    scopes: Vec<Vec<(String, Value)>>,
+    budget: u64,
+}
+
+impl ScopedGasUsageCaps {
+    pub fn new(per_sender: Option<u64>, per_package: Option<u64>) -> Self {
+        Self {
+            per_sender: AtomicU64::new(per_sender.unwrap_or(NO_CAP)),
+            per_package: AtomicU64::new(per_package.unwrap_or(NO_CAP)),
+        }
+    }
+
+    pub fn set_caps(&self, per_sender: Option<u64>, per_package: Option<u64>) {
+        self.per_sender
+            .store(per_sender.unwrap_or(NO_CAP), Ordering::Relaxed);
+        self.per_package
+            .store(per_package.unwrap_or(NO_CAP), Ordering::Relaxed);
+    }
+
+    /// Counts `budget` against the cap of the sender and of every called package. Nothing is
+    /// counted if any of the caps would be exceeded.
+    pub async fn reserve(
+        &self,
+        stats_tracker: &StatsTracker,
+        sender: IotaAddress,
+        packages: &[IotaAddress],
+        budget: u64,
+    ) -> anyhow::Result<GasUsageReservation> {
+        let mut scopes = vec![];
+        let per_sender = self.per_sender.load(Ordering::Relaxed);
+        if per_sender != NO_CAP {
+            scopes.push((sender_scope(sender), per_sender));
+        }
+        let per_package = self.per_package.load(Ordering::Relaxed);
+        if per_package != NO_CAP {
+            let mut packages = packages.to_vec();
+            packages.sort();
+            packages.dedup();
+            scopes.extend(
+                packages
+                    .into_iter()
+                    .map(|package| (package_scope(package), per_package)),
+            );
+        }
+
+        let mut reservation = GasUsageReservation {
+            scopes: vec![],
+            budget,
+        };
+        for (scope, cap) in scopes {
+            let usage = stats_tracker
+                .update_aggr(scope.clone(), &aggregate_of(&scope), budget as i64)
+                .await;
+            let usage = match usage {
+                Ok(usage) => usage,
+                Err(err) => {
+                    reservation.settle(stats_tracker, None).await;
+                    return Err(err);
+                }
+            };
+            reservation.scopes.push(scope);
+            if usage > cap as i64 {
+                let scope = describe_scope(reservation.scopes.last().unwrap());
+                reservation.settle(stats_tracker, None).await;
+                return Err(ScopedGasUsageCapExceeded { scope, cap }.into());
+            }
+        }
+        Ok(reservation)
+    }
+}
+
+impl GasUsageReservation {
+    /// Replaces the reserved budget with `gas_used`, or releases it entirely if the transaction
+    /// was not executed.
+    pub async fn settle(self, stats_tracker: &StatsTracker, gas_used: Option<u64>) {
+        let diff = gas_used.unwrap_or(0) as i64 - self.budget as i64;
+        if diff == 0 {
+            return;
+        }
+        for scope in self.scopes {
+            let aggregate = aggregate_of(&scope);
+            if let Err(err) = stats_tracker.update_aggr(scope, &aggregate, diff).await {
+                error!(
+                    "Failed to settle the gas usage of {}: {:?}",
+                    aggregate.name, err
+                );
+            }
+        }
+    }
+}
+
+pub fn is_scoped_gas_usage_cap_exceeded(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ScopedGasUsageCapExceeded>().is_some()
+}
+
+fn sender_scope(sender: IotaAddress) -> Vec<(String, Value)> {
+    vec![(
+        "sender_address".to_string(),
+        Value::String(sender.to_string()),
+    )]
+}
+
+fn package_scope(package: IotaAddress) -> Vec<(String, Value)> {
+    vec![(
+        "package_address".to_string(),
+        Value::String(package.to_string()),
+    )]
+}
+
+fn aggregate_of(scope: &[(String, Value)]) -> Aggregate {
+    let name = match scope.first().map(|(key, _)| key.as_str()) {
+        Some("package_address") => PACKAGE_AGGREGATE_NAME,
+        _ => SENDER_AGGREGATE_NAME,
+    };
+    Aggregate::with_name(name)
+        .with_aggr_type(AggregateType::Sum)
+        .with_window(DAILY_WINDOW)
+}
+
+fn describe_scope(scope: &[(String, Value)]) -> String {
+    scope
+        .iter()
+        .map(|(key, value)| format!("{} {}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{mocked_stats_tracker, new_stats_tracker_for_testing, random_address};
+
+    #[tokio::test]
+    async fn test_no_caps() {
+        let caps = ScopedGasUsageCaps::new(None, None);
+        let reservation = caps
+            .reserve(
+                &mocked_stats_tracker(),
+                random_address(),
+                &[random_address()],
+                100,
+            )
+            .await
+            .unwrap();
+        assert!(reservation.scopes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_called_packages_are_deduplicated() {
+        let caps = ScopedGasUsageCaps::new(Some(1000), Some(1000));
+        let package = random_address();
+        let reservation = caps
+            .reserve(
+                &mocked_stats_tracker(),
+                random_address(),
+                &[package, random_address(), package],
+                100,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reservation.scopes.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_per_sender_cap() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let caps = ScopedGasUsageCaps::new(Some(100), None);
+        let sender = random_address();
+
+        let reservation = caps.reserve(&stats_tracker, sender, &[], 80).await.unwrap();
+        // Only the gas actually used remains counted against the cap.
+        reservation.settle(&stats_tracker, Some(50)).await;
+        let reservation = caps.reserve(&stats_tracker, sender, &[], 50).await.unwrap();
+        reservation.settle(&stats_tracker, Some(50)).await;
+
+        let err = caps
+            .reserve(&stats_tracker, sender, &[], 1)
+            .await
+            .err()
+            .unwrap();
+        assert!(is_scoped_gas_usage_cap_exceeded(&err));
+        // Other senders are not affected.
+        caps.reserve(&stats_tracker, random_address(), &[], 100)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_per_package_cap() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let caps = ScopedGasUsageCaps::new(None, Some(100));
+        let package = random_address();
+
+        let reservation = caps
+            .reserve(&stats_tracker, random_address(), &[package], 100)
+            .await
+            .unwrap();
+        let err = caps
+            .reserve(&stats_tracker, random_address(), &[package], 1)
+            .await
+            .err()
+            .unwrap();
+        assert!(is_scoped_gas_usage_cap_exceeded(&err));
+
+        // The budget of a failed transaction is released.
+        reservation.settle(&stats_tracker, None).await;
+        caps.reserve(&stats_tracker, random_address(), &[package], 100)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,389 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+
+use super::{
+    aggr_key, sliding_window_buckets, Aggregate, AggregateType, AggregateValue, StatsTrackerStorage,
+};
+
+/// Expired entries are purged after this many updates, so that keys that are never updated
+/// again don't accumulate.
+const PURGE_INTERVAL: u64 = 10_000;
+
+#[derive(Default)]
+pub struct InMemoryStatsTrackerStorage {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    sliding_entries: HashMap<String, SlidingEntry>,
+    pending_confirmations: HashMap<String, String>,
+    held_transactions: HashMap<String, String>,
+    updates_since_purge: u64,
+}
+
+struct Entry {
+    value: i64,
+    expires_at: Instant,
+}
+
+/// Sums of the buckets of a sliding window, by bucket index. Buckets that left the window are
+/// removed on update.
+struct SlidingEntry {
+    buckets: BTreeMap<u64, i64>,
+    expires_at: Instant,
+}
+
+impl SlidingEntry {
+    fn get(&self, aggr: &Aggregate, now_secs: u64) -> AggregateValue {
+        let (bucket_secs, bucket_count) = sliding_window_buckets(aggr.window);
+        let oldest_bucket = (now_secs / bucket_secs + 1).saturating_sub(bucket_count);
+        let buckets = self.buckets.range(oldest_bucket..);
+        let Some((oldest_used_bucket, _)) = buckets.clone().next() else {
+            return AggregateValue::default();
+        };
+        let resets_at = (oldest_used_bucket + bucket_count) * bucket_secs;
+        AggregateValue {
+            value: buckets.fold(0i64, |sum, (_, value)| sum.saturating_add(*value)),
+            resets_in: Some(Duration::from_secs(resets_at.saturating_sub(now_secs))),
+        }
+    }
+}
+
+impl InMemoryStatsTrackerStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl Inner {
+    fn get(&self, key: &str, aggr: &Aggregate, now: Instant) -> AggregateValue {
+        match aggr.aggr_type {
+            AggregateType::Sum => self
+                .entries
+                .get(key)
+                .filter(|entry| entry.expires_at > now)
+                .map_or_else(AggregateValue::default, |entry| AggregateValue {
+                    value: entry.value,
+                    resets_in: Some(entry.expires_at - now),
+                }),
+            AggregateType::SlidingSum => self
+                .sliding_entries
+                .get(key)
+                .map_or_else(AggregateValue::default, |entry| {
+                    entry.get(aggr, unix_secs())
+                }),
+        }
+    }
+}
+
+#[async_trait]
+impl StatsTrackerStorage for InMemoryStatsTrackerStorage {
+    async fn update_aggr(
+        &self,
+        key: &[(String, Value)],
+        aggr: &Aggregate,
+        value: i64,
+    ) -> Result<i64> {
+        let key = aggr_key(key, aggr);
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+
+        inner.updates_since_purge += 1;
+        if inner.updates_since_purge >= PURGE_INTERVAL {
+            inner.entries.retain(|_, entry| entry.expires_at > now);
+            inner
+                .sliding_entries
+                .retain(|_, entry| entry.expires_at > now);
+            inner.updates_since_purge = 0;
+        }
+
+        match aggr.aggr_type {
+            AggregateType::Sum => {
+                // Like the Redis implementation, the window starts with the first update and the
+                // value is reset once it elapsed.
+                let entry = inner
+                    .entries
+                    .entry(key)
+                    .and_modify(|entry| {
+                        if entry.expires_at <= now {
+                            entry.value = 0;
+                            entry.expires_at = now + aggr.window;
+                        }
+                    })
+                    .or_insert_with(|| Entry {
+                        value: 0,
+                        expires_at: now + aggr.window,
+                    });
+                entry.value = entry.value.checked_add(value).unwrap_or(i64::MAX);
+                Ok(entry.value)
+            }
+            AggregateType::SlidingSum => {
+                let (bucket_secs, bucket_count) = sliding_window_buckets(aggr.window);
+                let now_secs = unix_secs();
+                let bucket = now_secs / bucket_secs;
+                let entry = inner
+                    .sliding_entries
+                    .entry(key)
+                    .or_insert_with(|| SlidingEntry {
+                        buckets: BTreeMap::new(),
+                        expires_at: now,
+                    });
+                entry.buckets = entry
+                    .buckets
+                    .split_off(&(bucket + 1).saturating_sub(bucket_count));
+                let bucket_value = entry.buckets.entry(bucket).or_default();
+                *bucket_value = bucket_value.checked_add(value).unwrap_or(i64::MAX);
+                entry.expires_at = now + Duration::from_secs(bucket_secs * (bucket_count + 1));
+                Ok(entry.get(aggr, now_secs).value)
+            }
+        }
+    }
+
+    async fn get_aggr_values(
+        &self,
+        key_metas: &[Vec<(String, Value)>],
+        aggr: &Aggregate,
+    ) -> Result<Vec<i64>> {
+        let now = Instant::now();
+        let inner = self.inner.lock();
+        Ok(key_metas
+            .iter()
+            .map(|key| inner.get(&aggr_key(key, aggr), aggr, now).value)
+            .collect())
+    }
+
+    async fn get_aggr(&self, key: &[(String, Value)], aggr: &Aggregate) -> Result<AggregateValue> {
+        let now = Instant::now();
+        Ok(self.inner.lock().get(&aggr_key(key, aggr), aggr, now))
+    }
+
+    async fn reset_aggr(&self, key: &[(String, Value)], aggr: &Aggregate) -> Result<()> {
+        let key = aggr_key(key, aggr);
+        let mut inner = self.inner.lock();
+        match aggr.aggr_type {
+            AggregateType::Sum => {
+                inner.entries.remove(&key);
+            }
+            AggregateType::SlidingSum => {
+                inner.sliding_entries.remove(&key);
+            }
+        }
+        Ok(())
+    }
+
+    async fn put_pending_confirmation(&self, id: &str, data: &str) -> Result<()> {
+        self.inner
+            .lock()
+            .pending_confirmations
+            .insert(id.to_string(), data.to_string());
+        Ok(())
+    }
+
+    async fn take_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.inner.lock().pending_confirmations.remove(id))
+    }
+
+    async fn get_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.inner.lock().pending_confirmations.get(id).cloned())
+    }
+
+    async fn get_pending_confirmations(&self) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .inner
+            .lock()
+            .pending_confirmations
+            .iter()
+            .map(|(id, data)| (id.clone(), data.clone()))
+            .collect())
+    }
+
+    async fn put_held_transaction(&self, id: &str, data: &str) -> Result<()> {
+        self.inner
+            .lock()
+            .held_transactions
+            .insert(id.to_string(), data.to_string());
+        Ok(())
+    }
+
+    async fn take_held_transaction(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.inner.lock().held_transactions.remove(id))
+    }
+
+    async fn get_held_transactions(&self) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .inner
+            .lock()
+            .held_transactions
+            .iter()
+            .map(|(id, data)| (id.clone(), data.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_aggr() {
+        let storage = InMemoryStatsTrackerStorage::new();
+        let window = Duration::from_millis(200);
+        let aggregate = Aggregate::with_name("gas_usage")
+            .with_aggr_type(AggregateType::Sum)
+            .with_window(window);
+        let key_meta = vec![("sender_address".to_string(), json!("0x1234567890abcdef"))];
+
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 1).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 2).await.unwrap(),
+            3
+        );
+        assert_eq!(
+            storage
+                .get_aggr_values(&[key_meta.clone(), vec![]], &aggregate)
+                .await
+                .unwrap(),
+            vec![3, 0]
+        );
+        let value = storage.get_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(value.value, 3);
+        assert!(value.resets_in.unwrap() <= window);
+        storage.reset_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 3).await.unwrap(),
+            3
+        );
+        assert_eq!(
+            storage
+                .update_aggr(&key_meta, &aggregate, i64::MAX)
+                .await
+                .unwrap(),
+            i64::MAX
+        );
+
+        tokio::time::sleep(window + Duration::from_millis(50)).await;
+        assert_eq!(
+            storage
+                .get_aggr_values(&[key_meta.clone()], &aggregate)
+                .await
+                .unwrap(),
+            vec![0]
+        );
+        assert_eq!(
+            storage.get_aggr(&key_meta, &aggregate).await.unwrap(),
+            AggregateValue::default()
+        );
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 2).await.unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_aggr_sliding_sum() {
+        let storage = InMemoryStatsTrackerStorage::new();
+        let aggregate = Aggregate::with_name("gas_usage")
+            .with_aggr_type(AggregateType::SlidingSum)
+            .with_window(Duration::from_secs(60));
+        let key_meta = vec![("sender_address".to_string(), json!("0x1234567890abcdef"))];
+
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 1).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 2).await.unwrap(),
+            3
+        );
+        let value = storage.get_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(value.value, 3);
+        assert!(value.resets_in.unwrap() <= Duration::from_secs(60));
+
+        // Buckets that left the window are not counted
+        let now_secs = unix_secs();
+        let entry = SlidingEntry {
+            buckets: BTreeMap::from([(now_secs - 61, 5), (now_secs - 30, 2), (now_secs, 1)]),
+            expires_at: Instant::now(),
+        };
+        assert_eq!(
+            entry.get(&aggregate, now_secs),
+            AggregateValue {
+                value: 3,
+                resets_in: Some(Duration::from_secs(30)),
+            }
+        );
+
+        storage.reset_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(
+            storage.get_aggr(&key_meta, &aggregate).await.unwrap(),
+            AggregateValue::default()
+        );
+    }
+    #[tokio::test]
+    async fn test_pending_confirmations() {
+        let storage = InMemoryStatsTrackerStorage::new();
+        storage.put_pending_confirmation("a", "1").await.unwrap();
+        storage.put_pending_confirmation("b", "2").await.unwrap();
+        assert_eq!(
+            storage.get_pending_confirmation("a").await.unwrap(),
+            Some("1".to_string())
+        );
+
+        let mut confirmations = storage.get_pending_confirmations().await.unwrap();
+        confirmations.sort();
+        assert_eq!(
+            confirmations,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string())
+            ]
+        );
+        assert_eq!(
+            storage.take_pending_confirmation("a").await.unwrap(),
+            Some("1".to_string())
+        );
+        assert_eq!(storage.take_pending_confirmation("a").await.unwrap(), None);
+        assert_eq!(storage.get_pending_confirmations().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_held_transactions() {
+        let storage = InMemoryStatsTrackerStorage::new();
+        storage.put_held_transaction("a", "1").await.unwrap();
+        storage.put_pending_confirmation("b", "2").await.unwrap();
+
+        assert_eq!(
+            storage.get_held_transactions().await.unwrap(),
+            vec![("a".to_string(), "1".to_string())]
+        );
+        assert_eq!(
+            storage.take_held_transaction("a").await.unwrap(),
+            Some("1".to_string())
+        );
+        assert_eq!(storage.take_held_transaction("a").await.unwrap(), None);
+    }
+}
@@ -3,13 +3,26 @@
 
 use std::{
     fmt::{Display, Formatter},
+    sync::Arc,
     time::Duration,
 };
 
 use anyhow::Result;
 use async_trait::async_trait;
+use fastcrypto::hash::*;
+use iota_types::base_types::IotaAddress;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use serde_json_canonicalizer::to_string;
 
+use crate::config::GasStationStorageConfig;
+use crate::metrics::StorageMetrics;
+use crate::redis_connection::RedisMode;
+use crate::storage::storage_key_prefix;
+
+pub mod in_memory;
+pub mod postgres;
 pub mod redis;
 
 #[async_trait]
@@ -20,9 +33,59 @@ pub trait StatsTrackerStorage: Sync + Send {
         update: &Aggregate,
         value: i64,
     ) -> Result<i64>;
+
+    /// Returns the current value of the aggregate for each of the keys, 0 if it has no value.
+    async fn get_aggr_values(
+        &self,
+        key_metas: &[Vec<(String, Value)>],
+        aggregate: &Aggregate,
+    ) -> Result<Vec<i64>>;
+
+    /// Returns the current value of the aggregate and the time left until it decreases.
+    async fn get_aggr(
+        &self,
+        key_meta: &[(String, Value)],
+        aggregate: &Aggregate,
+    ) -> Result<AggregateValue>;
+
+    /// Removes the value of the aggregate, its window starts again with the next update.
+    async fn reset_aggr(&self, key_meta: &[(String, Value)], aggregate: &Aggregate) -> Result<()>;
+
+    /// Stores the data of a pending confirmation, replacing the previous data stored under `id`.
+    async fn put_pending_confirmation(&self, id: &str, data: &str) -> Result<()>;
+
+    /// Removes the pending confirmation and returns its data. If called concurrently, only one of
+    /// the callers gets the data.
+    async fn take_pending_confirmation(&self, id: &str) -> Result<Option<String>>;
+
+    /// Returns the data of the pending confirmation, without removing it.
+    async fn get_pending_confirmation(&self, id: &str) -> Result<Option<String>>;
+
+    /// Returns the ids and the data of all the pending confirmations.
+    async fn get_pending_confirmations(&self) -> Result<Vec<(String, String)>>;
+
+    /// Stores the data of a transaction held for manual approval, replacing the previous data
+    /// stored under `id`.
+    async fn put_held_transaction(&self, id: &str, data: &str) -> Result<()>;
+
+    /// Removes the held transaction and returns its data. If called concurrently, only one of the
+    /// callers gets the data.
+    async fn take_held_transaction(&self, id: &str) -> Result<Option<String>>;
+
+    /// Returns the ids and the data of all the held transactions.
+    async fn get_held_transactions(&self) -> Result<Vec<(String, String)>>;
+}
+
+/// Current value of an aggregate within its window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AggregateValue {
+    pub value: i64,
+    /// Time left until the window resets, or for sliding windows until the oldest counted
+    /// bucket leaves the window. `None` if the aggregate has no value.
+    pub resets_in: Option<Duration>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Aggregate {
     pub name: String,
     pub window: Duration,
@@ -46,16 +109,114 @@ impl Aggregate {
     }
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
 pub enum AggregateType {
+    /// Sum over a fixed window, starting with the first update and reset to 0 once it elapsed.
     #[default]
     Sum,
+    /// Sum over the last window, split into buckets that leave the window one by one.
+    SlidingSum,
 }
 
 impl Display for AggregateType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             AggregateType::Sum => write!(f, "sum"),
+            AggregateType::SlidingSum => write!(f, "sliding_sum"),
+        }
+    }
+}
+
+/// Sliding windows are split into this many buckets. The oldest bucket is counted as a whole, so
+/// a value can be counted up to one bucket longer than the window.
+const SLIDING_WINDOW_BUCKET_COUNT: u64 = 60;
+
+/// Returns the length in seconds and the number of the buckets of a sliding window.
+pub(crate) fn sliding_window_buckets(window: Duration) -> (u64, u64) {
+    let window_secs = window.as_secs().max(1);
+    let bucket_secs = window_secs.div_ceil(SLIDING_WINDOW_BUCKET_COUNT);
+    (bucket_secs, window_secs.div_ceil(bucket_secs))
+}
+
+/// Where the stats tracker keeps its aggregates. Defaults to the storage of the gas pool.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatsStorageConfig {
+    Redis {
+        redis_url: String,
+        /// See the namespace of the storage of the gas pool.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+        #[serde(default, skip_serializing_if = "RedisMode::is_single")]
+        mode: RedisMode,
+    },
+    /// Kept in a Postgres table. Expired rows are deleted periodically.
+    Postgres {
+        postgres_url: String,
+    },
+    /// Kept in the memory of the process, so the aggregates are neither shared between
+    /// replicas nor kept across restarts. Only suitable for single-instance deployments.
+    InMemory,
+}
+
+impl From<&GasStationStorageConfig> for StatsStorageConfig {
+    fn from(config: &GasStationStorageConfig) -> Self {
+        match config {
+            GasStationStorageConfig::Redis {
+                redis_url,
+                namespace,
+                mode,
+            } => Self::Redis {
+                redis_url: redis_url.clone(),
+                namespace: namespace.clone(),
+                mode: mode.clone(),
+            },
         }
     }
 }
+
+pub async fn connect_stats_storage(
+    config: &StatsStorageConfig,
+    sponsor_address: IotaAddress,
+    metrics: &StorageMetrics,
+) -> Arc<dyn StatsTrackerStorage> {
+    match config {
+        StatsStorageConfig::Redis {
+            redis_url,
+            namespace,
+            mode,
+        } => Arc::new(
+            redis::RedisStatsTrackerStorage::new(
+                redis_url,
+                mode,
+                storage_key_prefix(namespace.as_deref(), sponsor_address),
+                metrics.redis_connection_metrics("stats"),
+            )
+            .await,
+        ),
+        StatsStorageConfig::Postgres { postgres_url } => Arc::new(
+            postgres::PostgresStatsTrackerStorage::new(postgres_url, sponsor_address.to_string())
+                .await
+                .expect("Unable to connect to the stats tracker storage"),
+        ),
+        StatsStorageConfig::InMemory => Arc::new(in_memory::InMemoryStatsTrackerStorage::new()),
+    }
+}
+
+pub(crate) fn aggr_key(key: &[(String, Value)], aggr: &Aggregate) -> String {
+    let hash = generate_hash_from_key(key);
+    format!("{}:{}:{}", aggr.name, aggr.aggr_type, hash)
+}
+
+// we should generate the canonical hash key from the given key
+pub(crate) fn generate_hash_from_key(key: &[(String, Value)]) -> String {
+    let mut hash_key = String::new();
+    for (k, v) in key.iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+        hash_key.push_str(k);
+        hash_key.push_str(&to_string(&v).unwrap());
+    }
+
+    let mut hasher = Sha256::default();
+    hasher.update(hash_key.as_bytes());
+    hasher.finalize().to_string()
+}
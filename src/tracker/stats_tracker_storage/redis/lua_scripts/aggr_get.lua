@@ -0,0 +1,15 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+local sponsor_address = ARGV[1]
+local key_name = ARGV[2]
+
+local key = sponsor_address .. ':' .. key_name
+
+-- The value is returned as a string, Lua numbers cannot represent every i64.
+local value = redis.call('GET', key)
+if not value then
+    return {'0', -2}
+end
+
+return {value, redis.call('TTL', key)}
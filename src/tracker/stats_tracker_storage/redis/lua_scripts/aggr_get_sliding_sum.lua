@@ -0,0 +1,36 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+local sponsor_address = ARGV[1]
+local key_name = ARGV[2]
+local bucket_secs = tonumber(ARGV[3])
+local bucket_count = tonumber(ARGV[4])
+
+local MAX_I64 = 9223372036854775807
+local key = sponsor_address .. ':' .. key_name
+
+local now = tonumber(redis.call('TIME')[1])
+local oldest_bucket = math.floor(now / bucket_secs) - bucket_count + 1
+
+local sum = 0
+local oldest_used_bucket = nil
+local entries = redis.call('HGETALL', key)
+for i = 1, #entries, 2 do
+    local bucket = tonumber(entries[i])
+    if bucket >= oldest_bucket then
+        sum = sum + tonumber(entries[i + 1])
+        if oldest_used_bucket == nil or bucket < oldest_used_bucket then
+            oldest_used_bucket = bucket
+        end
+    end
+end
+
+if oldest_used_bucket == nil then
+    return {0, -2}
+end
+if sum > MAX_I64 then
+    sum = MAX_I64
+end
+
+-- Seconds until the oldest bucket leaves the window, decreasing the sum.
+return {sum, (oldest_used_bucket + bucket_count) * bucket_secs - now}
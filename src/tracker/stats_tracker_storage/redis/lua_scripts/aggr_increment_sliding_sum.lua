@@ -0,0 +1,40 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+-- The aggregate is a hash of bucket index to the sum of the bucket. Buckets that left the window
+-- are removed whenever the aggregate is updated.
+
+local sponsor_address = ARGV[1]
+local key_name = ARGV[2]
+local amount = tonumber(ARGV[3])
+local bucket_secs = tonumber(ARGV[4])
+local bucket_count = tonumber(ARGV[5])
+
+local MAX_I64 = 9223372036854775807
+local key = sponsor_address .. ':' .. key_name
+
+local now = tonumber(redis.call('TIME')[1])
+local bucket = math.floor(now / bucket_secs)
+local oldest_bucket = bucket - bucket_count + 1
+
+local ok = pcall(redis.call, 'HINCRBY', key, bucket, amount)
+if not ok then
+    -- overflow handling
+    redis.call('HSET', key, bucket, MAX_I64)
+end
+
+local sum = 0
+local entries = redis.call('HGETALL', key)
+for i = 1, #entries, 2 do
+    if tonumber(entries[i]) < oldest_bucket then
+        redis.call('HDEL', key, entries[i])
+    else
+        sum = sum + tonumber(entries[i + 1])
+    end
+end
+redis.call('EXPIRE', key, bucket_secs * (bucket_count + 1))
+
+if sum > MAX_I64 then
+    return MAX_I64
+end
+return sum
@@ -0,0 +1,14 @@
+-- Copyright (c) 2025 IOTA Stiftung
+-- SPDX-License-Identifier: Apache-2.0
+
+local sponsor_address = ARGV[1]
+local id = ARGV[2]
+
+local key = sponsor_address .. ':pending_confirmations'
+
+local data = redis.call('HGET', key, id)
+if not data then
+    return nil
+end
+redis.call('HDEL', key, id)
+return data
@@ -1,45 +1,68 @@
 // Copyright (c) 2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
 use async_trait::async_trait;
-use fastcrypto::hash::*;
 
 use anyhow::Result;
-use iota_types::base_types::IotaAddress;
-use itertools::Itertools;
-use redis::aio::ConnectionManager;
 use script_manager::ScriptManager;
 use serde_json::Value;
-use serde_json_canonicalizer::to_string;
 
-use crate::config::GasStationStorageConfig;
+use crate::metrics::RedisConnectionMetrics;
+use crate::redis_connection::{connect_redis, RedisConnection, RedisMode};
 
-use super::{Aggregate, AggregateType, StatsTrackerStorage};
+use super::{
+    aggr_key, sliding_window_buckets, Aggregate, AggregateType, AggregateValue, StatsTrackerStorage,
+};
 
 mod script_manager;
 
 #[derive(Clone)]
 pub struct RedisStatsTrackerStorage {
-    conn_manager: ConnectionManager,
-    // String format of the sponsor address to avoid converting it to string multiple times.
+    conn_manager: RedisConnection,
+    // Prefix of the keys, the sponsor address preceded by the namespace if any. It is also passed
+    // as the key of the scripts, so that a Redis Cluster routes them to the node holding the keys.
     pub sponsor_key: String,
 }
 
 impl RedisStatsTrackerStorage {
-    pub async fn new(redis_url: impl AsRef<str>, sponsor: impl AsRef<str>) -> Self {
-        let client = redis::Client::open(redis_url.as_ref()).unwrap();
-        let conn_manager = ConnectionManager::new(client).await.unwrap();
+    pub async fn new(
+        redis_url: impl AsRef<str>,
+        mode: &RedisMode,
+        sponsor: impl AsRef<str>,
+        metrics: RedisConnectionMetrics,
+    ) -> Self {
+        let conn_manager = connect_redis(redis_url.as_ref(), mode, metrics)
+            .await
+            .unwrap();
         Self {
             conn_manager,
-            sponsor_key: sponsor.as_ref().to_string(),
+            sponsor_key: mode.key_prefix(sponsor.as_ref().to_string()),
         }
     }
 
+    fn pending_confirmations_key(&self) -> String {
+        format!("{}:pending_confirmations", self.sponsor_key)
+    }
+
+    fn held_transactions_key(&self) -> String {
+        format!("{}:held_transactions", self.sponsor_key)
+    }
+
     #[cfg(test)]
     pub async fn new_localhost() -> RedisStatsTrackerStorage {
+        use crate::metrics::StorageMetrics;
         use crate::test_env::random_address;
         let sponsor_key = random_address().to_string();
-        Self::new("redis://127.0.0.1:6379", sponsor_key).await
+        let metrics = StorageMetrics::new_for_testing().redis_connection_metrics("stats");
+        Self::new(
+            "redis://127.0.0.1:6379",
+            &RedisMode::Single,
+            sponsor_key,
+            metrics,
+        )
+        .await
     }
 }
 
@@ -51,14 +74,14 @@ impl StatsTrackerStorage for RedisStatsTrackerStorage {
         aggr: &Aggregate,
         value: i64,
     ) -> Result<i64> {
-        let hash = generate_hash_from_key(key);
-        let key = format!("{}:{}:{}", aggr.name, aggr.aggr_type, hash);
+        let key = aggr_key(key, aggr);
 
         match aggr.aggr_type {
             AggregateType::Sum => {
                 let script = ScriptManager::increment_aggr_sum_script();
                 let mut conn = self.conn_manager.clone();
                 let new_value: i64 = script
+                    .key(&self.sponsor_key)
                     .arg(self.sponsor_key.to_string())
                     .arg(key)
                     .arg(value)
@@ -67,44 +90,171 @@ impl StatsTrackerStorage for RedisStatsTrackerStorage {
                     .await?;
                 Ok(new_value)
             }
+            AggregateType::SlidingSum => {
+                let (bucket_secs, bucket_count) = sliding_window_buckets(aggr.window);
+                let script = ScriptManager::increment_aggr_sliding_sum_script();
+                let mut conn = self.conn_manager.clone();
+                let new_value: i64 = script
+                    .key(&self.sponsor_key)
+                    .arg(self.sponsor_key.to_string())
+                    .arg(key)
+                    .arg(value)
+                    .arg(bucket_secs)
+                    .arg(bucket_count)
+                    .invoke_async(&mut conn)
+                    .await?;
+                Ok(new_value)
+            }
         }
     }
-}
 
-// we should generate the canonical hash key from the given key
-fn generate_hash_from_key<'a>(key: &[(String, Value)]) -> String {
-    let mut hash_key = String::new();
-    for (k, v) in key.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
-        hash_key.push_str(&k);
-        hash_key.push_str(&to_string(&v).unwrap());
+    async fn get_aggr_values(
+        &self,
+        key_metas: &[Vec<(String, Value)>],
+        aggr: &Aggregate,
+    ) -> Result<Vec<i64>> {
+        if key_metas.is_empty() {
+            return Ok(vec![]);
+        }
+        if let AggregateType::SlidingSum = aggr.aggr_type {
+            let mut values = Vec::with_capacity(key_metas.len());
+            for key in key_metas {
+                values.push(self.get_aggr(key, aggr).await?.value);
+            }
+            return Ok(values);
+        }
+        let keys = key_metas
+            .iter()
+            .map(|key| format!("{}:{}", self.sponsor_key, aggr_key(key, aggr)))
+            .collect::<Vec<_>>();
+        let mut conn = self.conn_manager.clone();
+        let values: Vec<Option<i64>> = redis::cmd("MGET").arg(keys).query_async(&mut conn).await?;
+        Ok(values
+            .into_iter()
+            .map(|value| value.unwrap_or_default())
+            .collect())
     }
 
-    let mut hasher = Sha256::default();
-    hasher.update(hash_key.as_bytes());
-    hasher.finalize().to_string()
-}
+    async fn get_aggr(&self, key: &[(String, Value)], aggr: &Aggregate) -> Result<AggregateValue> {
+        let key = aggr_key(key, aggr);
+        let mut conn = self.conn_manager.clone();
+        let (value, ttl): (i64, i64) = match aggr.aggr_type {
+            AggregateType::Sum => {
+                ScriptManager::get_aggr_script()
+                    .key(&self.sponsor_key)
+                    .arg(self.sponsor_key.to_string())
+                    .arg(key)
+                    .invoke_async(&mut conn)
+                    .await?
+            }
+            AggregateType::SlidingSum => {
+                let (bucket_secs, bucket_count) = sliding_window_buckets(aggr.window);
+                ScriptManager::get_aggr_sliding_sum_script()
+                    .key(&self.sponsor_key)
+                    .arg(self.sponsor_key.to_string())
+                    .arg(key)
+                    .arg(bucket_secs)
+                    .arg(bucket_count)
+                    .invoke_async(&mut conn)
+                    .await?
+            }
+        };
+        Ok(AggregateValue {
+            value,
+            // TTL is negative if the key doesn't exist or has no expiry.
+            resets_in: (ttl >= 0).then(|| Duration::from_secs(ttl as u64)),
+        })
+    }
 
-pub async fn connect_stats_storage(
-    config: &GasStationStorageConfig,
-    sponsor_address: IotaAddress,
-) -> RedisStatsTrackerStorage {
-    let storage = match config {
-        GasStationStorageConfig::Redis { redis_url } => {
-            RedisStatsTrackerStorage::new(redis_url, sponsor_address.to_string()).await
-        }
-    };
+    async fn reset_aggr(&self, key: &[(String, Value)], aggr: &Aggregate) -> Result<()> {
+        let key = format!("{}:{}", self.sponsor_key, aggr_key(key, aggr));
+        let mut conn = self.conn_manager.clone();
+        redis::cmd("DEL").arg(key).query_async(&mut conn).await?;
+        Ok(())
+    }
+
+    async fn put_pending_confirmation(&self, id: &str, data: &str) -> Result<()> {
+        let mut conn = self.conn_manager.clone();
+        redis::cmd("HSET")
+            .arg(self.pending_confirmations_key())
+            .arg(id)
+            .arg(data)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn take_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        let script = ScriptManager::take_pending_confirmation_script();
+        let mut conn = self.conn_manager.clone();
+        let data: Option<String> = script
+            .key(&self.sponsor_key)
+            .arg(self.sponsor_key.to_string())
+            .arg(id)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(data)
+    }
 
-    storage
+    async fn get_pending_confirmation(&self, id: &str) -> Result<Option<String>> {
+        let mut conn = self.conn_manager.clone();
+        let data: Option<String> = redis::cmd("HGET")
+            .arg(self.pending_confirmations_key())
+            .arg(id)
+            .query_async(&mut conn)
+            .await?;
+        Ok(data)
+    }
+
+    async fn get_pending_confirmations(&self) -> Result<Vec<(String, String)>> {
+        let mut conn = self.conn_manager.clone();
+        let confirmations: Vec<(String, String)> = redis::cmd("HGETALL")
+            .arg(self.pending_confirmations_key())
+            .query_async(&mut conn)
+            .await?;
+        Ok(confirmations)
+    }
+
+    async fn put_held_transaction(&self, id: &str, data: &str) -> Result<()> {
+        let mut conn = self.conn_manager.clone();
+        redis::cmd("HSET")
+            .arg(self.held_transactions_key())
+            .arg(id)
+            .arg(data)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn take_held_transaction(&self, id: &str) -> Result<Option<String>> {
+        let script = ScriptManager::take_held_transaction_script();
+        let mut conn = self.conn_manager.clone();
+        let data: Option<String> = script
+            .key(&self.sponsor_key)
+            .arg(self.sponsor_key.to_string())
+            .arg(id)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(data)
+    }
+
+    async fn get_held_transactions(&self) -> Result<Vec<(String, String)>> {
+        let mut conn = self.conn_manager.clone();
+        let transactions: Vec<(String, String)> = redis::cmd("HGETALL")
+            .arg(self.held_transactions_key())
+            .query_async(&mut conn)
+            .await?;
+        Ok(transactions)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use std::time::Duration;
-
     use serde_json::json;
     use tokio::time;
 
     use super::*;
+    use crate::tracker::stats_tracker_storage::generate_hash_from_key;
 
     #[tokio::test]
     async fn update_aggr() {
@@ -137,6 +287,31 @@ mod test {
             .unwrap();
         assert_eq!(result, 3);
 
+        let values = storage
+            .get_aggr_values(&[key_meta.clone(), vec![]], &aggregate)
+            .await
+            .unwrap();
+        assert_eq!(values, vec![3, 0]);
+
+        let value = storage.get_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(value.value, 3);
+        assert!(value.resets_in.unwrap() <= window_size);
+        assert_eq!(
+            storage.get_aggr(&[], &aggregate).await.unwrap(),
+            AggregateValue::default()
+        );
+
+        storage.reset_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(
+            storage.get_aggr(&key_meta, &aggregate).await.unwrap(),
+            AggregateValue::default()
+        );
+        let result = storage
+            .update_aggr(&key_meta, &aggregate, 3)
+            .await
+            .unwrap();
+        assert_eq!(result, 3);
+
         time::sleep(window_size + Duration::from_secs(1)).await;
         let result = storage
             .update_aggr(&key_meta, &aggregate, 2)
@@ -145,6 +320,75 @@ mod test {
         assert_eq!(result, 2);
     }
 
+    #[tokio::test]
+    async fn update_aggr_sliding_sum() {
+        let storage = RedisStatsTrackerStorage::new_localhost().await;
+        // Split into buckets of one second
+        let aggregate = Aggregate::with_name("gas_usage")
+            .with_aggr_type(AggregateType::SlidingSum)
+            .with_window(Duration::from_secs(6));
+        let key_meta = vec![("sender_address".to_string(), json!("0x1234567890abcdef"))];
+
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 1).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 2).await.unwrap(),
+            3
+        );
+
+        time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 4).await.unwrap(),
+            7
+        );
+        let value = storage.get_aggr(&key_meta, &aggregate).await.unwrap();
+        assert_eq!(value.value, 7);
+        assert!(value.resets_in.unwrap() <= Duration::from_secs(3));
+
+        // Only the first bucket left the window
+        time::sleep(Duration::from_secs(4)).await;
+        let values = storage
+            .get_aggr_values(&[key_meta.clone(), vec![]], &aggregate)
+            .await
+            .unwrap();
+        assert_eq!(values, vec![4, 0]);
+        assert_eq!(
+            storage.update_aggr(&key_meta, &aggregate, 1).await.unwrap(),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn pending_confirmations() {
+        let storage = RedisStatsTrackerStorage::new_localhost().await;
+        storage.put_pending_confirmation("a", "1").await.unwrap();
+        storage.put_pending_confirmation("b", "2").await.unwrap();
+        storage.put_pending_confirmation("b", "3").await.unwrap();
+        assert_eq!(
+            storage.get_pending_confirmation("b").await.unwrap(),
+            Some("3".to_string())
+        );
+
+        let mut confirmations = storage.get_pending_confirmations().await.unwrap();
+        confirmations.sort();
+        assert_eq!(
+            confirmations,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "3".to_string())
+            ]
+        );
+
+        assert_eq!(
+            storage.take_pending_confirmation("a").await.unwrap(),
+            Some("1".to_string())
+        );
+        assert_eq!(storage.take_pending_confirmation("a").await.unwrap(), None);
+        assert_eq!(storage.get_pending_confirmations().await.unwrap().len(), 1);
+    }
+
     #[test]
     fn test_calculate_hash_map() {
         let map_data = json!({
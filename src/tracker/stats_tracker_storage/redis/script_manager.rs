@@ -5,6 +5,13 @@ use once_cell::sync::Lazy;
 use redis::Script;
 
 const RESERVE_GAS_COINS_SCRIPT: &str = include_str!("lua_scripts/aggr_increment_sum.lua");
+const GET_AGGR_SCRIPT: &str = include_str!("lua_scripts/aggr_get.lua");
+const INCREMENT_AGGR_SLIDING_SUM_SCRIPT: &str =
+    include_str!("lua_scripts/aggr_increment_sliding_sum.lua");
+const GET_AGGR_SLIDING_SUM_SCRIPT: &str = include_str!("lua_scripts/aggr_get_sliding_sum.lua");
+const TAKE_PENDING_CONFIRMATION_SCRIPT: &str =
+    include_str!("lua_scripts/take_pending_confirmation.lua");
+const TAKE_HELD_TRANSACTION_SCRIPT: &str = include_str!("lua_scripts/take_held_transaction.lua");
 
 pub struct ScriptManager;
 
@@ -13,4 +20,29 @@ impl ScriptManager {
         static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(RESERVE_GAS_COINS_SCRIPT));
         Lazy::force(&SCRIPT)
     }
+
+    pub fn get_aggr_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_AGGR_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn increment_aggr_sliding_sum_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(INCREMENT_AGGR_SLIDING_SUM_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn get_aggr_sliding_sum_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(GET_AGGR_SLIDING_SUM_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn take_pending_confirmation_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(TAKE_PENDING_CONFIRMATION_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
+
+    pub fn take_held_transaction_script() -> &'static Script {
+        static SCRIPT: Lazy<Script> = Lazy::new(|| Script::new(TAKE_HELD_TRANSACTION_SCRIPT));
+        Lazy::force(&SCRIPT)
+    }
 }
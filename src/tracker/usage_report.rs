@@ -0,0 +1,180 @@
+//  Copyright (c) 2025 IOTA Stiftung
+//  SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use iota_types::base_types::IotaAddress;
+use serde_json::Value;
+use tracing::error;
+
+use super::stats_tracker_storage::{Aggregate, AggregateType};
+use super::StatsTracker;
+
+/// Gas usage is recorded in buckets of this length, so reports are accurate to the hour.
+const BUCKET: Duration = Duration::from_secs(60 * 60);
+/// Longest window a usage report can cover.
+pub const MAX_USAGE_REPORT_WINDOW: Duration = Duration::from_secs(60 * 60 * 24 * 31);
+const GAS_USED_AGGREGATE_NAME: &str = "gas_used";
+const TRANSACTION_COUNT_AGGREGATE_NAME: &str = "sponsored_transaction_count";
+
+/// Who the gas was spent by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageScope {
+    Sender(IotaAddress),
+    Package(IotaAddress),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasUsage {
+    pub gas_used: u64,
+    pub transaction_count: u64,
+}
+
+impl StatsTracker {
+    /// Records the gas actually used by an executed transaction, for its sender and for every
+    /// Move package it calls. Failures are only logged, they must not fail the transaction.
+    pub async fn record_gas_usage(
+        &self,
+        sender: IotaAddress,
+        packages: &[IotaAddress],
+        gas_used: u64,
+    ) {
+        let mut packages = packages.to_vec();
+        packages.sort();
+        packages.dedup();
+        let scopes = std::iter::once(UsageScope::Sender(sender))
+            .chain(packages.into_iter().map(UsageScope::Package));
+
+        let bucket = current_bucket();
+        for scope in scopes {
+            let key_meta = bucket_key(scope, bucket);
+            let results = [
+                (gas_used_aggregate(), gas_used as i64),
+                (transaction_count_aggregate(), 1),
+            ];
+            for (aggregate, value) in results {
+                if let Err(err) = self.update_aggr(key_meta.clone(), &aggregate, value).await {
+                    error!(
+                        "Failed to record {} of {:?}: {:?}",
+                        aggregate.name, scope, err
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the gas used within the last `window`, rounded up to whole hours.
+    pub async fn get_gas_usage(&self, scope: UsageScope, window: Duration) -> Result<GasUsage> {
+        if window > MAX_USAGE_REPORT_WINDOW {
+            bail!(
+                "Window must not exceed {}",
+                humantime::format_duration(MAX_USAGE_REPORT_WINDOW)
+            );
+        }
+        let last_bucket = current_bucket();
+        let first_bucket = last_bucket.saturating_sub(window.as_secs() / BUCKET.as_secs());
+        let key_metas = (first_bucket..=last_bucket)
+            .map(|bucket| bucket_key(scope, bucket))
+            .collect::<Vec<_>>();
+
+        let gas_used = self
+            .store
+            .get_aggr_values(&key_metas, &gas_used_aggregate())
+            .await?;
+        let transaction_count = self
+            .store
+            .get_aggr_values(&key_metas, &transaction_count_aggregate())
+            .await?;
+        Ok(GasUsage {
+            gas_used: gas_used.into_iter().sum::<i64>().max(0) as u64,
+            transaction_count: transaction_count.into_iter().sum::<i64>().max(0) as u64,
+        })
+    }
+}
+
+fn current_bucket() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / BUCKET.as_secs()
+}
+
+fn bucket_key(scope: UsageScope, bucket: u64) -> Vec<(String, Value)> {
+    let (name, address) = match scope {
+        UsageScope::Sender(address) => ("sender_address", address),
+        UsageScope::Package(address) => ("package_address", address),
+    };
+    vec![
+        (name.to_string(), Value::String(address.to_string())),
+        ("bucket".to_string(), Value::from(bucket)),
+    ]
+}
+
+fn gas_used_aggregate() -> Aggregate {
+    usage_aggregate(GAS_USED_AGGREGATE_NAME)
+}
+
+fn transaction_count_aggregate() -> Aggregate {
+    usage_aggregate(TRANSACTION_COUNT_AGGREGATE_NAME)
+}
+
+fn usage_aggregate(name: &str) -> Aggregate {
+    // Each bucket is kept as long as it can be part of a report.
+    Aggregate::with_name(name)
+        .with_aggr_type(AggregateType::Sum)
+        .with_window(MAX_USAGE_REPORT_WINDOW + BUCKET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{new_stats_tracker_for_testing, random_address};
+
+    #[tokio::test]
+    async fn test_gas_usage_report() {
+        let stats_tracker = new_stats_tracker_for_testing(random_address()).await;
+        let sender = random_address();
+        let package = random_address();
+        let window = Duration::from_secs(60 * 60 * 24);
+
+        stats_tracker
+            .record_gas_usage(sender, &[package, package], 100)
+            .await;
+        stats_tracker.record_gas_usage(sender, &[], 50).await;
+
+        let usage = stats_tracker
+            .get_gas_usage(UsageScope::Sender(sender), window)
+            .await
+            .unwrap();
+        assert_eq!(
+            usage,
+            GasUsage {
+                gas_used: 150,
+                transaction_count: 2
+            }
+        );
+        let usage = stats_tracker
+            .get_gas_usage(UsageScope::Package(package), window)
+            .await
+            .unwrap();
+        assert_eq!(
+            usage,
+            GasUsage {
+                gas_used: 100,
+                transaction_count: 1
+            }
+        );
+        let usage = stats_tracker
+            .get_gas_usage(UsageScope::Sender(random_address()), window)
+            .await
+            .unwrap();
+        assert_eq!(usage.gas_used, 0);
+
+        assert!(stats_tracker
+            .get_gas_usage(UsageScope::Sender(sender), MAX_USAGE_REPORT_WINDOW + BUCKET)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays the transactions of the transaction log through a candidate access controller, to find
+//! the transactions executed so far that new rules would deny or hold before deploying them.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use axum::http::HeaderMap;
+use fastcrypto::encoding::Base64;
+use iota_types::base_types::IotaAddress;
+use iota_types::digests::TransactionDigest;
+use serde_json::Value;
+
+use crate::access_controller::decision::Decision;
+use crate::access_controller::rule::TransactionContext;
+use crate::access_controller::AccessController;
+use crate::iota_client::IotaClient;
+use crate::logging::FileTxLogLine;
+use crate::tracker::stats_tracker_storage::in_memory::InMemoryStatsTrackerStorage;
+use crate::tracker::StatsTracker;
+
+/// Number of transactions fetched from the fullnode per request.
+const FETCH_BATCH_SIZE: usize = 50;
+
+/// A transaction executed by the Gas Station, read from the transaction log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxLogEntry {
+    pub digest: TransactionDigest,
+    /// Decision of the access controller at the time. Only the file sink records it, the
+    /// transactions of the tracing target were allowed or approved.
+    pub decision: Option<Decision>,
+}
+
+/// A transaction the candidate rules decide differently about.
+#[derive(Debug)]
+pub struct ReplayChange {
+    pub digest: TransactionDigest,
+    pub sender: IotaAddress,
+    pub recorded: Option<Decision>,
+    pub decision: Decision,
+    /// The rule that made the new decision, `None` for the access policy.
+    pub matched_rule: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub num_replayed: usize,
+    /// Transactions the fullnode doesn't know, e.g. pruned.
+    pub num_missing: usize,
+    pub num_errors: usize,
+    /// Number of transactions per new decision.
+    pub decisions: BTreeMap<String, usize>,
+    /// Transactions that would now be denied or held.
+    pub changes: Vec<ReplayChange>,
+}
+
+/// Reads the transaction log written by the file sink, one JSON object per line, or by the
+/// `transactions` tracing target. The lines that don't describe a transaction are skipped.
+pub fn read_tx_log(path: &Path) -> anyhow::Result<Vec<TxLogEntry>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(content.lines().filter_map(parse_tx_log_line).collect())
+}
+
+fn parse_tx_log_line(line: &str) -> Option<TxLogEntry> {
+    if let Ok(line) = serde_json::from_str::<FileTxLogLine>(line) {
+        return Some(TxLogEntry {
+            digest: line.digest,
+            decision: Some(line.decision),
+        });
+    }
+    // The tracing target logs a `TxLogMessage` with the effects as details, possibly wrapped in a
+    // JSON formatted tracing event.
+    let message = line.find("{\"timestamp\"").and_then(|start| {
+        serde_json::Deserializer::from_str(&line[start..])
+            .into_iter::<Value>()
+            .next()?
+            .ok()
+    });
+    let digest = message.as_ref().and_then(|message| {
+        message
+            .pointer("/details/transactionDigest")?
+            .as_str()?
+            .parse()
+            .ok()
+    });
+    if let Some(digest) = digest {
+        return Some(TxLogEntry {
+            digest,
+            decision: None,
+        });
+    }
+    let event: Value = serde_json::from_str(line).ok()?;
+    parse_tx_log_line(event.pointer("/fields/message")?.as_str()?)
+}
+
+/// Fetches the logged transactions from the fullnode and checks them with `access_controller`, in
+/// the order of the log, with the stats kept in memory. The hooks are called, the notifications
+/// are not sent.
+pub async fn replay_tx_log(
+    iota_client: &IotaClient,
+    mut access_controller: AccessController,
+    entries: &[TxLogEntry],
+) -> anyhow::Result<ReplayReport> {
+    access_controller.disable_notifications();
+    access_controller.initialize().await?;
+    let stats_tracker = StatsTracker::new(Arc::new(InMemoryStatsTrackerStorage::new()));
+    // The current price, the gas price ratios of old transactions may differ slightly.
+    let reference_gas_price = iota_client.get_reference_gas_price().await?;
+
+    let mut report = ReplayReport::default();
+    for batch in entries.chunks(FETCH_BATCH_SIZE) {
+        let mut transactions = iota_client
+            .get_sender_signed_transactions(batch.iter().map(|entry| entry.digest).collect())
+            .await?;
+        for entry in batch {
+            let Some(signed_data) = transactions.remove(&entry.digest) else {
+                report.num_missing += 1;
+                continue;
+            };
+            let tx_data = signed_data.transaction_data();
+            // The station submits the signature of the sponsor first.
+            let Some(user_sig) = signed_data.tx_signatures().last() else {
+                report.num_errors += 1;
+                continue;
+            };
+            let ctx = TransactionContext::new(
+                user_sig,
+                tx_data,
+                stats_tracker.clone(),
+                0,
+                Base64::from_bytes(&bcs::to_bytes(tx_data)?),
+                Base64::from_bytes(user_sig.as_ref()),
+                None,
+                HeaderMap::new(),
+            )
+            .with_reference_gas_price(reference_gas_price);
+            let ctx = if !ctx.coin_object_ids.is_empty() && access_controller.uses_coin_types() {
+                let object_types = iota_client
+                    .get_object_types(ctx.coin_object_ids.clone())
+                    .await?;
+                ctx.with_coin_object_types(&object_types)
+            } else {
+                ctx
+            };
+
+            report.num_replayed += 1;
+            let access_decision = match access_controller.check_access_with_details(&ctx).await {
+                Ok(access_decision) => access_decision,
+                Err(_) => {
+                    report.num_errors += 1;
+                    continue;
+                }
+            };
+            let decision = access_decision.decision;
+            *report
+                .decisions
+                .entry(format!("{:?}", decision).to_lowercase())
+                .or_insert(0) += 1;
+            if decision != Decision::Allow && entry.decision.as_ref() != Some(&decision) {
+                report.changes.push(ReplayChange {
+                    digest: entry.digest,
+                    sender: ctx.sender_address,
+                    recorded: entry.decision.clone(),
+                    decision,
+                    matched_rule: access_decision.matched_rule,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+impl ReplayReport {
+    pub fn print(&self) {
+        println!(
+            "Replayed transactions: {} (not found: {}, errors: {})",
+            self.num_replayed, self.num_missing, self.num_errors
+        );
+        for (decision, count) in &self.decisions {
+            println!(" - {}: {}", decision, count);
+        }
+        println!(
+            "Transactions the new rules would deny or hold: {}",
+            self.changes.len()
+        );
+        for change in &self.changes {
+            println!(
+                " - {} from {}: {:?} by {}{}",
+                change.digest,
+                change.sender,
+                change.decision,
+                change
+                    .matched_rule
+                    .as_deref()
+                    .unwrap_or("the access policy"),
+                change
+                    .recorded
+                    .as_ref()
+                    .map(|recorded| format!(", was {:?}", recorded))
+                    .unwrap_or_default()
+            );
+        }
+    }
+
+    /// Fails if any transaction would now be denied or held, e.g. to gate a policy change in CI.
+    pub fn check_no_regression(&self) -> anyhow::Result<()> {
+        if !self.changes.is_empty() {
+            return Err(anyhow!(
+                "{} logged transactions would be denied or held by the new rules",
+                self.changes.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tx_log_line() {
+        let digest = TransactionDigest::random();
+        let file_line = format!(
+            r#"{{"timestamp":"2025-01-01T00:00:00Z","digest":"{}","sender":"{}","sponsor":"{}","gasUsed":1000,"success":true,"decision":"Hold"}}"#,
+            digest,
+            IotaAddress::ZERO,
+            IotaAddress::ZERO
+        );
+        assert_eq!(
+            parse_tx_log_line(&file_line),
+            Some(TxLogEntry {
+                digest,
+                decision: Some(Decision::Hold)
+            })
+        );
+
+        let message = format!(
+            r#"{{"timestamp":1735689600,"level":"trace","host":"station","message":"transaction data","details":{{"transactionDigest":"{}"}}}}"#,
+            digest
+        );
+        let expected = Some(TxLogEntry {
+            digest,
+            decision: None,
+        });
+        assert_eq!(
+            parse_tx_log_line(&format!(
+                "2025-01-01T00:00:00Z TRACE transactions: {}",
+                message
+            )),
+            expected
+        );
+        let event = format!(
+            r#"{{"timestamp":"2025-01-01T00:00:00Z","level":"TRACE","fields":{{"message":{}}},"target":"transactions"}}"#,
+            serde_json::to_string(&message).unwrap()
+        );
+        assert_eq!(parse_tx_log_line(&event), expected);
+
+        assert_eq!(parse_tx_log_line("2025-01-01T00:00:00Z INFO started"), None);
+    }
+}
@@ -1,18 +1,47 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::anyhow;
+use crate::config::{MultisigSignerConfig, SidecarLoadBalancing, SidecarSignerConfig};
+use crate::retry_with_max_attempts;
+use anyhow::{anyhow, bail, Context};
 use fastcrypto::encoding::{Base64, Encoding};
-use reqwest::Client;
-use serde::Deserialize;
+use fastcrypto::hash::{Blake2b256, HashFunction, Sha256};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use gcp_auth::TokenProvider;
+use iota_types::base_types::IotaAddress;
+use iota_types::crypto::{IotaKeyPair, PublicKey, Signature, SignatureScheme, ToFromBytes};
+use iota_types::multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit};
+use iota_types::signature::GenericSignature;
+use iota_types::transaction::TransactionData;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Certificate, Client, Identity, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use shared_crypto::intent::{Intent, IntentMessage};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use iota_types::base_types::IotaAddress;
-use iota_types::crypto::{Signature, IotaKeyPair};
-use iota_types::signature::GenericSignature;
-use iota_types::transaction::TransactionData;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+const GCP_KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1";
+const GCP_KMS_SCOPES: &[&str] = &["https://www.googleapis.com/auth/cloudkms"];
+const GCP_KMS_SECP256K1_ALGORITHM: &str = "EC_SIGN_SECP256K1_SHA256";
+const GCP_KMS_MAX_ATTEMPTS: usize = 5;
+/// Interval of the probes of GCP KMS once a signing request failed.
+const GCP_KMS_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Order of the secp256k1 curve, big-endian.
+const SECP256K1_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+/// Half of the order of the secp256k1 curve, rounded down, big-endian.
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
 
 #[async_trait::async_trait]
 pub trait TxSigner: Send + Sync {
@@ -22,6 +51,47 @@ pub trait TxSigner: Send + Sync {
     fn is_valid_address(&self, address: &IotaAddress) -> bool {
         self.get_address() == *address
     }
+    /// Signs a transaction allowed by the access controller. The backends able to check the
+    /// access decision receive it with the transaction.
+    async fn sign_transaction_with_context(
+        &self,
+        tx_data: &TransactionData,
+        _context: &SigningContext,
+    ) -> anyhow::Result<GenericSignature> {
+        self.sign_transaction(tx_data).await
+    }
+    /// Whether the backend refuses to sign the transactions of users without an attested access
+    /// decision.
+    fn requires_attestation(&self) -> bool {
+        false
+    }
+    /// Public key of the sponsor, when the backend knows it.
+    fn public_key(&self) -> Option<PublicKey> {
+        None
+    }
+    /// Name of the signing backend, used as the label of the signer metrics.
+    fn backend(&self) -> &'static str;
+    /// Fails when the backend could not be reached recently. It doesn't send any request, so
+    /// that it can back frequent readiness probes.
+    fn check_reachability(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    /// Signers this signer combines the signatures of, e.g. the members of a multisig.
+    fn members(&self) -> Vec<Arc<dyn TxSigner>> {
+        vec![]
+    }
+}
+
+/// Access decision of a transaction, sent to the sidecar signer with the transaction, so that a
+/// compromised station can't get transactions signed without the approval of the policy.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningContext {
+    /// Base58 encoded.
+    pub transaction_digest: String,
+    pub matched_rule: Option<String>,
+    /// Given by the hook that allowed the transaction, to be verified by the sidecar.
+    pub attestation: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -36,31 +106,227 @@ struct IotaAddressResponse {
     iota_pubkey_address: IotaAddress,
 }
 
+struct SidecarEndpoint {
+    url: String,
+    healthy: AtomicBool,
+}
+
+impl SidecarEndpoint {
+    fn set_healthy(&self, healthy: bool) {
+        if self.healthy.swap(healthy, Ordering::Relaxed) != healthy {
+            if healthy {
+                info!("Sidecar {} is healthy again", self.url);
+            } else {
+                warn!("Sidecar {} is marked as unhealthy", self.url);
+            }
+        }
+    }
+}
+
+/// Signs transactions with one or more sidecars holding the same key. Sidecars that fail a
+/// request or a periodic health check are skipped until they recover, and are only used as a
+/// last resort.
 pub struct SidecarTxSigner {
-    sidecar_url: String,
+    endpoints: Arc<Vec<SidecarEndpoint>>,
+    load_balancing: SidecarLoadBalancing,
+    next_endpoint: AtomicUsize,
     client: Client,
+    max_attempts: usize,
+    require_attestation: bool,
     iota_address: IotaAddress,
+    health_check_task: JoinHandle<()>,
 }
 
 impl SidecarTxSigner {
-    pub async fn new(sidecar_url: String) -> Arc<Self> {
-        let client = Client::new();
-        let resp = client
-            .get(format!("{}/{}", sidecar_url, "get-pubkey-address"))
-            .send()
-            .await
-            .unwrap_or_else(|err| panic!("Failed to get pubkey address: {}", err));
-        let iota_address = resp
-            .json::<IotaAddressResponse>()
-            .await
-            .unwrap_or_else(|err| panic!("Failed to parse address response: {}", err))
-            .iota_pubkey_address;
+    pub async fn new(config: SidecarSignerConfig) -> Arc<Self> {
+        let client = new_sidecar_client(&config)
+            .unwrap_or_else(|err| panic!("Failed to create sidecar client: {:?}", err));
+        let endpoints: Arc<Vec<_>> = Arc::new(
+            config
+                .sidecar_urls()
+                .into_iter()
+                .map(|url| SidecarEndpoint {
+                    url,
+                    healthy: AtomicBool::new(true),
+                })
+                .collect(),
+        );
+        let iota_address = retry_with_max_attempts!(
+            Self::get_pubkey_address_from_any(&client, &endpoints),
+            config.max_attempts
+        )
+        .unwrap_or_else(|err| panic!("Failed to get pubkey address: {}", err));
+        let health_check_task = tokio::spawn(Self::run_health_checks(
+            client.clone(),
+            endpoints.clone(),
+            iota_address,
+            Duration::from_secs(config.health_check_interval_sec),
+        ));
         Arc::new(Self {
-            sidecar_url,
+            endpoints,
+            load_balancing: config.load_balancing,
+            next_endpoint: AtomicUsize::new(0),
             client,
+            max_attempts: config.max_attempts,
+            require_attestation: config.require_attestation,
             iota_address,
+            health_check_task,
         })
     }
+
+    async fn get_pubkey_address(client: &Client, sidecar_url: &str) -> anyhow::Result<IotaAddress> {
+        let resp = client
+            .get(format!("{}/{}", sidecar_url, "get-pubkey-address"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<IotaAddressResponse>()
+            .await?;
+        Ok(resp.iota_pubkey_address)
+    }
+
+    async fn get_pubkey_address_from_any(
+        client: &Client,
+        endpoints: &[SidecarEndpoint],
+    ) -> anyhow::Result<IotaAddress> {
+        let mut last_err = anyhow!("No sidecar configured");
+        for endpoint in endpoints {
+            match Self::get_pubkey_address(client, &endpoint.url).await {
+                Ok(address) => return Ok(address),
+                Err(err) => {
+                    warn!(
+                        "Failed to get pubkey address from {}: {:?}",
+                        endpoint.url, err
+                    );
+                    endpoint.set_healthy(false);
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// Periodically checks that every sidecar is reachable and signs with the sponsor key.
+    async fn run_health_checks(
+        client: Client,
+        endpoints: Arc<Vec<SidecarEndpoint>>,
+        iota_address: IotaAddress,
+        interval: Duration,
+    ) {
+        loop {
+            tokio::time::sleep(interval).await;
+            for endpoint in endpoints.iter() {
+                let healthy = match Self::get_pubkey_address(&client, &endpoint.url).await {
+                    Ok(address) if address == iota_address => true,
+                    Ok(address) => {
+                        error!(
+                            "Sidecar {} signs for {} instead of the sponsor address {}",
+                            endpoint.url, address, iota_address
+                        );
+                        false
+                    }
+                    Err(err) => {
+                        debug!("Health check of sidecar {} failed: {:?}", endpoint.url, err);
+                        false
+                    }
+                };
+                endpoint.set_healthy(healthy);
+            }
+        }
+    }
+
+    async fn request_signature(
+        &self,
+        sidecar_url: &str,
+        tx_bytes: &str,
+        context: Option<&SigningContext>,
+    ) -> anyhow::Result<SignatureResponse> {
+        let mut body = json!({"txBytes": tx_bytes});
+        if let Some(context) = context {
+            body["context"] = json!(context);
+        }
+        let resp = self
+            .client
+            .post(format!("{}/{}", sidecar_url, "sign-transaction"))
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SignatureResponse>()
+            .await?;
+        Ok(resp)
+    }
+
+    /// Tries every sidecar once, in the order given by the load balancing strategy.
+    async fn request_signature_from_any(
+        &self,
+        tx_bytes: &str,
+        context: Option<&SigningContext>,
+    ) -> anyhow::Result<SignatureResponse> {
+        let start = match self.load_balancing {
+            SidecarLoadBalancing::Failover => 0,
+            SidecarLoadBalancing::RoundRobin => self.next_endpoint.fetch_add(1, Ordering::Relaxed),
+        };
+        let healthy: Vec<_> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.healthy.load(Ordering::Relaxed))
+            .collect();
+        let mut last_err = anyhow!("No sidecar configured");
+        for index in candidate_order(start, &healthy) {
+            let endpoint = &self.endpoints[index];
+            match self
+                .request_signature(&endpoint.url, tx_bytes, context)
+                .await
+            {
+                Ok(resp) => {
+                    endpoint.set_healthy(true);
+                    return Ok(resp);
+                }
+                // The sidecar refused to sign the transaction, e.g. without a valid attestation,
+                // which the other sidecars would refuse as well.
+                Err(err) if is_refusal(&err) => return Err(err),
+                Err(err) => {
+                    warn!(
+                        "Sidecar {} failed to sign transaction: {:?}",
+                        endpoint.url, err
+                    );
+                    endpoint.set_healthy(false);
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    async fn sign(
+        &self,
+        tx_data: &TransactionData,
+        context: Option<&SigningContext>,
+    ) -> anyhow::Result<GenericSignature> {
+        let bytes = Base64::encode(bcs::to_bytes(&tx_data)?);
+        let sig_bytes = retry_with_max_attempts!(
+            self.request_signature_from_any(&bytes, context),
+            self.max_attempts
+        )?;
+        let sig = GenericSignature::from_str(&sig_bytes.signature)
+            .map_err(|err| anyhow!(err.to_string()))?;
+        Ok(sig)
+    }
+}
+
+impl Drop for SidecarTxSigner {
+    fn drop(&mut self) {
+        self.health_check_task.abort();
+    }
+}
+
+/// Whether the sidecar answered that it doesn't sign the transaction.
+fn is_refusal(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(StatusCode::FORBIDDEN)
 }
 
 #[async_trait::async_trait]
@@ -69,23 +335,502 @@ impl TxSigner for SidecarTxSigner {
         &self,
         tx_data: &TransactionData,
     ) -> anyhow::Result<GenericSignature> {
-        let bytes = Base64::encode(bcs::to_bytes(&tx_data)?);
+        self.sign(tx_data, None).await
+    }
+
+    async fn sign_transaction_with_context(
+        &self,
+        tx_data: &TransactionData,
+        context: &SigningContext,
+    ) -> anyhow::Result<GenericSignature> {
+        self.sign(tx_data, Some(context)).await
+    }
+
+    fn requires_attestation(&self) -> bool {
+        self.require_attestation
+    }
+
+    fn get_address(&self) -> IotaAddress {
+        self.iota_address
+    }
+
+    fn backend(&self) -> &'static str {
+        "sidecar"
+    }
+
+    fn check_reachability(&self) -> anyhow::Result<()> {
+        if !self
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.healthy.load(Ordering::Relaxed))
+        {
+            bail!("None of the sidecars is healthy");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the order in which the sidecars are tried, starting from `start` and wrapping around.
+/// Healthy sidecars always come before the unhealthy ones.
+fn candidate_order(start: usize, healthy: &[bool]) -> Vec<usize> {
+    let count = healthy.len();
+    let (mut healthy_indexes, unhealthy_indexes): (Vec<_>, Vec<_>) = (0..count)
+        .map(|i| (start + i) % count)
+        .partition(|i| healthy[*i]);
+    healthy_indexes.extend(unhealthy_indexes);
+    healthy_indexes
+}
+
+fn new_sidecar_client(config: &SidecarSignerConfig) -> anyhow::Result<Client> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        headers.insert(HeaderName::from_str(name)?, HeaderValue::from_str(value)?);
+    }
+    if let Some(bearer_token) = &config.bearer_token {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", bearer_token))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    let mut builder = Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(config.request_timeout_sec));
+    if let Some(tls) = &config.tls {
+        let cert = std::fs::read(&tls.client_cert_path)
+            .with_context(|| format!("Failed to read {:?}", tls.client_cert_path))?;
+        let key = std::fs::read(&tls.client_key_path)
+            .with_context(|| format!("Failed to read {:?}", tls.client_key_path))?;
+        builder = builder.identity(Identity::from_pkcs8_pem(&cert, &key)?);
+        if let Some(ca_cert_path) = &tls.ca_cert_path {
+            let ca_cert = std::fs::read(ca_cert_path)
+                .with_context(|| format!("Failed to read {:?}", ca_cert_path))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&ca_cert)?);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GcpKmsPublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GcpKmsSignResponse {
+    signature: String,
+    name: String,
+}
+
+/// Signs transactions with a secp256k1 key stored in Google Cloud KMS. The key is pinned to a
+/// single key version, i.e.
+/// `projects/<project>/locations/<location>/keyRings/<ring>/cryptoKeys/<key>/cryptoKeyVersions/<n>`,
+/// so rotating the key in KMS never changes the sponsor address silently.
+/// Credentials are taken from the environment (Application Default Credentials).
+pub struct GcpKmsTxSigner {
+    key_version_name: String,
+    client: Client,
+    token_provider: Arc<dyn TokenProvider>,
+    public_key: PublicKey,
+    // Whether the last signing request, or the last probe since, reached KMS.
+    reachable: Arc<AtomicBool>,
+    health_check_task: JoinHandle<()>,
+}
+
+impl GcpKmsTxSigner {
+    pub async fn new(key_version_name: String) -> Arc<Self> {
+        assert!(
+            key_version_name.contains("/cryptoKeyVersions/"),
+            "GCP KMS key must be pinned to a key version, got: {}",
+            key_version_name
+        );
+        let token_provider = gcp_auth::provider()
+            .await
+            .unwrap_or_else(|err| panic!("Failed to get GCP credentials: {}", err));
+        let client = Client::new();
+        let resp = retry_with_max_attempts!(
+            Self::get_public_key(&client, &token_provider, &key_version_name),
+            GCP_KMS_MAX_ATTEMPTS
+        )
+        .unwrap_or_else(|err| panic!("Failed to get public key from GCP KMS: {}", err));
+        assert_eq!(
+            resp.algorithm, GCP_KMS_SECP256K1_ALGORITHM,
+            "Unsupported GCP KMS key algorithm"
+        );
+        let public_key = secp256k1_public_key_from_pem(&resp.pem)
+            .and_then(|key| {
+                PublicKey::try_from_bytes(SignatureScheme::Secp256k1, &key)
+                    .map_err(|err| anyhow!(err.to_string()))
+            })
+            .unwrap_or_else(|err| panic!("Failed to parse GCP KMS public key: {}", err));
+        let reachable = Arc::new(AtomicBool::new(true));
+        let health_check_task = tokio::spawn(Self::run_health_checks(
+            client.clone(),
+            token_provider.clone(),
+            key_version_name.clone(),
+            reachable.clone(),
+        ));
+        Arc::new(Self {
+            key_version_name,
+            client,
+            token_provider,
+            public_key,
+            reachable,
+            health_check_task,
+        })
+    }
+
+    /// Once a signing request failed, periodically gets the public key of the key version until
+    /// KMS answers, so that the signer is reachable again without waiting for a transaction.
+    async fn run_health_checks(
+        client: Client,
+        token_provider: Arc<dyn TokenProvider>,
+        key_version_name: String,
+        reachable: Arc<AtomicBool>,
+    ) {
+        loop {
+            tokio::time::sleep(GCP_KMS_HEALTH_CHECK_INTERVAL).await;
+            if reachable.load(Ordering::Relaxed) {
+                continue;
+            }
+            match Self::get_public_key(&client, &token_provider, &key_version_name).await {
+                Ok(_) => {
+                    info!("GCP KMS key {} is reachable again", key_version_name);
+                    reachable.store(true, Ordering::Relaxed);
+                }
+                Err(err) => debug!("Health check of GCP KMS failed: {:?}", err),
+            }
+        }
+    }
+
+    async fn get_public_key(
+        client: &Client,
+        token_provider: &Arc<dyn TokenProvider>,
+        key_version_name: &str,
+    ) -> anyhow::Result<GcpKmsPublicKeyResponse> {
+        let token = token_provider.token(GCP_KMS_SCOPES).await?;
+        let resp = client
+            .get(format!(
+                "{}/{}/publicKey",
+                GCP_KMS_API_URL, key_version_name
+            ))
+            .bearer_auth(token.as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<GcpKmsPublicKeyResponse>()
+            .await?;
+        Ok(resp)
+    }
+
+    async fn asymmetric_sign(&self, digest: &[u8]) -> anyhow::Result<GcpKmsSignResponse> {
+        let token = self.token_provider.token(GCP_KMS_SCOPES).await?;
         let resp = self
             .client
-            .post(format!("{}/{}", self.sidecar_url, "sign-transaction"))
-            .header("Content-Type", "application/json")
-            .json(&json!({"txBytes": bytes}))
+            .post(format!(
+                "{}/{}:asymmetricSign",
+                GCP_KMS_API_URL, self.key_version_name
+            ))
+            .bearer_auth(token.as_str())
+            .json(&json!({"digest": {"sha256": Base64::encode(digest)}}))
             .send()
+            .await?
+            .error_for_status()?
+            .json::<GcpKmsSignResponse>()
             .await?;
-        let sig_bytes = resp.json::<SignatureResponse>().await?;
-        let sig = GenericSignature::from_str(&sig_bytes.signature)
-            .map_err(|err| anyhow!(err.to_string()))?;
-        Ok(sig)
+        Ok(resp)
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner for GcpKmsTxSigner {
+    async fn sign_transaction(
+        &self,
+        tx_data: &TransactionData,
+    ) -> anyhow::Result<GenericSignature> {
+        let intent_msg = IntentMessage::new(Intent::iota_transaction(), tx_data);
+        let intent_digest = Blake2b256::digest(bcs::to_bytes(&intent_msg)?);
+        // KMS signs the SHA-256 digest of the message, same as a local secp256k1 keypair.
+        let digest = Sha256::digest(intent_digest.digest);
+        let resp =
+            retry_with_max_attempts!(self.asymmetric_sign(&digest.digest), GCP_KMS_MAX_ATTEMPTS);
+        self.reachable.store(resp.is_ok(), Ordering::Relaxed);
+        let resp = resp?;
+        if resp.name != self.key_version_name {
+            bail!(
+                "Transaction was signed with unexpected key version {}",
+                resp.name
+            );
+        }
+        let der = Base64::decode(&resp.signature).map_err(|err| anyhow!(err.to_string()))?;
+        let mut sig_bytes = vec![SignatureScheme::Secp256k1.flag()];
+        sig_bytes.extend_from_slice(&secp256k1_signature_from_der(&der)?);
+        sig_bytes.extend_from_slice(self.public_key.as_ref());
+        let sig = Signature::from_bytes(&sig_bytes).map_err(|err| anyhow!(err.to_string()))?;
+        Ok(sig.into())
+    }
+
+    fn get_address(&self) -> IotaAddress {
+        (&self.public_key).into()
+    }
+
+    fn public_key(&self) -> Option<PublicKey> {
+        Some(self.public_key.clone())
+    }
+
+    fn backend(&self) -> &'static str {
+        "gcp-kms"
+    }
+
+    fn check_reachability(&self) -> anyhow::Result<()> {
+        if !self.reachable.load(Ordering::Relaxed) {
+            bail!("The last request to GCP KMS failed");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for GcpKmsTxSigner {
+    fn drop(&mut self) {
+        self.health_check_task.abort();
+    }
+}
+
+struct MultisigMember {
+    signer: Arc<dyn TxSigner>,
+    weight: WeightUnit,
+}
+
+/// Signs for a multisig address with several signer backends. The members are asked to sign
+/// concurrently, and their signatures are combined as soon as the weight of the members that
+/// signed reaches the threshold, so that a slow or failing member doesn't hold the others back.
+pub struct MultisigTxSigner {
+    members: Vec<MultisigMember>,
+    threshold: ThresholdUnit,
+    multisig_pk: MultiSigPublicKey,
+    iota_address: IotaAddress,
+}
+
+impl MultisigTxSigner {
+    pub async fn new(config: MultisigSignerConfig) -> Arc<Self> {
+        let mut members = vec![];
+        let mut public_keys = vec![];
+        for member_config in config.members {
+            let signer = Box::pin(member_config.signer.new_signer()).await;
+            let address = signer.get_address();
+            let public_key = member_config
+                .public_key
+                .or_else(|| signer.public_key())
+                .unwrap_or_else(|| {
+                    panic!("The public key of multisig member {} is required", address)
+                });
+            assert_eq!(
+                IotaAddress::from(&public_key),
+                address,
+                "The public key of multisig member {} doesn't match its address",
+                address
+            );
+            public_keys.push(public_key);
+            members.push(MultisigMember {
+                signer,
+                weight: member_config.weight,
+            });
+        }
+        let weights = members.iter().map(|member| member.weight).collect();
+        let multisig_pk = MultiSigPublicKey::new(public_keys, weights, config.threshold)
+            .unwrap_or_else(|err| panic!("Invalid multisig: {}", err));
+        let iota_address = IotaAddress::from(&multisig_pk);
+        Arc::new(Self {
+            members,
+            threshold: config.threshold,
+            multisig_pk,
+            iota_address,
+        })
+    }
+
+    async fn sign(
+        &self,
+        tx_data: &TransactionData,
+        context: Option<&SigningContext>,
+    ) -> anyhow::Result<GenericSignature> {
+        let mut pending: FuturesUnordered<_> = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| async move {
+                let result = match context {
+                    Some(context) => {
+                        member
+                            .signer
+                            .sign_transaction_with_context(tx_data, context)
+                            .await
+                    }
+                    None => member.signer.sign_transaction(tx_data).await,
+                };
+                (index, result)
+            })
+            .collect();
+        let mut signatures = vec![];
+        let mut weight: ThresholdUnit = 0;
+        while let Some((index, result)) = pending.next().await {
+            let member = &self.members[index];
+            match result {
+                Ok(sig) => {
+                    signatures.push((index, sig));
+                    weight += member.weight as ThresholdUnit;
+                    if weight >= self.threshold {
+                        break;
+                    }
+                }
+                Err(err) => warn!(
+                    "Multisig member {} failed to sign transaction: {:?}",
+                    member.signer.get_address(),
+                    err
+                ),
+            }
+        }
+        if weight < self.threshold {
+            bail!(
+                "The members that signed have a weight of {}, below the threshold {}",
+                weight,
+                self.threshold
+            );
+        }
+        // The signatures are expected in the order of the members.
+        signatures.sort_by_key(|(index, _)| *index);
+        let multisig = MultiSig::combine(
+            signatures.into_iter().map(|(_, sig)| sig).collect(),
+            self.multisig_pk.clone(),
+        )
+        .map_err(|err| anyhow!(err.to_string()))?;
+        Ok(GenericSignature::MultiSig(multisig))
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner for MultisigTxSigner {
+    async fn sign_transaction(
+        &self,
+        tx_data: &TransactionData,
+    ) -> anyhow::Result<GenericSignature> {
+        self.sign(tx_data, None).await
+    }
+
+    async fn sign_transaction_with_context(
+        &self,
+        tx_data: &TransactionData,
+        context: &SigningContext,
+    ) -> anyhow::Result<GenericSignature> {
+        self.sign(tx_data, Some(context)).await
+    }
+
+    fn requires_attestation(&self) -> bool {
+        self.members
+            .iter()
+            .any(|member| member.signer.requires_attestation())
     }
 
     fn get_address(&self) -> IotaAddress {
         self.iota_address
     }
+
+    fn backend(&self) -> &'static str {
+        "multisig"
+    }
+
+    fn check_reachability(&self) -> anyhow::Result<()> {
+        let reachable_weight: ThresholdUnit = self
+            .members
+            .iter()
+            .filter(|member| member.signer.check_reachability().is_ok())
+            .map(|member| member.weight as ThresholdUnit)
+            .sum();
+        if reachable_weight < self.threshold {
+            bail!(
+                "The reachable members have a weight of {}, below the threshold {}",
+                reachable_weight,
+                self.threshold
+            );
+        }
+        Ok(())
+    }
+
+    fn members(&self) -> Vec<Arc<dyn TxSigner>> {
+        self.members
+            .iter()
+            .map(|member| member.signer.clone())
+            .collect()
+    }
+}
+
+/// Extracts the compressed secp256k1 public key from a PEM encoded SubjectPublicKeyInfo.
+fn secp256k1_public_key_from_pem(pem: &str) -> anyhow::Result<[u8; 33]> {
+    let der_base64: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+    let der = Base64::decode(&der_base64).map_err(|err| anyhow!(err.to_string()))?;
+    // The SubjectPublicKeyInfo ends with the uncompressed point 0x04 || x || y.
+    let Some(point) = der.len().checked_sub(65).map(|start| &der[start..]) else {
+        bail!("Public key is too short");
+    };
+    if point[0] != 0x04 {
+        bail!("Public key is not an uncompressed EC point");
+    }
+    let mut compressed = [0u8; 33];
+    compressed[0] = if point[64] % 2 == 0 { 0x02 } else { 0x03 };
+    compressed[1..].copy_from_slice(&point[1..33]);
+    Ok(compressed)
+}
+
+/// Converts a DER encoded ECDSA signature into the 64 bytes `r || s` form, with `s` normalized
+/// to the lower half of the curve order as required for IOTA signatures.
+fn secp256k1_signature_from_der(der: &[u8]) -> anyhow::Result<[u8; 64]> {
+    if der.len() < 2 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        bail!("Invalid DER signature");
+    }
+    let (r, rest) = read_der_integer(&der[2..])?;
+    let (s, rest) = read_der_integer(rest)?;
+    if !rest.is_empty() {
+        bail!("Invalid DER signature");
+    }
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(&r);
+    sig[32..].copy_from_slice(&normalize_s(s));
+    Ok(sig)
+}
+
+fn read_der_integer(data: &[u8]) -> anyhow::Result<([u8; 32], &[u8])> {
+    if data.len() < 2 || data[0] != 0x02 {
+        bail!("Invalid DER integer");
+    }
+    let len = data[1] as usize;
+    let Some(value) = data.get(2..2 + len) else {
+        bail!("Invalid DER integer");
+    };
+    // Strip the leading zero which keeps the integer positive.
+    let value = match value {
+        [0, rest @ ..] => rest,
+        _ => value,
+    };
+    if value.len() > 32 {
+        bail!("Invalid DER integer");
+    }
+    let mut result = [0u8; 32];
+    result[32 - value.len()..].copy_from_slice(value);
+    Ok((result, &data[2 + len..]))
+}
+
+fn normalize_s(s: [u8; 32]) -> [u8; 32] {
+    if s <= SECP256K1_HALF_ORDER {
+        return s;
+    }
+    let mut result = [0u8; 32];
+    let mut borrow = 0;
+    for i in (0..32).rev() {
+        let diff = SECP256K1_ORDER[i] as i16 - s[i] as i16 - borrow;
+        borrow = (diff < 0) as i16;
+        result[i] = diff.rem_euclid(256) as u8;
+    }
+    result
 }
 
 pub struct TestTxSigner {
@@ -112,4 +857,144 @@ impl TxSigner for TestTxSigner {
     fn get_address(&self) -> IotaAddress {
         (&self.keypair.public()).into()
     }
+
+    fn public_key(&self) -> Option<PublicKey> {
+        Some(self.keypair.public())
+    }
+
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MultisigMemberConfig, TxSignerConfig};
+    use iota_types::base_types::random_object_ref;
+    use iota_types::crypto::get_account_key_pair;
+    use iota_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+    use iota_types::transaction::TransactionKind;
+
+    #[test]
+    fn test_candidate_order() {
+        assert_eq!(candidate_order(0, &[true, true, true]), vec![0, 1, 2]);
+        assert_eq!(candidate_order(4, &[true, true, true]), vec![1, 2, 0]);
+        assert_eq!(candidate_order(0, &[false, true, true]), vec![1, 2, 0]);
+        assert_eq!(candidate_order(2, &[true, false, false]), vec![0, 2, 1]);
+        assert!(candidate_order(0, &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multisig_signer() {
+        let keypairs: Vec<IotaKeyPair> = (0..3).map(|_| get_account_key_pair().1.into()).collect();
+        let config = MultisigSignerConfig {
+            threshold: 2,
+            members: keypairs
+                .iter()
+                .map(|keypair| MultisigMemberConfig {
+                    signer: TxSignerConfig::Local {
+                        keypair: keypair.copy(),
+                    },
+                    weight: 1,
+                    public_key: None,
+                })
+                .collect(),
+        };
+        let signer = MultisigTxSigner::new(config).await;
+        let multisig_pk = MultiSigPublicKey::new(
+            keypairs.iter().map(|keypair| keypair.public()).collect(),
+            vec![1; 3],
+            2,
+        )
+        .unwrap();
+        assert_eq!(signer.get_address(), IotaAddress::from(&multisig_pk));
+        assert_eq!(signer.backend(), "multisig");
+        signer.check_reachability().unwrap();
+        assert_eq!(signer.members().len(), 3);
+
+        let tx_data = TransactionData::new_with_gas_coins(
+            TransactionKind::ProgrammableTransaction(
+                ProgrammableTransactionBuilder::new().finish(),
+            ),
+            IotaAddress::default(),
+            vec![random_object_ref()],
+            1000,
+            1,
+        );
+        let GenericSignature::MultiSig(multisig) = signer.sign_transaction(&tx_data).await.unwrap()
+        else {
+            panic!("Expected a multisig signature");
+        };
+        // The third member isn't needed once the threshold is reached.
+        assert_eq!(multisig.get_sigs().len(), 2);
+    }
+
+    #[test]
+    fn test_new_sidecar_client() {
+        let mut config = SidecarSignerConfig::new("http://localhost:3000".to_string());
+        config.bearer_token = Some("secret".to_string());
+        config
+            .headers
+            .insert("x-api-key".to_string(), "key".to_string());
+        assert!(new_sidecar_client(&config).is_ok());
+
+        config
+            .headers
+            .insert("invalid header".to_string(), "key".to_string());
+        assert!(new_sidecar_client(&config).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_public_key_from_pem() {
+        let mut der = spki_header();
+        der.push(0x04);
+        der.extend_from_slice(&[1u8; 32]);
+        der.extend_from_slice(&[3u8; 32]);
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            Base64::encode(&der)
+        );
+        let key = secp256k1_public_key_from_pem(&pem).unwrap();
+        assert_eq!(key[0], 0x03);
+        assert_eq!(&key[1..], &[1u8; 32]);
+
+        assert!(secp256k1_public_key_from_pem("-----BEGIN PUBLIC KEY-----\nAAAA\n").is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_signature_from_der() {
+        let mut r = vec![0x00, 0x80];
+        r.extend_from_slice(&[1u8; 31]);
+        let s = vec![0x05];
+        let mut der = vec![0x30, (r.len() + s.len() + 4) as u8, 0x02, r.len() as u8];
+        der.extend_from_slice(&r);
+        der.extend_from_slice(&[0x02, s.len() as u8]);
+        der.extend_from_slice(&s);
+
+        let sig = secp256k1_signature_from_der(&der).unwrap();
+        assert_eq!(&sig[..32], &r[1..]);
+        assert_eq!(&sig[32..63], &[0u8; 31]);
+        assert_eq!(sig[63], 0x05);
+
+        assert!(secp256k1_signature_from_der(&der[..der.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_normalize_s() {
+        assert_eq!(normalize_s(SECP256K1_HALF_ORDER), SECP256K1_HALF_ORDER);
+        let mut order_minus_one = SECP256K1_ORDER;
+        order_minus_one[31] -= 1;
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        assert_eq!(normalize_s(order_minus_one), one);
+    }
+
+    /// SubjectPublicKeyInfo header of a secp256k1 public key.
+    fn spki_header() -> Vec<u8> {
+        vec![
+            0x30, 0x56, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+            0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a, 0x03, 0x42, 0x00,
+        ]
+    }
 }
@@ -38,6 +38,101 @@ impl From<IotaGasCoin> for GasCoin {
     }
 }
 
+/// A gas coin that is kept out of the pool until `release_epoch`, because it is locked by a
+/// conflicting transaction.
+#[derive(Clone, Debug, Eq, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct QuarantinedCoin {
+    pub object_id: ObjectID,
+    pub balance: u64,
+    pub release_epoch: u64,
+}
+
+/// A gas coin of the pool, as listed to debug the pool.
+#[derive(Clone, Debug, Eq, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct PoolCoin {
+    pub object_id: ObjectID,
+    /// Only known for available coins, reserved coins are stored by object ID alone.
+    pub version: Option<u64>,
+    /// Not known for reserved coins.
+    pub balance: Option<u64>,
+    pub state: PoolCoinState,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolCoinState {
+    Available,
+    Reserved {
+        reservation_id: ReservationID,
+        expiration_time: ExpirationTimeMs,
+    },
+    Quarantined {
+        release_epoch: u64,
+    },
+}
+
+/// Snapshot of the reservations of a sponsor, sampled for metrics.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReservationStats {
+    pub reserved_coin_count: u64,
+    /// When the oldest reservation that is neither executed nor expired was made.
+    pub oldest_reservation_time_ms: Option<u64>,
+}
+
+/// Reservations of a client, and how many of them expired without being executed.
+#[derive(Clone, Debug, Eq, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct ClientReservationStats {
+    /// The `x-client-id` header of the reservations, or the IP address they came from.
+    pub client: String,
+    pub reservation_count: u64,
+    pub expired_count: u64,
+}
+
+/// Progress of the coin initializer while it splits coins. It is kept in the storage, so that an
+/// interrupted initialization is resumed from there instead of restarted.
+#[derive(Clone, Debug, Default, Eq, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct InitProgress {
+    /// Target balance of the split coins.
+    pub target_init_balance: u64,
+    /// Coins left to split, including the split coins that are still large enough to be split
+    /// again.
+    pub pending_coins: Vec<ObjectID>,
+    /// Number of coins split so far.
+    pub split_coin_count: u64,
+    /// Number of coins added to the pool so far.
+    pub added_coin_count: u64,
+    pub started_at_ms: u64,
+}
+
+/// The whole state of the pool of a sponsor, as copied between storages by `migrate-storage`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PoolSnapshot {
+    pub available_coins: Vec<GasCoin>,
+    pub reservations: Vec<ReservationSnapshot>,
+    pub quarantined_coins: Vec<QuarantinedCoin>,
+    /// The last reservation ID given, so that the IDs are not reused after the copy.
+    pub last_reservation_id: ReservationID,
+    pub is_initialized: bool,
+    pub is_draining: bool,
+}
+
+/// A reservation that is neither executed nor expired. Reserved coins are stored by object ID
+/// alone.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReservationSnapshot {
+    pub reservation_id: ReservationID,
+    pub object_ids: Vec<ObjectID>,
+    pub expiration_time_ms: u64,
+    pub reservation_time_ms: u64,
+}
+
+/// The gas budget of a reservation and the coins reserved for it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reservation {
+    pub gas_budget: u64,
+    pub gas_coins: Vec<GasCoin>,
+}
+
 pub type ReservationID = u64;
 pub type ExpirationTimeMs = u64;
 pub type GasGroupKey = ObjectID;
@@ -0,0 +1,307 @@
+// Copyright (c) 2025 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Webhooks notified of the lifecycle of the reservations, so that downstream accounting systems
+//! can follow the gas spent by the Gas Station without scraping its logs.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hmac::{hmac_sha3_256, HmacKey};
+use fastcrypto::traits::ToFromBytes;
+use iota_types::base_types::{IotaAddress, ObjectID};
+use iota_types::digests::TransactionDigest;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+use url::Url;
+
+use crate::retry_policy::RetryPolicy;
+use crate::types::ReservationID;
+
+/// Unix timestamp in milliseconds of the delivery, included in the signed payload.
+pub const TIMESTAMP_HEADER: &str = "x-gas-station-timestamp";
+/// Hex encoded HMAC-SHA3-256 of `<timestamp>.<body>`, keyed by the secret of the webhook.
+pub const SIGNATURE_HEADER: &str = "x-gas-station-signature";
+
+const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WebhookConfig {
+    pub url: Url,
+    /// Extra headers sent with every event, e.g. an authorization header.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// The events sent to the webhook. All of them if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<WebhookEventKind>,
+    /// Secret used to sign the events. They are not signed if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Timeout of each delivery attempt.
+    #[serde(default = "default_webhook_timeout_ms")]
+    pub timeout_ms: u64,
+    /// How failed deliveries are retried before the event is dropped.
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+}
+
+fn default_webhook_timeout_ms() -> u64 {
+    DEFAULT_WEBHOOK_TIMEOUT_MS
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEventKind {
+    ReservationCreated,
+    ReservationExpiredUnused,
+    ExecuteSuccess,
+    ExecuteFailure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    #[serde(rename_all = "camelCase")]
+    ReservationCreated {
+        sponsor: IotaAddress,
+        reservation_id: ReservationID,
+        gas_budget: u64,
+        gas_coins: Vec<ObjectID>,
+        reserve_duration_secs: u64,
+    },
+    /// The reservation expired before a transaction was executed with it.
+    #[serde(rename_all = "camelCase")]
+    ReservationExpiredUnused {
+        sponsor: IotaAddress,
+        reservation_id: ReservationID,
+        gas_coins: Vec<ObjectID>,
+    },
+    /// The transaction was executed. `success` is false if it was aborted on chain, in which case
+    /// the gas is used as well.
+    #[serde(rename_all = "camelCase")]
+    ExecuteSuccess {
+        sponsor: IotaAddress,
+        sender: IotaAddress,
+        reservation_id: ReservationID,
+        digest: TransactionDigest,
+        gas_used: u64,
+        success: bool,
+    },
+    /// The transaction could not be executed and no gas was used.
+    #[serde(rename_all = "camelCase")]
+    ExecuteFailure {
+        sponsor: IotaAddress,
+        sender: IotaAddress,
+        reservation_id: ReservationID,
+        error: String,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            WebhookEvent::ReservationCreated { .. } => WebhookEventKind::ReservationCreated,
+            WebhookEvent::ReservationExpiredUnused { .. } => {
+                WebhookEventKind::ReservationExpiredUnused
+            }
+            WebhookEvent::ExecuteSuccess { .. } => WebhookEventKind::ExecuteSuccess,
+            WebhookEvent::ExecuteFailure { .. } => WebhookEventKind::ExecuteFailure,
+        }
+    }
+}
+
+/// The body sent to the webhooks. The `id` stays the same across the retries of a delivery, so
+/// that the receiver can deduplicate them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookMessage {
+    pub id: String,
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+}
+
+impl WebhookMessage {
+    pub fn new(event: WebhookEvent) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            event,
+        }
+    }
+}
+
+pub struct Webhook {
+    client: reqwest::Client,
+    config: WebhookConfig,
+    headers: HeaderMap,
+}
+
+impl Webhook {
+    pub fn new(config: &WebhookConfig) -> anyhow::Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            headers.insert(
+                HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("Invalid webhook header name {}", name))?,
+                HeaderValue::try_from(value.as_str())
+                    .with_context(|| format!("Invalid value of webhook header {}", name))?,
+            );
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(Self {
+            client,
+            config: config.clone(),
+            headers,
+        })
+    }
+
+    fn accepts(&self, kind: WebhookEventKind) -> bool {
+        self.config.events.is_empty() || self.config.events.contains(&kind)
+    }
+
+    /// Sends the message until the webhook responds with a success status or the retry policy
+    /// is exhausted.
+    pub async fn send(&self, message: &WebhookMessage) -> anyhow::Result<()> {
+        let body = serde_json::to_string(message)?;
+        let timestamp = message.timestamp.to_string();
+        let mut headers = self.headers.clone();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::try_from(timestamp.as_str())?);
+        if let Some(secret) = &self.config.secret {
+            let signature = sign(secret, &timestamp, &body)?;
+            headers.insert(SIGNATURE_HEADER, HeaderValue::try_from(signature)?);
+        }
+        self.config
+            .retry_policy
+            .retry(|| async {
+                let response = self
+                    .client
+                    .post(self.config.url.clone())
+                    .headers(headers.clone())
+                    .body(body.clone())
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    bail!("Webhook responded with status {}", response.status());
+                }
+                Ok(())
+            })
+            .await
+    }
+}
+
+/// Signs `<timestamp>.<body>` with the secret of the webhook.
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> anyhow::Result<String> {
+    let key = HmacKey::from_bytes(secret.as_bytes())
+        .map_err(|err| anyhow::anyhow!("Invalid webhook secret: {:?}", err))?;
+    let digest = hmac_sha3_256(&key, format!("{}.{}", timestamp, body).as_bytes());
+    Ok(Hex::encode(digest.digest))
+}
+
+/// Notifies all the configured webhooks of the reservation events.
+#[derive(Clone, Default)]
+pub struct Webhooks {
+    webhooks: Vec<Arc<Webhook>>,
+}
+
+impl Webhooks {
+    pub fn from_config(configs: &[WebhookConfig]) -> anyhow::Result<Self> {
+        let webhooks = configs
+            .iter()
+            .map(|config| Webhook::new(config).map(Arc::new))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { webhooks })
+    }
+
+    /// Sends the event in the background, so that slow webhooks don't delay the response.
+    /// Failures are only logged.
+    pub fn notify(&self, event: WebhookEvent) {
+        let kind = event.kind();
+        let webhooks = self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.accepts(kind))
+            .cloned()
+            .collect::<Vec<_>>();
+        if webhooks.is_empty() {
+            return;
+        }
+        let message = WebhookMessage::new(event);
+        for webhook in webhooks {
+            let message = message.clone();
+            tokio::spawn(async move {
+                match webhook.send(&message).await {
+                    Ok(()) => debug!(
+                        "Sent {:?} event {} to webhook {}",
+                        kind, message.id, webhook.config.url
+                    ),
+                    Err(err) => error!(
+                        "Failed to send {:?} event {} to webhook {}: {:?}",
+                        kind, message.id, webhook.config.url, err
+                    ),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_config() {
+        let config: Vec<WebhookConfig> = serde_yaml::from_str(
+            "- url: https://accounting.example.com/gas-station\n  \
+               headers:\n    authorization: Bearer token\n  \
+               events: [execute-success, execute-failure]\n  \
+               secret: webhook secret\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config[0].events,
+            vec![
+                WebhookEventKind::ExecuteSuccess,
+                WebhookEventKind::ExecuteFailure
+            ]
+        );
+        assert_eq!(config[0].timeout_ms, DEFAULT_WEBHOOK_TIMEOUT_MS);
+
+        let webhook = Webhook::new(&config[0]).unwrap();
+        assert!(webhook.accepts(WebhookEventKind::ExecuteSuccess));
+        assert!(!webhook.accepts(WebhookEventKind::ReservationCreated));
+    }
+
+    #[test]
+    fn test_webhook_message() {
+        let message = WebhookMessage::new(WebhookEvent::ReservationExpiredUnused {
+            sponsor: IotaAddress::ZERO,
+            reservation_id: 7,
+            gas_coins: vec![],
+        });
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["event"], "reservation-expired-unused");
+        assert_eq!(value["reservationId"], 7);
+        assert_eq!(value["id"], message.id);
+    }
+
+    #[test]
+    fn test_sign() {
+        let signature = sign("secret", "1700000000000", "{}").unwrap();
+        assert_eq!(signature.len(), 64);
+        assert_eq!(signature, sign("secret", "1700000000000", "{}").unwrap());
+        assert_ne!(signature, sign("secret", "1700000000001", "{}").unwrap());
+        assert_ne!(
+            signature,
+            sign("other secret", "1700000000000", "{}").unwrap()
+        );
+    }
+}